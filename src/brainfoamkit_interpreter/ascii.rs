@@ -0,0 +1,12 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use brainfoamkit_lib::AsciiTable;
+
+/// Print every ASCII character with its binary, hexadecimal and textual
+/// representations.
+pub fn print_table() {
+    AsciiTable::new().to_display_table().printstd();
+}
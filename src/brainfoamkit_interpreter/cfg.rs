@@ -0,0 +1,35 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::Path,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    analysis,
+    Program,
+};
+
+/// Write the control flow graph of the program in `file` in the GraphViz DOT
+/// language, either to `output` or to STDOUT.
+pub fn export(file: &Path, output: Option<&Path>) -> Result<()> {
+    let source = fs::read_to_string(file)
+        .with_context(|| format!("Unable to read program from {}", file.display()))?;
+    let dot = analysis::to_dot(&Program::from(source.as_str()));
+
+    match output {
+        Some(output) => fs::write(output, dot)
+            .with_context(|| format!("Unable to write graph to {}", output.display())),
+        None => {
+            print!("{dot}");
+            Ok(())
+        }
+    }
+}
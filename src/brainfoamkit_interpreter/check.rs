@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::Path,
+};
+
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    analysis::{
+        self,
+        Lint,
+    },
+    Program,
+};
+
+use crate::run::InvalidProgram;
+
+/// Print the beginner mistakes found in the program in `file`, pointing at
+/// the lines they are on. With `explain`, a longer description of every kind
+/// of mistake found follows.
+///
+/// # Errors
+///
+/// Fails with an [`InvalidProgram`] if any of the mistakes keeps the program
+/// from running.
+pub fn report(file: &Path, explain: bool) -> Result<()> {
+    let source = fs::read_to_string(file)
+        .with_context(|| format!("Unable to read program from {}", file.display()))?;
+    let diagnostics = analysis::lint(&Program::from(source.as_str()));
+
+    if diagnostics.is_empty() {
+        println!("No problems found in {}", file.display());
+        return Ok(());
+    }
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic.render(&source));
+    }
+    if explain {
+        let mut explained: Vec<Lint> = Vec::new();
+        for diagnostic in &diagnostics {
+            if !explained.contains(&diagnostic.lint) {
+                explained.push(diagnostic.lint);
+                println!("{}: {}\n", diagnostic.lint, diagnostic.lint.explanation());
+            }
+        }
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.lint.is_error())
+        .count();
+    if errors > 0 {
+        return Err(anyhow!("{} has {errors} error(s)", file.display()).context(InvalidProgram));
+    }
+    Ok(())
+}
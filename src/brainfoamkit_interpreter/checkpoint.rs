@@ -0,0 +1,204 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs::{
+        File,
+        OpenOptions,
+    },
+    io::{
+        self,
+        Seek,
+        SeekFrom,
+    },
+    path::Path,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use anyhow::{
+    bail,
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    config::Config,
+    CancellationToken,
+    Checkpoint,
+    CheckpointError,
+    Dialect,
+    ReadError,
+    TerminalReader,
+    VMReader,
+    VMWriter,
+    VirtualMachine,
+};
+
+use crate::run::{
+    self,
+    Interrupted,
+    InvalidProgram,
+};
+
+/// The number of instructions executed between two looks at the clock
+const CLOCK_INTERVAL: usize = 4096;
+
+/// Where and how often a run saves its state, and what it needs to save.
+pub struct Checkpointing<'a> {
+    pub path:    &'a Path,
+    pub every:   Duration,
+    pub source:  &'a str,
+    pub dialect: Dialect,
+    pub config:  &'a Config,
+    pub cancel:  &'a CancellationToken,
+}
+
+impl Checkpointing<'_> {
+    /// Run `machine` until it halts, saving its state when it starts and every
+    /// `every` after that.
+    ///
+    /// The output is flushed before every checkpoint, so that everything the
+    /// program wrote before the checkpoint has been written when it is saved.
+    ///
+    /// Once `cancel` is cancelled, a last checkpoint is saved and the run
+    /// stops with [`Interrupted`].
+    pub fn run<R: VMReader, W: VMWriter>(
+        &self,
+        machine: &mut VirtualMachine<CountingReader<R>, W>,
+    ) -> Result<()> {
+        self.save(machine)?;
+        let mut last = Instant::now();
+        let mut executed = 0;
+
+        while machine.get_instruction().is_some() {
+            if self.cancel.is_cancelled() {
+                self.save(machine)?;
+                return Err(Interrupted.into());
+            }
+            machine.execute_instruction()?;
+            executed += 1;
+            if executed % CLOCK_INTERVAL == 0 && last.elapsed() >= self.every {
+                self.save(machine)?;
+                last = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    fn save<R: VMReader, W: VMWriter>(
+        &self,
+        machine: &mut VirtualMachine<CountingReader<R>, W>,
+    ) -> Result<()> {
+        machine.output_device().flush()?;
+        let input_read = machine.input_device().read;
+        Checkpoint::take(
+            machine,
+            self.source,
+            self.dialect,
+            self.config.clone(),
+            input_read,
+        )?
+        .save(self.path)?;
+        Ok(())
+    }
+}
+
+/// Continue the run saved in the checkpoint at `path`, reading the same input
+/// again from STDIN.
+///
+/// The output goes to STDOUT, or with `output`, is appended to that file after
+/// cutting it back to the output written before the checkpoint, so that the
+/// file ends up with the output of an uninterrupted run. With
+/// `checkpoint_every`, the state of the resumed run is saved to the same
+/// checkpoint at that interval, and once more when Ctrl-C stops it.
+pub fn resume(
+    path: &Path,
+    output: Option<&Path>,
+    checkpoint_every: Option<Duration>,
+) -> Result<()> {
+    let checkpoint = Checkpoint::load(path)?;
+    let counters = checkpoint.snapshot.counters();
+    let device: Box<dyn VMWriter> = match output {
+        Some(output) => Box::new(truncate(output, counters.bytes_written)?),
+        None => Box::new(io::stdout()),
+    };
+
+    let mut machine = checkpoint
+        .resume(CountingReader::new(TerminalReader::new()), device)
+        .map_err(invalid_program)?;
+    eprintln!(
+        "Resuming after {} steps and {} bytes of output",
+        counters.steps, counters.bytes_written
+    );
+
+    let cancel = run::cancel_on_interrupt()?;
+    let result = match checkpoint_every {
+        Some(every) => Checkpointing {
+            path,
+            every,
+            source: &checkpoint.program,
+            dialect: checkpoint.dialect,
+            config: &checkpoint.config,
+            cancel: &cancel,
+        }
+        .run(&mut machine),
+        None => run::run_until_interrupted(&mut machine, &cancel),
+    };
+    machine.output_device().finish()?;
+    result
+}
+
+/// Open the output file of a checkpointed run to append to it, after
+/// cutting it back to the `length` bytes written before the checkpoint.
+fn truncate(path: &Path, length: usize) -> Result<File> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Unable to open {}", path.display()))?;
+    let actual = file.metadata()?.len();
+    let length = u64::try_from(length)?;
+    if actual < length {
+        bail!(
+            "{} holds {actual} bytes, but the run wrote {length} bytes before the checkpoint",
+            path.display()
+        );
+    }
+
+    file.set_len(length)?;
+    file.seek(SeekFrom::End(0))?;
+    Ok(file)
+}
+
+/// Mark a program whose brackets are not balanced as an [`InvalidProgram`].
+fn invalid_program(error: CheckpointError) -> anyhow::Error {
+    if matches!(error, CheckpointError::Build(_)) {
+        anyhow::Error::new(error).context(InvalidProgram)
+    } else {
+        error.into()
+    }
+}
+
+/// A reader that counts the bytes read through it, so that a checkpoint knows
+/// how much of the input to skip when it is resumed.
+pub struct CountingReader<R: VMReader> {
+    inner: R,
+    read:  usize,
+}
+
+impl<R: VMReader> CountingReader<R> {
+    pub const fn new(inner: R) -> Self {
+        Self { inner, read: 0 }
+    }
+}
+
+impl<R: VMReader> VMReader for CountingReader<R> {
+    fn read(&mut self) -> Result<u8, ReadError> {
+        let byte = self.inner.read()?;
+        self.read += 1;
+        Ok(byte)
+    }
+}
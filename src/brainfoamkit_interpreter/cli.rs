@@ -0,0 +1,451 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    time::Duration,
+};
+
+use brainfoamkit_lib::{
+    project::ProjectData,
+    BehaviorProfile,
+    Dialect,
+    EofBehavior,
+    IoMode,
+    TapeFormat,
+    WarningPolicy,
+};
+use clap::{
+    Args,
+    Parser,
+    Subcommand,
+    ValueEnum,
+};
+
+/// Tools for running and inspecting brainfuck programs
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the ASCII table with binary and hexadecimal representations
+    Ascii,
+    /// Check that every program of the project in the nearest `bfk.toml` can
+    /// be run
+    Build,
+    /// Point out common beginner mistakes in a program, such as unbalanced
+    /// brackets or loops that never end
+    Check {
+        /// The brainfuck program to check
+        file:    PathBuf,
+        /// Describe every kind of mistake found and how to fix it
+        #[arg(long)]
+        explain: bool,
+    },
+    /// Export the control flow graph of a program in the GraphViz DOT language
+    Cfg {
+        /// The brainfuck program to analyze
+        file:   PathBuf,
+        /// Write the graph to this file instead of STDOUT
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run a program and list its source with the number of times every
+    /// line executed, marking the instructions that never did
+    Cover {
+        /// The brainfuck program to run
+        file:    PathBuf,
+        /// The input of the program: the contents of this file, or the text
+        /// itself if there is no such file
+        #[arg(long, value_name = "FILE_OR_TEXT")]
+        input:   Option<String>,
+        /// Also save the coverage to this file in the lcov format
+        #[arg(long, value_name = "FILE")]
+        lcov:    Option<PathBuf>,
+        /// The dialect the program is written in: `standard`, `debug`,
+        /// `pbrain`, `random` or `stack`
+        #[arg(long, default_value_t = Dialect::Standard)]
+        dialect: Dialect,
+    },
+    /// Keep programs loaded in memory and run them on request, for scripts
+    /// that run the same programs over and over. Requests are lines of JSON
+    /// sent to a Unix domain socket.
+    #[cfg(unix)]
+    Daemon(DaemonArgs),
+    /// Learn brainfuck with a tutorial of short lessons, each with a task to
+    /// solve in the terminal
+    Learn {
+        /// The lesson to start with, counting from 1
+        #[arg(long, default_value_t = 1)]
+        lesson: usize,
+    },
+    /// Run the tests of `test` against small changes to their programs, and
+    /// list the changes that still pass, which the tests never check
+    Mutate {
+        /// The directory holding the tests. Without it, the programs with an
+        /// expected output in the nearest `bfk.toml` are mutated.
+        directory: Option<PathBuf>,
+    },
+    /// Start a new project with a `bfk.toml`, a program printing Hello World!
+    /// and its tests
+    New {
+        /// The directory of the project, named after it, which must not
+        /// exist yet
+        directory: PathBuf,
+    },
+    /// Run a program, reading from STDIN and writing to STDOUT, and save
+    /// everything needed to replay the run exactly to a bundle
+    Record {
+        /// The brainfuck program to run
+        file:    PathBuf,
+        /// Where to save the bundle, by default next to the program with the
+        /// `.bfkrun` extension
+        #[arg(short, long, value_name = "FILE")]
+        output:  Option<PathBuf>,
+        /// The dialect the program is written in: `standard`, `debug`,
+        /// `pbrain`, `random` or `stack`
+        #[arg(long, default_value_t = Dialect::Standard)]
+        dialect: Dialect,
+        /// Seed the random bytes of `?` in the `random` dialect. Without it, a
+        /// seed is taken from the clock. Either way, it is saved in the bundle.
+        #[arg(long, value_name = "SEED")]
+        seed:    Option<u64>,
+    },
+    /// Run the program of a bundle saved by `record` again, on the recorded
+    /// input, and check that it behaves exactly as it did
+    Replay {
+        /// The bundle to replay
+        bundle: PathBuf,
+    },
+    /// Continue a run from the checkpoint saved by `run --checkpoint-every`,
+    /// feeding it the same input on STDIN
+    Resume {
+        /// The checkpoint to continue from
+        checkpoint:       PathBuf,
+        /// Append the output to this file, which holds the output of the
+        /// checkpointed run, after cutting it back to what was written before
+        /// the checkpoint
+        #[arg(short, long, value_name = "FILE")]
+        output:           Option<PathBuf>,
+        /// Keep saving the state of the run to the checkpoint this often
+        #[arg(long, value_name = "INTERVAL", value_parser = parse_interval)]
+        checkpoint_every: Option<Duration>,
+    },
+    /// Run a program, reading from STDIN and writing to STDOUT
+    Run(Box<RunArgs>),
+    /// Host a program on a TCP socket: every connection runs the program,
+    /// which reads what the peer sends and sends its output back
+    Serve(ServeArgs),
+    /// Compare every program in a directory with every other one and list
+    /// the groups of near-duplicates, for example among the submissions to
+    /// an assignment
+    Similar {
+        /// The directory holding the programs, searched recursively for
+        /// `.bf` and `.b` files
+        directory: PathBuf,
+        /// How alike two programs must be to count as near-duplicates, from
+        /// 0 to 1
+        #[arg(long, default_value_t = 0.9)]
+        threshold: f64,
+    },
+    /// Explore the paths through a short program symbolically, listing the
+    /// conditions on the input that lead down each of them with an example
+    /// input and its output. This is experimental.
+    Solve {
+        /// The brainfuck program to explore
+        file:      PathBuf,
+        /// The number of paths to explore before giving up
+        #[arg(long, default_value_t = 100)]
+        max_paths: usize,
+        /// The number of instructions to execute on a path before cutting it
+        /// short
+        #[arg(long, default_value_t = 100_000)]
+        max_steps: usize,
+    },
+    /// Print a static overview of a program without running it
+    Stat {
+        /// The brainfuck program to analyze
+        file: PathBuf,
+    },
+    /// Run every program in a directory that has a `.expected` file next to
+    /// it and compare its output, feeding it the `.input` file if there is one
+    Test {
+        /// The directory holding the tests. Without it, the programs with an
+        /// expected output in the nearest `bfk.toml` are run.
+        directory: Option<PathBuf>,
+        /// Report the results in the Test Anything Protocol
+        #[arg(long)]
+        tap:       bool,
+        /// Inject faults into the input and output of the tests, chosen by
+        /// SEED, to check that every fault stops the program with an error
+        #[arg(long, value_name = "SEED")]
+        chaos:     Option<u64>,
+    },
+}
+
+#[cfg(unix)]
+#[derive(Debug, Args)]
+pub struct DaemonArgs {
+    /// The path of the socket to listen on
+    #[arg(long, value_name = "PATH", default_value = "bfkrun.sock")]
+    pub socket:       PathBuf,
+    /// The number of instructions a program may execute before its run is
+    /// stopped, which a request can only lower
+    #[arg(long, value_name = "N", default_value_t = 100_000_000)]
+    pub max_steps:    usize,
+    /// The number of bytes a run may write before it is stopped
+    #[arg(long, value_name = "BYTES", default_value_t = 1024 * 1024)]
+    pub max_output:   usize,
+    /// The number of programs kept loaded. Loading one more forgets the
+    /// program that was used the longest time ago
+    #[arg(long, value_name = "N", default_value_t = 1024)]
+    pub max_programs: usize,
+    /// The number of bytes a request may have. A longer request is answered
+    /// with an error and the connection is closed
+    #[arg(long, value_name = "BYTES", default_value_t = 1024 * 1024)]
+    pub max_request:  usize,
+}
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// The brainfuck program to host
+    pub file:         PathBuf,
+    /// The address and port to listen on
+    #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:4000")]
+    pub listen:       SocketAddr,
+    /// The dialect the program is written in: `standard`, `debug`, `pbrain`,
+    /// `random` or `stack`
+    #[arg(long, default_value_t = Dialect::Standard)]
+    pub dialect:      Dialect,
+    /// Run a single session for everyone: the first connection drives the
+    /// program, and every later one watches the tape change, as a line of
+    /// JSON for every update
+    #[arg(long)]
+    pub shared:       bool,
+    /// Send the watchers of `--shared` an update after every N steps, and
+    /// whenever the program waits for input or halts
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "shared")]
+    pub update_every: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// The brainfuck program to run, or the name of a program of the project
+    /// in the nearest `bfk.toml`. With the `http` feature, it can also be an
+    /// `http://` or `https://` URL to download the program from.
+    pub file:             PathBuf,
+    /// Treat everything after the first `!` in the file as the input of the
+    /// program instead of reading STDIN
+    #[arg(long)]
+    pub bang_input:       bool,
+    /// The dialect the program is written in: `standard`, `debug` to print a
+    /// window of the tape to STDERR at every `#`, `pbrain` for procedures,
+    /// `random` for `?`, which writes a random byte to the current cell, or
+    /// `stack` for `@` and `$`, which push the current cell onto a second
+    /// tape and pop it back
+    #[arg(long, default_value_t = Dialect::Standard)]
+    pub dialect:          Dialect,
+    /// Expand the `@define`, `@use`, `@include` and `@data` directives of
+    /// the preprocessor before running the program, and load the files of
+    /// `@data` into the tape
+    #[arg(long)]
+    pub preprocess:       bool,
+    /// Seed the random bytes of `?` in the `random` dialect, to repeat a run
+    /// exactly. Without it, every run is different.
+    #[arg(long, value_name = "SEED")]
+    pub seed:             Option<u64>,
+    /// Stop the program once it has written this many bytes
+    #[arg(long, value_name = "BYTES")]
+    pub max_output:       Option<usize>,
+    /// Flush STDOUT after every N bytes instead of only when the program
+    /// halts
+    #[arg(long, value_name = "N")]
+    pub flush_every:      Option<usize>,
+    /// Record the output of the program with its timing to this file as an
+    /// asciicast v2 recording
+    #[arg(long, value_name = "FILE", conflicts_with = "watch")]
+    pub record:           Option<PathBuf>,
+    /// Run the program again whenever the file changes and show how its
+    /// output changed. STDIN is read once and fed to every run.
+    #[arg(long, conflicts_with = "flush_every")]
+    pub watch:            bool,
+    /// Read every key as soon as it is pressed, without showing it, instead
+    /// of a line at a time. Needs STDIN to be a terminal.
+    #[arg(long, conflicts_with_all = ["bang_input", "watch", "output_format"])]
+    pub raw:              bool,
+    /// Write every byte the program reads from STDIN to STDOUT
+    #[arg(long, conflicts_with_all = ["bang_input", "watch", "output_format"])]
+    pub echo:             bool,
+    /// Compare the output of the program with the contents of this file, or
+    /// with this text if there is no such file, and fail with a diff if they
+    /// differ
+    #[arg(long, value_name = "FILE_OR_STRING", conflicts_with = "watch")]
+    pub expect_output:    Option<String>,
+    /// Print the output of the program as it runs, or a JSON document with
+    /// the output and the state of the machine once it stops
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        conflicts_with_all = ["flush_every", "record", "watch", "expect_output"]
+    )]
+    pub output_format:    OutputFormat,
+    /// Seed the tape with the values in this file before the program starts
+    #[arg(long, value_name = "FILE", conflicts_with = "watch")]
+    pub load_tape:        Option<PathBuf>,
+    /// Write the values of every cell of the tape to this file once the
+    /// program stops
+    #[arg(long, value_name = "FILE", conflicts_with = "watch")]
+    pub dump_tape:        Option<PathBuf>,
+    /// The format of the files of `--load-tape` and `--dump-tape`: `raw` for
+    /// a byte per cell or `hex` for pairs of hexadecimal digits
+    #[arg(long, value_name = "FORMAT", default_value_t = TapeFormat::Raw)]
+    pub tape_format:      TapeFormat,
+    /// The data files loaded into the tape after `--load-tape`, from the
+    /// manifest of a program of the project
+    #[arg(skip)]
+    pub data:             Vec<ProjectData>,
+    /// Show the machine as it runs: `simple` for a single line on STDERR with
+    /// the memory pointer and the cells around it
+    #[arg(long, value_enum, conflicts_with_all = ["watch", "output_format"])]
+    pub visual:           Option<Visual>,
+    /// Show cells as signed values from -128 to 127 in the view of
+    /// `--visual` and the tape dumps of the `debug` dialect
+    #[arg(long)]
+    pub signed:           bool,
+    /// Describe every instruction on STDERR before it is executed, for
+    /// example "Move the pointer right to cell 5, which contains 0x41 'A'"
+    #[arg(long, conflicts_with_all = ["visual", "watch", "output_format"])]
+    pub explain:          bool,
+    /// Update the view of `--visual` after every N steps
+    #[arg(long, value_name = "N", default_value_t = 1000, requires = "visual")]
+    pub visual_every:     usize,
+    /// The behavior profile: `classic` for a wrapping tape of 30,000 cells,
+    /// `modern` for a tape that grows to the right, or `strict` to stop at
+    /// anything interpreters disagree on
+    #[arg(long)]
+    pub profile:          Option<BehaviorProfile>,
+    /// The number of cells on the tape, overriding `tape-size` in
+    /// `brainfoamkit.toml` and `BRAINFOAMKIT_TAPE_SIZE`
+    #[arg(long, value_name = "CELLS")]
+    pub tape_size:        Option<usize>,
+    /// The number of bits in a cell. Only 8 is supported.
+    #[arg(long, value_name = "BITS")]
+    pub cell_width:       Option<u32>,
+    /// What `,` does at the end of the input: `unchanged`, `zero` or `max`
+    #[arg(long, value_name = "BEHAVIOR")]
+    pub eof:              Option<EofBehavior>,
+    /// Whether `,` and `.` exchange `bytes`, or `numeric` decimal numbers
+    /// from 0 to 255, one per line on output
+    #[arg(long, value_name = "MODE")]
+    pub io_mode:          Option<IoMode>,
+    /// What to do when the machine does something unusual without stopping,
+    /// like wrapping the pointer around the tape or ignoring an input byte
+    /// above 127: `ignore` it, `print` it to STDERR with its step number, or
+    /// stop with an `error`
+    #[arg(long, value_name = "POLICY", default_value_t = WarningPolicy::Ignore)]
+    pub warnings:         WarningPolicy,
+    /// Put N canary cells past either end of the tape and stop with an error
+    /// naming the instruction, the direction and the step as soon as one is
+    /// read or written, instead of wrapping the pointer around
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub canary_cells:     usize,
+    /// Save the state of the run to the file of `--checkpoint-file` this
+    /// often, for example `90s`, `10m` or `2h`, so that `resume` can continue
+    /// the run if it is stopped
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        value_parser = parse_interval,
+        requires = "checkpoint_file",
+        conflicts_with_all = ["bang_input", "watch", "raw", "echo", "output_format", "visual", "explain"]
+    )]
+    pub checkpoint_every: Option<Duration>,
+    /// Where `--checkpoint-every` saves the state of the run
+    #[arg(long, value_name = "FILE", requires = "checkpoint_every")]
+    pub checkpoint_file:  Option<PathBuf>,
+    /// Execute at most this many instructions per second, for example to
+    /// follow the run with `--visual` or `--explain`
+    #[arg(
+        long,
+        value_name = "IPS",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["watch", "output_format"]
+    )]
+    pub speed:            Option<u32>,
+    /// Follow which input bytes influence every byte the program writes, and
+    /// list them on STDERR once the program stops
+    #[arg(long, conflicts_with_all = ["watch", "output_format", "checkpoint_every"])]
+    pub taint:            bool,
+    /// Print the number of instructions executed, how long they took and the
+    /// size of the output on STDERR once the program stops, with the CPU time
+    /// and the peak memory when built with the `resources` feature
+    #[arg(long, conflicts_with_all = ["watch", "output_format"])]
+    pub stats:            bool,
+    /// Show the number of instructions executed, the loops completed and the
+    /// size of the output on STDERR while the program runs
+    #[arg(long, conflicts_with_all = ["watch", "output_format", "visual", "explain"])]
+    pub progress:         bool,
+    /// The most bytes a program downloaded from a URL may have
+    #[cfg(feature = "http")]
+    #[arg(long, value_name = "BYTES", default_value_t = 1024 * 1024)]
+    pub max_download:     usize,
+    /// Only run a program downloaded from a URL if it has this fingerprint,
+    /// as printed when it is first downloaded, and run the copy in the cache
+    /// if there is one instead of downloading it again. The fingerprint
+    /// depends on `--dialect` and ignores comments, use `--sha256` to check
+    /// the bytes of the program.
+    #[cfg(feature = "http")]
+    #[arg(long, value_name = "HEX")]
+    pub fingerprint:      Option<String>,
+    /// Only run a program downloaded from a URL if its bytes have this
+    /// SHA-256 digest, as printed when it is first downloaded
+    #[cfg(feature = "http")]
+    #[arg(long, value_name = "HEX")]
+    pub sha256:           Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// The output of the program
+    Text,
+    /// A JSON document describing the run
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Visual {
+    /// A single line updated in place, which works on any terminal
+    Simple,
+}
+
+/// Parse an interval such as `500ms`, `90s`, `10m` or `2h`. A number without
+/// a unit is a number of seconds.
+fn parse_interval(interval: &str) -> Result<Duration, String> {
+    let digits = interval
+        .find(|character: char| !character.is_ascii_digit())
+        .unwrap_or(interval.len());
+    let (value, unit) = interval.split_at(digits);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("Expected a number followed by ms, s, m or h, found '{interval}'"))?;
+
+    let duration = match unit {
+        "ms" => Duration::from_millis(value),
+        "" | "s" => Duration::from_secs(value),
+        "m" => Duration::from_secs(value.saturating_mul(60)),
+        "h" => Duration::from_secs(value.saturating_mul(3600)),
+        _ => return Err(format!("Unknown unit '{unit}', expected ms, s, m or h")),
+    };
+    if duration.is_zero() {
+        return Err(String::from("The interval must be longer than zero"));
+    }
+    Ok(duration)
+}
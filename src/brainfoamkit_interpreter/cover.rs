@@ -0,0 +1,79 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::Path,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    config::Config,
+    CaptureWriter,
+    CoverageRecorder,
+    Dialect,
+    Program,
+    VecReader,
+    VirtualMachine,
+};
+
+use crate::run::{
+    self,
+    InvalidProgram,
+};
+
+/// Run the program in `file` on `input` and print its source annotated with
+/// the coverage of every line, followed by the counts of every loop. The
+/// output of the program is discarded. With `lcov`, the coverage is also
+/// saved in the lcov format.
+///
+/// The behavior profile, tape size, cell width, end of input behavior and I/O
+/// mode are taken from the environment and the nearest `brainfoamkit.toml`.
+/// A program that fails is reported once the coverage up to the failure is
+/// printed.
+pub fn report(
+    file: &Path,
+    input: Option<&str>,
+    lcov: Option<&Path>,
+    dialect: Dialect,
+) -> Result<()> {
+    let source = fs::read_to_string(file)
+        .with_context(|| format!("Unable to read program from {}", file.display()))?;
+    let input = input
+        .map(run::file_or_text)
+        .transpose()?
+        .unwrap_or_default();
+    let program = Program::parse(&source, dialect);
+
+    let builder = VirtualMachine::builder()
+        .input_device(VecReader::new(input))
+        .output_device(CaptureWriter::default())
+        .program(program.clone());
+    let mut machine = Config::resolve()?
+        .apply(builder)?
+        .build()
+        .context(InvalidProgram)?;
+    let mut coverage = CoverageRecorder::new(&program);
+    let result = coverage.run(&mut machine);
+
+    print!("{}", coverage.annotate(&source));
+    let (covered, total) = (coverage.covered(), coverage.total());
+    #[allow(clippy::cast_precision_loss)]
+    let percentage = if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    };
+    println!("\nExecuted {covered} of {total} instructions ({percentage:.1}%)");
+    if let Some(path) = lcov {
+        coverage.export_lcov(path, file, &source)?;
+    }
+
+    result?;
+    Ok(())
+}
@@ -0,0 +1,357 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{
+        self,
+        BufRead,
+        BufReader,
+        ErrorKind,
+        Read,
+        Write,
+    },
+    os::unix::net::{
+        UnixListener,
+        UnixStream,
+    },
+    path::Path,
+    sync::{
+        Arc,
+        Mutex,
+        PoisonError,
+    },
+    thread,
+};
+
+use anyhow::{
+    anyhow,
+    bail,
+    Context,
+    Result,
+};
+use base64::{
+    engine::general_purpose::STANDARD,
+    Engine,
+};
+use brainfoamkit_lib::{
+    config::Config,
+    CaptureWriter,
+    Dialect,
+    Limits,
+    Program,
+    VecReader,
+    VirtualMachine,
+};
+use serde_json::{
+    json,
+    Value,
+};
+
+use crate::cli::DaemonArgs;
+
+/// The programs loaded into the daemon
+type Programs = Arc<Mutex<ProgramCache>>;
+
+/// Listen for requests on the Unix domain socket at `args.socket` until the
+/// process is stopped.
+///
+/// The daemon keeps the programs it is sent parsed, with their jump tables
+/// checked, so that scripts running the same program over and over only pay
+/// for parsing it once. Every run still starts a machine of its own. Every
+/// connection is served on a thread of its own and sends requests as lines
+/// of JSON, each answered with a line of JSON:
+///
+/// ```json
+/// {"load": "++++++++[>++++++++<-]>+.", "dialect": "standard"}
+/// {"fingerprint": "753de043a7cc256d"}
+/// {"run": "753de043a7cc256d", "input": "", "max_steps": 1000}
+/// {"output": "QQ==", "status": "halted", "steps": 108}
+/// ```
+///
+/// A `load` request parses the source in the dialect, `standard` by default,
+/// and answers with the fingerprint of the program. A `run` request runs the
+/// program with that fingerprint and dialect on the input encoded in base64,
+/// stopping after its `max_steps` if they are fewer than those of the
+/// daemon, so that a program that never halts does not hold on to its
+/// thread, and answers with the output encoded in base64, the number of
+/// executed instructions and whether the program halted. A program that
+/// fails, and a request that cannot be handled, are answered with an `error`
+/// message.
+///
+/// At most `max_programs` programs are kept, and loading another one forgets
+/// the program run or loaded the longest time ago. A run stops once it has
+/// written `max_output` bytes, and a request longer than `max_request` bytes
+/// is answered with an error and ends the connection.
+///
+/// The behavior profile, tape size, end of input behavior and I/O mode are
+/// taken from the environment and the nearest `brainfoamkit.toml`.
+pub fn execute(args: &DaemonArgs) -> Result<()> {
+    if args.max_programs == 0 {
+        bail!("The daemon must be able to keep at least one program, see --max-programs");
+    }
+    let config = Config::resolve()?;
+    let socket = &args.socket;
+    let listener = bind(socket)?;
+    eprintln!("Listening on {}", socket.display());
+
+    let programs = Arc::new(Mutex::new(ProgramCache::new(args.max_programs)));
+    let limits = Limits::new()
+        .max_steps(args.max_steps)
+        .max_output_bytes(args.max_output);
+    let max_request = args.max_request;
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("Unable to accept a connection: {error}");
+                continue;
+            }
+        };
+        let programs = Arc::clone(&programs);
+        let config = config.clone();
+        thread::spawn(move || {
+            if let Err(error) = serve(stream, &programs, &config, limits, max_request) {
+                eprintln!("{error:#}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Listen on `socket`, replacing the socket a daemon that is gone left
+/// behind.
+fn bind(socket: &Path) -> Result<UnixListener> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            bail!("A daemon is already listening on {}", socket.display());
+        }
+        fs::remove_file(socket)
+            .with_context(|| format!("Unable to remove the stale socket {}", socket.display()))?;
+    }
+    UnixListener::bind(socket).with_context(|| format!("Unable to listen on {}", socket.display()))
+}
+
+/// Answer the requests of the client on the other end of `stream` until it
+/// hangs up or sends a request longer than `max_request` bytes.
+fn serve(
+    stream: UnixStream,
+    programs: &Programs,
+    config: &Config,
+    limits: Limits,
+    max_request: usize,
+) -> Result<()> {
+    let mut replies = stream.try_clone()?;
+    let mut requests = BufReader::new(stream);
+    loop {
+        let request = match read_request(&mut requests, max_request) {
+            Ok(Some(request)) => request,
+            Ok(None) => break,
+            Err(error) if error.kind() == ErrorKind::ConnectionReset => break,
+            Err(error) if error.kind() == ErrorKind::InvalidData => {
+                writeln!(replies, "{}", json!({ "error": error.to_string() }))?;
+                break;
+            }
+            Err(error) => return Err(error.into()),
+        };
+        if request.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let reply = handle(&request, programs, config, limits)
+            .unwrap_or_else(|error| json!({ "error": format!("{error:#}") }));
+        writeln!(replies, "{reply}")?;
+    }
+    Ok(())
+}
+
+/// The next line of `reader`, without reading more than `max_request` bytes
+/// of it, or `None` once the client hangs up.
+///
+/// # Errors
+///
+/// A line longer than `max_request` bytes is an `InvalidData` error.
+fn read_request(reader: &mut impl BufRead, max_request: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    let limit = u64::try_from(max_request).map_or(u64::MAX, |max| max.saturating_add(1));
+    reader.take(limit).read_until(b'\n', &mut line)?;
+    if line.is_empty() {
+        return Ok(None);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    } else if line.len() > max_request {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("The request is longer than {max_request} bytes"),
+        ));
+    }
+    Ok(Some(line))
+}
+
+/// The reply to a single request.
+fn handle(request: &[u8], programs: &Programs, config: &Config, limits: Limits) -> Result<Value> {
+    let request: Value = serde_json::from_slice(request).context("Invalid request")?;
+    let dialect = match request.get("dialect").and_then(Value::as_str) {
+        Some(dialect) => dialect.parse()?,
+        None => Dialect::Standard,
+    };
+    if let Some(source) = request.get("load") {
+        let source = source
+            .as_str()
+            .ok_or_else(|| anyhow!("`load` must be the source of a program"))?;
+        load(Program::parse(source, dialect), programs)
+    } else if let Some(fingerprint) = request.get("run") {
+        let fingerprint = fingerprint
+            .as_str()
+            .and_then(|fingerprint| u64::from_str_radix(fingerprint, 16).ok())
+            .ok_or_else(|| anyhow!("`run` must be the fingerprint of a loaded program"))?;
+        let input = match request.get("input").and_then(Value::as_str) {
+            Some(input) => STANDARD.decode(input).context("`input` must be base64")?,
+            None => Vec::new(),
+        };
+        let limits = match request.get("max_steps").and_then(Value::as_u64) {
+            Some(requested) => {
+                let most = limits.step_limit().unwrap_or(usize::MAX);
+                limits.max_steps(usize::try_from(requested)?.min(most))
+            }
+            None => limits,
+        };
+        run((dialect, fingerprint), input, limits, programs, config)
+    } else {
+        bail!("Unknown request, expected `load` or `run`")
+    }
+}
+
+/// Keep `program` ready to run, and reply with its fingerprint.
+fn load(program: Program, programs: &Programs) -> Result<Value> {
+    program.jump_table()?;
+    let fingerprint = program.fingerprint();
+    programs
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert((program.dialect(), fingerprint), program);
+    Ok(json!({ "fingerprint": format!("{fingerprint:016x}") }))
+}
+
+/// Run the program with `key` on `input`, and reply with its output.
+fn run(
+    key: Key,
+    input: Vec<u8>,
+    limits: Limits,
+    programs: &Programs,
+    config: &Config,
+) -> Result<Value> {
+    let (dialect, fingerprint) = key;
+    let program = programs
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(key)
+        .ok_or_else(|| {
+            anyhow!("No {dialect} program with the fingerprint {fingerprint:016x} is loaded")
+        })?;
+    let builder = VirtualMachine::builder()
+        .input_device(VecReader::new(input))
+        .output_device(CaptureWriter::default())
+        .program(program)
+        .limits(limits);
+    let mut machine = config.apply(builder)?.build()?;
+
+    let result = machine.run();
+    let mut reply = json!({
+        "status": if result.is_ok() { "halted" } else { "error" },
+        "output": STANDARD.encode(&machine.output_device().data),
+        "steps": machine.steps(),
+    });
+    if let Err(error) = result {
+        reply["error"] = json!(error.to_string());
+    }
+    Ok(reply)
+}
+
+/// A loaded program: the dialect it was parsed in and its fingerprint
+type Key = (Dialect, u64);
+
+/// The programs loaded into the daemon, forgetting the least recently used
+/// one when there are more than it may keep
+struct ProgramCache {
+    programs: HashMap<Key, (Program, u64)>,
+    capacity: usize,
+    clock:    u64,
+}
+
+impl ProgramCache {
+    /// An empty cache of at most `capacity` programs
+    fn new(capacity: usize) -> Self {
+        Self {
+            programs: HashMap::new(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    /// Keep `program` under `key`, forgetting the least recently used
+    /// program if the cache is full.
+    fn insert(&mut self, key: Key, program: Program) {
+        self.clock += 1;
+        if !self.programs.contains_key(&key) && self.programs.len() >= self.capacity {
+            let oldest = self
+                .programs
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(&key, _)| key);
+            if let Some(oldest) = oldest {
+                self.programs.remove(&oldest);
+            }
+        }
+        self.programs.insert(key, (program, self.clock));
+    }
+
+    /// The program under `key`, which now counts as the most recently used
+    fn get(&mut self, key: Key) -> Option<Program> {
+        self.clock += 1;
+        let (program, used) = self.programs.get_mut(&key)?;
+        *used = self.clock;
+        Some(program.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_program_cache() {
+        let mut cache = ProgramCache::new(2);
+        let program = |source| Program::parse(source, Dialect::Pbrain);
+        let key = |source| (Dialect::Pbrain, program(source).fingerprint());
+
+        cache.insert(key("+"), program("+"));
+        cache.insert(key("-"), program("-"));
+        assert!(cache.get(key("+")).is_some());
+        // `-` is the least recently used, and makes room for `.`
+        cache.insert(key("."), program("."));
+        assert!(cache.get(key("-")).is_none());
+        assert!(cache.get(key("+")).is_some());
+        assert!(cache.get(key(".")).is_some());
+        assert!(cache.get((Dialect::Stack, key("+").1)).is_none());
+    }
+
+    #[test]
+    fn test_read_request() {
+        let mut reader = Cursor::new(b"1234\n12345\n123".to_vec());
+
+        assert_eq!(read_request(&mut reader, 4).unwrap(), Some(b"1234".to_vec()));
+        let error = read_request(&mut reader, 4).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        let mut reader = Cursor::new(b"123".to_vec());
+        assert_eq!(read_request(&mut reader, 4).unwrap(), Some(b"123".to_vec()));
+        assert_eq!(read_request(&mut reader, 4).unwrap(), None);
+    }
+}
@@ -0,0 +1,66 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::process::ExitCode;
+
+use anyhow::Error;
+use brainfoamkit_lib::VMError;
+
+use crate::{
+    replay::ReplayDiverged,
+    run::{
+        Interrupted,
+        InvalidProgram,
+        OutputMismatch,
+    },
+};
+
+/// Any error without an exit status of its own, such as a file that cannot be
+/// read.
+pub const FAILURE: u8 = 1;
+
+/// The program cannot be run, for example because its brackets are not
+/// balanced. Invalid command line arguments exit with 2.
+pub const INVALID_PROGRAM: u8 = 3;
+
+/// The program exceeded one of the resource limits of the run.
+pub const LIMIT_EXCEEDED: u8 = 4;
+
+/// The program broke the rules of the behavior profile.
+pub const PROFILE_VIOLATION: u8 = 5;
+
+/// The program wrote something other than the expected output.
+pub const OUTPUT_MISMATCH: u8 = 6;
+
+/// A replayed program did not behave as it did when it was recorded.
+pub const REPLAY_DIVERGED: u8 = 7;
+
+/// The run was stopped with Ctrl-C, as by the shell convention of 128 plus
+/// the number of `SIGINT`.
+pub const INTERRUPTED: u8 = 130;
+
+/// The exit status of the process after `error` stopped it.
+pub fn for_error(error: &Error) -> ExitCode {
+    ExitCode::from(code(error))
+}
+
+/// The number of the exit status for `error`.
+pub fn code(error: &Error) -> u8 {
+    if error.downcast_ref::<InvalidProgram>().is_some() {
+        INVALID_PROGRAM
+    } else if matches!(error.downcast_ref(), Some(VMError::LimitExceeded(_))) {
+        LIMIT_EXCEEDED
+    } else if matches!(error.downcast_ref(), Some(VMError::ProfileViolation(_))) {
+        PROFILE_VIOLATION
+    } else if error.downcast_ref::<OutputMismatch>().is_some() {
+        OUTPUT_MISMATCH
+    } else if error.downcast_ref::<ReplayDiverged>().is_some() {
+        REPLAY_DIVERGED
+    } else if error.downcast_ref::<Interrupted>().is_some() {
+        INTERRUPTED
+    } else {
+        FAILURE
+    }
+}
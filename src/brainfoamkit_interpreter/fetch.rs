@@ -0,0 +1,307 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    env,
+    fs,
+    io::Read,
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::Duration,
+};
+
+use anyhow::{
+    anyhow,
+    bail,
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    Dialect,
+    Program,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
+
+use crate::cli::RunArgs;
+
+/// How long connecting to the server, and every read from it, may take
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The most redirects followed before giving up
+const MAX_REDIRECTS: u32 = 5;
+
+/// Download the program at the URL in `args.file` and run the downloaded
+/// copy instead.
+///
+/// Programs are saved in the cache directory, `$XDG_CACHE_HOME` or
+/// `~/.cache`, under their dialect and fingerprint. With `fingerprint`, the
+/// download must have that fingerprint, and a program already in the cache is
+/// run without downloading it again, once it is parsed again and found to
+/// still have that fingerprint. The fingerprint ignores comments, so with
+/// `sha256`
+/// the bytes of the program, downloaded or cached, must also have that
+/// SHA-256 digest. A program larger than `max_download` bytes is not
+/// downloaded at all.
+///
+/// Both `http://` and `https://` URLs can be downloaded. Anything that is
+/// not a URL is left alone.
+pub fn resolve(args: &mut RunArgs) -> Result<()> {
+    let Some(url) = args.file.to_str().filter(|file| is_url(file)) else {
+        return Ok(());
+    };
+    let url = url.to_owned();
+    let expected = args
+        .fingerprint
+        .as_deref()
+        .map(|fingerprint| {
+            u64::from_str_radix(fingerprint, 16).map_err(|_| {
+                anyhow!("Invalid fingerprint '{fingerprint}', expected 16 hexadecimal digits")
+            })
+        })
+        .transpose()?;
+    let digest = args
+        .sha256
+        .as_deref()
+        .map(|digest| {
+            if digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                Ok(digest.to_ascii_lowercase())
+            } else {
+                Err(anyhow!(
+                    "Invalid digest '{digest}', expected 64 hexadecimal digits"
+                ))
+            }
+        })
+        .transpose()?;
+
+    let cache = cache_dir();
+    if let Some(expected) = expected {
+        let cached = cache_path(&cache, args.dialect, expected);
+        if is_cached(&cached, args.dialect, expected, digest.as_deref()) {
+            args.file = cached;
+            return Ok(());
+        }
+    }
+
+    let source = download(&url, args.max_download)?;
+    if let Some(expected) = &digest {
+        check_digest(&url, &source, expected)?;
+    }
+    let fingerprint = Program::parse(&source, args.dialect).fingerprint();
+    if let Some(expected) = expected {
+        if fingerprint != expected {
+            bail!(
+                "The program at {url} has the fingerprint {fingerprint:016x}, expected \
+                 {expected:016x}"
+            );
+        }
+    }
+
+    fs::create_dir_all(&cache)
+        .with_context(|| format!("Unable to create the cache directory {}", cache.display()))?;
+    let path = cache_path(&cache, args.dialect, fingerprint);
+    fs::write(&path, &source)
+        .with_context(|| format!("Unable to save the program to {}", path.display()))?;
+    eprintln!(
+        "Downloaded {url} ({fingerprint:016x}, sha256 {})",
+        sha256(source.as_bytes())
+    );
+    args.file = path;
+    Ok(())
+}
+
+/// Where the program of `dialect` with `fingerprint` is kept in `cache`.
+fn cache_path(cache: &Path, dialect: Dialect, fingerprint: u64) -> PathBuf {
+    cache.join(format!("{dialect}-{fingerprint:016x}.bf"))
+}
+
+/// Whether the program at `path` can be run in place of a download: it
+/// parses in `dialect` to a program with the `expected` fingerprint, and has
+/// the `digest` if there is one.
+fn is_cached(path: &Path, dialect: Dialect, expected: u64, digest: Option<&str>) -> bool {
+    fs::read_to_string(path).is_ok_and(|source| {
+        Program::parse(&source, dialect).fingerprint() == expected
+            && digest.map_or(true, |digest| sha256(source.as_bytes()) == digest)
+    })
+}
+
+/// The SHA-256 digest of `bytes`, in lowercase hexadecimal.
+fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Check that the program downloaded from `url` has the `expected` digest.
+fn check_digest(url: &str, source: &str, expected: &str) -> Result<()> {
+    let digest = sha256(source.as_bytes());
+    if digest != expected {
+        bail!("The program at {url} has the SHA-256 digest {digest}, expected {expected}");
+    }
+    Ok(())
+}
+
+/// Whether the program is named by a URL rather than a path.
+fn is_url(file: &str) -> bool {
+    file.starts_with("http://") || file.starts_with("https://")
+}
+
+/// Where downloaded programs are kept.
+fn cache_dir() -> PathBuf {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(env::temp_dir)
+        .join("brainfoamkit")
+        .join("programs")
+}
+
+/// Fetch the body of `url`, following redirects.
+///
+/// `ureq` handles TLS and the redirects. The body is read up to one byte
+/// past `max_download`, so that a server lying about, or leaving out, the
+/// length of a larger program is caught without reading all of it.
+fn download(url: &str, max_download: usize) -> Result<String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(TIMEOUT)
+        .timeout_read(TIMEOUT)
+        .redirects(MAX_REDIRECTS)
+        .user_agent(concat!("bfkrun/", env!("CARGO_PKG_VERSION")))
+        .build();
+    let response = agent.get(url).call().map_err(|error| match error {
+        ureq::Error::Status(status, _) => {
+            anyhow!("Unable to download {url}: the server answered {status}")
+        }
+        ureq::Error::Transport(error) => {
+            anyhow!(error).context(format!("Unable to download {url}"))
+        }
+    })?;
+
+    let too_large =
+        || anyhow!("The program at {url} is larger than {max_download} bytes, see --max-download");
+    let length = response
+        .header("Content-Length")
+        .and_then(|length| length.parse::<usize>().ok());
+    if length.is_some_and(|length| length > max_download) {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(u64::try_from(max_download)?.saturating_add(1))
+        .read_to_end(&mut body)
+        .with_context(|| format!("Unable to download {url}"))?;
+    if body.len() > max_download {
+        return Err(too_large());
+    }
+    String::from_utf8(body).with_context(|| format!("The program at {url} is not UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{
+            BufRead,
+            BufReader,
+            Write,
+        },
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    /// Answer one request for every response in `responses`, in order, on a
+    /// local port, and return the URL of the server.
+    fn serve(responses: Vec<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while request.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    fn ok(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    #[test]
+    fn test_download_follows_redirects() {
+        let url = serve(vec![
+            "HTTP/1.1 302 Found\r\nLocation: /hello.bf\r\nContent-Length: 0\r\nConnection: \
+             close\r\n\r\n"
+                .to_owned(),
+            ok("+[.+]"),
+        ]);
+
+        assert_eq!(download(&format!("{url}/start"), 100).unwrap(), "+[.+]");
+    }
+
+    #[test]
+    fn test_check_digest() {
+        let url = serve(vec![ok("abc")]);
+        let source = download(&url, 100).unwrap();
+
+        // The SHA-256 digest of "abc"
+        let digest = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert!(check_digest(&url, &source, digest).is_ok());
+        let error = check_digest(&url, &source, &"0".repeat(64))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains(&format!("has the SHA-256 digest {digest}")),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_is_cached() {
+        let cache = tempfile::tempdir().unwrap();
+        let fingerprint = Program::parse("+@", Dialect::Stack).fingerprint();
+        let path = cache_path(cache.path(), Dialect::Stack, fingerprint);
+        assert!(path.ends_with(format!("stack-{fingerprint:016x}.bf")));
+
+        assert!(!is_cached(&path, Dialect::Stack, fingerprint, None));
+        fs::write(&path, "+@").unwrap();
+        assert!(is_cached(&path, Dialect::Stack, fingerprint, None));
+        assert!(!is_cached(&path, Dialect::Pbrain, fingerprint, None));
+        assert!(!is_cached(&path, Dialect::Stack, fingerprint, Some("00")));
+
+        // A cached file that changed since it was downloaded is not run
+        fs::write(&path, "-@").unwrap();
+        assert!(!is_cached(&path, Dialect::Stack, fingerprint, None));
+    }
+
+    #[test]
+    fn test_download_errors() {
+        let url = serve(vec![
+            ok("++++++++"),
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_owned(),
+        ]);
+
+        let error = download(&url, 4).unwrap_err().to_string();
+        assert!(error.contains("larger than 4 bytes"), "{error}");
+        let error = download(&url, 100).unwrap_err().to_string();
+        assert!(error.contains("answered 404"), "{error}");
+    }
+}
@@ -0,0 +1,176 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    io::{
+        self,
+        Read,
+    },
+    time::Instant,
+};
+
+use anyhow::{
+    Context,
+    Error,
+    Result,
+};
+use base64::{
+    engine::general_purpose::STANDARD,
+    Engine,
+};
+use brainfoamkit_lib::{
+    config::Config,
+    CaptureWriter,
+    Program,
+    ResourceUsage,
+    VecReader,
+};
+use serde_json::{
+    json,
+    Value,
+};
+
+use crate::{
+    cli::RunArgs,
+    exit_code,
+    run,
+};
+
+/// Run the program in `source` and print a JSON document describing the run.
+///
+/// The document holds the status of the run, the exit status of the process,
+/// the output of the program encoded in base64, the number of executed
+/// instructions, the duration of the run in seconds, the final memory pointer
+/// and program counter, and the error that stopped the program, if any, with
+/// the line and column of the offending instruction. With the `resources`
+/// feature, it also holds the CPU time of the run in seconds and the peak
+/// memory of the process in bytes, and `null` otherwise:
+///
+/// ```json
+/// {
+///   "duration": 0.0001,
+///   "error": {
+///     "column": 12,
+///     "instruction": 57,
+///     "line": 3,
+///     "message": "Output at instruction 57 exceeded the limit of 2 bytes"
+///   },
+///   "exit_code": 4,
+///   "memory_pointer": 1,
+///   "output": "SGk=",
+///   "program_counter": 57,
+///   "resources": {
+///     "cpu_time": 0.0001,
+///     "peak_memory": 3211264,
+///     "system_time": 0.0,
+///     "user_time": 0.0001
+///   },
+///   "status": "error",
+///   "steps": 1042
+/// }
+/// ```
+///
+/// The machine is built as for a plain run, see [`run::build`]. The program
+/// reads STDIN to the end before it starts. Returns the error
+/// that stopped the program after printing the document, so that the process
+/// exits with a non-zero status.
+pub fn execute(args: &RunArgs, source: &str, config: &Config) -> Result<()> {
+    let (program, input) = if args.bang_input {
+        Program::parse_with_input(source, args.dialect)
+    } else {
+        let mut input = Vec::new();
+        io::stdin()
+            .read_to_end(&mut input)
+            .context("Unable to read STDIN")?;
+        (Program::parse(source, args.dialect), input)
+    };
+
+    let machine = run::build(
+        program,
+        VecReader::new(input),
+        CaptureWriter::default(),
+        args,
+        config,
+    );
+    let (mut machine, progress) = match machine {
+        Ok(machine) => machine,
+        Err(error) => {
+            let document = json!({
+                "status": "error",
+                "exit_code": exit_code::code(&error),
+                "output": "",
+                "steps": 0,
+                "duration": 0.0,
+                "memory_pointer": 0,
+                "program_counter": 0,
+                "resources": Value::Null,
+                "error": { "message": format!("{error:#}") },
+            });
+            println!("{}", serde_json::to_string_pretty(&document)?);
+            return Err(error);
+        }
+    };
+
+    let (started, usage) = (Instant::now(), ResourceUsage::current());
+    let result = machine.run().map_err(Error::from);
+    let duration = started.elapsed();
+    if let Some(progress) = progress {
+        progress.finish_and_clear();
+    }
+    let usage = run::run_usage(usage);
+    run::dump_tape(&machine, args)?;
+
+    let program_counter = machine.program_counter();
+    let document = json!({
+        "status": if result.is_ok() { "halted" } else { "error" },
+        "exit_code": result.as_ref().map_or_else(exit_code::code, |_| 0),
+        "output": STANDARD.encode(&machine.output_device().data),
+        "steps": machine.steps(),
+        "duration": duration.as_secs_f64(),
+        "memory_pointer": machine.memory_pointer(),
+        "program_counter": program_counter,
+        "resources": usage.map_or(Value::Null, |usage| {
+            json!({
+                "cpu_time": usage.cpu_time().as_secs_f64(),
+                "user_time": usage.user_time().as_secs_f64(),
+                "system_time": usage.system_time().as_secs_f64(),
+                "peak_memory": usage.peak_memory(),
+            })
+        }),
+        "error": result
+            .as_ref()
+            .err()
+            .map_or(Value::Null, |error| describe(error, source, program_counter)),
+    });
+    println!("{}", serde_json::to_string_pretty(&document)?);
+
+    result.map(|_| ())
+}
+
+/// Describe an error that stopped the program at the instruction at
+/// `program_counter`.
+///
+/// The machine stops before executing the offending instruction, so the
+/// program counter points at it. Since every character of the source is an
+/// instruction, it is also the index of the character in the source.
+fn describe(error: &Error, source: &str, program_counter: usize) -> Value {
+    let mut line = 1;
+    let mut column = 1;
+    for character in source.chars().take(program_counter) {
+        if character == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+
+    json!({
+        "message": format!("{error:#}"),
+        "instruction": program_counter,
+        "line": line,
+        "column": column,
+    })
+}
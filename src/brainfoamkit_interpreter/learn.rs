@@ -0,0 +1,273 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::io::{
+    self,
+    Stdout,
+};
+
+use anyhow::{
+    bail,
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    tutorial::{
+        self,
+        Attempt,
+        Lesson,
+    },
+    LimitExceeded,
+    VMError,
+};
+use crossterm::{
+    event::{
+        self,
+        Event,
+        KeyCode,
+        KeyEventKind,
+        KeyModifiers,
+    },
+    execute,
+    terminal::{
+        disable_raw_mode,
+        enable_raw_mode,
+        EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use ratatui::{
+    prelude::*,
+    widgets::{
+        Block,
+        Borders,
+        Paragraph,
+        Wrap,
+    },
+};
+
+/// The keys of the tutorial, shown at the bottom of the screen
+const HELP: &str =
+    "Enter: run  Tab: next lesson  Shift+Tab: previous lesson  Ctrl+S: show a solution  Esc: quit";
+
+/// Take the lessons of the tutorial in the terminal, starting with the lesson
+/// numbered `lesson`, counting from 1.
+///
+/// Every lesson shows its instructions and the starting tape above a line to
+/// type a program on. Enter runs the program on the lesson and tells whether
+/// it reached the goal, showing the output and the tape it left behind.
+pub fn execute(lesson: usize) -> Result<()> {
+    let lessons = tutorial::lessons();
+    if lesson == 0 || lesson > lessons.len() {
+        bail!(
+            "There is no lesson {lesson}, pick one from 1 to {}",
+            lessons.len()
+        );
+    }
+    let mut tutor = Tutor {
+        lessons,
+        current: lesson - 1,
+        program: String::new(),
+        verdict: None,
+        quit: false,
+    };
+
+    let mut terminal = setup_terminal().context("setup failed")?;
+    let result = tutor.run(&mut terminal);
+    restore_terminal(&mut terminal).context("restore terminal failed")?;
+    result
+}
+
+/// The result of running the program of the learner.
+enum Verdict {
+    Solved(Attempt),
+    NotYet(Attempt),
+    Failed(String),
+}
+
+struct Tutor {
+    lessons: &'static [Lesson],
+    current: usize,
+    program: String,
+    verdict: Option<Verdict>,
+    quit:    bool,
+}
+
+impl Tutor {
+    fn run(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.render(frame))?;
+            if let Event::Key(key) = event::read().context("event read failed")? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Esc => self.quit = true,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.quit = true;
+                    }
+                    KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.program = self.lesson().solution.to_string();
+                        self.verdict = None;
+                    }
+                    KeyCode::Char(character) => {
+                        self.program.push(character);
+                        self.verdict = None;
+                    }
+                    KeyCode::Backspace => {
+                        self.program.pop();
+                        self.verdict = None;
+                    }
+                    KeyCode::Enter => self.check(),
+                    KeyCode::Tab => self.go_to((self.current + 1).min(self.lessons.len() - 1)),
+                    KeyCode::BackTab => self.go_to(self.current.saturating_sub(1)),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    const fn lesson(&self) -> &Lesson {
+        &self.lessons[self.current]
+    }
+
+    fn go_to(&mut self, lesson: usize) {
+        if lesson != self.current {
+            self.current = lesson;
+            self.program.clear();
+            self.verdict = None;
+        }
+    }
+
+    fn check(&mut self) {
+        let lesson = self.lesson();
+        self.verdict = Some(match lesson.attempt(&self.program) {
+            Ok(attempt) if lesson.is_solved_by(&attempt) => Verdict::Solved(attempt),
+            Ok(attempt) => Verdict::NotYet(attempt),
+            Err(VMError::LimitExceeded(LimitExceeded::Steps { limit })) => {
+                Verdict::Failed(format!(
+                    "The program did not finish within {limit} steps, is there a loop that never \
+                     ends?"
+                ))
+            }
+            Err(error) => Verdict::Failed(error.to_string()),
+        });
+    }
+
+    /// Render the lesson, the program typed so far, the verdict on the last
+    /// run and the available keys.
+    fn render(&self, frame: &mut Frame) {
+        let [lesson_area, tape_area, program_area, verdict_area, help_area] = Layout::vertical([
+            Constraint::Min(5),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(6),
+            Constraint::Length(1),
+        ])
+        .areas(frame.size());
+        let lesson = self.lesson();
+
+        let title = format!(
+            "Lesson {} of {}: {}",
+            self.current + 1,
+            self.lessons.len(),
+            lesson.title
+        );
+        frame.render_widget(
+            Paragraph::new(lesson.instructions)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .wrap(Wrap { trim: true }),
+            lesson_area,
+        );
+
+        let mut tape = vec![0; tutorial::TAPE_SIZE.max(lesson.tape.len())];
+        tape[..lesson.tape.len()].copy_from_slice(lesson.tape);
+        frame.render_widget(
+            Paragraph::new(cells(&tape, 0)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Starting tape"),
+            ),
+            tape_area,
+        );
+
+        frame.render_widget(
+            Paragraph::new(format!("{}_", self.program))
+                .block(Block::default().borders(Borders::ALL).title("Your program")),
+            program_area,
+        );
+
+        let (title, color, text) = match &self.verdict {
+            None => (
+                "Result",
+                Color::Reset,
+                "Press Enter to run your program".to_string(),
+            ),
+            Some(Verdict::Solved(attempt)) => (
+                "Solved!",
+                Color::Green,
+                summary(attempt, "Well done! Press Tab for the next lesson."),
+            ),
+            Some(Verdict::NotYet(attempt)) => (
+                "Not yet",
+                Color::Yellow,
+                summary(attempt, "Your program ran, but did not reach the goal."),
+            ),
+            Some(Verdict::Failed(error)) => ("Error", Color::Red, error.clone()),
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(title)
+                        .border_style(Style::default().fg(color)),
+                )
+                .wrap(Wrap { trim: false }),
+            verdict_area,
+        );
+
+        frame.render_widget(Paragraph::new(HELP), help_area);
+    }
+}
+
+/// Describe the output and the tape an attempt left behind.
+fn summary(attempt: &Attempt, message: &str) -> String {
+    format!(
+        "{message}\nOutput: {:?}\nTape:   {}",
+        String::from_utf8_lossy(&attempt.output),
+        cells(&attempt.tape, attempt.memory_pointer)
+    )
+}
+
+/// The values of the cells, with the cell under the pointer in brackets.
+fn cells(tape: &[u8], memory_pointer: usize) -> String {
+    tape.iter()
+        .enumerate()
+        .map(|(index, value)| {
+            if index == memory_pointer {
+                format!("[{value}]")
+            } else {
+                value.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
+    let mut stdout = io::stdout();
+    enable_raw_mode().context("failed to enable raw mode")?;
+    execute!(stdout, EnterAlternateScreen).context("unable to enter alternate screen")?;
+    Terminal::new(CrosstermBackend::new(stdout)).context("creating terminal failed")
+}
+
+fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    disable_raw_mode().context("failed to disable raw mode")?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)
+        .context("unable to switch to main screen")?;
+    terminal.show_cursor().context("unable to show cursor")
+}
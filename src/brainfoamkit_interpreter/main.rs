@@ -3,39 +3,111 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use brainfoamkit_lib::{
-    AsciiChar,
-    AsciiTable,
-    Byte,
-};
-use prettytable::{
-    format::{
-        self,
-    },
-    row,
-    Table,
-};
+mod ascii;
+mod cfg;
+mod check;
+mod checkpoint;
+mod cli;
+mod cover;
+#[cfg(unix)]
+mod daemon;
+mod exit_code;
+#[cfg(feature = "http")]
+mod fetch;
+mod json;
+mod learn;
+mod mutate;
+mod progress;
+mod project;
+mod replay;
+mod run;
+mod serve;
+mod similar;
+mod solve;
+mod stat;
+mod test_runner;
+mod visual;
+mod watch;
 
-fn main() {
-    let mut table = Table::new();
-    let ascii = AsciiTable::new();
+use std::process::ExitCode;
 
-    table.set_titles(row![bc => "Byte", "Binary", "Hexadecimal", "String", "Representation"]);
-    table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+use anyhow::Result;
+use clap::Parser;
+
+use crate::cli::{
+    Cli,
+    Command,
+};
+
+/// Run the command and exit with a status that tells apart the ways it can
+/// fail, see [`exit_code`].
+fn main() -> ExitCode {
+    brainfoamkit_lib::crash::install_panic_hook();
+    match execute(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            exit_code::for_error(&error)
+        }
+    }
+}
 
-    for num in 0..128 {
-        let byte = Byte::from(num);
-        let char = ascii
-            .get(byte)
-            .map_or("NA".to_owned(), AsciiChar::character_value);
-        table.add_row(row![c=>
-            format!("{num}", num = &byte),
-            format!("{num:#010b}", num = u8::from(&byte)),
-            format!("{num:#04X}", num = u8::from(&byte)),
-            format!("{num}", num = byte.to_string()),
-            format!("{char}", char = char)
-        ]);
+fn execute(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::Ascii => ascii::print_table(),
+        Command::Build => project::build()?,
+        Command::Check { file, explain } => check::report(&file, explain)?,
+        Command::Cfg { file, output } => cfg::export(&file, output.as_deref())?,
+        Command::Cover {
+            file,
+            input,
+            lcov,
+            dialect,
+        } => cover::report(&file, input.as_deref(), lcov.as_deref(), dialect)?,
+        #[cfg(unix)]
+        Command::Daemon(args) => daemon::execute(&args)?,
+        Command::Learn { lesson } => learn::execute(lesson)?,
+        Command::Mutate { directory } => mutate::execute(directory.as_deref())?,
+        Command::New { directory } => project::new(&directory)?,
+        Command::Record {
+            file,
+            output,
+            dialect,
+            seed,
+        } => replay::record(&file, output, dialect, seed)?,
+        Command::Replay { bundle } => replay::replay(&bundle)?,
+        Command::Resume {
+            checkpoint,
+            output,
+            checkpoint_every,
+        } => checkpoint::resume(&checkpoint, output.as_deref(), checkpoint_every)?,
+        Command::Run(mut args) => {
+            #[cfg(feature = "http")]
+            fetch::resolve(&mut args)?;
+            project::resolve(&mut args)?;
+            if args.watch {
+                watch::execute(&args)?;
+            } else {
+                run::execute(&args)?;
+            }
+        }
+        Command::Serve(args) => serve::execute(&args)?,
+        Command::Similar {
+            directory,
+            threshold,
+        } => similar::report(&directory, threshold)?,
+        Command::Solve {
+            file,
+            max_paths,
+            max_steps,
+        } => solve::report(&file, max_paths, max_steps)?,
+        Command::Stat { file } => stat::report(&file)?,
+        Command::Test {
+            directory,
+            tap,
+            chaos,
+        } => test_runner::execute(directory.as_deref(), tap, chaos)?,
     }
 
-    table.printstd();
+    Ok(())
 }
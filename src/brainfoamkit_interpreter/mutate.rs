@@ -0,0 +1,45 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::path::Path;
+
+use anyhow::{
+    anyhow,
+    Result,
+};
+use brainfoamkit_lib::{
+    mutate,
+    test_runner,
+    Limits,
+};
+
+use crate::project;
+
+/// Run the tests in `directory` against every mutant of their programs and
+/// print the mutants that survived. Without a directory, the tests of the
+/// project in the nearest `bfk.toml` are used.
+///
+/// Returns an error if a mutant survived, so that the process exits with a
+/// non-zero status.
+pub fn execute(directory: Option<&Path>) -> Result<()> {
+    let report = match directory {
+        Some(directory) => mutate::run_dir(directory)?,
+        None => mutate::run(
+            project::manifest()?.test_cases(),
+            Limits::new().max_steps(test_runner::MAX_STEPS),
+        ),
+    };
+    print!("{report}");
+
+    let survived = report.survivors().count();
+    if survived == 0 {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{survived} of {} mutants survived",
+            report.mutants.len()
+        ))
+    }
+}
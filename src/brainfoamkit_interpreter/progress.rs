@@ -0,0 +1,70 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::time::Instant;
+
+use brainfoamkit_lib::{
+    format::{
+        human_bytes,
+        human_count,
+        human_duration,
+        human_rate,
+    },
+    Progress,
+    ProgressReporter,
+};
+use indicatif::{
+    ProgressBar,
+    ProgressDrawTarget,
+    ProgressStyle,
+};
+
+/// The number of steps between two reports of the machine
+pub const INTERVAL: usize = 100_000;
+
+/// A spinner on STDERR with the steps executed, the loops completed and the
+/// output written so far.
+///
+/// indicatif redraws the line in place at most a few times a second, and
+/// draws nothing when STDERR is not a terminal, which keeps logs clean.
+pub struct ProgressLine {
+    bar:     ProgressBar,
+    started: Instant,
+}
+
+impl ProgressLine {
+    pub fn new() -> Self {
+        let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        Self {
+            bar,
+            started: Instant::now(),
+        }
+    }
+
+    /// A handle on the spinner, to clear it once the machine that owns the
+    /// line stops.
+    pub fn bar(&self) -> ProgressBar {
+        self.bar.clone()
+    }
+}
+
+impl ProgressReporter for ProgressLine {
+    fn report(&mut self, progress: &Progress) {
+        let elapsed = self.started.elapsed();
+        self.bar.set_message(format!(
+            "{} steps ({}), {} loops completed, {} written, {}",
+            human_count(progress.steps),
+            human_rate(progress.steps, elapsed),
+            human_count(progress.loops_completed),
+            human_bytes(progress.bytes_written),
+            human_duration(elapsed),
+        ));
+        self.bar.tick();
+    }
+}
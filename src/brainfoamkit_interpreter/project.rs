@@ -0,0 +1,108 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::path::Path;
+
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    project::{
+        Manifest,
+        MANIFEST_NAME,
+    },
+    Dialect,
+};
+
+use crate::{
+    cli::RunArgs,
+    run::InvalidProgram,
+};
+
+/// The manifest of the project in the nearest `bfk.toml`.
+///
+/// Fails if there is no manifest in the current directory or its ancestors.
+pub fn manifest() -> Result<Manifest> {
+    Manifest::discover()?.ok_or_else(|| {
+        anyhow!("No {MANIFEST_NAME} found in the current directory or its ancestors")
+    })
+}
+
+/// Start a new project in `directory` and list what to do with it.
+pub fn new(directory: &Path) -> Result<()> {
+    let manifest = Manifest::scaffold(directory)?;
+    let name = &manifest.programs[0].name;
+    eprintln!("Created the project {name} in {}", directory.display());
+    eprintln!();
+    eprintln!(
+        "Run its program with:    cd {} && bfkrun run {name}",
+        directory.display()
+    );
+    eprintln!("Run its tests with:      bfkrun test");
+    eprintln!("Run the examples with:   bfkrun test examples");
+    Ok(())
+}
+
+/// Check that every program of the project can be run, and report each one.
+///
+/// Fails with an [`InvalidProgram`] if a program cannot be read or its
+/// brackets are not balanced.
+pub fn build() -> Result<()> {
+    let manifest = manifest()?;
+    let mut errors = 0;
+    for program in &manifest.programs {
+        match program.build() {
+            Ok(_) => eprintln!("Built {} ({})", program.name, program.path.display()),
+            Err(error) => {
+                eprintln!(
+                    "Failed {} ({}): {error}",
+                    program.name,
+                    program.path.display()
+                );
+                errors += 1;
+            }
+        }
+    }
+
+    if errors > 0 {
+        return Err(anyhow!(
+            "{errors} of {} programs failed to build",
+            manifest.programs.len()
+        )
+        .context(InvalidProgram));
+    }
+    Ok(())
+}
+
+/// Run the program of the project called `args.file` if there is no file
+/// with that name.
+///
+/// The program runs with the dialect and behavior profile of the manifest,
+/// unless they are given on the command line, and with its data files loaded
+/// into the tape.
+pub fn resolve(args: &mut RunArgs) -> Result<()> {
+    if args.file.exists() {
+        return Ok(());
+    }
+    let Some(name) = args.file.to_str() else {
+        return Ok(());
+    };
+    let Some(manifest) = Manifest::discover()? else {
+        return Ok(());
+    };
+    let program = manifest
+        .program(name)
+        .with_context(|| format!("No program or file called {name}"))?;
+
+    args.file.clone_from(&program.path);
+    if args.dialect == Dialect::default() {
+        args.dialect = program.dialect;
+    }
+    args.profile = args.profile.or(program.profile);
+    args.data.clone_from(&program.data);
+    Ok(())
+}
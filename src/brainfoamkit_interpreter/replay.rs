@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    fs,
+    io::{
+        self,
+        Write,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use anyhow::{
+    bail,
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    config::Config,
+    test_runner,
+    Dialect,
+    ReplayBundle,
+    ReplayError,
+    ReplayOutcome,
+    TerminalReader,
+};
+
+use crate::run::InvalidProgram;
+
+/// Run the program in `file` on STDIN and STDOUT, and save the run to a
+/// bundle at `output`, or next to the program with the `.bfkrun` extension.
+///
+/// The behavior profile, tape size, cell width, end of input behavior and I/O
+/// mode are taken from the environment and the nearest `brainfoamkit.toml`,
+/// and saved in the bundle, with the seed of the random bytes of the `random`
+/// dialect. A program that fails is recorded with its error,
+/// and the failure is reported once the bundle is saved.
+pub fn record(
+    file: &Path,
+    output: Option<PathBuf>,
+    dialect: Dialect,
+    seed: Option<u64>,
+) -> Result<()> {
+    let source = fs::read_to_string(file)
+        .with_context(|| format!("Unable to read program from {}", file.display()))?;
+    let config = Config::resolve()?;
+
+    let (bundle, _) = ReplayBundle::record(
+        &source,
+        dialect,
+        config,
+        seed,
+        TerminalReader::new(),
+        io::stdout(),
+    )
+    .map_err(invalid_program)?;
+    let path = output.unwrap_or_else(|| file.with_extension("bfkrun"));
+    bundle.save(&path)?;
+    eprintln!(
+        "Recorded {} steps and {} byte(s) of input to {}",
+        bundle.outcome.steps,
+        bundle.input.len(),
+        path.display()
+    );
+
+    if let Some(error) = bundle.outcome.error {
+        bail!(error);
+    }
+    Ok(())
+}
+
+/// Replay the bundle at `path`, writing the output of the program to STDOUT.
+///
+/// Fails with a [`ReplayDiverged`] if the program does not write the same
+/// output, take the same number of steps and fail the same way as it did when
+/// it was recorded.
+pub fn replay(path: &Path) -> Result<()> {
+    let bundle = ReplayBundle::load(path)?;
+    let outcome = bundle.replay().map_err(invalid_program)?;
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(&outcome.output)?;
+    stdout.flush()?;
+
+    if outcome != bundle.outcome {
+        return Err(ReplayDiverged {
+            recorded: bundle.outcome,
+            replayed: outcome,
+        }
+        .into());
+    }
+    eprintln!("Reproduced {} steps from {}", outcome.steps, path.display());
+    Ok(())
+}
+
+/// Mark a program whose brackets are not balanced as an [`InvalidProgram`].
+fn invalid_program(error: ReplayError) -> anyhow::Error {
+    if matches!(error, ReplayError::Build(_)) {
+        anyhow::Error::new(error).context(InvalidProgram)
+    } else {
+        error.into()
+    }
+}
+
+/// The replayed program did not behave as it did when it was recorded.
+#[derive(Debug)]
+pub struct ReplayDiverged {
+    recorded: ReplayOutcome,
+    replayed: ReplayOutcome,
+}
+
+impl Display for ReplayDiverged {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "The replay diverged from the recording")?;
+        if self.recorded.steps != self.replayed.steps {
+            write!(
+                f,
+                "\nRecorded {} steps, replayed {}",
+                self.recorded.steps, self.replayed.steps
+            )?;
+        }
+        if self.recorded.error != self.replayed.error {
+            write!(
+                f,
+                "\nRecorded error: {}\nReplayed error: {}",
+                self.recorded.error.as_deref().unwrap_or("none"),
+                self.replayed.error.as_deref().unwrap_or("none")
+            )?;
+        }
+        if self.recorded.output != self.replayed.output {
+            write!(
+                f,
+                "\n--- recorded\n+++ replayed\n{}",
+                test_runner::diff(
+                    &String::from_utf8_lossy(&self.recorded.output),
+                    &String::from_utf8_lossy(&self.replayed.output),
+                )
+                .trim_end()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ReplayDiverged {}
@@ -0,0 +1,590 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    fs::{
+        self,
+        File,
+    },
+    io::{
+        self,
+        BufWriter,
+        IsTerminal,
+    },
+    path::Path,
+    sync::{
+        atomic::AtomicBool,
+        Arc,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use anyhow::{
+    bail,
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    config::Config,
+    crash,
+    explain,
+    format::{
+        human_bytes,
+        human_count,
+        human_duration,
+        human_rate,
+    },
+    project::ProjectData,
+    test_runner,
+    Asciicast,
+    CancellationToken,
+    CellView,
+    Instruction,
+    Limits,
+    Preprocessor,
+    Program,
+    ResourceUsage,
+    StopReason,
+    TaintTracker,
+    TerminalReader,
+    VMReader,
+    VMWriter,
+    VecReader,
+    VirtualMachine,
+};
+use crossterm::terminal;
+use indicatif::ProgressBar;
+use signal_hook::{
+    consts::SIGINT,
+    flag,
+};
+
+use crate::{
+    checkpoint::{
+        Checkpointing,
+        CountingReader,
+    },
+    cli::{
+        OutputFormat,
+        RunArgs,
+    },
+    json,
+    progress::{
+        self,
+        ProgressLine,
+    },
+    visual,
+};
+
+/// Run the program in `args.file` until it halts.
+///
+/// With `preprocess`, the directives of the [`Preprocessor`] in the file are
+/// expanded before the program is parsed, and the files of its `@data`
+/// directives are loaded into the tape.
+///
+/// With `bang_input`, the input of the program is taken from the file itself,
+/// after the first `!`. Otherwise the program reads STDIN, a key at a time
+/// with `raw` and echoing the input with `echo`.
+///
+/// When STDOUT is a terminal, the output is decoded as UTF-8 so that
+/// characters written a byte at a time show up whole.
+///
+/// The run stops with an error once the program writes more than
+/// `max_output` bytes. With `flush_every`, STDOUT is flushed after that many
+/// bytes so that the output of a long running program shows up early. With
+/// `record`, the output is also recorded to an asciicast file. With the JSON
+/// `output_format`, the output is captured and printed as part of a JSON
+/// document describing the run instead.
+///
+/// The behavior profile, tape size, cell width, end of input behavior and I/O
+/// mode given on the command line override those of the environment and of the
+/// nearest `brainfoamkit.toml`.
+///
+/// With `load_tape`, the tape is seeded with the values in a file before the
+/// program starts, and with `dump_tape`, the tape is saved to a file once the
+/// program stops, whether it halted or failed.
+///
+/// With `visual`, a view of the machine is drawn on STDERR every
+/// `visual_every` steps. With `signed`, the view and the tape dumps of the
+/// `debug` dialect show cells as signed values.
+///
+/// With `expect_output`, the run fails with an [`OutputMismatch`] if the
+/// program writes anything other than the expected output.
+///
+/// With `speed`, the program executes at most that many instructions per
+/// second.
+///
+/// With `checkpoint_every`, the state of the run is saved to
+/// `checkpoint_file` at that interval, see [`checkpoint`].
+///
+/// With `taint`, every byte the program wrote is listed on STDERR once it
+/// stops, with the input bytes that influenced it. With `stats`, the number of
+/// instructions executed, the time they took and the size of the output are
+/// printed on STDERR then too. With `progress`, they are shown on STDERR
+/// while the program runs.
+///
+/// Pressing Ctrl-C stops the run with [`Interrupted`], after flushing the
+/// output and printing the statistics of the run so far on STDERR, and with
+/// `checkpoint_every`, after saving a last checkpoint. Pressing it again
+/// kills the interpreter.
+///
+/// If the interpreter panics while the program runs, a crash bundle is saved
+/// and its path printed, see [`crash::guard`].
+pub fn execute(args: &RunArgs) -> Result<()> {
+    let config = config(args)?;
+    let source = read_source(args)?;
+    if args.raw && !io::stdin().is_terminal() {
+        bail!("Raw input needs STDIN to be a terminal");
+    }
+    if args.output_format == OutputFormat::Json {
+        return json::execute(args, &source, &config);
+    }
+
+    let expected = args
+        .expect_output
+        .as_deref()
+        .map(file_or_text)
+        .transpose()?;
+
+    let mut output: Box<dyn VMWriter> = if io::stdout().is_terminal() {
+        Box::new(io::stdout().decode_utf8())
+    } else {
+        Box::new(io::stdout())
+    };
+    if let Some(interval) = args.flush_every {
+        output = Box::new(output.flush_every(interval));
+    }
+    if let Some(path) = &args.record {
+        let file = File::create(path)
+            .with_context(|| format!("Unable to create recording {}", path.display()))?;
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        output = Box::new(output.record(Asciicast::new(BufWriter::new(file), width, height)?));
+    }
+    let cancel = cancel_on_interrupt()?;
+
+    let output = Tee {
+        inner:    output,
+        captured: expected.is_some().then(Vec::new),
+    };
+
+    let output = if let (Some(every), Some(path)) = (args.checkpoint_every, &args.checkpoint_file) {
+        let (mut machine, progress) = build(
+            Program::parse(&source, args.dialect),
+            CountingReader::new(TerminalReader::new().raw(args.raw).echo(args.echo)),
+            output,
+            args,
+            &config,
+        )?;
+        let checkpointing = Checkpointing {
+            path,
+            every,
+            source: &source,
+            dialect: args.dialect,
+            config: &config,
+            cancel: &cancel,
+        };
+        let (started, usage) = (Instant::now(), ResourceUsage::current());
+        let result = crash::guard(&mut machine, &source, args.dialect, &config, |machine| {
+            checkpointing.run(machine)
+        });
+        finish(machine, progress, args, started.elapsed(), usage, result)?
+    } else if args.bang_input {
+        let (source, input) = Program::split_input(&source);
+        run_program(
+            source,
+            VecReader::new(input.to_vec()),
+            output,
+            args,
+            &config,
+            &cancel,
+        )?
+    } else {
+        run_program(
+            &source,
+            TerminalReader::new().raw(args.raw).echo(args.echo),
+            output,
+            args,
+            &config,
+            &cancel,
+        )?
+    };
+
+    match (expected, output.captured) {
+        (Some(expected), Some(actual)) if expected != actual => {
+            Err(OutputMismatch { expected, actual }.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Run `source` to completion, saving a crash bundle if the interpreter
+/// panics.
+fn run_program<R: VMReader, W: VMWriter>(
+    source: &str,
+    input: R,
+    output: W,
+    args: &RunArgs,
+    config: &Config,
+    cancel: &CancellationToken,
+) -> Result<W> {
+    let program = Program::parse(source, args.dialect);
+    let (mut machine, progress) = build(program, input, output, args, config)?;
+    let (started, usage) = (Instant::now(), ResourceUsage::current());
+    let result = crash::guard(
+        &mut machine,
+        source,
+        args.dialect,
+        config,
+        |machine| match args.visual {
+            Some(_) => visual::run(machine, args.visual_every.max(1), cell_view(args), cancel),
+            None if args.explain => run_explained(machine, cancel),
+            None => run_until_interrupted(machine, cancel),
+        },
+    );
+    finish(machine, progress, args, started.elapsed(), usage, result)
+}
+
+/// Build the machine for `program` with the configuration and the flags of
+/// the run, and seed its tape.
+///
+/// With `--progress`, the progress spinner of the machine is handed back with
+/// it, so that it can be cleared once the run stops.
+pub fn build<R: VMReader, W: VMWriter>(
+    program: Program,
+    input: R,
+    output: W,
+    args: &RunArgs,
+    config: &Config,
+) -> Result<(VirtualMachine<R, W>, Option<ProgressBar>)> {
+    let builder = VirtualMachine::builder()
+        .input_device(input)
+        .output_device(output)
+        .program(program)
+        .limits(limits(args))
+        .taint_tracking(args.taint)
+        .canary_cells(args.canary_cells)
+        .history_capacity(crash::TRACE_LENGTH)
+        .diagnostics_sink(args.warnings);
+    let builder = if args.signed {
+        builder.debug_hook(|dump| eprintln!("{}", dump.with_view(CellView::Signed)))
+    } else {
+        builder
+    };
+    let progress = args.progress.then(ProgressLine::new);
+    let bar = progress.as_ref().map(ProgressLine::bar);
+    let builder = match progress {
+        Some(progress) => builder.progress_reporter(progress::INTERVAL, progress),
+        None => builder,
+    };
+    let builder = match args.seed {
+        Some(seed) => builder.random_seed(seed),
+        None => builder,
+    };
+    let mut machine = config.apply(builder)?.build().context(InvalidProgram)?;
+    if let Some(speed) = args.speed {
+        machine.set_speed(speed);
+    }
+    load_tape(&mut machine, args)?;
+    Ok((machine, bar))
+}
+
+/// Finish the output of `machine`, clear its `progress` spinner and dump its
+/// tape once the run stopped with `result` after `elapsed`, having started
+/// when the process had used `usage`, and hand back its output device.
+fn finish<R: VMReader, W: VMWriter>(
+    mut machine: VirtualMachine<R, W>,
+    progress: Option<ProgressBar>,
+    args: &RunArgs,
+    elapsed: Duration,
+    usage: Option<ResourceUsage>,
+    result: Result<()>,
+) -> Result<W> {
+    machine.output_device().finish()?;
+    if let Some(progress) = progress {
+        progress.finish_and_clear();
+    }
+    dump_tape(&machine, args)?;
+    if let Some(taint) = machine.taint() {
+        report_taint(taint);
+    }
+    let interrupted = matches!(&result, Err(error) if error.is::<Interrupted>());
+    if args.stats || interrupted {
+        report_stats(&machine, elapsed, usage);
+    }
+    result?;
+    let (_, _, devices, _) = machine.into_parts();
+    Ok(devices.output)
+}
+
+/// A token cancelled when Ctrl-C is pressed. Pressing it a second time exits
+/// the process right away, in case the program is stuck waiting for input.
+pub fn cancel_on_interrupt() -> Result<CancellationToken> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    flag::register_conditional_shutdown(SIGINT, 130, Arc::clone(&interrupted))
+        .context("Unable to handle Ctrl-C")?;
+    flag::register(SIGINT, Arc::clone(&interrupted)).context("Unable to handle Ctrl-C")?;
+    Ok(CancellationToken::from(interrupted))
+}
+
+/// Run `machine` until it halts, or stop with [`Interrupted`] once `cancel`
+/// is cancelled.
+pub fn run_until_interrupted<R: VMReader, W: VMWriter>(
+    machine: &mut VirtualMachine<R, W>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    match machine.run_with_cancel(cancel)? {
+        StopReason::Cancelled => Err(Interrupted.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Print the statistics of the run of `machine` on STDERR.
+fn report_stats<R: VMReader, W: VMWriter>(
+    machine: &VirtualMachine<R, W>,
+    elapsed: Duration,
+    usage: Option<ResourceUsage>,
+) {
+    eprintln!(
+        "Executed {} instructions in {} ({})",
+        human_count(machine.steps()),
+        human_duration(elapsed),
+        human_rate(machine.steps(), elapsed)
+    );
+    eprintln!(
+        "Wrote {} of output with a tape of {} cells",
+        human_bytes(machine.bytes_written()),
+        human_count(machine.length())
+    );
+    if let Some(usage) = run_usage(usage) {
+        eprintln!("Used {usage}");
+    }
+}
+
+/// The resources used since the process had used `start`, if the operating
+/// system tells.
+pub fn run_usage(start: Option<ResourceUsage>) -> Option<ResourceUsage> {
+    let (start, now) = start.zip(ResourceUsage::current())?;
+    Some(now.since(&start))
+}
+
+/// List every byte written with the input bytes that influenced it on STDERR.
+fn report_taint(taint: &TaintTracker) {
+    eprintln!(
+        "Taint of {} output bytes from {} input bytes:",
+        taint.outputs().len(),
+        taint.inputs_read()
+    );
+    for output in taint.outputs() {
+        let sources = if output.sources.is_empty() {
+            String::from("no input")
+        } else {
+            let positions: Vec<String> = output.sources.iter().map(ToString::to_string).collect();
+            format!("input {}", positions.join(", "))
+        };
+        eprintln!(
+            "{:>6}: 0x{:02x} '{}' <- {sources}",
+            output.position,
+            output.value,
+            output.value.escape_ascii()
+        );
+    }
+}
+
+/// Run `machine` until it halts, describing every instruction on STDERR
+/// before it is executed. Comments are executed silently. The output of the
+/// program is flushed before every description, so that both show up in the
+/// order they happen. The run stops with [`Interrupted`] once `cancel` is
+/// cancelled.
+fn run_explained<R: VMReader, W: VMWriter>(
+    machine: &mut VirtualMachine<R, W>,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    while let Some(instruction) = machine.get_instruction() {
+        if cancel.is_cancelled() {
+            return Err(Interrupted.into());
+        }
+        if instruction == Instruction::NoOp {
+            machine.execute_instruction()?;
+            continue;
+        }
+        machine.output_device().flush()?;
+        eprintln!(
+            "{:>6}: {}",
+            machine.program_counter(),
+            explain::explain_step(instruction, machine)
+        );
+        machine.execute_instruction()?;
+    }
+    Ok(())
+}
+
+/// The bytes given with a flag such as `--expect-output`: the contents of the
+/// file at `file_or_string`, or the text itself if there is no such file.
+pub fn file_or_text(file_or_string: &str) -> Result<Vec<u8>> {
+    let path = Path::new(file_or_string);
+    if path.is_file() {
+        fs::read(path).with_context(|| format!("Unable to read {}", path.display()))
+    } else {
+        Ok(file_or_string.as_bytes().to_vec())
+    }
+}
+
+/// A writer that keeps a copy of everything written to it, if asked to.
+struct Tee<W: VMWriter> {
+    inner:    W,
+    captured: Option<Vec<u8>>,
+}
+
+impl<W: VMWriter> VMWriter for Tee<W> {
+    fn write(&mut self, value: u8) -> io::Result<()> {
+        if let Some(captured) = &mut self.captured {
+            captured.push(value);
+        }
+        self.inner.write(value)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.finish()
+    }
+}
+
+/// The program cannot be run, for example because its brackets are not
+/// balanced.
+#[derive(Debug)]
+pub struct InvalidProgram;
+
+impl Display for InvalidProgram {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Invalid program")
+    }
+}
+
+/// The run was stopped with Ctrl-C before the program halted.
+#[derive(Debug)]
+pub struct Interrupted;
+
+impl Display for Interrupted {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Interrupted")
+    }
+}
+
+impl Error for Interrupted {}
+
+/// The program wrote something other than the output given with
+/// `--expect-output`.
+#[derive(Debug)]
+pub struct OutputMismatch {
+    expected: Vec<u8>,
+    actual:   Vec<u8>,
+}
+
+impl Display for OutputMismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "The output differs from the expected output")?;
+        writeln!(f, "--- expected")?;
+        writeln!(f, "+++ actual")?;
+        write!(
+            f,
+            "{}",
+            test_runner::diff(
+                &String::from_utf8_lossy(&self.expected),
+                &String::from_utf8_lossy(&self.actual),
+            )
+            .trim_end()
+        )
+    }
+}
+
+impl Error for OutputMismatch {}
+
+/// The configuration of the run: the command line flags of `args` merged
+/// over the environment and the nearest `brainfoamkit.toml`.
+pub fn config(args: &RunArgs) -> Result<Config> {
+    Ok(Config::resolve()?.merge(Config {
+        profile: args.profile,
+        tape_size: args.tape_size,
+        cell_width: args.cell_width,
+        eof: args.eof,
+        io_mode: args.io_mode,
+        ..Config::default()
+    }))
+}
+
+/// The resource limits given on the command line.
+pub fn limits(args: &RunArgs) -> Limits {
+    let mut limits = Limits::new();
+    if let Some(bytes) = args.max_output {
+        limits = limits.max_output_bytes(bytes);
+    }
+    limits
+}
+
+/// Seed the tape of `machine` with the file given with `--load-tape`, if any,
+/// and then with the data files of the program of the project and, with
+/// `--preprocess`, those of its `@data` directives.
+pub fn load_tape<R: VMReader, W: VMWriter>(
+    machine: &mut VirtualMachine<R, W>,
+    args: &RunArgs,
+) -> Result<()> {
+    if let Some(path) = &args.load_tape {
+        machine.load_tape_from(path, args.tape_format)?;
+    }
+    let data = args
+        .data
+        .iter()
+        .map(ProjectData::read)
+        .collect::<Result<Vec<_>, _>>()?;
+    machine.load_data(&data)?;
+    if args.preprocess {
+        let source = Preprocessor::new().process_file(&args.file)?;
+        machine.load_data(source.data_segments())?;
+    }
+    Ok(())
+}
+
+/// Save the tape of `machine` to the file given with `--dump-tape`, if any.
+pub fn dump_tape<R: VMReader, W: VMWriter>(
+    machine: &VirtualMachine<R, W>,
+    args: &RunArgs,
+) -> Result<()> {
+    match &args.dump_tape {
+        Some(path) => Ok(machine.dump_tape_to(path, args.tape_format)?),
+        None => Ok(()),
+    }
+}
+
+/// How cells are shown, as given with `--signed`.
+pub fn cell_view(args: &RunArgs) -> CellView {
+    if args.signed {
+        CellView::Signed
+    } else {
+        CellView::Unsigned
+    }
+}
+
+/// Read the program in `args.file`, expanding the directives of the
+/// preprocessor with `--preprocess`.
+pub fn read_source(args: &RunArgs) -> Result<String> {
+    if args.preprocess {
+        let source = Preprocessor::new().process_file(&args.file)?;
+        return Ok(source.source().to_owned());
+    }
+    fs::read_to_string(&args.file)
+        .with_context(|| format!("Unable to read program from {}", args.file.display()))
+}
@@ -0,0 +1,188 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+    thread,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    config::Config,
+    net::{
+        self,
+        TapeBroadcast,
+        TcpReader,
+        TcpWriter,
+    },
+    Instruction,
+    Program,
+    VMWriter,
+    VirtualMachine,
+};
+
+use crate::{
+    cli::ServeArgs,
+    run::InvalidProgram,
+};
+
+/// Host the program in `args.file` on `args.listen`.
+///
+/// Every connection runs the program from the start on a fresh machine, on a
+/// thread of its own: `,` reads the bytes the peer sends and `.` sends the
+/// output back. The connection is closed once the program halts. A program
+/// that fails only ends its own connection, and the failure is reported on
+/// STDERR.
+///
+/// With `shared`, the program runs once instead, see [`serve_shared`].
+///
+/// The behavior profile, tape size, cell width, end of input behavior and I/O
+/// mode are taken from the environment and the nearest `brainfoamkit.toml`.
+pub fn execute(args: &ServeArgs) -> Result<()> {
+    let source = fs::read_to_string(&args.file)
+        .with_context(|| format!("Unable to read program from {}", args.file.display()))?;
+    let program = Program::parse(&source, args.dialect);
+    program.jump_table().context(InvalidProgram)?;
+    let config = Config::resolve()?;
+
+    let listener = TcpListener::bind(args.listen)
+        .with_context(|| format!("Unable to listen on {}", args.listen))?;
+    eprintln!(
+        "Serving {} on {}",
+        args.file.display(),
+        listener.local_addr().unwrap_or(args.listen)
+    );
+    if args.shared {
+        return serve_shared(&listener, program, &config, args.update_every.max(1));
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("Unable to accept a connection: {error}");
+                continue;
+            }
+        };
+        let program = program.clone();
+        let config = config.clone();
+        thread::spawn(move || {
+            let peer = peer_name(&stream);
+            eprintln!("{peer}: connected");
+            match serve(stream, program, &config) {
+                Ok(steps) => eprintln!("{peer}: halted after {steps} steps"),
+                Err(error) => eprintln!("{peer}: {error:#}"),
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Run `program` for the peer on the other end of `stream`.
+///
+/// # Returns
+///
+/// The number of steps the program took to halt.
+fn serve(stream: TcpStream, program: Program, config: &Config) -> Result<usize> {
+    let (input, output) = net::split(stream)?;
+    let builder = VirtualMachine::builder()
+        .input_device(input)
+        .output_device(output)
+        .program(program);
+    let mut machine = config.apply(builder)?.build().context(InvalidProgram)?;
+
+    let result = machine.run();
+    machine.output_device().finish()?;
+    result?;
+    Ok(machine.steps())
+}
+
+/// Run `program` once, for everyone who connects to `listener`.
+///
+/// The first connection drives the program, as in the default mode. Every
+/// later connection is a watcher: it receives a snapshot of the tape, and
+/// then the cells that changed as a line of JSON after every `update_every`
+/// steps, whenever the program waits for the input of the driver and once it
+/// halts. See [`TapeBroadcast`] for the messages. The server stops once the
+/// program halts.
+fn serve_shared(
+    listener: &TcpListener,
+    program: Program,
+    config: &Config,
+    update_every: usize,
+) -> Result<()> {
+    let (driver, _) = listener.accept().context("Unable to accept the driver")?;
+    let peer = peer_name(&driver);
+    eprintln!("{peer}: connected, driving the program");
+    let (input, output) = net::split(driver)?;
+    let builder = VirtualMachine::builder()
+        .input_device(input)
+        .output_device(output)
+        .program(program);
+    let mut machine = config.apply(builder)?.build().context(InvalidProgram)?;
+
+    let broadcast = TapeBroadcast::new();
+    broadcast.publish(&machine);
+    let watchers = listener.try_clone()?;
+    {
+        let broadcast = broadcast.clone();
+        thread::spawn(move || {
+            for stream in watchers.incoming().flatten() {
+                eprintln!("{}: connected, watching", peer_name(&stream));
+                broadcast.add_observer(stream);
+            }
+        });
+    }
+
+    let result = run_shared(&mut machine, &broadcast, update_every);
+    broadcast.publish(&machine);
+    machine.output_device().finish()?;
+    result?;
+    eprintln!(
+        "{peer}: halted after {} steps, watched by {}",
+        machine.steps(),
+        broadcast.observers()
+    );
+    Ok(())
+}
+
+/// Run `machine` until it halts, publishing its state after every
+/// `update_every` steps and before every `,`, so that the watchers see what
+/// the program is waiting for.
+fn run_shared(
+    machine: &mut VirtualMachine<TcpReader, TcpWriter>,
+    broadcast: &TapeBroadcast,
+    update_every: usize,
+) -> Result<()> {
+    let mut since_update = 0;
+    while let Some(instruction) = machine.get_instruction() {
+        if instruction == Instruction::InputValue {
+            broadcast.publish(machine);
+            since_update = 0;
+        }
+        machine.execute_instruction()?;
+        since_update += 1;
+        if since_update >= update_every {
+            broadcast.publish(machine);
+            since_update = 0;
+        }
+    }
+    Ok(())
+}
+
+/// The address of the peer on the other end of `stream`, for the log.
+fn peer_name(stream: &TcpStream) -> String {
+    stream
+        .peer_addr()
+        .map_or_else(|_| "unknown peer".to_string(), |peer| peer.to_string())
+}
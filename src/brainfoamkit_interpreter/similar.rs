@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    analysis,
+    Program,
+};
+
+/// The extensions of program files
+const PROGRAM_EXTENSIONS: [&str; 2] = ["bf", "b"];
+
+/// Compare every program in `directory` and its subdirectories with every
+/// other one and print the groups of programs that are at least `threshold`
+/// alike, with the similarity of every such pair.
+///
+/// Programs end up in the same group when they are linked by a chain of
+/// similar pairs.
+pub fn report(directory: &Path, threshold: f64) -> Result<()> {
+    let mut paths = Vec::new();
+    collect(directory, &mut paths)?;
+    paths.sort();
+    let programs = paths
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path)
+                .map(|source| Program::from(source.as_str()))
+                .with_context(|| format!("Unable to read program from {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Every program starts in a group of its own, and similar pairs merge
+    // their groups
+    let mut groups: Vec<usize> = (0..programs.len()).collect();
+    let mut pairs = Vec::new();
+    for a in 0..programs.len() {
+        for b in a + 1..programs.len() {
+            let similarity = analysis::similarity(&programs[a], &programs[b]);
+            if similarity >= threshold {
+                pairs.push((a, b, similarity));
+                let (group_a, group_b) = (root(&groups, a), root(&groups, b));
+                groups[group_a.max(group_b)] = group_a.min(group_b);
+            }
+        }
+    }
+
+    let name = |index: usize| {
+        paths[index]
+            .strip_prefix(directory)
+            .unwrap_or(&paths[index])
+            .display()
+            .to_string()
+    };
+    let mut clusters = 0;
+    for group in 0..programs.len() {
+        let members: Vec<usize> = (0..programs.len())
+            .filter(|&index| root(&groups, index) == group)
+            .collect();
+        if members.len() < 2 {
+            continue;
+        }
+        clusters += 1;
+        println!("Group {clusters}:");
+        for &member in &members {
+            println!("  {}", name(member));
+        }
+        for &(a, b, similarity) in &pairs {
+            if members.contains(&a) {
+                println!("    {similarity:.2} {} ~ {}", name(a), name(b));
+            }
+        }
+    }
+    println!(
+        "{} programs, {clusters} groups of near-duplicates",
+        programs.len()
+    );
+    Ok(())
+}
+
+/// The group `index` belongs to
+fn root(groups: &[usize], mut index: usize) -> usize {
+    while groups[index] != index {
+        index = groups[index];
+    }
+    index
+}
+
+/// Add the program files in `directory` and its subdirectories to `paths`.
+fn collect(directory: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("Unable to read directory {}", directory.display()))?;
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("Unable to read directory {}", directory.display()))?
+            .path();
+        if path.is_dir() {
+            collect(&path, paths)?;
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| PROGRAM_EXTENSIONS.contains(&extension))
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::Path,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    symbolic::{
+        self,
+        Bounds,
+    },
+    Program,
+};
+
+/// Explore the program in `file` symbolically and print every path through
+/// it, following at most `max_paths` paths for at most `max_steps`
+/// instructions each.
+pub fn report(file: &Path, max_paths: usize, max_steps: usize) -> Result<()> {
+    let source = fs::read_to_string(file)
+        .with_context(|| format!("Unable to read program from {}", file.display()))?;
+    let program = Program::from(source.as_str());
+    let exploration = symbolic::explore(
+        &program,
+        Bounds {
+            max_paths,
+            max_steps,
+        },
+    )?;
+
+    for (number, path) in exploration.paths.iter().enumerate() {
+        println!("Path {} ({}):", number + 1, path.end);
+        if path.constraints.is_empty() {
+            println!("  constraints: none");
+        } else {
+            let constraints: Vec<String> =
+                path.constraints.iter().map(ToString::to_string).collect();
+            println!("  constraints: {}", constraints.join(" && "));
+        }
+        println!("  input:       {}", escape(&path.example_input()));
+        let outputs: Vec<String> = path.outputs.iter().map(ToString::to_string).collect();
+        println!("  outputs:     [{}]", outputs.join(", "));
+        println!("  output:      {}", escape(&path.example_output()));
+    }
+    if !exploration.complete {
+        println!(
+            "Stopped after {} paths, there may be more (see --max-paths)",
+            exploration.paths.len()
+        );
+    }
+    Ok(())
+}
+
+/// `bytes` as a quoted string with anything unprintable escaped.
+fn escape(bytes: &[u8]) -> String {
+    format!("\"{}\"", bytes.escape_ascii())
+}
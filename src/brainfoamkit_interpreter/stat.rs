@@ -0,0 +1,25 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::Path,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use brainfoamkit_lib::Program;
+
+/// Print the statistics of the program in `file`.
+pub fn report(file: &Path) -> Result<()> {
+    let source = fs::read_to_string(file)
+        .with_context(|| format!("Unable to read program from {}", file.display()))?;
+    let statistics = Program::from(source.as_str()).statistics();
+
+    print!("{statistics}");
+    Ok(())
+}
@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::path::Path;
+
+use anyhow::{
+    anyhow,
+    Result,
+};
+use brainfoamkit_lib::{
+    test_runner::{
+        self,
+        Report,
+    },
+    Limits,
+};
+
+use crate::project;
+
+/// Run the tests in `directory` and print a report, in the Test Anything
+/// Protocol with `tap`. Without a directory, the tests of the project in the
+/// nearest `bfk.toml` are run.
+///
+/// With `chaos`, faults chosen by the seed are injected into the input and
+/// output of every test. A test stopped by a fault passes, a test whose output
+/// changes without an error fails.
+///
+/// Returns an error if a test failed, so that the process exits with a
+/// non-zero status.
+pub fn execute(directory: Option<&Path>, tap: bool, chaos: Option<u64>) -> Result<()> {
+    let limits = Limits::new().max_steps(test_runner::MAX_STEPS);
+    let report = match (directory, chaos) {
+        (Some(directory), Some(seed)) => test_runner::run_dir_with_chaos(directory, limits, seed)?,
+        (Some(directory), None) => test_runner::run_dir_with_limits(directory, limits)?,
+        (None, Some(seed)) => Report {
+            results: project::manifest()?
+                .test_cases()
+                .into_iter()
+                .map(|case| {
+                    let outcome = case.run_with_chaos(limits, seed);
+                    (case, outcome)
+                })
+                .collect(),
+        },
+        (None, None) => project::manifest()?.run_tests(limits),
+    };
+    if tap {
+        print!("{}", report.tap());
+    } else {
+        print!("{report}");
+    }
+
+    if report.is_success() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{} of {} tests failed",
+            report.failed(),
+            report.results.len()
+        ))
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::io::{
+    self,
+    IsTerminal,
+    Stderr,
+    Write,
+};
+
+use anyhow::Result;
+use brainfoamkit_lib::{
+    CancellationToken,
+    CellView,
+    VMReader,
+    VMWriter,
+    VirtualMachine,
+};
+use crossterm::{
+    cursor::MoveToColumn,
+    queue,
+    style::{
+        Print,
+        Stylize,
+    },
+    terminal::{
+        Clear,
+        ClearType,
+    },
+};
+
+use crate::run::Interrupted;
+
+/// The number of cells on either side of the memory pointer in the view
+const WINDOW_RADIUS: usize = 8;
+
+/// Run `machine` until it halts, drawing a view of it on STDERR after every
+/// `interval` steps and once it stops.
+///
+/// The view is a single line with the number of executed steps, the program
+/// counter, the memory pointer and the cells around it, the current cell
+/// highlighted and its value shown in `view`. On a terminal, every view
+/// replaces the previous one, so the line shows the machine as it runs.
+/// Otherwise every view is printed on its own line, which keeps logs readable.
+///
+/// The output of the program is flushed before every view, so that it shows
+/// up in the order it was written.
+///
+/// The run stops with [`Interrupted`] once `cancel` is cancelled.
+pub fn run<R: VMReader, W: VMWriter>(
+    machine: &mut VirtualMachine<R, W>,
+    interval: usize,
+    cell_view: CellView,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let mut view = View {
+        stderr: io::stderr(),
+        terminal: io::stderr().is_terminal(),
+        cell_view,
+    };
+
+    let result = loop {
+        if machine.is_halted() {
+            break Ok(());
+        }
+        if cancel.is_cancelled() {
+            break Err(Interrupted.into());
+        }
+        if let Err(error) = machine.execute_instruction() {
+            break Err(error.into());
+        }
+        if machine.steps() % interval == 0 {
+            machine.output_device().flush()?;
+            view.draw(machine)?;
+        }
+    };
+
+    machine.output_device().flush()?;
+    view.draw(machine)?;
+    if view.terminal {
+        writeln!(view.stderr)?;
+    }
+    result
+}
+
+struct View {
+    stderr:    Stderr,
+    terminal:  bool,
+    cell_view: CellView,
+}
+
+impl View {
+    fn draw<R: VMReader, W: VMWriter>(&mut self, machine: &VirtualMachine<R, W>) -> Result<()> {
+        let tape = machine.tape();
+        let pointer = machine.memory_pointer();
+        let start = pointer.saturating_sub(WINDOW_RADIUS);
+        let end = pointer.saturating_add(WINDOW_RADIUS + 1).min(tape.len());
+
+        if self.terminal {
+            queue!(self.stderr, MoveToColumn(0), Clear(ClearType::CurrentLine))?;
+        }
+        queue!(
+            self.stderr,
+            Print(format!(
+                "step {:>10}  pc {:>6}  ptr {:>6} | {start}:",
+                machine.steps(),
+                machine.program_counter(),
+                pointer,
+            ))
+        )?;
+        for (index, cell) in tape.slice(start..end).iter().enumerate() {
+            let value = self.cell_view.value(cell);
+            if start + index != pointer {
+                queue!(self.stderr, Print(format!(" {value:>4}")))?;
+            } else if self.terminal {
+                queue!(
+                    self.stderr,
+                    Print(" "),
+                    Print(format!("{value:>4}").reverse())
+                )?;
+            } else {
+                queue!(self.stderr, Print(format!(" [{value}]")))?;
+            }
+        }
+        if !self.terminal {
+            queue!(self.stderr, Print("\n"))?;
+        }
+        self.stderr.flush()?;
+        Ok(())
+    }
+}
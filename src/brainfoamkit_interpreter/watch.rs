@@ -0,0 +1,185 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    io::{
+        self,
+        Read,
+        Write,
+    },
+    mem,
+    path::Path,
+    sync::mpsc::{
+        self,
+        Receiver,
+        RecvTimeoutError,
+    },
+    time::Duration,
+};
+
+use anyhow::{
+    anyhow,
+    Context,
+    Result,
+};
+use brainfoamkit_lib::{
+    CaptureWriter,
+    Program,
+    VecReader,
+    VirtualMachine,
+};
+use notify::{
+    Event,
+    RecursiveMode,
+    Watcher,
+};
+
+use crate::{
+    cli::RunArgs,
+    run,
+};
+
+/// How long to wait for more changes after the file changed, since editors
+/// often save a file in several steps.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Run the program in `args.file` every time the file changes.
+///
+/// The first run prints the output of the program. Every later run prints the
+/// lines of the output that changed since the previous run, removed lines
+/// prefixed with `-` and added lines with `+`. Errors are reported on STDERR
+/// and do not stop watching.
+///
+/// STDIN is read to the end once, before the first run, and every run reads
+/// the same input.
+pub fn execute(args: &RunArgs) -> Result<()> {
+    let mut stdin = Vec::new();
+    if !args.bang_input {
+        io::stdin()
+            .read_to_end(&mut stdin)
+            .context("Unable to read STDIN")?;
+    }
+
+    let path = args
+        .file
+        .canonicalize()
+        .with_context(|| format!("Unable to find {}", args.file.display()))?;
+    let directory = path
+        .parent()
+        .ok_or_else(|| anyhow!("{} has no parent directory", path.display()))?;
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    // Editors often replace the file instead of writing to it, which only
+    // shows up as an event of its directory
+    watcher.watch(directory, RecursiveMode::NonRecursive)?;
+
+    let mut previous: Option<String> = None;
+    loop {
+        let (output, result) = run_once(args, &stdin);
+        let output = String::from_utf8_lossy(&output).into_owned();
+        match &previous {
+            None if output.is_empty() || output.ends_with('\n') => print!("{output}"),
+            None => println!("{output}"),
+            Some(previous) if *previous == output => eprintln!("Output unchanged"),
+            Some(previous) => print_changes(previous, &output),
+        }
+        if let Err(error) = result {
+            eprintln!("Error: {error:#}");
+        }
+        previous = Some(output);
+        io::stdout().flush()?;
+
+        eprintln!("Watching {} for changes...", args.file.display());
+        wait_for_change(&events, &path)?;
+    }
+}
+
+/// Run the current version of the program, capturing its output.
+///
+/// Returns the output written before the program halted or failed, and
+/// whether it failed.
+fn run_once(args: &RunArgs, stdin: &[u8]) -> (Vec<u8>, Result<()>) {
+    let mut machine = match build(args, stdin) {
+        Ok(machine) => machine,
+        Err(error) => return (Vec::new(), Err(error)),
+    };
+    let result = machine.run().map(|_| ()).map_err(anyhow::Error::from);
+    (mem::take(&mut machine.output_device().data), result)
+}
+
+fn build(args: &RunArgs, stdin: &[u8]) -> Result<VirtualMachine<VecReader, CaptureWriter>> {
+    let config = run::config(args)?;
+    let source = run::read_source(args)?;
+    let (program, input) = if args.bang_input {
+        Program::parse_with_input(&source, args.dialect)
+    } else {
+        (Program::parse(&source, args.dialect), stdin.to_vec())
+    };
+
+    let builder = VirtualMachine::builder()
+        .input_device(VecReader::new(input))
+        .output_device(CaptureWriter::default())
+        .program(program)
+        .limits(run::limits(args));
+    Ok(config.apply(builder)?.build()?)
+}
+
+/// Block until the file at `path` changes, then wait for the changes to
+/// settle.
+fn wait_for_change(events: &Receiver<notify::Result<Event>>, path: &Path) -> Result<()> {
+    loop {
+        let event = events.recv()??;
+        let changed = event
+            .paths
+            .iter()
+            .any(|changed| changed.file_name() == path.file_name());
+        if changed && (event.kind.is_create() || event.kind.is_modify()) {
+            break;
+        }
+    }
+    loop {
+        match events.recv_timeout(DEBOUNCE) {
+            Ok(event) => {
+                event?;
+            }
+            Err(RecvTimeoutError::Timeout) => return Ok(()),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(anyhow!("The file watcher stopped unexpectedly"))
+            }
+        }
+    }
+}
+
+/// Print the lines that differ between two outputs.
+///
+/// The lines both outputs start and end with are skipped. The rest of the
+/// previous output is printed as removed and the rest of the current output as
+/// added.
+fn print_changes(previous: &str, current: &str) {
+    let previous: Vec<&str> = previous.lines().collect();
+    let current: Vec<&str> = current.lines().collect();
+
+    let prefix = previous
+        .iter()
+        .zip(&current)
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(previous, current)| previous == current)
+        .count();
+
+    if prefix > 0 {
+        eprintln!("@@ line {} @@", prefix + 1);
+    }
+    for line in &previous[prefix..previous.len() - suffix] {
+        println!("-{line}");
+    }
+    for line in &current[prefix..current.len() - suffix] {
+        println!("+{line}");
+    }
+}
@@ -0,0 +1,1063 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Static analysis of `Program`s
+//!
+//! The functions in this module inspect a [`Program`] without
+//! running it. [`to_dot`] draws its control flow graph, [`lint`] looks for
+//! the mistakes people new to Brainfuck tend to make, [`backward_slice`]
+//! finds the instructions that can influence a cell, and [`similarity`]
+//! compares two programs.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    fmt::{
+        self,
+        Display,
+        Formatter,
+        Write,
+    },
+    ops::RangeInclusive,
+};
+
+use crate::{
+    Instruction,
+    Program,
+};
+
+/// The longest instruction sequence shown in a basic block label before it is
+/// shortened
+const MAX_LABEL_LENGTH: usize = 32;
+
+/// A node of the control flow graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Entry,
+    Exit,
+    /// A run of instructions without any loop brackets
+    Block {
+        start: usize,
+        end:   usize,
+        code:  String,
+    },
+    /// A `[` that checks the current cell before entering the loop body
+    LoopHeader(usize),
+    /// A `]` that checks the current cell before repeating the loop body
+    LoopEnd(usize),
+}
+
+impl Node {
+    fn attributes(&self) -> String {
+        match self {
+            Self::Entry => String::from("label=\"entry\", shape=oval"),
+            Self::Exit => String::from("label=\"exit\", shape=oval"),
+            Self::Block { start, end, code } => {
+                let code = if code.chars().count() > MAX_LABEL_LENGTH {
+                    let shortened: String = code.chars().take(MAX_LABEL_LENGTH - 3).collect();
+                    format!("{shortened}...")
+                } else {
+                    code.clone()
+                };
+                if start == end {
+                    format!("label=\"{start}: {code}\"")
+                } else {
+                    format!("label=\"{start}-{end}: {code}\"")
+                }
+            }
+            Self::LoopHeader(index) => format!("label=\"[ at {index}\", shape=diamond"),
+            Self::LoopEnd(index) => format!("label=\"] at {index}\", shape=diamond"),
+        }
+    }
+}
+
+/// Split a program into the nodes of its control flow graph, in program order
+fn nodes(program: &Program) -> Vec<Node> {
+    let mut nodes = vec![Node::Entry];
+    let mut block: Option<(usize, usize, String)> = None;
+
+    let length = program.length().unwrap_or(0);
+    for index in 0..length {
+        let Some(instruction) = program.get_instruction(index) else {
+            continue;
+        };
+        match instruction {
+            Instruction::JumpForward | Instruction::JumpBackward => {
+                if let Some((start, end, code)) = block.take() {
+                    nodes.push(Node::Block { start, end, code });
+                }
+                nodes.push(if instruction == Instruction::JumpForward {
+                    Node::LoopHeader(index)
+                } else {
+                    Node::LoopEnd(index)
+                });
+            }
+            Instruction::NoOp => {}
+            _ => {
+                let character = instruction.to_char().unwrap_or_default();
+                match block.as_mut() {
+                    Some((_, end, code)) => {
+                        *end = index;
+                        code.push(character);
+                    }
+                    None => block = Some((index, index, character.to_string())),
+                }
+            }
+        }
+    }
+    if let Some((start, end, code)) = block {
+        nodes.push(Node::Block { start, end, code });
+    }
+    nodes.push(Node::Exit);
+
+    nodes
+}
+
+/// Render the control flow graph of a `Program` in the GraphViz DOT language
+///
+/// Every run of instructions without loop brackets becomes a basic block.
+/// Every `[` and `]` becomes a diamond-shaped node with two outgoing edges:
+/// one taken when the current cell is zero and one taken when it is not.
+/// Comments are ignored. If the brackets of the program are not balanced, the
+/// unmatched brackets only get an edge to the following node.
+///
+/// The output can be rendered with `dot -Tsvg`.
+///
+/// # Arguments
+///
+/// * `program` - The program to analyze
+///
+/// # Returns
+///
+/// A `String` containing a `digraph` in the DOT language
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     analysis,
+///     Program,
+/// };
+///
+/// let dot = analysis::to_dot(&Program::from("++[->+<]"));
+///
+/// assert!(dot.starts_with("digraph program {"));
+/// assert!(dot.contains("n2 [label=\"[ at 2\", shape=diamond];"));
+/// assert!(dot.contains("n2 -> n5 [label=\"zero\"];"));
+/// assert!(dot.contains("n4 -> n3 [label=\"nonzero\"];"));
+/// ```
+#[must_use]
+pub fn to_dot(program: &Program) -> String {
+    let nodes = nodes(program);
+    let jump_table = program.jump_table().unwrap_or_default();
+    let node_at = |index: usize| {
+        nodes.iter().position(|node| {
+            matches!(node, Node::LoopHeader(position) | Node::LoopEnd(position) if *position == index)
+        })
+    };
+
+    let mut dot = String::from("digraph program {\n");
+    dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+    for (id, node) in nodes.iter().enumerate() {
+        let _ = writeln!(dot, "    n{id} [{}];", node.attributes());
+    }
+
+    for (id, node) in nodes.iter().enumerate() {
+        let matching = match node {
+            Node::LoopHeader(index) | Node::LoopEnd(index) => {
+                jump_table.get(*index).copied().flatten().and_then(node_at)
+            }
+            _ => None,
+        };
+        match (node, matching) {
+            (Node::Exit, _) => {}
+            (Node::LoopHeader(_), Some(end)) => {
+                let _ = writeln!(dot, "    n{id} -> n{} [label=\"nonzero\"];", id + 1);
+                let _ = writeln!(dot, "    n{id} -> n{} [label=\"zero\"];", end + 1);
+            }
+            (Node::LoopEnd(_), Some(header)) => {
+                let _ = writeln!(dot, "    n{id} -> n{} [label=\"nonzero\"];", header + 1);
+                let _ = writeln!(dot, "    n{id} -> n{} [label=\"zero\"];", id + 1);
+            }
+            _ => {
+                let _ = writeln!(dot, "    n{id} -> n{};", id + 1);
+            }
+        }
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+/// A kind of mistake found by [`lint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// A `[` without a matching `]`, or a `]` without a matching `[`
+    UnmatchedBracket,
+    /// A loop that can never end once it is entered
+    EndlessLoop,
+    /// The pointer moves left of the first cell
+    PointerUnderflow,
+}
+
+impl Lint {
+    /// Whether the program cannot run at all because of this mistake
+    #[must_use]
+    pub const fn is_error(self) -> bool {
+        matches!(self, Self::UnmatchedBracket)
+    }
+
+    /// A short name for the lint, such as `endless-loop`
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::UnmatchedBracket => "unmatched-bracket",
+            Self::EndlessLoop => "endless-loop",
+            Self::PointerUnderflow => "pointer-underflow",
+        }
+    }
+
+    /// A longer description of the mistake and how to fix it
+    #[must_use]
+    pub const fn explanation(self) -> &'static str {
+        match self {
+            Self::UnmatchedBracket => {
+                "Every `[` starts a loop that a `]` later in the program has to close, and every \
+                 `]` has to close a loop an earlier `[` started. When the cell under the pointer \
+                 is 0, `[` jumps past its `]`, and otherwise `]` jumps back to its `[`, so a \
+                 program with a bracket that has no partner cannot run. Count the brackets of the \
+                 loop that is marked: usually a `]` is missing at the end of a loop body, or one \
+                 was typed twice."
+            }
+            Self::EndlessLoop => {
+                "A loop repeats until the cell under the pointer is 0 when its `]` is reached. The \
+                 body of this loop leaves the pointer where it started and adds as much to that \
+                 cell as it subtracts, so the cell never changes, and once the loop is entered it \
+                 never ends. Most loops count the cell they test down with `-`, such as `[->+<]`, \
+                 which moves the value of a cell into its neighbor."
+            }
+            Self::PointerUnderflow => {
+                "The pointer starts on the first cell of the tape, and `<` moves it one cell to \
+                 the left. Moving left of the first cell wraps around to the last cell on a \
+                 classic tape, but it stops the program on a tape that does not wrap, such as with \
+                 the `strict` and `modern` profiles. Start by moving right with `>` to make room \
+                 for cells on the left, or check that every `<` follows a `>`."
+            }
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// A mistake found by [`lint`], with the instructions it concerns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The kind of mistake
+    pub lint:    Lint,
+    /// The positions of the instructions in the program, which are also the
+    /// positions of the characters in its source
+    pub span:    RangeInclusive<usize>,
+    /// A description of this particular mistake
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Show the diagnostic together with the line of the source it points at
+    ///
+    /// The instructions of the span are marked with carets. A span across
+    /// several lines is marked up to the end of its first line.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source the program was parsed from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     analysis,
+    ///     Program,
+    /// };
+    ///
+    /// let source = "+++\n>[>+<";
+    /// let diagnostics = analysis::lint(&Program::from(source));
+    ///
+    /// assert_eq!(
+    ///     diagnostics[0].render(source),
+    ///     "error[unmatched-bracket]: This '[' is never closed by a matching \
+    ///      ']'\n  --> 2:2\n   |\n 2 | >[>+<\n   |  ^\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        let start = *self.span.start();
+        let mut line_number = 1;
+        let mut line_start = 0;
+        for (index, character) in source.chars().enumerate().take(start) {
+            if character == '\n' {
+                line_number += 1;
+                line_start = index + 1;
+            }
+        }
+        let line: String = source
+            .chars()
+            .skip(line_start)
+            .take_while(|character| *character != '\n')
+            .collect();
+        let column = start - line_start;
+        let width = (self.span.end() - start + 1).min(line.chars().count() - column);
+
+        let severity = if self.lint.is_error() {
+            "error"
+        } else {
+            "warning"
+        };
+        let gutter = " ".repeat(line_number.to_string().len());
+        format!(
+            "{severity}[{}]: {}\n{gutter} --> {line_number}:{}\n{gutter}  |\n {line_number} | \
+             {line}\n{gutter}  | {}{}\n",
+            self.lint,
+            self.message,
+            column + 1,
+            " ".repeat(column),
+            "^".repeat(width)
+        )
+    }
+}
+
+/// Look for common beginner mistakes in a `Program`
+///
+/// * Brackets without a partner, which keep the program from running.
+/// * Loops whose body never changes the cell the loop tests, so that they run
+///   forever once they are entered. Only loops without nested loops, input or
+///   procedure calls are checked.
+/// * `<` that may move the pointer left of the first cell. The position of the
+///   pointer is followed from the start of the program until it reaches a loop
+///   that does not return the pointer to where the loop started.
+///
+/// # Arguments
+///
+/// * `program` - The program to check
+///
+/// # Returns
+///
+/// The mistakes found, in the order of the instructions they point at
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     analysis::{
+///         self,
+///         Lint,
+///     },
+///     Program,
+/// };
+///
+/// let diagnostics = analysis::lint(&Program::from("+[>+<]<"));
+/// let lints: Vec<Lint> = diagnostics
+///     .iter()
+///     .map(|diagnostic| diagnostic.lint)
+///     .collect();
+///
+/// assert_eq!(lints, [Lint::EndlessLoop, Lint::PointerUnderflow]);
+/// assert_eq!(diagnostics[0].span, 1..=5);
+/// assert!(analysis::lint(&Program::from("+[->+<]>")).is_empty());
+/// ```
+#[must_use]
+pub fn lint(program: &Program) -> Vec<Diagnostic> {
+    let instructions: Vec<Instruction> = (0..program.length().unwrap_or(0))
+        .filter_map(|index| program.get_instruction(index))
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    let mut open = Vec::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction {
+            Instruction::JumpForward => open.push(index),
+            Instruction::JumpBackward => match open.pop() {
+                Some(start) => {
+                    if never_ends(&instructions[start + 1..index]) {
+                        diagnostics.push(Diagnostic {
+                            lint:    Lint::EndlessLoop,
+                            span:    start..=index,
+                            message: String::from(
+                                "This loop never ends once it is entered, because its body never \
+                                 changes the cell it tests",
+                            ),
+                        });
+                    }
+                }
+                None => diagnostics.push(Diagnostic {
+                    lint:    Lint::UnmatchedBracket,
+                    span:    index..=index,
+                    message: String::from("This ']' has no matching '['"),
+                }),
+            },
+            _ => {}
+        }
+    }
+    diagnostics.extend(open.into_iter().map(|index| Diagnostic {
+        lint:    Lint::UnmatchedBracket,
+        span:    index..=index,
+        message: String::from("This '[' is never closed by a matching ']'"),
+    }));
+    if let Some(index) = pointer_underflow(&instructions) {
+        diagnostics.push(Diagnostic {
+            lint:    Lint::PointerUnderflow,
+            span:    index..=index,
+            message: String::from(
+                "The pointer may move left of the first cell here, which stops the program on a \
+                 tape that does not wrap around",
+            ),
+        });
+    }
+
+    diagnostics.sort_by_key(|diagnostic| *diagnostic.span.start());
+    diagnostics
+}
+
+/// Whether a loop body without nested loops leaves the cell the loop tests
+/// unchanged, so that the loop repeats forever once it is entered
+fn never_ends(body: &[Instruction]) -> bool {
+    let mut offset: isize = 0;
+    let mut change: u8 = 0;
+    for instruction in body {
+        match instruction {
+            Instruction::IncrementPointer => offset += 1,
+            Instruction::DecrementPointer => offset -= 1,
+            Instruction::IncrementValue if offset == 0 => change = change.wrapping_add(1),
+            Instruction::DecrementValue if offset == 0 => change = change.wrapping_sub(1),
+            Instruction::InputValue
+            | Instruction::JumpForward
+            | Instruction::JumpBackward
+            | Instruction::DefineProcedure
+            | Instruction::EndProcedure
+            | Instruction::CallProcedure
+            | Instruction::Halt => return false,
+            _ => {}
+        }
+    }
+    offset == 0 && change == 0
+}
+
+/// The position of the first `<` that may move the pointer left of the first
+/// cell, as far as the pointer can be followed
+fn pointer_underflow(instructions: &[Instruction]) -> Option<usize> {
+    let mut offset: usize = 0;
+    let mut loop_starts = Vec::new();
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction {
+            Instruction::IncrementPointer => offset += 1,
+            Instruction::DecrementPointer => match offset.checked_sub(1) {
+                Some(previous) => offset = previous,
+                None => return Some(index),
+            },
+            Instruction::JumpForward => loop_starts.push(offset),
+            // The body runs again with the pointer somewhere else, which is
+            // not followed
+            Instruction::JumpBackward if loop_starts.pop() != Some(offset) => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The instructions that can influence the final value of a cell
+///
+/// The slice holds every `+`, `-` and `,` that can change the cell, or
+/// another cell whose value flows into it through a loop, along with the
+/// brackets of every loop that holds such an instruction, since the cell
+/// tested by the loop decides how often the instruction runs. Every `@` is
+/// part of the slice too, as stopping the program early changes the final
+/// values.
+///
+/// The position of the memory pointer is followed through the program as
+/// long as every loop leaves it where it found it, as if the tape neither
+/// wraps around nor grows. Where the position is known, pointer moves only
+/// decide which cell the other instructions work on and are left out of the
+/// slice. Where it is not, as after a loop like `[>]`, an instruction may
+/// work on any cell: it is part of the slice, and since the cell it works on
+/// depends on the values of earlier cells, so are every pointer move and
+/// everything before it that can change a value.
+///
+/// The slice is conservative: it may hold instructions that cannot influence
+/// the cell in any run, but it holds every instruction that can. Programs
+/// with unbalanced brackets or procedures are not analyzed, and their slice
+/// holds every instruction that is not a comment.
+///
+/// # Arguments
+///
+/// * `program` - The program to analyze
+/// * `cell` - The index of the cell whose final value is of interest
+///
+/// # Returns
+///
+/// The positions of the instructions in the slice, in ascending order
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     analysis,
+///     Program,
+/// };
+///
+/// // Cell 1 receives three times cell 0, while cell 2 is never touched by
+/// // the loop
+/// let program = Program::from("++[>+++<-]>>+");
+///
+/// assert_eq!(
+///     analysis::backward_slice(&program, 1),
+///     [0, 1, 2, 4, 5, 6, 8, 9]
+/// );
+/// assert_eq!(analysis::backward_slice(&program, 2), [12]);
+/// ```
+#[must_use]
+pub fn backward_slice(program: &Program, cell: usize) -> Vec<usize> {
+    let instructions: Vec<Instruction> = (0..program.length().unwrap_or(0))
+        .filter_map(|index| program.get_instruction(index))
+        .collect();
+    let everything = || {
+        instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| **instruction != Instruction::NoOp)
+            .map(|(index, _)| index)
+            .collect()
+    };
+
+    let has_procedures = instructions.iter().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::DefineProcedure | Instruction::EndProcedure | Instruction::CallProcedure
+        )
+    });
+    let Some(slicer) = Slicer::new(&instructions).filter(|_| !has_procedures) else {
+        return everything();
+    };
+    slicer.slice(cell)
+}
+
+/// The cells whose values can flow into the cell being sliced on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Relevant {
+    cells: BTreeSet<usize>,
+    /// Every cell is relevant
+    all:   bool,
+}
+
+impl Relevant {
+    /// Whether an instruction working on the cell at `position`, or on any
+    /// cell if the position is unknown, can change a relevant cell
+    fn contains(&self, position: Option<usize>) -> bool {
+        self.all
+            || position.map_or(!self.cells.is_empty(), |position| {
+                self.cells.contains(&position)
+            })
+    }
+
+    fn insert(&mut self, position: Option<usize>) {
+        match position {
+            Some(position) => {
+                self.cells.insert(position);
+            }
+            None => self.all = true,
+        }
+    }
+
+    /// The cell at `position` is overwritten, so its earlier value no longer
+    /// matters. Nothing is known to be overwritten at an unknown position.
+    fn overwrite(&mut self, position: Option<usize>) {
+        if let Some(position) = position {
+            self.cells.remove(&position);
+        }
+    }
+
+    fn extend(&mut self, other: &Self) {
+        self.cells.extend(&other.cells);
+        self.all |= other.all;
+    }
+}
+
+/// Computes backward slices of a program with balanced brackets
+struct Slicer<'a> {
+    instructions: &'a [Instruction],
+    /// The position of the memory pointer when each instruction runs, if it
+    /// is known
+    positions:    Vec<Option<usize>>,
+    /// The matching bracket of every bracket
+    matches:      Vec<usize>,
+    included:     Vec<bool>,
+}
+
+impl<'a> Slicer<'a> {
+    /// Follow the memory pointer through the program, or return `None` if its
+    /// brackets are not balanced
+    fn new(instructions: &'a [Instruction]) -> Option<Self> {
+        let mut positions = vec![None; instructions.len()];
+        let mut matches = vec![0; instructions.len()];
+        let mut open = Vec::new();
+        let mut position: Option<usize> = Some(0);
+        for (index, instruction) in instructions.iter().enumerate() {
+            positions[index] = position;
+            match instruction {
+                Instruction::IncrementPointer => position = position.map(|position| position + 1),
+                Instruction::DecrementPointer => {
+                    position = position.and_then(|position| position.checked_sub(1));
+                }
+                Instruction::JumpForward => open.push((index, position)),
+                Instruction::JumpBackward => {
+                    let (start, entry) = open.pop()?;
+                    matches[start] = index;
+                    matches[index] = start;
+                    // Every iteration of the loop starts somewhere else
+                    if position != entry {
+                        positions[start..=index].fill(None);
+                        position = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !open.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            instructions,
+            positions,
+            matches,
+            included: vec![false; instructions.len()],
+        })
+    }
+
+    fn slice(mut self, cell: usize) -> Vec<usize> {
+        let mut relevant = Relevant::default();
+        relevant.insert(Some(cell));
+        self.slice_block(0, self.instructions.len(), &mut relevant);
+
+        let unknown_included = self
+            .included
+            .iter()
+            .zip(&self.positions)
+            .any(|(included, position)| *included && position.is_none());
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            match instruction {
+                Instruction::IncrementPointer | Instruction::DecrementPointer => {
+                    self.included[index] |= unknown_included;
+                }
+                Instruction::Halt => self.included[index] = true,
+                _ => {}
+            }
+        }
+
+        self.included
+            .iter()
+            .enumerate()
+            .filter(|(_, included)| **included)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Walk the instructions from `start` up to `end` backwards, adding those
+    /// that change a relevant cell to the slice and updating the relevant
+    /// cells to those that matter before `start`
+    fn slice_block(&mut self, start: usize, end: usize, relevant: &mut Relevant) {
+        let mut index = end;
+        while index > start {
+            index -= 1;
+            let position = self.positions[index];
+            match self.instructions[index] {
+                Instruction::JumpBackward => {
+                    let loop_start = self.matches[index];
+                    self.slice_loop(loop_start, index, relevant);
+                    index = loop_start;
+                }
+                // The new value depends on the old one, so the cell stays
+                // relevant
+                Instruction::IncrementValue | Instruction::DecrementValue
+                    if relevant.contains(position) =>
+                {
+                    self.include(index, relevant);
+                }
+                Instruction::InputValue if relevant.contains(position) => {
+                    self.include(index, relevant);
+                    relevant.overwrite(position);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Add the instruction at `index` to the slice. If the cell it works on is
+    /// not known, which cell that is depends on the values of earlier cells,
+    /// so every cell becomes relevant.
+    fn include(&mut self, index: usize, relevant: &mut Relevant) {
+        self.included[index] = true;
+        if self.positions[index].is_none() {
+            relevant.insert(None);
+        }
+    }
+
+    /// Slice the loop between the brackets at `start` and `end` until the
+    /// relevant cells settle, since each iteration can carry values into the
+    /// next one
+    fn slice_loop(&mut self, start: usize, end: usize, relevant: &mut Relevant) {
+        loop {
+            // The loop may not run at all
+            let after = relevant.clone();
+            self.slice_block(start + 1, end, relevant);
+            if relevant.all || self.included[start + 1..end].contains(&true) {
+                self.included[start] = true;
+                self.included[end] = true;
+                relevant.insert(self.positions[start]);
+            }
+            relevant.extend(&after);
+            if *relevant == after {
+                return;
+            }
+        }
+    }
+}
+
+/// How alike two `Program`s are, from 0 for nothing in common to 1 for the
+/// same code
+///
+/// The score is the average of two comparisons, so that programs that were
+/// copied and then touched up still score high:
+///
+/// * The instruction histograms: how often each of the eight instructions
+///   appears, relative to the length of the code. The histograms are compared
+///   by their overlap, one minus half the sum of the differences.
+/// * The loop structure: every loop is hashed by its body, with runs of the
+///   same instruction collapsed, so that `[->+++<]` and `[->++<]` hash the
+///   same. The loops of the two programs are compared as multisets, by the size
+///   of their intersection relative to their union.
+///
+/// Comments take no part in the comparison. Two programs without any code,
+/// or without any loops, are alike in that respect.
+///
+/// # Arguments
+///
+/// * `a` - The first program
+/// * `b` - The second program
+///
+/// # Returns
+///
+/// The similarity, between 0 and 1
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     analysis,
+///     Program,
+/// };
+///
+/// let original = Program::from("++++++++[>++++++++<-]>+.");
+/// let renamed = Program::from("8 times 8 ++++++++[>++++++++<-] plus 1 >+.");
+/// let tweaked = Program::from("+++++++[>+++++++++<-]>++.");
+/// let other = Program::from(",[.,]");
+///
+/// assert!(
+///     (analysis::similarity(&original, &renamed) - 1.0).abs() < f64::EPSILON
+/// );
+/// assert!(analysis::similarity(&original, &tweaked) > 0.9);
+/// assert!(analysis::similarity(&original, &other) < 0.5);
+/// ```
+#[must_use]
+pub fn similarity(a: &Program, b: &Program) -> f64 {
+    let histograms = histogram_overlap(&histogram(a), &histogram(b));
+    let loops = multiset_overlap(&loop_hashes(a), &loop_hashes(b));
+    (histograms + loops) / 2.0
+}
+
+/// The share of the code of `program` taken up by each of the eight
+/// instructions
+#[allow(clippy::cast_precision_loss)]
+fn histogram(program: &Program) -> Option<Vec<f64>> {
+    let statistics = program.statistics();
+    let counts = Instruction::all().map(|instruction| statistics.count(instruction));
+    let total: usize = counts.iter().sum();
+    (total > 0).then(|| {
+        counts
+            .iter()
+            .map(|count| *count as f64 / total as f64)
+            .collect()
+    })
+}
+
+fn histogram_overlap(a: &Option<Vec<f64>>, b: &Option<Vec<f64>>) -> f64 {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let distance: f64 = a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum();
+            1.0 - distance / 2.0
+        }
+        (None, None) => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// The number of loops of `program` with each hash of their body
+fn loop_hashes(program: &Program) -> BTreeMap<u64, usize> {
+    let code: Vec<Instruction> = (0..program.length().unwrap_or(0))
+        .filter_map(|index| program.get_instruction(index))
+        .filter(|instruction| *instruction != Instruction::NoOp)
+        .collect();
+
+    let mut hashes = BTreeMap::new();
+    let mut open = Vec::new();
+    for (index, instruction) in code.iter().enumerate() {
+        match instruction {
+            Instruction::JumpForward => open.push(index),
+            Instruction::JumpBackward => {
+                let Some(start) = open.pop() else {
+                    continue;
+                };
+                let mut body = code[start..=index].to_vec();
+                body.dedup();
+                *hashes.entry(Program::from(body).fingerprint()).or_insert(0) += 1;
+            }
+            _ => {}
+        }
+    }
+    hashes
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn multiset_overlap(a: &BTreeMap<u64, usize>, b: &BTreeMap<u64, usize>) -> f64 {
+    let keys: BTreeSet<&u64> = a.keys().chain(b.keys()).collect();
+    let (mut intersection, mut union) = (0, 0);
+    for key in keys {
+        let (a, b) = (
+            a.get(key).copied().unwrap_or(0),
+            b.get(key).copied().unwrap_or(0),
+        );
+        intersection += a.min(b);
+        union += a.max(b);
+    }
+    if union == 0 {
+        1.0
+    } else {
+        intersection as f64 / union as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_straight_line_program() {
+        let dot = to_dot(&Program::from("+> comment <-"));
+        let expected = r#"digraph program {
+    node [shape=box, fontname="monospace"];
+    n0 [label="entry", shape=oval];
+    n1 [label="0-12: +><-"];
+    n2 [label="exit", shape=oval];
+    n0 -> n1;
+    n1 -> n2;
+}
+"#;
+        assert_eq!(dot, expected);
+    }
+
+    #[test]
+    fn test_loop() {
+        let dot = to_dot(&Program::from("+[-]."));
+        let expected_edges = [
+            "n0 -> n1;",
+            "n1 -> n2;",
+            "n2 -> n3 [label=\"nonzero\"];",
+            "n2 -> n5 [label=\"zero\"];",
+            "n3 -> n4;",
+            "n4 -> n3 [label=\"nonzero\"];",
+            "n4 -> n5 [label=\"zero\"];",
+            "n5 -> n6;",
+        ];
+        for edge in expected_edges {
+            assert!(dot.contains(edge), "missing edge {edge} in\n{dot}");
+        }
+        assert!(dot.contains("n1 [label=\"0: +\"];"));
+        assert!(dot.contains("n4 [label=\"] at 3\", shape=diamond];"));
+    }
+
+    #[test]
+    fn test_empty_loop_body() {
+        let dot = to_dot(&Program::from("[]"));
+        assert!(dot.contains("n1 -> n2 [label=\"nonzero\"];"));
+        assert!(dot.contains("n1 -> n3 [label=\"zero\"];"));
+        assert!(dot.contains("n2 -> n2 [label=\"nonzero\"];"));
+        assert!(dot.contains("n2 -> n3 [label=\"zero\"];"));
+    }
+
+    #[test]
+    fn test_unbalanced_program() {
+        let dot = to_dot(&Program::from("[+"));
+        assert!(dot.contains("n1 -> n2;"));
+        assert!(!dot.contains("zero"));
+    }
+
+    #[test]
+    fn test_long_block_label() {
+        let dot = to_dot(&Program::from("+".repeat(40).as_str()));
+        let expected = format!("n1 [label=\"0-39: {}...\"];", "+".repeat(29));
+        assert!(dot.contains(&expected));
+    }
+
+    #[test]
+    fn test_empty_program() {
+        let dot = to_dot(&Program::from(""));
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n1 [label=\"exit\", shape=oval];"));
+    }
+
+    #[test]
+    fn test_unmatched_brackets() {
+        let diagnostics = lint(&Program::from("]+[[-]"));
+        let spans: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.lint, diagnostic.span.clone()))
+            .collect();
+
+        assert_eq!(
+            spans,
+            [
+                (Lint::UnmatchedBracket, 0..=0),
+                (Lint::UnmatchedBracket, 2..=2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_endless_loops() {
+        for source in ["+[]", "+[.]", "+[>+<]", "+[-+]", ">+[<>]"] {
+            let diagnostics = lint(&Program::from(source));
+            assert_eq!(diagnostics.len(), 1, "{source}");
+            assert_eq!(diagnostics[0].lint, Lint::EndlessLoop, "{source}");
+        }
+        for source in ["+[-]", "+[>]", "+[,]", "+[[-]]", "+[->+<]", "+[--]"] {
+            assert!(lint(&Program::from(source)).is_empty(), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_pointer_underflow() {
+        let diagnostics = lint(&Program::from(">+[<->-]<<"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].lint, Lint::PointerUnderflow);
+        assert_eq!(diagnostics[0].span, 9..=9);
+
+        // The pointer cannot be followed past a loop that moves it
+        assert!(lint(&Program::from("+[>]<<")).is_empty());
+        assert!(lint(&Program::from("><>")).is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let source = "++\n+[\n\n  .  ]";
+        let diagnostics = lint(&Program::from(source));
+        assert_eq!(diagnostics[0].span, 4..=12);
+
+        let rendered = diagnostics[0].render(source);
+        assert!(rendered.starts_with("warning[endless-loop]: "));
+        assert!(rendered.ends_with(" 2 | +[\n   |  ^\n"));
+    }
+
+    #[test]
+    fn test_explanations() {
+        for lint in [
+            Lint::UnmatchedBracket,
+            Lint::EndlessLoop,
+            Lint::PointerUnderflow,
+        ] {
+            assert!(!lint.explanation().is_empty());
+            assert_eq!(lint.to_string(), lint.code());
+        }
+    }
+
+    #[test]
+    fn test_backward_slice_straight_line() {
+        let program = Program::from("+>++>,<-.");
+
+        assert_eq!(backward_slice(&program, 0), [0]);
+        assert_eq!(backward_slice(&program, 1), [2, 3, 7]);
+        assert_eq!(backward_slice(&program, 2), [5]);
+        assert!(backward_slice(&program, 3).is_empty());
+    }
+
+    #[test]
+    fn test_backward_slice_input_overwrites() {
+        let program = Program::from("+++,");
+
+        assert_eq!(backward_slice(&program, 0), [3]);
+    }
+
+    #[test]
+    fn test_backward_slice_loops() {
+        // Cell 2 is copied from cell 1, which counts down from the value of
+        // cell 0
+        let program = Program::from("+++[>++<-]>[>+<-]");
+
+        assert_eq!(
+            backward_slice(&program, 2),
+            [0, 1, 2, 3, 5, 6, 8, 9, 11, 13, 15, 16]
+        );
+        assert_eq!(backward_slice(&program, 0), [0, 1, 2, 3, 8, 9]);
+    }
+
+    #[test]
+    fn test_backward_slice_unknown_pointer() {
+        // After the scan, the `+` may work on any cell
+        let program = Program::from("+>+<[>]+");
+
+        assert_eq!(backward_slice(&program, 5), [0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_backward_slice_unanalyzable_programs() {
+        assert_eq!(backward_slice(&Program::from("+[>"), 9), [0, 1, 2]);
+        assert_eq!(
+            backward_slice(&Program::parse("(+):", crate::Dialect::Pbrain), 9),
+            [0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_similarity_identical() {
+        let program = Program::from("+[>,.<-]");
+
+        assert!((similarity(&program, &program) - 1.0).abs() < f64::EPSILON);
+        assert!(
+            (similarity(&Program::from(""), &Program::from("text")) - 1.0).abs() < f64::EPSILON
+        );
+    }
+
+    #[test]
+    fn test_similarity_symmetric() {
+        let a = Program::from("++[>+<-]>[>+<-]");
+        let b = Program::from("+++[>++<-].");
+
+        assert!((similarity(&a, &b) - similarity(&b, &a)).abs() < f64::EPSILON);
+        assert!(similarity(&a, &b) > 0.5);
+        assert!(similarity(&a, &Program::from("")) < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_loop_hashes() {
+        // Runs are collapsed, and nested loops count on their own
+        let hashes = loop_hashes(&Program::from("[->+++<][->+<]+[[-]>]"));
+
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(hashes.values().copied().max(), Some(2));
+    }
+}
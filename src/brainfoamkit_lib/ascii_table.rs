@@ -3,7 +3,13 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+
+use prettytable::{
+    format::consts::FORMAT_NO_LINESEP_WITH_TITLE,
+    row,
+    Table,
+};
 
 use crate::{
     AsciiChar,
@@ -12,11 +18,15 @@ use crate::{
 
 /// Represents a table of ASCII characters.
 ///
-/// The table is implemented as a [`HashMap`](https://doc.rust-lang.org/std/collections/struct.HashMap.html)
-/// of [`Byte`](struct.Byte.html) values to [`AsciiChar`](struct.AsciiChar.html)
+/// The table is implemented as a [`BTreeMap`](https://doc.rust-lang.org/std/collections/struct.BTreeMap.html)
+/// of byte values to [`AsciiChar`](struct.AsciiChar.html)
 ///
 /// It maps the valid ASCII [`Byte`](struct.Byte.html) values to their
-/// corresponding [`AsciiChar`](struct.AsciiChar.html) values.
+/// corresponding [`AsciiChar`](struct.AsciiChar.html) values. The characters
+/// are always listed in the order of their values, so
+/// [`iter()`](#method.iter) and
+/// [`to_display_table()`](#method.to_display_table) give the same output on
+/// every run.
 ///
 /// # Examples
 ///
@@ -48,7 +58,7 @@ use crate::{
 /// * [ASCII Table](https://www.asciitable.com/)
 /// * [ASCII Table and Description](https://www.cs.cmu.edu/~pattis/15-1XX/common/handouts/ascii.html)
 pub struct AsciiTable {
-    table: HashMap<Byte, AsciiChar>,
+    table: BTreeMap<u8, AsciiChar>,
 }
 
 impl AsciiTable {
@@ -81,520 +91,430 @@ impl AsciiTable {
     #[must_use]
     #[allow(clippy::too_many_lines)]
     pub fn new() -> Self {
-        let mut table = HashMap::new();
+        let mut table = BTreeMap::new();
 
         {
             table.insert(
-                Byte::from(0),
+                0,
                 AsciiChar::new(Byte::from(0), "CNUL", "Null character", "\\000"),
             );
 
             table.insert(
-                Byte::from(1),
+                1,
                 AsciiChar::new(Byte::from(1), "CSOH", "Start of heading", "\\001"),
             );
             table.insert(
-                Byte::from(2),
+                2,
                 AsciiChar::new(Byte::from(2), "CSTX", "Start of text", "\\002"),
             );
             table.insert(
-                Byte::from(3),
+                3,
                 AsciiChar::new(Byte::from(3), "CETX", "End of text", "\\003"),
             );
             table.insert(
-                Byte::from(4),
+                4,
                 AsciiChar::new(Byte::from(4), "CEOT", "End of transmission", "\\004"),
             );
+            table.insert(5, AsciiChar::new(Byte::from(5), "CENQ", "Enquiry", "\\005"));
             table.insert(
-                Byte::from(5),
-                AsciiChar::new(Byte::from(5), "CENQ", "Enquiry", "\\005"),
-            );
-            table.insert(
-                Byte::from(6),
+                6,
                 AsciiChar::new(Byte::from(6), "CACK", "Acknowledge", "\\006"),
             );
+            table.insert(7, AsciiChar::new(Byte::from(7), "CBEL", "Bell", "\\007"));
             table.insert(
-                Byte::from(7),
-                AsciiChar::new(Byte::from(7), "CBEL", "Bell", "\\007"),
-            );
-            table.insert(
-                Byte::from(8),
+                8,
                 AsciiChar::new(Byte::from(8), "CBS", "Backspace", "\\008"),
             );
             table.insert(
-                Byte::from(9),
+                9,
                 AsciiChar::new(Byte::from(9), "CTAB", "Horizontal tab", "\\009"),
             );
             table.insert(
-                Byte::from(10),
+                10,
                 AsciiChar::new(Byte::from(10), "CLF", "Line feed", "\\010"),
             );
             table.insert(
-                Byte::from(11),
+                11,
                 AsciiChar::new(Byte::from(11), "CVT", "Vertical tab", "\\011"),
             );
             table.insert(
-                Byte::from(12),
+                12,
                 AsciiChar::new(Byte::from(12), "CFF", "Form feed", "\\012"),
             );
             table.insert(
-                Byte::from(13),
+                13,
                 AsciiChar::new(Byte::from(13), "CCR", "Carriage return", "\\013"),
             );
             table.insert(
-                Byte::from(14),
+                14,
                 AsciiChar::new(Byte::from(14), "CSO", "Shift out", "\\014"),
             );
             table.insert(
-                Byte::from(15),
+                15,
                 AsciiChar::new(Byte::from(15), "CSI", "Shift in", "\\015"),
             );
             table.insert(
-                Byte::from(16),
+                16,
                 AsciiChar::new(Byte::from(16), "CDLE", "Data link escape", "\\016"),
             );
             table.insert(
-                Byte::from(17),
+                17,
                 AsciiChar::new(Byte::from(17), "CDC1", "Device control 1", "\\017"),
             );
             table.insert(
-                Byte::from(18),
+                18,
                 AsciiChar::new(Byte::from(18), "CDC2", "Device control 2", "\\018"),
             );
             table.insert(
-                Byte::from(19),
+                19,
                 AsciiChar::new(Byte::from(19), "CDC3", "Device control 3", "\\019"),
             );
             table.insert(
-                Byte::from(20),
+                20,
                 AsciiChar::new(Byte::from(20), "CDC4", "Device control 4", "\\020"),
             );
             table.insert(
-                Byte::from(21),
+                21,
                 AsciiChar::new(Byte::from(21), "CNAK", "Negative acknowledge", "\\021"),
             );
             table.insert(
-                Byte::from(22),
+                22,
                 AsciiChar::new(Byte::from(22), "CSYN", "Synchronous idle", "\\022"),
             );
             table.insert(
-                Byte::from(23),
+                23,
                 AsciiChar::new(Byte::from(23), "CETB", "End of transmission block", "\\023"),
             );
             table.insert(
-                Byte::from(24),
+                24,
                 AsciiChar::new(Byte::from(24), "CCAN", "Cancel", "\\024"),
             );
             table.insert(
-                Byte::from(25),
+                25,
                 AsciiChar::new(Byte::from(25), "CEM", "End of medium", "\\025"),
             );
             table.insert(
-                Byte::from(26),
+                26,
                 AsciiChar::new(Byte::from(26), "CSUB", "Substitute", "\\026"),
             );
             table.insert(
-                Byte::from(27),
+                27,
                 AsciiChar::new(Byte::from(27), "CESC", "Escape", "\\027"),
             );
             table.insert(
-                Byte::from(28),
+                28,
                 AsciiChar::new(Byte::from(28), "CFS", "File separator", "\\028"),
             );
             table.insert(
-                Byte::from(29),
+                29,
                 AsciiChar::new(Byte::from(29), "CGS", "Group separator", "\\029"),
             );
             table.insert(
-                Byte::from(30),
+                30,
                 AsciiChar::new(Byte::from(30), "CRS", "Record separator", "\\030"),
             );
             table.insert(
-                Byte::from(31),
+                31,
                 AsciiChar::new(Byte::from(31), "CUS", "Unit separator", "\\031"),
             );
+            table.insert(32, AsciiChar::new(Byte::from(32), "WSP", "Space", " "));
             table.insert(
-                Byte::from(32),
-                AsciiChar::new(Byte::from(32), "WSP", "Space", " "),
-            );
-            table.insert(
-                Byte::from(33),
+                33,
                 AsciiChar::new(Byte::from(33), "SBANG", "Exclamation mark", "!"),
             );
             table.insert(
-                Byte::from(34),
+                34,
                 AsciiChar::new(Byte::from(34), "SDBLQ", "Double quote", "\""),
             );
+            table.insert(35, AsciiChar::new(Byte::from(35), "SHASH", "Hash", "#"));
             table.insert(
-                Byte::from(35),
-                AsciiChar::new(Byte::from(35), "SHASH", "Hash", "#"),
-            );
-            table.insert(
-                Byte::from(36),
+                36,
                 AsciiChar::new(Byte::from(36), "SDOLL", "Dollar sign", "$"),
             );
+            table.insert(37, AsciiChar::new(Byte::from(37), "SPERC", "Percent", "%"));
+            table.insert(38, AsciiChar::new(Byte::from(38), "SAMP", "Ampersand", "&"));
             table.insert(
-                Byte::from(37),
-                AsciiChar::new(Byte::from(37), "SPERC", "Percent", "%"),
-            );
-            table.insert(
-                Byte::from(38),
-                AsciiChar::new(Byte::from(38), "SAMP", "Ampersand", "&"),
-            );
-            table.insert(
-                Byte::from(39),
+                39,
                 AsciiChar::new(Byte::from(39), "SSQT", "Single quote", "'"),
             );
             table.insert(
-                Byte::from(40),
+                40,
                 AsciiChar::new(Byte::from(40), "SOPAR", "Open parenthesis", "("),
             );
             table.insert(
-                Byte::from(41),
+                41,
                 AsciiChar::new(Byte::from(41), "SCPAR", "Close parenthesis", ")"),
             );
-            table.insert(
-                Byte::from(42),
-                AsciiChar::new(Byte::from(42), "SSTAR", "Asterisk", "*"),
-            );
-            table.insert(
-                Byte::from(43),
-                AsciiChar::new(Byte::from(43), "SPLUS", "Plus", "+"),
-            );
-            table.insert(
-                Byte::from(44),
-                AsciiChar::new(Byte::from(44), "SCOM", "Comma", ","),
-            );
-            table.insert(
-                Byte::from(45),
-                AsciiChar::new(Byte::from(45), "SDASH", "Dash", "-"),
-            );
-            table.insert(
-                Byte::from(46),
-                AsciiChar::new(Byte::from(46), "SDOT", "Period", "."),
-            );
-            table.insert(
-                Byte::from(47),
-                AsciiChar::new(Byte::from(47), "SSLASH", "Slash", "/"),
-            );
-            table.insert(
-                Byte::from(48),
-                AsciiChar::new(Byte::from(48), "DIG0", "Zero", "0"),
-            );
-            table.insert(
-                Byte::from(49),
-                AsciiChar::new(Byte::from(49), "DIG1", "One", "1"),
-            );
-            table.insert(
-                Byte::from(50),
-                AsciiChar::new(Byte::from(50), "DIG2", "Two", "2"),
-            );
-            table.insert(
-                Byte::from(51),
-                AsciiChar::new(Byte::from(51), "DIG3", "Three", "3"),
-            );
-            table.insert(
-                Byte::from(52),
-                AsciiChar::new(Byte::from(52), "DIG4", "Four", "4"),
-            );
-            table.insert(
-                Byte::from(53),
-                AsciiChar::new(Byte::from(53), "DIG5", "Five", "5"),
-            );
-            table.insert(
-                Byte::from(54),
-                AsciiChar::new(Byte::from(54), "DIG6", "Six", "6"),
-            );
-            table.insert(
-                Byte::from(55),
-                AsciiChar::new(Byte::from(55), "DIG7", "Seven", "7"),
-            );
-            table.insert(
-                Byte::from(56),
-                AsciiChar::new(Byte::from(56), "DIG8", "Eight", "8"),
-            );
-            table.insert(
-                Byte::from(57),
-                AsciiChar::new(Byte::from(57), "DIG9", "Nine", "9"),
-            );
-            table.insert(
-                Byte::from(58),
-                AsciiChar::new(Byte::from(58), "SCOL", "Colon", ":"),
-            );
-            table.insert(
-                Byte::from(59),
-                AsciiChar::new(Byte::from(59), "SSCL", "Semicolon", ";"),
-            );
-            table.insert(
-                Byte::from(60),
-                AsciiChar::new(Byte::from(60), "SLT", "Less than", "<"),
-            );
-            table.insert(
-                Byte::from(61),
-                AsciiChar::new(Byte::from(61), "SEQ", "Equals", "="),
-            );
-            table.insert(
-                Byte::from(62),
+            table.insert(42, AsciiChar::new(Byte::from(42), "SSTAR", "Asterisk", "*"));
+            table.insert(43, AsciiChar::new(Byte::from(43), "SPLUS", "Plus", "+"));
+            table.insert(44, AsciiChar::new(Byte::from(44), "SCOM", "Comma", ","));
+            table.insert(45, AsciiChar::new(Byte::from(45), "SDASH", "Dash", "-"));
+            table.insert(46, AsciiChar::new(Byte::from(46), "SDOT", "Period", "."));
+            table.insert(47, AsciiChar::new(Byte::from(47), "SSLASH", "Slash", "/"));
+            table.insert(48, AsciiChar::new(Byte::from(48), "DIG0", "Zero", "0"));
+            table.insert(49, AsciiChar::new(Byte::from(49), "DIG1", "One", "1"));
+            table.insert(50, AsciiChar::new(Byte::from(50), "DIG2", "Two", "2"));
+            table.insert(51, AsciiChar::new(Byte::from(51), "DIG3", "Three", "3"));
+            table.insert(52, AsciiChar::new(Byte::from(52), "DIG4", "Four", "4"));
+            table.insert(53, AsciiChar::new(Byte::from(53), "DIG5", "Five", "5"));
+            table.insert(54, AsciiChar::new(Byte::from(54), "DIG6", "Six", "6"));
+            table.insert(55, AsciiChar::new(Byte::from(55), "DIG7", "Seven", "7"));
+            table.insert(56, AsciiChar::new(Byte::from(56), "DIG8", "Eight", "8"));
+            table.insert(57, AsciiChar::new(Byte::from(57), "DIG9", "Nine", "9"));
+            table.insert(58, AsciiChar::new(Byte::from(58), "SCOL", "Colon", ":"));
+            table.insert(59, AsciiChar::new(Byte::from(59), "SSCL", "Semicolon", ";"));
+            table.insert(60, AsciiChar::new(Byte::from(60), "SLT", "Less than", "<"));
+            table.insert(61, AsciiChar::new(Byte::from(61), "SEQ", "Equals", "="));
+            table.insert(
+                62,
                 AsciiChar::new(Byte::from(62), "SGT", "Greater than", ">"),
             );
             table.insert(
-                Byte::from(63),
+                63,
                 AsciiChar::new(Byte::from(63), "SQUES", "Question mark", "?"),
             );
+            table.insert(64, AsciiChar::new(Byte::from(64), "SAT", "At sign", "@"));
             table.insert(
-                Byte::from(64),
-                AsciiChar::new(Byte::from(64), "SAT", "At sign", "@"),
-            );
-            table.insert(
-                Byte::from(65),
+                65,
                 AsciiChar::new(Byte::from(65), "UCLA", "Uppercase Letter A", "A"),
             );
             table.insert(
-                Byte::from(66),
+                66,
                 AsciiChar::new(Byte::from(66), "UCLB", "Uppercase Letter B", "B"),
             );
             table.insert(
-                Byte::from(67),
+                67,
                 AsciiChar::new(Byte::from(67), "UCLC", "Uppercase Letter C", "C"),
             );
             table.insert(
-                Byte::from(68),
+                68,
                 AsciiChar::new(Byte::from(68), "UCLD", "Uppercase Letter D", "D"),
             );
             table.insert(
-                Byte::from(69),
+                69,
                 AsciiChar::new(Byte::from(69), "UCLE", "Uppercase Letter E", "E"),
             );
             table.insert(
-                Byte::from(70),
+                70,
                 AsciiChar::new(Byte::from(70), "UCLF", "Uppercase Letter F", "F"),
             );
             table.insert(
-                Byte::from(71),
+                71,
                 AsciiChar::new(Byte::from(71), "UCLG", "Uppercase Letter G", "G"),
             );
             table.insert(
-                Byte::from(72),
+                72,
                 AsciiChar::new(Byte::from(72), "UCLH", "Uppercase Letter H", "H"),
             );
             table.insert(
-                Byte::from(73),
+                73,
                 AsciiChar::new(Byte::from(73), "UCLI", "Uppercase Letter I", "I"),
             );
             table.insert(
-                Byte::from(74),
+                74,
                 AsciiChar::new(Byte::from(74), "UCLJ", "Uppercase Letter J", "J"),
             );
             table.insert(
-                Byte::from(75),
+                75,
                 AsciiChar::new(Byte::from(75), "UCLK", "Uppercase Letter K", "K"),
             );
             table.insert(
-                Byte::from(76),
+                76,
                 AsciiChar::new(Byte::from(76), "UCLL", "Uppercase Letter L", "L"),
             );
             table.insert(
-                Byte::from(77),
+                77,
                 AsciiChar::new(Byte::from(77), "UCLM", "Uppercase Letter M", "M"),
             );
             table.insert(
-                Byte::from(78),
+                78,
                 AsciiChar::new(Byte::from(78), "UCLN", "Uppercase Letter N", "N"),
             );
             table.insert(
-                Byte::from(79),
+                79,
                 AsciiChar::new(Byte::from(79), "UCLO", "Uppercase Letter O", "O"),
             );
             table.insert(
-                Byte::from(80),
+                80,
                 AsciiChar::new(Byte::from(80), "UCLP", "Uppercase Letter P", "P"),
             );
             table.insert(
-                Byte::from(81),
+                81,
                 AsciiChar::new(Byte::from(81), "UCLQ", "Uppercase Letter Q", "Q"),
             );
             table.insert(
-                Byte::from(82),
+                82,
                 AsciiChar::new(Byte::from(82), "UCLR", "Uppercase Letter R", "R"),
             );
             table.insert(
-                Byte::from(83),
+                83,
                 AsciiChar::new(Byte::from(83), "UCLS", "Uppercase Letter S", "S"),
             );
             table.insert(
-                Byte::from(84),
+                84,
                 AsciiChar::new(Byte::from(84), "UCLT", "Uppercase Letter T", "T"),
             );
             table.insert(
-                Byte::from(85),
+                85,
                 AsciiChar::new(Byte::from(85), "UCLU", "Uppercase Letter U", "U"),
             );
             table.insert(
-                Byte::from(86),
+                86,
                 AsciiChar::new(Byte::from(86), "UCLV", "Uppercase Letter V", "V"),
             );
             table.insert(
-                Byte::from(87),
+                87,
                 AsciiChar::new(Byte::from(87), "UCLW", "Uppercase Letter W", "W"),
             );
             table.insert(
-                Byte::from(88),
+                88,
                 AsciiChar::new(Byte::from(88), "UCLX", "Uppercase Letter X", "X"),
             );
             table.insert(
-                Byte::from(89),
+                89,
                 AsciiChar::new(Byte::from(89), "UCLY", "Uppercase Letter Y", "Y"),
             );
             table.insert(
-                Byte::from(90),
+                90,
                 AsciiChar::new(Byte::from(90), "UCLZ", "Uppercase Letter Z", "Z"),
             );
             table.insert(
-                Byte::from(91),
+                91,
                 AsciiChar::new(Byte::from(91), "SOSB", "Open square bracket", "["),
             );
             table.insert(
-                Byte::from(92),
+                92,
                 AsciiChar::new(Byte::from(92), "SBKS", "Backslash", "\\"),
             );
             table.insert(
-                Byte::from(93),
+                93,
                 AsciiChar::new(Byte::from(93), "SCSB", "Close square bracket", "]"),
             );
+            table.insert(94, AsciiChar::new(Byte::from(94), "SCAR", "Caret", "^"));
             table.insert(
-                Byte::from(94),
-                AsciiChar::new(Byte::from(94), "SCAR", "Caret", "^"),
-            );
-            table.insert(
-                Byte::from(95),
+                95,
                 AsciiChar::new(Byte::from(95), "SUSC", "Underscore", "_"),
             );
+            table.insert(96, AsciiChar::new(Byte::from(96), "SBTK", "Backtick", "`"));
             table.insert(
-                Byte::from(96),
-                AsciiChar::new(Byte::from(96), "SBTK", "Backtick", "`"),
-            );
-            table.insert(
-                Byte::from(97),
+                97,
                 AsciiChar::new(Byte::from(97), "LCLA", "Lowercase Letter a", "a"),
             );
             table.insert(
-                Byte::from(98),
+                98,
                 AsciiChar::new(Byte::from(98), "LCLB", "Lowercase Letter b", "b"),
             );
             table.insert(
-                Byte::from(99),
+                99,
                 AsciiChar::new(Byte::from(99), "LCLC", "Lowercase Letter c", "c"),
             );
             table.insert(
-                Byte::from(100),
+                100,
                 AsciiChar::new(Byte::from(100), "LCLD", "Lowercase Letter d", "d"),
             );
             table.insert(
-                Byte::from(101),
+                101,
                 AsciiChar::new(Byte::from(101), "LCLE", "Lowercase Letter e", "e"),
             );
             table.insert(
-                Byte::from(102),
+                102,
                 AsciiChar::new(Byte::from(102), "LCLF", "Lowercase Letter f", "f"),
             );
             table.insert(
-                Byte::from(103),
+                103,
                 AsciiChar::new(Byte::from(103), "LCLG", "Lowercase Letter g", "g"),
             );
             table.insert(
-                Byte::from(104),
+                104,
                 AsciiChar::new(Byte::from(104), "LCLH", "Lowercase Letter h", "h"),
             );
             table.insert(
-                Byte::from(105),
+                105,
                 AsciiChar::new(Byte::from(105), "LCLI", "Lowercase Letter i", "i"),
             );
             table.insert(
-                Byte::from(106),
+                106,
                 AsciiChar::new(Byte::from(106), "LCLJ", "Lowercase Letter j", "j"),
             );
             table.insert(
-                Byte::from(107),
+                107,
                 AsciiChar::new(Byte::from(107), "LCLK", "Lowercase Letter k", "k"),
             );
             table.insert(
-                Byte::from(108),
+                108,
                 AsciiChar::new(Byte::from(108), "LCLL", "Lowercase Letter l", "l"),
             );
             table.insert(
-                Byte::from(109),
+                109,
                 AsciiChar::new(Byte::from(109), "LCLM", "Lowercase Letter m", "m"),
             );
             table.insert(
-                Byte::from(110),
+                110,
                 AsciiChar::new(Byte::from(110), "LCLN", "Lowercase Letter n", "n"),
             );
             table.insert(
-                Byte::from(111),
+                111,
                 AsciiChar::new(Byte::from(111), "LCLO", "Lowercase Letter o", "o"),
             );
             table.insert(
-                Byte::from(112),
+                112,
                 AsciiChar::new(Byte::from(112), "LCLP", "Lowercase Letter p", "p"),
             );
             table.insert(
-                Byte::from(113),
+                113,
                 AsciiChar::new(Byte::from(113), "LCLQ", "Lowercase Letter q", "q"),
             );
             table.insert(
-                Byte::from(114),
+                114,
                 AsciiChar::new(Byte::from(114), "LCLR", "Lowercase Letter r", "r"),
             );
             table.insert(
-                Byte::from(115),
+                115,
                 AsciiChar::new(Byte::from(115), "LCLS", "Lowercase Letter s", "s"),
             );
             table.insert(
-                Byte::from(116),
+                116,
                 AsciiChar::new(Byte::from(116), "LCLT", "Lowercase Letter t", "t"),
             );
             table.insert(
-                Byte::from(117),
+                117,
                 AsciiChar::new(Byte::from(117), "LCLU", "Lowercase Letter u", "u"),
             );
             table.insert(
-                Byte::from(118),
+                118,
                 AsciiChar::new(Byte::from(118), "LCLV", "Lowercase Letter v", "v"),
             );
             table.insert(
-                Byte::from(119),
+                119,
                 AsciiChar::new(Byte::from(119), "LCLW", "Lowercase Letter w", "w"),
             );
             table.insert(
-                Byte::from(120),
+                120,
                 AsciiChar::new(Byte::from(120), "LCLX", "Lowercase Letter x", "x"),
             );
             table.insert(
-                Byte::from(121),
+                121,
                 AsciiChar::new(Byte::from(121), "LCLY", "Lowercase Letter y", "y"),
             );
             table.insert(
-                Byte::from(122),
+                122,
                 AsciiChar::new(Byte::from(122), "LCLZ", "Lowercase Letter z", "z"),
             );
             table.insert(
-                Byte::from(123),
+                123,
                 AsciiChar::new(Byte::from(123), "SOCB", "Open curly brace", "{"),
             );
             table.insert(
-                Byte::from(124),
+                124,
                 AsciiChar::new(Byte::from(124), "SVBAR", "Vertical bar", "|"),
             );
             table.insert(
-                Byte::from(125),
+                125,
                 AsciiChar::new(Byte::from(125), "SCCB", "Close curly brace", "}"),
             );
+            table.insert(126, AsciiChar::new(Byte::from(126), "STLD", "Tilde", "~"));
             table.insert(
-                Byte::from(126),
-                AsciiChar::new(Byte::from(126), "STLD", "Tilde", "~"),
-            );
-            table.insert(
-                Byte::from(127),
+                127,
                 AsciiChar::new(Byte::from(127), "CDEL", "Delete", "\\127"),
             );
         }
@@ -641,7 +561,104 @@ impl AsciiTable {
     /// ```
     #[must_use]
     pub fn get(&self, byte: Byte) -> Option<&AsciiChar> {
-        self.table.get(&byte)
+        self.table.get(&u8::from(&byte))
+    }
+
+    /// Iterate over the ASCII characters in the order of their values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::AsciiTable;
+    ///
+    /// let ascii_table = AsciiTable::new();
+    /// let codes: Vec<String> = ascii_table
+    ///     .iter()
+    ///     .take(3)
+    ///     .map(|ascii_char| ascii_char.character_code())
+    ///     .collect();
+    ///
+    /// assert_eq!(codes, ["CNUL", "CSOH", "CSTX"]);
+    /// assert_eq!(ascii_table.iter().count(), 128);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &AsciiChar> {
+        self.table.values()
+    }
+
+    /// The table for printing to a terminal, with a row for every character
+    /// giving its byte, binary, hexadecimal and textual forms.
+    ///
+    /// The rows are in the order of the values of the characters, so the
+    /// rendered table is the same on every run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::AsciiTable;
+    ///
+    /// let rendered = AsciiTable::new().to_display_table().to_string();
+    /// let lines: Vec<&str> = rendered.lines().collect();
+    ///
+    /// assert!(lines[1].contains("Byte"));
+    /// assert!(lines[68].contains("0x41"));
+    /// assert_eq!(
+    ///     lines[68],
+    ///     "| 0x41 | 0b01000001 |    0x41     |  0x41  |       A        |"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_display_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(row![bc => "Byte", "Binary", "Hexadecimal", "String", "Representation"]);
+        table.set_format(*FORMAT_NO_LINESEP_WITH_TITLE);
+
+        for ascii_char in self.iter() {
+            let value = ascii_char.decimal_value();
+            let byte = Byte::from(value);
+            table.add_row(row![c =>
+                byte,
+                format!("{value:#010b}"),
+                format!("{value:#04X}"),
+                byte,
+                ascii_char.character_value()
+            ]);
+        }
+        table
+    }
+
+    /// Convert text to the values of its ASCII characters.
+    ///
+    /// Every character of `text` is looked up by its
+    /// [`character_value`](AsciiChar::character_value), so only printable
+    /// characters and the space can be converted.
+    ///
+    /// # Returns
+    ///
+    /// The value of every character in order, or `None` if a character is not
+    /// in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::AsciiTable;
+    ///
+    /// let ascii_table = AsciiTable::new();
+    ///
+    /// assert_eq!(ascii_table.encode("Hi!"), Some(vec![72, 105, 33]));
+    /// assert_eq!(ascii_table.encode("café"), None);
+    /// ```
+    #[must_use]
+    pub fn encode(&self, text: &str) -> Option<Vec<u8>> {
+        text.chars()
+            .map(|character| {
+                let mut buffer = [0; 4];
+                let character: &str = character.encode_utf8(&mut buffer);
+                self.table
+                    .values()
+                    .find(|ascii_char| ascii_char.character_value() == character)
+                    .map(AsciiChar::decimal_value)
+            })
+            .collect()
     }
 }
 
@@ -734,4 +751,26 @@ mod test {
             "There should be no AsciiChar for Byte value 128"
         );
     }
+
+    #[test]
+    fn test_ascii_table_order() {
+        let ascii_table = AsciiTable::new();
+        let values: Vec<u8> = ascii_table.iter().map(AsciiChar::decimal_value).collect();
+
+        assert_eq!(values, (0..128).collect::<Vec<u8>>());
+        assert_eq!(
+            ascii_table.to_display_table().to_string(),
+            AsciiTable::new().to_display_table().to_string()
+        );
+    }
+
+    #[test]
+    fn test_ascii_table_encode() {
+        let ascii_table = AsciiTable::new();
+
+        assert_eq!(ascii_table.encode("a b"), Some(vec![97, 32, 98]));
+        assert_eq!(ascii_table.encode(""), Some(vec![]));
+        assert_eq!(ascii_table.encode("\n"), None);
+        assert_eq!(ascii_table.encode("ü"), None);
+    }
 }
@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    io::{
+        Result,
+        Write,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use serde_json::json;
+
+/// Writes a terminal recording in the asciicast v2 format
+///
+/// An asciicast file starts with a header describing the size of the
+/// terminal, followed by one line for every chunk of output and the time at
+/// which it was printed. Recordings can be played back with
+/// [asciinema](https://asciinema.org) or embedded in a web page.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use brainfoamkit_lib::Asciicast;
+///
+/// let mut cast = Asciicast::new(Vec::new(), 80, 24).unwrap();
+/// cast.output_at(Duration::from_millis(1500), "Hello\r\n")
+///     .unwrap();
+///
+/// let recording = String::from_utf8(cast.into_inner()).unwrap();
+/// let mut lines = recording.lines();
+///
+/// assert_eq!(
+///     lines.next(),
+///     Some(r#"{"height":24,"version":2,"width":80}"#)
+/// );
+/// assert_eq!(lines.next(), Some(r#"[1.5,"o","Hello\r\n"]"#));
+/// ```
+#[derive(Debug)]
+pub struct Asciicast<W: Write> {
+    out:     W,
+    started: Instant,
+}
+
+impl<W: Write> Asciicast<W> {
+    /// Start a recording and write its header
+    ///
+    /// The clock of the recording starts now.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - Where to write the recording
+    /// * `width` - The number of columns of the recorded terminal
+    /// * `height` - The number of rows of the recorded terminal
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header cannot be written.
+    pub fn new(mut out: W, width: u16, height: u16) -> Result<Self> {
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+        });
+        writeln!(out, "{header}")?;
+
+        Ok(Self {
+            out,
+            started: Instant::now(),
+        })
+    }
+
+    /// Record output printed now
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be written.
+    pub fn output(&mut self, data: &str) -> Result<()> {
+        self.output_at(self.elapsed(), data)
+    }
+
+    /// Record output printed `time` after the start of the recording
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event cannot be written.
+    pub fn output_at(&mut self, time: Duration, data: &str) -> Result<()> {
+        let event = json!([time.as_secs_f64(), "o", data]);
+        writeln!(self.out, "{event}")?;
+        Ok(())
+    }
+
+    /// The time since the recording started
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Flush the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer cannot be flushed.
+    pub fn flush(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Stop recording and return the underlying writer
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn lines(cast: Asciicast<Vec<u8>>) -> Vec<Value> {
+        String::from_utf8(cast.into_inner())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_header() {
+        let cast = Asciicast::new(Vec::new(), 100, 30).unwrap();
+
+        assert_eq!(
+            lines(cast),
+            [json!({ "version": 2, "width": 100, "height": 30 })]
+        );
+    }
+
+    #[test]
+    fn test_events() {
+        let mut cast = Asciicast::new(Vec::new(), 80, 24).unwrap();
+        cast.output_at(Duration::from_millis(250), "a\"b").unwrap();
+        cast.output_at(Duration::from_secs(2), "\u{1b}[2J").unwrap();
+
+        let lines = lines(cast);
+        assert_eq!(lines[1], json!([0.25, "o", "a\"b"]));
+        assert_eq!(lines[2], json!([2.0, "o", "\u{1b}[2J"]));
+    }
+
+    #[test]
+    fn test_output_uses_clock() {
+        let mut cast = Asciicast::new(Vec::new(), 80, 24).unwrap();
+        cast.output("x").unwrap();
+        cast.flush().unwrap();
+
+        let lines = lines(cast);
+        let time = lines[1][0].as_f64().unwrap();
+        assert!((0.0..1.0).contains(&time));
+    }
+}
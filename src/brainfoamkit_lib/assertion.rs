@@ -0,0 +1,172 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+};
+
+use crate::TapeStorage;
+
+/// The number of cells on either side of the checked cell in the tape dump of
+/// an `AssertionFailed` error
+const WINDOW_RADIUS: usize = 8;
+
+/// A cell did not hold the value a program or its test expected
+///
+/// Returned by
+/// [`VirtualMachine::assert_cell`](crate::VirtualMachine::assert_cell). The
+/// error keeps a window of the tape around the checked cell, which its
+/// `Display` implementation prints below the message with the checked cell in
+/// brackets.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Program,
+///     VMError,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("+++>++"))
+///     .tape_size(4)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// let Err(VMError::AssertionFailed(failed)) = machine.assert_cell(1, 3)
+/// else {
+///     panic!("Cell 1 should hold 2");
+/// };
+///
+/// assert_eq!(failed.actual(), 2);
+/// assert_eq!(
+///     failed.to_string(),
+///     "Cell 1 holds 2 instead of 3 at instruction 6\n  0: 3 [2] 0 0"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionFailed {
+    cell:            usize,
+    expected:        u8,
+    actual:          u8,
+    program_counter: usize,
+    window_start:    usize,
+    window:          Vec<u8>,
+}
+
+impl AssertionFailed {
+    pub(crate) fn new(
+        tape: &TapeStorage,
+        cell: usize,
+        expected: u8,
+        program_counter: usize,
+    ) -> Self {
+        let window_start = cell.saturating_sub(WINDOW_RADIUS);
+        let window_end = cell
+            .saturating_add(WINDOW_RADIUS)
+            .saturating_add(1)
+            .min(tape.len());
+
+        Self {
+            cell,
+            expected,
+            actual: u8::from(&tape[cell]),
+            program_counter,
+            window_start,
+            window: tape
+                .slice(window_start..window_end)
+                .iter()
+                .map(u8::from)
+                .collect(),
+        }
+    }
+
+    /// The index of the checked cell
+    #[must_use]
+    pub const fn cell(&self) -> usize {
+        self.cell
+    }
+
+    /// The value the cell was expected to hold
+    #[must_use]
+    pub const fn expected(&self) -> u8 {
+        self.expected
+    }
+
+    /// The value the cell held
+    #[must_use]
+    pub const fn actual(&self) -> u8 {
+        self.actual
+    }
+
+    /// The position in the `Program` of the next instruction when the cell was
+    /// checked
+    #[must_use]
+    pub const fn program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    /// The cells around the checked cell
+    ///
+    /// # Returns
+    ///
+    /// The index of the first cell of the window, and the values of the cells.
+    #[must_use]
+    pub fn window(&self) -> (usize, &[u8]) {
+        (self.window_start, &self.window)
+    }
+}
+
+impl Display for AssertionFailed {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Cell {} holds {} instead of {} at instruction {}",
+            self.cell, self.actual, self.expected, self.program_counter
+        )?;
+        write!(f, "  {}:", self.window_start)?;
+        for (index, value) in self.window.iter().enumerate() {
+            if self.window_start + index == self.cell {
+                write!(f, " [{value}]")?;
+            } else {
+                write!(f, " {value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for AssertionFailed {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Byte;
+
+    #[test]
+    fn test_window() {
+        let tape = TapeStorage::memory((0..30).map(Byte::from).collect());
+        let failed = AssertionFailed::new(&tape, 20, 0, 5);
+
+        assert_eq!(failed.cell(), 20);
+        assert_eq!(failed.expected(), 0);
+        assert_eq!(failed.actual(), 20);
+        assert_eq!(failed.program_counter(), 5);
+        assert_eq!(failed.window().0, 12);
+        assert_eq!(failed.window().1.len(), 17);
+
+        let failed = AssertionFailed::new(&tape, 29, 0, 5);
+        let expected: Vec<u8> = (21..30).collect();
+        assert_eq!(failed.window(), (21, &expected[..]));
+    }
+}
@@ -0,0 +1,201 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+};
+
+use tokio::io::{
+    AsyncRead,
+    AsyncReadExt,
+    AsyncWrite,
+    AsyncWriteExt,
+};
+
+use crate::ReadError;
+
+/// A future returned by the methods of [`AsyncVMReader`] and
+/// [`AsyncVMWriter`]
+pub type IoFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The `AsyncVMReader` trait
+///
+/// The async counterpart of the [`VMReader`](crate::VMReader) trait, used by
+/// [`VirtualMachine::run_async`](crate::VirtualMachine::run_async). It is
+/// implemented for every tokio [`AsyncRead`], such as a `TcpStream` or
+/// `tokio::io::stdin()`, so a program can wait for input without blocking a
+/// thread.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::AsyncVMReader;
+///
+/// # tokio::runtime::Builder::new_current_thread()
+/// #     .build()
+/// #     .unwrap()
+/// #     .block_on(async {
+/// let mut reader: &[u8] = b"Hi";
+///
+/// assert_eq!(reader.read().await.unwrap(), b'H');
+/// assert_eq!(reader.read().await.unwrap(), b'i');
+/// assert!(reader.read().await.is_err());
+/// # });
+/// ```
+pub trait AsyncVMReader: Send {
+    /// Read a single byte from the reader
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`ReadError::Eof`] at the end of the input
+    /// and another error if the reader fails.
+    fn read(&mut self) -> IoFuture<'_, Result<u8, ReadError>>;
+
+    /// Read a decimal number from 0 to 255 from the reader
+    ///
+    /// This function is used by `,` in
+    /// [`IoMode::Numeric`](crate::IoMode::Numeric) and reads a number the
+    /// same way as [`VMReader::read_number`](crate::VMReader::read_number).
+    ///
+    /// # Returns
+    ///
+    /// The number, or `None` if the input ends before a number starts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the input holds something other
+    /// than a number, or a number larger than 255.
+    fn read_number(&mut self) -> IoFuture<'_, Result<Option<u8>, ReadError>> {
+        Box::pin(async move {
+            let mut byte = loop {
+                match self.read().await {
+                    Ok(byte) if byte.is_ascii_whitespace() => {}
+                    Ok(byte) => break byte,
+                    Err(_) => return Ok(None),
+                }
+            };
+            if !byte.is_ascii_digit() {
+                return Err(ReadError::NotANumber(byte));
+            }
+
+            let mut number: u32 = 0;
+            while byte.is_ascii_digit() {
+                number = number * 10 + u32::from(byte - b'0');
+                if number > u32::from(u8::MAX) {
+                    return Err(ReadError::NumberTooLarge);
+                }
+                match self.read().await {
+                    Ok(next) => byte = next,
+                    Err(_) => break,
+                }
+            }
+            Ok(u8::try_from(number).ok())
+        })
+    }
+}
+
+impl<T> AsyncVMReader for T
+where
+    T: AsyncRead + Unpin + Send,
+{
+    fn read(&mut self) -> IoFuture<'_, Result<u8, ReadError>> {
+        Box::pin(async move { Ok(self.read_u8().await?) })
+    }
+}
+
+/// The `AsyncVMWriter` trait
+///
+/// The async counterpart of the [`VMWriter`](crate::VMWriter) trait, used by
+/// [`VirtualMachine::run_async`](crate::VirtualMachine::run_async). It is
+/// implemented for every tokio [`AsyncWrite`]. Every byte is flushed as soon
+/// as it is written, so the other end of a socket sees the output of an
+/// interactive program before it asks for more input.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::AsyncVMWriter;
+///
+/// # tokio::runtime::Builder::new_current_thread()
+/// #     .build()
+/// #     .unwrap()
+/// #     .block_on(async {
+/// let mut writer = Vec::new();
+///
+/// writer.write(b'H').await.unwrap();
+/// writer.write(b'i').await.unwrap();
+/// assert_eq!(writer, b"Hi");
+/// # });
+/// ```
+pub trait AsyncVMWriter: Send {
+    /// Write a single byte to the writer
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte could not be written.
+    fn write(&mut self, value: u8) -> IoFuture<'_, io::Result<()>>;
+
+    /// Write a value as a decimal number followed by a newline
+    ///
+    /// This function is used by `.` in
+    /// [`IoMode::Numeric`](crate::IoMode::Numeric).
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the number could not be written.
+    fn write_number(&mut self, value: u8) -> IoFuture<'_, io::Result<usize>> {
+        Box::pin(async move {
+            let text = format!("{value}\n");
+            for byte in text.bytes() {
+                self.write(byte).await?;
+            }
+            Ok(text.len())
+        })
+    }
+}
+
+impl<T> AsyncVMWriter for T
+where
+    T: AsyncWrite + Unpin + Send,
+{
+    fn write(&mut self, value: u8) -> IoFuture<'_, io::Result<()>> {
+        Box::pin(async move {
+            self.write_u8(value).await?;
+            self.flush().await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_number() {
+        let mut reader: &[u8] = b" 12\n255 256";
+
+        assert_eq!(reader.read_number().await.unwrap(), Some(12));
+        assert_eq!(reader.read_number().await.unwrap(), Some(255));
+        assert!(matches!(
+            reader.read_number().await,
+            Err(ReadError::NumberTooLarge)
+        ));
+        assert_eq!(reader.read_number().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_write_number() {
+        let mut writer = Vec::new();
+
+        assert_eq!(writer.write_number(42).await.unwrap(), 3);
+        assert_eq!(writer, b"42\n");
+    }
+}
@@ -0,0 +1,295 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    str::FromStr,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    EofBehavior,
+    ParseError,
+};
+
+/// What happens when the memory pointer moves off an end of the tape
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TapeEdge {
+    /// Move to the cell at the other end of the tape
+    #[default]
+    Wrap,
+    /// Append a cell when moving off the right end. Moving off the left end is
+    /// a [`ProfileViolation`].
+    Grow,
+    /// Moving off either end is a [`ProfileViolation`]
+    Error,
+}
+
+/// A named bundle of the settings on which interpreters disagree
+///
+/// Programs are written against the conventions of one interpreter or
+/// another. A profile pins the tape size, what happens at the ends of the
+/// tape, whether cells wrap around and what `,` does at the end of the input,
+/// so that a program behaves the same wherever it runs. The guarantees of a
+/// profile do not change within a major version of this crate; a profile with
+/// different semantics gets a new name.
+///
+/// | Profile         | Tape                          | Cells                        | End of input |
+/// |-----------------|-------------------------------|------------------------------|--------------|
+/// | `Classic30k`    | 30,000 cells, wrapping        | 8 bits, wrapping             | unchanged    |
+/// | `ModernDynamic` | 30,000 cells, growing right   | 8 bits, wrapping             | zero         |
+/// | `Strict`        | 30,000 cells, ends are errors | 8 bits, overflow is an error | unchanged    |
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     BehaviorProfile,
+///     ProfileViolation,
+///     Program,
+///     VMError,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("-"))
+///     .behavior_profile(BehaviorProfile::Strict)
+///     .build()
+///     .unwrap();
+///
+/// let error = machine.run().unwrap_err();
+/// assert!(matches!(
+///     error,
+///     VMError::ProfileViolation(ProfileViolation::CellUnderflow {
+///         instruction: 0,
+///         cell:        0,
+///     })
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BehaviorProfile {
+    /// The behavior of the original interpreter and most of its descendants:
+    /// a fixed tape of 30,000 cells whose ends wrap around, wrapping cells, and
+    /// `,` leaves the cell unchanged at the end of the input.
+    #[serde(rename = "classic")]
+    Classic30k,
+    /// A tape that starts with 30,000 cells and grows to the right as needed,
+    /// wrapping cells, and `,` sets the cell to zero at the end of the input.
+    /// Moving left of the first cell is an error.
+    #[serde(rename = "modern")]
+    ModernDynamic,
+    /// Only what every interpreter agrees on: a fixed tape of 30,000 cells,
+    /// where moving off either end and overflowing or underflowing a cell are
+    /// errors, and `,` leaves the cell unchanged at the end of the input. A
+    /// program that runs under this profile runs the same under the others.
+    #[serde(rename = "strict")]
+    Strict,
+}
+
+impl BehaviorProfile {
+    /// Every profile, in the order of the table above
+    pub const ALL: [Self; 3] = [Self::Classic30k, Self::ModernDynamic, Self::Strict];
+
+    /// The name of the profile, as accepted by [`from_str`](Self::from_str)
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Classic30k => "classic",
+            Self::ModernDynamic => "modern",
+            Self::Strict => "strict",
+        }
+    }
+
+    /// The number of cells on the tape when the program starts
+    #[must_use]
+    pub const fn tape_size(self) -> usize {
+        30000
+    }
+
+    /// What happens when the memory pointer moves off an end of the tape
+    #[must_use]
+    pub const fn tape_edge(self) -> TapeEdge {
+        match self {
+            Self::Classic30k => TapeEdge::Wrap,
+            Self::ModernDynamic => TapeEdge::Grow,
+            Self::Strict => TapeEdge::Error,
+        }
+    }
+
+    /// Whether incrementing 255 gives 0 and decrementing 0 gives 255
+    ///
+    /// Otherwise overflowing or underflowing a cell is a
+    /// [`ProfileViolation`].
+    #[must_use]
+    pub const fn wraps_cells(self) -> bool {
+        !matches!(self, Self::Strict)
+    }
+
+    /// What `,` does when there is no more input
+    #[must_use]
+    pub const fn eof_behavior(self) -> EofBehavior {
+        match self {
+            Self::Classic30k | Self::Strict => EofBehavior::Unchanged,
+            Self::ModernDynamic => EofBehavior::Zero,
+        }
+    }
+}
+
+impl Display for BehaviorProfile {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for BehaviorProfile {
+    type Err = ParseError;
+
+    /// Parse the name of a behavior profile
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::BehaviorProfile;
+    ///
+    /// assert_eq!(
+    ///     "modern".parse::<BehaviorProfile>().unwrap(),
+    ///     BehaviorProfile::ModernDynamic
+    /// );
+    /// assert!("lenient".parse::<BehaviorProfile>().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name does not match any profile.
+    fn from_str(name: &str) -> Result<Self, ParseError> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name() == name)
+            .ok_or_else(|| ParseError::UnknownName {
+                kind: "behavior profile",
+                name: name.to_string(),
+            })
+    }
+}
+
+/// An instruction that would break the rules of the machine's behavior
+/// profile
+///
+/// The `VirtualMachine` stops before the offending instruction takes effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileViolation {
+    /// A `<` tried to move left of the first cell
+    PointerUnderflow {
+        /// The position of the offending `<`
+        instruction: usize,
+    },
+    /// A `>` tried to move right of the last cell
+    PointerOverflow {
+        /// The position of the offending `>`
+        instruction: usize,
+    },
+    /// A `+` tried to increment a cell holding 255
+    CellOverflow {
+        /// The position of the offending `+`
+        instruction: usize,
+        /// The cell the memory pointer pointed to
+        cell:        usize,
+    },
+    /// A `-` tried to decrement a cell holding 0
+    CellUnderflow {
+        /// The position of the offending `-`
+        instruction: usize,
+        /// The cell the memory pointer pointed to
+        cell:        usize,
+    },
+}
+
+impl ProfileViolation {
+    /// The position in the `Program` of the offending instruction
+    #[must_use]
+    pub const fn program_counter(&self) -> usize {
+        match self {
+            Self::PointerUnderflow { instruction }
+            | Self::PointerOverflow { instruction }
+            | Self::CellOverflow { instruction, .. }
+            | Self::CellUnderflow { instruction, .. } => *instruction,
+        }
+    }
+}
+
+impl Display for ProfileViolation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::PointerUnderflow { instruction } => write!(
+                f,
+                "Instruction {instruction} moved the memory pointer left of the first cell"
+            ),
+            Self::PointerOverflow { instruction } => write!(
+                f,
+                "Instruction {instruction} moved the memory pointer right of the last cell"
+            ),
+            Self::CellOverflow { instruction, cell } => {
+                write!(f, "Instruction {instruction} overflowed cell {cell}")
+            }
+            Self::CellUnderflow { instruction, cell } => {
+                write!(f, "Instruction {instruction} underflowed cell {cell}")
+            }
+        }
+    }
+}
+
+impl Error for ProfileViolation {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings() {
+        assert_eq!(BehaviorProfile::Classic30k.tape_edge(), TapeEdge::Wrap);
+        assert_eq!(BehaviorProfile::ModernDynamic.tape_edge(), TapeEdge::Grow);
+        assert_eq!(BehaviorProfile::Strict.tape_edge(), TapeEdge::Error);
+        assert!(BehaviorProfile::Classic30k.wraps_cells());
+        assert!(!BehaviorProfile::Strict.wraps_cells());
+        assert_eq!(
+            BehaviorProfile::ModernDynamic.eof_behavior(),
+            EofBehavior::Zero
+        );
+        for profile in BehaviorProfile::ALL {
+            assert_eq!(profile.tape_size(), 30000);
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        for profile in BehaviorProfile::ALL {
+            assert_eq!(
+                profile.to_string().parse::<BehaviorProfile>().unwrap(),
+                profile
+            );
+        }
+        assert!("Strict".parse::<BehaviorProfile>().is_err());
+    }
+
+    #[test]
+    fn test_violation() {
+        let violation = ProfileViolation::CellOverflow {
+            instruction: 4,
+            cell:        2,
+        };
+        assert_eq!(violation.program_counter(), 4);
+        assert_eq!(violation.to_string(), "Instruction 4 overflowed cell 2");
+    }
+}
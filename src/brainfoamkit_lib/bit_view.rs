@@ -0,0 +1,169 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use alloc::vec::Vec;
+
+use crate::{
+    Bit,
+    Byte,
+    Nybble,
+};
+
+/// A cell taken apart into the [`Nybble`]s and [`Bit`]s it is made of
+///
+/// A `BitView` shows how a cell of the machine is built from the smaller
+/// types of this crate, for teaching binary arithmetic: a [`Byte`] is a high
+/// and a low `Nybble`, and every `Nybble` is four `Bit`s. It also follows a
+/// change of the cell bit by bit. When a cell is incremented, the carry
+/// ripples up from the least significant bit, flipping every `1` it passes
+/// to `0` until it turns a `0` into a `1`, and a decrement borrows the same
+/// way. [`ripple`](Self::ripple) lists the values the cell goes through as
+/// the bits flip one at a time, so that the change can be animated.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Bit,
+///     BitView,
+///     Byte,
+///     Nybble,
+/// };
+///
+/// let view = BitView::new(Byte::from(0x2F));
+///
+/// assert_eq!(view.high_nybble(), Nybble::from(0x2));
+/// assert_eq!(view.low_nybble(), Nybble::from(0xF));
+/// assert_eq!(view.bits()[2], Bit::One);
+///
+/// // Incrementing 0x2F carries through the four low bits into bit 4
+/// assert_eq!(view.flips_to(Byte::from(0x30)), [0, 1, 2, 3, 4]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BitView {
+    byte: Byte,
+}
+
+impl BitView {
+    /// Take `byte` apart
+    #[must_use]
+    pub const fn new(byte: Byte) -> Self {
+        Self { byte }
+    }
+
+    /// The whole cell
+    #[must_use]
+    pub const fn byte(&self) -> Byte {
+        self.byte
+    }
+
+    /// The four most significant bits of the cell
+    #[must_use]
+    pub fn high_nybble(&self) -> Nybble {
+        self.byte.get_high_nybble()
+    }
+
+    /// The four least significant bits of the cell
+    #[must_use]
+    pub fn low_nybble(&self) -> Nybble {
+        self.byte.get_low_nybble()
+    }
+
+    /// The bits of the cell, most significant first, the order they are
+    /// written in
+    #[must_use]
+    pub fn bits(&self) -> [Bit; 8] {
+        let mut bits = [Bit::zero(); 8];
+        for (position, bit) in bits.iter_mut().enumerate() {
+            // Bit 7 comes first
+            #[allow(clippy::cast_possible_truncation)]
+            let index = 7 - position as u8;
+            *bit = self.byte.get_bit(index);
+        }
+        bits
+    }
+
+    /// The indexes of the bits that differ between the cell and `next`,
+    /// least significant first
+    ///
+    /// For an increment or a decrement, this is the order in which the carry
+    /// or the borrow flips the bits.
+    #[must_use]
+    pub fn flips_to(&self, next: Byte) -> Vec<u8> {
+        (0..8)
+            .filter(|&index| self.byte.get_bit(index) != next.get_bit(index))
+            .collect()
+    }
+
+    /// The values the cell goes through on its way to `next`, flipping the
+    /// bits that differ one at a time, least significant first
+    ///
+    /// The last value is `next` itself. If the cell already holds `next`,
+    /// there are none. For an increment or a decrement, these are the values
+    /// of [`Byte::increment_steps`] or [`Byte::decrement_steps`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     BitView,
+    ///     Byte,
+    /// };
+    ///
+    /// let frames = BitView::new(Byte::from(0b0111)).ripple(Byte::from(0b1000));
+    /// let values: Vec<u8> = frames.iter().map(u8::from).collect();
+    ///
+    /// assert_eq!(values, [0b0110, 0b0100, 0b0000, 0b1000]);
+    /// ```
+    #[must_use]
+    pub fn ripple(&self, next: Byte) -> Vec<Byte> {
+        let mut current = self.byte;
+        self.flips_to(next)
+            .into_iter()
+            .map(|index| {
+                current.flip_bit(index);
+                current
+            })
+            .collect()
+    }
+}
+
+impl From<Byte> for BitView {
+    fn from(byte: Byte) -> Self {
+        Self::new(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decomposition() {
+        let view = BitView::from(Byte::from(0b1010_0110));
+
+        assert_eq!(u8::from(&view.high_nybble()), 0b1010);
+        assert_eq!(u8::from(&view.low_nybble()), 0b0110);
+        assert_eq!(view.bits().map(u8::from), [1, 0, 1, 0, 0, 1, 1, 0]);
+        assert_eq!(
+            Byte::from_nybbles(view.high_nybble(), view.low_nybble()),
+            view.byte()
+        );
+    }
+
+    #[test]
+    fn test_ripple_wraps_around() {
+        let view = BitView::new(Byte::from(0));
+        let mut decremented = view.byte();
+        decremented.decrement();
+
+        assert_eq!(view.flips_to(decremented), [0, 1, 2, 3, 4, 5, 6, 7]);
+        let frames = view.ripple(decremented);
+        assert_eq!(frames.len(), 8);
+        assert_eq!(u8::from(&frames[0]), 0b0000_0001);
+        assert_eq!(frames.last(), Some(&Byte::from(255)));
+        assert!(view.ripple(view.byte()).is_empty());
+    }
+}
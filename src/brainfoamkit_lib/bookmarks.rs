@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeMap;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Named positions on the tape and in the program
+///
+/// Bookmarks give memorable names to the cells and instructions that matter
+/// while debugging, such as the cell that holds the result or the start of
+/// the main loop, so that they can be found again on a large tape or in a
+/// long program. Cells and instructions have separate names, so the same name
+/// can mark both. Bookmarking a name again moves the bookmark.
+///
+/// Bookmarks are usually set on a
+/// [`VirtualMachine`](crate::VirtualMachine) with
+/// [`bookmark_cell`](crate::VirtualMachine::bookmark_cell) and
+/// [`bookmark_pc`](crate::VirtualMachine::bookmark_pc), and saved with a
+/// [`DebugSession`](crate::DebugSession).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::Bookmarks;
+///
+/// let mut bookmarks = Bookmarks::new();
+/// bookmarks.bookmark_cell("result", 3);
+/// bookmarks.bookmark_pc("main_loop", 12);
+///
+/// assert_eq!(bookmarks.cell("result"), Some(3));
+/// assert_eq!(bookmarks.pc("main_loop"), Some(12));
+/// assert_eq!(bookmarks.cell("main_loop"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bookmarks {
+    cells:        BTreeMap<String, usize>,
+    instructions: BTreeMap<String, usize>,
+}
+
+impl Bookmarks {
+    /// Create an empty set of bookmarks
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name the cell at `index`
+    pub fn bookmark_cell(&mut self, name: impl Into<String>, index: usize) {
+        self.cells.insert(name.into(), index);
+    }
+
+    /// Name the instruction at `pc`
+    pub fn bookmark_pc(&mut self, name: impl Into<String>, pc: usize) {
+        self.instructions.insert(name.into(), pc);
+    }
+
+    /// The cell called `name`, if there is one
+    #[must_use]
+    pub fn cell(&self, name: &str) -> Option<usize> {
+        self.cells.get(name).copied()
+    }
+
+    /// The instruction called `name`, if there is one
+    #[must_use]
+    pub fn pc(&self, name: &str) -> Option<usize> {
+        self.instructions.get(name).copied()
+    }
+
+    /// Remove the bookmarks of both the cell and the instruction called
+    /// `name`
+    ///
+    /// # Returns
+    ///
+    /// `true` if there was a bookmark called `name`.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let cell = self.cells.remove(name).is_some();
+        let instruction = self.instructions.remove(name).is_some();
+        cell || instruction
+    }
+
+    /// The bookmarked cells with their names, ordered by name
+    pub fn cells(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.cells
+            .iter()
+            .map(|(name, &index)| (name.as_str(), index))
+    }
+
+    /// The bookmarked instructions with their names, ordered by name
+    pub fn instructions(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.instructions
+            .iter()
+            .map(|(name, &pc)| (name.as_str(), pc))
+    }
+
+    /// The number of bookmarks
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cells.len() + self.instructions.len()
+    }
+
+    /// Returns `true` if there are no bookmarks
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.instructions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmarks() {
+        let mut bookmarks = Bookmarks::new();
+        assert!(bookmarks.is_empty());
+
+        bookmarks.bookmark_cell("total", 4);
+        bookmarks.bookmark_cell("counter", 1);
+        bookmarks.bookmark_pc("total", 9);
+        bookmarks.bookmark_cell("counter", 2);
+
+        assert_eq!(bookmarks.len(), 3);
+        assert_eq!(
+            bookmarks.cells().collect::<Vec<_>>(),
+            [("counter", 2), ("total", 4)]
+        );
+        assert_eq!(bookmarks.instructions().collect::<Vec<_>>(), [("total", 9)]);
+
+        assert!(bookmarks.remove("total"));
+        assert!(!bookmarks.remove("total"));
+        assert_eq!(bookmarks.len(), 1);
+    }
+}
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::{
+use core::{
     fmt::{
         self,
         Display,
@@ -22,6 +22,7 @@ use std::{
 
 use crate::{
     Bit,
+    CarrySteps,
     IterableByte,
     Nybble,
 };
@@ -743,28 +744,45 @@ impl Byte {
     /// * [Integer Overflow](https://en.wikipedia.org/wiki/Integer_overflow): An
     ///   overview of the mathematics behind integer overflow
     pub fn increment(&mut self) {
-        let mut carry = true;
-        let mut changes = Vec::new();
-
-        for (i, bit) in self.iter().enumerate() {
-            if !carry {
-                break;
-            }
-            if bit == Bit::One {
-                changes.push((i, false)); // Store the index and the new value
-            } else {
-                changes.push((i, true)); // Store the index and the new value
-                carry = false;
-            }
+        if let Some(step) = self.increment_steps().last() {
+            *self = step.value;
         }
+    }
 
-        for (i, value) in changes {
-            if value {
-                self.set_bit(i);
-            } else {
-                self.unset_bit(i);
-            }
-        }
+    /// The bit flips of an increment of the Byte, one at a time.
+    ///
+    /// The carry starts at the least significant bit and moves up for as long
+    /// as it turns a `1` into a `0`. Every step holds the value of the Byte
+    /// after the flip, so that the carry can be followed bit by bit, for
+    /// example to animate it. The Byte itself is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Byte;
+    ///
+    /// let byte = Byte::from(0b0000_0111);
+    /// let values: Vec<u8> = byte
+    ///     .increment_steps()
+    ///     .map(|step| u8::from(&step.value))
+    ///     .collect();
+    ///
+    /// assert_eq!(values, [0b0000_0110, 0b0000_0100, 0b0000_0000, 0b0000_1000]);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A [`CarrySteps`](crate::CarrySteps) iterator, whose last step holds the
+    /// incremented value.
+    ///
+    /// # See Also
+    ///
+    /// * [`increment()`](#method.increment): Increment the Byte by one.
+    /// * [`decrement_steps()`](#method.decrement_steps): The bit flips of a
+    ///   decrement.
+    #[must_use]
+    pub const fn increment_steps(&self) -> CarrySteps {
+        CarrySteps::increment(*self)
     }
 
     /// Decrements the Byte by one.
@@ -820,28 +838,103 @@ impl Byte {
     /// * [`increment()`](#method.increment): Increment the Byte by one.
     /// * [`flip()`](#method.flip): Flip all of the Bit values in the Byte.
     pub fn decrement(&mut self) {
-        let mut borrow = true;
-        let mut changes = Vec::new();
-
-        for (i, bit) in self.iter().enumerate() {
-            if !borrow {
-                break;
-            }
-            if bit == Bit::Zero {
-                changes.push((i, true)); // Store the index and the new value
-            } else {
-                changes.push((i, false)); // Store the index and the new value
-                borrow = false;
-            }
+        if let Some(step) = self.decrement_steps().last() {
+            *self = step.value;
         }
+    }
 
-        for (i, value) in changes {
-            if value {
-                self.set_bit(i);
-            } else {
-                self.unset_bit(i);
-            }
-        }
+    /// The bit flips of a decrement of the Byte, one at a time.
+    ///
+    /// The borrow starts at the least significant bit and moves up for as
+    /// long as it turns a `0` into a `1`. Every step holds the value of the
+    /// Byte after the flip. The Byte itself is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Byte;
+    ///
+    /// let steps: Vec<_> = Byte::from(0b0000_0100).decrement_steps().collect();
+    ///
+    /// assert_eq!(steps.len(), 3);
+    /// assert!(!steps[2].carry);
+    /// assert_eq!(u8::from(&steps[2].value), 0b0000_0011);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A [`CarrySteps`](crate::CarrySteps) iterator, whose last step holds the
+    /// decremented value.
+    ///
+    /// # See Also
+    ///
+    /// * [`decrement()`](#method.decrement): Decrement the Byte by one.
+    /// * [`increment_steps()`](#method.increment_steps): The bit flips of an
+    ///   increment.
+    #[must_use]
+    pub const fn decrement_steps(&self) -> CarrySteps {
+        CarrySteps::decrement(*self)
+    }
+
+    /// Interprets the Byte as a signed 8-bit integer (i8).
+    ///
+    /// The bits are read in two's complement, so that values from 128 to 255
+    /// become -128 to -1. Many Brainfuck algorithms treat a cell that was
+    /// decremented below zero as a negative number, which is easier to follow
+    /// in this form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Byte;
+    ///
+    /// let mut byte = Byte::from(0);
+    /// byte.decrement();
+    ///
+    /// assert_eq!(byte.as_i8(), -1);
+    /// assert_eq!(Byte::from(127).as_i8(), 127);
+    /// assert_eq!(Byte::from(128).as_i8(), -128);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The value of the Byte as an i8.
+    ///
+    /// # See Also
+    ///
+    /// * [`from_i8()`](#method.from_i8): Create a new Byte from an i8.
+    #[must_use]
+    pub fn as_i8(&self) -> i8 {
+        i8::from_ne_bytes([u8::from(self)])
+    }
+
+    /// Creates a new Byte from a signed 8-bit integer (i8).
+    ///
+    /// The value is stored in two's complement, so that -1 becomes 255.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The i8 to create the Byte from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Byte;
+    ///
+    /// assert_eq!(u8::from(&Byte::from_i8(-1)), 255);
+    /// assert_eq!(Byte::from_i8(-100).as_i8(), -100);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A Byte holding the value in two's complement.
+    ///
+    /// # See Also
+    ///
+    /// * [`as_i8()`](#method.as_i8): Interpret the Byte as an i8.
+    #[must_use]
+    pub fn from_i8(value: i8) -> Self {
+        Self::from(value.to_ne_bytes()[0])
     }
 
     /// Create an iterator over the Byte.
@@ -1960,4 +2053,13 @@ mod tests {
         assert_eq!(iter.next(), Some(Bit::Zero));
         assert_eq!(iter.next(), None); // Ensure the iterator is exhausted
     }
+
+    #[test]
+    fn test_signed_round_trip() {
+        for value in i8::MIN..=i8::MAX {
+            assert_eq!(Byte::from_i8(value).as_i8(), value);
+        }
+        assert_eq!(u8::from(&Byte::from_i8(i8::MIN)), 128);
+        assert_eq!(Byte::from(255).as_i8(), -1);
+    }
 }
@@ -0,0 +1,114 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::sync::{
+    atomic::{
+        AtomicBool,
+        Ordering,
+    },
+    Arc,
+};
+
+/// A flag that stops a run of the `VirtualMachine` from another thread
+///
+/// A token is handed to
+/// [`VirtualMachine::run_with_cancel`](crate::VirtualMachine::run_with_cancel),
+/// which looks at it before every instruction. Clones share the same flag, so
+/// one clone can be kept by a thread, a timer or a signal handler to cancel
+/// the run, which then returns
+/// [`StopReason::Cancelled`](crate::StopReason::Cancelled).
+///
+/// A token built from an `Arc<AtomicBool>` uses that flag, which is how it is
+/// set from a signal handler that can only store to an atomic.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// use brainfoamkit_lib::{
+///     CancellationToken,
+///     Program,
+///     StopReason,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("+[]"))
+///     .build()
+///     .unwrap();
+///
+/// let token = CancellationToken::new();
+/// let canceller = token.clone();
+/// thread::spawn(move || canceller.cancel());
+///
+/// assert_eq!(
+///     machine.run_with_cancel(&token).unwrap(),
+///     StopReason::Cancelled
+/// );
+/// assert!(!machine.is_halted());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled yet
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every run that looks at the token
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token has been cancelled
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancellationToken {
+    fn from(cancelled: Arc<AtomicBool>) -> Self {
+        Self { cancelled }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Byte,
+        StopReason,
+        VirtualMachine,
+    };
+
+    #[test]
+    fn test_cancel_at_a_break() {
+        let mut machine = VirtualMachine::testing("+.", b"").unwrap();
+        machine.set_break_on_output(true);
+        let token = CancellationToken::new();
+
+        assert_eq!(
+            machine.run_with_cancel(&token).unwrap(),
+            StopReason::OutputPending(Byte::from(1))
+        );
+        token.cancel();
+        assert_eq!(
+            machine.run_with_cancel(&token).unwrap(),
+            StopReason::Cancelled
+        );
+        assert!(machine.output_device().data.is_empty());
+
+        // The pending output is written without breaking on it again
+        assert_eq!(machine.run().unwrap(), StopReason::Halted);
+        assert_eq!(machine.output_device().data, [1]);
+    }
+}
@@ -0,0 +1,142 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{
+    Bit,
+    Byte,
+};
+
+/// A single bit flip of an increment or a decrement of a `Byte`
+///
+/// See [`CarrySteps`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CarryStep {
+    /// The index of the flipped `Bit`, from 0 for the least significant one
+    pub bit:   u8,
+    /// The value of the `Byte` once the bit is flipped
+    pub value: Byte,
+    /// Whether the carry, or the borrow, moves on to the next bit. On the
+    /// last step, this means the `Byte` wrapped around.
+    pub carry: bool,
+}
+
+/// An iterator over the bit flips of an increment or a decrement of a `Byte`
+///
+/// Incrementing a byte adds one to its least significant bit. Adding one to a
+/// `1` makes it a `0` and carries the one to the next bit, until a `0`
+/// becomes a `1` and the carry stops. Decrementing borrows the same way,
+/// turning `0`s into `1`s until a `1` becomes a `0`. A carry or a borrow
+/// that runs past the most significant bit is what makes the byte wrap
+/// around.
+///
+/// This iterator returns a [`CarryStep`] for every bit that flips, from the
+/// least significant up, with the value of the byte after the flip. The last
+/// step holds the result of the increment or the decrement. It is created
+/// with [`Byte::increment_steps`] or [`Byte::decrement_steps`].
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::Byte;
+///
+/// let byte = Byte::from(0b0000_0011);
+/// let steps: Vec<_> = byte.increment_steps().collect();
+///
+/// assert_eq!(steps.len(), 3);
+/// assert_eq!(u8::from(&steps[0].value), 0b0000_0010);
+/// assert!(steps[0].carry);
+/// assert_eq!(u8::from(&steps[1].value), 0b0000_0000);
+/// assert_eq!((steps[2].bit, steps[2].carry), (2, false));
+/// assert_eq!(u8::from(&steps[2].value), 0b0000_0100);
+/// ```
+///
+/// # See Also
+///
+/// * [`Byte::increment`](crate::Byte::increment)
+/// * [`Byte::decrement`](crate::Byte::decrement)
+/// * [`BitView`](crate::BitView)
+#[derive(Debug, Clone)]
+pub struct CarrySteps {
+    value:    Byte,
+    /// The bit that becomes a `1` when incrementing, or a `0` when
+    /// decrementing, and stops the carry
+    stop:     Bit,
+    next_bit: u8,
+    carrying: bool,
+}
+
+impl CarrySteps {
+    /// The flips of an increment of `value`
+    #[must_use]
+    pub const fn increment(value: Byte) -> Self {
+        Self {
+            value,
+            stop: Bit::Zero,
+            next_bit: 0,
+            carrying: true,
+        }
+    }
+
+    /// The flips of a decrement of `value`
+    #[must_use]
+    pub const fn decrement(value: Byte) -> Self {
+        Self {
+            value,
+            stop: Bit::One,
+            next_bit: 0,
+            carrying: true,
+        }
+    }
+}
+
+impl Iterator for CarrySteps {
+    type Item = CarryStep;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.carrying || self.next_bit > 7 {
+            return None;
+        }
+        let bit = self.next_bit;
+        let carry = self.value.get_bit(bit) != self.stop;
+        self.value.flip_bit(bit);
+        self.next_bit += 1;
+        self.carrying = carry;
+
+        Some(CarryStep {
+            bit,
+            value: self.value,
+            carry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_increment_wraps_around() {
+        let steps: Vec<CarryStep> = CarrySteps::increment(Byte::from(255)).collect();
+
+        assert_eq!(steps.len(), 8);
+        assert!(steps.iter().all(|step| step.carry));
+        assert_eq!(steps.last().map(|step| step.value), Some(Byte::from(0)));
+    }
+
+    #[test]
+    fn test_decrement() {
+        let steps: Vec<CarryStep> = CarrySteps::decrement(Byte::from(0b0001_0000)).collect();
+        let bits: Vec<u8> = steps.iter().map(|step| step.bit).collect();
+
+        assert_eq!(bits, [0, 1, 2, 3, 4]);
+        assert!(!steps[4].carry);
+        assert_eq!(steps[4].value, Byte::from(0b0000_1111));
+
+        let steps: Vec<CarryStep> = CarrySteps::decrement(Byte::from(0)).collect();
+        assert_eq!(steps.len(), 8);
+        assert_eq!(steps[7].value, Byte::from(255));
+        assert!(steps[7].carry);
+    }
+}
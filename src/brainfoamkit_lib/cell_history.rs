@@ -0,0 +1,222 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::VecDeque;
+
+use crate::{
+    vm_reader::VMReader,
+    vm_writer::VMWriter,
+    VMError,
+    VirtualMachine,
+};
+
+/// Records the value of a single cell over the most recent steps of a run
+///
+/// The recorder keeps one sample per step, as a pair of the step number and
+/// the value of the cell after that step, and forgets the oldest samples once
+/// it holds `capacity` of them. It is meant for debugging counters and
+/// accumulators, for example by plotting the samples as a chart.
+///
+/// The recorder can drive the machine itself with [`step`](Self::step) and
+/// [`run`](Self::run), like the [`Profiler`](crate::Profiler), or observe a
+/// machine that is stepped elsewhere with [`record`](Self::record).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CellHistoryRecorder,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("+++[>++<-]"))
+///     .build()
+///     .unwrap();
+///
+/// let mut recorder = CellHistoryRecorder::new(1, 4);
+/// recorder.run(&mut machine).unwrap();
+///
+/// // The last four steps: `<`, `-`, `]` and the `]` that leaves the loop
+/// let values: Vec<u8> = recorder.samples().map(|(_, value)| value).collect();
+/// assert_eq!(values, [6, 6, 6, 6]);
+/// assert_eq!(recorder.samples().last(), Some((machine.steps(), 6)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellHistoryRecorder {
+    cell:     usize,
+    capacity: usize,
+    samples:  VecDeque<(usize, u8)>,
+}
+
+impl CellHistoryRecorder {
+    /// Record the value of `cell` over the last `capacity` steps
+    #[must_use]
+    pub fn new(cell: usize, capacity: usize) -> Self {
+        Self {
+            cell,
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// The cell being recorded
+    #[must_use]
+    pub const fn cell(&self) -> usize {
+        self.cell
+    }
+
+    /// The number of samples kept
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Record another cell from now on, forgetting the samples of the
+    /// previous one
+    pub fn select(&mut self, cell: usize) {
+        self.cell = cell;
+        self.samples.clear();
+    }
+
+    /// Take a sample of the cell in the current state of `machine`
+    ///
+    /// A second sample at the same step replaces the first one. A cell
+    /// beyond the end of the tape, which a growing tape may still reach, is
+    /// recorded as zero.
+    pub fn record<R, W>(&mut self, machine: &VirtualMachine<R, W>)
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        if self.capacity == 0 {
+            return;
+        }
+        let step = machine.steps();
+        let value = machine.tape().get(self.cell).map_or(0, u8::from);
+        if self.samples.back().is_some_and(|&(last, _)| last == step) {
+            self.samples.pop_back();
+        } else if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((step, value));
+    }
+
+    /// Execute a single instruction of `machine` and record the cell
+    /// afterwards
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction).
+    pub fn step<R, W>(&mut self, machine: &mut VirtualMachine<R, W>) -> Result<(), VMError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        machine.execute_instruction()?;
+        self.record(machine);
+        Ok(())
+    }
+
+    /// Execute `machine` until it halts, recording the cell after every step
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction).
+    /// The samples recorded up to the error are kept.
+    pub fn run<R, W>(&mut self, machine: &mut VirtualMachine<R, W>) -> Result<(), VMError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        while !machine.is_halted() {
+            self.step(machine)?;
+        }
+        Ok(())
+    }
+
+    /// The samples, as pairs of the step number and the value of the cell,
+    /// oldest first
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = (usize, u8)> + '_ {
+        self.samples.iter().copied()
+    }
+
+    /// The number of samples recorded
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns `true` if no samples are recorded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Program;
+
+    fn machine(source: &str) -> VirtualMachine<std::io::Stdin> {
+        VirtualMachine::builder()
+            .input_device(std::io::stdin())
+            .program(Program::from(source))
+            .tape_size(4)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut machine = machine("+++++");
+        let mut recorder = CellHistoryRecorder::new(0, 3);
+        recorder.run(&mut machine).unwrap();
+
+        assert_eq!(recorder.len(), 3);
+        assert_eq!(
+            recorder.samples().collect::<Vec<_>>(),
+            [(3, 3), (4, 4), (5, 5)]
+        );
+    }
+
+    #[test]
+    fn test_record_and_select() {
+        let mut machine = machine("+>++");
+        let mut recorder = CellHistoryRecorder::new(1, 8);
+        recorder.record(&machine);
+        recorder.record(&machine);
+        assert_eq!(recorder.samples().collect::<Vec<_>>(), [(0, 0)]);
+
+        recorder.run(&mut machine).unwrap();
+        assert_eq!(
+            recorder
+                .samples()
+                .map(|(_, value)| value)
+                .collect::<Vec<_>>(),
+            [0, 0, 0, 1, 2]
+        );
+
+        recorder.select(0);
+        assert!(recorder.is_empty());
+        recorder.record(&machine);
+        assert_eq!(recorder.cell(), 0);
+        assert_eq!(recorder.samples().next(), Some((4, 1)));
+    }
+
+    #[test]
+    fn test_cell_beyond_the_tape() {
+        let machine = machine("+");
+        let mut recorder = CellHistoryRecorder::new(10, 2);
+        recorder.record(&machine);
+
+        assert_eq!(recorder.samples().next(), Some((0, 0)));
+    }
+}
@@ -0,0 +1,98 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// Access counts for a single cell of the tape
+///
+/// When profiling is enabled, the `VirtualMachine` keeps one `CellStats` for
+/// every cell of its tape. Instructions that inspect a cell (`+`, `-`, `.`,
+/// `[` and `]`) count as reads, and instructions that change it (`+`, `-` and
+/// `,`) count as writes.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CellStats,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from(">++[-]"))
+///     .tape_size(4)
+///     .profiling(true)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// let heatmap = machine.heatmap();
+/// assert_eq!(heatmap[0], CellStats::default());
+/// assert_eq!(heatmap[1].reads(), 7);
+/// assert_eq!(heatmap[1].writes(), 4);
+/// ```
+///
+/// # See Also
+///
+/// * [`VirtualMachine::heatmap`](crate::VirtualMachine::heatmap)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CellStats {
+    reads:  usize,
+    writes: usize,
+}
+
+impl CellStats {
+    /// The number of times the cell was read
+    #[must_use]
+    pub const fn reads(&self) -> usize {
+        self.reads
+    }
+
+    /// The number of times the cell was written
+    #[must_use]
+    pub const fn writes(&self) -> usize {
+        self.writes
+    }
+
+    /// The total number of reads and writes of the cell
+    #[must_use]
+    pub const fn accesses(&self) -> usize {
+        self.reads + self.writes
+    }
+
+    /// Record a read of the cell
+    pub(crate) fn record_read(&mut self) {
+        self.reads += 1;
+    }
+
+    /// Record a write to the cell
+    pub(crate) fn record_write(&mut self) {
+        self.writes += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let stats = CellStats::default();
+        assert_eq!(stats.reads(), 0);
+        assert_eq!(stats.writes(), 0);
+        assert_eq!(stats.accesses(), 0);
+    }
+
+    #[test]
+    fn test_record() {
+        let mut stats = CellStats::default();
+        stats.record_read();
+        stats.record_read();
+        stats.record_write();
+        assert_eq!(stats.reads(), 2);
+        assert_eq!(stats.writes(), 1);
+        assert_eq!(stats.accesses(), 3);
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::Byte;
+
+/// How the value of a cell is shown
+///
+/// Cells hold bytes from 0 to 255, but many classic algorithms decrement
+/// cells below zero and are easier to follow when such cells show up as
+/// negative numbers. The signed view reads cells in two's complement, see
+/// [`Byte::as_i8`].
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     CellView,
+/// };
+///
+/// let cell = Byte::from(254);
+///
+/// assert_eq!(CellView::Unsigned.value(&cell), 254);
+/// assert_eq!(CellView::Signed.value(&cell), -2);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CellView {
+    /// Values from 0 to 255
+    #[default]
+    Unsigned,
+    /// Values from -128 to 127
+    Signed,
+}
+
+impl CellView {
+    /// The value of `cell` in this view
+    #[must_use]
+    pub fn value(self, cell: &Byte) -> i16 {
+        match self {
+            Self::Unsigned => i16::from(u8::from(cell)),
+            Self::Signed => i16::from(cell.as_i8()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value() {
+        let cell = Byte::from(127);
+        assert_eq!(CellView::Unsigned.value(&cell), 127);
+        assert_eq!(CellView::Signed.value(&cell), 127);
+
+        let cell = Byte::from(128);
+        assert_eq!(CellView::Unsigned.value(&cell), 128);
+        assert_eq!(CellView::Signed.value(&cell), -128);
+    }
+}
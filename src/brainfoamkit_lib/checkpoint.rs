@@ -0,0 +1,385 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::Path,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    config::Config,
+    CheckpointError,
+    Dialect,
+    FileError,
+    Program,
+    VMReader,
+    VMSnapshot,
+    VMWriter,
+    VirtualMachine,
+};
+
+/// The version of the checkpoint format written by [`Checkpoint::save`]
+const FORMAT_VERSION: u32 = 2;
+
+/// The state of a long run, saved so that the run can continue later
+///
+/// A checkpoint holds the source of the program, its dialect, the
+/// configuration it runs with, a [`VMSnapshot`] of the machine and the number
+/// of bytes the program has read. [`resume`](Self::resume) puts a machine back
+/// into that state, so a run that was stopped continues where the checkpoint
+/// was taken instead of starting over.
+///
+/// The output of the resumed run starts right after the
+/// [`bytes_written`](crate::Counters::bytes_written) of the snapshot: the
+/// output written before the checkpoint, followed by the output of the resumed
+/// run, is the output of an uninterrupted run.
+///
+/// The snapshot keeps the loops the program is in and the procedures of the
+/// `pbrain` dialect it defined and is calling. The state of the random bytes
+/// of the `random` dialect and the auxiliary tape of the `stack` dialect are
+/// not part of a snapshot, so programs in those dialects cannot be
+/// checkpointed.
+///
+/// Checkpoints are saved as JSON, conventionally in a file ending in `.ckpt`.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     config::Config,
+///     CaptureWriter,
+///     Checkpoint,
+///     Dialect,
+///     Program,
+///     VecReader,
+///     VirtualMachine,
+/// };
+///
+/// let source = ",.,.,.";
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(VecReader::new(b"abc".to_vec()))
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from(source))
+///     .build()
+///     .unwrap();
+/// while machine.bytes_written() < 1 {
+///     machine.execute_instruction().unwrap();
+/// }
+/// let checkpoint = Checkpoint::take(
+///     &machine,
+///     source,
+///     Dialect::Standard,
+///     Config::default(),
+///     1,
+/// )
+/// .unwrap();
+///
+/// // Resume on the same input: the byte read before the checkpoint is skipped
+/// let checkpoint = Checkpoint::from_json(&checkpoint.to_json()).unwrap();
+/// let mut machine = checkpoint
+///     .resume(VecReader::new(b"abc".to_vec()), CaptureWriter::default())
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(machine.output_device().data, b"bc");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Checkpoint {
+    /// The version of the format of the checkpoint
+    pub version:    u32,
+    /// The source of the program
+    pub program:    String,
+    /// The dialect the program is written in
+    pub dialect:    Dialect,
+    /// The configuration the program runs with, with every setting of the
+    /// environment and the configuration files already merged in
+    pub config:     Config,
+    /// The number of bytes the program read before the checkpoint
+    pub input_read: usize,
+    /// The state of the machine
+    pub snapshot:   VMSnapshot,
+}
+
+impl Checkpoint {
+    /// Save the state of a running machine
+    ///
+    /// # Arguments
+    ///
+    /// * `machine` - The machine to save, which should be stopped between two
+    ///   instructions with its output flushed
+    /// * `source` - The source of the program of the machine
+    /// * `dialect` - The dialect the program is written in
+    /// * `config` - The configuration the machine was built with
+    /// * `input_read` - The number of bytes the machine has read so far
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program is written in the `random` or the
+    /// `stack` dialect.
+    pub fn take<R, W>(
+        machine: &VirtualMachine<R, W>,
+        source: &str,
+        dialect: Dialect,
+        config: Config,
+        input_read: usize,
+    ) -> Result<Self, CheckpointError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        if matches!(dialect, Dialect::Random | Dialect::Stack) {
+            return Err(CheckpointError::UnsupportedDialect(dialect));
+        }
+
+        Ok(Self {
+            version: FORMAT_VERSION,
+            program: source.to_string(),
+            dialect,
+            config,
+            input_read,
+            snapshot: machine.snapshot(),
+        })
+    }
+
+    /// Build a machine in the state of the checkpoint
+    ///
+    /// `input` is expected to deliver the same input as the run that was
+    /// checkpointed: the bytes read before the checkpoint are read from it
+    /// and dropped, so the program continues with the first byte it had not
+    /// read yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input device of the program
+    /// * `output` - The output device for the output after the checkpoint
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program is written in the `random` or the
+    /// `stack` dialect, the configuration of the checkpoint
+    /// is not supported, the brackets of the program are not balanced or the
+    /// snapshot does not fit the program.
+    pub fn resume<R, W>(
+        &self,
+        mut input: R,
+        output: W,
+    ) -> Result<VirtualMachine<R, W>, CheckpointError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        if matches!(self.dialect, Dialect::Random | Dialect::Stack) {
+            return Err(CheckpointError::UnsupportedDialect(self.dialect));
+        }
+        for _ in 0..self.input_read {
+            if input.read().is_err() {
+                break;
+            }
+        }
+
+        let builder = VirtualMachine::builder()
+            .input_device(input)
+            .output_device(output)
+            .program(Program::parse(&self.program, self.dialect));
+        let mut machine = self.config.apply(builder)?.build()?;
+        machine.restore(&self.snapshot)?;
+        Ok(machine)
+    }
+
+    /// The checkpoint as JSON
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Read a checkpoint from JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a checkpoint, or a checkpoint of a
+    /// newer version of the format.
+    pub fn from_json(json: &str) -> Result<Self, CheckpointError> {
+        let checkpoint: Self = serde_json::from_str(json)?;
+        if checkpoint.version > FORMAT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(checkpoint.version));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Write the checkpoint to a file
+    ///
+    /// The checkpoint is written to a temporary file next to `path` first,
+    /// which then replaces `path`, so a run that is stopped while it saves a
+    /// checkpoint leaves the previous checkpoint intact.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), FileError> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let temporary = Path::new(&temporary);
+
+        fs::write(temporary, self.to_json())
+            .and_then(|()| fs::rename(temporary, path))
+            .map_err(|source| FileError::Write {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Read a checkpoint from a file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or does not hold a
+    /// checkpoint.
+    pub fn load(path: &Path) -> Result<Self, CheckpointError> {
+        let json = fs::read_to_string(path).map_err(|source| FileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_json(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        CaptureWriter,
+        EofBehavior,
+        VecReader,
+    };
+
+    const SOURCE: &str = ",[.>+<,]>.";
+
+    fn machine(input: &[u8], config: &Config) -> VirtualMachine<VecReader, CaptureWriter> {
+        let builder = VirtualMachine::builder()
+            .input_device(VecReader::new(input.to_vec()))
+            .output_device(CaptureWriter::default())
+            .program(Program::from(SOURCE));
+        config.apply(builder).unwrap().build().unwrap()
+    }
+
+    #[test]
+    fn test_output_stitches_together() {
+        let config = Config {
+            eof: Some(EofBehavior::Zero),
+            ..Config::default()
+        };
+        let mut uninterrupted = machine(b"abcd", &config);
+        uninterrupted.run().unwrap();
+
+        for steps in 0..uninterrupted.steps() {
+            let mut machine = machine(b"abcd", &config);
+            let mut input_read = 0;
+            for _ in 0..steps {
+                if machine.get_instruction() == Some(crate::Instruction::InputValue) {
+                    input_read += 1;
+                }
+                machine.execute_instruction().unwrap();
+            }
+            let checkpoint = Checkpoint::take(
+                &machine,
+                SOURCE,
+                Dialect::Standard,
+                config.clone(),
+                input_read,
+            )
+            .unwrap();
+            let mut output = machine.output_device().data.clone();
+
+            let mut resumed = checkpoint
+                .resume(VecReader::new(b"abcd".to_vec()), CaptureWriter::default())
+                .unwrap();
+            resumed.run().unwrap();
+            output.extend(&resumed.output_device().data);
+
+            assert_eq!(output, uninterrupted.output_device().data, "{steps}");
+            assert_eq!(resumed.snapshot(), uninterrupted.snapshot());
+        }
+    }
+
+    #[test]
+    fn test_resume_inside_procedure() {
+        // Procedure 1 prints one more than the cell, and is called twice
+        let source = "+(+.-)::";
+        let build = || {
+            VirtualMachine::builder()
+                .input_device(VecReader::new(Vec::new()))
+                .output_device(CaptureWriter::default())
+                .program(Program::parse(source, Dialect::Pbrain))
+                .build()
+                .unwrap()
+        };
+        let mut uninterrupted = build();
+        uninterrupted.run().unwrap();
+        assert_eq!(uninterrupted.output_device().data, [2, 2]);
+
+        for steps in 0..uninterrupted.steps() {
+            let mut machine = build();
+            for _ in 0..steps {
+                machine.execute_instruction().unwrap();
+            }
+            let checkpoint =
+                Checkpoint::take(&machine, source, Dialect::Pbrain, Config::default(), 0)
+                    .unwrap();
+            let mut output = machine.output_device().data.clone();
+
+            let mut resumed = checkpoint
+                .resume(VecReader::new(Vec::new()), CaptureWriter::default())
+                .unwrap();
+            resumed.run().unwrap();
+            output.extend(&resumed.output_device().data);
+
+            assert_eq!(output, uninterrupted.output_device().data, "{steps}");
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join("run.ckpt");
+        let mut machine = machine(b"xy", &Config::default());
+        machine.execute_instruction().unwrap();
+        let checkpoint =
+            Checkpoint::take(&machine, SOURCE, Dialect::Standard, Config::default(), 1).unwrap();
+
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+        assert!(!directory.path().join("run.ckpt.tmp").exists());
+    }
+
+    #[test]
+    fn test_unsupported_checkpoints() {
+        let machine = machine(b"", &Config::default());
+
+        assert!(matches!(
+            Checkpoint::take(&machine, SOURCE, Dialect::Stack, Config::default(), 0),
+            Err(CheckpointError::UnsupportedDialect(Dialect::Stack))
+        ));
+
+        let mut checkpoint =
+            Checkpoint::take(&machine, SOURCE, Dialect::Standard, Config::default(), 0).unwrap();
+        checkpoint.version = FORMAT_VERSION + 1;
+        assert!(matches!(
+            Checkpoint::from_json(&checkpoint.to_json()),
+            Err(CheckpointError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            Checkpoint::from_json("{}"),
+            Err(CheckpointError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,486 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Project configuration
+//!
+//! A project pins the semantics of the interpreter in a `brainfoamkit.toml`
+//! file, so that its programs behave the same for everyone who runs them:
+//!
+//! ```toml
+//! profile = "modern"
+//! tape-size = 65536
+//! cell-width = 8
+//! eof = "zero"
+//! io-mode = "bytes"
+//! theme = "light"
+//! ```
+//!
+//! Every setting is optional. Settings are taken from, in increasing order of
+//! precedence, the configuration file, `BRAINFOAMKIT_*` environment variables
+//! and the command line. The environment variables are named after the keys of
+//! the file, such as `BRAINFOAMKIT_TAPE_SIZE` and `BRAINFOAMKIT_EOF`.
+
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    BehaviorProfile,
+    ConfigError,
+    EofBehavior,
+    FileError,
+    IoMode,
+    VMReader,
+    VMWriter,
+    VirtualMachineBuilder,
+};
+
+/// The name of the configuration file looked for by [`Config::discover`]
+pub const FILE_NAME: &str = "brainfoamkit.toml";
+
+/// The prefix of the environment variables read by [`Config::from_env`]
+pub const ENV_PREFIX: &str = "BRAINFOAMKIT_";
+
+/// Settings for the interpreter and the tools built on it
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     config::Config,
+///     EofBehavior,
+///     Program,
+///     VecReader,
+///     VirtualMachine,
+/// };
+///
+/// let file = Config::from_toml("tape-size = 16\neof = \"zero\"").unwrap();
+/// let cli = Config {
+///     tape_size: Some(8),
+///     ..Config::default()
+/// };
+/// let config = file.merge(cli);
+///
+/// let builder = VirtualMachine::builder()
+///     .input_device(VecReader::new(Vec::new()))
+///     .program(Program::from("+,"));
+/// let mut machine = config.apply(builder).unwrap().build().unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(machine.length(), 8);
+/// assert_eq!(machine.eof_behavior(), EofBehavior::Zero);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// The behavior profile the other interpreter settings are applied on top
+    /// of
+    pub profile:            Option<BehaviorProfile>,
+    /// The number of cells on the tape
+    pub tape_size:          Option<usize>,
+    /// The number of bits in a cell. Only 8-bit cells are supported.
+    pub cell_width:         Option<u32>,
+    /// What `,` does when there is no more input
+    pub eof:                Option<EofBehavior>,
+    /// Whether `,` and `.` exchange bytes or decimal numbers
+    pub io_mode:            Option<IoMode>,
+    /// How aggressively programs are optimized before they run. This setting
+    /// is accepted so that projects can pin it, but has no effect yet.
+    pub optimization_level: Option<u8>,
+    /// The name of the color theme of the visualizer
+    pub theme:              Option<String>,
+}
+
+impl Config {
+    /// Parse a configuration from the contents of a TOML file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contents are not valid TOML, contain unknown
+    /// keys or values of the wrong type.
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Read a configuration file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid
+    /// configuration.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|source| FileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&contents).map_err(|source| ConfigError::InvalidFile {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Find the nearest configuration file
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The directory to start looking in
+    ///
+    /// # Returns
+    ///
+    /// The path of the first `brainfoamkit.toml` found in `start` or one of
+    /// its ancestors, or `None` if there is none.
+    #[must_use]
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|directory| directory.join(FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Load the nearest configuration file of the current directory
+    ///
+    /// # Returns
+    ///
+    /// The configuration of the nearest `brainfoamkit.toml`, or the default
+    /// configuration if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current directory is not accessible or the
+    /// configuration file is not valid.
+    pub fn discover() -> Result<Self, ConfigError> {
+        let current = env::current_dir().map_err(ConfigError::CurrentDirectory)?;
+        Self::find(&current).map_or_else(|| Ok(Self::default()), |path| Self::load(&path))
+    }
+
+    /// Read the configuration from `BRAINFOAMKIT_*` environment variables
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a variable holds an invalid value.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(env::vars())
+    }
+
+    /// Read the configuration from a set of environment variables
+    ///
+    /// Variables without the `BRAINFOAMKIT_` prefix are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::config::Config;
+    ///
+    /// let config = Config::from_vars([
+    ///     ("BRAINFOAMKIT_TAPE_SIZE", "100"),
+    ///     ("HOME", "/home/user"),
+    /// ])
+    /// .unwrap();
+    ///
+    /// assert_eq!(config.tape_size, Some(100));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a variable with the prefix is unknown or holds an
+    /// invalid value.
+    pub fn from_vars<I, K, V>(vars: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut config = Self::default();
+        for (key, value) in vars {
+            let Some(name) = key.as_ref().strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let (key, value) = (key.as_ref(), value.as_ref());
+            match name {
+                "PROFILE" => config.profile = Some(value.parse().map_err(invalid(key, value))?),
+                "TAPE_SIZE" => {
+                    config.tape_size = Some(value.parse().map_err(invalid(key, value))?);
+                }
+                "CELL_WIDTH" => {
+                    config.cell_width = Some(value.parse().map_err(invalid(key, value))?);
+                }
+                "EOF" => config.eof = Some(value.parse().map_err(invalid(key, value))?),
+                "IO_MODE" => config.io_mode = Some(value.parse().map_err(invalid(key, value))?),
+                "OPTIMIZATION_LEVEL" => {
+                    config.optimization_level = Some(value.parse().map_err(invalid(key, value))?);
+                }
+                "THEME" => config.theme = Some(value.to_string()),
+                _ => return Err(ConfigError::UnknownSetting(key.to_string())),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// The configuration of the current project and environment
+    ///
+    /// This is the nearest configuration file, overridden by the environment
+    /// variables. Command line flags can be merged on top with
+    /// [`merge`](Config::merge).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration file or an environment variable
+    /// is not valid.
+    pub fn resolve() -> Result<Self, ConfigError> {
+        Ok(Self::discover()?.merge(Self::from_env()?))
+    }
+
+    /// Override this configuration with the settings of `other`
+    ///
+    /// # Returns
+    ///
+    /// A configuration with every setting of `other` that is set, and the
+    /// settings of `self` for the rest.
+    #[must_use]
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            profile:            other.profile.or(self.profile),
+            tape_size:          other.tape_size.or(self.tape_size),
+            cell_width:         other.cell_width.or(self.cell_width),
+            eof:                other.eof.or(self.eof),
+            io_mode:            other.io_mode.or(self.io_mode),
+            optimization_level: other.optimization_level.or(self.optimization_level),
+            theme:              other.theme.or(self.theme),
+        }
+    }
+
+    /// Check that the interpreter supports the configuration
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error if the tape size is zero.
+    /// * Returns an error if the cell width is not 8 bits.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.tape_size == Some(0) {
+            return Err(ConfigError::EmptyTape);
+        }
+        if let Some(width) = self.cell_width.filter(|width| *width != 8) {
+            return Err(ConfigError::UnsupportedCellWidth(width));
+        }
+        Ok(())
+    }
+
+    /// Apply the interpreter settings to a `VirtualMachineBuilder`
+    ///
+    /// The behavior profile is applied first, so that the tape size and the
+    /// end of input behavior override those of the profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration is not valid, see
+    /// [`validate`](Config::validate).
+    pub fn apply<R, W>(
+        &self,
+        mut builder: VirtualMachineBuilder<R, W>,
+    ) -> Result<VirtualMachineBuilder<R, W>, ConfigError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        self.validate()?;
+        if let Some(profile) = self.profile {
+            builder = builder.behavior_profile(profile);
+        }
+        if let Some(tape_size) = self.tape_size {
+            builder = builder.tape_size(tape_size);
+        }
+        if let Some(eof) = self.eof {
+            builder = builder.eof_behavior(eof);
+        }
+        if let Some(mode) = self.io_mode {
+            builder = builder.io_mode(mode);
+        }
+        Ok(builder)
+    }
+}
+
+/// Wrap the error of parsing the value of an environment variable
+fn invalid<E>(key: &str, value: &str) -> impl FnOnce(E) -> ConfigError
+where
+    E: Error + Send + Sync + 'static,
+{
+    let (key, value) = (key.to_string(), value.to_string());
+    move |source| ConfigError::InvalidValue {
+        key,
+        value,
+        source: Box::new(source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        MockReader,
+        TapeEdge,
+        VirtualMachine,
+    };
+
+    #[test]
+    fn test_from_toml() {
+        let config = Config::from_toml(
+            r#"
+            profile = "strict"
+            tape-size = 100
+            cell-width = 8
+            eof = "max"
+            io-mode = "numeric"
+            optimization-level = 2
+            theme = "light"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                profile:            Some(BehaviorProfile::Strict),
+                tape_size:          Some(100),
+                cell_width:         Some(8),
+                eof:                Some(EofBehavior::Max),
+                io_mode:            Some(IoMode::Numeric),
+                optimization_level: Some(2),
+                theme:              Some(String::from("light")),
+            }
+        );
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_invalid_toml() {
+        assert!(Config::from_toml("tape-size = \"big\"").is_err());
+        assert!(Config::from_toml("eof = \"minus-one\"").is_err());
+        assert!(Config::from_toml("tape_size = 10").is_err());
+    }
+
+    #[test]
+    fn test_find() {
+        let root = tempdir().unwrap();
+        let nested = root.path().join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(Config::find(&nested), None);
+
+        let path = root.path().join(FILE_NAME);
+        fs::write(&path, "tape-size = 5").unwrap();
+        assert_eq!(Config::find(&nested), Some(path.clone()));
+        assert_eq!(Config::load(&path).unwrap().tape_size, Some(5));
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let config = Config::from_vars([
+            ("BRAINFOAMKIT_PROFILE", "classic"),
+            ("BRAINFOAMKIT_TAPE_SIZE", "10"),
+            ("BRAINFOAMKIT_CELL_WIDTH", "8"),
+            ("BRAINFOAMKIT_EOF", "zero"),
+            ("BRAINFOAMKIT_IO_MODE", "numeric"),
+            ("BRAINFOAMKIT_OPTIMIZATION_LEVEL", "1"),
+            ("BRAINFOAMKIT_THEME", "dark"),
+            ("PATH", "/bin"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.profile, Some(BehaviorProfile::Classic30k));
+        assert_eq!(config.tape_size, Some(10));
+        assert_eq!(config.cell_width, Some(8));
+        assert_eq!(config.eof, Some(EofBehavior::Zero));
+        assert_eq!(config.io_mode, Some(IoMode::Numeric));
+        assert_eq!(config.optimization_level, Some(1));
+        assert_eq!(config.theme.as_deref(), Some("dark"));
+
+        assert!(Config::from_vars([("BRAINFOAMKIT_TAPE_SIZE", "lots")]).is_err());
+        assert!(Config::from_vars([("BRAINFOAMKIT_COLOUR", "red")]).is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let file = Config {
+            tape_size: Some(10),
+            eof: Some(EofBehavior::Zero),
+            ..Config::default()
+        };
+        let cli = Config {
+            tape_size: Some(20),
+            theme: Some(String::from("light")),
+            ..Config::default()
+        };
+
+        let merged = file.merge(cli);
+        assert_eq!(merged.tape_size, Some(20));
+        assert_eq!(merged.eof, Some(EofBehavior::Zero));
+        assert_eq!(merged.theme.as_deref(), Some("light"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Config::default().validate().is_ok());
+        assert!(Config {
+            cell_width: Some(16),
+            ..Config::default()
+        }
+        .validate()
+        .is_err());
+        assert!(Config {
+            tape_size: Some(0),
+            ..Config::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let config = Config {
+            tape_size: Some(3),
+            eof: Some(EofBehavior::Max),
+            io_mode: Some(IoMode::Numeric),
+            ..Config::default()
+        };
+        let builder = VirtualMachine::builder().input_device(MockReader {
+            data: Cursor::new(Vec::new()),
+        });
+        let machine = config.apply(builder).unwrap().build().unwrap();
+
+        assert_eq!(machine.length(), 3);
+        assert_eq!(machine.eof_behavior(), EofBehavior::Max);
+        assert_eq!(machine.io_mode(), IoMode::Numeric);
+
+        let config = Config {
+            profile: Some(BehaviorProfile::ModernDynamic),
+            tape_size: Some(3),
+            ..Config::default()
+        };
+        let builder = VirtualMachine::builder().input_device(MockReader {
+            data: Cursor::new(Vec::new()),
+        });
+        let machine = config.apply(builder).unwrap().build().unwrap();
+
+        assert_eq!(machine.length(), 3);
+        assert_eq!(machine.tape_edge(), TapeEdge::Grow);
+        assert_eq!(machine.eof_behavior(), EofBehavior::Zero);
+    }
+}
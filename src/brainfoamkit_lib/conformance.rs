@@ -0,0 +1,111 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Conformance tests against well-known brainfuck programs
+//!
+//! These programs are widely used to check that an interpreter agrees with
+//! the community conventions: 8-bit cells that wrap around, a tape of at least
+//! 30000 cells and `,` leaving the current cell unchanged at the end of the
+//! input. Most of them come from Daniel B. Cristofani's collection of test
+//! programs.
+//!
+//! The tests that run a program on top of the `dbfi` self-interpreter take a
+//! long time and are ignored by default. Run them with
+//! `cargo test -- --ignored`.
+
+use crate::{
+    CaptureWriter,
+    Program,
+    VecReader,
+    VirtualMachine,
+};
+
+/// Prints "Hello World!\n" and exercises cell wrapping, moving left of the
+/// starting cell and some unusual loop structures
+const HELLO_WORLD: &str = r">++++++++[-<+++++++++>]<.>>+>-[+]++>++>+++[>[->+++<<+++>]<<]>-----.>->
++++..+++.>-.<<+[>[+>+]>>]<--------------.>>.+++.------.--------.>+.>+.";
+
+/// Prints "H\n" if brackets are matched correctly, including brackets
+/// surrounded by other characters and a loop at the very start of the program
+const BRACKETS: &str = r#"[]++++++++++[>>+>+>++++++[<<+<+++>>>-]<<<<-]
+"A*$";?@![#>>+<<]>[>>]<<<<[>++<[-]]>.>."#;
+
+/// Prints "#\n" if the tape has at least 30000 cells
+const TAPE_SIZE: &str = r"++++[>++++++<-]>[>+++++>+++++++<<-]>>++++<[[>[[>>+<<-]<]>>>-]>-[>+>+<<-]>]
++++++[>+++++++<<++>-]>.<<.";
+
+/// Prints its own source code, without the line break
+const QUINE: &str = r"-->+++>+>+>+>+++++>++>++>->+++>++>+>>>>>>>>>>>>>>>>->++++>>>>->+++>+++>+++>+++>+++>+++>+>+>>>->->>++++>+>>>>->>++++>+>+>>->->++>++>++>++++>+>++>->++>++++>+>+>++>++>->->++>++>++++>+>+>>>>>->>->>++++>++>++>++++>>>>>->>>>>+++>->++++>->->->+++>>>+>+>+++>+>++++>>+++>->>>>>->>>++++>++>++>+>+++>->++++>>->->+++>+>+++>+>++++>>>+++>->++++>>->->++>++++>++>++++>>++[-[->>+[>]++[<]<]>>+[>]<--[++>++++>]+[<]<<++]>>>[>]++++>++++[--[+>+>++++<<[-->>--<<[->-<[--->>+<<[+>+++<[+>>++<<]]]]]]>+++[>+++++++++++++++<-]>--.<<<]";
+
+/// A self-interpreter that reads a program, a `!` and the program's input
+const DBFI: &str = r">>>+[[-]>>[-]++>+>+++++++[<++++>>++<-]++>>+>+>+++++[>++>++++++<<-]+>>>,<++[[>[
+->>]<[>>]<<-]<[<]<+>>[>]>[<+>-[[<+>-]>]<[[[-]<]++<-[<+++++++++>[<->-]>>]>>]]<<
+]<]<[[<]>[[>]>>[>>]+[<<]<[<]<+>>-]>[>]+[->>]<<<<[[<<]<[<]+<<[+>+<<-[>-->+<<-[>
++<[>>+<<-]]]>[<+>-]<]++>>-->[>]>>[>>]]<<[>>+<[[<]<]>[[<<]<[<]+[-<+>>-[<<+>++>-
+[<->[<<+>>-]]]<[>+<-]>]>[>]>]>[>>]>>]<<[>>+>>+>>]<<[->>>>>>>>]<<[>.>>>>>>>]<<[
+>->>>>>]<<[>,>>>]<<[>+>]<<[+<<]<]";
+
+fn run(source: &str, input: &[u8]) -> Vec<u8> {
+    let mut machine = VirtualMachine::builder()
+        .input_device(VecReader::new(input.to_vec()))
+        .output_device(CaptureWriter::default())
+        .program(Program::from(source))
+        .build()
+        .unwrap();
+    machine.run().unwrap();
+
+    machine.output_device().data.clone()
+}
+
+#[test]
+fn test_hello_world() {
+    assert_eq!(run(HELLO_WORLD, b""), b"Hello World!\n");
+}
+
+#[test]
+fn test_brackets() {
+    assert_eq!(run(BRACKETS, b""), b"H\n");
+}
+
+#[test]
+fn test_tape_size() {
+    assert_eq!(run(TAPE_SIZE, b""), b"#\n");
+}
+
+#[test]
+fn test_quine() {
+    assert_eq!(run(QUINE, b""), QUINE.as_bytes());
+}
+
+#[test]
+fn test_cell_wrapping() {
+    assert_eq!(run("-.+.+.", b""), [255, 0, 1]);
+    assert_eq!(run("-[-]+[+]>+.", b""), [1]);
+}
+
+#[test]
+fn test_end_of_input_leaves_cell_unchanged() {
+    assert_eq!(run("+,.,.", b"A"), b"AA");
+    assert_eq!(run("+++,.", b""), [3]);
+}
+
+#[test]
+fn test_echo_until_end_of_input() {
+    assert_eq!(run(",[.[-],]", b"echo\n"), b"echo\n");
+}
+
+#[test]
+#[ignore = "runs a program on top of a self-interpreter"]
+fn test_dbfi_hello_world() {
+    let input = format!("{HELLO_WORLD}!");
+
+    assert_eq!(run(DBFI, input.as_bytes()), b"Hello World!\n");
+}
+
+#[test]
+#[ignore = "runs a program on top of a self-interpreter"]
+fn test_dbfi_input() {
+    assert_eq!(run(DBFI, b",[.[-],]!nested"), b"nested");
+}
@@ -0,0 +1,345 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use alloc::{
+    vec,
+    vec::Vec,
+};
+
+use crate::{
+    Byte,
+    Instruction,
+    ParseError,
+    Program,
+    Tape,
+};
+
+/// A minimal virtual machine that only needs `alloc`
+///
+/// The `CoreMachine` is available without the `std` feature, for example on
+/// embedded targets or in WASM environments without a file system or
+/// terminal. It runs the standard instructions with the default behavior of
+/// the [`VirtualMachine`](crate::VirtualMachine): cells wrap around on
+/// overflow, the pointer wraps around at both ends of the tape and reading
+/// past the end of the input leaves the current cell unchanged. Every other
+/// instruction, such as those of `pbrain`, is skipped.
+///
+/// Instead of readers and writers, the machine is given two closures: `input`
+/// returns the next byte of input, or `None` at the end of the input, and
+/// `output` receives every byte the program writes. The cells are kept in a
+/// [`Tape`], a `Vec<Byte>` unless the machine is created
+/// [`with_tape`](Self::with_tape). Loops find their matching bracket by
+/// scanning the program, so the machine allocates nothing besides the tape.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CoreMachine,
+///     Program,
+/// };
+///
+/// let mut input = b"a".iter().copied();
+/// let mut output = Vec::new();
+///
+/// let mut machine = CoreMachine::new(
+///     Program::from(",-."),
+///     16,
+///     || input.next(),
+///     |byte| output.push(byte),
+/// )
+/// .unwrap();
+/// machine.run();
+/// drop(machine);
+///
+/// assert_eq!(output, b"`");
+/// ```
+pub struct CoreMachine<I, O, T = Vec<Byte>>
+where
+    I: FnMut() -> Option<u8>,
+    O: FnMut(u8),
+    T: Tape,
+{
+    program:         Program,
+    tape:            T,
+    memory_pointer:  usize,
+    program_counter: usize,
+    steps:           usize,
+    input:           I,
+    output:          O,
+}
+
+impl<I, O> CoreMachine<I, O>
+where
+    I: FnMut() -> Option<u8>,
+    O: FnMut(u8),
+{
+    /// Create a machine with a tape of `tape_size` cells, all holding zero
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The program to run
+    /// * `tape_size` - The number of cells on the tape
+    /// * `input` - Returns the next byte of input, or `None` at its end
+    /// * `output` - Receives every byte the program writes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the brackets of the program are not balanced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tape_size` is zero.
+    pub fn new(
+        program: Program,
+        tape_size: usize,
+        input: I,
+        output: O,
+    ) -> Result<Self, ParseError> {
+        assert!(tape_size > 0, "the tape needs at least one cell");
+        program.jump_table()?;
+
+        Ok(Self::with_tape(
+            program,
+            vec![Byte::default(); tape_size],
+            input,
+            output,
+        ))
+    }
+}
+
+impl<I, O, T> CoreMachine<I, O, T>
+where
+    I: FnMut() -> Option<u8>,
+    O: FnMut(u8),
+    T: Tape,
+{
+    /// Create a machine that keeps its cells in `tape`
+    ///
+    /// This is a `const fn`, so that a machine on a
+    /// [`FixedTape`](crate::FixedTape) can be set up without running any code.
+    /// For the same reason, the brackets of the program are not checked:
+    /// check them with [`Program::jump_table`] first. A `[` without a partner
+    /// ends the program when it skips its loop, and a `]` without a partner
+    /// does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The program to run
+    /// * `tape` - The cells, with the memory pointer on the first one
+    /// * `input` - Returns the next byte of input, or `None` at its end
+    /// * `output` - Receives every byte the program writes
+    pub const fn with_tape(program: Program, tape: T, input: I, output: O) -> Self {
+        Self {
+            program,
+            tape,
+            memory_pointer: 0,
+            program_counter: 0,
+            steps: 0,
+            input,
+            output,
+        }
+    }
+
+    /// The cells of the tape
+    #[must_use]
+    pub fn tape(&self) -> &[Byte] {
+        self.tape.cells()
+    }
+
+    /// The position of the memory pointer on the tape
+    #[must_use]
+    pub const fn memory_pointer(&self) -> usize {
+        self.memory_pointer
+    }
+
+    /// The position of the next instruction in the program
+    #[must_use]
+    pub const fn program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    /// The number of instructions executed so far
+    #[must_use]
+    pub const fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Whether every instruction of the program has been executed
+    #[must_use]
+    pub fn is_halted(&self) -> bool {
+        self.program.get_instruction(self.program_counter).is_none()
+    }
+
+    /// Execute the next instruction
+    ///
+    /// # Returns
+    ///
+    /// `false` if the machine had already halted, `true` otherwise.
+    pub fn execute_instruction(&mut self) -> bool {
+        let Some(instruction) = self.program.get_instruction(self.program_counter) else {
+            return false;
+        };
+        let cells = self.tape.cells_mut();
+        let length = cells.len();
+        let cell = &mut cells[self.memory_pointer];
+
+        match instruction {
+            Instruction::IncrementPointer => {
+                self.memory_pointer = (self.memory_pointer + 1) % length;
+            }
+            Instruction::DecrementPointer => {
+                self.memory_pointer = (self.memory_pointer + length - 1) % length;
+            }
+            Instruction::IncrementValue => cell.increment(),
+            Instruction::DecrementValue => cell.decrement(),
+            Instruction::OutputValue => (self.output)(u8::from(&*cell)),
+            Instruction::InputValue => {
+                if let Some(value) = (self.input)() {
+                    *cell = Byte::from(value);
+                }
+            }
+            Instruction::JumpForward if *cell == Byte::default() => {
+                self.program_counter = self
+                    .matching_bracket()
+                    .unwrap_or_else(|| self.program.length().unwrap_or(0).saturating_sub(1));
+            }
+            Instruction::JumpBackward if *cell != Byte::default() => {
+                self.program_counter = self.matching_bracket().unwrap_or(self.program_counter);
+            }
+            _ => {}
+        }
+        self.program_counter += 1;
+        self.steps += 1;
+
+        true
+    }
+
+    /// Execute instructions until the program halts
+    pub fn run(&mut self) {
+        while self.execute_instruction() {}
+    }
+
+    /// Find the partner of the bracket under the program counter
+    fn matching_bracket(&self) -> Option<usize> {
+        let forward =
+            self.program.get_instruction(self.program_counter)? == Instruction::JumpForward;
+        let mut depth = 0_usize;
+        let mut position = self.program_counter;
+        loop {
+            match self.program.get_instruction(position)? {
+                Instruction::JumpForward if forward => depth += 1,
+                Instruction::JumpBackward if !forward => depth += 1,
+                Instruction::JumpForward | Instruction::JumpBackward => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(position);
+                    }
+                }
+                _ => {}
+            }
+            position = if forward {
+                position + 1
+            } else {
+                position.checked_sub(1)?
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FixedTape;
+
+    fn run(source: &str, input: &[u8], tape_size: usize) -> (Vec<u8>, Vec<u8>) {
+        let mut input = input.iter().copied();
+        let mut output = Vec::new();
+        let mut machine = CoreMachine::new(
+            Program::from(source),
+            tape_size,
+            || input.next(),
+            |byte| output.push(byte),
+        )
+        .unwrap();
+        machine.run();
+        let tape = machine.tape().iter().map(u8::from).collect();
+        drop(machine);
+        (output, tape)
+    }
+
+    #[test]
+    fn test_hello_world() {
+        let (output, _) = run(
+            "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.\
+             ------.--------.>>+.>++.",
+            &[],
+            30000,
+        );
+
+        assert_eq!(output, b"Hello World!\n");
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let (_, tape) = run("-<+", &[], 3);
+
+        assert_eq!(tape, vec![255, 0, 1]);
+    }
+
+    #[test]
+    fn test_input() {
+        let (output, tape) = run(",.,.,", b"hi", 1);
+
+        assert_eq!(output, b"hi");
+        assert_eq!(
+            tape,
+            vec![b'i'],
+            "The end of the input leaves the cell unchanged"
+        );
+    }
+
+    #[test]
+    fn test_steps_and_halt() {
+        let mut machine = CoreMachine::new(Program::from("+[-]"), 1, || None, |_| ()).unwrap();
+
+        assert!(!machine.is_halted());
+        machine.run();
+        assert!(machine.is_halted());
+        assert_eq!(machine.steps(), 4);
+        assert!(!machine.execute_instruction());
+    }
+
+    #[test]
+    fn test_unbalanced_program() {
+        assert!(CoreMachine::new(Program::from("[+"), 1, || None, |_| ()).is_err());
+
+        let mut machine =
+            CoreMachine::with_tape(Program::from("[+"), FixedTape::<1>::new(), || None, |_| ());
+        machine.run();
+        assert_eq!(machine.tape(), [Byte::default()]);
+
+        let mut machine =
+            CoreMachine::with_tape(Program::from("+]+"), FixedTape::<1>::new(), || None, |_| ());
+        machine.run();
+        assert_eq!(machine.tape(), [Byte::from(2)]);
+    }
+
+    #[test]
+    fn test_fixed_tape() {
+        let mut output = Vec::new();
+        let mut machine = CoreMachine::with_tape(
+            Program::from("+[>++[>+++<-]<-]>>.<<<."),
+            FixedTape::<4>::new(),
+            || None,
+            |byte| output.push(byte),
+        );
+        machine.run();
+
+        assert_eq!(machine.memory_pointer(), 3);
+        drop(machine);
+        assert_eq!(output, [6, 0]);
+    }
+}
@@ -0,0 +1,474 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::Path,
+};
+
+use crate::{
+    vm_reader::VMReader,
+    vm_writer::VMWriter,
+    FileError,
+    Instruction,
+    Program,
+    VMError,
+    VirtualMachine,
+};
+
+/// How often a loop recorded by the [`CoverageRecorder`] ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopCoverage {
+    /// The position of the loop's `[` in the `Program`
+    pub entry:      usize,
+    /// The number of times the loop was reached
+    pub reached:    usize,
+    /// The number of times the loop was entered, rather than skipped because
+    /// its cell was zero
+    pub entered:    usize,
+    /// The number of times the body of the loop ran, over all the times it
+    /// was entered
+    pub iterations: usize,
+}
+
+impl LoopCoverage {
+    /// The number of times the loop was skipped because its cell was zero
+    #[must_use]
+    pub const fn skipped(&self) -> usize {
+        self.reached - self.entered
+    }
+}
+
+/// Records which instructions of a program execute while driving a
+/// `VirtualMachine`
+///
+/// The recorder counts the executions of every instruction, and for every
+/// loop how often it was reached, entered and repeated. Comments are not
+/// counted as code. The counts can be shown as a listing of the source that
+/// marks the instructions that never executed, with
+/// [`annotate`](Self::annotate), or exported in the lcov format used by
+/// coverage viewers, with [`lcov`](Self::lcov).
+///
+/// Like the [`Profiler`](crate::Profiler), the recorder executes the machine
+/// itself, with [`step`](Self::step) and [`run`](Self::run).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CoverageRecorder,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let program = Program::from("++[>+<-]>[-]");
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(program.clone())
+///     .build()
+///     .unwrap();
+///
+/// let mut coverage = CoverageRecorder::new(&program);
+/// coverage.run(&mut machine).unwrap();
+///
+/// assert_eq!(coverage.hits()[4], 2);
+/// assert_eq!(coverage.loops()[0].iterations, 2);
+/// assert_eq!((coverage.covered(), coverage.total()), (12, 12));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageRecorder {
+    instructions: Vec<Instruction>,
+    hits:         Vec<usize>,
+    loops:        Vec<LoopCoverage>,
+}
+
+impl CoverageRecorder {
+    /// Record the coverage of `program`, which the machine must be running
+    #[must_use]
+    pub fn new(program: &Program) -> Self {
+        let instructions: Vec<Instruction> = (0..program.length().unwrap_or(0))
+            .filter_map(|index| program.get_instruction(index))
+            .collect();
+        let loops = instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| **instruction == Instruction::JumpForward)
+            .map(|(entry, _)| LoopCoverage {
+                entry,
+                reached: 0,
+                entered: 0,
+                iterations: 0,
+            })
+            .collect();
+        Self {
+            hits: vec![0; instructions.len()],
+            instructions,
+            loops,
+        }
+    }
+
+    /// Execute a single instruction of `machine` and count it
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction).
+    /// An instruction that fails is not counted.
+    pub fn step<R, W>(&mut self, machine: &mut VirtualMachine<R, W>) -> Result<(), VMError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        let position = machine.program_counter();
+        let depth = machine.loop_stack().len();
+        machine.execute_instruction()?;
+
+        let Some(hits) = self.hits.get_mut(position) else {
+            return Ok(());
+        };
+        *hits += 1;
+        let entry = match self.instructions[position] {
+            Instruction::JumpForward => {
+                let entered = machine.loop_stack().len() > depth;
+                if let Some(coverage) = self.loop_mut(position) {
+                    coverage.reached += 1;
+                    if entered {
+                        coverage.entered += 1;
+                        coverage.iterations += 1;
+                    }
+                }
+                return Ok(());
+            }
+            // A `]` that does not leave its loop starts another iteration
+            Instruction::JumpBackward if machine.loop_stack().len() == depth => {
+                machine.loop_stack().last().map(|frame| frame.entry())
+            }
+            _ => None,
+        };
+        if let Some(coverage) = entry.and_then(|entry| self.loop_mut(entry)) {
+            coverage.iterations += 1;
+        }
+        Ok(())
+    }
+
+    /// Execute `machine` until it halts, counting every instruction
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction).
+    /// The counts recorded up to the error are kept.
+    pub fn run<R, W>(&mut self, machine: &mut VirtualMachine<R, W>) -> Result<(), VMError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        while !machine.is_halted() {
+            self.step(machine)?;
+        }
+        Ok(())
+    }
+
+    /// The number of times every instruction executed, in program order
+    #[must_use]
+    pub fn hits(&self) -> &[usize] {
+        &self.hits
+    }
+
+    /// The coverage of every loop, in the order of their `[`
+    #[must_use]
+    pub fn loops(&self) -> &[LoopCoverage] {
+        &self.loops
+    }
+
+    /// The number of instructions, other than comments, that executed at
+    /// least once
+    #[must_use]
+    pub fn covered(&self) -> usize {
+        self.code()
+            .filter(|&position| self.hits[position] > 0)
+            .count()
+    }
+
+    /// The number of instructions other than comments
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.code().count()
+    }
+
+    /// A listing of `source`, the text the program was parsed from, with the
+    /// coverage of every line
+    ///
+    /// Every line starts with the largest number of times any of its
+    /// instructions executed, `#####` if none of them did, or `-` if it only
+    /// holds comments. Lines where only some of the instructions executed are
+    /// followed by a line marking the others with `^`. The listing ends with
+    /// the counts of every loop.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CoverageRecorder,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let source = "clear\n[-]+";
+    /// let program = Program::from(source);
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(program.clone())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut coverage = CoverageRecorder::new(&program);
+    /// coverage.run(&mut machine).unwrap();
+    ///
+    /// assert_eq!(
+    ///     coverage.annotate(source),
+    ///     "        - | clear\n        1 | [-]+\n          |  ^^\n\nLoops:\n    \
+    ///      2:1 reached 1, entered 0, 0 iteration(s)\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn annotate(&self, source: &str) -> String {
+        let mut listing = String::new();
+        let mut position = 0;
+        for line in source.split('\n') {
+            let length = line.chars().count();
+            let code: Vec<usize> = (position..position + length)
+                .filter(|&index| self.is_code(index))
+                .collect();
+            let count = code.iter().map(|&index| self.hits[index]).max();
+            let column = match count {
+                None => String::from("-"),
+                Some(0) => String::from("#####"),
+                Some(count) => count.to_string(),
+            };
+            let _ = writeln!(listing, "{column:>9} | {line}");
+
+            if count.is_some_and(|count| count > 0)
+                && code.iter().any(|&index| self.hits[index] == 0)
+            {
+                let markers: String = (position..position + length)
+                    .map(|index| {
+                        if self.is_code(index) && self.hits[index] == 0 {
+                            '^'
+                        } else {
+                            ' '
+                        }
+                    })
+                    .collect();
+                let _ = writeln!(listing, "          | {}", markers.trim_end());
+            }
+            position += length + 1;
+        }
+
+        if !self.loops.is_empty() {
+            listing.push_str("\nLoops:\n");
+            for coverage in &self.loops {
+                let (line, column) = location(source, coverage.entry);
+                let _ = writeln!(
+                    listing,
+                    "{:>7} reached {}, entered {}, {} iteration(s)",
+                    format!("{line}:{column}"),
+                    coverage.reached,
+                    coverage.entered,
+                    coverage.iterations
+                );
+            }
+        }
+        listing
+    }
+
+    /// The coverage in the lcov tracefile format, for `source` read from
+    /// `source_file`
+    ///
+    /// Every line with code gets the largest number of times any of its
+    /// instructions executed. Every loop is a branch with two outcomes, being
+    /// entered and being skipped.
+    #[must_use]
+    pub fn lcov(&self, source_file: &Path, source: &str) -> String {
+        let mut tracefile = format!("TN:\nSF:{}\n", source_file.display());
+
+        let mut branches_hit = 0;
+        for (block, coverage) in self.loops.iter().enumerate() {
+            let (line, _) = location(source, coverage.entry);
+            for (branch, taken) in [coverage.entered, coverage.skipped()]
+                .into_iter()
+                .enumerate()
+            {
+                let taken = if coverage.reached == 0 {
+                    String::from("-")
+                } else {
+                    if taken > 0 {
+                        branches_hit += 1;
+                    }
+                    taken.to_string()
+                };
+                let _ = writeln!(tracefile, "BRDA:{line},{block},{branch},{taken}");
+            }
+        }
+        let _ = writeln!(tracefile, "BRF:{}", self.loops.len() * 2);
+        let _ = writeln!(tracefile, "BRH:{branches_hit}");
+
+        let mut lines: Vec<(usize, usize)> = Vec::new();
+        for position in self.code() {
+            let (line, _) = location(source, position);
+            match lines.last_mut() {
+                Some((last, hits)) if *last == line => *hits = (*hits).max(self.hits[position]),
+                _ => lines.push((line, self.hits[position])),
+            }
+        }
+        for (line, hits) in &lines {
+            let _ = writeln!(tracefile, "DA:{line},{hits}");
+        }
+        let _ = writeln!(tracefile, "LF:{}", lines.len());
+        let _ = writeln!(
+            tracefile,
+            "LH:{}",
+            lines.iter().filter(|(_, hits)| *hits > 0).count()
+        );
+        tracefile.push_str("end_of_record\n");
+        tracefile
+    }
+
+    /// Write the coverage to a file in the lcov tracefile format, see
+    /// [`lcov`](Self::lcov)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn export_lcov<P: AsRef<Path>>(
+        &self,
+        path: P,
+        source_file: &Path,
+        source: &str,
+    ) -> Result<(), FileError> {
+        let path = path.as_ref();
+        fs::write(path, self.lcov(source_file, source)).map_err(|source| FileError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    fn is_code(&self, position: usize) -> bool {
+        self.instructions
+            .get(position)
+            .is_some_and(|instruction| *instruction != Instruction::NoOp)
+    }
+
+    fn code(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.instructions.len()).filter(|&position| self.is_code(position))
+    }
+
+    fn loop_mut(&mut self, entry: usize) -> Option<&mut LoopCoverage> {
+        self.loops
+            .binary_search_by_key(&entry, |coverage| coverage.entry)
+            .ok()
+            .map(|index| &mut self.loops[index])
+    }
+}
+
+/// The line and column of the character at `index` in `source`, counting
+/// from 1
+pub(crate) fn location(source: &str, index: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    for character in source.chars().take(index) {
+        if character == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::vm_reader::MockReader;
+
+    fn covered(source: &str, input: &[u8]) -> CoverageRecorder {
+        let program = Program::from(source);
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(input.to_vec()),
+            })
+            .program(program.clone())
+            .build()
+            .unwrap();
+        let mut coverage = CoverageRecorder::new(&program);
+        coverage.run(&mut machine).unwrap();
+        coverage
+    }
+
+    #[test]
+    fn test_hits() {
+        let coverage = covered("+++[>+<-] comment", b"");
+
+        assert_eq!(&coverage.hits()[..9], [1, 1, 1, 1, 3, 3, 3, 3, 3]);
+        assert_eq!(coverage.hits().len(), 17);
+        assert_eq!(coverage.covered(), 9);
+        assert_eq!(coverage.total(), 9);
+    }
+
+    #[test]
+    fn test_loops() {
+        // The last loop is skipped, since its cell was cleared
+        let coverage = covered(",[>+++[-]<[-]]>[+]", b"\x01");
+        let loops = coverage.loops();
+
+        assert_eq!(loops.len(), 4);
+        assert_eq!(
+            loops[0],
+            LoopCoverage {
+                entry:      1,
+                reached:    1,
+                entered:    1,
+                iterations: 1,
+            }
+        );
+        assert_eq!((loops[1].reached, loops[1].entered), (1, 1));
+        assert_eq!(loops[1].iterations, 3);
+        assert_eq!((loops[2].reached, loops[2].skipped()), (1, 0));
+        assert_eq!(
+            (loops[3].reached, loops[3].entered, loops[3].skipped()),
+            (1, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_annotate() {
+        let source = ",[.]\n>+";
+        let coverage = covered(source, b"\x00");
+
+        assert_eq!(coverage.covered(), 4);
+        assert_eq!(coverage.total(), 6);
+        assert_eq!(
+            coverage.annotate(source),
+            "        1 | ,[.]\n          |   ^^\n        1 | >+\n\nLoops:\n    1:2 reached 1, \
+             entered 0, 0 iteration(s)\n"
+        );
+    }
+
+    #[test]
+    fn test_lcov() {
+        let source = "+[-]\n\n[>]\n";
+        let coverage = covered(source, b"");
+
+        assert_eq!(
+            coverage.lcov(Path::new("loops.bf"), source),
+            "TN:\nSF:loops.bf\nBRDA:1,0,0,1\nBRDA:1,0,1,0\nBRDA:3,1,0,0\nBRDA:3,1,1,1\nBRF:4\nBRH:\
+             2\nDA:1,1\nDA:3,1\nLF:2\nLH:2\nend_of_record\n"
+        );
+    }
+}
@@ -0,0 +1,332 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Crash bundles for reporting bugs in the interpreter
+//!
+//! A panic while a program runs is a bug in this crate, never in the program.
+//! To make it easy to report, [`guard`] runs a machine and, if anything
+//! panics, saves a [`CrashBundle`] to a new temporary directory before the
+//! panic goes on: the program and its fingerprint, the dialect and the
+//! configuration it ran with, the last instructions it executed and a
+//! snapshot of the machine. [`install_panic_hook`] lets the bundle record
+//! where the panic happened.
+
+use std::{
+    any::Any,
+    env,
+    fs,
+    panic::{
+        self,
+        AssertUnwindSafe,
+    },
+    path::PathBuf,
+    process,
+    sync::Mutex,
+    time::{
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    config::Config,
+    Dialect,
+    FileError,
+    HistoryEntry,
+    Program,
+    VMReader,
+    VMSnapshot,
+    VMWriter,
+    VirtualMachine,
+};
+
+/// The number of executed instructions a machine should keep in its
+/// [`History`](crate::History) for a crash bundle, see
+/// [`VirtualMachineBuilder::history_capacity`](crate::VirtualMachineBuilder::history_capacity)
+pub const TRACE_LENGTH: usize = 64;
+
+/// The version of the crash bundle format
+const FORMAT_VERSION: u32 = 1;
+
+/// Where the last panic happened, as recorded by the hook of
+/// [`install_panic_hook`]
+static PANIC_LOCATION: Mutex<Option<String>> = Mutex::new(None);
+
+/// An executed instruction in a [`CrashBundle`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TraceEvent {
+    /// The position of the instruction in the program
+    pub program_counter: usize,
+    /// The instruction, as it is displayed
+    pub instruction:     String,
+    /// The position of the memory pointer before the instruction
+    pub memory_pointer:  usize,
+    /// The value of the current cell before the instruction
+    pub cell:            u8,
+}
+
+impl From<&HistoryEntry> for TraceEvent {
+    fn from(entry: &HistoryEntry) -> Self {
+        Self {
+            program_counter: entry.program_counter(),
+            instruction:     entry.instruction().to_string(),
+            memory_pointer:  entry.memory_pointer(),
+            cell:            u8::from(&entry.cell()),
+        }
+    }
+}
+
+/// The state of a machine when the interpreter panicked
+///
+/// Bundles are saved as JSON, see [`save`](Self::save).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CrashBundle {
+    /// The version of the format of the bundle
+    pub version:     u32,
+    /// The message of the panic
+    pub message:     String,
+    /// The file, line and column of the panic, if the hook of
+    /// [`install_panic_hook`] recorded it
+    pub location:    Option<String>,
+    /// The [fingerprint](crate::Program::fingerprint) of the program, in
+    /// hexadecimal
+    pub fingerprint: String,
+    /// The source of the program
+    pub program:     String,
+    /// The dialect the program is written in
+    pub dialect:     Dialect,
+    /// The configuration the program ran with
+    pub config:      Config,
+    /// The last instructions executed, oldest first
+    pub trace:       Vec<TraceEvent>,
+    /// The machine when the panic happened
+    pub snapshot:    VMSnapshot,
+}
+
+impl CrashBundle {
+    /// Collect the state of `machine`
+    ///
+    /// The trace holds the instructions of the [`History`](crate::History) of
+    /// the machine, so it is empty unless the machine was built with a
+    /// history capacity.
+    ///
+    /// # Arguments
+    ///
+    /// * `machine` - The machine that was running
+    /// * `source` - The source of its program
+    /// * `dialect` - The dialect of the program
+    /// * `config` - The configuration the machine was built with
+    /// * `message` - What went wrong
+    #[must_use]
+    pub fn capture<R, W>(
+        machine: &VirtualMachine<R, W>,
+        source: &str,
+        dialect: Dialect,
+        config: Config,
+        message: String,
+    ) -> Self
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        Self {
+            version: FORMAT_VERSION,
+            message,
+            location: PANIC_LOCATION
+                .lock()
+                .ok()
+                .and_then(|location| location.clone()),
+            fingerprint: format!("{:016x}", Program::parse(source, dialect).fingerprint()),
+            program: source.to_string(),
+            dialect,
+            config,
+            trace: machine.history().iter().map(TraceEvent::from).collect(),
+            snapshot: machine.snapshot(),
+        }
+    }
+
+    /// The bundle as JSON
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Write the bundle to `crash.json` in a new directory in the temporary
+    /// directory of the system
+    ///
+    /// # Returns
+    ///
+    /// The path of the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or the file cannot be created.
+    pub fn save(&self) -> Result<PathBuf, FileError> {
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos());
+        let directory =
+            env::temp_dir().join(format!("brainfoamkit-crash-{}-{created}", process::id()));
+        let path = directory.join("crash.json");
+        fs::create_dir_all(&directory)
+            .and_then(|()| fs::write(&path, self.to_json()))
+            .map_err(|source| FileError::Write {
+                path: path.clone(),
+                source,
+            })?;
+        Ok(path)
+    }
+}
+
+/// Record where panics happen for the crash bundles of [`guard`]
+///
+/// The previous panic hook still runs, so the panic is printed as usual.
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if let (Some(location), Ok(mut recorded)) = (info.location(), PANIC_LOCATION.lock()) {
+            *recorded = Some(location.to_string());
+        }
+        previous(info);
+    }));
+}
+
+/// Run `run` on `machine`, and save a crash bundle if it panics
+///
+/// The path of the bundle is printed to STDERR with a request to report the
+/// bug, then the panic goes on.
+///
+/// # Arguments
+///
+/// * `machine` - The machine to run
+/// * `source` - The source of its program
+/// * `dialect` - The dialect of the program
+/// * `config` - The configuration the machine was built with
+/// * `run` - What to do with the machine, usually
+///   [`VirtualMachine::run`](crate::VirtualMachine::run)
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     config::Config,
+///     crash,
+///     Dialect,
+///     Program,
+///     StopReason,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("+++"))
+///     .build()
+///     .unwrap();
+///
+/// let stop = crash::guard(
+///     &mut machine,
+///     "+++",
+///     Dialect::Standard,
+///     &Config::default(),
+///     |machine| machine.run(),
+/// )
+/// .unwrap();
+/// assert_eq!(stop, StopReason::Halted);
+/// ```
+pub fn guard<R, W, T, F>(
+    machine: &mut VirtualMachine<R, W>,
+    source: &str,
+    dialect: Dialect,
+    config: &Config,
+    run: F,
+) -> T
+where
+    R: VMReader,
+    W: VMWriter,
+    F: FnOnce(&mut VirtualMachine<R, W>) -> T,
+{
+    match panic::catch_unwind(AssertUnwindSafe(|| run(machine))) {
+        Ok(result) => result,
+        Err(payload) => {
+            let bundle =
+                CrashBundle::capture(machine, source, dialect, config.clone(), message(&*payload));
+            match bundle.save() {
+                Ok(path) => eprintln!(
+                    "The interpreter crashed. Please report this bug with the crash bundle saved \
+                     to {}",
+                    path.display()
+                ),
+                Err(error) => eprintln!("The interpreter crashed, and {error}"),
+            }
+            panic::resume_unwind(payload)
+        }
+    }
+}
+
+/// The message of a panic
+fn message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_saves_bundle() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(std::io::stdin())
+            .program(Program::from("++>+"))
+            .history_capacity(TRACE_LENGTH)
+            .build()
+            .unwrap();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            guard(
+                &mut machine,
+                "++>+",
+                Dialect::Standard,
+                &Config::default(),
+                |machine| {
+                    machine.run().unwrap();
+                    panic!("cell out of order")
+                },
+            )
+        }));
+        assert!(result.is_err());
+
+        let prefix = format!("brainfoamkit-crash-{}-", process::id());
+        let directories: Vec<PathBuf> = fs::read_dir(env::temp_dir())
+            .unwrap()
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with(&prefix))
+            })
+            .collect();
+        assert_eq!(directories.len(), 1);
+        let json = fs::read_to_string(directories[0].join("crash.json")).unwrap();
+        fs::remove_dir_all(&directories[0]).unwrap();
+
+        let bundle: CrashBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(bundle.message, "cell out of order");
+        assert_eq!(bundle.program, "++>+");
+        assert_eq!(bundle.trace.len(), 4);
+        assert_eq!(bundle.trace[2].instruction, "INCPTR");
+        assert_eq!(bundle.trace[1].cell, 1);
+        assert_eq!(bundle.snapshot, machine.snapshot());
+    }
+}
@@ -0,0 +1,252 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+
+use crate::{
+    Byte,
+    CellView,
+};
+
+/// The number of cells on either side of the memory pointer shown by the
+/// `Display` implementation of `DebugDump`
+const WINDOW_RADIUS: usize = 8;
+
+/// A callback invoked by the `VirtualMachine` for every `#` instruction
+///
+/// See [`VirtualMachineBuilder::debug_hook`](crate::VirtualMachineBuilder::debug_hook).
+pub type DebugHook = Box<dyn FnMut(&DebugDump) + Send>;
+
+/// A view of the `VirtualMachine` at a `#` instruction
+///
+/// When a program written in the [`Debug`](enum.Dialect.html#variant.Debug)
+/// dialect executes `#`, the `VirtualMachine` passes a `DebugDump` to its
+/// [`DebugHook`]. The default hook prints the dump to STDERR.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{
+///     Arc,
+///     Mutex,
+/// };
+///
+/// use brainfoamkit_lib::{
+///     Dialect,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let dumps = Arc::new(Mutex::new(Vec::new()));
+/// let recorder = Arc::clone(&dumps);
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::parse("+++>+#", Dialect::Debug))
+///     .tape_size(4)
+///     .debug_hook(move |dump| recorder.lock().unwrap().push(dump.to_string()))
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(
+///     dumps.lock().unwrap().as_slice(),
+///     ["# at instruction 5 after 5 steps\n  0: 3 [1] 0 0"]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugDump<'a> {
+    tape:            &'a [Byte],
+    memory_pointer:  usize,
+    program_counter: usize,
+    steps:           usize,
+    view:            CellView,
+}
+
+impl<'a> DebugDump<'a> {
+    pub(crate) const fn new(
+        tape: &'a [Byte],
+        memory_pointer: usize,
+        program_counter: usize,
+        steps: usize,
+    ) -> Self {
+        Self {
+            tape,
+            memory_pointer,
+            program_counter,
+            steps,
+            view: CellView::Unsigned,
+        }
+    }
+
+    /// Show the cells in `view` when the dump is displayed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CellView,
+    ///     Dialect,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::parse("->--#", Dialect::Debug))
+    ///     .tape_size(2)
+    ///     .debug_hook(|dump| {
+    ///         assert!(dump
+    ///             .with_view(CellView::Signed)
+    ///             .to_string()
+    ///             .ends_with("0: -1 [-2]"))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    /// ```
+    #[must_use]
+    pub const fn with_view(mut self, view: CellView) -> Self {
+        self.view = view;
+        self
+    }
+
+    /// How the cells are shown when the dump is displayed
+    #[must_use]
+    pub const fn view(&self) -> CellView {
+        self.view
+    }
+
+    /// The whole tape of the machine
+    #[must_use]
+    pub const fn tape(&self) -> &'a [Byte] {
+        self.tape
+    }
+
+    /// The position of the memory pointer
+    #[must_use]
+    pub const fn memory_pointer(&self) -> usize {
+        self.memory_pointer
+    }
+
+    /// The position of the `#` instruction in the program
+    #[must_use]
+    pub const fn program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    /// The number of instructions executed before the `#`
+    #[must_use]
+    pub const fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// The cells within `radius` cells of the memory pointer
+    ///
+    /// # Arguments
+    ///
+    /// * `radius` - The number of cells to include on either side of the
+    ///   pointer
+    ///
+    /// # Returns
+    ///
+    /// The index of the first cell of the window, and the cells themselves.
+    /// The window is cut short at the ends of the tape.
+    #[must_use]
+    pub fn window(&self, radius: usize) -> (usize, &'a [Byte]) {
+        let start = self.memory_pointer.saturating_sub(radius);
+        let end = self
+            .memory_pointer
+            .saturating_add(radius)
+            .saturating_add(1)
+            .min(self.tape.len());
+
+        (start, &self.tape[start..end])
+    }
+}
+
+impl Display for DebugDump<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "# at instruction {} after {} steps",
+            self.program_counter, self.steps
+        )?;
+        let (start, cells) = self.window(WINDOW_RADIUS);
+        write!(f, "  {start}:")?;
+        for (index, cell) in cells.iter().enumerate() {
+            let value = self.view.value(cell);
+            if start + index == self.memory_pointer {
+                write!(f, " [{value}]")?;
+            } else {
+                write!(f, " {value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `DebugHook` used unless another one is configured
+pub(crate) fn print_to_stderr() -> DebugHook {
+    Box::new(|dump| eprintln!("{dump}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tape(values: &[u8]) -> Vec<Byte> {
+        values.iter().map(|value| Byte::from(*value)).collect()
+    }
+
+    #[test]
+    fn test_window() {
+        let tape = tape(&[1, 2, 3, 4, 5]);
+        let dump = DebugDump::new(&tape, 2, 0, 0);
+
+        assert_eq!(dump.window(1), (1, &tape[1..4]));
+        assert_eq!(dump.window(5), (0, &tape[..]));
+        assert_eq!(dump.window(0), (2, &tape[2..3]));
+    }
+
+    #[test]
+    fn test_display() {
+        let values: Vec<u8> = (0..20).collect();
+        let tape = tape(&values);
+        let dump = DebugDump::new(&tape, 10, 7, 42);
+
+        assert_eq!(
+            dump.to_string(),
+            "# at instruction 7 after 42 steps\n  2: 2 3 4 5 6 7 8 9 [10] 11 12 13 14 15 16 17 18"
+        );
+    }
+
+    #[test]
+    fn test_signed_display() {
+        let tape = tape(&[255, 128, 127]);
+        let dump = DebugDump::new(&tape, 1, 0, 0).with_view(CellView::Signed);
+
+        assert_eq!(dump.view(), CellView::Signed);
+        assert_eq!(
+            dump.to_string(),
+            "# at instruction 0 after 0 steps\n  0: -1 [-128] 127"
+        );
+    }
+
+    #[test]
+    fn test_getters() {
+        let tape = tape(&[9]);
+        let dump = DebugDump::new(&tape, 0, 3, 4);
+
+        assert_eq!(dump.tape(), &tape[..]);
+        assert_eq!(dump.memory_pointer(), 0);
+        assert_eq!(dump.program_counter(), 3);
+        assert_eq!(dump.steps(), 4);
+    }
+}
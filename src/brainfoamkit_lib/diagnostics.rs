@@ -0,0 +1,327 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    ops::ControlFlow,
+    str::FromStr,
+};
+
+use crate::ParseError;
+
+/// Something unusual the `VirtualMachine` did without stopping
+///
+/// The machine carries on after every one of these by default. A
+/// [`DiagnosticsSink`] can report them, or stop the machine at the first
+/// one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// The memory pointer moved off an end of the tape and came back at the
+    /// other end
+    PointerWrapped {
+        /// The cell the pointer moved from
+        from: usize,
+        /// The cell the pointer moved to
+        to:   usize,
+    },
+    /// A cell overflowed or underflowed and wrapped around
+    CellWrapped {
+        /// The position of the cell
+        cell:  usize,
+        /// The value of the cell after wrapping
+        value: u8,
+    },
+    /// A byte outside of the ASCII range was read and ended the input
+    InputNotAscii(u8),
+    /// The input was interrupted, which ended it
+    InputInterrupted,
+}
+
+impl Display for WarningKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PointerWrapped { from, to } => {
+                write!(f, "pointer wrapped from cell {from} to cell {to}")
+            }
+            Self::CellWrapped { cell, value } => {
+                write!(f, "cell {cell} wrapped around to {value}")
+            }
+            Self::InputNotAscii(byte) => {
+                write!(
+                    f,
+                    "input byte {byte} > 127 ignored, treated as end of input"
+                )
+            }
+            Self::InputInterrupted => write!(f, "input interrupted, treated as end of input"),
+        }
+    }
+}
+
+/// A [`WarningKind`] with the place it happened at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Warning {
+    /// The number of instructions executed before the one that warned
+    pub step:        usize,
+    /// The position of the instruction in the program
+    pub instruction: usize,
+    /// What happened
+    pub kind:        WarningKind,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "step {} (instruction {}): {}",
+            self.step, self.instruction, self.kind
+        )
+    }
+}
+
+/// Where the `VirtualMachine` sends its warnings
+///
+/// A sink is set with
+/// [`VirtualMachineBuilder::diagnostics_sink`](crate::VirtualMachineBuilder::diagnostics_sink).
+/// Any `FnMut(&Warning)` closure is a sink that never stops the machine. A
+/// [`WarningPolicy`] covers the common cases.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{
+///     Arc,
+///     Mutex,
+/// };
+///
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     Program,
+///     VirtualMachine,
+///     Warning,
+///     WarningKind,
+/// };
+///
+/// let warnings = Arc::new(Mutex::new(Vec::new()));
+/// let recorder = Arc::clone(&warnings);
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from("+<-."))
+///     .tape_size(2)
+///     .diagnostics_sink(move |warning: &Warning| {
+///         recorder.lock().unwrap().push(*warning)
+///     })
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// let warnings = warnings.lock().unwrap();
+/// assert_eq!(warnings.len(), 2);
+/// assert_eq!(warnings[0].step, 1);
+/// assert_eq!(
+///     warnings[0].kind,
+///     WarningKind::PointerWrapped { from: 0, to: 1 }
+/// );
+/// assert_eq!(
+///     warnings[1].kind,
+///     WarningKind::CellWrapped {
+///         cell:  1,
+///         value: 255,
+///     }
+/// );
+/// ```
+pub trait DiagnosticsSink: Send {
+    /// Receive a warning
+    ///
+    /// # Arguments
+    ///
+    /// * `warning` - What happened, and where
+    ///
+    /// # Returns
+    ///
+    /// [`ControlFlow::Break`] stops the machine with a
+    /// [`VMError::Warning`](crate::VMError::Warning), [`ControlFlow::Continue`]
+    /// lets it carry on.
+    fn warn(&mut self, warning: &Warning) -> ControlFlow<()>;
+}
+
+impl<F> DiagnosticsSink for F
+where
+    F: FnMut(&Warning) + Send,
+{
+    fn warn(&mut self, warning: &Warning) -> ControlFlow<()> {
+        self(warning);
+        ControlFlow::Continue(())
+    }
+}
+
+/// What the `VirtualMachine` does with its warnings
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     Program,
+///     VMError,
+///     VirtualMachine,
+///     WarningPolicy,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from("-."))
+///     .diagnostics_sink(WarningPolicy::Error)
+///     .build()
+///     .unwrap();
+///
+/// assert!(matches!(machine.run(), Err(VMError::Warning(_))));
+/// assert!(machine.output_device().data.is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WarningPolicy {
+    /// Drop every warning
+    #[default]
+    Ignore,
+    /// Print every warning to STDERR and carry on
+    Print,
+    /// Stop the machine at the first warning
+    Error,
+}
+
+impl DiagnosticsSink for WarningPolicy {
+    fn warn(&mut self, warning: &Warning) -> ControlFlow<()> {
+        match self {
+            Self::Ignore => ControlFlow::Continue(()),
+            Self::Print => {
+                eprintln!("warning: {warning}");
+                ControlFlow::Continue(())
+            }
+            Self::Error => ControlFlow::Break(()),
+        }
+    }
+}
+
+impl Display for WarningPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Ignore => "ignore",
+            Self::Print => "print",
+            Self::Error => "error",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for WarningPolicy {
+    type Err = ParseError;
+
+    /// Parse the name of a warning policy
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::WarningPolicy;
+    ///
+    /// assert_eq!(
+    ///     "print".parse::<WarningPolicy>().unwrap(),
+    ///     WarningPolicy::Print
+    /// );
+    /// assert!("panic".parse::<WarningPolicy>().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name does not match any policy.
+    fn from_str(name: &str) -> Result<Self, ParseError> {
+        match name {
+            "ignore" => Ok(Self::Ignore),
+            "print" => Ok(Self::Print),
+            "error" => Ok(Self::Error),
+            _ => Err(ParseError::UnknownName {
+                kind: "warning policy",
+                name: name.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        sync::{
+            Arc,
+            Mutex,
+        },
+    };
+
+    use super::*;
+    use crate::{
+        vm_reader::MockReader,
+        CaptureWriter,
+        Program,
+        VMError,
+        VirtualMachine,
+    };
+
+    #[test]
+    fn test_input_warnings() {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&warnings);
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(vec![b'A', 200]),
+            })
+            .output_device(CaptureWriter::default())
+            .program(Program::from(",.,."))
+            .diagnostics_sink(move |warning: &Warning| recorder.lock().unwrap().push(*warning))
+            .build()
+            .unwrap();
+        machine.run().unwrap();
+
+        assert_eq!(machine.output_device().data, b"AA");
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            [Warning {
+                step:        2,
+                instruction: 2,
+                kind:        WarningKind::InputNotAscii(200),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_error_policy() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(Vec::new()),
+            })
+            .output_device(CaptureWriter::default())
+            .program(Program::from("+.>>."))
+            .tape_size(2)
+            .diagnostics_sink(WarningPolicy::Error)
+            .build()
+            .unwrap();
+
+        let Err(VMError::Warning(warning)) = machine.run() else {
+            panic!("the pointer should have wrapped");
+        };
+        assert_eq!(warning.step, 3);
+        assert_eq!(warning.kind, WarningKind::PointerWrapped { from: 1, to: 0 });
+        assert_eq!(
+            warning.to_string(),
+            "step 3 (instruction 3): pointer wrapped from cell 1 to cell 0"
+        );
+        assert_eq!(machine.output_device().data, [1]);
+    }
+}
@@ -0,0 +1,198 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use alloc::string::ToString;
+use core::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    str::FromStr,
+};
+
+#[cfg(feature = "std")]
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Instruction,
+    ParseError,
+};
+
+/// The flavors of `BrainFuck` understood by the interpreter
+///
+/// A `Dialect` decides which characters of a source file are instructions.
+/// In the `Standard` dialect only the eight `BrainFuck` commands are
+/// instructions and every other character is a comment. The other dialects
+/// add extension instructions on top of the standard ones.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Dialect,
+///     Instruction,
+/// };
+///
+/// assert_eq!(Dialect::Standard.instruction('#'), Instruction::NoOp);
+/// assert_eq!(Dialect::Debug.instruction('#'), Instruction::DebugDump);
+/// assert_eq!(Dialect::Debug.instruction('+'), Instruction::IncrementValue);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "lowercase"))]
+pub enum Dialect {
+    /// The eight `BrainFuck` instructions
+    #[default]
+    Standard,
+    /// The standard instructions and `#`, which dumps the tape around the
+    /// memory pointer for debugging
+    Debug,
+    /// The standard instructions and the procedures of pbrain: `(` defines
+    /// the procedure numbered by the current value, `)` ends it and `:` calls
+    /// the procedure numbered by the current value. `@` stops the program.
+    Pbrain,
+    /// The standard instructions and `?`, which writes a random byte to the
+    /// current cell. See [`RandomByte`](crate::RandomByte).
+    Random,
+    /// The standard instructions and a stack of cells next to the tape: `@`
+    /// pushes the current cell onto the stack and `$` pops the top of the
+    /// stack into the current cell. See
+    /// [`AuxiliaryTape`](crate::AuxiliaryTape).
+    Stack,
+}
+
+impl Dialect {
+    /// Convert a character to an `Instruction` of this dialect
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - A character of the source code
+    ///
+    /// # Returns
+    ///
+    /// The `Instruction` for the character, or `NoOp` if the character is not
+    /// an instruction in this dialect
+    #[must_use]
+    pub const fn instruction(self, c: char) -> Instruction {
+        match (self, c) {
+            (Self::Debug, '#') => Instruction::DebugDump,
+            (Self::Pbrain, '(') => Instruction::DefineProcedure,
+            (Self::Pbrain, ')') => Instruction::EndProcedure,
+            (Self::Pbrain, ':') => Instruction::CallProcedure,
+            (Self::Pbrain, '@') => Instruction::Halt,
+            (Self::Random, '?') => Instruction::Extension('?'),
+            (Self::Stack, '@') => Instruction::Extension('@'),
+            (Self::Stack, '$') => Instruction::Extension('$'),
+            _ => Instruction::from_char(c),
+        }
+    }
+}
+
+impl Display for Dialect {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Standard => write!(f, "standard"),
+            Self::Debug => write!(f, "debug"),
+            Self::Pbrain => write!(f, "pbrain"),
+            Self::Random => write!(f, "random"),
+            Self::Stack => write!(f, "stack"),
+        }
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = ParseError;
+
+    /// Parse the name of a dialect
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Dialect;
+    ///
+    /// assert_eq!("debug".parse::<Dialect>().unwrap(), Dialect::Debug);
+    /// assert!("klingon".parse::<Dialect>().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name does not match any dialect.
+    fn from_str(name: &str) -> Result<Self, ParseError> {
+        match name {
+            "standard" => Ok(Self::Standard),
+            "debug" => Ok(Self::Debug),
+            "pbrain" => Ok(Self::Pbrain),
+            "random" => Ok(Self::Random),
+            "stack" => Ok(Self::Stack),
+            _ => Err(ParseError::UnknownName {
+                kind: "dialect",
+                name: name.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_dialect() {
+        for instruction in Instruction::all() {
+            let c = instruction.to_char().unwrap();
+            assert_eq!(Dialect::Standard.instruction(c), instruction);
+        }
+        assert_eq!(Dialect::Standard.instruction('#'), Instruction::NoOp);
+        assert_eq!(Dialect::default(), Dialect::Standard);
+    }
+
+    #[test]
+    fn test_debug_dialect() {
+        assert_eq!(Dialect::Debug.instruction('#'), Instruction::DebugDump);
+        assert_eq!(Dialect::Debug.instruction(']'), Instruction::JumpBackward);
+        assert_eq!(Dialect::Debug.instruction('a'), Instruction::NoOp);
+    }
+
+    #[test]
+    fn test_pbrain_dialect() {
+        assert_eq!(
+            Dialect::Pbrain.instruction('('),
+            Instruction::DefineProcedure
+        );
+        assert_eq!(Dialect::Pbrain.instruction(')'), Instruction::EndProcedure);
+        assert_eq!(Dialect::Pbrain.instruction(':'), Instruction::CallProcedure);
+        assert_eq!(Dialect::Pbrain.instruction('@'), Instruction::Halt);
+        assert_eq!(Dialect::Pbrain.instruction('#'), Instruction::NoOp);
+        assert_eq!(Dialect::Standard.instruction(':'), Instruction::NoOp);
+    }
+
+    #[test]
+    fn test_random_dialect() {
+        assert_eq!(
+            Dialect::Random.instruction('?'),
+            Instruction::Extension('?')
+        );
+        assert_eq!(Dialect::Random.instruction('.'), Instruction::OutputValue);
+        assert_eq!(Dialect::Standard.instruction('?'), Instruction::NoOp);
+    }
+
+    #[test]
+    fn test_dialect_names() {
+        for dialect in [
+            Dialect::Standard,
+            Dialect::Debug,
+            Dialect::Pbrain,
+            Dialect::Random,
+            Dialect::Stack,
+        ] {
+            assert_eq!(dialect.to_string().parse::<Dialect>().unwrap(), dialect);
+        }
+        assert!("".parse::<Dialect>().is_err());
+    }
+}
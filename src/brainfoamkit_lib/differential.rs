@@ -0,0 +1,216 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Differential testing of the `VirtualMachine`
+//!
+//! The functions in this module run a program on both the
+//! [`VirtualMachine`] and the
+//! [`reference`](mod@crate::reference) interpreter and check that they agree.
+//! Together with [`minimize::reduce`](crate::minimize::reduce), a
+//! disagreement can be shrunk to a small program for a bug report.
+
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    io::Cursor,
+};
+
+use thiserror::Error;
+
+use crate::{
+    reference,
+    CaptureWriter,
+    Limits,
+    MockReader,
+    Program,
+    VMError,
+    VirtualMachine,
+};
+
+/// The number of cells on the tape of both interpreters
+const TAPE_SIZE: usize = 30000;
+
+/// The number of instructions either interpreter executes before giving up
+const MAX_STEPS: usize = 10_000_000;
+
+/// A disagreement between the `VirtualMachine` and the reference interpreter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The two interpreters wrote different output
+    Output {
+        /// The output of the `VirtualMachine`
+        machine:   Vec<u8>,
+        /// The output of the reference interpreter
+        reference: Vec<u8>,
+    },
+    /// The two interpreters halted with a different value in a cell
+    Tape {
+        /// The index of the first cell that differs
+        cell:      usize,
+        /// The value of the cell on the `VirtualMachine`
+        machine:   u8,
+        /// The value of the cell on the reference interpreter
+        reference: u8,
+    },
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Output { machine, reference } => write!(
+                f,
+                "The virtual machine wrote {machine:?} but the reference interpreter wrote \
+                 {reference:?}"
+            ),
+            Self::Tape {
+                cell,
+                machine,
+                reference,
+            } => write!(
+                f,
+                "Cell {cell} holds {machine} on the virtual machine but {reference} on the \
+                 reference interpreter"
+            ),
+        }
+    }
+}
+
+impl Error for Mismatch {}
+
+/// The reason [`compare`] failed
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CompareError {
+    /// The two interpreters disagree
+    #[error(transparent)]
+    Mismatch(#[from] Mismatch),
+    /// Either interpreter failed to run the program
+    #[error(transparent)]
+    Run(#[from] VMError),
+}
+
+/// Cross-check the `VirtualMachine` against the reference interpreter
+///
+/// The program is run on both interpreters with the same input and a tape of
+/// 30000 cells. Their output and their final tapes must be identical.
+///
+/// # Arguments
+///
+/// * `program` - The program to run
+/// * `input` - The bytes the program reads, in order
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     differential,
+///     Program,
+/// };
+///
+/// let program = Program::from(",[->+>+<<]>>.");
+///
+/// differential::compare(&program, b"A").unwrap();
+/// ```
+///
+/// # Errors
+///
+/// * Returns [`CompareError::Mismatch`] describing the first difference if the
+///   two interpreters disagree.
+/// * Returns another [`CompareError`] if either interpreter fails to run the
+///   program, for example because its brackets are not balanced or it runs for
+///   more than ten million steps.
+pub fn compare(program: &Program, input: &[u8]) -> Result<(), CompareError> {
+    let expected = reference::run(program, input, TAPE_SIZE, MAX_STEPS)?;
+
+    let mut machine = VirtualMachine::builder()
+        .input_device(MockReader {
+            data: Cursor::new(input.to_vec()),
+        })
+        .output_device(CaptureWriter::default())
+        .program(program.clone())
+        .tape_size(TAPE_SIZE)
+        .limits(Limits::new().max_steps(MAX_STEPS))
+        .build()
+        .map_err(VMError::from)?;
+    machine.run()?;
+
+    let output = machine.output_device().data.clone();
+    if output != expected.output {
+        return Err(Mismatch::Output {
+            machine:   output,
+            reference: expected.output,
+        }
+        .into());
+    }
+
+    let tape = machine.tape().iter().map(u8::from);
+    if let Some((cell, (machine, reference))) = tape
+        .zip(expected.tape)
+        .enumerate()
+        .find(|(_, (machine, reference))| machine != reference)
+    {
+        return Err(Mismatch::Tape {
+            cell,
+            machine,
+            reference,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agreeing_programs() {
+        let programs = [
+            "",
+            "+++[>++<-]>.",
+            "-.>--.",
+            "<+.",
+            "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.",
+            ",[.[-],]",
+        ];
+        for program in programs {
+            compare(&Program::from(program), b"echo").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_unbalanced_program() {
+        let error = compare(&Program::from("+["), &[]).unwrap_err();
+
+        assert!(matches!(error, CompareError::Run(VMError::Program(_))));
+    }
+
+    #[test]
+    fn test_mismatch_display() {
+        let output = Mismatch::Output {
+            machine:   vec![1],
+            reference: vec![2],
+        };
+        assert_eq!(
+            output.to_string(),
+            "The virtual machine wrote [1] but the reference interpreter wrote [2]"
+        );
+
+        let tape = Mismatch::Tape {
+            cell:      3,
+            machine:   4,
+            reference: 5,
+        };
+        assert_eq!(
+            tape.to_string(),
+            "Cell 3 holds 4 on the virtual machine but 5 on the reference interpreter"
+        );
+    }
+}
@@ -0,0 +1,257 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    io::Stdout,
+    ops::{
+        Deref,
+        DerefMut,
+    },
+    sync::{
+        Arc,
+        Mutex,
+        MutexGuard,
+    },
+};
+
+use crate::{
+    vm_reader::VMReader,
+    vm_writer::VMWriter,
+    Byte,
+    InstructionHandler,
+    VirtualMachine,
+    VmContext,
+};
+
+/// The second tape of the [`Stack`](crate::Dialect::Stack) dialect
+///
+/// The auxiliary tape is a stack of cells next to the main tape. `@` pushes
+/// the value of the current cell onto it and `$` pops the top of the stack
+/// into the current cell, or zero once the stack is empty.
+///
+/// An `AuxiliaryTape` is a handle: clones share the same cells, so the host
+/// program can keep one to look at the stack while the machine runs. It is
+/// registered with
+/// [`VirtualMachineBuilder::auxiliary_tape`](crate::VirtualMachineBuilder::auxiliary_tape).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     AuxiliaryTape,
+///     CaptureWriter,
+///     Dialect,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let stack = AuxiliaryTape::new();
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::parse("+@++@>$.$.$.", Dialect::Stack))
+///     .auxiliary_tape(stack.clone())
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(machine.output_device().data, [3, 1, 0]);
+/// assert!(stack.is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AuxiliaryTape {
+    cells: Arc<Mutex<Vec<Byte>>>,
+}
+
+impl AuxiliaryTape {
+    /// Create an empty auxiliary tape
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Byte>> {
+        // The cells are valid even if a thread panicked while holding them
+        self.cells
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// The cells of the stack, from the bottom to the top
+    #[must_use]
+    pub fn cells(&self) -> Vec<Byte> {
+        self.lock().clone()
+    }
+
+    /// The number of cells on the stack
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether the stack is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Push a value onto the stack
+    pub fn push(&self, value: Byte) {
+        self.lock().push(value);
+    }
+
+    /// Pop the value on top of the stack
+    #[must_use]
+    pub fn pop(&self) -> Option<Byte> {
+        self.lock().pop()
+    }
+
+    /// The handler of `@`
+    pub(crate) fn push_handler(&self) -> Push {
+        Push(self.clone())
+    }
+
+    /// The handler of `$`
+    pub(crate) fn pop_handler(&self) -> Pop {
+        Pop(self.clone())
+    }
+}
+
+/// `@` pushes the current cell onto the auxiliary tape
+pub(crate) struct Push(AuxiliaryTape);
+
+impl InstructionHandler for Push {
+    fn execute(&mut self, vm: &mut VmContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.0.push(vm.cell());
+        Ok(())
+    }
+}
+
+/// `$` pops the auxiliary tape into the current cell
+pub(crate) struct Pop(AuxiliaryTape);
+
+impl InstructionHandler for Pop {
+    fn execute(&mut self, vm: &mut VmContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+        vm.set_cell(self.0.pop().unwrap_or_default());
+        Ok(())
+    }
+}
+
+/// A `VirtualMachine` with an [`AuxiliaryTape`]
+///
+/// A `DualTapeVM` is built with
+/// [`VirtualMachineBuilder::build_dual_tape`](crate::VirtualMachineBuilder::build_dual_tape)
+/// and runs programs of the [`Stack`](crate::Dialect::Stack) dialect. It
+/// dereferences to the `VirtualMachine` it wraps, so every method of the
+/// main machine is available, and adds access to the second tape.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     CaptureWriter,
+///     Dialect,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::parse("+++@@-@", Dialect::Stack))
+///     .build_dual_tape()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(machine.tape()[0], Byte::from(2));
+/// assert_eq!(
+///     machine.auxiliary_tape().cells(),
+///     [Byte::from(3), Byte::from(3), Byte::from(2)]
+/// );
+/// ```
+pub struct DualTapeVM<R, W = Stdout>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    machine:   VirtualMachine<R, W>,
+    auxiliary: AuxiliaryTape,
+}
+
+impl<R, W> DualTapeVM<R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    pub(crate) const fn new(machine: VirtualMachine<R, W>, auxiliary: AuxiliaryTape) -> Self {
+        Self { machine, auxiliary }
+    }
+
+    /// The second tape of the machine
+    #[must_use]
+    pub const fn auxiliary_tape(&self) -> &AuxiliaryTape {
+        &self.auxiliary
+    }
+
+    /// Take the `VirtualMachine` out of the `DualTapeVM`
+    ///
+    /// The machine keeps running `@` and `$` on the auxiliary tape.
+    #[must_use]
+    pub fn into_inner(self) -> VirtualMachine<R, W> {
+        self.machine
+    }
+}
+
+impl<R, W> Deref for DualTapeVM<R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    type Target = VirtualMachine<R, W>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.machine
+    }
+}
+
+impl<R, W> DerefMut for DualTapeVM<R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.machine
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CaptureWriter,
+        Dialect,
+        EofBehavior,
+        Program,
+        VecReader,
+    };
+
+    #[test]
+    fn test_reverse_input() {
+        // Push every byte of the input, then pop them back in reverse order
+        let mut machine = VirtualMachine::builder()
+            .input_device(VecReader::new(b"abc".to_vec()))
+            .output_device(CaptureWriter::default())
+            .program(Program::parse(",[@,]$[.$]", Dialect::Stack))
+            .eof_behavior(EofBehavior::Zero)
+            .build_dual_tape()
+            .unwrap();
+        machine.run().unwrap();
+
+        assert_eq!(machine.output_device().data, b"cba");
+        assert!(machine.auxiliary_tape().is_empty());
+    }
+}
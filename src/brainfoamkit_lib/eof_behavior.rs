@@ -0,0 +1,145 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    str::FromStr,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Byte,
+    ParseError,
+};
+
+/// What `,` does when there is no more input
+///
+/// Interpreters disagree on this, and programs are written for one convention
+/// or another. Leaving the cell unchanged is the most portable choice and the
+/// default.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     EofBehavior,
+///     Program,
+///     VecReader,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(VecReader::new(Vec::new()))
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from("+,."))
+///     .eof_behavior(EofBehavior::Max)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(machine.output_device().data, [255]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EofBehavior {
+    /// Leave the current cell unchanged
+    #[default]
+    Unchanged,
+    /// Set the current cell to zero
+    Zero,
+    /// Set the current cell to 255, the equivalent of -1
+    Max,
+}
+
+impl EofBehavior {
+    /// The value of the current cell after reading past the end of the input
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The value of the cell before the read
+    #[must_use]
+    pub fn apply(self, current: Byte) -> Byte {
+        match self {
+            Self::Unchanged => current,
+            Self::Zero => Byte::from(0),
+            Self::Max => Byte::from(u8::MAX),
+        }
+    }
+}
+
+impl Display for EofBehavior {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Unchanged => write!(f, "unchanged"),
+            Self::Zero => write!(f, "zero"),
+            Self::Max => write!(f, "max"),
+        }
+    }
+}
+
+impl FromStr for EofBehavior {
+    type Err = ParseError;
+
+    /// Parse the name of an end of input behavior
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::EofBehavior;
+    ///
+    /// assert_eq!("zero".parse::<EofBehavior>().unwrap(), EofBehavior::Zero);
+    /// assert!("minus-one".parse::<EofBehavior>().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name does not match any behavior.
+    fn from_str(name: &str) -> Result<Self, ParseError> {
+        match name {
+            "unchanged" => Ok(Self::Unchanged),
+            "zero" => Ok(Self::Zero),
+            "max" => Ok(Self::Max),
+            _ => Err(ParseError::UnknownName {
+                kind: "end of input behavior",
+                name: name.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let current = Byte::from(7);
+
+        assert_eq!(EofBehavior::Unchanged.apply(current), current);
+        assert_eq!(EofBehavior::Zero.apply(current), Byte::from(0));
+        assert_eq!(EofBehavior::Max.apply(current), Byte::from(255));
+        assert_eq!(EofBehavior::default(), EofBehavior::Unchanged);
+    }
+
+    #[test]
+    fn test_names() {
+        for behavior in [EofBehavior::Unchanged, EofBehavior::Zero, EofBehavior::Max] {
+            assert_eq!(
+                behavior.to_string().parse::<EofBehavior>().unwrap(),
+                behavior
+            );
+        }
+        assert!("".parse::<EofBehavior>().is_err());
+    }
+}
@@ -0,0 +1,482 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    io,
+    path::PathBuf,
+};
+
+use thiserror::Error;
+
+use crate::{
+    Access,
+    AssertionFailed,
+    Dialect,
+    Direction,
+    LimitExceeded,
+    ParseError,
+    ProfileViolation,
+    Warning,
+};
+
+/// A `VMReader` could not provide a byte
+///
+/// Returned by [`VMReader::read`](crate::VMReader::read) and
+/// [`VMReader::read_number`](crate::VMReader::read_number). The
+/// `VirtualMachine` treats every error of `read` as the end of the input.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     ReadError,
+///     VMReader,
+///     VecReader,
+/// };
+///
+/// let mut reader = VecReader::new(b"x".to_vec());
+/// reader.read().unwrap();
+///
+/// assert!(matches!(reader.read(), Err(ReadError::Eof)));
+/// ```
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ReadError {
+    /// Every byte of the input has been read
+    #[error("No input left to read")]
+    Eof,
+    /// The byte read is outside of the ASCII range
+    #[error("Byte is not within the ASCII range")]
+    NotAscii(u8),
+    /// A number was expected, but the input holds another byte
+    #[error("Expected a decimal number, found '{}'", .0.escape_ascii())]
+    NotANumber(u8),
+    /// The number read does not fit in a cell
+    #[error("Numbers larger than 255 do not fit in a cell")]
+    NumberTooLarge,
+    /// The user interrupted the input
+    #[error("Interrupted")]
+    Interrupted,
+    /// Raw input was requested, but STDIN is not a terminal
+    #[error("Raw input needs STDIN to be a terminal")]
+    NotATerminal,
+    /// The underlying device failed
+    #[error(transparent)]
+    Io(io::Error),
+}
+
+impl From<io::Error> for ReadError {
+    /// Running out of bytes is the end of the input, everything else is a
+    /// failure of the device
+    fn from(error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Eof
+        } else {
+            Self::Io(error)
+        }
+    }
+}
+
+/// A file could not be read or written
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum FileError {
+    /// The file could not be read
+    #[error("Unable to read {}", .path.display())]
+    Read {
+        /// The path of the file
+        path:   PathBuf,
+        /// The reason it could not be read
+        source: io::Error,
+    },
+    /// The file could not be written
+    #[error("Unable to write {}", .path.display())]
+    Write {
+        /// The path of the file
+        path:   PathBuf,
+        /// The reason it could not be written
+        source: io::Error,
+    },
+}
+
+/// A `VirtualMachine` could not be put together
+///
+/// Returned by
+/// [`VirtualMachineBuilder::build`](crate::VirtualMachineBuilder::build)
+/// and [`VirtualMachine::from_parts`](crate::VirtualMachine::from_parts).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     BuildError,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let result = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("[["))
+///     .build();
+///
+/// assert!(matches!(result, Err(BuildError::Program(_))));
+/// ```
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The builder was not given an input device
+    #[error("Input device not set.")]
+    MissingInputDevice,
+    /// The brackets of the program are not balanced
+    #[error(transparent)]
+    Program(#[from] ParseError),
+    /// The memory pointer is not on the tape
+    #[error("The memory pointer {pointer} is outside of a tape of {length} cells")]
+    PointerOutOfRange {
+        /// The position of the memory pointer
+        pointer: usize,
+        /// The number of cells on the tape
+        length:  usize,
+    },
+    /// The file of a memory-mapped tape could not be changed
+    #[error(transparent)]
+    File(#[from] FileError),
+}
+
+/// A `VirtualMachine` stopped with an error
+///
+/// Returned by
+/// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction),
+/// [`VirtualMachine::run`](crate::VirtualMachine::run) and the other methods
+/// that change the state of a machine.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     LimitExceeded,
+///     Limits,
+///     Program,
+///     VMError,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("+[]"))
+///     .limits(Limits::new().max_steps(100))
+///     .build()
+///     .unwrap();
+///
+/// assert!(matches!(
+///     machine.run(),
+///     Err(VMError::LimitExceeded(LimitExceeded::Steps { limit: 100 }))
+/// ));
+/// ```
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum VMError {
+    /// The program exceeded a resource limit
+    #[error(transparent)]
+    LimitExceeded(#[from] LimitExceeded),
+    /// The program broke a rule of the behavior profile
+    #[error(transparent)]
+    ProfileViolation(#[from] ProfileViolation),
+    /// A cell did not hold the expected value
+    #[error(transparent)]
+    AssertionFailed(#[from] AssertionFailed),
+    /// An instruction used a cell its region of the
+    /// [`MemoryMap`](crate::MemoryMap) does not allow
+    #[error("The instruction at {instruction} cannot {access} the protected cell {cell}")]
+    AccessViolation {
+        /// What the instruction tried to do with the cell
+        access:      Access,
+        /// The position of the cell
+        cell:        usize,
+        /// The position of the instruction in the program
+        instruction: usize,
+    },
+    /// An instruction used a canary cell past an end of the tape, see
+    /// [`VirtualMachineBuilder::canary_cells`](crate::VirtualMachineBuilder::canary_cells)
+    #[error(
+        "The instruction at {instruction} touched canary cell {distance} to the {direction} of \
+         the tape at step {step}"
+    )]
+    CanaryTouched {
+        /// The end of the tape the memory pointer moved past
+        direction:   Direction,
+        /// How many cells past the end of the tape the canary is, from 1
+        distance:    usize,
+        /// The position of the instruction in the program
+        instruction: usize,
+        /// The number of instructions executed before it
+        step:        usize,
+    },
+    /// A cell beyond the end of the tape was checked
+    #[error("Cell {cell} is beyond the end of the tape of {length} cells")]
+    CellOutOfRange {
+        /// The position of the cell
+        cell:   usize,
+        /// The number of cells on the tape
+        length: usize,
+    },
+    /// `,` could not read a number in
+    /// [`IoMode::Numeric`](crate::IoMode::Numeric), or the input device
+    /// failed
+    #[error("Instruction {instruction} could not read its input")]
+    Input {
+        /// The position of the `,` in the program
+        instruction: usize,
+        /// The reason the number could not be read
+        source:      ReadError,
+    },
+    /// The output device failed
+    #[error("Unable to write the output")]
+    Output(#[from] io::Error),
+    /// A procedure of the `pbrain` dialect was called before it was defined
+    #[error("Procedure {id} called at instruction {instruction} is not defined")]
+    UndefinedProcedure {
+        /// The number of the procedure
+        id:          u8,
+        /// The position of the `:` in the program
+        instruction: usize,
+    },
+    /// The program holds an extension instruction with no
+    /// [`InstructionHandler`](crate::InstructionHandler) registered for it
+    #[error("No handler is registered for the instruction '{character}' at {instruction}")]
+    UnknownExtension {
+        /// The character of the instruction
+        character:   char,
+        /// The position of the instruction in the program
+        instruction: usize,
+    },
+    /// The [`InstructionHandler`](crate::InstructionHandler) of an extension
+    /// instruction failed
+    #[error("The instruction '{character}' at {instruction} failed")]
+    Extension {
+        /// The character of the instruction
+        character:   char,
+        /// The position of the instruction in the program
+        instruction: usize,
+        /// The error returned by the handler
+        source:      Box<dyn Error + Send + Sync>,
+    },
+    /// A [`DiagnosticsSink`](crate::DiagnosticsSink) stopped the machine at
+    /// a warning
+    #[error("Stopped at a warning: {0}")]
+    Warning(Warning),
+    /// The program was extended while the machine was still running it
+    #[error("The program can only be extended while the machine is halted")]
+    NotHalted,
+    /// The brackets of the program are not balanced
+    #[error(transparent)]
+    Program(#[from] ParseError),
+    /// The machine could not be put together
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    /// The configuration of the machine is not supported
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    /// A tape file could not be read or written
+    #[error(transparent)]
+    File(#[from] FileError),
+    /// A tape file is not in the expected format
+    #[error("Invalid tape in {}", .path.display())]
+    InvalidTape {
+        /// The path of the tape file
+        path:   PathBuf,
+        /// The reason the tape could not be decoded
+        source: ParseError,
+    },
+    /// A tape file holds more values than the tape has cells
+    #[error("{} holds {values} values, but the tape only has {cells} cells", .path.display())]
+    TapeTooLarge {
+        /// The path of the tape file
+        path:   PathBuf,
+        /// The number of values in the file
+        values: usize,
+        /// The number of cells on the tape
+        cells:  usize,
+    },
+}
+
+/// A configuration could not be loaded or is not supported
+///
+/// Returned by the functions of [`config`](crate::config).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The configuration file could not be read
+    #[error(transparent)]
+    File(#[from] FileError),
+    /// The current directory could not be found
+    #[error("Unable to access the current directory")]
+    CurrentDirectory(#[source] io::Error),
+    /// The configuration is not valid TOML or holds unknown keys or values of
+    /// the wrong type
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    /// A configuration file is not valid
+    #[error("Invalid configuration in {}", .path.display())]
+    InvalidFile {
+        /// The path of the configuration file
+        path:   PathBuf,
+        /// The reason it is not valid
+        source: toml::de::Error,
+    },
+    /// An environment variable holds an invalid value
+    #[error("Invalid value '{value}' for {key}")]
+    InvalidValue {
+        /// The name of the variable
+        key:    String,
+        /// The value of the variable
+        value:  String,
+        /// The reason the value is not valid
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// An environment variable with the `BRAINFOAMKIT_` prefix is not a
+    /// setting
+    #[error("Unknown setting {0}")]
+    UnknownSetting(String),
+    /// The tape size is zero
+    #[error("The tape needs at least one cell")]
+    EmptyTape,
+    /// The cell width is not 8 bits
+    #[error("Cells of {0} bits are not supported, only 8-bit cells are")]
+    UnsupportedCellWidth(u32),
+    /// A project manifest defines two programs with the same name
+    #[error("The manifest defines the program {0} more than once")]
+    DuplicateProgram(String),
+    /// A new project would overwrite an existing directory
+    #[error("{} already exists", .0.display())]
+    ProjectExists(PathBuf),
+}
+
+/// A checkpoint could not be read, written or resumed
+///
+/// Returned by the methods of [`Checkpoint`](crate::Checkpoint).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CheckpointError {
+    /// The checkpoint could not be read or written
+    #[error(transparent)]
+    File(#[from] FileError),
+    /// The checkpoint is not valid
+    #[error("Invalid checkpoint")]
+    Invalid(#[from] serde_json::Error),
+    /// The checkpoint was written by a newer version
+    #[error("Checkpoints of version {0} are not supported")]
+    UnsupportedVersion(u32),
+    /// The active procedure calls, the state of the random bytes or the
+    /// auxiliary tape of the dialect are not part of a checkpoint
+    #[error("Programs in the {0} dialect cannot be checkpointed")]
+    UnsupportedDialect(Dialect),
+    /// The configuration of the checkpoint is not supported
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    /// The machine could not be put together
+    #[error(transparent)]
+    Build(#[from] BuildError),
+}
+
+/// A debugging session could not be read
+///
+/// Returned by the methods of [`DebugSession`](crate::DebugSession).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SessionError {
+    /// The session could not be read
+    #[error(transparent)]
+    File(#[from] FileError),
+    /// The session is not valid
+    #[error("Invalid session")]
+    Invalid(#[from] serde_json::Error),
+    /// The session was written by a newer version
+    #[error("Sessions of version {0} are not supported")]
+    UnsupportedVersion(u32),
+}
+
+/// A replay bundle could not be recorded, read or replayed
+///
+/// Returned by the methods of [`ReplayBundle`](crate::ReplayBundle). A
+/// program that fails while it is recorded or replayed is not an error: the
+/// failure is part of its [`ReplayOutcome`](crate::ReplayOutcome).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ReplayError {
+    /// The bundle could not be read or written
+    #[error(transparent)]
+    File(#[from] FileError),
+    /// The bundle is not valid
+    #[error("Invalid replay bundle")]
+    Invalid(#[from] serde_json::Error),
+    /// The bundle was written by a newer version
+    #[error("Replay bundles of version {0} are not supported")]
+    UnsupportedVersion(u32),
+    /// The configuration of the bundle is not supported
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    /// The machine could not be put together
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    /// The output of the recorded run could not be written
+    #[error("Unable to write the output")]
+    Output(#[from] io::Error),
+}
+
+/// A trace could not be normalized
+///
+/// Returned by [`trace::normalize`](crate::trace::normalize) and
+/// [`trace::compare`](crate::trace::compare).
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TraceError {
+    /// The trace is JSON, but in none of the formats the crate writes
+    #[error("The trace is not a crash bundle, a list of trace events or a profile")]
+    UnknownFormat,
+    /// An event of the trace is missing a field, or the field is not valid
+    #[error("Event {index} of the trace has no valid '{field}'")]
+    InvalidEvent {
+        /// The position of the event in the trace, from 0
+        index: usize,
+        /// The name of the field
+        field: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_error_from_io() {
+        let eof = io::Error::from(io::ErrorKind::UnexpectedEof);
+        assert!(matches!(ReadError::from(eof), ReadError::Eof));
+
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(matches!(ReadError::from(denied), ReadError::Io(_)));
+    }
+
+    #[test]
+    fn test_messages() {
+        assert_eq!(
+            ReadError::NotANumber(b'x').to_string(),
+            "Expected a decimal number, found 'x'"
+        );
+        assert_eq!(
+            ParseError::UnknownName {
+                kind: "dialect",
+                name: String::from("foo"),
+            }
+            .to_string(),
+            "Unknown dialect 'foo'"
+        );
+        assert_eq!(
+            VMError::from(LimitExceeded::Steps { limit: 10 }).to_string(),
+            "Exceeded the limit of 10 steps"
+        );
+    }
+}
@@ -0,0 +1,302 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Plain English descriptions of what an instruction does
+//!
+//! Watching the tape change is not always enough to understand a program
+//! when you are new to Brainfuck. [`explain_step`] describes what the next
+//! instruction is about to do in a sentence, using the current state of the
+//! [`VirtualMachine`](crate::VirtualMachine): which cell the pointer moves to
+//! and what it holds, how a value changes, or whether a loop is entered or
+//! left.
+
+use crate::{
+    Byte,
+    Instruction,
+    IoMode,
+    TapeEdge,
+    VMReader,
+    VMWriter,
+    VirtualMachine,
+};
+
+/// Describe what an instruction is about to do
+///
+/// The description uses the state of the machine before the instruction is
+/// executed, so it is meant to be shown for
+/// [`get_instruction`](crate::VirtualMachine::get_instruction) before calling
+/// [`execute_instruction`](crate::VirtualMachine::execute_instruction).
+///
+/// # Arguments
+///
+/// * `instruction` - The instruction to describe
+/// * `machine` - The machine that is about to execute the instruction
+///
+/// # Returns
+///
+/// A sentence describing the effect of the instruction
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     explain,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("+>"))
+///     .tape_size(4)
+///     .build()
+///     .unwrap();
+///
+/// let instruction = machine.get_instruction().unwrap();
+/// assert_eq!(
+///     explain::explain_step(instruction, &machine),
+///     "Increment cell 0 from 0x00 to 0x01"
+/// );
+///
+/// machine.execute_instruction().unwrap();
+/// let instruction = machine.get_instruction().unwrap();
+/// assert_eq!(
+///     explain::explain_step(instruction, &machine),
+///     "Move the pointer right to cell 1, which contains 0x00"
+/// );
+/// ```
+#[must_use]
+pub fn explain_step<R: VMReader, W: VMWriter>(
+    instruction: Instruction,
+    machine: &VirtualMachine<R, W>,
+) -> String {
+    let tape = machine.tape();
+    let pointer = machine.memory_pointer();
+    let cell = tape[pointer];
+    let value = u8::from(&cell);
+
+    match instruction {
+        Instruction::IncrementPointer => {
+            if pointer + 1 < tape.len() {
+                return format!(
+                    "Move the pointer right to cell {}, which contains {}",
+                    pointer + 1,
+                    describe(tape[pointer + 1])
+                );
+            }
+            match machine.tape_edge() {
+                TapeEdge::Wrap => format!(
+                    "Move the pointer right past the end of the tape, back to cell 0, which \
+                     contains {}",
+                    describe(tape[0])
+                ),
+                TapeEdge::Grow => format!(
+                    "Move the pointer right to cell {}, a new cell that contains 0x00",
+                    pointer + 1
+                ),
+                TapeEdge::Error => "Try to move the pointer right past the end of the tape, which \
+                                    stops the program"
+                    .to_string(),
+            }
+        }
+        Instruction::DecrementPointer => {
+            if pointer > 0 {
+                return format!(
+                    "Move the pointer left to cell {}, which contains {}",
+                    pointer - 1,
+                    describe(tape[pointer - 1])
+                );
+            }
+            match machine.tape_edge() {
+                TapeEdge::Wrap => format!(
+                    "Move the pointer left past the start of the tape, around to cell {}, which \
+                     contains {}",
+                    tape.len() - 1,
+                    describe(tape[tape.len() - 1])
+                ),
+                TapeEdge::Grow | TapeEdge::Error => "Try to move the pointer left past the start \
+                                                     of the tape, which stops the program"
+                    .to_string(),
+            }
+        }
+        Instruction::IncrementValue => {
+            if value == u8::MAX && !machine.wraps_cells() {
+                format!("Try to increment cell {pointer} past 0xFF, which stops the program")
+            } else {
+                format!(
+                    "Increment cell {pointer} from {} to {}",
+                    describe(cell),
+                    describe(Byte::from(value.wrapping_add(1)))
+                )
+            }
+        }
+        Instruction::DecrementValue => {
+            if value == 0 && !machine.wraps_cells() {
+                format!("Try to decrement cell {pointer} below 0x00, which stops the program")
+            } else {
+                format!(
+                    "Decrement cell {pointer} from {} to {}",
+                    describe(cell),
+                    describe(Byte::from(value.wrapping_sub(1)))
+                )
+            }
+        }
+        Instruction::OutputValue => match machine.io_mode() {
+            IoMode::Bytes => format!("Output cell {pointer}, which contains {}", describe(cell)),
+            IoMode::Numeric => format!("Output cell {pointer} as the number {value}"),
+        },
+        Instruction::InputValue => match machine.io_mode() {
+            IoMode::Bytes => format!("Read a byte of input into cell {pointer}"),
+            IoMode::Numeric => format!("Read a number from the input into cell {pointer}"),
+        },
+        Instruction::JumpForward => {
+            if value == 0 {
+                format!(
+                    "Cell {pointer} contains 0x00, so skip the loop and continue after the \
+                     matching ]"
+                )
+            } else {
+                format!(
+                    "Cell {pointer} contains {}, which is not zero, so enter the loop",
+                    describe(cell)
+                )
+            }
+        }
+        Instruction::JumpBackward => {
+            if value == 0 {
+                format!("Cell {pointer} contains 0x00, so leave the loop")
+            } else {
+                format!(
+                    "Cell {pointer} contains {}, which is not zero, so repeat the loop",
+                    describe(cell)
+                )
+            }
+        }
+        Instruction::DebugDump => format!("Print the tape around cell {pointer} for debugging"),
+        Instruction::DefineProcedure => format!(
+            "Define procedure {value}, named after the value of cell {pointer}, and skip its body"
+        ),
+        Instruction::EndProcedure => {
+            if machine.call_stack().is_empty() {
+                "End the definition of a procedure".to_string()
+            } else {
+                "Return from the procedure to where it was called".to_string()
+            }
+        }
+        Instruction::CallProcedure => {
+            format!("Call procedure {value}, named by the value of cell {pointer}")
+        }
+        Instruction::Halt => "Stop the program".to_string(),
+        Instruction::Extension(character) => format!("Run the custom instruction {character}"),
+        Instruction::NoOp => "Skip a character that is not an instruction".to_string(),
+    }
+}
+
+/// Describe the value of a cell in hexadecimal, followed by the character it
+/// stands for if it is a printable ASCII character
+fn describe(cell: Byte) -> String {
+    let value = u8::from(&cell);
+    if value.is_ascii_graphic() || value == b' ' {
+        format!("{cell} '{}'", char::from(value))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BehaviorProfile,
+        MockReader,
+        Program,
+    };
+
+    fn machine(source: &str, profile: BehaviorProfile) -> VirtualMachine<MockReader> {
+        VirtualMachine::builder()
+            .input_device(MockReader {
+                data: std::io::Cursor::new(Vec::new()),
+            })
+            .program(Program::from(source))
+            .behavior_profile(profile)
+            .tape_size(3)
+            .build()
+            .unwrap()
+    }
+
+    fn explain_next(machine: &VirtualMachine<MockReader>) -> String {
+        explain_step(machine.get_instruction().unwrap(), machine)
+    }
+
+    #[test]
+    fn test_pointer_moves() {
+        let mut vm = machine(">>>", BehaviorProfile::Classic30k);
+        for _ in 0..2 {
+            vm.execute_instruction().unwrap();
+        }
+        assert_eq!(
+            explain_next(&vm),
+            "Move the pointer right past the end of the tape, back to cell 0, which contains 0x00"
+        );
+
+        let vm = machine("<", BehaviorProfile::Classic30k);
+        assert_eq!(
+            explain_next(&vm),
+            "Move the pointer left past the start of the tape, around to cell 2, which contains \
+             0x00"
+        );
+
+        let vm = machine("<", BehaviorProfile::Strict);
+        assert!(explain_next(&vm).ends_with("which stops the program"));
+    }
+
+    #[test]
+    fn test_values() {
+        let mut vm = machine(&"+".repeat(66), BehaviorProfile::Classic30k);
+        for _ in 0..65 {
+            vm.execute_instruction().unwrap();
+        }
+        assert_eq!(
+            explain_next(&vm),
+            "Increment cell 0 from 0x41 'A' to 0x42 'B'"
+        );
+
+        let vm = machine("-", BehaviorProfile::Classic30k);
+        assert_eq!(explain_next(&vm), "Decrement cell 0 from 0x00 to 0xFF");
+    }
+
+    #[test]
+    fn test_loops() {
+        let mut vm = machine("[+]+[-]", BehaviorProfile::Classic30k);
+        assert!(explain_next(&vm).contains("skip the loop"));
+
+        vm.execute_instruction().unwrap();
+        vm.execute_instruction().unwrap();
+        assert!(explain_next(&vm).contains("so enter the loop"));
+    }
+
+    #[test]
+    fn test_every_instruction() {
+        let vm = machine("", BehaviorProfile::Classic30k);
+        for instruction in [
+            Instruction::IncrementPointer,
+            Instruction::DecrementPointer,
+            Instruction::IncrementValue,
+            Instruction::DecrementValue,
+            Instruction::OutputValue,
+            Instruction::InputValue,
+            Instruction::JumpForward,
+            Instruction::JumpBackward,
+            Instruction::DebugDump,
+            Instruction::DefineProcedure,
+            Instruction::EndProcedure,
+            Instruction::CallProcedure,
+            Instruction::Halt,
+            Instruction::NoOp,
+        ] {
+            assert!(!explain_step(instruction, &vm).is_empty());
+        }
+    }
+}
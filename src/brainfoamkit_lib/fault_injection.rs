@@ -0,0 +1,336 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::io;
+
+use thiserror::Error;
+
+use crate::{
+    rng::Rng,
+    ReadError,
+    VMError,
+    VMReader,
+    VMWriter,
+};
+
+/// How often the fault injecting devices fail unless configured otherwise:
+/// once in this many operations, on average
+pub const DEFAULT_FAULT_RATE: u32 = 16;
+
+/// The error returned by a [`FaultInjectingReader`] or a
+/// [`FaultInjectingWriter`] when it injects a fault
+///
+/// It is wrapped in an `io::Error`, so it reaches the caller of
+/// [`VirtualMachine::run`](crate::VirtualMachine::run) as a
+/// [`VMError::Input`] or a [`VMError::Output`], like the failure of a real
+/// device. [`caused`](Self::caused) tells the two apart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
+#[error("Injected fault")]
+pub struct InjectedFault;
+
+impl InjectedFault {
+    /// Whether `error` was caused by an injected fault
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     FaultInjectingWriter,
+    ///     InjectedFault,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(
+    ///         FaultInjectingWriter::new(CaptureWriter::default(), 7).with_rate(1),
+    ///     )
+    ///     .program(Program::from("+."))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let error = machine.run().unwrap_err();
+    /// assert!(InjectedFault::caused(&error));
+    /// ```
+    #[must_use]
+    pub fn caused(error: &VMError) -> bool {
+        let source = match error {
+            VMError::Input {
+                source: ReadError::Io(source),
+                ..
+            }
+            | VMError::Output(source) => source,
+            _ => return false,
+        };
+        source.get_ref().is_some_and(|inner| inner.is::<Self>())
+    }
+
+    fn into_io_error(self) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, self)
+    }
+}
+
+/// A reader that occasionally fails, to test how the code around a
+/// `VirtualMachine` copes with a failing input device
+///
+/// Every read fails once in [`rate`](Self::with_rate) reads on average, with
+/// an [`InjectedFault`]. The faults are chosen by a random number generator
+/// seeded with `seed`, so the same seed fails the same reads on every run.
+/// Reads that do not fail are passed on to the wrapped reader.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     FaultInjectingReader,
+///     VMReader,
+///     VecReader,
+/// };
+///
+/// let mut reader =
+///     FaultInjectingReader::new(VecReader::new(b"abc".to_vec()), 42);
+/// let mut read = Vec::new();
+/// while read.len() < 3 {
+///     if let Ok(byte) = reader.read() {
+///         read.push(byte);
+///     }
+/// }
+///
+/// assert_eq!(read, b"abc");
+/// ```
+#[derive(Debug)]
+pub struct FaultInjectingReader<R: VMReader> {
+    inner:         R,
+    rng:           Rng,
+    rate:          u32,
+    partial_reads: bool,
+    faults:        usize,
+}
+
+impl<R: VMReader> FaultInjectingReader<R> {
+    /// Inject faults into the reads of `inner`, chosen by `seed`
+    #[must_use]
+    pub const fn new(inner: R, seed: u64) -> Self {
+        Self {
+            inner,
+            rng: Rng::new(seed),
+            rate: DEFAULT_FAULT_RATE,
+            partial_reads: false,
+            faults: 0,
+        }
+    }
+
+    /// Fail once in `rate` reads on average, never if `rate` is zero
+    ///
+    /// # Returns
+    ///
+    /// The `FaultInjectingReader` by value with the rate set.
+    #[must_use]
+    pub const fn with_rate(mut self, rate: u32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Let half of the faults cut the input short instead of failing
+    ///
+    /// A short read returns [`ReadError::Eof`] even though the wrapped reader
+    /// has more input, which the `VirtualMachine` treats as the end of the
+    /// input. The input continues with the next read.
+    ///
+    /// # Returns
+    ///
+    /// The `FaultInjectingReader` by value with short reads enabled or
+    /// disabled.
+    #[must_use]
+    pub const fn with_partial_reads(mut self, enabled: bool) -> Self {
+        self.partial_reads = enabled;
+        self
+    }
+
+    /// The number of faults injected so far
+    #[must_use]
+    pub const fn faults(&self) -> usize {
+        self.faults
+    }
+
+    /// Unwrap the reader
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: VMReader> VMReader for FaultInjectingReader<R> {
+    fn read(&mut self) -> Result<u8, ReadError> {
+        if self.rng.one_in(self.rate) {
+            self.faults += 1;
+            if self.partial_reads && self.rng.one_in(2) {
+                return Err(ReadError::Eof);
+            }
+            return Err(ReadError::Io(InjectedFault.into_io_error()));
+        }
+        self.inner.read()
+    }
+}
+
+/// A writer that occasionally fails, to test how the code around a
+/// `VirtualMachine` copes with a failing output device
+///
+/// Every write and flush fails once in [`rate`](Self::with_rate) calls on
+/// average, with an [`InjectedFault`], without reaching the wrapped writer.
+/// The faults are chosen by a random number generator seeded with `seed`, so
+/// the same seed fails the same calls on every run.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     FaultInjectingWriter,
+///     VMWriter,
+/// };
+///
+/// let mut writer =
+///     FaultInjectingWriter::new(CaptureWriter::default(), 42).with_rate(2);
+/// let written = (0..100).filter(|_| writer.write(b'x').is_ok()).count();
+///
+/// assert_eq!(written + writer.faults(), 100);
+/// assert_eq!(writer.into_inner().data.len(), written);
+/// ```
+#[derive(Debug)]
+pub struct FaultInjectingWriter<W: VMWriter> {
+    inner:  W,
+    rng:    Rng,
+    rate:   u32,
+    faults: usize,
+}
+
+impl<W: VMWriter> FaultInjectingWriter<W> {
+    /// Inject faults into the writes of `inner`, chosen by `seed`
+    #[must_use]
+    pub const fn new(inner: W, seed: u64) -> Self {
+        Self {
+            inner,
+            rng: Rng::new(seed),
+            rate: DEFAULT_FAULT_RATE,
+            faults: 0,
+        }
+    }
+
+    /// Fail once in `rate` calls on average, never if `rate` is zero
+    ///
+    /// # Returns
+    ///
+    /// The `FaultInjectingWriter` by value with the rate set.
+    #[must_use]
+    pub const fn with_rate(mut self, rate: u32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// The number of faults injected so far
+    #[must_use]
+    pub const fn faults(&self) -> usize {
+        self.faults
+    }
+
+    /// Unwrap the writer
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn inject(&mut self) -> io::Result<()> {
+        if self.rng.one_in(self.rate) {
+            self.faults += 1;
+            return Err(InjectedFault.into_io_error());
+        }
+        Ok(())
+    }
+}
+
+impl<W: VMWriter> VMWriter for FaultInjectingWriter<W> {
+    fn write(&mut self, value: u8) -> io::Result<()> {
+        self.inject()?;
+        self.inner.write(value)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inject()?;
+        self.inner.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CaptureWriter,
+        Program,
+        VecReader,
+        VirtualMachine,
+    };
+
+    #[test]
+    fn test_reader_faults_are_reproducible() {
+        let reads = |seed| {
+            let mut reader = FaultInjectingReader::new(VecReader::new(vec![1; 64]), seed);
+            (0..64).map(|_| reader.read().is_ok()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(reads(3), reads(3));
+        assert!(reads(3).contains(&false));
+        assert_ne!(reads(3), reads(4));
+    }
+
+    #[test]
+    fn test_rate_zero_never_fails() {
+        let mut reader = FaultInjectingReader::new(VecReader::new(vec![1; 100]), 0).with_rate(0);
+        let mut writer = FaultInjectingWriter::new(CaptureWriter::default(), 0).with_rate(0);
+
+        for _ in 0..100 {
+            assert_eq!(reader.read().unwrap(), 1);
+            writer.write(1).unwrap();
+        }
+        assert_eq!(reader.faults() + writer.faults(), 0);
+    }
+
+    #[test]
+    fn test_partial_reads() {
+        let mut reader = FaultInjectingReader::new(VecReader::new(vec![1; 256]), 9)
+            .with_rate(1)
+            .with_partial_reads(true);
+        let results: Vec<_> = (0..64).map(|_| reader.read()).collect();
+
+        assert!(results
+            .iter()
+            .any(|result| matches!(result, Err(ReadError::Eof))));
+        assert!(results
+            .iter()
+            .any(|result| matches!(result, Err(ReadError::Io(_)))));
+        assert_eq!(reader.faults(), 64);
+    }
+
+    #[test]
+    fn test_faults_reach_the_caller() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(FaultInjectingReader::new(VecReader::new(vec![1; 8]), 5).with_rate(1))
+            .output_device(CaptureWriter::default())
+            .program(Program::from(",."))
+            .build()
+            .unwrap();
+
+        let error = machine.run().unwrap_err();
+        assert!(matches!(error, VMError::Input { instruction: 0, .. }));
+        assert!(InjectedFault::caused(&error));
+        assert!(!InjectedFault::caused(&VMError::NotHalted));
+    }
+}
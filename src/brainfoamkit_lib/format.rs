@@ -0,0 +1,208 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Short, readable forms of counts, durations and sizes
+//!
+//! A long run executes billions of instructions, and a report full of
+//! twelve-digit numbers is hard to take in at a glance. The functions below
+//! round a number to three significant digits and add a unit, so that every
+//! report of the crate, from the statistics of `bfkrun run --stats` to the
+//! [`Profiler`](crate::Profiler) summary and the status bar of `bfkview`,
+//! shows them the same way.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use brainfoamkit_lib::format::{
+//!     human_bytes,
+//!     human_count,
+//!     human_duration,
+//!     human_rate,
+//! };
+//!
+//! assert_eq!(human_count(1_234_567), "1.2M");
+//! assert_eq!(human_bytes(30_000), "29.3 KiB");
+//! assert_eq!(human_duration(Duration::from_millis(1_500)), "1.50 s");
+//! assert_eq!(human_rate(3_000_000, Duration::from_millis(500)), "6.0M/s");
+//! ```
+
+use alloc::{
+    format,
+    string::String,
+};
+use core::time::Duration;
+
+/// The suffixes of [`human_count`], for powers of 1000
+const COUNT_UNITS: [&str; 7] = ["", "k", "M", "G", "T", "P", "E"];
+
+/// The units of [`human_bytes`], for powers of 1024
+const BYTE_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Scale `value` down by `base` until it fits under it once rounded
+#[allow(clippy::cast_precision_loss)]
+fn scale(value: usize, base: f64) -> (f64, usize) {
+    let mut scaled = value as f64;
+    let mut unit = 0;
+    while scaled >= base - 0.5 && unit < COUNT_UNITS.len() - 1 {
+        scaled /= base;
+        unit += 1;
+    }
+    (scaled, unit)
+}
+
+/// Round a scaled value to three significant digits
+fn significant(value: f64) -> String {
+    if value < 99.95 {
+        format!("{value:.1}")
+    } else {
+        format!("{value:.0}")
+    }
+}
+
+/// Format a count with a metric suffix
+///
+/// Counts under a thousand are shown in full, larger ones with one decimal
+/// and `k`, `M`, `G` and so on.
+///
+/// # Arguments
+///
+/// * `count` - The number of things
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::format::human_count;
+///
+/// assert_eq!(human_count(999), "999");
+/// assert_eq!(human_count(12_345), "12.3k");
+/// assert_eq!(human_count(999_999), "1.0M");
+/// ```
+#[must_use]
+pub fn human_count(count: usize) -> String {
+    if count < 1000 {
+        return format!("{count}");
+    }
+    let (value, unit) = scale(count, 1000.0);
+    format!("{}{}", significant(value), COUNT_UNITS[unit])
+}
+
+/// Format a size in bytes with a binary unit
+///
+/// # Arguments
+///
+/// * `bytes` - The number of bytes
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::format::human_bytes;
+///
+/// assert_eq!(human_bytes(512), "512 B");
+/// assert_eq!(human_bytes(1536), "1.5 KiB");
+/// assert_eq!(human_bytes(3 << 30), "3.0 GiB");
+/// ```
+#[must_use]
+pub fn human_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let (value, unit) = scale(bytes, 1024.0);
+    format!("{} {}", significant(value), BYTE_UNITS[unit])
+}
+
+/// Format a duration with the largest unit that keeps it readable
+///
+/// Durations under a minute are shown in nanoseconds, microseconds,
+/// milliseconds or seconds, longer ones in minutes and seconds, or hours and
+/// minutes.
+///
+/// # Arguments
+///
+/// * `duration` - How long something took
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use brainfoamkit_lib::format::human_duration;
+///
+/// assert_eq!(human_duration(Duration::from_nanos(850)), "850 ns");
+/// assert_eq!(human_duration(Duration::from_micros(12_340)), "12.3 ms");
+/// assert_eq!(human_duration(Duration::from_secs(125)), "2m 05s");
+/// assert_eq!(human_duration(Duration::from_secs(7_380)), "2h 03m");
+/// ```
+#[must_use]
+pub fn human_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    let seconds = duration.as_secs_f64();
+    if nanos < 1000 {
+        format!("{nanos} ns")
+    } else if nanos < 999_950 {
+        format!("{:.1} µs", seconds * 1e6)
+    } else if nanos < 999_950_000 {
+        format!("{:.1} ms", seconds * 1e3)
+    } else if seconds < 59.995 {
+        format!("{seconds:.2} s")
+    } else {
+        let seconds = duration.as_secs() + u64::from(duration.subsec_millis() >= 500);
+        if seconds < 3600 {
+            format!("{}m {:02}s", seconds / 60, seconds % 60)
+        } else {
+            format!("{}h {:02}m", seconds / 3600, seconds / 60 % 60)
+        }
+    }
+}
+
+/// Format how many things happened per second
+///
+/// # Arguments
+///
+/// * `count` - The number of things
+/// * `elapsed` - How long they took
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use brainfoamkit_lib::format::human_rate;
+///
+/// assert_eq!(human_rate(250, Duration::from_secs(2)), "125/s");
+/// assert_eq!(human_rate(250, Duration::ZERO), "-");
+/// ```
+#[must_use]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn human_rate(count: usize, elapsed: Duration) -> String {
+    if elapsed.is_zero() {
+        return String::from("-");
+    }
+    let rate = count as f64 / elapsed.as_secs_f64() + 0.5;
+    format!("{}/s", human_count(rate as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounding_up_to_the_next_unit() {
+        assert_eq!(human_count(1000), "1.0k");
+        assert_eq!(human_count(99_949), "99.9k");
+        assert_eq!(human_count(99_950), "100k");
+        assert_eq!(human_count(999_499), "999k");
+        assert_eq!(human_count(usize::MAX), "18.4E");
+        assert_eq!(human_bytes(1_048_000), "1023 KiB");
+        assert_eq!(human_bytes(1_048_575), "1.0 MiB");
+        assert_eq!(human_duration(Duration::from_nanos(999_960)), "1.0 ms");
+        assert_eq!(human_duration(Duration::from_millis(59_999)), "1m 00s");
+    }
+}
@@ -0,0 +1,453 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Logic gates built from [`Bit`]s
+//!
+//! A [`Bit`] is a logic value, so the gates of a digital circuit can be
+//! modelled on top of it, and composed the way the crate composes `Bit`s into
+//! [`Nybble`](crate::Nybble)s and [`Byte`]s. Every gate implements [`Gate`].
+//! The simple gates, [`And`], [`Or`], [`Xor`] and [`Nand`], work on a pair of
+//! bits and [`Mux`] selects one of two. The adders are built from them: a
+//! [`HalfAdder`] is a `Xor` and an `And`, a [`FullAdder`] is two
+//! `HalfAdder`s and an `Or`, and a [`RippleCarryAdder`] chains eight
+//! `FullAdder`s to add two `Byte`s, passing the carry from each bit to the
+//! next.
+//!
+//! [`truth_table`] lists the output of any expression over `Bit`s for every
+//! combination of its inputs, as a Markdown or a terminal table, which is how
+//! the gates below are documented.
+//!
+//! # Examples
+//!
+//! ```
+//! use brainfoamkit_lib::{
+//!     gates::{
+//!         FullAdder,
+//!         Gate,
+//!         RippleCarryAdder,
+//!     },
+//!     Bit,
+//!     Byte,
+//! };
+//!
+//! // 1 + 1 + 1 is 1, carrying 1
+//! assert_eq!(
+//!     FullAdder.evaluate((Bit::one(), Bit::one(), Bit::one())),
+//!     (Bit::one(), Bit::one())
+//! );
+//!
+//! let (sum, carry) = RippleCarryAdder.evaluate((
+//!     Byte::from(200),
+//!     Byte::from(100),
+//!     Bit::zero(),
+//! ));
+//! assert_eq!(u8::from(&sum), 44);
+//! assert_eq!(carry, Bit::one());
+//! ```
+
+use alloc::{
+    format,
+    string::String,
+    vec::Vec,
+};
+
+#[cfg(feature = "std")]
+use prettytable::{
+    format::consts::FORMAT_NO_LINESEP_WITH_TITLE,
+    Cell,
+    Row,
+    Table,
+};
+
+use crate::{
+    Bit,
+    Byte,
+};
+
+/// A logic circuit that turns its inputs into its outputs
+pub trait Gate {
+    /// The input signals of the gate
+    type Input;
+    /// The output signals of the gate
+    type Output;
+
+    /// The outputs of the gate for `input`
+    fn evaluate(&self, input: Self::Input) -> Self::Output;
+}
+
+/// A gate whose output is `1` when both inputs are `1`
+///
+/// | A | B | Out |
+/// | - | - | --- |
+/// | 0 | 0 | 0 |
+/// | 0 | 1 | 0 |
+/// | 1 | 0 | 0 |
+/// | 1 | 1 | 1 |
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct And;
+
+impl Gate for And {
+    type Input = (Bit, Bit);
+    type Output = Bit;
+
+    fn evaluate(&self, (a, b): Self::Input) -> Self::Output {
+        a & b
+    }
+}
+
+/// A gate whose output is `1` when either input is `1`
+///
+/// | A | B | Out |
+/// | - | - | --- |
+/// | 0 | 0 | 0 |
+/// | 0 | 1 | 1 |
+/// | 1 | 0 | 1 |
+/// | 1 | 1 | 1 |
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Or;
+
+impl Gate for Or {
+    type Input = (Bit, Bit);
+    type Output = Bit;
+
+    fn evaluate(&self, (a, b): Self::Input) -> Self::Output {
+        a | b
+    }
+}
+
+/// A gate whose output is `1` when exactly one input is `1`
+///
+/// | A | B | Out |
+/// | - | - | --- |
+/// | 0 | 0 | 0 |
+/// | 0 | 1 | 1 |
+/// | 1 | 0 | 1 |
+/// | 1 | 1 | 0 |
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Xor;
+
+impl Gate for Xor {
+    type Input = (Bit, Bit);
+    type Output = Bit;
+
+    fn evaluate(&self, (a, b): Self::Input) -> Self::Output {
+        a ^ b
+    }
+}
+
+/// A gate whose output is `0` when both inputs are `1`
+///
+/// Any other gate can be built from `Nand`s alone.
+///
+/// | A | B | Out |
+/// | - | - | --- |
+/// | 0 | 0 | 1 |
+/// | 0 | 1 | 1 |
+/// | 1 | 0 | 1 |
+/// | 1 | 1 | 0 |
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Nand;
+
+impl Gate for Nand {
+    type Input = (Bit, Bit);
+    type Output = Bit;
+
+    fn evaluate(&self, input: Self::Input) -> Self::Output {
+        !And.evaluate(input)
+    }
+}
+
+/// A multiplexer, which passes on one of two inputs
+///
+/// The input is `(select, a, b)`. The output is `a` when `select` is `0` and
+/// `b` when it is `1`.
+///
+/// | Select | A | B | Out |
+/// | ------ | - | - | --- |
+/// | 0 | 0 | 0 | 0 |
+/// | 0 | 0 | 1 | 0 |
+/// | 0 | 1 | 0 | 1 |
+/// | 0 | 1 | 1 | 1 |
+/// | 1 | 0 | 0 | 0 |
+/// | 1 | 0 | 1 | 1 |
+/// | 1 | 1 | 0 | 0 |
+/// | 1 | 1 | 1 | 1 |
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Mux;
+
+impl Gate for Mux {
+    type Input = (Bit, Bit, Bit);
+    type Output = Bit;
+
+    fn evaluate(&self, (select, a, b): Self::Input) -> Self::Output {
+        Or.evaluate((And.evaluate((!select, a)), And.evaluate((select, b))))
+    }
+}
+
+/// A circuit that adds two bits
+///
+/// The output is `(sum, carry)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HalfAdder;
+
+impl Gate for HalfAdder {
+    type Input = (Bit, Bit);
+    type Output = (Bit, Bit);
+
+    fn evaluate(&self, input: Self::Input) -> Self::Output {
+        (Xor.evaluate(input), And.evaluate(input))
+    }
+}
+
+/// A circuit that adds two bits and the carry of a less significant bit
+///
+/// The input is `(a, b, carry_in)` and the output is `(sum, carry_out)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FullAdder;
+
+impl Gate for FullAdder {
+    type Input = (Bit, Bit, Bit);
+    type Output = (Bit, Bit);
+
+    fn evaluate(&self, (a, b, carry_in): Self::Input) -> Self::Output {
+        let (partial, first_carry) = HalfAdder.evaluate((a, b));
+        let (sum, second_carry) = HalfAdder.evaluate((partial, carry_in));
+        (sum, Or.evaluate((first_carry, second_carry)))
+    }
+}
+
+/// A circuit that adds two `Byte`s one bit at a time
+///
+/// The input is `(a, b, carry_in)` and the output is `(sum, carry_out)`. Each
+/// of the eight [`FullAdder`]s adds a bit of `a` and `b` and the carry of the
+/// adder before it, from the least significant bit up, so the sum wraps
+/// around like [`Byte::increment`] does and `carry_out` tells whether it did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RippleCarryAdder;
+
+impl RippleCarryAdder {
+    /// The sum and the carry out of every bit, least significant first, as
+    /// the carry ripples through the adders
+    #[must_use]
+    pub fn carries(&self, (a, b, carry_in): (Byte, Byte, Bit)) -> [(Bit, Bit); 8] {
+        let mut carry = carry_in;
+        let mut stages = [(Bit::zero(), Bit::zero()); 8];
+        for (index, stage) in (0..8).zip(stages.iter_mut()) {
+            *stage = FullAdder.evaluate((a.get_bit(index), b.get_bit(index), carry));
+            carry = stage.1;
+        }
+        stages
+    }
+}
+
+impl Gate for RippleCarryAdder {
+    type Input = (Byte, Byte, Bit);
+    type Output = (Byte, Bit);
+
+    fn evaluate(&self, input: Self::Input) -> Self::Output {
+        let mut sum = Byte::default();
+        let mut carry = input.2;
+        for (index, (bit, carry_out)) in self.carries(input).into_iter().enumerate() {
+            if bit.is_set() {
+                sum.set_bit(index);
+            }
+            carry = carry_out;
+        }
+        (sum, carry)
+    }
+}
+
+/// The output of an expression over `Bit`s for every combination of its
+/// inputs
+///
+/// See [`truth_table`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruthTable<const N: usize> {
+    rows: Vec<([Bit; N], Bit)>,
+}
+
+impl<const N: usize> TruthTable<N> {
+    /// The inputs and the output of every row, with the inputs counting up
+    /// in binary from all `0`s to all `1`s
+    #[must_use]
+    pub fn rows(&self) -> &[([Bit; N], Bit)] {
+        &self.rows
+    }
+
+    /// The output of every row
+    pub fn outputs(&self) -> impl Iterator<Item = Bit> + '_ {
+        self.rows.iter().map(|&(_, output)| output)
+    }
+
+    /// The table as Markdown, with the inputs named `A`, `B`, `C` and so on
+    /// and the output named `Out`
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let names = Self::names();
+        let mut markdown = format!("| {} | Out |\n", names.join(" | "));
+        markdown.push_str(&format!("|{} --- |\n", " - |".repeat(N)));
+        for (inputs, output) in &self.rows {
+            for input in inputs {
+                markdown.push_str(&format!("| {input} "));
+            }
+            markdown.push_str(&format!("| {output} |\n"));
+        }
+        markdown
+    }
+
+    /// The table for printing to a terminal, with the same headers as
+    /// [`to_markdown`](Self::to_markdown)
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_format(*FORMAT_NO_LINESEP_WITH_TITLE);
+        let mut titles: Vec<Cell> = Self::names().iter().map(|name| Cell::new(name)).collect();
+        titles.push(Cell::new("Out"));
+        table.set_titles(Row::new(titles));
+        for (inputs, output) in &self.rows {
+            let mut cells: Vec<Cell> = inputs
+                .iter()
+                .map(|input| Cell::new(&input.to_string()))
+                .collect();
+            cells.push(Cell::new(&output.to_string()));
+            table.add_row(Row::new(cells));
+        }
+        table
+    }
+
+    fn names() -> Vec<String> {
+        (b'A'..=b'Z')
+            .take(N)
+            .map(|letter| String::from(char::from(letter)))
+            .collect()
+    }
+}
+
+/// The truth table of `expression`, an expression over `N` `Bit`s
+///
+/// The expression is evaluated for all `2^N` combinations of its inputs. The
+/// inputs are named by letter, so `N` can be at most 26, and any expression
+/// worth tabulating has far fewer.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     gates::{
+///         self,
+///         Gate,
+///         Nand,
+///     },
+///     Bit,
+/// };
+///
+/// let table = gates::truth_table(|[a, b]: [Bit; 2]| Nand.evaluate((a, b)));
+///
+/// assert_eq!(
+///     table.to_markdown(),
+///     "| A | B | Out |\n| - | - | --- |\n| 0 | 0 | 1 |\n| 0 | 1 | 1 |\n| 1 \
+///      | 0 | 1 |\n| 1 | 1 | 0 |\n"
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if `N` is more than 26.
+pub fn truth_table<const N: usize, F>(expression: F) -> TruthTable<N>
+where
+    F: Fn([Bit; N]) -> Bit,
+{
+    assert!(N <= 26, "a truth table has at most 26 inputs");
+    let rows = (0..1_usize << N)
+        .map(|row| {
+            let mut inputs = [Bit::zero(); N];
+            for (position, input) in inputs.iter_mut().enumerate() {
+                // The first input is the most significant
+                *input = Bit::from(u8::from(row >> (N - 1 - position) & 1 == 1));
+            }
+            (inputs, expression(inputs))
+        })
+        .collect();
+    TruthTable { rows }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BITS: [Bit; 2] = [Bit::Zero, Bit::One];
+
+    #[test]
+    fn test_simple_gates() {
+        for a in BITS {
+            for b in BITS {
+                let (x, y) = (u8::from(a), u8::from(b));
+                assert_eq!(u8::from(And.evaluate((a, b))), x & y);
+                assert_eq!(u8::from(Or.evaluate((a, b))), x | y);
+                assert_eq!(u8::from(Xor.evaluate((a, b))), x ^ y);
+                assert_eq!(u8::from(Nand.evaluate((a, b))), 1 - (x & y));
+                assert_eq!(Mux.evaluate((Bit::zero(), a, b)), a);
+                assert_eq!(Mux.evaluate((Bit::one(), a, b)), b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_truth_table() {
+        let table = truth_table(|[select, a, b]: [Bit; 3]| Mux.evaluate((select, a, b)));
+        let outputs: Vec<u8> = table.outputs().map(u8::from).collect();
+
+        assert_eq!(table.rows().len(), 8);
+        assert_eq!(table.rows()[3].0, [Bit::zero(), Bit::one(), Bit::one()]);
+        assert_eq!(outputs, [0, 0, 1, 1, 0, 1, 0, 1]);
+        assert!(table
+            .to_markdown()
+            .starts_with("| A | B | C | Out |\n| - | - | - | --- |\n| 0 | 0 | 0 | 0 |\n"));
+
+        let rendered = table.to_table().to_string();
+        assert_eq!(rendered.lines().count(), 12);
+        assert!(rendered.contains("| A | B | C | Out |"));
+    }
+
+    #[test]
+    fn test_full_adder() {
+        for a in BITS {
+            for b in BITS {
+                for carry in BITS {
+                    let total = u8::from(a) + u8::from(b) + u8::from(carry);
+                    let (sum, carry_out) = FullAdder.evaluate((a, b, carry));
+                    assert_eq!(u8::from(sum), total % 2);
+                    assert_eq!(u8::from(carry_out), total / 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ripple_carry_adder() {
+        for (a, b) in [
+            (0_u8, 0_u8),
+            (1, 1),
+            (15, 1),
+            (127, 129),
+            (255, 255),
+            (200, 37),
+        ] {
+            let (sum, carry) =
+                RippleCarryAdder.evaluate((Byte::from(a), Byte::from(b), Bit::zero()));
+            let (expected, overflowed) = a.overflowing_add(b);
+            assert_eq!(u8::from(&sum), expected);
+            assert_eq!(carry.is_set(), overflowed);
+        }
+
+        let (sum, carry) = RippleCarryAdder.evaluate((Byte::from(255), Byte::from(0), Bit::one()));
+        assert_eq!((sum, carry), (Byte::from(0), Bit::one()));
+
+        let stages = RippleCarryAdder.carries((Byte::from(0b0111), Byte::from(1), Bit::zero()));
+        let carries: Vec<u8> = stages.iter().map(|&(_, carry)| u8::from(carry)).collect();
+        assert_eq!(carries, [1, 1, 1, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,279 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::{
+    vec_deque::Iter,
+    VecDeque,
+};
+
+use crate::{
+    Byte,
+    Instruction,
+};
+
+/// A single step recorded in the execution `History`
+///
+/// Each entry captures the state of the `VirtualMachine` right before an
+/// instruction was executed.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     HistoryEntry,
+///     Instruction,
+/// };
+///
+/// let entry =
+///     HistoryEntry::new(0, Instruction::IncrementValue, 0, Byte::from(0));
+///
+/// assert_eq!(entry.program_counter(), 0);
+/// assert_eq!(entry.instruction(), Instruction::IncrementValue);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct HistoryEntry {
+    program_counter: usize,
+    instruction:     Instruction,
+    memory_pointer:  usize,
+    cell:            Byte,
+}
+
+impl HistoryEntry {
+    /// Create a new `HistoryEntry`
+    ///
+    /// # Arguments
+    ///
+    /// * `program_counter` - The position of the instruction in the `Program`
+    /// * `instruction` - The instruction that was executed
+    /// * `memory_pointer` - The position of the memory pointer before execution
+    /// * `cell` - The value of the current cell before execution
+    ///
+    /// # Returns
+    ///
+    /// A new `HistoryEntry`
+    #[must_use]
+    pub const fn new(
+        program_counter: usize,
+        instruction: Instruction,
+        memory_pointer: usize,
+        cell: Byte,
+    ) -> Self {
+        Self {
+            program_counter,
+            instruction,
+            memory_pointer,
+            cell,
+        }
+    }
+
+    /// The position of the executed instruction in the `Program`
+    #[must_use]
+    pub const fn program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    /// The instruction that was executed
+    #[must_use]
+    pub const fn instruction(&self) -> Instruction {
+        self.instruction
+    }
+
+    /// The position of the memory pointer before the instruction executed
+    #[must_use]
+    pub const fn memory_pointer(&self) -> usize {
+        self.memory_pointer
+    }
+
+    /// The value of the current cell before the instruction executed
+    #[must_use]
+    pub const fn cell(&self) -> Byte {
+        self.cell
+    }
+}
+
+/// A ring buffer of the most recently executed instructions
+///
+/// The `History` keeps the last `capacity` steps of a `VirtualMachine`. Once it
+/// is full, recording a new step discards the oldest one. A `History` with a
+/// capacity of zero records nothing, which is the default for a
+/// `VirtualMachine` so that execution pays no cost unless it is requested.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     History,
+///     HistoryEntry,
+///     Instruction,
+/// };
+///
+/// let mut history = History::new(2);
+/// for pc in 0..3 {
+///     history.record(HistoryEntry::new(
+///         pc,
+///         Instruction::IncrementValue,
+///         0,
+///         Byte::from(0),
+///     ));
+/// }
+///
+/// assert_eq!(history.len(), 2);
+/// assert_eq!(history.last().unwrap().program_counter(), 2);
+/// ```
+///
+/// # See Also
+///
+/// * [`VirtualMachineBuilder::history_capacity`](crate::VirtualMachineBuilder::history_capacity)
+/// * [`VirtualMachine::history`](crate::VirtualMachine::history)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    capacity: usize,
+    entries:  VecDeque<HistoryEntry>,
+}
+
+impl History {
+    /// Create a new, empty `History`
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of entries to keep
+    ///
+    /// # Returns
+    ///
+    /// A new `History` with the given capacity
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Record a step, discarding the oldest entry if the `History` is full
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The step to record
+    pub fn record(&mut self, entry: HistoryEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// The maximum number of entries the `History` keeps
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of entries currently recorded
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no entries are recorded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The most recently recorded entry, if any
+    #[must_use]
+    pub fn last(&self) -> Option<&HistoryEntry> {
+        self.entries.back()
+    }
+
+    /// Iterate over the recorded entries, from oldest to newest
+    pub fn iter(&self) -> Iter<'_, HistoryEntry> {
+        self.entries.iter()
+    }
+
+    /// Remove all recorded entries, keeping the capacity
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<'a> IntoIterator for &'a History {
+    type IntoIter = Iter<'a, HistoryEntry>;
+    type Item = &'a HistoryEntry;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(program_counter: usize) -> HistoryEntry {
+        HistoryEntry::new(
+            program_counter,
+            Instruction::IncrementValue,
+            0,
+            Byte::from(0),
+        )
+    }
+
+    #[test]
+    fn test_record_within_capacity() {
+        let mut history = History::new(3);
+        history.record(entry(0));
+        history.record(entry(1));
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.capacity(), 3);
+        assert_eq!(history.last(), Some(&entry(1)));
+    }
+
+    #[test]
+    fn test_record_wraps_around() {
+        let mut history = History::new(3);
+        for pc in 0..5 {
+            history.record(entry(pc));
+        }
+
+        let recorded: Vec<usize> = history.iter().map(HistoryEntry::program_counter).collect();
+        assert_eq!(recorded, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut history = History::default();
+        history.record(entry(0));
+
+        assert!(history.is_empty());
+        assert_eq!(history.last(), None);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut history = History::new(2);
+        history.record(entry(0));
+        history.clear();
+
+        assert!(history.is_empty());
+        assert_eq!(history.capacity(), 2);
+    }
+
+    #[test]
+    fn test_entry_accessors() {
+        let entry = HistoryEntry::new(4, Instruction::OutputValue, 2, Byte::from(65));
+
+        assert_eq!(entry.program_counter(), 4);
+        assert_eq!(entry.instruction(), Instruction::OutputValue);
+        assert_eq!(entry.memory_pointer(), 2);
+        assert_eq!(entry.cell(), Byte::from(65));
+    }
+}
@@ -0,0 +1,224 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    time::Instant,
+};
+
+use crate::{
+    Byte,
+    InstructionHandler,
+    VMError,
+    VmContext,
+};
+
+/// An extension instruction that calls a function of the host program
+///
+/// A `HostCall` is the simplest way to add an instruction: the function
+/// receives the cells from the memory pointer to the end of the tape, the
+/// current cell first, and can read and change them. The cells of a
+/// memory-mapped tape are copied for the call and written back after it. It is
+/// usually registered with
+/// [`VirtualMachineBuilder::host_call`](crate::VirtualMachineBuilder::host_call).
+/// Any error the function returns stops the machine.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     Dialect,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// // `s` swaps the current cell with the next one
+/// let program =
+///     Program::parse_with_extensions("+s", Dialect::Standard, &['s']);
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(program)
+///     .host_call('s', |cells: &mut [Byte]| {
+///         cells.swap(0, 1);
+///         Ok(())
+///     })
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(
+///     machine.tape().slice(0..2)[..],
+///     [Byte::from(0), Byte::from(1)]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct HostCall<F> {
+    function: F,
+}
+
+impl<F> HostCall<F>
+where
+    F: FnMut(&mut [Byte]) -> Result<(), Box<dyn Error + Send + Sync>> + Send,
+{
+    /// An instruction that calls `function`
+    pub const fn new(function: F) -> Self {
+        Self { function }
+    }
+}
+
+impl<F> InstructionHandler for HostCall<F>
+where
+    F: FnMut(&mut [Byte]) -> Result<(), Box<dyn Error + Send + Sync>> + Send,
+{
+    fn execute(&mut self, vm: &mut VmContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let pointer = vm.memory_pointer();
+        let end = vm.tape().len();
+        vm.tape_mut()
+            .edit(pointer..end, &mut self.function)
+            .unwrap_or(Ok(()))
+    }
+}
+
+/// An extension instruction that reads a clock
+///
+/// The instruction writes the number of milliseconds since the clock was
+/// created, usually when the machine was built, to the current cell and the
+/// three cells after it, most significant byte first. The memory pointer
+/// does not move. Reading the clock before and after a piece of a program
+/// measures how long it takes, from within the program. The count saturates
+/// after about 49 days.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Clock,
+///     Dialect,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let program =
+///     Program::parse_with_extensions("+++$", Dialect::Standard, &['$']);
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(program)
+///     .instruction_handler('$', Clock::new())
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// // The clock overwrote the 3, the program took far less than 2^24 ms
+/// assert_eq!(u8::from(&machine.tape()[0]), 0);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    start: Instant,
+}
+
+impl Clock {
+    /// The number of cells the clock writes
+    pub const CELLS: usize = 4;
+
+    /// A clock that starts now
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+
+    /// The milliseconds since the clock started, as the clock writes them
+    #[must_use]
+    pub fn elapsed(&self) -> [Byte; Self::CELLS] {
+        let milliseconds = u32::try_from(self.start.elapsed().as_millis()).unwrap_or(u32::MAX);
+        milliseconds.to_be_bytes().map(Byte::from)
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InstructionHandler for Clock {
+    fn execute(&mut self, vm: &mut VmContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let pointer = vm.memory_pointer();
+        let length = vm.tape().len();
+        let elapsed = self.elapsed();
+        vm.tape_mut()
+            .edit(pointer..pointer + Self::CELLS, |cells| {
+                cells.copy_from_slice(&elapsed);
+            })
+            .ok_or_else(|| {
+                VMError::CellOutOfRange {
+                    cell: pointer + Self::CELLS - 1,
+                    length,
+                }
+                .into()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        thread,
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::{
+        Dialect,
+        Program,
+        VirtualMachine,
+    };
+
+    #[test]
+    fn test_clock() {
+        let clock = Clock::new();
+        thread::sleep(Duration::from_millis(20));
+        let elapsed = u32::from_be_bytes(clock.elapsed().map(|cell| u8::from(&cell)));
+        assert!((20..10_000).contains(&elapsed));
+
+        let mut machine = VirtualMachine::builder()
+            .input_device(std::io::stdin())
+            .tape_size(5)
+            .program(Program::parse_with_extensions(
+                ">>$",
+                Dialect::Standard,
+                &['$'],
+            ))
+            .instruction_handler('$', clock)
+            .build()
+            .unwrap();
+        let error = machine.run().unwrap_err();
+        assert_eq!(
+            error.source().unwrap().to_string(),
+            "Cell 5 is beyond the end of the tape of 5 cells"
+        );
+    }
+
+    #[test]
+    fn test_host_call_errors() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(std::io::stdin())
+            .program(Program::parse_with_extensions(
+                "h",
+                Dialect::Standard,
+                &['h'],
+            ))
+            .host_call('h', |_: &mut [Byte]| Err("refused".into()))
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            machine.run(),
+            Err(VMError::Extension { character: 'h', .. })
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{
+    vm_reader::VMReader,
+    vm_writer::VMWriter,
+    Instruction,
+    Profiler,
+    Program,
+    VMError,
+    VirtualMachine,
+};
+
+/// A loop taken out of a `Program` so that it can be benchmarked on its own
+///
+/// Most of the time of a run is often spent in a single loop. A `HotLoop`
+/// holds that loop as a standalone [`Program`], together with the state of
+/// the tape the first time the loop ran: the value of every cell that was not
+/// zero, and the cell the pointer was on. The [`harness`](Self::harness)
+/// puts those values back in place before running the loop, so a rewrite of
+/// the loop can be checked and timed without the rest of the program.
+///
+/// Setting a cell up costs one `+` for every unit of its value, so the
+/// harness itself runs in a number of steps proportional to the sum of the
+/// cells. A loop that reads input reads from whatever device the harness is
+/// run with.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     HotLoop,
+///     Profiler,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let source = Program::from("+++>++[<[->>+<<]>-]");
+/// let machine = || {
+///     VirtualMachine::builder()
+///         .input_device(std::io::stdin())
+///         .output_device(CaptureWriter::default())
+///         .program(source.clone())
+///         .build()
+///         .unwrap()
+/// };
+///
+/// let mut profiler = Profiler::new();
+/// profiler.run(&mut machine()).unwrap();
+///
+/// let hot = HotLoop::extract(&profiler, &mut machine())
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(hot.entry(), 6);
+/// assert_eq!(hot.body(), &Program::from("[<[->>+<<]>-]"));
+/// assert_eq!(hot.cells(), [(0, 3), (1, 2)]);
+/// assert_eq!(hot.memory_pointer(), 1);
+/// assert_eq!(hot.harness(), Program::from("+++>++[<[->>+<<]>-]"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotLoop {
+    entry:          usize,
+    body:           Program,
+    cells:          Vec<(usize, u8)>,
+    memory_pointer: usize,
+}
+
+impl HotLoop {
+    /// Extract the loop that executed the most instructions in `profiler`
+    ///
+    /// `machine` must be loaded with the program that was profiled and not
+    /// have run yet. It is run up to the first time the loop is entered, that
+    /// is the first time its `[` is reached with a cell that is not zero, and
+    /// the state of its tape at that point becomes the precondition of the
+    /// loop.
+    ///
+    /// # Returns
+    ///
+    /// The loop, or `None` if the profile has no loop or `machine` halts
+    /// without entering it.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction)
+    /// while running up to the loop.
+    pub fn extract<R, W>(
+        profiler: &Profiler,
+        machine: &mut VirtualMachine<R, W>,
+    ) -> Result<Option<Self>, VMError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        match profiler.hottest_loop() {
+            Some(entry) => Self::extract_at(entry, machine),
+            None => Ok(None),
+        }
+    }
+
+    /// Extract the loop whose `[` is at `entry` in the program of `machine`
+    ///
+    /// This works like [`extract`](Self::extract) for a loop chosen by the
+    /// caller.
+    ///
+    /// # Returns
+    ///
+    /// The loop, or `None` if there is no `[` at `entry` or `machine` halts
+    /// without entering the loop.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction)
+    /// while running up to the loop.
+    pub fn extract_at<R, W>(
+        entry: usize,
+        machine: &mut VirtualMachine<R, W>,
+    ) -> Result<Option<Self>, VMError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        let program = machine.program();
+        let Some(exit) = program.find_matching_bracket(entry) else {
+            return Ok(None);
+        };
+
+        while !machine.is_halted() {
+            let pointer = machine.memory_pointer();
+            if machine.program_counter() == entry && u8::from(&machine.tape()[pointer]) != 0 {
+                let cells = machine
+                    .tape()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, cell)| (index, u8::from(cell)))
+                    .filter(|(_, value)| *value != 0)
+                    .collect();
+                return Ok(Some(Self {
+                    entry,
+                    body: program.slice(entry..=exit),
+                    cells,
+                    memory_pointer: pointer,
+                }));
+            }
+            machine.execute_instruction()?;
+        }
+        Ok(None)
+    }
+
+    /// The position of the loop's `[` in the original program
+    #[must_use]
+    pub const fn entry(&self) -> usize {
+        self.entry
+    }
+
+    /// The loop on its own, from its `[` to its `]`
+    #[must_use]
+    pub const fn body(&self) -> &Program {
+        &self.body
+    }
+
+    /// The cells that were not zero when the loop was first entered, with
+    /// their values, in the order of the tape
+    #[must_use]
+    pub fn cells(&self) -> &[(usize, u8)] {
+        &self.cells
+    }
+
+    /// The cell the pointer was on when the loop was first entered
+    #[must_use]
+    pub const fn memory_pointer(&self) -> usize {
+        self.memory_pointer
+    }
+
+    /// A program that sets the tape up as it was when the loop was first
+    /// entered, moves to the cell the loop started on and runs the loop
+    ///
+    /// The harness expects a tape of zeros with the pointer on the first
+    /// cell, which is how every `VirtualMachine` starts.
+    #[must_use]
+    pub fn harness(&self) -> Program {
+        let mut instructions = Vec::new();
+        let mut pointer = 0;
+        let targets = self.cells.iter().copied().chain([(self.memory_pointer, 0)]);
+        for (index, value) in targets {
+            let (step, distance) = if index >= pointer {
+                (Instruction::IncrementPointer, index - pointer)
+            } else {
+                (Instruction::DecrementPointer, pointer - index)
+            };
+            instructions.extend(std::iter::repeat(step).take(distance));
+            instructions.extend(std::iter::repeat(Instruction::IncrementValue).take(value.into()));
+            pointer = index;
+        }
+
+        Program::from(instructions).concat(&self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CaptureWriter,
+        MockReader,
+    };
+
+    fn machine(program: Program) -> VirtualMachine<MockReader, CaptureWriter> {
+        VirtualMachine::builder()
+            .input_device(MockReader::default())
+            .output_device(CaptureWriter::default())
+            .program(program)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_harness_reproduces_the_loop() {
+        let program = Program::from(">>+++++[<++<+++>>-]<<[-]");
+        let mut profiler = Profiler::new();
+        let mut original = machine(program.clone());
+        profiler.run(&mut original).unwrap();
+
+        let hot = HotLoop::extract(&profiler, &mut machine(program))
+            .unwrap()
+            .unwrap();
+        assert_eq!(hot.entry(), 7);
+        assert_eq!(hot.cells(), [(2, 5)]);
+
+        // Running the harness leaves the tape as the loop did in the program
+        let mut harness = machine(hot.harness());
+        harness.run().unwrap();
+        assert_eq!(
+            harness.tape().slice(0..3)[..],
+            [15, 10, 0].map(crate::Byte::from)
+        );
+        assert_eq!(harness.memory_pointer(), 2);
+    }
+
+    #[test]
+    fn test_loop_never_entered() {
+        let mut vm = machine(Program::from("+[-][+]"));
+        assert_eq!(HotLoop::extract_at(4, &mut vm).unwrap(), None);
+
+        let mut vm = machine(Program::from("+[-][+]"));
+        assert_eq!(HotLoop::extract_at(2, &mut vm).unwrap(), None);
+    }
+}
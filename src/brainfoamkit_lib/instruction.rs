@@ -3,12 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::fmt::{
+use alloc::string::ToString;
+use core::fmt::{
     self,
     Display,
     Formatter,
 };
 
+use crate::ParseError;
+
 /// All possible instructions that can be understood by the interpreter
 ///
 /// This enum is at the heart of the interpreter. This enumerates
@@ -66,6 +69,40 @@ pub enum Instruction {
     ///
     /// This does not have a corresponding instruction in `BrainFuck`
     NoOp,
+    /// Instruction to hand a dump of the tape to the debug hook
+    ///
+    /// Internal representation of the `#` instruction of the
+    /// [`Debug`](enum.Dialect.html#variant.Debug) dialect.
+    DebugDump,
+    /// Instruction to define a procedure numbered by the current value
+    ///
+    /// Internal representation of the `(` instruction of the
+    /// [`Pbrain`](enum.Dialect.html#variant.Pbrain) dialect. The body up to
+    /// the matching `)` is skipped when the definition is executed.
+    DefineProcedure,
+    /// Instruction to return from a procedure
+    ///
+    /// Internal representation of the `)` instruction of the
+    /// [`Pbrain`](enum.Dialect.html#variant.Pbrain) dialect.
+    EndProcedure,
+    /// Instruction to call the procedure numbered by the current value
+    ///
+    /// Internal representation of the `:` instruction of the
+    /// [`Pbrain`](enum.Dialect.html#variant.Pbrain) dialect.
+    CallProcedure,
+    /// Instruction to stop the program
+    ///
+    /// Internal representation of the `@` instruction of the
+    /// [`Pbrain`](enum.Dialect.html#variant.Pbrain) dialect.
+    Halt,
+    /// A custom instruction, executed by the
+    /// [`InstructionHandler`](crate::InstructionHandler) registered for its
+    /// character
+    ///
+    /// See
+    /// [`Program::parse_with_extensions`](struct.Program.html#method.
+    /// parse_with_extensions).
+    Extension(char),
 }
 
 impl Instruction {
@@ -73,6 +110,8 @@ impl Instruction {
     ///
     /// This method takes in a a single instruction (character
     /// and converts that into the instruction.
+    /// Use [`Dialect::instruction`](enum.Dialect.html#method.instruction) to
+    /// also recognize extension instructions.
     ///
     /// This ignores any instructions not in the standard alphabet
     /// of `BrainFuck` and counts them as No-Ops
@@ -129,6 +168,200 @@ impl Instruction {
             _ => Self::NoOp,
         }
     }
+
+    /// Return the canonical `BrainFuck` instruction set
+    ///
+    /// This returns the eight instructions that have a corresponding
+    /// character in `BrainFuck`, in the order `> < + - . , [ ]`. The `NoOp`
+    /// instruction is not part of the canonical set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Instruction;
+    ///
+    /// let commands: String = Instruction::all()
+    ///     .iter()
+    ///     .filter_map(|instruction| instruction.to_char())
+    ///     .collect();
+    ///
+    /// assert_eq!(commands, "><+-.,[]");
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// An array holding every instruction except `NoOp`
+    #[must_use]
+    pub const fn all() -> [Self; 8] {
+        [
+            Self::IncrementPointer,
+            Self::DecrementPointer,
+            Self::IncrementValue,
+            Self::DecrementValue,
+            Self::OutputValue,
+            Self::InputValue,
+            Self::JumpForward,
+            Self::JumpBackward,
+        ]
+    }
+
+    /// Convert an Instruction back to its `BrainFuck` character
+    ///
+    /// This is the inverse of [`from_char()`](#method.from_char) for the
+    /// eight command characters. Extension instructions return the character
+    /// they have in their [`Dialect`](enum.Dialect.html).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Instruction;
+    ///
+    /// assert_eq!(Instruction::IncrementValue.to_char(), Some('+'));
+    /// assert_eq!(Instruction::NoOp.to_char(), None);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The character for the instruction, or `None` for `NoOp` since it has
+    /// no character of its own
+    #[must_use]
+    pub const fn to_char(&self) -> Option<char> {
+        match self {
+            Self::IncrementPointer => Some('>'),
+            Self::DecrementPointer => Some('<'),
+            Self::IncrementValue => Some('+'),
+            Self::DecrementValue => Some('-'),
+            Self::OutputValue => Some('.'),
+            Self::InputValue => Some(','),
+            Self::JumpForward => Some('['),
+            Self::JumpBackward => Some(']'),
+            Self::DebugDump => Some('#'),
+            Self::DefineProcedure => Some('('),
+            Self::EndProcedure => Some(')'),
+            Self::CallProcedure => Some(':'),
+            Self::Halt => Some('@'),
+            Self::Extension(c) => Some(*c),
+            Self::NoOp => None,
+        }
+    }
+
+    /// Whether the instruction reads the cell under the memory pointer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Instruction;
+    ///
+    /// assert!(Instruction::OutputValue.reads_cell());
+    /// assert!(Instruction::JumpBackward.reads_cell());
+    /// assert!(!Instruction::InputValue.reads_cell());
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// `true` for `+`, `-`, `.`, `[`, `]` and the procedure instructions that
+    /// read their id from the cell. Extension instructions can access any
+    /// cell, so they return `false`.
+    #[must_use]
+    pub const fn reads_cell(&self) -> bool {
+        matches!(
+            self,
+            Self::IncrementValue
+                | Self::DecrementValue
+                | Self::OutputValue
+                | Self::JumpForward
+                | Self::JumpBackward
+                | Self::DefineProcedure
+                | Self::CallProcedure
+        )
+    }
+
+    /// Whether the instruction writes the cell under the memory pointer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Instruction;
+    ///
+    /// assert!(Instruction::InputValue.writes_cell());
+    /// assert!(!Instruction::OutputValue.writes_cell());
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// `true` for `+`, `-` and `,`. Extension instructions can access any
+    /// cell, so they return `false`.
+    #[must_use]
+    pub const fn writes_cell(&self) -> bool {
+        matches!(
+            self,
+            Self::IncrementValue | Self::DecrementValue | Self::InputValue
+        )
+    }
+}
+
+impl TryFrom<char> for Instruction {
+    type Error = ParseError;
+
+    /// Strictly convert a char to an Instruction
+    ///
+    /// Unlike [`from_char()`](#method.from_char), this does not treat
+    /// unknown characters as `NoOp`s. This allows strict parsers and analyzers
+    /// to tell comments apart from commands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Instruction;
+    ///
+    /// assert_eq!(
+    ///     Instruction::try_from('[').unwrap(),
+    ///     Instruction::JumpForward
+    /// );
+    /// assert!(Instruction::try_from('a').is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the character is not one of the eight `BrainFuck`
+    /// command characters.
+    fn try_from(c: char) -> Result<Self, ParseError> {
+        match Self::from_char(c) {
+            Self::NoOp => Err(ParseError::NotAnInstruction(c.to_string())),
+            instruction => Ok(instruction),
+        }
+    }
+}
+
+impl TryFrom<&str> for Instruction {
+    type Error = ParseError;
+
+    /// Strictly convert a single-character string to an Instruction
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Instruction;
+    ///
+    /// assert_eq!(
+    ///     Instruction::try_from(".").unwrap(),
+    ///     Instruction::OutputValue
+    /// );
+    /// assert!(Instruction::try_from("..").is_err());
+    /// assert!(Instruction::try_from("").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not exactly one `BrainFuck` command
+    /// character.
+    fn try_from(s: &str) -> Result<Self, ParseError> {
+        let mut characters = s.chars();
+        match (characters.next(), characters.next()) {
+            (Some(c), None) => Self::try_from(c),
+            _ => Err(ParseError::NotASingleInstruction(s.to_string())),
+        }
+    }
 }
 
 /// Convert an instruction to a String
@@ -169,6 +402,12 @@ impl Display for Instruction {
             Self::JumpForward => write!(f, "JMPFWD"),
             Self::JumpBackward => write!(f, "JMPBCK"),
             Self::NoOp => write!(f, "NOOP"),
+            Self::DebugDump => write!(f, "DBGDMP"),
+            Self::DefineProcedure => write!(f, "DEFPRC"),
+            Self::EndProcedure => write!(f, "ENDPRC"),
+            Self::CallProcedure => write!(f, "CALPRC"),
+            Self::Halt => write!(f, "HALT"),
+            Self::Extension(c) => write!(f, "EXT({c})"),
         }
     }
 }
@@ -190,6 +429,63 @@ mod tests {
         assert_eq!(Instruction::from_char(' '), Instruction::NoOp);
     }
 
+    #[test]
+    fn test_instruction_try_from_char() {
+        for instruction in Instruction::all() {
+            let c = instruction.to_char().unwrap();
+            assert_eq!(Instruction::try_from(c).unwrap(), instruction);
+        }
+        assert!(Instruction::try_from(' ').is_err());
+        assert!(Instruction::try_from('a').is_err());
+    }
+
+    #[test]
+    fn test_instruction_try_from_str() {
+        assert_eq!(
+            Instruction::try_from(">").unwrap(),
+            Instruction::IncrementPointer
+        );
+        assert_eq!(
+            Instruction::try_from("]").unwrap(),
+            Instruction::JumpBackward
+        );
+        assert!(Instruction::try_from("").is_err());
+        assert!(Instruction::try_from("+-").is_err());
+        assert!(Instruction::try_from("#").is_err());
+    }
+
+    #[test]
+    fn test_instruction_all() {
+        let all = Instruction::all();
+
+        assert_eq!(all.len(), 8);
+        assert!(!all.contains(&Instruction::NoOp));
+        for instruction in all {
+            assert_eq!(
+                Instruction::from_char(instruction.to_char().unwrap()),
+                instruction
+            );
+        }
+    }
+
+    #[test]
+    fn test_instruction_to_char() {
+        assert_eq!(Instruction::IncrementPointer.to_char(), Some('>'));
+        assert_eq!(Instruction::DecrementPointer.to_char(), Some('<'));
+        assert_eq!(Instruction::IncrementValue.to_char(), Some('+'));
+        assert_eq!(Instruction::DecrementValue.to_char(), Some('-'));
+        assert_eq!(Instruction::OutputValue.to_char(), Some('.'));
+        assert_eq!(Instruction::InputValue.to_char(), Some(','));
+        assert_eq!(Instruction::JumpForward.to_char(), Some('['));
+        assert_eq!(Instruction::JumpBackward.to_char(), Some(']'));
+        assert_eq!(Instruction::DebugDump.to_char(), Some('#'));
+        assert_eq!(Instruction::DefineProcedure.to_char(), Some('('));
+        assert_eq!(Instruction::EndProcedure.to_char(), Some(')'));
+        assert_eq!(Instruction::CallProcedure.to_char(), Some(':'));
+        assert_eq!(Instruction::Halt.to_char(), Some('@'));
+        assert_eq!(Instruction::NoOp.to_char(), None);
+    }
+
     #[test]
     fn test_instruction_display() {
         assert_eq!(format!("{}", Instruction::IncrementPointer), "INCPTR");
@@ -201,5 +497,11 @@ mod tests {
         assert_eq!(format!("{}", Instruction::JumpForward), "JMPFWD");
         assert_eq!(format!("{}", Instruction::JumpBackward), "JMPBCK");
         assert_eq!(format!("{}", Instruction::NoOp), "NOOP");
+        assert_eq!(format!("{}", Instruction::DebugDump), "DBGDMP");
+        assert_eq!(format!("{}", Instruction::DefineProcedure), "DEFPRC");
+        assert_eq!(format!("{}", Instruction::EndProcedure), "ENDPRC");
+        assert_eq!(format!("{}", Instruction::CallProcedure), "CALPRC");
+        assert_eq!(format!("{}", Instruction::Halt), "HALT");
+        assert_eq!(format!("{}", Instruction::Extension('%')), "EXT(%)");
     }
 }
@@ -0,0 +1,298 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    io,
+};
+
+use crate::{
+    vm_reader::VMReader,
+    vm_writer::VMWriter,
+    Byte,
+    ReadError,
+    TapeStorage,
+    VMError,
+};
+
+/// A custom instruction
+///
+/// An `InstructionHandler` adds an instruction to the `VirtualMachine`
+/// without changing the machine itself. It is registered for a character with
+/// [`VirtualMachineBuilder::instruction_handler`](crate::VirtualMachineBuilder::instruction_handler),
+/// and runs every time the machine executes an
+/// [`Instruction::Extension`](crate::Instruction::Extension) with that
+/// character. Programs with extension instructions are parsed with
+/// [`Program::parse_with_extensions`](crate::Program::parse_with_extensions).
+///
+/// A handler must be `Send`, so that the machine can still be moved to
+/// another thread.
+///
+/// # Examples
+///
+/// ```
+/// use std::error::Error;
+///
+/// use brainfoamkit_lib::{
+///     Byte,
+///     CaptureWriter,
+///     Dialect,
+///     InstructionHandler,
+///     Program,
+///     VirtualMachine,
+///     VmContext,
+/// };
+///
+/// /// `*` doubles the current cell
+/// struct Double;
+///
+/// impl InstructionHandler for Double {
+///     fn execute(
+///         &mut self,
+///         vm: &mut VmContext,
+///     ) -> Result<(), Box<dyn Error + Send + Sync>> {
+///         let value = u8::from(&vm.cell());
+///         vm.set_cell(Byte::from(value.wrapping_mul(2)));
+///         Ok(())
+///     }
+/// }
+///
+/// let program =
+///     Program::parse_with_extensions("+++**.", Dialect::Standard, &['*']);
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(program)
+///     .instruction_handler('*', Double)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(machine.output_device().data, [12]);
+/// ```
+pub trait InstructionHandler: Send {
+    /// Execute the instruction
+    ///
+    /// # Arguments
+    ///
+    /// * `vm` - The parts of the machine the instruction may use
+    ///
+    /// # Errors
+    ///
+    /// Any error stops the machine with a
+    /// [`VMError::Extension`](crate::VMError::Extension) that wraps it.
+    fn execute(&mut self, vm: &mut VmContext) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+/// The parts of a `VirtualMachine` an [`InstructionHandler`] can use
+///
+/// A handler can read and change the tape and the memory pointer, read from
+/// the input device and write to the output device. The program counter
+/// moves on to the next instruction after the handler returns, as it does
+/// for every other instruction.
+pub struct VmContext<'a> {
+    tape:            &'a mut TapeStorage,
+    memory_pointer:  &'a mut usize,
+    program_counter: usize,
+    steps:           usize,
+    input:           &'a mut dyn VMReader,
+    output:          &'a mut dyn VMWriter,
+    bytes_written:   &'a mut usize,
+}
+
+impl<'a> VmContext<'a> {
+    pub(crate) fn new(
+        tape: &'a mut TapeStorage,
+        memory_pointer: &'a mut usize,
+        program_counter: usize,
+        steps: usize,
+        input: &'a mut dyn VMReader,
+        output: &'a mut dyn VMWriter,
+        bytes_written: &'a mut usize,
+    ) -> Self {
+        Self {
+            tape,
+            memory_pointer,
+            program_counter,
+            steps,
+            input,
+            output,
+            bytes_written,
+        }
+    }
+
+    /// The value of the cell under the memory pointer
+    #[must_use]
+    pub fn cell(&self) -> Byte {
+        self.tape[*self.memory_pointer]
+    }
+
+    /// Replace the value of the cell under the memory pointer
+    pub fn set_cell(&mut self, value: Byte) {
+        self.tape.set(*self.memory_pointer, value);
+    }
+
+    /// The cells of the tape
+    #[must_use]
+    pub fn tape(&self) -> &TapeStorage {
+        self.tape
+    }
+
+    /// The cells of the tape, for changing cells other than the current one
+    pub fn tape_mut(&mut self) -> &mut TapeStorage {
+        self.tape
+    }
+
+    /// The position of the memory pointer
+    #[must_use]
+    pub fn memory_pointer(&self) -> usize {
+        *self.memory_pointer
+    }
+
+    /// Move the memory pointer to `pointer`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VMError::CellOutOfRange`] if `pointer` is beyond the end of
+    /// the tape. The memory pointer does not move.
+    pub fn set_memory_pointer(&mut self, pointer: usize) -> Result<(), VMError> {
+        if pointer >= self.tape.len() {
+            return Err(VMError::CellOutOfRange {
+                cell:   pointer,
+                length: self.tape.len(),
+            });
+        }
+        *self.memory_pointer = pointer;
+        Ok(())
+    }
+
+    /// The position of the instruction being executed
+    #[must_use]
+    pub const fn program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    /// The number of instructions executed before this one
+    #[must_use]
+    pub const fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Read a byte from the input device
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input device fails or has no more input.
+    pub fn read(&mut self) -> Result<u8, ReadError> {
+        self.input.read()
+    }
+
+    /// Write a byte to the output device
+    ///
+    /// The byte counts towards the output limit of the machine, which is
+    /// only checked before `.` instructions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the output device fails.
+    pub fn write(&mut self, value: u8) -> io::Result<()> {
+        self.output.write(value)?;
+        *self.bytes_written += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        vm_reader::MockReader,
+        CaptureWriter,
+        Dialect,
+        Instruction,
+        Program,
+        VirtualMachine,
+    };
+
+    /// `%` echoes the input twice and moves to the last cell, `!` fails
+    struct Echo;
+
+    impl InstructionHandler for Echo {
+        fn execute(&mut self, vm: &mut VmContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+            let value = vm.read()?;
+            vm.write(value)?;
+            vm.write(value)?;
+            vm.set_cell(Byte::from(value));
+            vm.set_memory_pointer(vm.tape().len() - 1)?;
+            Ok(())
+        }
+    }
+
+    struct Fail;
+
+    impl InstructionHandler for Fail {
+        fn execute(&mut self, _vm: &mut VmContext) -> Result<(), Box<dyn Error + Send + Sync>> {
+            Err("out of order".into())
+        }
+    }
+
+    fn extended_machine(source: &str) -> VirtualMachine<MockReader, CaptureWriter> {
+        VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(b"A".to_vec()),
+            })
+            .output_device(CaptureWriter::default())
+            .tape_size(4)
+            .program(Program::parse_with_extensions(
+                source,
+                Dialect::Standard,
+                &['%', '!', '$'],
+            ))
+            .instruction_handler('%', Echo)
+            .instruction_handler('!', Fail)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_handler() {
+        let mut machine = extended_machine("%+.");
+        machine.run().unwrap();
+
+        assert_eq!(machine.output_device().data, b"AA\x01");
+        assert_eq!(machine.tape()[0], Byte::from(b'A'));
+        assert_eq!(machine.memory_pointer(), 3);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut machine = extended_machine("+!");
+        let error = machine.run().unwrap_err();
+        assert!(matches!(
+            error,
+            VMError::Extension {
+                character: '!',
+                instruction: 1,
+                ..
+            }
+        ));
+        assert_eq!(error.source().unwrap().to_string(), "out of order");
+
+        let mut machine = extended_machine("$");
+        assert_eq!(
+            machine.program().get_instruction(0),
+            Some(Instruction::Extension('$'))
+        );
+        assert!(matches!(
+            machine.run(),
+            Err(VMError::UnknownExtension {
+                character:   '$',
+                instruction: 0,
+            })
+        ));
+    }
+}
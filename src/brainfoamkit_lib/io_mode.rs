@@ -0,0 +1,114 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    str::FromStr,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::ParseError;
+
+/// How `,` and `.` exchange the value of a cell with the outside world
+///
+/// Programs normally read and write bytes, which is what text processing
+/// needs. Programs that do arithmetic are easier to follow when their input
+/// and output are numbers instead: in numeric mode, `,` reads a decimal number
+/// from 0 to 255 and `.` writes the value of the cell as a decimal number
+/// followed by a newline. See
+/// [`VMReader::read_number`](crate::VMReader::read_number) and
+/// [`VMWriter::write_number`](crate::VMWriter::write_number).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     IoMode,
+///     Program,
+///     VecReader,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(VecReader::new(b"40 2".to_vec()))
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from(",>,[-<+>]<."))
+///     .io_mode(IoMode::Numeric)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(machine.output_device().data, b"42\n");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoMode {
+    /// Read and write the value of the cell as a single byte
+    #[default]
+    Bytes,
+    /// Read and write the value of the cell as a decimal number
+    Numeric,
+}
+
+impl Display for IoMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Bytes => write!(f, "bytes"),
+            Self::Numeric => write!(f, "numeric"),
+        }
+    }
+}
+
+impl FromStr for IoMode {
+    type Err = ParseError;
+
+    /// Parse the name of an I/O mode
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::IoMode;
+    ///
+    /// assert_eq!("numeric".parse::<IoMode>().unwrap(), IoMode::Numeric);
+    /// assert!("decimal".parse::<IoMode>().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name does not match any mode.
+    fn from_str(name: &str) -> Result<Self, ParseError> {
+        match name {
+            "bytes" => Ok(Self::Bytes),
+            "numeric" => Ok(Self::Numeric),
+            _ => Err(ParseError::UnknownName {
+                kind: "I/O mode",
+                name: name.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for mode in [IoMode::Bytes, IoMode::Numeric] {
+            assert_eq!(mode.to_string().parse::<IoMode>().unwrap(), mode);
+        }
+        assert_eq!(IoMode::default(), IoMode::Bytes);
+        assert!("Bytes".parse::<IoMode>().is_err());
+    }
+}
@@ -3,34 +3,412 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+//! The building blocks of the `BrainFoamKit` interpreter
+//!
+//! With the default `std` feature, the crate provides the full
+//! [`VirtualMachine`] and the tools built on it. Without it, the crate is
+//! `no_std` and only needs `alloc`: the core types, such as [`Byte`],
+//! [`Instruction`] and [`Program`], and the [`CoreMachine`] remain available,
+//! so that programs can run on embedded targets and in constrained WASM
+//! environments. The `tokio` feature adds
+//! `VirtualMachine::run_async`, which waits for input and output on tokio's
+//! readers and writers without blocking a thread. The default `cli` feature
+//! only builds the `bfkrun` and `bfkview` binaries; a library that depends on
+//! this crate can turn it off with `default-features = false, features =
+//! ["std"]`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// `Byte::iter` and `Nybble::iter` elide the lifetime of their iterators,
+// which newer compilers lint against and the MSRV does not know about
+#![allow(unknown_lints, mismatched_lifetime_syntaxes)]
+
+extern crate alloc;
+
 // Add the relevant modules
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
 mod ascii_char;
+#[cfg(feature = "std")]
 mod ascii_table;
+#[cfg(feature = "std")]
+mod asciicast;
+#[cfg(feature = "std")]
+mod assertion;
+#[cfg(feature = "tokio")]
+mod async_io;
+#[cfg(feature = "std")]
+mod behavior_profile;
 mod bit;
+mod bit_view;
+#[cfg(feature = "std")]
+mod bookmarks;
 mod byte;
+#[cfg(feature = "std")]
+mod cancellation;
+mod carry_steps;
+#[cfg(feature = "std")]
+mod cell_history;
+#[cfg(feature = "std")]
+mod cell_stats;
+#[cfg(feature = "std")]
+mod cell_view;
+#[cfg(feature = "std")]
+mod checkpoint;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(all(test, feature = "std"))]
+mod conformance;
+mod core_machine;
+#[cfg(feature = "std")]
+mod coverage;
+#[cfg(feature = "std")]
+pub mod crash;
+#[cfg(feature = "std")]
+mod debug_dump;
+#[cfg(feature = "std")]
+mod diagnostics;
+mod dialect;
+#[cfg(feature = "std")]
+pub mod differential;
+#[cfg(feature = "std")]
+mod dual_tape;
+#[cfg(feature = "std")]
+mod eof_behavior;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "std")]
+mod fault_injection;
+pub mod format;
+pub mod gates;
+#[cfg(feature = "std")]
+mod history;
+#[cfg(feature = "std")]
+mod host_call;
+#[cfg(feature = "std")]
+mod hot_loop;
 mod instruction;
+#[cfg(feature = "std")]
+mod instruction_handler;
+#[cfg(feature = "std")]
+mod io_mode;
 mod iterable_byte;
 mod iterable_nybble;
+mod limits;
+#[cfg(feature = "std")]
+mod loop_frame;
+#[cfg(feature = "std")]
 mod machine;
+#[cfg(feature = "std")]
 mod machine_builder;
+#[cfg(feature = "std")]
+mod machine_parts;
+#[cfg(feature = "std")]
+mod memory_map;
+#[cfg(feature = "std")]
+pub mod minimize;
+#[cfg(feature = "mmap")]
+mod mmap_tape;
+#[cfg(feature = "std")]
+pub mod mutate;
+#[cfg(feature = "std")]
+pub mod net;
 mod nybble;
+#[cfg(feature = "std")]
+mod paged_tape;
+mod parse_error;
+mod pipeline;
+#[cfg(feature = "std")]
+mod preprocessor;
+#[cfg(feature = "std")]
+mod profiler;
 mod program;
+#[cfg(feature = "std")]
+mod program_builder;
+mod program_statistics;
+#[cfg(feature = "std")]
+mod progress;
+#[cfg(feature = "std")]
+pub mod project;
+#[cfg(feature = "std")]
+mod random_byte;
+#[cfg(feature = "std")]
+pub mod reference;
+#[cfg(feature = "std")]
+mod replay_bundle;
+#[cfg(feature = "std")]
+mod resources;
+#[cfg(feature = "std")]
+mod rng;
+#[cfg(feature = "std")]
+mod run_iter;
+#[cfg(feature = "std")]
+mod session;
+#[cfg(feature = "std")]
+mod shared_vm;
+#[cfg(feature = "std")]
+mod snapshot_store;
+#[cfg(feature = "std")]
+mod state_diff;
+#[cfg(feature = "std")]
+pub mod symbolic;
+#[cfg(feature = "std")]
+mod taint;
+mod tape;
+#[cfg(feature = "std")]
+mod tape_format;
+#[cfg(feature = "std")]
+mod tape_storage;
+#[cfg(feature = "std")]
+pub mod test_runner;
+#[cfg(feature = "std")]
+mod throttle;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod tutorial;
+#[cfg(feature = "std")]
 mod vm_reader;
+#[cfg(feature = "std")]
+mod vm_snapshot;
+#[cfg(feature = "std")]
+mod vm_writer;
+mod word;
 
 // Re-export the useful contents
+#[cfg(feature = "std")]
 pub use ascii_char::AsciiChar;
+#[cfg(feature = "std")]
 pub use ascii_table::AsciiTable;
+#[cfg(feature = "std")]
+pub use asciicast::Asciicast;
+#[cfg(feature = "std")]
+pub use assertion::AssertionFailed;
+#[cfg(feature = "tokio")]
+pub use async_io::{
+    AsyncVMReader,
+    AsyncVMWriter,
+    IoFuture,
+};
+#[cfg(feature = "std")]
+pub use behavior_profile::{
+    BehaviorProfile,
+    ProfileViolation,
+    TapeEdge,
+};
 pub use bit::Bit;
+pub use bit_view::BitView;
+#[cfg(feature = "std")]
+pub use bookmarks::Bookmarks;
 pub use byte::Byte;
+#[cfg(feature = "std")]
+pub use cancellation::CancellationToken;
+pub use carry_steps::{
+    CarryStep,
+    CarrySteps,
+};
+#[cfg(feature = "std")]
+pub use cell_history::CellHistoryRecorder;
+#[cfg(feature = "std")]
+pub use cell_stats::CellStats;
+#[cfg(feature = "std")]
+pub use cell_view::CellView;
+#[cfg(feature = "std")]
+pub use checkpoint::Checkpoint;
+pub use core_machine::CoreMachine;
+#[cfg(feature = "std")]
+pub use coverage::{
+    CoverageRecorder,
+    LoopCoverage,
+};
+#[cfg(feature = "std")]
+pub use debug_dump::{
+    DebugDump,
+    DebugHook,
+};
+#[cfg(feature = "std")]
+pub use diagnostics::{
+    DiagnosticsSink,
+    Warning,
+    WarningKind,
+    WarningPolicy,
+};
+pub use dialect::Dialect;
+#[cfg(feature = "std")]
+pub use dual_tape::{
+    AuxiliaryTape,
+    DualTapeVM,
+};
+#[cfg(feature = "std")]
+pub use eof_behavior::EofBehavior;
+#[cfg(feature = "std")]
+pub use error::{
+    BuildError,
+    CheckpointError,
+    ConfigError,
+    FileError,
+    ReadError,
+    ReplayError,
+    SessionError,
+    TraceError,
+    VMError,
+};
+#[cfg(feature = "std")]
+pub use fault_injection::{
+    FaultInjectingReader,
+    FaultInjectingWriter,
+    InjectedFault,
+    DEFAULT_FAULT_RATE,
+};
+#[cfg(feature = "std")]
+pub use history::{
+    History,
+    HistoryEntry,
+};
+#[cfg(feature = "std")]
+pub use host_call::{
+    Clock,
+    HostCall,
+};
+#[cfg(feature = "std")]
+pub use hot_loop::HotLoop;
 pub use instruction::Instruction;
+#[cfg(feature = "std")]
+pub use instruction_handler::{
+    InstructionHandler,
+    VmContext,
+};
+#[cfg(feature = "std")]
+pub use io_mode::IoMode;
 pub use iterable_byte::IterableByte;
 pub use iterable_nybble::IterableNybble;
-pub use machine::VirtualMachine;
+pub use limits::{
+    LimitExceeded,
+    Limits,
+};
+#[cfg(feature = "std")]
+pub use loop_frame::LoopFrame;
+#[cfg(feature = "std")]
+pub use machine::{
+    StopReason,
+    VirtualMachine,
+};
+#[cfg(feature = "std")]
 pub use machine_builder::VirtualMachineBuilder;
+#[cfg(feature = "std")]
+pub use machine_parts::{
+    Counters,
+    IoDevices,
+    TapeBackend,
+};
+#[cfg(feature = "std")]
+pub use memory_map::{
+    Access,
+    Direction,
+    MemoryMap,
+    Permission,
+};
+#[cfg(feature = "mmap")]
+pub use mmap_tape::MmapTape;
 pub use nybble::Nybble;
+#[cfg(feature = "std")]
+pub use paged_tape::PagedTape;
+pub use parse_error::ParseError;
+pub use pipeline::{
+    Pipeline,
+    PipelineSlot,
+    PipelineStage,
+    PipelineStats,
+};
+#[cfg(feature = "std")]
+pub use preprocessor::{
+    DataSegment,
+    PreprocessedSource,
+    Preprocessor,
+    SourceLocation,
+    SourceMap,
+};
+#[cfg(feature = "std")]
+pub use profiler::{
+    ProfileSpan,
+    Profiler,
+};
 pub use program::Program;
+#[cfg(feature = "std")]
+pub use program_builder::ProgramBuilder;
+pub use program_statistics::ProgramStatistics;
+#[cfg(feature = "std")]
+pub use progress::{
+    Progress,
+    ProgressReporter,
+};
+#[cfg(feature = "std")]
+pub use random_byte::RandomByte;
+#[cfg(feature = "std")]
+pub use replay_bundle::{
+    RecordingReader,
+    ReplayBundle,
+    ReplayOutcome,
+};
+#[cfg(feature = "std")]
+pub use resources::ResourceUsage;
+#[cfg(feature = "std")]
+pub use run_iter::{
+    ExecutionEvent,
+    RunIter,
+};
+#[cfg(feature = "std")]
+pub use session::DebugSession;
+#[cfg(feature = "std")]
+pub use shared_vm::SharedVm;
+#[cfg(feature = "std")]
+pub use snapshot_store::SnapshotStore;
+#[cfg(feature = "std")]
+pub use state_diff::{
+    DiffStream,
+    StateDiff,
+};
+#[cfg(feature = "std")]
+pub use taint::{
+    TaintTracker,
+    TaintedOutput,
+};
+pub use tape::{
+    FixedTape,
+    Tape,
+};
+#[cfg(feature = "std")]
+pub use tape_format::TapeFormat;
+#[cfg(feature = "std")]
+pub use tape_storage::TapeStorage;
+#[cfg(feature = "std")]
+pub use throttle::Throttle;
+#[cfg(feature = "std")]
 pub use vm_reader::{
     MockReader,
+    TerminalReader,
     VMReader,
     VMReaderType,
+    VecReader,
+};
+#[cfg(feature = "std")]
+pub use vm_snapshot::{
+    MachineCounter,
+    StateDifference,
+    VMSnapshot,
+};
+#[cfg(feature = "std")]
+pub use vm_writer::{
+    CaptureWriter,
+    FlushEvery,
+    RecordingWriter,
+    Utf8DecodingWriter,
+    VMWriter,
+    VMWriterType,
+};
+pub use word::{
+    Endianness,
+    Word,
 };
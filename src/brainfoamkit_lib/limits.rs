@@ -0,0 +1,408 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use core::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// Resource limits for a `VirtualMachine`
+///
+/// `Limits` stop a run that takes more resources than expected. Every limit is
+/// disabled by default. When a limit is exceeded, the `VirtualMachine` returns
+/// a [`LimitExceeded`](enum.LimitExceeded.html) error and stops before
+/// executing the offending instruction.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     LimitExceeded,
+///     Limits,
+///     Program,
+///     VMError,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("+[]"))
+///     .limits(Limits::new().max_steps(100))
+///     .build()
+///     .unwrap();
+///
+/// let error = machine.run().unwrap_err();
+/// assert!(matches!(
+///     error,
+///     VMError::LimitExceeded(LimitExceeded::Steps { limit: 100 })
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    max_steps:           Option<usize>,
+    max_loop_iterations: Option<usize>,
+    max_call_depth:      Option<usize>,
+    max_output_bytes:    Option<usize>,
+}
+
+impl Limits {
+    /// Create a new set of `Limits` with every limit disabled
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_steps:           None,
+            max_loop_iterations: None,
+            max_call_depth:      None,
+            max_output_bytes:    None,
+        }
+    }
+
+    /// Limit the total number of instructions a `VirtualMachine` executes
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - The maximum number of instructions to execute
+    ///
+    /// # Returns
+    ///
+    /// The `Limits` by value with the step limit set.
+    #[must_use]
+    pub const fn max_steps(mut self, steps: usize) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Limit the number of iterations of any single loop
+    ///
+    /// Unlike the global step limit, this catches an accidentally infinite
+    /// loop as soon as it starts spinning, and reports the loop that caused
+    /// it.
+    ///
+    /// # Arguments
+    ///
+    /// * `iterations` - The maximum number of times a loop body may be entered
+    ///   each time the loop is reached
+    ///
+    /// # Returns
+    ///
+    /// The `Limits` by value with the loop iteration limit set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Limits,
+    ///     Preprocessor,
+    ///     VMError,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let source = Preprocessor::new().process_str("+++\n[>+<]").unwrap();
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(source.program())
+    ///     .limits(Limits::new().max_loop_iterations(1000))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let Err(VMError::LimitExceeded(exceeded)) = machine.run() else {
+    ///     panic!("The loop should exceed its limit");
+    /// };
+    /// let location = source
+    ///     .source_map()
+    ///     .describe(exceeded.program_counter().unwrap());
+    ///
+    /// assert_eq!(location.as_deref(), Some("<input>:2:1"));
+    /// ```
+    #[must_use]
+    pub const fn max_loop_iterations(mut self, iterations: usize) -> Self {
+        self.max_loop_iterations = Some(iterations);
+        self
+    }
+
+    /// Limit the number of nested procedure calls
+    ///
+    /// Procedures only exist in the
+    /// [`Pbrain`](enum.Dialect.html#variant.Pbrain) dialect. The limit stops
+    /// runaway recursion before the call stack grows without bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - The maximum number of procedure calls that may be active at
+    ///   the same time
+    ///
+    /// # Returns
+    ///
+    /// The `Limits` by value with the call depth limit set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Dialect,
+    ///     LimitExceeded,
+    ///     Limits,
+    ///     Program,
+    ///     VMError,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::parse("+(:):", Dialect::Pbrain))
+    ///     .limits(Limits::new().max_call_depth(64))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let error = machine.run().unwrap_err();
+    /// assert!(matches!(
+    ///     error,
+    ///     VMError::LimitExceeded(LimitExceeded::CallDepth {
+    ///         call:  2,
+    ///         limit: 64,
+    ///     })
+    /// ));
+    /// ```
+    #[must_use]
+    pub const fn max_call_depth(mut self, depth: usize) -> Self {
+        self.max_call_depth = Some(depth);
+        self
+    }
+
+    /// Limit the number of bytes a `VirtualMachine` writes to its output
+    ///
+    /// A program that floods its output can fill a terminal or a disk long
+    /// before it runs out of steps. The `.` that would write past the limit
+    /// is not executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The maximum number of bytes to write
+    ///
+    /// # Returns
+    ///
+    /// The `Limits` by value with the output limit set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     LimitExceeded,
+    ///     Limits,
+    ///     Program,
+    ///     VMError,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("+[.]"))
+    ///     .limits(Limits::new().max_output_bytes(3))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let error = machine.run().unwrap_err();
+    /// assert!(matches!(
+    ///     error,
+    ///     VMError::LimitExceeded(LimitExceeded::OutputBytes {
+    ///         instruction: 2,
+    ///         limit:       3,
+    ///     })
+    /// ));
+    /// assert_eq!(machine.output_device().data, [1, 1, 1]);
+    /// ```
+    #[must_use]
+    pub const fn max_output_bytes(mut self, bytes: usize) -> Self {
+        self.max_output_bytes = Some(bytes);
+        self
+    }
+
+    /// The maximum number of instructions to execute, if limited
+    #[must_use]
+    pub const fn step_limit(&self) -> Option<usize> {
+        self.max_steps
+    }
+
+    /// The maximum number of iterations of a single loop, if limited
+    #[must_use]
+    pub const fn loop_iteration_limit(&self) -> Option<usize> {
+        self.max_loop_iterations
+    }
+
+    /// The maximum number of nested procedure calls, if limited
+    #[must_use]
+    pub const fn call_depth_limit(&self) -> Option<usize> {
+        self.max_call_depth
+    }
+
+    /// The maximum number of bytes to write, if limited
+    #[must_use]
+    pub const fn output_byte_limit(&self) -> Option<usize> {
+        self.max_output_bytes
+    }
+}
+
+/// The error returned when a `VirtualMachine` exceeds one of its `Limits`
+///
+/// The error is returned as
+/// [`VMError::LimitExceeded`](crate::VMError::LimitExceeded).
+///
+/// # See Also
+///
+/// * [`Limits`](struct.Limits.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitExceeded {
+    /// The total number of executed instructions reached the step limit
+    Steps {
+        /// The step limit that was reached
+        limit: usize,
+    },
+    /// A single loop ran for more iterations than allowed
+    LoopIterations {
+        /// The position of the loop's `[` in the `Program`
+        entry: usize,
+        /// The loop iteration limit that was exceeded
+        limit: usize,
+    },
+    /// A procedure call would nest deeper than allowed
+    CallDepth {
+        /// The position of the `:` that made the call
+        call:  usize,
+        /// The call depth limit that was exceeded
+        limit: usize,
+    },
+    /// The program tried to write more output than allowed
+    OutputBytes {
+        /// The position of the `.` that would have exceeded the limit
+        instruction: usize,
+        /// The output limit that was reached
+        limit:       usize,
+    },
+}
+
+impl LimitExceeded {
+    /// The position in the `Program` that caused the limit to be exceeded
+    ///
+    /// For a loop iteration limit, this is the position of the loop's `[`.
+    /// For a call depth limit, this is the position of the offending `:`, and
+    /// for an output limit the position of the offending `.`.
+    /// It can be mapped back to the original source with a
+    /// [`SourceMap`](struct.SourceMap.html).
+    ///
+    /// # Returns
+    ///
+    /// The position of the offending instruction, or `None` if the limit is
+    /// not tied to a single instruction.
+    #[must_use]
+    pub const fn program_counter(&self) -> Option<usize> {
+        match self {
+            Self::Steps { .. } => None,
+            Self::LoopIterations { entry, .. } => Some(*entry),
+            Self::CallDepth { call, .. } => Some(*call),
+            Self::OutputBytes { instruction, .. } => Some(*instruction),
+        }
+    }
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Steps { limit } => write!(f, "Exceeded the limit of {limit} steps"),
+            Self::LoopIterations { entry, limit } => write!(
+                f,
+                "Loop at instruction {entry} exceeded the limit of {limit} iterations"
+            ),
+            Self::CallDepth { call, limit } => write!(
+                f,
+                "Procedure call at instruction {call} exceeded the limit of {limit} nested calls"
+            ),
+            Self::OutputBytes { instruction, limit } => write!(
+                f,
+                "Output at instruction {instruction} exceeded the limit of {limit} bytes"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for LimitExceeded {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_limits() {
+        let limits = Limits::default();
+        assert_eq!(limits, Limits::new());
+        assert_eq!(limits.step_limit(), None);
+        assert_eq!(limits.loop_iteration_limit(), None);
+        assert_eq!(limits.call_depth_limit(), None);
+        assert_eq!(limits.output_byte_limit(), None);
+    }
+
+    #[test]
+    fn test_set_limits() {
+        let limits = Limits::new()
+            .max_steps(10)
+            .max_loop_iterations(5)
+            .max_call_depth(3)
+            .max_output_bytes(7);
+        assert_eq!(limits.step_limit(), Some(10));
+        assert_eq!(limits.loop_iteration_limit(), Some(5));
+        assert_eq!(limits.call_depth_limit(), Some(3));
+        assert_eq!(limits.output_byte_limit(), Some(7));
+    }
+
+    #[test]
+    fn test_limit_exceeded_display() {
+        assert_eq!(
+            LimitExceeded::Steps { limit: 10 }.to_string(),
+            "Exceeded the limit of 10 steps"
+        );
+        assert_eq!(
+            LimitExceeded::LoopIterations { entry: 3, limit: 5 }.to_string(),
+            "Loop at instruction 3 exceeded the limit of 5 iterations"
+        );
+        assert_eq!(
+            LimitExceeded::CallDepth { call: 4, limit: 2 }.to_string(),
+            "Procedure call at instruction 4 exceeded the limit of 2 nested calls"
+        );
+        assert_eq!(
+            LimitExceeded::OutputBytes {
+                instruction: 6,
+                limit:       1,
+            }
+            .to_string(),
+            "Output at instruction 6 exceeded the limit of 1 bytes"
+        );
+    }
+
+    #[test]
+    fn test_program_counter() {
+        assert_eq!(LimitExceeded::Steps { limit: 10 }.program_counter(), None);
+        assert_eq!(
+            LimitExceeded::LoopIterations { entry: 3, limit: 5 }.program_counter(),
+            Some(3)
+        );
+        assert_eq!(
+            LimitExceeded::CallDepth { call: 4, limit: 2 }.program_counter(),
+            Some(4)
+        );
+        assert_eq!(
+            LimitExceeded::OutputBytes {
+                instruction: 6,
+                limit:       1,
+            }
+            .program_counter(),
+            Some(6)
+        );
+    }
+}
@@ -0,0 +1,103 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// A loop that is currently being executed by the `VirtualMachine`
+///
+/// The `VirtualMachine` keeps a stack of `LoopFrame`s, one for every loop it
+/// has entered but not yet left. The innermost loop is at the top of the
+/// stack. This gives debuggers a "call stack"-like view of the nested loops
+/// that led to the current instruction.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     LoopFrame,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("++[>+<-]"))
+///     .build()
+///     .unwrap();
+///
+/// for _ in 0..4 {
+///     machine.execute_instruction().unwrap();
+/// }
+///
+/// assert_eq!(machine.loop_stack(), &[LoopFrame::new(2, 1)]);
+/// ```
+///
+/// # See Also
+///
+/// * [`VirtualMachine::loop_stack`](crate::VirtualMachine::loop_stack)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoopFrame {
+    entry:      usize,
+    iterations: usize,
+}
+
+impl LoopFrame {
+    /// Create a new `LoopFrame`
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The position of the loop's `[` in the `Program`
+    /// * `iterations` - The number of times the loop body has been entered
+    ///
+    /// # Returns
+    ///
+    /// A new `LoopFrame`
+    #[must_use]
+    pub const fn new(entry: usize, iterations: usize) -> Self {
+        Self { entry, iterations }
+    }
+
+    /// The position of the loop's `[` in the `Program`
+    #[must_use]
+    pub const fn entry(&self) -> usize {
+        self.entry
+    }
+
+    /// The number of times the loop body has been entered, including the
+    /// current iteration
+    #[must_use]
+    pub const fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Record another pass through the loop body
+    pub(crate) fn next_iteration(&mut self) {
+        self.iterations += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let frame = LoopFrame::new(3, 1);
+        assert_eq!(frame.entry(), 3);
+        assert_eq!(frame.iterations(), 1);
+    }
+
+    #[test]
+    fn test_next_iteration() {
+        let mut frame = LoopFrame::new(0, 1);
+        frame.next_iteration();
+        frame.next_iteration();
+        assert_eq!(frame.iterations(), 3);
+    }
+}
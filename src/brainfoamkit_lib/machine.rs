@@ -3,21 +3,135 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use std::{
+    collections::HashMap,
+    fs,
+    io::{
+        Cursor,
+        Stdout,
+    },
+    ops::{
+        ControlFlow,
+        Range,
+    },
+    path::Path,
+};
+
+#[cfg(feature = "mmap")]
+use crate::MmapTape;
 use crate::{
+    debug_dump,
     vm_reader::VMReader,
+    vm_writer::VMWriter,
+    Access,
+    AssertionFailed,
+    Bookmarks,
+    BuildError,
     Byte,
+    CancellationToken,
+    CaptureWriter,
+    CellStats,
+    Counters,
+    DataSegment,
+    DebugDump,
+    DebugHook,
+    DiagnosticsSink,
+    DiffStream,
+    Direction,
+    Endianness,
+    EofBehavior,
+    FileError,
+    History,
+    HistoryEntry,
     Instruction,
+    InstructionHandler,
+    IoDevices,
+    IoMode,
+    LimitExceeded,
+    Limits,
+    LoopFrame,
+    MemoryMap,
+    MockReader,
+    PagedTape,
+    ParseError,
+    ProfileViolation,
     Program,
+    Progress,
+    ProgressReporter,
+    ReadError,
+    RunIter,
+    TaintTracker,
+    TapeBackend,
+    TapeEdge,
+    TapeFormat,
+    TapeStorage,
+    Throttle,
+    VMError,
+    VMSnapshot,
     VirtualMachineBuilder,
+    VmContext,
+    Warning,
+    WarningKind,
+    WarningPolicy,
+    Word,
+};
+#[cfg(feature = "tokio")]
+use crate::{
+    AsyncVMReader,
+    AsyncVMWriter,
 };
 
+/// The number of instructions `run_async` executes before it lets other tasks
+/// run
+#[cfg(feature = "tokio")]
+const ASYNC_YIELD_INTERVAL: usize = 1024;
+
+/// The reason a call to
+/// [`VirtualMachine::run`](struct.VirtualMachine.html#method.run)
+/// returned control to the caller.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     Program,
+///     StopReason,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from("+."))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(machine.run().unwrap(), StopReason::Halted);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The program counter moved past the last instruction of the `Program`
+    Halted,
+    /// The next instruction is an `OutputValue` that will write the contained
+    /// value. Only returned when breaking on output is enabled.
+    OutputPending(Byte),
+    /// The next instruction is an `InputValue`. Only returned when breaking on
+    /// input is enabled.
+    InputPending,
+    /// The [`CancellationToken`] of
+    /// [`run_with_cancel`](VirtualMachine::run_with_cancel) was cancelled
+    /// before the next instruction. Only returned by that method.
+    Cancelled,
+}
+
 /// `VirtualMachine` is a struct representing a Virtual Machine capable of
 /// interpreting a `BrainFuck` program and tracking its state.
 ///
 /// # Fields
 ///
-/// * `tape`: A vector of `Byte` values representing the memory of the machine.
-///   Each `Byte` in the vector is a cell in the memory tape.
+/// * `tape`: The cells of the memory of the machine, in memory or in a
+///   memory-mapped file.
 /// * `program`: A `Program` instance representing the Brainfuck program that
 ///   the machine is executing.
 /// * `memory_pointer`: A `usize` value representing the current position of the
@@ -25,6 +139,41 @@ use crate::{
 ///   tape.
 /// * `program_counter`: A `usize` that represents which instruction of the
 ///   `Program` is being executed right now.
+/// * `jump_table`: The jump table of the `Program`, mapping every loop bracket
+///   to its matching bracket.
+/// * `input`: The input device the machine reads from.
+/// * `output`: The output device the machine writes to.
+/// * `history`: The most recently executed instructions.
+/// * `loop_stack`: The loops that are currently being executed.
+/// * `limits`: The resource limits of the machine.
+/// * `steps`: The number of instructions executed so far.
+/// * `bytes_written`: The number of bytes written to the output device so far.
+/// * `heatmap`: The per-cell access counts, if profiling is enabled.
+/// * `debug_hook`: The callback invoked for every `#` instruction.
+/// * `procedures`: The position of the `(` of every defined procedure.
+/// * `call_stack`: The positions of the `:` of the active procedure calls.
+/// * `eof_behavior`: What `,` does when there is no more input.
+/// * `tape_edge`: What happens when the memory pointer moves off an end of the
+///   tape.
+/// * `wrap_cells`: Whether cells wrap around when they overflow or underflow.
+/// * `io_mode`: Whether `,` and `.` exchange bytes or decimal numbers.
+/// * `throttle`: The limit on the instructions executed per second, if any.
+/// * `bookmarks`: The named cells and instructions.
+/// * `taint`: The input bytes that influenced every cell and output byte, if
+///   taint tracking is enabled.
+/// * `extensions`: The handlers of the custom instructions, by character.
+/// * `diagnostics`: Where the warnings go.
+/// * `memory_map`: The permissions of regions of the tape, if any.
+/// * `canary_cells`: The number of canary cells on either side of the tape.
+/// * `off_tape`: How far past an end of the tape the memory pointer is, if it
+///   is on a canary cell.
+///
+/// # Threads
+///
+/// A `VirtualMachine` is `Send` whenever its input and output devices are, so
+/// it can be moved to a worker thread. It is not `Sync`: use a
+/// [`SharedVm`](crate::SharedVm) to step a machine from one thread while
+/// another thread inspects it.
 ///
 /// # Example
 ///
@@ -38,23 +187,142 @@ use crate::{
 /// let machine = VirtualMachine::builder().input_device(input_device).build();
 /// ```
 #[allow(clippy::module_name_repetitions)]
-pub struct VirtualMachine<R>
+pub struct VirtualMachine<R, W = Stdout>
 where
     R: VMReader,
+    W: VMWriter,
 {
-    tape:            Vec<Byte>,
+    tape:            TapeStorage,
     program:         Program,
     memory_pointer:  usize,
     program_counter: usize,
+    jump_table:      Vec<Option<usize>>,
     input:           R,
-    //    output: W,
+    output:          W,
+    break_on_output: bool,
+    break_on_input:  bool,
+    resuming:        bool,
+    history:         History,
+    loop_stack:      Vec<LoopFrame>,
+    limits:          Limits,
+    steps:           usize,
+    bytes_written:   usize,
+    heatmap:         Option<Vec<CellStats>>,
+    debug_hook:      DebugHook,
+    procedures:      HashMap<u8, usize>,
+    call_stack:      Vec<usize>,
+    eof_behavior:    EofBehavior,
+    tape_edge:       TapeEdge,
+    wrap_cells:      bool,
+    io_mode:         IoMode,
+    throttle:        Option<Throttle>,
+    bookmarks:       Bookmarks,
+    taint:           Option<TaintTracker>,
+    extensions:      HashMap<char, Box<dyn InstructionHandler>>,
+    diagnostics:     Box<dyn DiagnosticsSink>,
+    memory_map:      Option<MemoryMap>,
+    canary_cells:    usize,
+    off_tape:        Option<(Direction, usize)>,
+    progress:        Option<(usize, Box<dyn ProgressReporter>)>,
+    loops_completed: usize,
+}
+
+impl<R> VirtualMachine<R, Stdout>
+where
+    R: VMReader,
+{
+    /// Create a new instance of `VirtualMachine` using `VirtualMachineBuilder`.
+    ///
+    /// This method provides a convenient way to create a new instance of
+    /// `VirtualMachine` using `VirtualMachineBuilder`. This method returns
+    /// a `VirtualMachineBuilder` instance that can be used to configure the
+    /// `VirtualMachine` before building it.
+    ///
+    /// # Returns
+    ///
+    /// A `VirtualMachineBuilder` instance that can be used to configure the
+    /// `VirtualMachine` before building it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     VMReader,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    ///
+    /// let machine = VirtualMachine::builder().input_device(input_device).build();
+    /// ```
+    ///
+    /// # See Also
+    ///
+    /// * [`VirtualMachineBuilder`](struct.VirtualMachineBuilder.html)
+    #[must_use]
+    pub fn builder() -> VirtualMachineBuilder<R> {
+        VirtualMachineBuilder::<R>::new()
+    }
+}
+
+impl VirtualMachine<MockReader, CaptureWriter> {
+    /// The number of cells on the tape of a [`testing`](#method.testing)
+    /// machine
+    pub const TESTING_TAPE_SIZE: usize = 1024;
+
+    /// Create a `VirtualMachine` for a test in one call.
+    ///
+    /// The machine runs `program_source` in the standard dialect, reads
+    /// `input_bytes` and captures its output. It has a tape of
+    /// [`TESTING_TAPE_SIZE`](#associatedconstant.TESTING_TAPE_SIZE) cells and
+    /// strict [`Limits`]: at most a million steps, 64 KiB of output and a
+    /// call depth of 256, so that a runaway program fails the test instead of
+    /// hanging it.
+    ///
+    /// # Arguments
+    ///
+    /// * `program_source` - The source of the program
+    /// * `input_bytes` - The input of the program
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::VirtualMachine;
+    ///
+    /// let mut machine = VirtualMachine::testing(",+.,+.", b"ab").unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(machine.output_device().data, b"bc");
+    /// assert!(VirtualMachine::testing("+[]", b"").unwrap().run().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the brackets of the program are not balanced.
+    pub fn testing(program_source: &str, input_bytes: &[u8]) -> Result<Self, BuildError> {
+        VirtualMachineBuilder::new()
+            .input_device(MockReader {
+                data: Cursor::new(input_bytes.to_vec()),
+            })
+            .output_device(CaptureWriter::default())
+            .program(Program::from(program_source))
+            .tape_size(Self::TESTING_TAPE_SIZE)
+            .limits(
+                Limits::new()
+                    .max_steps(1_000_000)
+                    .max_output_bytes(64 * 1024)
+                    .max_call_depth(256),
+            )
+            .build()
+    }
 }
 
 #[allow(dead_code)]
 #[allow(clippy::len_without_is_empty)]
-impl<R> VirtualMachine<R>
+impl<R, W> VirtualMachine<R, W>
 where
     R: VMReader,
+    W: VMWriter,
 {
     pub(crate) fn new(
         tape_size: usize,
@@ -62,19 +330,152 @@ where
         memory_pointer: usize,
         program_counter: usize,
         input: R,
+        output: W,
     ) -> Self {
         // FIXME - Remove `memory_pointer` and `program_counter` from the constructor
         // since they should always be set to 0 on initialization.
 
         Self {
-            tape: vec![Byte::default(); tape_size],
+            tape: TapeStorage::memory(vec![Byte::default(); tape_size]),
             program,
             memory_pointer,
             program_counter,
+            jump_table: Vec::new(),
             input,
+            output,
+            break_on_output: false,
+            break_on_input: false,
+            resuming: false,
+            history: History::default(),
+            loop_stack: Vec::new(),
+            limits: Limits::default(),
+            steps: 0,
+            bytes_written: 0,
+            heatmap: None,
+            debug_hook: debug_dump::print_to_stderr(),
+            procedures: HashMap::new(),
+            call_stack: Vec::new(),
+            eof_behavior: EofBehavior::default(),
+            tape_edge: TapeEdge::default(),
+            wrap_cells: true,
+            io_mode: IoMode::default(),
+            throttle: None,
+            bookmarks: Bookmarks::default(),
+            taint: None,
+            extensions: HashMap::new(),
+            diagnostics: Box::new(WarningPolicy::default()),
+            memory_map: None,
+            canary_cells: 0,
+            off_tape: None,
+            progress: None,
+            loops_completed: 0,
+        }
+    }
+
+    /// Set what `,` does when there is no more input.
+    pub(crate) fn set_eof_behavior(&mut self, behavior: EofBehavior) {
+        self.eof_behavior = behavior;
+    }
+
+    /// Set what happens when the memory pointer moves off an end of the tape.
+    ///
+    /// A tape that grows is moved to pages, which share the zeros it grows
+    /// into.
+    pub(crate) fn set_tape_edge(&mut self, edge: TapeEdge) {
+        self.tape_edge = edge;
+        if edge == TapeEdge::Grow {
+            let mut tape = PagedTape::from_cells(&self.tape.to_vec());
+            tape.compact();
+            self.tape = TapeStorage::paged(tape);
+        }
+    }
+
+    /// Set whether cells wrap around when they overflow or underflow.
+    pub(crate) fn set_wrap_cells(&mut self, enabled: bool) {
+        self.wrap_cells = enabled;
+    }
+
+    /// Set whether `,` and `.` exchange bytes or decimal numbers.
+    pub(crate) fn set_io_mode(&mut self, mode: IoMode) {
+        self.io_mode = mode;
+    }
+
+    /// Set the handlers of the custom instructions.
+    pub(crate) fn set_extensions(
+        &mut self,
+        extensions: HashMap<char, Box<dyn InstructionHandler>>,
+    ) {
+        self.extensions = extensions;
+    }
+
+    /// Set where the warnings go.
+    pub(crate) fn set_diagnostics_sink(&mut self, sink: Box<dyn DiagnosticsSink>) {
+        self.diagnostics = sink;
+    }
+
+    /// Set the permissions of regions of the tape.
+    pub(crate) fn set_memory_map(&mut self, memory_map: Option<MemoryMap>) {
+        self.memory_map = memory_map;
+    }
+
+    /// Set the number of canary cells on either side of the tape.
+    pub(crate) fn set_canary_cells(&mut self, count: usize) {
+        self.canary_cells = count;
+    }
+
+    /// Set the reporter of the progress and how many steps go by between two
+    /// reports.
+    pub(crate) fn set_progress_reporter(
+        &mut self,
+        progress: Option<(usize, Box<dyn ProgressReporter>)>,
+    ) {
+        self.progress = progress.map(|(every, reporter)| (every.max(1), reporter));
+    }
+
+    /// Store the cells of the tape in a memory-mapped file.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn set_mmap_tape(&mut self, tape: MmapTape) {
+        self.tape = TapeStorage::mapped(tape);
+        if let Some(heatmap) = self.heatmap.as_mut() {
+            heatmap.resize(self.tape.len(), CellStats::default());
         }
     }
 
+    /// Replace the callback invoked for every `#` instruction.
+    pub(crate) fn set_debug_hook(&mut self, hook: DebugHook) {
+        self.debug_hook = hook;
+    }
+
+    /// Enable or disable counting cell accesses.
+    pub(crate) fn set_profiling(&mut self, enabled: bool) {
+        self.heatmap = enabled.then(|| vec![CellStats::default(); self.tape.len()]);
+    }
+
+    /// Enable or disable following the input bytes through the machine.
+    pub(crate) fn set_taint_tracking(&mut self, enabled: bool) {
+        self.taint = enabled.then(TaintTracker::default);
+    }
+
+    /// Replace the resource `Limits` of the machine.
+    pub(crate) fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Replace the execution `History` with an empty one of the given capacity.
+    pub(crate) fn set_history_capacity(&mut self, capacity: usize) {
+        self.history = History::new(capacity);
+    }
+
+    /// Rebuild the jump table from the current `Program`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the brackets of the `Program` are not balanced.
+    pub(crate) fn rebuild_jump_table(&mut self) -> Result<(), ParseError> {
+        self.jump_table = self.program.jump_table()?;
+        Ok(())
+    }
+
     /// Return the length of the "memory" or the `tape_size` of the
     /// `VirtualMachine`.
     ///
@@ -140,17 +541,14 @@ where
         self.program.clone()
     }
 
-    /// Create a new instance of `VirtualMachine` using `VirtualMachineBuilder`.
+    /// Returns the length of the `tape` inside the `VirtualMachine`.
     ///
-    /// This method provides a convenient way to create a new instance of
-    /// `VirtualMachine` using `VirtualMachineBuilder`. This method returns
-    /// a `VirtualMachineBuilder` instance that can be used to configure the
-    /// `VirtualMachine` before building it.
+    /// This method returns the length of the `tape` vector of the
+    /// `VirtualMachine`.
     ///
     /// # Returns
     ///
-    /// A `VirtualMachineBuilder` instance that can be used to configure the
-    /// `VirtualMachine` before building it.
+    /// A `usize` value representing the length of the `VirtualMachine`.
     ///
     /// # Example
     ///
@@ -161,46 +559,94 @@ where
     /// };
     ///
     /// let input_device = std::io::stdin();
+    /// let machine = VirtualMachine::builder()
+    ///     .input_device(input_device)
+    ///     .tape_size(10)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(machine.length(), 10);
+    /// ```
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.tape.len()
+    }
+
+    /// Returns the number of bytes the cells of the tape take up.
+    ///
+    /// A fixed tape is a single block of cells. A tape that grows, with
+    /// [`TapeEdge::Grow`](crate::TapeEdge::Grow), is kept in pages: the pages
+    /// of zeros it grows into all share one page, and only the pages the
+    /// program wrote to take up memory, see [`PagedTape`](crate::PagedTape).
+    /// The cells of a memory-mapped tape are in its file, and are not counted.
+    ///
+    /// # Example
     ///
-    /// let machine = VirtualMachine::builder().input_device(input_device).build();
     /// ```
+    /// use brainfoamkit_lib::{
+    ///     BehaviorProfile,
+    ///     Byte,
+    ///     PagedTape,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
     ///
-    /// # See Also
+    /// let source = format!("+{}+", ">".repeat(100_000));
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from(source.as_str()))
+    ///     .behavior_profile(BehaviorProfile::ModernDynamic)
+    ///     .tape_size(1)
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
     ///
-    /// * [`VirtualMachineBuilder`](struct.VirtualMachineBuilder.html)
+    /// // Only the first and the last page of the tape hold anything
+    /// let page = PagedTape::PAGE_SIZE * std::mem::size_of::<Byte>();
+    /// assert_eq!(machine.length(), 100_001);
+    /// assert!(machine.tape_memory_usage() < 3 * page);
+    /// ```
     #[must_use]
-    pub const fn builder() -> VirtualMachineBuilder<R> {
-        VirtualMachineBuilder::<R>::new()
+    pub fn tape_memory_usage(&self) -> usize {
+        self.tape.memory_usage()
     }
 
-    /// Returns the length of the `tape` inside the `VirtualMachine`.
+    /// Free the pages of a growing tape that are back to zero.
     ///
-    /// This method returns the length of the `tape` vector of the
-    /// `VirtualMachine`.
+    /// Writing to a page of zeros of a tape that grows gives it its own copy,
+    /// which is kept once its cells are back to zero. The machine compacts its
+    /// tape on its own as the tape grows, whenever those copies have doubled
+    /// the memory it takes up. `compact_tape` compacts it right away, for
+    /// example before checking [`tape_memory_usage`](Self::tape_memory_usage)
+    /// against a budget. See [`PagedTape::compact`](crate::PagedTape::compact).
     ///
     /// # Returns
     ///
-    /// A `usize` value representing the length of the `VirtualMachine`.
+    /// The number of bytes freed. Only a tape that grows is ever compacted,
+    /// for any other tape this is zero.
     ///
     /// # Example
     ///
     /// ```
     /// use brainfoamkit_lib::{
-    ///     VMReader,
+    ///     BehaviorProfile,
+    ///     Program,
     ///     VirtualMachine,
     /// };
     ///
-    /// let input_device = std::io::stdin();
-    /// let machine = VirtualMachine::builder()
-    ///     .input_device(input_device)
-    ///     .tape_size(10)
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+-"))
+    ///     .behavior_profile(BehaviorProfile::ModernDynamic)
     ///     .build()
     ///     .unwrap();
-    /// assert_eq!(machine.length(), 10);
+    /// machine.run().unwrap();
+    ///
+    /// let before = machine.tape_memory_usage();
+    /// assert!(machine.compact_tape() > 0);
+    /// assert!(machine.tape_memory_usage() < before);
     /// ```
-    #[must_use]
-    pub fn length(&self) -> usize {
-        self.tape.len()
+    pub fn compact_tape(&mut self) -> usize {
+        self.tape.compact()
     }
 
     /// Returns the current position of the memory pointer.
@@ -301,436 +747,3537 @@ where
         &mut self.input
     }
 
-    /// Returns the current instruction of the `VirtualMachine`.
+    /// Returns the current output device of the `VirtualMachine`.
     ///
-    /// This method returns the instruction at the current position of the
-    /// program counter in the program. If the program counter is out of
-    /// bounds of the program, this method returns `None`.
+    /// This method returns the current output device of the `VirtualMachine`.
+    /// This allows for inspecting the output of a program, for example when
+    /// using a [`CaptureWriter`](struct.CaptureWriter.html).
     ///
     /// # Returns
     ///
-    /// An `Option` that contains the current instruction if the program counter
-    /// is within the bounds of the program, or `None` if the program
-    /// counter is out of bounds.
+    /// A reference to the current output device of the `VirtualMachine`.
     ///
     /// # Example
     ///
     /// ```
     /// use brainfoamkit_lib::{
-    ///     Instruction,
+    ///     CaptureWriter,
     ///     Program,
-    ///     VMReader,
     ///     VirtualMachine,
     /// };
     ///
-    /// let program = Program::from(vec![
-    ///     Instruction::IncrementPointer,
-    ///     Instruction::IncrementValue,
-    /// ]);
-    /// let input_device = std::io::stdin();
     /// let mut machine = VirtualMachine::builder()
-    ///     .input_device(input_device)
-    ///     .program(program)
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("++++++++[>++++++++<-]>+."))
     ///     .build()
     ///     .unwrap();
-    /// assert_eq!(
-    ///     machine.get_instruction(),
-    ///     Some(Instruction::IncrementPointer)
-    /// );
-    /// machine.execute_instruction();
-    /// assert_eq!(machine.get_instruction(), Some(Instruction::IncrementValue));
-    /// machine.execute_instruction();
-    /// assert_eq!(machine.get_instruction(), None);
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(machine.output_device().data, b"A");
     /// ```
+    ///
+    /// # See Also
+    ///
+    /// * [`VMWriter`](trait.VMWriter.html)
+    /// * [`VirtualMachineBuilder`](struct.VirtualMachineBuilder.html)
     #[must_use]
-    pub fn get_instruction(&self) -> Option<Instruction> {
-        self.program.get_instruction(self.program_counter)
+    pub fn output_device(&mut self) -> &mut W {
+        &mut self.output
     }
 
-    /// Executes the current instruction of the `VirtualMachine`.
+    /// Returns the contents of the `tape` of the `VirtualMachine`.
     ///
-    /// This method executes the instruction at the current position of the
-    /// memory pointer in the program. If the memory pointer is out of bounds of
-    /// the program, this method does nothing.
+    /// # Returns
+    ///
+    /// The cells of the memory of the `VirtualMachine`, wherever they are
+    /// stored.
     ///
     /// # Example
     ///
     /// ```
     /// use brainfoamkit_lib::{
-    ///     Instruction,
+    ///     Byte,
     ///     Program,
-    ///     VMReader,
     ///     VirtualMachine,
     /// };
     ///
-    /// let program = Program::from(vec![
-    ///     Instruction::IncrementPointer,
-    ///     Instruction::IncrementValue,
-    /// ]);
-    /// let input_device = std::io::stdin();
     /// let mut machine = VirtualMachine::builder()
-    ///     .input_device(input_device)
-    ///     .program(program)
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from(">++"))
+    ///     .tape_size(4)
     ///     .build()
     ///     .unwrap();
-    /// assert_eq!(machine.memory_pointer(), 0);
-    /// machine.execute_instruction();
-    /// assert_eq!(machine.memory_pointer(), 1);
-    /// machine.execute_instruction();
-    /// assert_eq!(machine.memory_pointer(), 1);
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(machine.tape()[1], Byte::from(2));
     /// ```
-    pub fn execute_instruction(&mut self) {
-        let current_instruction = self.get_instruction().unwrap_or(Instruction::NoOp);
-        match current_instruction {
-            Instruction::IncrementPointer => self.increment_pointer(),
-            Instruction::DecrementPointer => self.decrement_pointer(),
-            Instruction::IncrementValue => self.increment_value(),
-            Instruction::DecrementValue => self.decrement_value(),
-            Instruction::OutputValue => self.output_value(),
-            Instruction::InputValue => self.input_value(),
-            Instruction::JumpForward => self.jump_forward(),
-            Instruction::JumpBackward => self.jump_backward(),
-            Instruction::NoOp => {}
-        }
-        self.program_counter += 1;
+    #[must_use]
+    pub const fn tape(&self) -> &TapeStorage {
+        &self.tape
     }
 
-    fn increment_pointer(&mut self) {
-        let next = self.memory_pointer.checked_add(1);
-        if let Some(next) = next {
-            self.memory_pointer = next;
-        } else {
-            self.memory_pointer = 0;
+    /// Finds every place on the tape where the cells hold `pattern`.
+    ///
+    /// Matches may overlap. An empty pattern matches nowhere.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The values to look for, one per cell
+    ///
+    /// # Returns
+    ///
+    /// The index of the first cell of every match, in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+>++>+>++"))
+    ///     .tape_size(8)
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(machine.find_in_tape(&[1, 2]), [0, 2]);
+    /// assert_eq!(machine.find_in_tape(&[0, 0]), [4, 5, 6]);
+    /// ```
+    #[must_use]
+    pub fn find_in_tape(&self, pattern: &[u8]) -> Vec<usize> {
+        if pattern.is_empty() || pattern.len() > self.tape.len() {
+            return Vec::new();
         }
+        (0..=self.tape.len() - pattern.len())
+            .filter(|&index| {
+                pattern
+                    .iter()
+                    .enumerate()
+                    .all(|(offset, &value)| u8::from(&self.tape[index + offset]) == value)
+            })
+            .collect()
     }
 
-    fn decrement_pointer(&mut self) {
-        let next = self.memory_pointer.checked_sub(1);
-        if let Some(next) = next {
-            self.memory_pointer = next;
-        } else {
-            self.memory_pointer = self.tape.len() - 1;
-        }
+    /// Reads a 16-bit number from two consecutive cells.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the first cell
+    /// * `endianness` - Whether the first cell holds the most significant byte
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Endianness,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+>++"))
+    ///     .tape_size(4)
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(machine.read_u16_at(0, Endianness::Big).unwrap(), 0x0102);
+    /// assert_eq!(machine.read_u16_at(0, Endianness::Little).unwrap(), 0x0201);
+    /// assert!(machine.read_u16_at(3, Endianness::Big).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the second cell is beyond the end of the tape.
+    pub fn read_u16_at(&self, index: usize, endianness: Endianness) -> Result<u16, VMError> {
+        let cells = self.cells_at::<2>(index)?;
+        Ok(u16::from(&Word::from_bytes(cells, endianness)))
     }
 
-    fn increment_value(&mut self) {
-        self.tape[self.memory_pointer].increment();
+    /// Reads a 32-bit number from four consecutive cells.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the first cell
+    /// * `endianness` - Whether the first cell holds the most significant byte
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the last cell is beyond the end of the tape.
+    pub fn read_u32_at(&self, index: usize, endianness: Endianness) -> Result<u32, VMError> {
+        let bytes = self.cells_at::<4>(index)?.map(|cell| u8::from(&cell));
+        Ok(match endianness {
+            Endianness::Big => u32::from_be_bytes(bytes),
+            Endianness::Little => u32::from_le_bytes(bytes),
+        })
+    }
+
+    /// Writes a 16-bit number to two consecutive cells.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the first cell
+    /// * `value` - The number to write
+    /// * `endianness` - Whether the first cell gets the most significant byte
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the second cell is beyond the end of the tape. No
+    /// cell is changed then.
+    pub fn write_u16_at(
+        &mut self,
+        index: usize,
+        value: u16,
+        endianness: Endianness,
+    ) -> Result<(), VMError> {
+        self.set_cells_at(index, Word::from(value).to_bytes(endianness))
+    }
+
+    /// Writes a 32-bit number to four consecutive cells.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the first cell
+    /// * `value` - The number to write
+    /// * `endianness` - Whether the first cell gets the most significant byte
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Endianness,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .tape_size(4)
+    ///     .build()
+    ///     .unwrap();
+    /// machine
+    ///     .write_u32_at(0, 0xDEAD_BEEF, Endianness::Little)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(machine.find_in_tape(&[0xEF, 0xBE, 0xAD, 0xDE]), [0]);
+    /// assert_eq!(
+    ///     machine.read_u32_at(0, Endianness::Little).unwrap(),
+    ///     0xDEAD_BEEF
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the last cell is beyond the end of the tape. No
+    /// cell is changed then.
+    pub fn write_u32_at(
+        &mut self,
+        index: usize,
+        value: u32,
+        endianness: Endianness,
+    ) -> Result<(), VMError> {
+        let bytes = match endianness {
+            Endianness::Big => value.to_be_bytes(),
+            Endianness::Little => value.to_le_bytes(),
+        };
+        self.set_cells_at(index, bytes.map(Byte::from))
+    }
+
+    /// The `N` cells from `index` on
+    fn cells_at<const N: usize>(&self, index: usize) -> Result<[Byte; N], VMError> {
+        let end = index.saturating_add(N);
+        (end <= self.tape.len())
+            .then(|| <[Byte; N]>::try_from(&self.tape.slice(index..end)[..]).ok())
+            .flatten()
+            .ok_or(VMError::CellOutOfRange {
+                cell:   index.saturating_add(N - 1),
+                length: self.tape.len(),
+            })
+    }
+
+    /// Replace the `N` cells from `index` on
+    fn set_cells_at<const N: usize>(
+        &mut self,
+        index: usize,
+        values: [Byte; N],
+    ) -> Result<(), VMError> {
+        let length = self.tape.len();
+        self.tape
+            .edit(index..index.saturating_add(N), |cells| {
+                cells.copy_from_slice(&values);
+            })
+            .ok_or(VMError::CellOutOfRange {
+                cell: index.saturating_add(N - 1),
+                length,
+            })
+    }
+
+    /// Checks that a cell holds the expected value.
+    ///
+    /// This lets tests and host programs verify the state of the tape between
+    /// runs, for example to build self-checking programs that are run a
+    /// section at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the cell to check
+    /// * `expected` - The value the cell should hold
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("++[>+++<-]"))
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert!(machine.assert_cell(1, 6).is_ok());
+    /// assert!(machine.assert_cell(0, 1).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns an [`AssertionFailed`] error with a dump of the surrounding
+    ///   cells if the cell holds another value.
+    /// * Returns an error if the cell is beyond the end of the tape.
+    pub fn assert_cell(&self, index: usize, expected: u8) -> Result<(), VMError> {
+        let Some(cell) = self.tape.get(index) else {
+            return Err(VMError::CellOutOfRange {
+                cell:   index,
+                length: self.tape.len(),
+            });
+        };
+        if u8::from(cell) == expected {
+            Ok(())
+        } else {
+            Err(AssertionFailed::new(&self.tape, index, expected, self.program_counter).into())
+        }
+    }
+
+    /// Seeds the tape with the values in a file.
+    ///
+    /// The values are written to the cells from the first cell on. The rest of
+    /// the tape is left as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file holding the values
+    /// * `format` - The format of the file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Byte,
+    ///     Program,
+    ///     TapeFormat,
+    ///     VirtualMachine,
+    /// };
+    /// use tempfile::NamedTempFile;
+    ///
+    /// let file = NamedTempFile::new().unwrap();
+    /// std::fs::write(file.path(), "48 69").unwrap();
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+"))
+    ///     .tape_size(4)
+    ///     .build()
+    ///     .unwrap();
+    /// machine
+    ///     .load_tape_from(file.path(), TapeFormat::Hex)
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(
+    ///     machine.tape().slice(0..3)[..],
+    ///     [Byte::from(73), Byte::from(105), Byte::from(0)]
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error if the file cannot be read or is not in the format.
+    /// * Returns an error if the file holds more values than the tape has
+    ///   cells. The tape is left unchanged in this case.
+    pub fn load_tape_from<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        format: TapeFormat,
+    ) -> Result<(), VMError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|source| FileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let values = format
+            .decode(&data)
+            .map_err(|source| VMError::InvalidTape {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        if values.len() > self.tape.len() {
+            return Err(VMError::TapeTooLarge {
+                path:   path.to_path_buf(),
+                values: values.len(),
+                cells:  self.tape.len(),
+            });
+        }
+
+        for (index, value) in values.into_iter().enumerate() {
+            self.tape.set(index, Byte::from(value));
+        }
+        Ok(())
+    }
+
+    /// Loads the data declared by the `@data` directives of a program into the
+    /// tape.
+    ///
+    /// Every segment is written to the cells from its start on. The rest of
+    /// the tape is left as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `segments` - The data to load, usually the
+    ///   [`data_segments`](crate::PreprocessedSource::data_segments) of a
+    ///   preprocessed source
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Byte,
+    ///     Preprocessor,
+    ///     VirtualMachine,
+    /// };
+    /// use tempfile::tempdir;
+    ///
+    /// let directory = tempdir().unwrap();
+    /// std::fs::write(directory.path().join("data.bin"), [7, 8]).unwrap();
+    /// let source = Preprocessor::new()
+    ///     .base_dir(directory.path())
+    ///     .process_str("@data \"data.bin\" -> cells 2..\n>>+")
+    ///     .unwrap();
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(source.program())
+    ///     .tape_size(4)
+    ///     .build()
+    ///     .unwrap();
+    /// machine.load_data(source.data_segments()).unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(
+    ///     machine.tape().to_vec(),
+    ///     [Byte::from(0), Byte::from(0), Byte::from(8), Byte::from(8)]
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a segment runs past the end of the tape. The tape is
+    /// left unchanged in this case.
+    pub fn load_data(&mut self, segments: &[DataSegment]) -> Result<(), VMError> {
+        let length = self.tape.len();
+        if let Some(segment) = segments
+            .iter()
+            .find(|segment| segment.start() + segment.bytes().len() > length)
+        {
+            return Err(VMError::CellOutOfRange {
+                cell: (segment.start() + segment.bytes().len() - 1).max(segment.start()),
+                length,
+            });
+        }
+
+        for segment in segments {
+            for (offset, value) in segment.bytes().iter().enumerate() {
+                self.tape.set(segment.start() + offset, Byte::from(*value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the values of every cell of the tape to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write, which is replaced if it exists
+    /// * `format` - The format to write the file in
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     TapeFormat,
+    ///     VirtualMachine,
+    /// };
+    /// use tempfile::NamedTempFile;
+    ///
+    /// let file = NamedTempFile::new().unwrap();
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from(">+++"))
+    ///     .tape_size(3)
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    /// machine.dump_tape_to(file.path(), TapeFormat::Raw).unwrap();
+    ///
+    /// assert_eq!(std::fs::read(file.path()).unwrap(), [0, 3, 0]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn dump_tape_to<P: AsRef<Path>>(&self, path: P, format: TapeFormat) -> Result<(), VMError> {
+        let path = path.as_ref();
+        let values: Vec<u8> = self.tape.iter().map(u8::from).collect();
+        fs::write(path, format.encode(&values)).map_err(|source| {
+            FileError::Write {
+                path: path.to_path_buf(),
+                source,
+            }
+            .into()
+        })
+    }
+
+    /// Wait until the tape is written to its memory-mapped file.
+    ///
+    /// The machine reads and writes the cells of a tape set with
+    /// [`VirtualMachineBuilder::mmap_tape`] in the file itself, so every
+    /// change is in the file as soon as it is made. This waits until the
+    /// operating system has written the changes to disk. Without a tape file,
+    /// this does nothing.
+    ///
+    /// Only available with the `mmap` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    #[cfg(feature = "mmap")]
+    pub fn flush_tape(&mut self) -> Result<(), VMError> {
+        self.tape.flush()?;
+        Ok(())
+    }
+
+    /// Returns the execution `History` of the `VirtualMachine`.
+    ///
+    /// The `History` holds the most recently executed instructions, up to the
+    /// capacity set with [`VirtualMachineBuilder::history_capacity`]. It is
+    /// empty if no capacity was set.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the `History` of the `VirtualMachine`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Instruction,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+>+<"))
+    ///     .history_capacity(2)
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// let history = machine.history();
+    /// assert_eq!(history.len(), 2);
+    /// assert_eq!(
+    ///     history.last().unwrap().instruction(),
+    ///     Instruction::DecrementPointer
+    /// );
+    /// ```
+    ///
+    /// # See Also
+    ///
+    /// * [`History`](struct.History.html)
+    #[must_use]
+    pub const fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Returns the stack of loops the `VirtualMachine` is currently inside.
+    ///
+    /// Every time the body of a loop is entered from its `[`, a new
+    /// [`LoopFrame`] is pushed. Jumping back from the `]` counts another
+    /// iteration of the innermost frame, and leaving the loop pops it. The
+    /// innermost loop is the last element of the slice.
+    ///
+    /// # Returns
+    ///
+    /// A slice of `LoopFrame`s, from the outermost to the innermost loop.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     LoopFrame,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+[>++[-]<-]"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // Run until the inner loop has been entered twice
+    /// for _ in 0..9 {
+    ///     machine.execute_instruction().unwrap();
+    /// }
+    ///
+    /// assert_eq!(
+    ///     machine.loop_stack(),
+    ///     &[LoopFrame::new(1, 1), LoopFrame::new(5, 2)]
+    /// );
+    ///
+    /// machine.run().unwrap();
+    /// assert!(machine.loop_stack().is_empty());
+    /// ```
+    #[must_use]
+    pub fn loop_stack(&self) -> &[LoopFrame] {
+        &self.loop_stack
+    }
+
+    /// Return the procedure calls that are currently active.
+    ///
+    /// Procedures only exist in the
+    /// [`Pbrain`](enum.Dialect.html#variant.Pbrain) dialect.
+    ///
+    /// # Returns
+    ///
+    /// The positions of the `:` instructions that made the active calls, from
+    /// the outermost to the innermost call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Dialect,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::parse("+(+):", Dialect::Pbrain))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // Define the procedure and call it
+    /// for _ in 0..3 {
+    ///     machine.execute_instruction().unwrap();
+    /// }
+    /// assert_eq!(machine.call_stack(), &[4]);
+    ///
+    /// machine.run().unwrap();
+    /// assert!(machine.call_stack().is_empty());
+    /// ```
+    #[must_use]
+    pub fn call_stack(&self) -> &[usize] {
+        &self.call_stack
+    }
+
+    /// Returns `true` if the `VirtualMachine` counts cell accesses.
+    #[must_use]
+    pub const fn is_profiling(&self) -> bool {
+        self.heatmap.is_some()
+    }
+
+    /// Returns the number of reads and writes of every cell of the tape.
+    ///
+    /// Access counts are only recorded when profiling is enabled with
+    /// [`VirtualMachineBuilder::profiling`]. The returned vector has one entry
+    /// per cell, in tape order, and is empty if profiling is disabled.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of [`CellStats`], one for every cell of the tape.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+>+++"))
+    ///     .tape_size(8)
+    ///     .profiling(true)
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// let writes: Vec<usize> = machine
+    ///     .heatmap()
+    ///     .iter()
+    ///     .map(|stats| stats.writes())
+    ///     .collect();
+    /// assert_eq!(writes, vec![1, 3, 0, 0, 0, 0, 0, 0]);
+    /// ```
+    #[must_use]
+    pub fn heatmap(&self) -> Vec<CellStats> {
+        self.heatmap.clone().unwrap_or_default()
+    }
+
+    /// Returns the input bytes that influenced every cell and output byte, or
+    /// `None` if taint tracking is disabled.
+    ///
+    /// Taint tracking is enabled with
+    /// [`VirtualMachineBuilder::taint_tracking`]. See [`TaintTracker`] for an
+    /// example.
+    #[must_use]
+    pub const fn taint(&self) -> Option<&TaintTracker> {
+        self.taint.as_ref()
+    }
+
+    /// Returns the resource `Limits` of the `VirtualMachine`.
+    ///
+    /// # See Also
+    ///
+    /// * [`VirtualMachineBuilder::limits`]
+    #[must_use]
+    pub const fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Returns the number of instructions the `VirtualMachine` has executed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("++[-]"))
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(machine.steps(), 7);
+    /// ```
+    #[must_use]
+    pub const fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// What `,` does when there is no more input
+    ///
+    /// See
+    /// [`VirtualMachineBuilder::eof_behavior`](crate::VirtualMachineBuilder::eof_behavior).
+    #[must_use]
+    pub const fn eof_behavior(&self) -> EofBehavior {
+        self.eof_behavior
+    }
+
+    /// What happens when the memory pointer moves off an end of the tape
+    #[must_use]
+    pub const fn tape_edge(&self) -> TapeEdge {
+        self.tape_edge
+    }
+
+    /// Whether cells wrap around when they overflow or underflow
+    #[must_use]
+    pub const fn wraps_cells(&self) -> bool {
+        self.wrap_cells
+    }
+
+    /// Whether `,` and `.` exchange bytes or decimal numbers
+    ///
+    /// See
+    /// [`VirtualMachineBuilder::io_mode`](crate::VirtualMachineBuilder::io_mode).
+    #[must_use]
+    pub const fn io_mode(&self) -> IoMode {
+        self.io_mode
+    }
+
+    /// The number of bytes the machine has written to its output device
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("+.+.."))
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(machine.bytes_written(), 3);
+    /// ```
+    #[must_use]
+    pub const fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    /// Enable or disable breaking before `OutputValue` instructions.
+    ///
+    /// See [`VirtualMachineBuilder::break_on_output`](struct.
+    /// VirtualMachineBuilder.html#method.break_on_output).
+    pub fn set_break_on_output(&mut self, enabled: bool) {
+        self.break_on_output = enabled;
+    }
+
+    /// Enable or disable breaking before `InputValue` instructions.
+    ///
+    /// See [`VirtualMachineBuilder::break_on_input`](struct.
+    /// VirtualMachineBuilder.html#method.break_on_input).
+    pub fn set_break_on_input(&mut self, enabled: bool) {
+        self.break_on_input = enabled;
+    }
+
+    /// Limit the `VirtualMachine` to `ips` instructions per second.
+    ///
+    /// Every instruction waits its turn in a [`Throttle`] before it is
+    /// executed, so a run can be followed by a person, for example in a
+    /// visualizer, without the caller pacing it. A speed of zero removes the
+    /// limit.
+    ///
+    /// [`run_async`](#method.run_async) waits with a tokio timer, which needs a
+    /// runtime with the time driver enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::{
+    ///     Duration,
+    ///     Instant,
+    /// };
+    ///
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+++++"))
+    ///     .build()
+    ///     .unwrap();
+    /// machine.set_speed(100);
+    ///
+    /// let start = Instant::now();
+    /// machine.run().unwrap();
+    /// assert!(start.elapsed() >= Duration::from_millis(40));
+    /// assert_eq!(machine.speed(), Some(100));
+    /// ```
+    pub fn set_speed(&mut self, ips: u32) {
+        self.throttle = (ips > 0).then(|| Throttle::new(ips));
+    }
+
+    /// The number of instructions the `VirtualMachine` may execute per second,
+    /// or `None` if it runs as fast as it can.
+    #[must_use]
+    pub fn speed(&self) -> Option<u32> {
+        self.throttle.as_ref().map(Throttle::rate)
+    }
+
+    /// Names the cell at `index`, so that it can be found again while
+    /// debugging.
+    ///
+    /// Bookmarking a name again moves the bookmark. The cell may be beyond
+    /// the end of a tape that grows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("++[>+++<-]"))
+    ///     .build()
+    ///     .unwrap();
+    /// machine.bookmark_cell("result", 1);
+    /// machine.bookmark_pc("main_loop", 2);
+    ///
+    /// assert_eq!(machine.bookmarks().cell("result"), Some(1));
+    /// assert_eq!(machine.bookmarks().pc("main_loop"), Some(2));
+    /// ```
+    pub fn bookmark_cell(&mut self, name: impl Into<String>, index: usize) {
+        self.bookmarks.bookmark_cell(name, index);
+    }
+
+    /// Names the instruction at `pc`, so that it can be found again while
+    /// debugging.
+    ///
+    /// Bookmarking a name again moves the bookmark. See
+    /// [`bookmark_cell`](#method.bookmark_cell) for an example.
+    pub fn bookmark_pc(&mut self, name: impl Into<String>, pc: usize) {
+        self.bookmarks.bookmark_pc(name, pc);
+    }
+
+    /// Returns the named cells and instructions of the `VirtualMachine`.
+    #[must_use]
+    pub const fn bookmarks(&self) -> &Bookmarks {
+        &self.bookmarks
+    }
+
+    /// Replaces the named cells and instructions of the `VirtualMachine`, for
+    /// example with the bookmarks of a [`DebugSession`](crate::DebugSession).
+    pub fn set_bookmarks(&mut self, bookmarks: Bookmarks) {
+        self.bookmarks = bookmarks;
+    }
+
+    /// Returns `true` if the `VirtualMachine` breaks before output.
+    #[must_use]
+    pub const fn breaks_on_output(&self) -> bool {
+        self.break_on_output
+    }
+
+    /// Returns `true` if the `VirtualMachine` breaks before input.
+    #[must_use]
+    pub const fn breaks_on_input(&self) -> bool {
+        self.break_on_input
+    }
+
+    /// Returns the current instruction of the `VirtualMachine`.
+    ///
+    /// This method returns the instruction at the current position of the
+    /// program counter in the program. If the program counter is out of
+    /// bounds of the program, this method returns `None`.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` that contains the current instruction if the program counter
+    /// is within the bounds of the program, or `None` if the program
+    /// counter is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Instruction,
+    ///     Program,
+    ///     VMReader,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let program = Program::from(vec![
+    ///     Instruction::IncrementPointer,
+    ///     Instruction::IncrementValue,
+    /// ]);
+    /// let input_device = std::io::stdin();
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(input_device)
+    ///     .program(program)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     machine.get_instruction(),
+    ///     Some(Instruction::IncrementPointer)
+    /// );
+    /// machine.execute_instruction().unwrap();
+    /// assert_eq!(machine.get_instruction(), Some(Instruction::IncrementValue));
+    /// machine.execute_instruction().unwrap();
+    /// assert_eq!(machine.get_instruction(), None);
+    /// ```
+    #[must_use]
+    pub fn get_instruction(&self) -> Option<Instruction> {
+        self.program.get_instruction(self.program_counter)
+    }
+
+    /// Executes the current instruction of the `VirtualMachine`.
+    ///
+    /// This method executes the instruction at the current position of the
+    /// program counter in the program. If the program counter is out of bounds
+    /// of the program, the `VirtualMachine` is halted and this method does
+    /// nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Instruction,
+    ///     Program,
+    ///     VMReader,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let program = Program::from(vec![
+    ///     Instruction::IncrementPointer,
+    ///     Instruction::IncrementValue,
+    /// ]);
+    /// let input_device = std::io::stdin();
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(input_device)
+    ///     .program(program)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(machine.memory_pointer(), 0);
+    /// machine.execute_instruction().unwrap();
+    /// assert_eq!(machine.memory_pointer(), 1);
+    /// machine.execute_instruction().unwrap();
+    /// assert_eq!(machine.memory_pointer(), 1);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error if the output device fails to write a value.
+    /// * Returns a [`LimitExceeded`] error if executing the instruction would
+    ///   exceed one of the machine's `Limits`. The instruction is not executed.
+    pub fn execute_instruction(&mut self) -> Result<(), VMError> {
+        if self.get_instruction().is_some() {
+            if let Some(throttle) = self.throttle.as_mut() {
+                throttle.wait();
+            }
+        }
+        self.step()
+    }
+
+    /// Execute the current instruction without waiting for the throttle
+    fn step(&mut self) -> Result<(), VMError> {
+        let Some(current_instruction) = self.begin_instruction()? else {
+            return Ok(());
+        };
+        match current_instruction {
+            Instruction::IncrementPointer => self.increment_pointer()?,
+            Instruction::DecrementPointer => self.decrement_pointer()?,
+            Instruction::IncrementValue => self.increment_value()?,
+            Instruction::DecrementValue => self.decrement_value()?,
+            Instruction::OutputValue => self.output_value()?,
+            Instruction::InputValue => self.input_value()?,
+            Instruction::JumpForward => self.jump_forward(),
+            Instruction::JumpBackward => self.jump_backward()?,
+            Instruction::DebugDump => self.debug_dump(),
+            Instruction::DefineProcedure => self.define_procedure(),
+            Instruction::EndProcedure => self.end_procedure(),
+            Instruction::CallProcedure => self.call_procedure()?,
+            Instruction::Halt => self.halt(),
+            Instruction::Extension(character) => self.execute_extension(character)?,
+            Instruction::NoOp => {}
+        }
+        self.finish_instruction();
+
+        Ok(())
+    }
+
+    /// Check the limits for the current instruction and record it, before
+    /// it is executed
+    fn begin_instruction(&mut self) -> Result<Option<Instruction>, VMError> {
+        self.resuming = false;
+        let Some(current_instruction) = self.get_instruction() else {
+            return Ok(None);
+        };
+        if let Some(limit) = self.limits.step_limit() {
+            if self.steps >= limit {
+                return Err(LimitExceeded::Steps { limit }.into());
+            }
+        }
+        if let Some(limit) = self.limits.output_byte_limit() {
+            if current_instruction == Instruction::OutputValue && self.bytes_written >= limit {
+                return Err(LimitExceeded::OutputBytes {
+                    instruction: self.program_counter,
+                    limit,
+                }
+                .into());
+            }
+        }
+        self.check_access(current_instruction)?;
+        self.history.record(HistoryEntry::new(
+            self.program_counter,
+            current_instruction,
+            self.memory_pointer,
+            self.tape[self.memory_pointer],
+        ));
+        self.record_access(current_instruction);
+
+        Ok(Some(current_instruction))
+    }
+
+    /// Move on to the next instruction once the current one is executed
+    fn finish_instruction(&mut self) {
+        self.program_counter += 1;
+        self.steps += 1;
+        let halted = self.is_halted();
+        if let Some((every, reporter)) = self.progress.as_mut() {
+            if self.steps % *every == 0 || halted {
+                reporter.report(&Progress {
+                    steps:           self.steps,
+                    loops_completed: self.loops_completed,
+                    bytes_written:   self.bytes_written,
+                });
+            }
+        }
+    }
+
+    /// Returns `true` if the `VirtualMachine` has run out of instructions.
+    ///
+    /// The `VirtualMachine` is halted once the program counter moves past the
+    /// last instruction of the `Program`. A halted `VirtualMachine` can be
+    /// resumed by appending more instructions with
+    /// [`extend_program`](#method.extend_program).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VMReader,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(input_device)
+    ///     .program(Program::from("+"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(!machine.is_halted());
+    /// machine.execute_instruction().unwrap();
+    /// assert!(machine.is_halted());
+    /// ```
+    #[must_use]
+    pub fn is_halted(&self) -> bool {
+        self.program_counter >= self.program.length().unwrap_or(0)
+    }
+
+    /// Runs the `VirtualMachine` until it halts or hits a break.
+    ///
+    /// This method executes instructions until the program counter moves past
+    /// the last instruction of the `Program`. If breaking on output or input is
+    /// enabled, it also returns right before an `OutputValue` or `InputValue`
+    /// instruction executes. Calling `run()` again resumes execution with that
+    /// instruction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     StopReason,
+    ///     VMReader,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(input_device)
+    ///     .program(Program::from("++[>+++<-]>"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(machine.run().unwrap(), StopReason::Halted);
+    /// assert!(machine.is_halted());
+    /// assert_eq!(machine.memory_pointer(), 1);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The [`StopReason`](enum.StopReason.html) that returned control to the
+    /// caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if executing an instruction fails.
+    pub fn run(&mut self) -> Result<StopReason, VMError> {
+        self.run_until(|| false)
+    }
+
+    /// Runs the `VirtualMachine` until it halts, hits a break or `token` is
+    /// cancelled.
+    ///
+    /// This works like [`run()`](#method.run), but looks at `token` before
+    /// every instruction and returns [`StopReason::Cancelled`] once it is
+    /// cancelled, leaving the machine ready to execute the next instruction.
+    /// Calling `run()` or `run_with_cancel()` again with a token that is not
+    /// cancelled carries on from there.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CancellationToken,
+    ///     Program,
+    ///     StopReason,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+++"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let token = CancellationToken::new();
+    /// token.cancel();
+    /// assert_eq!(
+    ///     machine.run_with_cancel(&token).unwrap(),
+    ///     StopReason::Cancelled
+    /// );
+    /// assert_eq!(machine.steps(), 0);
+    ///
+    /// assert_eq!(
+    ///     machine.run_with_cancel(&CancellationToken::new()).unwrap(),
+    ///     StopReason::Halted
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if executing an instruction fails.
+    pub fn run_with_cancel(&mut self, token: &CancellationToken) -> Result<StopReason, VMError> {
+        self.run_until(|| token.is_cancelled())
+    }
+
+    /// Run until the machine halts, hits a break or `cancelled` returns true
+    fn run_until(&mut self, cancelled: impl Fn() -> bool) -> Result<StopReason, VMError> {
+        while let Some(instruction) = self.get_instruction() {
+            if cancelled() {
+                return Ok(StopReason::Cancelled);
+            }
+            if !self.resuming {
+                let reason = match instruction {
+                    Instruction::OutputValue if self.break_on_output => {
+                        Some(StopReason::OutputPending(self.tape[self.memory_pointer]))
+                    }
+                    Instruction::InputValue if self.break_on_input => {
+                        Some(StopReason::InputPending)
+                    }
+                    _ => None,
+                };
+                if let Some(reason) = reason {
+                    self.resuming = true;
+                    return Ok(reason);
+                }
+            }
+            self.execute_instruction()?;
+        }
+
+        Ok(StopReason::Halted)
+    }
+
+    /// Run the machine as an iterator of
+    /// [`ExecutionEvent`](crate::ExecutionEvent)s
+    ///
+    /// Instead of stepping the machine in a loop, callers can handle the
+    /// events of a run with `for event in machine.run_iter()`: the output of
+    /// the program, a pending read of its input, a `#` breakpoint and the end
+    /// of the program. See [`RunIter`] for the details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     ExecutionEvent,
+    ///     Program,
+    ///     VecReader,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(VecReader::new(b"hi".to_vec()))
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from(",.,."))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut echoed = Vec::new();
+    /// for event in machine.run_iter() {
+    ///     if let ExecutionEvent::Output(byte) = event.unwrap() {
+    ///         echoed.push(u8::from(&byte));
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(echoed, b"hi");
+    /// ```
+    pub fn run_iter(&mut self) -> RunIter<'_, R, W> {
+        RunIter::new(self)
+    }
+
+    /// Run the machine as an iterator of the changes to its state
+    ///
+    /// Every changed cell, move of the memory pointer and written value is
+    /// yielded as a [`StateDiff`](crate::StateDiff), so a debugger or a web
+    /// front-end can mirror the machine step by step. See [`DiffStream`] for
+    /// the details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     Program,
+    ///     StateDiff,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("++>"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let diffs: Vec<StateDiff> =
+    ///     machine.diff_stream().map(Result::unwrap).collect();
+    ///
+    /// assert_eq!(diffs.len(), 3);
+    /// assert_eq!(diffs[2], StateDiff::PointerMoved { from: 0, to: 1 });
+    /// ```
+    pub fn diff_stream(&mut self) -> DiffStream<'_, R, W> {
+        DiffStream::new(self)
+    }
+
+    /// Runs the `VirtualMachine` until it halts, awaiting its input and output
+    ///
+    /// `,` reads from `input` and `.` writes to `output` instead of the input
+    /// and output devices of the machine, so an interactive program can wait
+    /// for a socket or for `tokio::io::stdin()` without blocking a thread.
+    /// Every other instruction runs as in [`run`](Self::run), and the machine
+    /// lets other tasks run every 1024 instructions, so a long computation
+    /// does not starve them. Breaking on input and output is ignored.
+    ///
+    /// This method needs the `tokio` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// # tokio::runtime::Builder::new_current_thread()
+    /// #     .build()
+    /// #     .unwrap()
+    /// #     .block_on(async {
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from(",+.,+."))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut input: &[u8] = b"HA";
+    /// let mut output = Vec::new();
+    /// machine.run_async(&mut input, &mut output).await.unwrap();
+    ///
+    /// assert_eq!(output, b"IB");
+    /// # });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if executing an instruction fails, including writing
+    /// to `output`.
+    #[cfg(feature = "tokio")]
+    pub async fn run_async<AR, AW>(
+        &mut self,
+        input: &mut AR,
+        output: &mut AW,
+    ) -> Result<(), VMError>
+    where
+        AR: AsyncVMReader + ?Sized,
+        AW: AsyncVMWriter + ?Sized,
+    {
+        while let Some(instruction) = self.get_instruction() {
+            if let Some(throttle) = self.throttle.as_mut() {
+                let delay = throttle.delay();
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+            match instruction {
+                Instruction::InputValue => {
+                    self.begin_instruction()?;
+                    let value = match self.io_mode {
+                        IoMode::Bytes => {
+                            let result = input.read().await;
+                            self.byte_input(result)?
+                        }
+                        IoMode::Numeric => {
+                            input.read_number().await.map_err(|source| VMError::Input {
+                                instruction: self.program_counter,
+                                source,
+                            })?
+                        }
+                    };
+                    self.store_input(value);
+                    self.finish_instruction();
+                }
+                Instruction::OutputValue => {
+                    self.begin_instruction()?;
+                    let value = u8::from(&self.tape[self.memory_pointer]);
+                    let written = match self.io_mode {
+                        IoMode::Bytes => {
+                            output.write(value).await?;
+                            1
+                        }
+                        IoMode::Numeric => output.write_number(value).await?,
+                    };
+                    self.record_output(value, written);
+                    self.finish_instruction();
+                }
+                _ => self.step()?,
+            }
+            if self.steps % ASYNC_YIELD_INTERVAL == 0 {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Appends a snippet of instructions to the loaded `Program`.
+    ///
+    /// This method is meant for incremental execution, such as in a REPL.
+    /// The snippet is appended while the `VirtualMachine` is halted, and
+    /// execution continues from the previous halt point with the tape,
+    /// memory pointer and program counter left as they were.
+    ///
+    /// # Arguments
+    ///
+    /// * `snippet` - The instructions to append to the `Program`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VMReader,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(input_device)
+    ///     .program(Program::from(">+"))
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// machine.extend_program(Program::from("[-<+>]<")).unwrap();
+    /// assert_eq!(machine.program_counter(), 2);
+    /// machine.run().unwrap();
+    /// assert_eq!(machine.memory_pointer(), 0);
+    /// assert_eq!(machine.program(), Program::from(">+[-<+>]<"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * If the `VirtualMachine` is not halted
+    /// * If the resulting `Program` has unbalanced brackets. The `Program` is
+    ///   left unchanged in this case.
+    pub fn extend_program(&mut self, snippet: Program) -> Result<(), VMError> {
+        if !self.is_halted() {
+            return Err(VMError::NotHalted);
+        }
+
+        let program = self.program.concat(&snippet);
+        self.jump_table = program.jump_table()?;
+        // Continue from the end of the previous program, even if it was empty
+        self.program_counter = self.program.length().unwrap_or(0);
+        self.program = program;
+
+        Ok(())
+    }
+
+    /// Replaces a range of instructions of the loaded `Program`.
+    ///
+    /// This method is meant for patching a program while debugging it. The
+    /// jump table is rebuilt, and the tape and the counters are kept. The
+    /// program counter follows the instruction it was on: it stays where it is
+    /// if it is at or before the start of the range, moves to the start of
+    /// the range if it was inside it, and shifts by the change in length if
+    /// it was after it. The procedures of the `pbrain` dialect move the same
+    /// way, and are forgotten if their `(` was replaced. The loops and
+    /// procedure calls in progress start over, as after a
+    /// [`restore`](#method.restore).
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of instructions to replace
+    /// * `replacement` - The instructions to put in their place
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Byte,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+++>+"))
+    ///     .build()
+    ///     .unwrap();
+    /// machine.execute_instruction().unwrap();
+    ///
+    /// // Make the first cell count down instead of up
+    /// machine.splice_program(1..3, &Program::from("--")).unwrap();
+    /// assert_eq!(machine.program_counter(), 1);
+    /// machine.run().unwrap();
+    /// assert_eq!(machine.tape()[0], Byte::from(255));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the brackets of the resulting `Program` are not
+    /// balanced. The machine is left unchanged in this case.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds of the `Program`.
+    pub fn splice_program(
+        &mut self,
+        range: Range<usize>,
+        replacement: &Program,
+    ) -> Result<(), VMError> {
+        let mut program = self.program.clone();
+        program.splice(range.clone(), replacement)?;
+        let inserted = replacement.length().unwrap_or(0);
+        let moved = |position: usize| {
+            if position <= range.start {
+                Some(position)
+            } else if position < range.end {
+                None
+            } else {
+                Some(position - range.len() + inserted)
+            }
+        };
+
+        self.jump_table = program.jump_table()?;
+        self.program = program;
+        self.program_counter = moved(self.program_counter).unwrap_or(range.start);
+        self.procedures = self
+            .procedures
+            .iter()
+            .filter_map(|(&id, &start)| Some((id, moved(start)?)))
+            .collect();
+        self.loop_stack.clear();
+        self.call_stack.clear();
+        self.resuming = false;
+
+        Ok(())
+    }
+
+    /// The current state of the machine: its tape, memory pointer and
+    /// counters
+    ///
+    /// Snapshots of two machines are equal if the machines are in the same
+    /// state, see [`VMSnapshot`](crate::VMSnapshot).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut first = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("++"))
+    ///     .build()
+    ///     .unwrap();
+    /// let mut second = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+-+-++"))
+    ///     .build()
+    ///     .unwrap();
+    /// first.run().unwrap();
+    /// second.run().unwrap();
+    ///
+    /// let differences = first.snapshot().diff(&second.snapshot());
+    /// assert_eq!(differences.len(), 2);
+    /// assert_eq!(differences[0].to_string(), "program counter: 2 != 6");
+    /// ```
+    #[must_use]
+    pub fn snapshot(&self) -> VMSnapshot {
+        VMSnapshot::new(
+            TapeBackend::new(self.tape.to_vec()).with_memory_pointer(self.memory_pointer),
+            Counters {
+                program_counter: self.program_counter,
+                steps:           self.steps,
+                bytes_written:   self.bytes_written,
+            },
+        )
+        .with_loop_stack(self.loop_stack.clone())
+        .with_procedures(
+            self.procedures.iter().map(|(&id, &start)| (id, start)).collect(),
+            self.call_stack.clone(),
+        )
+    }
+
+    /// Put the `VirtualMachine` back into the state of a snapshot
+    ///
+    /// The tape, the memory pointer, the counters, the active loops and the
+    /// procedures and their calls are replaced by those of `snapshot`, so the
+    /// machine continues from where the snapshot was taken, with the
+    /// iterations of the loops it is in still counting towards
+    /// [`max_loop_iterations`](crate::Limits::max_loop_iterations). The
+    /// `Program`, the configuration and the I/O devices are kept. The history
+    /// and the heatmap start over, since they are not part of a snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - The state to continue from, usually taken from a machine
+    ///   running the same `Program`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("++++++++[>++++++++<-]>+.+."))
+    ///     .build()
+    ///     .unwrap();
+    /// while machine.bytes_written() == 0 {
+    ///     machine.execute_instruction().unwrap();
+    /// }
+    /// let snapshot = machine.snapshot();
+    ///
+    /// let mut restored = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("++++++++[>++++++++<-]>+.+."))
+    ///     .build()
+    ///     .unwrap();
+    /// restored.restore(&snapshot).unwrap();
+    /// restored.run().unwrap();
+    ///
+    /// assert_eq!(restored.output_device().data, b"B");
+    /// assert_eq!(restored.bytes_written(), 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error if the memory pointer of the snapshot is not on its
+    ///   tape. The machine is left unchanged in this case.
+    /// * Returns an error if the file of a memory-mapped tape cannot be resized
+    ///   to the tape of the snapshot.
+    pub fn restore(&mut self, snapshot: &VMSnapshot) -> Result<(), BuildError> {
+        let tape = snapshot.tape();
+        if tape.memory_pointer() >= tape.len() {
+            return Err(BuildError::PointerOutOfRange {
+                pointer: tape.memory_pointer(),
+                length:  tape.len(),
+            });
+        }
+
+        let counters = snapshot.counters();
+        self.tape.replace(tape.cells())?;
+        self.memory_pointer = tape.memory_pointer();
+        self.off_tape = None;
+        self.program_counter = counters.program_counter;
+        self.steps = counters.steps;
+        self.bytes_written = counters.bytes_written;
+        self.resuming = false;
+        self.history = History::new(self.history.capacity());
+        self.loop_stack = snapshot.loop_stack().to_vec();
+        self.procedures = snapshot
+            .procedures()
+            .iter()
+            .map(|(&id, &start)| (id, start))
+            .collect();
+        self.call_stack = snapshot.call_stack().to_vec();
+        self.set_profiling(self.heatmap.is_some());
+        self.set_taint_tracking(self.taint.is_some());
+        Ok(())
+    }
+
+    /// Take the `VirtualMachine` apart into its components
+    ///
+    /// The components can be stored or modified individually and put back
+    /// together with [`from_parts`](#method.from_parts), for example to keep
+    /// the tape of a finished run and start another `Program` on it.
+    ///
+    /// # Returns
+    ///
+    /// The `Program`, the tape, the I/O devices and the counters of the
+    /// machine. Everything else, such as the history, the heatmap and the
+    /// configured `Limits`, is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Byte,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+++>+"))
+    ///     .tape_size(4)
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// let (program, tape, _, counters) = machine.into_parts();
+    ///
+    /// assert_eq!(program, Program::from("+++>+"));
+    /// assert_eq!(tape.cells()[0], Byte::from(3));
+    /// assert_eq!(tape.memory_pointer(), 1);
+    /// assert_eq!(counters.steps, 5);
+    /// ```
+    #[must_use]
+    pub fn into_parts(self) -> (Program, TapeBackend, IoDevices<R, W>, Counters) {
+        let tape = TapeBackend::new(self.tape.into_vec()).with_memory_pointer(self.memory_pointer);
+        let devices = IoDevices {
+            input:  self.input,
+            output: self.output,
+        };
+        let counters = Counters {
+            program_counter: self.program_counter,
+            steps:           self.steps,
+            bytes_written:   self.bytes_written,
+        };
+
+        (self.program, tape, devices, counters)
+    }
+
+    /// Put a `VirtualMachine` together from its components
+    ///
+    /// This is the inverse of [`into_parts`](#method.into_parts). The machine
+    /// starts with the default configuration of a
+    /// [`VirtualMachineBuilder`], an empty history and no active loops or
+    /// procedure calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The `Program` to run
+    /// * `tape` - The cells of the machine and the position of the memory
+    ///   pointer
+    /// * `devices` - The input and output devices
+    /// * `counters` - The program counter and the statistics to continue from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("++++++++[>++++++++<-]>+"))
+    ///     .build()
+    ///     .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// // Keep the tape, but run a different program on it
+    /// let (_, tape, devices, _) = machine.into_parts();
+    /// let mut machine = VirtualMachine::from_parts(
+    ///     Program::from("..+."),
+    ///     tape,
+    ///     devices,
+    ///     Default::default(),
+    /// )
+    /// .unwrap();
+    /// machine.run().unwrap();
+    ///
+    /// assert_eq!(machine.output_device().data, b"AAB");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error if the memory pointer is not on the tape.
+    /// * Returns an error if the brackets of the `Program` are not balanced.
+    pub fn from_parts(
+        program: Program,
+        tape: TapeBackend,
+        devices: IoDevices<R, W>,
+        counters: Counters,
+    ) -> Result<Self, BuildError> {
+        if tape.memory_pointer() >= tape.len() {
+            return Err(BuildError::PointerOutOfRange {
+                pointer: tape.memory_pointer(),
+                length:  tape.len(),
+            });
+        }
+
+        let memory_pointer = tape.memory_pointer();
+        let mut machine = Self::new(
+            0,
+            program,
+            memory_pointer,
+            counters.program_counter,
+            devices.input,
+            devices.output,
+        );
+        machine.tape = TapeStorage::memory(tape.into_cells());
+        machine.steps = counters.steps;
+        machine.bytes_written = counters.bytes_written;
+        machine.rebuild_jump_table()?;
+
+        Ok(machine)
+    }
+
+    /// Check that the instruction does not use a canary cell, and that the
+    /// `MemoryMap` allows it to use the current cell
+    fn check_access(&self, instruction: Instruction) -> Result<(), VMError> {
+        if let Some((direction, distance)) = self.off_tape {
+            if instruction.reads_cell() || instruction.writes_cell() {
+                return Err(self.canary_touched(direction, distance));
+            }
+        }
+        let Some(memory_map) = &self.memory_map else {
+            return Ok(());
+        };
+        let accesses = [
+            (instruction.reads_cell(), Access::Read),
+            (instruction.writes_cell(), Access::Write),
+        ];
+        for (used, access) in accesses {
+            if used && !memory_map.allows(self.memory_pointer, access) {
+                return Err(VMError::AccessViolation {
+                    access,
+                    cell: self.memory_pointer,
+                    instruction: self.program_counter,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn record_access(&mut self, instruction: Instruction) {
+        let Some(stats) = self
+            .heatmap
+            .as_mut()
+            .and_then(|heatmap| heatmap.get_mut(self.memory_pointer))
+        else {
+            return;
+        };
+        if instruction.reads_cell() {
+            stats.record_read();
+        }
+        if instruction.writes_cell() {
+            stats.record_write();
+        }
+    }
+
+    /// Move the memory pointer a cell towards `direction` while it is past
+    /// an end of the tape, or off the end it is on
+    fn move_over_canaries(&mut self, direction: Direction) -> Result<(), VMError> {
+        let (side, distance) = match self.off_tape {
+            Some((side, distance)) if side == direction => (side, distance + 1),
+            Some((side, distance)) => (side, distance - 1),
+            None => (direction, 1),
+        };
+        if distance > self.canary_cells {
+            return Err(self.canary_touched(side, self.canary_cells));
+        }
+        self.off_tape = (distance > 0).then_some((side, distance));
+        Ok(())
+    }
+
+    const fn canary_touched(&self, direction: Direction, distance: usize) -> VMError {
+        VMError::CanaryTouched {
+            direction,
+            distance,
+            instruction: self.program_counter,
+            step: self.steps,
+        }
+    }
+
+    fn increment_pointer(&mut self) -> Result<(), VMError> {
+        if self.off_tape.is_some()
+            || (self.canary_cells > 0 && self.memory_pointer + 1 >= self.tape.len())
+        {
+            return self.move_over_canaries(Direction::Right);
+        }
+        if self.memory_pointer + 1 < self.tape.len() {
+            self.memory_pointer += 1;
+            return Ok(());
+        }
+        match self.tape_edge {
+            TapeEdge::Wrap => {
+                self.memory_pointer = 0;
+                self.warn(WarningKind::PointerWrapped {
+                    from: self.tape.len() - 1,
+                    to:   0,
+                })?;
+            }
+            TapeEdge::Grow => {
+                self.tape.push()?;
+                self.tape.compact_when_grown();
+                if let Some(heatmap) = self.heatmap.as_mut() {
+                    heatmap.push(CellStats::default());
+                }
+                self.memory_pointer += 1;
+            }
+            TapeEdge::Error => {
+                return Err(ProfileViolation::PointerOverflow {
+                    instruction: self.program_counter,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    fn decrement_pointer(&mut self) -> Result<(), VMError> {
+        if self.off_tape.is_some() || (self.canary_cells > 0 && self.memory_pointer == 0) {
+            return self.move_over_canaries(Direction::Left);
+        }
+        if let Some(previous) = self.memory_pointer.checked_sub(1) {
+            self.memory_pointer = previous;
+            return Ok(());
+        }
+        match self.tape_edge {
+            TapeEdge::Wrap => {
+                self.memory_pointer = self.tape.len() - 1;
+                self.warn(WarningKind::PointerWrapped {
+                    from: 0,
+                    to:   self.memory_pointer,
+                })?;
+            }
+            TapeEdge::Grow | TapeEdge::Error => {
+                return Err(ProfileViolation::PointerUnderflow {
+                    instruction: self.program_counter,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    fn increment_value(&mut self) -> Result<(), VMError> {
+        if !self.wrap_cells && u8::from(&self.tape[self.memory_pointer]) == u8::MAX {
+            return Err(ProfileViolation::CellOverflow {
+                instruction: self.program_counter,
+                cell:        self.memory_pointer,
+            }
+            .into());
+        }
+        self.tape.update(self.memory_pointer, Byte::increment);
+        if let Some(taint) = self.taint.as_mut() {
+            taint.write(self.memory_pointer);
+        }
+        if u8::from(&self.tape[self.memory_pointer]) == 0 {
+            self.warn(WarningKind::CellWrapped {
+                cell:  self.memory_pointer,
+                value: 0,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn decrement_value(&mut self) -> Result<(), VMError> {
+        if !self.wrap_cells && u8::from(&self.tape[self.memory_pointer]) == 0 {
+            return Err(ProfileViolation::CellUnderflow {
+                instruction: self.program_counter,
+                cell:        self.memory_pointer,
+            }
+            .into());
+        }
+        self.tape.update(self.memory_pointer, Byte::decrement);
+        if let Some(taint) = self.taint.as_mut() {
+            taint.write(self.memory_pointer);
+        }
+        if u8::from(&self.tape[self.memory_pointer]) == u8::MAX {
+            self.warn(WarningKind::CellWrapped {
+                cell:  self.memory_pointer,
+                value: u8::MAX,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn output_value(&mut self) -> Result<(), VMError> {
+        let value = u8::from(&self.tape[self.memory_pointer]);
+        let written = match self.io_mode {
+            IoMode::Bytes => {
+                self.output.write(value)?;
+                1
+            }
+            IoMode::Numeric => self.output.write_number(value)?,
+        };
+        self.record_output(value, written);
+        Ok(())
+    }
+
+    /// Count the bytes `.` wrote for the `value` of the current cell, and
+    /// record its influence on the output
+    fn record_output(&mut self, value: u8, written: usize) {
+        self.bytes_written += written;
+        if let Some(taint) = self.taint.as_mut() {
+            taint.output(self.memory_pointer, value);
+        }
+    }
+
+    fn debug_dump(&mut self) {
+        (self.debug_hook)(&DebugDump::new(
+            &self.tape.slice(0..self.tape.len()),
+            self.memory_pointer,
+            self.program_counter,
+            self.steps,
+        ));
+    }
+
+    fn execute_extension(&mut self, character: char) -> Result<(), VMError> {
+        let Some(handler) = self.extensions.get_mut(&character) else {
+            return Err(VMError::UnknownExtension {
+                character,
+                instruction: self.program_counter,
+            });
+        };
+        let mut context = VmContext::new(
+            &mut self.tape,
+            &mut self.memory_pointer,
+            self.program_counter,
+            self.steps,
+            &mut self.input,
+            &mut self.output,
+            &mut self.bytes_written,
+        );
+        handler
+            .execute(&mut context)
+            .map_err(|source| VMError::Extension {
+                character,
+                instruction: self.program_counter,
+                source,
+            })
+    }
+
+    fn define_procedure(&mut self) {
+        let id = u8::from(&self.tape[self.memory_pointer]);
+        self.procedures.insert(id, self.program_counter);
+        if let Some(Some(end)) = self.jump_table.get(self.program_counter) {
+            self.program_counter = *end;
+        }
+    }
+
+    fn end_procedure(&mut self) {
+        if let Some(call) = self.call_stack.pop() {
+            self.program_counter = call;
+        }
+    }
+
+    fn call_procedure(&mut self) -> Result<(), VMError> {
+        let id = u8::from(&self.tape[self.memory_pointer]);
+        let Some(&start) = self.procedures.get(&id) else {
+            return Err(VMError::UndefinedProcedure {
+                id,
+                instruction: self.program_counter,
+            });
+        };
+        if let Some(limit) = self.limits.call_depth_limit() {
+            if self.call_stack.len() >= limit {
+                return Err(LimitExceeded::CallDepth {
+                    call: self.program_counter,
+                    limit,
+                }
+                .into());
+            }
+        }
+        self.call_stack.push(self.program_counter);
+        self.program_counter = start;
+
+        Ok(())
+    }
+
+    fn halt(&mut self) {
+        // The program counter is incremented once more after this
+        self.program_counter = self.program.length().unwrap_or(0).saturating_sub(1);
+    }
+
+    fn input_value(&mut self) -> Result<(), VMError> {
+        let input = match self.io_mode {
+            IoMode::Bytes => {
+                let result = self.input.read();
+                self.byte_input(result)?
+            }
+            IoMode::Numeric => self.input.read_number().map_err(|source| VMError::Input {
+                instruction: self.program_counter,
+                source,
+            })?,
+        };
+        self.store_input(input);
+        Ok(())
+    }
+
+    /// The value read by `,` in [`IoMode::Bytes`]
+    ///
+    /// The end of the input, an interrupted read and a byte outside of the
+    /// ASCII range all end the input, the last two with a warning. Only a
+    /// failure of the device itself stops the machine.
+    fn byte_input(&mut self, result: Result<u8, ReadError>) -> Result<Option<u8>, VMError> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(source @ ReadError::Io(_)) => Err(VMError::Input {
+                instruction: self.program_counter,
+                source,
+            }),
+            Err(ReadError::NotAscii(byte)) => {
+                self.warn(WarningKind::InputNotAscii(byte))?;
+                Ok(None)
+            }
+            Err(ReadError::Interrupted) => {
+                self.warn(WarningKind::InputInterrupted)?;
+                Ok(None)
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Send a warning about the current instruction to the diagnostics sink
+    fn warn(&mut self, kind: WarningKind) -> Result<(), VMError> {
+        let warning = Warning {
+            step: self.steps,
+            instruction: self.program_counter,
+            kind,
+        };
+        match self.diagnostics.warn(&warning) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(VMError::Warning(warning)),
+        }
+    }
+
+    /// Store a value read by `,` in the current cell, or apply the
+    /// `EofBehavior` if the input has ended, and record where the value of
+    /// the cell came from
+    fn store_input(&mut self, input: Option<u8>) {
+        let eof_behavior = self.eof_behavior;
+        self.tape.update(self.memory_pointer, |cell| {
+            *cell = match input {
+                Some(input) => Byte::from(input),
+                None => eof_behavior.apply(*cell),
+            };
+        });
+        if let Some(taint) = self.taint.as_mut() {
+            match (input, eof_behavior) {
+                (Some(_), _) => taint.read(self.memory_pointer),
+                (None, EofBehavior::Unchanged) => taint.write(self.memory_pointer),
+                (None, EofBehavior::Zero | EofBehavior::Max) => {
+                    taint.overwrite(self.memory_pointer);
+                }
+            }
+        }
+    }
+
+    fn jump_forward(&mut self) {
+        if self.tape[self.memory_pointer] == Byte::default() {
+            if let Some(Some(target)) = self.jump_table.get(self.program_counter) {
+                self.program_counter = *target;
+            }
+        } else {
+            self.loop_stack
+                .push(LoopFrame::new(self.program_counter, 1));
+            if let Some(taint) = self.taint.as_mut() {
+                taint.enter_loop(self.memory_pointer);
+            }
+        }
+    }
+
+    fn jump_backward(&mut self) -> Result<(), VMError> {
+        if self.tape[self.memory_pointer] == Byte::default() {
+            self.loop_stack.pop();
+            self.loops_completed += 1;
+            if let Some(taint) = self.taint.as_mut() {
+                taint.exit_loop();
+            }
+            return Ok(());
+        }
+        if let Some(frame) = self.loop_stack.last_mut() {
+            if let Some(limit) = self.limits.loop_iteration_limit() {
+                if frame.iterations() >= limit {
+                    return Err(LimitExceeded::LoopIterations {
+                        entry: frame.entry(),
+                        limit,
+                    }
+                    .into());
+                }
+            }
+            frame.next_iteration();
+        }
+        if let Some(taint) = self.taint.as_mut() {
+            taint.repeat_loop(self.memory_pointer);
+        }
+        if let Some(Some(target)) = self.jump_table.get(self.program_counter) {
+            self.program_counter = *target;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        vm_reader::MockReader,
+        vm_writer::CaptureWriter,
+        BehaviorProfile,
+        Dialect,
+    };
+
+    #[test]
+    fn test_machine_get_instruction() {
+        let instructions = vec![
+            Instruction::IncrementPointer,
+            Instruction::DecrementPointer,
+            Instruction::IncrementValue,
+            Instruction::DecrementValue,
+            Instruction::OutputValue,
+            Instruction::InputValue,
+            Instruction::JumpForward,
+            Instruction::JumpBackward,
+            Instruction::NoOp,
+        ];
+        let program = Program::from(instructions);
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(program)
+            .build()
+            .unwrap();
+        assert_eq!(
+            machine.get_instruction(),
+            Some(Instruction::IncrementPointer)
+        );
+    }
+
+    #[test]
+    fn test_machine_execute_instruction() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let program = Program::from(vec![
+            Instruction::IncrementPointer,
+            Instruction::IncrementValue,
+            Instruction::DecrementValue,
+            Instruction::DecrementPointer,
+        ]);
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(program)
+            .build()
+            .unwrap();
+
+        machine.execute_instruction().unwrap();
+        assert_eq!(
+            machine.memory_pointer(),
+            1,
+            "Memory pointer should be incremented"
+        );
+        assert_eq!(
+            machine.program_counter(),
+            1,
+            "Program counter should be incremented"
+        );
+
+        machine.execute_instruction().unwrap();
+        assert_eq!(
+            machine.tape[1],
+            Byte::from(0b0000_0001),
+            "Value at memory pointer should be incremented"
+        );
+        assert_eq!(
+            machine.memory_pointer(),
+            1,
+            "Memory pointer should not be changed"
+        );
+        assert_eq!(
+            machine.program_counter(),
+            2,
+            "Program counter should be incremented"
+        );
+
+        machine.execute_instruction().unwrap();
+        assert_eq!(
+            machine.tape[1],
+            Byte::from(0),
+            "Value at memory pointer should be decremented"
+        );
+        assert_eq!(
+            machine.memory_pointer(),
+            1,
+            "Memory pointer should not be decremented"
+        );
+        assert_eq!(
+            machine.program_counter(),
+            3,
+            "Program counter should be incremented"
+        );
+
+        machine.execute_instruction().unwrap();
+        assert_eq!(
+            machine.memory_pointer(),
+            0,
+            "Memory pointer should be decremented"
+        );
+        assert_eq!(
+            machine.program_counter(),
+            4,
+            "Program counter should be incremented"
+        );
+    }
+
+    #[test]
+    fn test_memory_pointer() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+        assert_eq!(
+            machine.memory_pointer(),
+            0,
+            "Memory pointer should be initialized to 0"
+        );
+    }
+
+    #[test]
+    fn test_program_counter() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+        assert_eq!(
+            machine.program_counter(),
+            0,
+            "Program counter should be initialized to 0"
+        );
+    }
+
+    #[test]
+    fn test_increment_pointer() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+        machine.increment_pointer().unwrap();
+        assert_eq!(
+            machine.memory_pointer(),
+            1,
+            "Memory pointer should be incremented"
+        );
+    }
+
+    #[test]
+    fn test_decrement_pointer() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .tape_size(100)
+            .build()
+            .unwrap();
+        machine.decrement_pointer().unwrap();
+        assert_eq!(
+            machine.memory_pointer(),
+            99,
+            "Memory pointer should be decremented"
+        );
+    }
+
+    #[test]
+    fn test_increment_value() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+        let increment_result = Byte::from(1);
+
+        machine.increment_value().unwrap();
+        assert_eq!(
+            machine.tape[0], increment_result,
+            "Value at memory pointer should be incremented"
+        );
+    }
+
+    #[test]
+    fn test_decrement_value() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+        machine.tape.set(0, Byte::from(1));
+        machine.decrement_value().unwrap();
+        assert_eq!(
+            machine.tape[0],
+            Byte::from(0),
+            "Value at memory pointer should be decremented"
+        );
     }
 
-    fn decrement_value(&mut self) {
-        self.tape[self.memory_pointer].decrement();
+    #[test]
+    fn test_output_value() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .build()
+            .unwrap();
+        machine.tape.set(0, Byte::from(65));
+        machine.output_value().unwrap();
+        assert_eq!(machine.output_device().data, b"A");
     }
 
-    fn output_value(&self) {
-        todo!("Implement output_value")
+    #[test]
+    fn test_valid_input_value() {
+        let data = vec![65]; // A's ASCII value is 65
+        let input_device = MockReader {
+            data: Cursor::new(data),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+
+        machine.input_value().unwrap();
+
+        assert_eq!(
+            machine.tape[0],
+            Byte::from(65),
+            "Value at memory pointer should be set to the input value"
+        );
     }
 
-    fn input_value(&mut self) {
-        let input = self.input.read();
-        if let Ok(input) = input {
-            self.tape[self.memory_pointer] = Byte::from(input);
-        }
+    #[test]
+    fn test_invalid_input_value() {
+        let data = vec![129]; // 129 is not a valid ASCII value
+        let input_device = MockReader {
+            data: Cursor::new(data),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+
+        machine.input_value().unwrap();
+
+        assert_eq!(
+            machine.tape[0],
+            Byte::from(0),
+            "Value at memory pointer should not be set to the input value"
+        );
     }
 
-    fn jump_forward(&self) {
-        todo!("Implement jump_forward")
+    #[test]
+    fn test_jump_forward() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::from("[+]+"))
+            .build()
+            .unwrap();
+
+        machine.execute_instruction().unwrap();
+        assert_eq!(
+            machine.program_counter(),
+            3,
+            "Loop should be skipped when the current cell is zero"
+        );
+
+        machine.execute_instruction().unwrap();
+        assert_eq!(machine.tape[0], Byte::from(1));
     }
 
-    fn jump_backward(&self) {
-        todo!("Implement jump_backward")
+    #[test]
+    fn test_jump_backward() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::from("+++[>+<-]"))
+            .build()
+            .unwrap();
+
+        machine.run().unwrap();
+        assert_eq!(machine.tape[0], Byte::from(0));
+        assert_eq!(machine.tape[1], Byte::from(3));
+        assert_eq!(machine.memory_pointer(), 0);
+        assert!(machine.is_halted());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::io::Cursor;
+    #[test]
+    fn test_nested_loops() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::from("++[>+++[>++<-]<-]"))
+            .build()
+            .unwrap();
 
-    use super::*;
-    use crate::vm_reader::MockReader;
+        machine.run().unwrap();
+        assert_eq!(machine.tape[2], Byte::from(12));
+    }
 
     #[test]
-    fn test_machine_get_instruction() {
-        let instructions = vec![
-            Instruction::IncrementPointer,
-            Instruction::DecrementPointer,
-            Instruction::IncrementValue,
-            Instruction::DecrementValue,
-            Instruction::OutputValue,
-            Instruction::InputValue,
-            Instruction::JumpForward,
-            Instruction::JumpBackward,
-            Instruction::NoOp,
-        ];
-        let program = Program::from(instructions);
+    fn test_unbalanced_program() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
         let machine = VirtualMachine::builder()
             .input_device(input_device)
-            .program(program)
+            .program(Program::from("[[]"))
+            .build();
+
+        assert!(machine.is_err());
+    }
+
+    #[test]
+    fn test_execute_instruction_when_halted() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::from("+"))
             .build()
             .unwrap();
-        assert_eq!(
-            machine.get_instruction(),
-            Some(Instruction::IncrementPointer)
-        );
+
+        machine.run().unwrap();
+        machine.execute_instruction().unwrap();
+        assert_eq!(machine.program_counter(), 1);
+        assert!(machine.is_halted());
     }
 
     #[test]
-    fn test_machine_execute_instruction() {
+    fn test_extend_program() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
-        let program = Program::from(vec![
-            Instruction::IncrementPointer,
-            Instruction::IncrementValue,
-            Instruction::DecrementValue,
-            Instruction::DecrementPointer,
-        ]);
         let mut machine = VirtualMachine::builder()
             .input_device(input_device)
-            .program(program)
+            .program(Program::from("++"))
             .build()
             .unwrap();
 
-        machine.execute_instruction();
-        assert_eq!(
-            machine.memory_pointer(),
-            1,
-            "Memory pointer should be incremented"
-        );
-        assert_eq!(
-            machine.program_counter(),
-            1,
-            "Program counter should be incremented"
-        );
+        machine.run().unwrap();
+        machine.extend_program(Program::from("[>+<-]")).unwrap();
+        assert!(!machine.is_halted());
+        machine.run().unwrap();
+        assert_eq!(machine.tape[1], Byte::from(2));
 
-        machine.execute_instruction();
-        assert_eq!(
-            machine.tape[1],
-            Byte::from(0b0000_0001),
-            "Value at memory pointer should be incremented"
-        );
-        assert_eq!(
-            machine.memory_pointer(),
-            1,
-            "Memory pointer should not be changed"
-        );
-        assert_eq!(
-            machine.program_counter(),
-            2,
-            "Program counter should be incremented"
+        machine.extend_program(Program::from(">+")).unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.tape[1], Byte::from(3));
+        assert_eq!(machine.program_counter(), 10);
+    }
+
+    #[test]
+    fn test_extend_empty_program() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::from(""))
+            .build()
+            .unwrap();
+
+        assert!(machine.is_halted());
+        machine.extend_program(Program::from("+")).unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.tape[0], Byte::from(1));
+    }
+
+    #[test]
+    fn test_extend_program_errors() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::from("+"))
+            .build()
+            .unwrap();
+
+        assert!(
+            machine.extend_program(Program::from("+")).is_err(),
+            "A running machine should not be extended"
         );
 
-        machine.execute_instruction();
-        assert_eq!(
-            machine.tape[1],
-            Byte::from(0),
-            "Value at memory pointer should be decremented"
+        machine.run().unwrap();
+        assert!(
+            machine.extend_program(Program::from("]")).is_err(),
+            "Unbalanced snippets should be rejected"
         );
+        assert_eq!(machine.program(), Program::from("+"));
+    }
+
+    #[test]
+    fn test_splice_program() {
+        let mut machine = VirtualMachine::testing("++[->+<]>.", b"").unwrap();
+        for _ in 0..4 {
+            machine.execute_instruction().unwrap();
+        }
+        assert_eq!(machine.program_counter(), 4);
+
+        // Before the program counter, which shifts along
+        machine.splice_program(0..0, &Program::from("+")).unwrap();
+        assert_eq!(machine.program_counter(), 5);
+        // Around it, which moves it to the start of the replacement
+        machine
+            .splice_program(4..7, &Program::from("->++"))
+            .unwrap();
+        assert_eq!(machine.program_counter(), 4);
+        assert_eq!(machine.program(), Program::from("+++[->++<]>."));
+        assert!(machine.loop_stack().is_empty());
+
+        assert!(machine.splice_program(3..4, &Program::from("")).is_err());
+        assert_eq!(machine.program(), Program::from("+++[->++<]>."));
+        // The first cell was already decremented from 2 to 1 before the edit
+        machine.run().unwrap();
+        assert_eq!(machine.tape()[1], Byte::from(2));
+    }
+
+    #[test]
+    fn test_parts_round_trip() {
+        let input_device = MockReader {
+            data: Cursor::new("AB".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .program(Program::from(",.>,+.<"))
+            .tape_size(3)
+            .build()
+            .unwrap();
+        machine.set_break_on_output(true);
+        machine.run().unwrap();
+
+        let (program, tape, devices, counters) = machine.into_parts();
+        assert_eq!(tape.memory_pointer(), 0);
+        assert_eq!(counters.program_counter, 1);
+        assert_eq!(counters.steps, 1);
+
+        let mut machine = VirtualMachine::from_parts(program, tape, devices, counters).unwrap();
+        machine.run().unwrap();
+
+        assert_eq!(machine.output_device().data, b"AC");
+        assert_eq!(machine.bytes_written(), 2);
+        assert_eq!(machine.steps(), 7);
+        assert_eq!(machine.memory_pointer(), 0);
+    }
+
+    #[test]
+    fn test_from_parts_errors() {
+        let devices = || IoDevices {
+            input:  MockReader {
+                data: Cursor::new(Vec::new()),
+            },
+            output: CaptureWriter::default(),
+        };
+        let tape = TapeBackend::new(vec![Byte::default(); 2]);
+
+        assert!(VirtualMachine::from_parts(
+            Program::from("+"),
+            tape.clone().with_memory_pointer(2),
+            devices(),
+            Counters::default()
+        )
+        .is_err());
+        assert!(VirtualMachine::from_parts(
+            Program::from("["),
+            tape.clone(),
+            devices(),
+            Counters::default()
+        )
+        .is_err());
+        assert!(VirtualMachine::from_parts(
+            Program::from("+"),
+            tape,
+            devices(),
+            Counters::default()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_run_breaks_on_output() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .program(Program::from("+.+."))
+            .break_on_output(true)
+            .build()
+            .unwrap();
+
         assert_eq!(
-            machine.memory_pointer(),
-            1,
-            "Memory pointer should not be decremented"
+            machine.run().unwrap(),
+            StopReason::OutputPending(Byte::from(1))
         );
+        assert_eq!(machine.program_counter(), 1);
+        assert!(machine.output_device().data.is_empty());
+
         assert_eq!(
-            machine.program_counter(),
-            3,
-            "Program counter should be incremented"
+            machine.run().unwrap(),
+            StopReason::OutputPending(Byte::from(2))
         );
+        assert_eq!(machine.output_device().data, vec![1]);
+
+        assert_eq!(machine.run().unwrap(), StopReason::Halted);
+        assert_eq!(machine.output_device().data, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_run_breaks_on_input() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .program(Program::from(">,."))
+            .break_on_input(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(machine.run().unwrap(), StopReason::InputPending);
+        assert_eq!(machine.program_counter(), 1);
+        assert_eq!(machine.tape[1], Byte::from(0));
+
+        assert_eq!(machine.run().unwrap(), StopReason::Halted);
+        assert_eq!(machine.tape[1], Byte::from(65));
+        assert_eq!(machine.output_device().data, b"A");
+    }
+
+    #[test]
+    fn test_run_without_breaks() {
+        let mut machine = VirtualMachine::testing(",.,.", b"A").unwrap();
 
-        machine.execute_instruction();
-        assert_eq!(
-            machine.memory_pointer(),
-            0,
-            "Memory pointer should be decremented"
-        );
-        assert_eq!(
-            machine.program_counter(),
-            4,
-            "Program counter should be incremented"
-        );
+        assert!(!machine.breaks_on_output());
+        assert!(!machine.breaks_on_input());
+        assert_eq!(machine.run().unwrap(), StopReason::Halted);
+        assert_eq!(machine.output_device().data, b"AA");
+
+        machine.set_break_on_output(true);
+        machine.set_break_on_input(true);
+        assert!(machine.breaks_on_output());
+        assert!(machine.breaks_on_input());
     }
 
     #[test]
-    fn test_memory_pointer() {
+    fn test_history() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
-        let machine = VirtualMachine::builder()
+        let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .program(Program::from("++[-]"))
+            .history_capacity(3)
             .build()
             .unwrap();
+
+        machine.run().unwrap();
+        let recorded: Vec<(usize, Instruction, usize, Byte)> = machine
+            .history()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.program_counter(),
+                    entry.instruction(),
+                    entry.memory_pointer(),
+                    entry.cell(),
+                )
+            })
+            .collect();
         assert_eq!(
-            machine.memory_pointer(),
-            0,
-            "Memory pointer should be initialized to 0"
+            recorded,
+            vec![
+                (4, Instruction::JumpBackward, 0, Byte::from(1)),
+                (3, Instruction::DecrementValue, 0, Byte::from(1)),
+                (4, Instruction::JumpBackward, 0, Byte::from(0)),
+            ]
         );
     }
 
     #[test]
-    fn test_program_counter() {
+    fn test_history_disabled_by_default() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
-        let machine = VirtualMachine::builder()
+        let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .program(Program::from("+++"))
             .build()
             .unwrap();
-        assert_eq!(
-            machine.program_counter(),
-            0,
-            "Program counter should be initialized to 0"
-        );
+
+        machine.run().unwrap();
+        assert!(machine.history().is_empty());
     }
 
     #[test]
-    fn test_increment_pointer() {
+    fn test_loop_stack() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
         let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .program(Program::from("++[>+++[-]<-]"))
             .build()
             .unwrap();
-        machine.increment_pointer();
+
+        // `++[` enters the outer loop
+        for _ in 0..3 {
+            machine.execute_instruction().unwrap();
+        }
+        assert_eq!(machine.loop_stack(), &[LoopFrame::new(2, 1)]);
+
+        // `>+++[` enters the inner loop, `-]` jumps back twice
+        for _ in 0..9 {
+            machine.execute_instruction().unwrap();
+        }
         assert_eq!(
-            machine.memory_pointer(),
-            1,
-            "Memory pointer should be incremented"
+            machine.loop_stack(),
+            &[LoopFrame::new(2, 1), LoopFrame::new(7, 3)]
         );
+
+        // The final `]` of the inner loop falls through
+        for _ in 0..2 {
+            machine.execute_instruction().unwrap();
+        }
+        assert_eq!(machine.loop_stack(), &[LoopFrame::new(2, 1)]);
+
+        // `<-]` jumps back into the outer loop
+        for _ in 0..3 {
+            machine.execute_instruction().unwrap();
+        }
+        assert_eq!(machine.loop_stack(), &[LoopFrame::new(2, 2)]);
+
+        machine.run().unwrap();
+        assert!(machine.loop_stack().is_empty());
     }
 
     #[test]
-    fn test_decrement_pointer() {
+    fn test_loop_stack_skipped_loop() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
         let mut machine = VirtualMachine::builder()
             .input_device(input_device)
-            .tape_size(100)
+            .program(Program::from("[+]"))
             .build()
             .unwrap();
-        machine.decrement_pointer();
-        assert_eq!(
-            machine.memory_pointer(),
-            99,
-            "Memory pointer should be decremented"
-        );
+
+        machine.execute_instruction().unwrap();
+        assert!(machine.loop_stack().is_empty());
     }
 
     #[test]
-    fn test_increment_value() {
+    fn test_steps() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
         let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .program(Program::from("+>+"))
             .build()
             .unwrap();
-        let increment_result = Byte::from(1);
 
-        machine.increment_value();
-        assert_eq!(
-            machine.tape[0], increment_result,
-            "Value at memory pointer should be incremented"
-        );
+        assert_eq!(machine.steps(), 0);
+        machine.run().unwrap();
+        assert_eq!(machine.steps(), 3);
+        machine.execute_instruction().unwrap();
+        assert_eq!(machine.steps(), 3, "Halted machines do not count steps");
     }
 
     #[test]
-    fn test_decrement_value() {
+    fn test_step_limit() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
         let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .program(Program::from("+++++"))
+            .limits(Limits::new().max_steps(3))
             .build()
             .unwrap();
-        machine.tape[0] = Byte::from(1);
-        machine.decrement_value();
+
+        let error = machine.run().unwrap_err();
+        assert!(matches!(
+            error,
+            VMError::LimitExceeded(LimitExceeded::Steps { limit: 3 })
+        ));
+        assert_eq!(machine.steps(), 3);
+        assert_eq!(machine.program_counter(), 3);
+        assert_eq!(machine.tape[0], Byte::from(3));
+    }
+
+    #[test]
+    fn test_testing() {
+        let mut machine = VirtualMachine::testing("+[]", b"").unwrap();
+        assert_eq!(machine.length(), 1024);
+        assert!(matches!(
+            machine.run(),
+            Err(VMError::LimitExceeded(LimitExceeded::Steps {
+                limit: 1_000_000,
+            }))
+        ));
+
+        assert!(VirtualMachine::testing("[", b"").is_err());
+    }
+
+    #[test]
+    fn test_multi_cell_integers() {
+        let mut machine = VirtualMachine::testing("", b"").unwrap();
+        machine
+            .write_u16_at(10, 0x1234, Endianness::Little)
+            .unwrap();
+        assert_eq!(machine.find_in_tape(&[0x34, 0x12]), [10]);
+        assert_eq!(machine.read_u16_at(10, Endianness::Big).unwrap(), 0x3412);
+
+        machine
+            .write_u32_at(1020, 0x0102_0304, Endianness::Big)
+            .unwrap();
         assert_eq!(
-            machine.tape[0],
-            Byte::from(0),
-            "Value at memory pointer should be decremented"
+            machine.read_u32_at(1020, Endianness::Big).unwrap(),
+            0x0102_0304
+        );
+        assert_eq!(
+            machine.read_u32_at(1020, Endianness::Little).unwrap(),
+            0x0403_0201
         );
+
+        assert!(matches!(
+            machine.write_u32_at(1021, u32::MAX, Endianness::Big),
+            Err(VMError::CellOutOfRange {
+                cell:   1024,
+                length: 1024,
+            })
+        ));
+        assert_eq!(machine.read_u16_at(1021, Endianness::Big).unwrap(), 0x0203);
+        assert!(machine.read_u16_at(usize::MAX, Endianness::Big).is_err());
     }
 
     #[test]
-    #[should_panic(expected = "not yet implemented")]
-    fn test_output_value() {
+    fn test_output_byte_limit() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
-        let machine = VirtualMachine::builder()
+        let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .program(Program::from("+.+.+.+."))
+            .limits(Limits::new().max_output_bytes(2))
             .build()
             .unwrap();
-        machine.output_value();
+
+        let error = machine.run().unwrap_err();
+        assert!(matches!(
+            error,
+            VMError::LimitExceeded(LimitExceeded::OutputBytes {
+                instruction: 5,
+                limit:       2,
+            })
+        ));
+        assert_eq!(machine.bytes_written(), 2);
+        assert_eq!(machine.output_device().data, [1, 2]);
+        assert_eq!(machine.tape[0], Byte::from(3));
     }
 
     #[test]
-    fn test_valid_input_value() {
-        let data = vec![65]; // A's ASCII value is 65
+    fn test_eof_behavior() {
+        for (behavior, expected) in [
+            (EofBehavior::Unchanged, 3),
+            (EofBehavior::Zero, 0),
+            (EofBehavior::Max, 255),
+        ] {
+            let input_device = MockReader {
+                data: Cursor::new("A".as_bytes().to_vec()),
+            };
+            let mut machine = VirtualMachine::builder()
+                .input_device(input_device)
+                .program(Program::from(",>+++,"))
+                .eof_behavior(behavior)
+                .build()
+                .unwrap();
+            machine.run().unwrap();
+
+            assert_eq!(machine.eof_behavior(), behavior);
+            assert_eq!(machine.tape[0], Byte::from(65));
+            assert_eq!(machine.tape[1], Byte::from(expected));
+        }
+    }
+
+    #[test]
+    fn test_load_tape_too_long() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), [1, 2, 3]).unwrap();
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(Vec::new()),
+            })
+            .tape_size(2)
+            .build()
+            .unwrap();
+
+        assert!(machine
+            .load_tape_from(file.path(), TapeFormat::Raw)
+            .is_err());
+        assert_eq!(machine.tape().to_vec(), [Byte::from(0), Byte::from(0)]);
+
+        machine
+            .load_tape_from(file.path().with_extension("missing"), TapeFormat::Raw)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_load_data_past_the_tape() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(directory.path().join("data.bin"), [1, 2, 3]).unwrap();
+        let source = crate::Preprocessor::new()
+            .base_dir(directory.path())
+            .process_str("@data \"data.bin\" -> cells 0..\n@data \"data.bin\" -> cells 2..")
+            .unwrap();
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(Vec::new()),
+            })
+            .tape_size(4)
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            machine.load_data(source.data_segments()),
+            Err(VMError::CellOutOfRange {
+                cell:   4,
+                length: 4,
+            })
+        ));
+        assert_eq!(machine.tape().to_vec(), [Byte::from(0); 4]);
+
+        machine.load_data(&source.data_segments()[..1]).unwrap();
+        assert_eq!(
+            machine.tape().to_vec(),
+            [Byte::from(1), Byte::from(2), Byte::from(3), Byte::from(0)]
+        );
+    }
+
+    #[test]
+    fn test_set_speed() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(Vec::new()),
+            })
+            .program(Program::from("+++"))
+            .build()
+            .unwrap();
+
+        assert_eq!(machine.speed(), None);
+        machine.set_speed(1_000_000);
+        assert_eq!(machine.speed(), Some(1_000_000));
+        machine.run().unwrap();
+        assert_eq!(machine.tape()[0], Byte::from(3));
+
+        machine.set_speed(0);
+        assert_eq!(machine.speed(), None);
+    }
+
+    #[test]
+    fn test_find_in_tape() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(Vec::new()),
+            })
+            .program(Program::from("+>+>+>++"))
+            .tape_size(5)
+            .build()
+            .unwrap();
+        machine.run().unwrap();
+
+        assert_eq!(machine.find_in_tape(&[1, 1]), [0, 1]);
+        assert_eq!(machine.find_in_tape(&[2, 0]), [3]);
+        assert!(machine.find_in_tape(&[3]).is_empty());
+        assert!(machine.find_in_tape(&[]).is_empty());
+        assert!(machine.find_in_tape(&[1; 6]).is_empty());
+    }
+
+    #[test]
+    fn test_numeric_io() {
         let input_device = MockReader {
-            data: Cursor::new(data),
+            data: Cursor::new("7 100".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .program(Program::from(",.>,.>+++,."))
+            .io_mode(IoMode::Numeric)
+            .eof_behavior(EofBehavior::Zero)
+            .build()
+            .unwrap();
+        machine.run().unwrap();
+
+        assert_eq!(machine.io_mode(), IoMode::Numeric);
+        assert_eq!(machine.output_device().data, b"7\n100\n0\n");
+        assert_eq!(machine.bytes_written(), 8);
+
+        let input_device = MockReader {
+            data: Cursor::new("seven".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::from("+,"))
+            .io_mode(IoMode::Numeric)
+            .build()
+            .unwrap();
+
+        assert!(machine.run().is_err());
+        assert_eq!(machine.program_counter(), 1);
+    }
+
+    fn run_with_profile(source: &str, profile: BehaviorProfile) -> VirtualMachine<MockReader> {
+        let input_device = MockReader {
+            data: Cursor::new(Vec::new()),
         };
         let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .program(Program::from(source))
+            .behavior_profile(profile)
+            .tape_size(3)
             .build()
             .unwrap();
+        let _ = machine.run();
+        machine
+    }
+
+    #[test]
+    fn test_tape_edge_wrap() {
+        let machine = run_with_profile(">>>+<<-", BehaviorProfile::Classic30k);
+        assert_eq!(machine.tape_edge(), TapeEdge::Wrap);
+        assert_eq!(machine.memory_pointer(), 1);
+        assert_eq!(machine.tape[0], Byte::from(1));
+        assert_eq!(machine.tape[1], Byte::from(255));
+        assert_eq!(machine.length(), 3);
+    }
+
+    #[test]
+    fn test_tape_edge_grow() {
+        let mut machine = run_with_profile(">>>>+", BehaviorProfile::ModernDynamic);
+        assert_eq!(machine.length(), 5);
+        assert_eq!(machine.tape[4], Byte::from(1));
+
+        machine.extend_program(Program::from("<<<<<")).unwrap();
+        let error = machine.run().unwrap_err();
+        assert!(matches!(
+            error,
+            VMError::ProfileViolation(ProfileViolation::PointerUnderflow { instruction: 9 })
+        ));
+        assert_eq!(machine.memory_pointer(), 0);
+        assert_eq!(machine.program_counter(), 9);
+    }
 
-        machine.input_value();
+    #[test]
+    fn test_growing_tape_is_compacted() {
+        let source = "+->".repeat(10 * PagedTape::PAGE_SIZE);
+        let mut machine = run_with_profile(&source, BehaviorProfile::ModernDynamic);
+        let page = PagedTape::PAGE_SIZE * std::mem::size_of::<Byte>();
 
+        // Every page was copied once, and freed again as the tape grew
+        assert_eq!(machine.length(), 10 * PagedTape::PAGE_SIZE + 1);
+        assert!(machine.tape_memory_usage() < 2 * page);
         assert_eq!(
-            machine.tape[0],
-            Byte::from(65),
-            "Value at memory pointer should be set to the input value"
+            machine.tape.to_vec(),
+            vec![Byte::default(); machine.length()]
         );
+        assert_eq!(machine.compact_tape(), 0);
     }
 
     #[test]
-    fn test_invalid_input_value() {
-        let data = vec![129]; // 129 is not a valid ASCII value
+    fn test_canary_cells() {
+        let machine = |source: &str| {
+            VirtualMachine::builder()
+                .input_device(MockReader {
+                    data: Cursor::new(Vec::new()),
+                })
+                .program(Program::from(source))
+                .tape_size(3)
+                .canary_cells(2)
+                .build()
+                .unwrap()
+        };
+
+        // Moving over the canaries and back is fine
+        let mut vm = machine(">>>><<+");
+        vm.run().unwrap();
+        assert_eq!(vm.memory_pointer(), 2);
+        assert_eq!(vm.tape[2], Byte::from(1));
+        assert_eq!(vm.tape[0], Byte::from(0));
+
+        let mut vm = machine(">>>+");
+        assert!(matches!(
+            vm.run(),
+            Err(VMError::CanaryTouched {
+                direction:   Direction::Right,
+                distance:    1,
+                instruction: 3,
+                step:        3,
+            })
+        ));
+
+        // Moving past the last canary touches it
+        let mut vm = machine("<<<");
+        assert!(matches!(
+            vm.run(),
+            Err(VMError::CanaryTouched {
+                direction:   Direction::Left,
+                distance:    2,
+                instruction: 2,
+                step:        2,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_strict_profile() {
         let input_device = MockReader {
-            data: Cursor::new(data),
+            data: Cursor::new(Vec::new()),
         };
         let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .program(Program::from("+>>>"))
+            .behavior_profile(BehaviorProfile::Strict)
+            .tape_size(3)
             .build()
             .unwrap();
+        let error = machine.run().unwrap_err();
+        assert!(matches!(
+            error,
+            VMError::ProfileViolation(ProfileViolation::PointerOverflow { instruction: 3 })
+        ));
+        assert_eq!(machine.memory_pointer(), 2);
+        assert!(!machine.wraps_cells());
 
-        machine.input_value();
+        let machine = run_with_profile("--", BehaviorProfile::Strict);
+        assert_eq!(machine.tape[0], Byte::from(0));
+        assert_eq!(machine.program_counter(), 0);
+        assert_eq!(machine.steps(), 0);
+    }
 
-        assert_eq!(
-            machine.tape[0],
-            Byte::from(0),
-            "Value at memory pointer should not be set to the input value"
-        );
+    #[test]
+    fn test_loop_iteration_limit() {
+        let input_device = MockReader {
+            data: Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut machine = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::from("+++[>+++[-]<-]+[]"))
+            .limits(Limits::new().max_loop_iterations(3))
+            .build()
+            .unwrap();
+
+        let error = machine.run().unwrap_err();
+        assert!(matches!(
+            error,
+            VMError::LimitExceeded(LimitExceeded::LoopIterations {
+                entry: 15,
+                limit: 3,
+            })
+        ));
+        assert_eq!(machine.tape[0], Byte::from(1));
+        assert_eq!(machine.program_counter(), 16);
     }
 
     #[test]
-    #[should_panic(expected = "not yet implemented")]
-    fn test_jump_forward() {
+    fn test_restore_inside_loop() {
+        let build = || {
+            VirtualMachine::builder()
+                .input_device(MockReader {
+                    data: Cursor::new(Vec::new()),
+                })
+                .program(Program::from("+[]"))
+                .limits(Limits::new().max_loop_iterations(3).max_steps(100))
+                .build()
+                .unwrap()
+        };
+        let mut machine = build();
+        // `+[]` enters the endless loop and jumps back once
+        for _ in 0..3 {
+            machine.execute_instruction().unwrap();
+        }
+        assert_eq!(machine.loop_stack(), &[LoopFrame::new(1, 2)]);
+
+        let mut restored = build();
+        restored.restore(&machine.snapshot()).unwrap();
+        assert_eq!(restored.loop_stack(), machine.loop_stack());
+        // The iterations before the snapshot still count towards the limit
+        let error = restored.run().unwrap_err();
+        assert!(matches!(
+            error,
+            VMError::LimitExceeded(LimitExceeded::LoopIterations { entry: 1, limit: 3 })
+        ));
+        let mut uninterrupted = build();
+        assert!(uninterrupted.run().is_err());
+        assert_eq!(restored.snapshot(), uninterrupted.snapshot());
+    }
+
+    #[test]
+    fn test_heatmap() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
-        let machine = VirtualMachine::builder()
+        let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .program(Program::from(",.>+[-]"))
+            .tape_size(3)
+            .profiling(true)
             .build()
             .unwrap();
-        machine.jump_forward();
+
+        assert!(machine.is_profiling());
+        machine.run().unwrap();
+        let heatmap = machine.heatmap();
+        assert_eq!((heatmap[0].reads(), heatmap[0].writes()), (1, 1));
+        assert_eq!((heatmap[1].reads(), heatmap[1].writes()), (4, 2));
+        assert_eq!(heatmap[2], CellStats::default());
     }
 
     #[test]
-    #[should_panic(expected = "not yet implemented")]
-    fn test_jump_backward() {
+    fn test_heatmap_disabled() {
         let input_device = MockReader {
             data: Cursor::new("A".as_bytes().to_vec()),
         };
-        let machine = VirtualMachine::builder()
+        let mut machine = VirtualMachine::builder()
             .input_device(input_device)
+            .program(Program::from("+++"))
+            .build()
+            .unwrap();
+
+        machine.run().unwrap();
+        assert!(!machine.is_profiling());
+        assert!(machine.heatmap().is_empty());
+    }
+
+    fn run_pbrain(source: &str, limits: Limits) -> (Result<StopReason, VMError>, Vec<u8>) {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader::default())
+            .output_device(CaptureWriter::default())
+            .program(Program::parse(source, Dialect::Pbrain))
+            .limits(limits)
             .build()
             .unwrap();
-        machine.jump_backward();
+        let result = machine.run();
+        (result, machine.output_device().data.clone())
+    }
+
+    #[test]
+    fn test_procedure_call() {
+        let (result, output) = run_pbrain("+++(.):", Limits::new());
+
+        assert_eq!(result.unwrap(), StopReason::Halted);
+        assert_eq!(output, vec![3]);
+    }
+
+    #[test]
+    fn test_nested_procedure_calls() {
+        // Procedure 1 adds two to the next cell, procedure 2 calls it twice
+        let (result, output) = run_pbrain("+(>++<)+(-::+):>.", Limits::new());
+
+        assert_eq!(result.unwrap(), StopReason::Halted);
+        assert_eq!(output, vec![4]);
+    }
+
+    #[test]
+    fn test_recursive_procedure() {
+        // Procedure 1 counts down the next cell and calls itself until it is zero
+        let (result, output) = run_pbrain("+(>-.[<:>]<)>+++<:", Limits::new());
+
+        assert_eq!(result.unwrap(), StopReason::Halted);
+        assert_eq!(output, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        let (result, _) = run_pbrain("+(>-[<:>]<)>+++<:", Limits::new().max_call_depth(2));
+        assert!(matches!(
+            result.unwrap_err(),
+            VMError::LimitExceeded(LimitExceeded::CallDepth { call: 6, limit: 2 })
+        ));
+
+        let (result, _) = run_pbrain("+(>-[<:>]<)>+++<:", Limits::new().max_call_depth(3));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_undefined_procedure() {
+        let (result, _) = run_pbrain("+(.)+:", Limits::new());
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Procedure 2 called at instruction 5 is not defined"
+        );
+    }
+
+    #[test]
+    fn test_halt() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader::default())
+            .program(Program::parse("+@+", Dialect::Pbrain))
+            .build()
+            .unwrap();
+
+        assert_eq!(machine.run().unwrap(), StopReason::Halted);
+        assert!(machine.is_halted());
+        assert_eq!(machine.tape[0], Byte::from(1));
+    }
+
+    #[test]
+    fn test_procedures_in_standard_dialect() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader::default())
+            .program(Program::from("+(+):@+"))
+            .build()
+            .unwrap();
+
+        machine.run().unwrap();
+        assert_eq!(machine.tape[0], Byte::from(3));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_run_async() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader::default())
+            .program(Program::from(",[.,]"))
+            .eof_behavior(EofBehavior::Zero)
+            .build()
+            .unwrap();
+
+        let mut input: &[u8] = b"echo";
+        let mut output = Vec::new();
+        machine.run_async(&mut input, &mut output).await.unwrap();
+
+        assert_eq!(output, b"echo");
+        assert_eq!(machine.bytes_written(), 4);
+        assert!(machine.is_halted());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_run_async_numeric() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader::default())
+            .program(Program::from(",>,[<+>-]<."))
+            .io_mode(IoMode::Numeric)
+            .build()
+            .unwrap();
+
+        let mut input: &[u8] = b"40 2";
+        let mut output = Vec::new();
+        machine.run_async(&mut input, &mut output).await.unwrap();
+
+        assert_eq!(output, b"42\n");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_run_async_taint() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader::default())
+            .program(Program::from(",>,[-<+>]<.,."))
+            .taint_tracking(true)
+            .build()
+            .unwrap();
+
+        let mut input: &[u8] = &[1, 2, 3];
+        let mut output = Vec::new();
+        machine.run_async(&mut input, &mut output).await.unwrap();
+
+        let taint = machine.taint().unwrap();
+        assert_eq!(output, [3, 3]);
+        assert_eq!(taint.inputs_read(), 3);
+        assert_eq!(taint.outputs()[0].sources, [0, 1]);
+        assert_eq!(taint.outputs()[1].sources, [2]);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_run_async_limits() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader::default())
+            .program(Program::from("+[.]"))
+            .limits(Limits::new().max_output_bytes(3))
+            .build()
+            .unwrap();
+
+        let mut input: &[u8] = b"";
+        let mut output = Vec::new();
+        let result = machine.run_async(&mut input, &mut output).await;
+
+        assert!(matches!(
+            result,
+            Err(VMError::LimitExceeded(LimitExceeded::OutputBytes {
+                limit: 3,
+                ..
+            }))
+        ));
+        assert_eq!(output, [1, 1, 1]);
     }
 }
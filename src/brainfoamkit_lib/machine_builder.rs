@@ -3,20 +3,50 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use anyhow::Result;
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::Stdout,
+};
+
+use rand_core::RngCore;
 
+#[cfg(feature = "mmap")]
+use crate::MmapTape;
 use crate::{
+    debug_dump,
     vm_reader::VMReader,
+    vm_writer::VMWriter,
+    AuxiliaryTape,
+    BehaviorProfile,
+    BuildError,
+    Byte,
+    DebugDump,
+    DebugHook,
+    DiagnosticsSink,
+    Dialect,
+    DualTapeVM,
+    EofBehavior,
+    HostCall,
+    InstructionHandler,
+    IoMode,
+    Limits,
+    MemoryMap,
     Program,
+    ProgressReporter,
+    RandomByte,
+    TapeEdge,
     VirtualMachine,
+    WarningPolicy,
 };
 
 /// `VirtualMachineBuilder` is a builder for the `VirtualMachine` struct.
 ///
-/// This builder allows you to set the `program` and `tape_size` for a
-/// `VirtualMachine` before building it. Both `program` and `tape_size` are
-/// optional. If they're not provided, the `VirtualMachine` will be initialized
-/// with default values.
+/// This builder allows you to set the `program`, `tape_size` and the input and
+/// output devices for a `VirtualMachine` before building it. Only the input
+/// device is required. If the other values are not provided, the
+/// `VirtualMachine` will be initialized with default values, writing its output
+/// to STDOUT.
 ///
 /// # Examples
 ///
@@ -35,11 +65,11 @@ use crate::{
 ///     .build()
 ///     .unwrap();
 /// ```
-#[derive(Default)]
 #[allow(clippy::module_name_repetitions)]
-pub struct VirtualMachineBuilder<R>
+pub struct VirtualMachineBuilder<R, W = Stdout>
 where
     R: VMReader,
+    W: VMWriter,
 {
     /// The program that the `VirtualMachine` will execute. If not provided,
     /// the `VirtualMachine` will be initialized with a default program.
@@ -53,17 +83,81 @@ where
     /// the `VirtualMachine` will be initialized with a STDIN as the input
     /// device.
     input_device: Option<R>,
+
+    /// The output device for the `VirtualMachine`. Defaults to STDOUT.
+    output_device: W,
+
+    /// Whether the `VirtualMachine` should pause before executing an
+    /// `OutputValue` instruction.
+    break_on_output: bool,
+
+    /// Whether the `VirtualMachine` should pause before executing an
+    /// `InputValue` instruction.
+    break_on_input: bool,
+
+    /// The number of executed instructions the `VirtualMachine` keeps in its
+    /// `History`. Defaults to zero, which disables recording.
+    history_capacity: usize,
+
+    /// The resource limits of the `VirtualMachine`. Every limit is disabled by
+    /// default.
+    limits: Limits,
+
+    /// Whether the `VirtualMachine` counts reads and writes of every cell.
+    profiling: bool,
+
+    /// Whether the `VirtualMachine` follows which input bytes influence which
+    /// output bytes.
+    taint_tracking: bool,
+
+    /// The callback for every `#` instruction. Defaults to printing the dump
+    /// to STDERR.
+    debug_hook: DebugHook,
+
+    /// What `,` does when there is no more input. Defaults to leaving the
+    /// cell unchanged.
+    eof_behavior: EofBehavior,
+
+    tape_edge: TapeEdge,
+
+    wrap_cells: bool,
+
+    /// Whether `,` and `.` exchange bytes or decimal numbers. Defaults to
+    /// bytes.
+    io_mode: IoMode,
+
+    /// The handlers of the custom instructions, by character.
+    extensions: HashMap<char, Box<dyn InstructionHandler>>,
+
+    /// Where the warnings of the `VirtualMachine` go. Defaults to ignoring
+    /// them.
+    diagnostics_sink: Box<dyn DiagnosticsSink>,
+
+    /// The permissions of regions of the tape, if any.
+    memory_map: Option<MemoryMap>,
+
+    /// The number of canary cells on either side of the tape. Defaults to
+    /// none.
+    canary_cells: usize,
+
+    /// Where the progress of a run goes, and how many steps go by between
+    /// two reports, if anywhere.
+    progress_reporter: Option<(usize, Box<dyn ProgressReporter>)>,
+
+    /// The file the tape is stored in, if any.
+    #[cfg(feature = "mmap")]
+    mmap_tape: Option<MmapTape>,
 }
 
-impl<R> VirtualMachineBuilder<R>
+impl<R> VirtualMachineBuilder<R, Stdout>
 where
     R: VMReader,
 {
     /// Creates a new `VirtualMachineBuilder` with empty values.
     ///
     /// This function returns a new `VirtualMachineBuilder` with `program` and
-    /// `tape_size` set to `None`. These values can be set later using the
-    /// builder's methods.
+    /// `tape_size` set to `None` and STDOUT as the output device. These values
+    /// can be set later using the builder's methods.
     ///
     /// # Returns
     ///
@@ -82,14 +176,48 @@ where
     /// let builder = VirtualMachineBuilder::<Stdin>::new();
     /// ```
     #[must_use]
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
-            program:      None,
-            tape_size:    None,
+            program: None,
+            tape_size: None,
             input_device: None,
+            output_device: std::io::stdout(),
+            break_on_output: false,
+            break_on_input: false,
+            history_capacity: 0,
+            limits: Limits::new(),
+            profiling: false,
+            taint_tracking: false,
+            debug_hook: debug_dump::print_to_stderr(),
+            eof_behavior: EofBehavior::Unchanged,
+            tape_edge: TapeEdge::Wrap,
+            wrap_cells: true,
+            io_mode: IoMode::Bytes,
+            extensions: HashMap::new(),
+            diagnostics_sink: Box::new(WarningPolicy::Ignore),
+            memory_map: None,
+            canary_cells: 0,
+            progress_reporter: None,
+            #[cfg(feature = "mmap")]
+            mmap_tape: None,
         }
     }
+}
+
+impl<R> Default for VirtualMachineBuilder<R, Stdout>
+where
+    R: VMReader,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+impl<R, W> VirtualMachineBuilder<R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
     /// Set the program to be run by the virtual machine.
     ///
     /// # Arguments
@@ -197,89 +325,1107 @@ where
         self
     }
 
-    /// Build the virtual machine.
+    /// Set the output device to be used by the virtual machine.
+    ///
+    /// The default output device is `stdout`.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_device` - The output device to be used by the virtual machine.
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the output device set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     VMWriter,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    ///
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(input_device)
+    ///     .output_device(CaptureWriter::default())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     vm.output_device().get_vmwriter_type(),
+    ///     brainfoamkit_lib::VMWriterType::Capture
+    /// );
+    /// ```
+    #[must_use]
+    pub fn output_device<V: VMWriter>(self, output_device: V) -> VirtualMachineBuilder<R, V> {
+        VirtualMachineBuilder {
+            program: self.program,
+            tape_size: self.tape_size,
+            input_device: self.input_device,
+            output_device,
+            break_on_output: self.break_on_output,
+            break_on_input: self.break_on_input,
+            history_capacity: self.history_capacity,
+            limits: self.limits,
+            profiling: self.profiling,
+            taint_tracking: self.taint_tracking,
+            debug_hook: self.debug_hook,
+            eof_behavior: self.eof_behavior,
+            tape_edge: self.tape_edge,
+            wrap_cells: self.wrap_cells,
+            io_mode: self.io_mode,
+            extensions: self.extensions,
+            diagnostics_sink: self.diagnostics_sink,
+            memory_map: self.memory_map,
+            canary_cells: self.canary_cells,
+            progress_reporter: self.progress_reporter,
+            #[cfg(feature = "mmap")]
+            mmap_tape: self.mmap_tape,
+        }
+    }
+
+    /// Pause the virtual machine right before an `OutputValue` instruction.
+    ///
+    /// When enabled, [`run()`](struct.VirtualMachine.html#method.run) returns
+    /// control to the caller with the value that is about to be written, before
+    /// the instruction executes. Calling `run()` again writes the value and
+    /// continues.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to break on output
     ///
     /// # Returns
     ///
-    /// * A `Result` containing either a `VirtualMachine` or an `Error`.
+    /// * Builder by value with the option set.
     ///
     /// # Examples
     ///
     /// ```
     /// use brainfoamkit_lib::{
+    ///     Byte,
+    ///     CaptureWriter,
     ///     Program,
-    ///     VMReader,
+    ///     StopReason,
     ///     VirtualMachineBuilder,
     /// };
     ///
     /// let input_device = std::io::stdin();
-    /// let program = Program::from("++++++[>++++++++++<-]>+++++.");
-    /// let vm = VirtualMachineBuilder::new()
-    ///     .program(program)
-    ///     .tape_size(100)
+    /// let mut vm = VirtualMachineBuilder::new()
     ///     .input_device(input_device)
-    ///     .build();
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("+++."))
+    ///     .break_on_output(true)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(vm.run().unwrap(), StopReason::OutputPending(Byte::from(3)));
+    /// assert_eq!(vm.run().unwrap(), StopReason::Halted);
+    /// assert_eq!(vm.output_device().data, vec![3]);
     /// ```
+    #[must_use]
+    pub const fn break_on_output(mut self, enabled: bool) -> Self {
+        self.break_on_output = enabled;
+        self
+    }
+
+    /// Pause the virtual machine right before an `InputValue` instruction.
     ///
-    /// # Errors
+    /// When enabled, [`run()`](struct.VirtualMachine.html#method.run) returns
+    /// control to the caller before the input is read. Calling `run()` again
+    /// reads the input and continues.
     ///
-    /// * If the input device is not set, this function will return an error.
-    pub fn build(self) -> Result<VirtualMachine<R>> {
-        let program = self.program.unwrap_or_default();
-        let tape_size = self.tape_size.unwrap_or(30000);
-        let Some(input_device) = self.input_device else {
-            return Err(anyhow::anyhow!("Input device not set."));
-        };
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to break on input
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the option set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     MockReader,
+    ///     Program,
+    ///     StopReason,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let input_device = MockReader {
+    ///     data: std::io::Cursor::new(b"A".to_vec()),
+    /// };
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(input_device)
+    ///     .program(Program::from(">,"))
+    ///     .break_on_input(true)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(vm.run().unwrap(), StopReason::InputPending);
+    /// assert_eq!(vm.program_counter(), 1);
+    /// assert_eq!(vm.run().unwrap(), StopReason::Halted);
+    /// ```
+    #[must_use]
+    pub const fn break_on_input(mut self, enabled: bool) -> Self {
+        self.break_on_input = enabled;
+        self
+    }
 
-        Ok(VirtualMachine::new(tape_size, program, 0, 0, input_device))
+    /// Keep the last `capacity` executed instructions in the `History`.
+    ///
+    /// The default capacity is zero, which disables recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of instructions to keep
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the capacity set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(input_device)
+    ///     .program(Program::from("+++++"))
+    ///     .history_capacity(3)
+    ///     .build()
+    ///     .unwrap();
+    /// vm.run().unwrap();
+    ///
+    /// assert_eq!(vm.history().len(), 3);
+    /// assert_eq!(vm.history().capacity(), 3);
+    /// ```
+    #[must_use]
+    pub const fn history_capacity(mut self, capacity: usize) -> Self {
+        self.history_capacity = capacity;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::vm_reader::{
-        MockReader,
-        VMReaderType,
-    };
+    /// Set the resource limits of the virtual machine.
+    ///
+    /// By default, the virtual machine runs without any limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The limits to enforce
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the limits set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Limits,
+    ///     Program,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(input_device)
+    ///     .program(Program::from("+[]"))
+    ///     .limits(Limits::new().max_loop_iterations(10))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(vm.run().is_err());
+    /// ```
+    #[must_use]
+    pub const fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
 
-    #[test]
-    fn test_program() {
-        let program = Program::from("++++++[>++++++++++<-]>+++++.");
-        let input_device = MockReader {
-            data: std::io::Cursor::new("A".as_bytes().to_vec()),
-        };
-        let vm = VirtualMachine::builder()
-            .input_device(input_device)
-            .program(program)
-            .build()
-            .unwrap();
-        assert_eq!(vm.program(), Program::from("++++++[>++++++++++<-]>+++++."));
+    /// Count reads and writes of every cell of the tape.
+    ///
+    /// Profiling is disabled by default since it adds work to every
+    /// instruction. The counts are available through
+    /// [`heatmap()`](struct.VirtualMachine.html#method.heatmap).
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to count cell accesses
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the option set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(input_device)
+    ///     .program(Program::from("++"))
+    ///     .tape_size(10)
+    ///     .profiling(true)
+    ///     .build()
+    ///     .unwrap();
+    /// vm.run().unwrap();
+    ///
+    /// assert_eq!(vm.heatmap()[0].writes(), 2);
+    /// ```
+    #[must_use]
+    pub const fn profiling(mut self, enabled: bool) -> Self {
+        self.profiling = enabled;
+        self
     }
 
-    #[test]
-    fn test_tape_size() {
-        let input_device = MockReader {
-            data: std::io::Cursor::new("A".as_bytes().to_vec()),
-        };
-        let vm = VirtualMachine::builder()
-            .input_device(input_device)
-            .tape_size(100)
-            .build()
-            .unwrap();
-        assert_eq!(vm.tape_size(), 100);
+    /// Follow which input bytes influence which output bytes.
+    ///
+    /// Taint tracking is disabled by default since it adds work to every
+    /// instruction. The sources of every output byte are available through
+    /// [`taint()`](struct.VirtualMachine.html#method.taint), see
+    /// [`TaintTracker`](crate::TaintTracker) for how they are found.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to track taint
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the option set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     Program,
+    ///     VecReader,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(VecReader::new(b"ab".to_vec()))
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from(",>,."))
+    ///     .taint_tracking(true)
+    ///     .build()
+    ///     .unwrap();
+    /// vm.run().unwrap();
+    ///
+    /// assert_eq!(vm.taint().unwrap().outputs()[0].sources, [1]);
+    /// ```
+    #[must_use]
+    pub const fn taint_tracking(mut self, enabled: bool) -> Self {
+        self.taint_tracking = enabled;
+        self
     }
 
-    #[test]
-    fn test_input_device() {
-        let input_device = MockReader {
-            data: std::io::Cursor::new("A".as_bytes().to_vec()),
-        };
-        let mut vm = VirtualMachine::builder()
-            .input_device(input_device)
-            .build()
-            .unwrap();
-        assert_eq!(vm.input_device().get_vmreader_type(), VMReaderType::Mock);
+    /// Set what `,` does when there is no more input.
+    ///
+    /// # Arguments
+    ///
+    /// * `behavior` - Whether to leave the cell unchanged, or set it to zero or
+    ///   255
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the option set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     EofBehavior,
+    ///     Program,
+    ///     VecReader,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(VecReader::new(Vec::new()))
+    ///     .program(Program::from("+,"))
+    ///     .eof_behavior(EofBehavior::Zero)
+    ///     .build()
+    ///     .unwrap();
+    /// vm.run().unwrap();
+    ///
+    /// assert_eq!(vm.tape()[0], 0.into());
+    /// ```
+    #[must_use]
+    pub const fn eof_behavior(mut self, behavior: EofBehavior) -> Self {
+        self.eof_behavior = behavior;
+        self
+    }
+
+    /// Set whether `,` and `.` exchange bytes or decimal numbers.
+    ///
+    /// In [`IoMode::Numeric`], `,` reads a decimal number from 0 to 255 and
+    /// `.` writes the value of the cell as a decimal number followed by a
+    /// newline.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The I/O mode
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the option set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     IoMode,
+    ///     Program,
+    ///     VecReader,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(VecReader::new(b"200".to_vec()))
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from(",+."))
+    ///     .io_mode(IoMode::Numeric)
+    ///     .build()
+    ///     .unwrap();
+    /// vm.run().unwrap();
+    ///
+    /// assert_eq!(vm.output_device().data, b"201\n");
+    /// ```
+    #[must_use]
+    pub const fn io_mode(mut self, mode: IoMode) -> Self {
+        self.io_mode = mode;
+        self
+    }
+
+    /// Apply the settings of a `BehaviorProfile`.
+    ///
+    /// This sets the tape size, what happens at the ends of the tape, whether
+    /// cells wrap around and what `,` does at the end of the input. Calling
+    /// [`tape_size`](#method.tape_size) or
+    /// [`eof_behavior`](#method.eof_behavior) after this method overrides the
+    /// setting of the profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The profile to apply
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the settings of the profile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     BehaviorProfile,
+    ///     Program,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from(">>>+"))
+    ///     .behavior_profile(BehaviorProfile::ModernDynamic)
+    ///     .tape_size(2)
+    ///     .build()
+    ///     .unwrap();
+    /// vm.run().unwrap();
+    ///
+    /// assert_eq!(vm.length(), 4);
+    /// assert_eq!(vm.tape()[3], 1.into());
+    /// ```
+    #[must_use]
+    pub const fn behavior_profile(mut self, profile: BehaviorProfile) -> Self {
+        self.tape_size = Some(profile.tape_size());
+        self.tape_edge = profile.tape_edge();
+        self.wrap_cells = profile.wraps_cells();
+        self.eof_behavior = profile.eof_behavior();
+        self
+    }
+
+    /// Set the callback invoked for every `#` instruction.
+    ///
+    /// The `#` instruction only exists in programs parsed in the
+    /// [`Debug`](enum.Dialect.html#variant.Debug) dialect. By default, the
+    /// virtual machine prints a window of the tape around the memory pointer
+    /// to STDERR.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The callback, which receives a [`DebugDump`] of the machine
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the hook set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{
+    ///     atomic::{
+    ///         AtomicUsize,
+    ///         Ordering,
+    ///     },
+    ///     Arc,
+    /// };
+    ///
+    /// use brainfoamkit_lib::{
+    ///     Dialect,
+    ///     Program,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let pointers = Arc::new(AtomicUsize::new(0));
+    /// let recorder = Arc::clone(&pointers);
+    ///
+    /// let input_device = std::io::stdin();
+    /// let mut vm = VirtualMachineBuilder::new()
+    ///     .input_device(input_device)
+    ///     .program(Program::parse(">>#", Dialect::Debug))
+    ///     .debug_hook(move |dump| {
+    ///         recorder.store(dump.memory_pointer(), Ordering::Relaxed)
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// vm.run().unwrap();
+    ///
+    /// assert_eq!(pointers.load(Ordering::Relaxed), 2);
+    /// ```
+    #[must_use]
+    pub fn debug_hook<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&DebugDump) + Send + 'static,
+    {
+        self.debug_hook = Box::new(hook);
+        self
+    }
+
+    /// Register the handler of a custom instruction.
+    ///
+    /// The handler runs for every
+    /// [`Instruction::Extension`](enum.Instruction.html#variant.Extension)
+    /// with the given character, see [`InstructionHandler`]. Registering a
+    /// second handler for the same character replaces the first. Running an
+    /// extension instruction that has no handler stops the machine with a
+    /// [`VMError::UnknownExtension`](enum.VMError.html#variant.
+    /// UnknownExtension).
+    ///
+    /// # Arguments
+    ///
+    /// * `character` - The character of the instruction
+    /// * `handler` - The handler that executes it
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the handler registered.
+    #[must_use]
+    pub fn instruction_handler<H>(mut self, character: char, handler: H) -> Self
+    where
+        H: InstructionHandler + 'static,
+    {
+        self.extensions.insert(character, Box::new(handler));
+        self
+    }
+
+    /// Set where the warnings of the virtual machine go.
+    ///
+    /// A warning is something unusual the machine does without stopping, like
+    /// the memory pointer wrapping around the tape or an input byte outside of
+    /// the ASCII range ending the input. Warnings are ignored by default. See
+    /// [`DiagnosticsSink`] and [`WarningPolicy`].
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The sink that receives the warnings
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the sink set.
+    #[must_use]
+    pub fn diagnostics_sink<S>(mut self, sink: S) -> Self
+    where
+        S: DiagnosticsSink + 'static,
+    {
+        self.diagnostics_sink = Box::new(sink);
+        self
+    }
+
+    /// Protect regions of the tape.
+    ///
+    /// An instruction that reads or writes a cell its region does not allow
+    /// stops the machine with a
+    /// [`VMError::AccessViolation`](enum.VMError.html#variant.AccessViolation),
+    /// see [`MemoryMap`].
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_map` - The permissions of the regions
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the memory map set.
+    #[must_use]
+    pub fn memory_map(mut self, memory_map: MemoryMap) -> Self {
+        self.memory_map = Some(memory_map);
+        self
+    }
+
+    /// Surround the tape with canary cells.
+    ///
+    /// With `count` canary cells on either side, the memory pointer moves up
+    /// to `count` cells past an end of the tape instead of wrapping around or
+    /// growing the tape. An instruction that reads or writes the cell there
+    /// stops the machine with a
+    /// [`VMError::CanaryTouched`](enum.VMError.html#variant.CanaryTouched)
+    /// naming the instruction, the end of the tape and the step. This catches
+    /// scans that run off the tape by a cell or two, which a wrapping tape
+    /// hides. Moving past the last canary cell counts as touching it.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of canary cells on either side, 0 for none
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     Direction,
+    ///     Program,
+    ///     VMError,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .output_device(CaptureWriter::default())
+    ///     .program(Program::from("+>+[<]"))
+    ///     .canary_cells(4)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(matches!(
+    ///     machine.run(),
+    ///     Err(VMError::CanaryTouched {
+    ///         direction:   Direction::Left,
+    ///         distance:    1,
+    ///         instruction: 5,
+    ///         step:        7,
+    ///     })
+    /// ));
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the number of canary cells set.
+    #[must_use]
+    pub const fn canary_cells(mut self, count: usize) -> Self {
+        self.canary_cells = count;
+        self
+    }
+
+    /// Report the progress of long runs.
+    ///
+    /// The reporter receives the [`Progress`](crate::Progress) of the run
+    /// after every `every` steps, and once more when the program halts. See
+    /// [`ProgressReporter`].
+    ///
+    /// # Arguments
+    ///
+    /// * `every` - The number of steps between two reports, at least one
+    /// * `reporter` - The reporter that receives the progress
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the reporter set.
+    #[must_use]
+    pub fn progress_reporter<P>(mut self, every: usize, reporter: P) -> Self
+    where
+        P: ProgressReporter + 'static,
+    {
+        self.progress_reporter = Some((every, Box::new(reporter)));
+        self
+    }
+
+    /// Store the tape in a memory-mapped file.
+    ///
+    /// The machine starts with the cells of the file and as many cells as
+    /// the file has, replacing any [`tape_size`](#method.tape_size). It reads
+    /// and writes its cells in the file, so the tape can be larger than the
+    /// memory of the computer, and a tape that grows grows the file.
+    /// [`VirtualMachine::flush_tape`](struct.VirtualMachine.html#method.
+    /// flush_tape) waits until the changes are on disk. Only available with
+    /// the `mmap` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `tape` - The mapped file
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the tape file set.
+    #[cfg(feature = "mmap")]
+    #[must_use]
+    pub fn mmap_tape(mut self, tape: MmapTape) -> Self {
+        self.mmap_tape = Some(tape);
+        self
+    }
+
+    /// Register a function of the host program as a custom instruction.
+    ///
+    /// The function receives the cells from the memory pointer to the end of
+    /// the tape, see [`HostCall`].
+    ///
+    /// # Arguments
+    ///
+    /// * `character` - The character of the instruction
+    /// * `function` - The function the instruction calls
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the function registered.
+    #[must_use]
+    pub fn host_call<F>(self, character: char, function: F) -> Self
+    where
+        F: FnMut(&mut [Byte]) -> Result<(), Box<dyn Error + Send + Sync>> + Send + 'static,
+    {
+        self.instruction_handler(character, HostCall::new(function))
+    }
+
+    /// Give the virtual machine a second tape for the
+    /// [`Stack`](enum.Dialect.html#variant.Stack) dialect.
+    ///
+    /// Registers the handlers of `@`, which pushes the current cell onto
+    /// `tape`, and `$`, which pops it back. Keep a clone of `tape` to look at
+    /// the stack while the machine runs, or use
+    /// [`build_dual_tape`](#method.build_dual_tape) instead. Without one, a
+    /// program parsed in the `Stack` dialect gets an empty auxiliary tape of
+    /// its own when the machine is built.
+    ///
+    /// # Arguments
+    ///
+    /// * `tape` - The auxiliary tape
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with the handlers registered.
+    #[must_use]
+    pub fn auxiliary_tape(self, tape: AuxiliaryTape) -> Self {
+        self.instruction_handler('@', tape.push_handler())
+            .instruction_handler('$', tape.pop_handler())
+    }
+
+    /// Seed the random bytes of the `?` instruction.
+    ///
+    /// The `?` instruction only exists in programs parsed in the
+    /// [`Random`](enum.Dialect.html#variant.Random) dialect. With the same
+    /// seed, it writes the same bytes on every run. Without one, the bytes
+    /// are seeded from the clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the random bytes
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with a [`RandomByte`] registered for `?`.
+    #[must_use]
+    pub fn random_seed(self, seed: u64) -> Self {
+        self.instruction_handler('?', RandomByte::new(seed))
+    }
+
+    /// Draw the random bytes of the `?` instruction from a generator of the
+    /// caller's choosing.
+    ///
+    /// Use this in place of [`random_seed`](Self::random_seed) to pick the
+    /// generator, such as one from the `rand` crates seeded with
+    /// [`SeedableRng::seed_from_u64`](rand_core::SeedableRng::seed_from_u64).
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The generator of the random bytes
+    ///
+    /// # Returns
+    ///
+    /// * Builder by value with a [`RandomByte`] over `rng` registered for
+    ///   `?`.
+    #[must_use]
+    pub fn random_source<G>(self, rng: G) -> Self
+    where
+        G: RngCore + Send + 'static,
+    {
+        self.instruction_handler('?', RandomByte::from_rng(rng))
+    }
+
+    /// Build a virtual machine with a second tape.
+    ///
+    /// The machine has an empty [`AuxiliaryTape`] for the `@` and `$`
+    /// instructions of the [`Stack`](enum.Dialect.html#variant.Stack)
+    /// dialect, see [`DualTapeVM`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`build`](#method.build).
+    pub fn build_dual_tape(self) -> Result<DualTapeVM<R, W>, BuildError> {
+        let tape = AuxiliaryTape::new();
+        let machine = self.auxiliary_tape(tape.clone()).build()?;
+        Ok(DualTapeVM::new(machine, tape))
+    }
+
+    /// Build the virtual machine.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result` containing either a `VirtualMachine` or a `BuildError`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     VMReader,
+    ///     VirtualMachineBuilder,
+    /// };
+    ///
+    /// let input_device = std::io::stdin();
+    /// let program = Program::from("++++++[>++++++++++<-]>+++++.");
+    /// let vm = VirtualMachineBuilder::new()
+    ///     .program(program)
+    ///     .tape_size(100)
+    ///     .input_device(input_device)
+    ///     .build();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * If the input device is not set, this function will return an error.
+    /// * If the brackets of the program are not balanced, this function will
+    ///   return an error.
+    pub fn build(self) -> Result<VirtualMachine<R, W>, BuildError> {
+        let program = self.program.unwrap_or_default();
+        let dialect = program.dialect();
+        let tape_size = self.tape_size.unwrap_or(30000);
+        let Some(input_device) = self.input_device else {
+            return Err(BuildError::MissingInputDevice);
+        };
+
+        let mut machine =
+            VirtualMachine::new(tape_size, program, 0, 0, input_device, self.output_device);
+        machine.set_break_on_output(self.break_on_output);
+        machine.set_break_on_input(self.break_on_input);
+        machine.set_history_capacity(self.history_capacity);
+        machine.set_limits(self.limits);
+        machine.set_profiling(self.profiling);
+        machine.set_taint_tracking(self.taint_tracking);
+        machine.set_debug_hook(self.debug_hook);
+        machine.set_eof_behavior(self.eof_behavior);
+        machine.set_tape_edge(self.tape_edge);
+        machine.set_wrap_cells(self.wrap_cells);
+        machine.set_io_mode(self.io_mode);
+        let mut extensions = self.extensions;
+        extensions
+            .entry('?')
+            .or_insert_with(|| Box::new(RandomByte::from_clock()));
+        if dialect == Dialect::Stack
+            && !extensions.contains_key(&'@')
+            && !extensions.contains_key(&'$')
+        {
+            let tape = AuxiliaryTape::new();
+            extensions.insert('@', Box::new(tape.push_handler()));
+            extensions.insert('$', Box::new(tape.pop_handler()));
+        }
+        machine.set_extensions(extensions);
+        machine.set_diagnostics_sink(self.diagnostics_sink);
+        machine.set_memory_map(self.memory_map);
+        machine.set_canary_cells(self.canary_cells);
+        machine.set_progress_reporter(self.progress_reporter);
+        #[cfg(feature = "mmap")]
+        if let Some(tape) = self.mmap_tape {
+            machine.set_mmap_tape(tape);
+        }
+        machine.rebuild_jump_table()?;
+
+        Ok(machine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        vm_reader::{
+            MockReader,
+            VMReaderType,
+        },
+        vm_writer::{
+            CaptureWriter,
+            VMWriterType,
+        },
+    };
+
+    #[test]
+    fn test_program() {
+        let program = Program::from("++++++[>++++++++++<-]>+++++.");
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(program)
+            .build()
+            .unwrap();
+        assert_eq!(vm.program(), Program::from("++++++[>++++++++++<-]>+++++."));
+    }
+
+    #[test]
+    fn test_stack_dialect_without_auxiliary_tape() {
+        let mut vm = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: std::io::Cursor::new(Vec::new()),
+            })
+            .output_device(CaptureWriter::default())
+            .program(Program::parse("++@[-]$.", Dialect::Stack))
+            .build()
+            .unwrap();
+        vm.run().unwrap();
+        assert_eq!(vm.output_device().data, [2]);
+
+        // Only the stack dialect gets an auxiliary tape
+        let mut vm = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: std::io::Cursor::new(Vec::new()),
+            })
+            .program(Program::parse_with_extensions(
+                "@",
+                Dialect::Standard,
+                &['@'],
+            ))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            vm.run(),
+            Err(crate::VMError::UnknownExtension { character: '@', .. })
+        ));
+    }
+
+    #[test]
+    fn test_random_source() {
+        // Counts up from zero in the high byte
+        struct Counter(u64);
+
+        impl RngCore for Counter {
+            fn next_u32(&mut self) -> u32 {
+                (self.next_u64() >> 32) as u32
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.0 += 1 << 56;
+                self.0
+            }
+
+            fn fill_bytes(&mut self, destination: &mut [u8]) {
+                rand_core::impls::fill_bytes_via_next(self, destination);
+            }
+        }
+
+        let mut vm = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: std::io::Cursor::new(Vec::new()),
+            })
+            .output_device(CaptureWriter::default())
+            .program(Program::parse("?.?.?.", Dialect::Random))
+            .random_source(Counter(0))
+            .build()
+            .unwrap();
+        vm.run().unwrap();
+        assert_eq!(vm.output_device().data, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_tape_size() {
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .tape_size(100)
+            .build()
+            .unwrap();
+        assert_eq!(vm.tape_size(), 100);
+    }
+
+    #[test]
+    fn test_input_device() {
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+        assert_eq!(vm.input_device().get_vmreader_type(), VMReaderType::Mock);
+    }
+
+    #[test]
+    fn test_output_device() {
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .build()
+            .unwrap();
+        assert_eq!(
+            vm.output_device().get_vmwriter_type(),
+            VMWriterType::Capture
+        );
+    }
+
+    #[test]
+    fn test_default_output_device() {
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let mut vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .build()
+            .unwrap();
+        assert_eq!(vm.output_device().get_vmwriter_type(), VMWriterType::Stdout);
+    }
+
+    #[test]
+    fn test_break_options() {
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let vm = VirtualMachine::builder()
+            .break_on_output(true)
+            .input_device(input_device)
+            .output_device(CaptureWriter::default())
+            .break_on_input(true)
+            .build()
+            .unwrap();
+        assert!(vm.breaks_on_output());
+        assert!(vm.breaks_on_input());
+    }
+
+    #[test]
+    fn test_history_capacity() {
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .history_capacity(16)
+            .build()
+            .unwrap();
+        assert_eq!(vm.history().capacity(), 16);
+        assert!(vm.history().is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let limits = Limits::new().max_steps(100).max_loop_iterations(10);
+        let vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .limits(limits)
+            .build()
+            .unwrap();
+        assert_eq!(vm.limits(), limits);
+    }
+
+    #[test]
+    fn test_profiling() {
+        let input_device = MockReader {
+            data: std::io::Cursor::new("A".as_bytes().to_vec()),
+        };
+        let vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .tape_size(10)
+            .profiling(true)
+            .build()
+            .unwrap();
+        assert!(vm.is_profiling());
+        assert_eq!(vm.heatmap().len(), 10);
+    }
+
+    #[test]
+    fn test_debug_hook() {
+        use std::sync::{
+            Arc,
+            Mutex,
+        };
+
+        use crate::Dialect;
+
+        let dumps = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&dumps);
+        let input_device = MockReader {
+            data: std::io::Cursor::new(Vec::new()),
+        };
+        let mut vm = VirtualMachine::builder()
+            .input_device(input_device)
+            .program(Program::parse("+#>++#", Dialect::Debug))
+            .tape_size(10)
+            .output_device(CaptureWriter::default())
+            .debug_hook(move |dump| {
+                recorder.lock().unwrap().push((
+                    dump.program_counter(),
+                    u8::from(&dump.tape()[dump.memory_pointer()]),
+                ));
+            })
+            .build()
+            .unwrap();
+        vm.run().unwrap();
+
+        assert_eq!(dumps.lock().unwrap().as_slice(), [(1, 1), (5, 2)]);
+    }
+
+    #[test]
+    fn test_progress_reporter() {
+        use std::sync::{
+            Arc,
+            Mutex,
+        };
+
+        use crate::Progress;
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&reports);
+        // Set before the output device, and with zero steps between reports
+        let mut vm = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: std::io::Cursor::new(Vec::new()),
+            })
+            .progress_reporter(0, move |progress: &Progress| {
+                recorder.lock().unwrap().push(progress.steps);
+            })
+            .output_device(CaptureWriter::default())
+            .program(Program::from("+[-]"))
+            .build()
+            .unwrap();
+        vm.run().unwrap();
+
+        assert_eq!(*reports.lock().unwrap(), [1, 2, 3, 4]);
     }
 
     #[test]
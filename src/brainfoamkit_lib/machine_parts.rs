@@ -0,0 +1,159 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::Byte;
+
+/// The memory of a `VirtualMachine`: its cells and the memory pointer
+///
+/// Returned by
+/// [`VirtualMachine::into_parts`](crate::VirtualMachine::into_parts)
+/// and accepted by
+/// [`VirtualMachine::from_parts`](crate::VirtualMachine::from_parts).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     TapeBackend,
+/// };
+///
+/// let tape = TapeBackend::new(vec![Byte::from(1); 4]).with_memory_pointer(2);
+///
+/// assert_eq!(tape.len(), 4);
+/// assert_eq!(tape.memory_pointer(), 2);
+/// assert_eq!(tape.cells()[2], Byte::from(1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TapeBackend {
+    #[serde(with = "cells")]
+    cells:          Vec<Byte>,
+    memory_pointer: usize,
+}
+
+impl TapeBackend {
+    /// Create a tape from its cells, with the memory pointer on the first cell
+    #[must_use]
+    pub const fn new(cells: Vec<Byte>) -> Self {
+        Self {
+            cells,
+            memory_pointer: 0,
+        }
+    }
+
+    /// Move the memory pointer to `memory_pointer`
+    ///
+    /// # Returns
+    ///
+    /// The `TapeBackend` by value with the memory pointer set.
+    #[must_use]
+    pub const fn with_memory_pointer(mut self, memory_pointer: usize) -> Self {
+        self.memory_pointer = memory_pointer;
+        self
+    }
+
+    /// The cells of the tape
+    #[must_use]
+    pub fn cells(&self) -> &[Byte] {
+        &self.cells
+    }
+
+    /// The position of the memory pointer
+    #[must_use]
+    pub const fn memory_pointer(&self) -> usize {
+        self.memory_pointer
+    }
+
+    /// The number of cells on the tape
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Whether the tape has no cells at all
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Take the cells out of the tape
+    #[must_use]
+    pub fn into_cells(self) -> Vec<Byte> {
+        self.cells
+    }
+}
+
+/// The input and output devices of a `VirtualMachine`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoDevices<R, W> {
+    /// The device the machine reads from
+    pub input:  R,
+    /// The device the machine writes to
+    pub output: W,
+}
+
+/// The progress of a `VirtualMachine` through its `Program`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Counters {
+    /// The position of the next instruction to execute
+    pub program_counter: usize,
+    /// The number of instructions executed so far
+    pub steps:           usize,
+    /// The number of bytes written to the output device so far
+    pub bytes_written:   usize,
+}
+
+/// Serialize the cells of a tape as their values
+mod cells {
+    use serde::{
+        Deserialize,
+        Deserializer,
+        Serializer,
+    };
+
+    use crate::Byte;
+
+    pub fn serialize<S: Serializer>(cells: &[Byte], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(cells.iter().map(u8::from))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Byte>, D::Error> {
+        let values = Vec::<u8>::deserialize(deserializer)?;
+        Ok(values.into_iter().map(Byte::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tape_backend() {
+        let tape = TapeBackend::new(vec![Byte::from(7), Byte::from(8)]);
+
+        assert_eq!(tape.memory_pointer(), 0);
+        assert_eq!(tape.len(), 2);
+        assert!(!tape.is_empty());
+
+        let tape = tape.with_memory_pointer(1);
+        assert_eq!(tape.memory_pointer(), 1);
+        assert_eq!(tape.into_cells(), vec![Byte::from(7), Byte::from(8)]);
+    }
+
+    #[test]
+    fn test_empty_tape_backend() {
+        let tape = TapeBackend::new(Vec::new());
+
+        assert!(tape.is_empty());
+        assert_eq!(tape.cells(), &[]);
+    }
+}
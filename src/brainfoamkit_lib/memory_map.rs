@@ -0,0 +1,229 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    ops::Range,
+};
+
+/// A way an instruction uses the cell under the memory pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// The value of the cell is looked at, by `.`, `[` or `]` for example
+    Read,
+    /// The value of the cell is changed, by `+`, `-` or `,`
+    Write,
+}
+
+impl Display for Access {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+        }
+    }
+}
+
+/// The end of the tape the memory pointer moved past
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Before the first cell, by `<`
+    Left,
+    /// After the last cell, by `>`
+    Right,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// What the program may do with the cells of a region of the tape
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Permission {
+    /// The cells can be read and written, as every cell can by default
+    #[default]
+    ReadWrite,
+    /// The cells can be read, but not written
+    ReadOnly,
+    /// The cells can be neither read nor written
+    NoAccess,
+}
+
+impl Permission {
+    /// Whether the permission allows `access`
+    #[must_use]
+    pub const fn allows(self, access: Access) -> bool {
+        match self {
+            Self::ReadWrite => true,
+            Self::ReadOnly => matches!(access, Access::Read),
+            Self::NoAccess => false,
+        }
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadWrite => write!(f, "read-write"),
+            Self::ReadOnly => write!(f, "read-only"),
+            Self::NoAccess => write!(f, "no-access"),
+        }
+    }
+}
+
+/// The permissions of regions of the tape
+///
+/// A `MemoryMap` set with
+/// [`VirtualMachineBuilder::memory_map`](crate::VirtualMachineBuilder::memory_map)
+/// protects cells from the program. An instruction that reads or writes a
+/// cell its region does not allow stops the machine with a
+/// [`VMError::AccessViolation`](crate::VMError::AccessViolation) before it
+/// is executed. The memory pointer can still move over protected cells, only
+/// using them is checked. Extension instructions are not checked.
+///
+/// Cells outside of every region can be read and written. Where regions
+/// overlap, the one added last wins.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Access,
+///     CaptureWriter,
+///     MemoryMap,
+///     Program,
+///     VMError,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from(">.+"))
+///     .memory_map(MemoryMap::new().read_only(1..2))
+///     .build()
+///     .unwrap();
+///
+/// assert!(matches!(
+///     machine.run(),
+///     Err(VMError::AccessViolation {
+///         access:      Access::Write,
+///         cell:        1,
+///         instruction: 2,
+///     })
+/// ));
+/// assert_eq!(machine.output_device().data, [0]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryMap {
+    regions: Vec<(Range<usize>, Permission)>,
+}
+
+impl MemoryMap {
+    /// Create a `MemoryMap` with every cell readable and writable
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+        }
+    }
+
+    /// Give the cells in `cells` a permission
+    ///
+    /// # Arguments
+    ///
+    /// * `cells` - The positions of the cells
+    /// * `permission` - What the program may do with them
+    #[must_use]
+    pub fn protect(mut self, cells: Range<usize>, permission: Permission) -> Self {
+        self.regions.push((cells, permission));
+        self
+    }
+
+    /// Make the cells in `cells` read-only
+    #[must_use]
+    pub fn read_only(self, cells: Range<usize>) -> Self {
+        self.protect(cells, Permission::ReadOnly)
+    }
+
+    /// Make the cells in `cells` neither readable nor writable
+    #[must_use]
+    pub fn no_access(self, cells: Range<usize>) -> Self {
+        self.protect(cells, Permission::NoAccess)
+    }
+
+    /// The regions, in the order they were added
+    #[must_use]
+    pub fn regions(&self) -> &[(Range<usize>, Permission)] {
+        &self.regions
+    }
+
+    /// The permission of the cell at `cell`
+    #[must_use]
+    pub fn permission(&self, cell: usize) -> Permission {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(cells, _)| cells.contains(&cell))
+            .map_or(Permission::ReadWrite, |(_, permission)| *permission)
+    }
+
+    /// Whether the cell at `cell` allows `access`
+    #[must_use]
+    pub fn allows(&self, cell: usize, access: Access) -> bool {
+        self.permission(cell).allows(access)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        VMError,
+        VirtualMachine,
+    };
+
+    #[test]
+    fn test_permission() {
+        let map = MemoryMap::new().no_access(0..10).read_only(4..6);
+
+        assert_eq!(map.permission(3), Permission::NoAccess);
+        assert_eq!(map.permission(5), Permission::ReadOnly);
+        assert_eq!(map.permission(10), Permission::ReadWrite);
+        assert!(map.allows(4, Access::Read));
+        assert!(!map.allows(4, Access::Write));
+        assert!(!map.allows(9, Access::Read));
+    }
+
+    #[test]
+    fn test_access_violation() {
+        let mut machine = VirtualMachine::testing(">>[-]<+", b"").unwrap();
+        machine.set_memory_map(Some(MemoryMap::new().no_access(2..3)));
+
+        let error = machine.run().unwrap_err();
+        assert!(matches!(
+            error,
+            VMError::AccessViolation {
+                access:      Access::Read,
+                cell:        2,
+                instruction: 2,
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "The instruction at 2 cannot read the protected cell 2"
+        );
+        assert_eq!(machine.program_counter(), 2);
+    }
+}
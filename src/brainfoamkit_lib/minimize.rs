@@ -0,0 +1,175 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Reduction of `Program`s to minimal reproducers
+//!
+//! The functions in this module shrink a [`Program`] while it
+//! keeps an interesting property, such as crashing the virtual machine or
+//! producing a particular output.
+
+use crate::Program;
+
+/// Shrink a `Program` while a predicate keeps holding
+///
+/// The reduction follows the delta debugging approach: spans of instructions
+/// are removed, starting with halves of the program and going down to single
+/// instructions, and a removal is kept whenever `check` still returns `true`
+/// for the smaller program. The result is minimal in the sense that removing
+/// any single instruction makes `check` fail.
+///
+/// If the brackets of `program` are balanced, only candidates with balanced
+/// brackets are passed to `check`, so the predicate does not have to guard
+/// against programs the virtual machine refuses to load.
+///
+/// # Arguments
+///
+/// * `program` - The program to reduce
+/// * `check` - The predicate to preserve. It is called once per candidate.
+///
+/// # Returns
+///
+/// The reduced `Program`. If `check` does not hold for `program` itself, a
+/// copy of `program` is returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     minimize,
+///     Instruction,
+///     Program,
+/// };
+///
+/// let program = Program::from("++>[-]<  print it: .");
+/// let reduced = minimize::reduce(&program, |candidate| {
+///     candidate.statistics().count(Instruction::OutputValue) > 0
+/// });
+///
+/// assert_eq!(reduced, Program::from("."));
+/// ```
+pub fn reduce<F>(program: &Program, mut check: F) -> Program
+where
+    F: FnMut(&Program) -> bool,
+{
+    let mut current = program.clone();
+    if !check(&current) {
+        return current;
+    }
+    let keep_balanced = program.jump_table().is_ok();
+
+    let mut span = (current.length().unwrap_or(0) + 1) / 2;
+    while span > 0 {
+        let mut reduced = false;
+        let mut start = 0;
+        while let Some(length) = current.length().filter(|length| start < *length) {
+            let end = (start + span).min(length);
+            let candidate = current.slice(..start).concat(&current.slice(end..));
+            if (!keep_balanced || candidate.jump_table().is_ok()) && check(&candidate) {
+                current = candidate;
+                reduced = true;
+            } else {
+                start += span;
+            }
+        }
+
+        if !reduced {
+            span /= 2;
+        }
+    }
+
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        CaptureWriter,
+        Instruction,
+        Limits,
+        MockReader,
+        VirtualMachine,
+    };
+
+    /// Run a candidate and capture its output. Candidates can move the
+    /// pointer past the end of the tape, which panics, so those count as
+    /// producing no output.
+    fn output_of(program: &Program) -> Option<Vec<u8>> {
+        let program = program.clone();
+        std::panic::catch_unwind(move || {
+            let mut machine = VirtualMachine::builder()
+                .input_device(MockReader {
+                    data: Cursor::new(Vec::new()),
+                })
+                .output_device(CaptureWriter::default())
+                .program(program)
+                .limits(Limits::new().max_steps(1000))
+                .build()
+                .ok()?;
+            machine.run().ok()?;
+            Some(machine.output_device().data.clone())
+        })
+        .ok()
+        .flatten()
+    }
+
+    #[test]
+    fn test_reduce_preserves_output() {
+        let program = Program::from("+++>++<[->+<]>. trailing comment");
+        let expected = output_of(&program).unwrap();
+
+        let reduced = reduce(&program, |candidate| {
+            output_of(candidate).as_ref() == Some(&expected)
+        });
+
+        assert_eq!(output_of(&reduced), Some(expected));
+        assert!(reduced.length() < program.length());
+        assert_eq!(reduced.statistics().comment_length(), 0);
+    }
+
+    #[test]
+    fn test_reduce_is_one_minimal() {
+        let program = Program::from("+-+-+[>+<-]>.<<");
+        let check = |candidate: &Program| output_of(candidate) == Some(vec![1]);
+        let reduced = reduce(&program, check);
+
+        for index in 0..reduced.length().unwrap_or(0) {
+            let smaller = reduced.slice(..index).concat(&reduced.slice(index + 1..));
+            assert!(!check(&smaller), "{smaller:?} is smaller than {reduced:?}");
+        }
+    }
+
+    #[test]
+    fn test_reduce_keeps_brackets_balanced() {
+        let program = Program::from("+[>+<-]>[.-]");
+        let mut calls = 0;
+
+        reduce(&program, |candidate| {
+            calls += 1;
+            assert!(candidate.jump_table().is_ok());
+            candidate.statistics().loops() > 0
+        });
+
+        assert!(calls > 1);
+    }
+
+    #[test]
+    fn test_reduce_failing_predicate() {
+        let program = Program::from("+.");
+
+        assert_eq!(reduce(&program, |_| false), program);
+    }
+
+    #[test]
+    fn test_reduce_to_empty() {
+        let program = Program::from("+>-");
+        let reduced = reduce(&program, |_| true);
+
+        assert_eq!(reduced.length(), None);
+        assert_eq!(reduced.statistics().count(Instruction::NoOp), 0);
+    }
+}
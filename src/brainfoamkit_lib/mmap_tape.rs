@@ -0,0 +1,304 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs::{
+        File,
+        OpenOptions,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use memmap2::MmapMut;
+
+use crate::{
+    Byte,
+    FileError,
+};
+
+/// A tape stored in a memory-mapped file
+///
+/// Every byte of the file is a cell. Changes to the cells reach the file
+/// once they are flushed, so the tape survives the process and a later run
+/// can continue from it. The operating system pages the file in and out as
+/// it is used, so a tape can be much larger than what a program touches.
+///
+/// A `MmapTape` is handed to a machine with
+/// [`VirtualMachineBuilder::mmap_tape`](crate::VirtualMachineBuilder::mmap_tape).
+/// The machine then reads and writes its cells in the mapped file, so the
+/// tape does not have to fit in memory. Its changes are in the file as soon as
+/// they are made, and
+/// [`VirtualMachine::flush_tape`](crate::VirtualMachine::flush_tape) waits
+/// until they are on disk.
+///
+/// This type is only available with the `mmap` feature.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     MmapTape,
+/// };
+///
+/// let directory = tempfile::tempdir().unwrap();
+/// let path = directory.path().join("tape");
+///
+/// let mut tape = MmapTape::open(&path, 16).unwrap();
+/// tape.set(3, Byte::from(42));
+/// tape.flush().unwrap();
+/// drop(tape);
+///
+/// let tape = MmapTape::open(&path, 16).unwrap();
+/// assert_eq!(tape.get(3), Some(Byte::from(42)));
+/// assert_eq!(std::fs::read(&path).unwrap().len(), 16);
+/// ```
+#[derive(Debug)]
+pub struct MmapTape {
+    path: PathBuf,
+    file: File,
+    map:  MmapMut,
+}
+
+impl MmapTape {
+    /// Map the file at `path` as a tape of at least `cells` cells
+    ///
+    /// The file is created if it does not exist and extended with zeroes if
+    /// it is shorter than `cells` bytes. A longer file keeps its length, and
+    /// all of it is the tape.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened, extended or mapped, or
+    /// if the tape would have no cells.
+    pub fn open<P: AsRef<Path>>(path: P, cells: usize) -> Result<Self, FileError> {
+        let path = path.as_ref();
+        let write_error = |source| FileError::Write {
+            path: path.to_path_buf(),
+            source,
+        };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(write_error)?;
+        let length = file.metadata().map_err(write_error)?.len();
+        let cells = u64::try_from(cells).unwrap_or(u64::MAX);
+        if length < cells {
+            file.set_len(cells).map_err(write_error)?;
+        }
+        if length.max(cells) == 0 {
+            return Err(write_error(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "a tape needs at least one cell",
+            )));
+        }
+        let map = map(&file).map_err(write_error)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            map,
+        })
+    }
+
+    /// The path of the file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The number of cells on the tape
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether the tape has no cells, which never happens
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// The value of a cell, or `None` if it is beyond the end of the tape
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Byte> {
+        self.map.get(index).copied().map(Byte::from)
+    }
+
+    /// Change the value of a cell. A cell beyond the end of the tape is
+    /// ignored.
+    pub fn set(&mut self, index: usize, value: Byte) {
+        if let Some(cell) = self.map.get_mut(index) {
+            *cell = u8::from(&value);
+        }
+    }
+
+    /// The values of every cell
+    #[must_use]
+    pub fn cells(&self) -> Vec<Byte> {
+        self.map.iter().copied().map(Byte::from).collect()
+    }
+
+    /// Replace the cells with `cells`, from the first one on
+    ///
+    /// Cells beyond the end of the tape are dropped, and the cells of the
+    /// tape beyond the end of `cells` keep their values.
+    pub fn copy_from(&mut self, cells: &[Byte]) {
+        for (cell, value) in self.map.iter_mut().zip(cells) {
+            *cell = u8::from(value);
+        }
+    }
+
+    /// Grow or shrink the tape, and its file, to `cells` cells
+    ///
+    /// The cells added hold zero. The file is mapped again, which is much
+    /// slower than changing the length of a tape in memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be resized or mapped again, or if
+    /// the tape would have no cells. The tape is left as it was.
+    pub fn resize(&mut self, cells: usize) -> Result<(), FileError> {
+        let write_error = |source| FileError::Write {
+            path: self.path.clone(),
+            source,
+        };
+        if cells == 0 {
+            return Err(write_error(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "a tape needs at least one cell",
+            )));
+        }
+        let length = u64::try_from(cells).unwrap_or(u64::MAX);
+        self.file.set_len(length).map_err(write_error)?;
+        self.map = map(&self.file).map_err(write_error)?;
+        Ok(())
+    }
+
+    /// The values of the cells, as the bytes of the file
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// The values of the cells, as the bytes of the file, to change them
+    pub(crate) fn bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+
+    /// Wait until the changed cells are written to disk
+    ///
+    /// A change to a cell is in the file as soon as it is made, and the
+    /// operating system writes it to disk in its own time. This forces it to
+    /// happen now.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn flush(&self) -> Result<(), FileError> {
+        self.map.flush().map_err(|source| FileError::Write {
+            path: self.path.clone(),
+            source,
+        })
+    }
+}
+
+/// Map the whole of `file`
+fn map(file: &File) -> std::io::Result<MmapMut> {
+    // SAFETY: the map is only valid as long as no other process changes the
+    // length of the file, which a tape file is not meant to share
+    unsafe { MmapMut::map_mut(file) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        BehaviorProfile,
+        Program,
+        VirtualMachine,
+    };
+
+    #[test]
+    fn test_open() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join("tape");
+        fs::write(&path, [1, 2, 3]).unwrap();
+
+        let tape = MmapTape::open(&path, 2).unwrap();
+        assert_eq!(tape.len(), 3);
+        assert_eq!(tape.get(2), Some(Byte::from(3)));
+        assert_eq!(tape.get(3), None);
+        drop(tape);
+
+        let tape = MmapTape::open(&path, 5).unwrap();
+        assert_eq!(
+            tape.cells()[2..],
+            [Byte::from(3), Byte::from(0), Byte::from(0)]
+        );
+        assert!(MmapTape::open(directory.path().join("empty"), 0).is_err());
+    }
+
+    #[test]
+    fn test_machine_survives_restart() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join("tape");
+
+        for _ in 0..2 {
+            let mut machine = VirtualMachine::builder()
+                .input_device(std::io::stdin())
+                .program(Program::from(">+++"))
+                .mmap_tape(MmapTape::open(&path, 4).unwrap())
+                .build()
+                .unwrap();
+            assert_eq!(machine.tape().len(), 4);
+            machine.run().unwrap();
+            machine.flush_tape().unwrap();
+        }
+
+        assert_eq!(fs::read(&path).unwrap(), [0, 6, 0, 0]);
+    }
+
+    #[test]
+    fn test_machine_works_in_the_file() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join("tape");
+        let cells = 100_000;
+
+        let mut machine = VirtualMachine::builder()
+            .input_device(std::io::stdin())
+            .program(Program::from(
+                format!("{}+++", ">".repeat(cells - 1)).as_str(),
+            ))
+            .tape_size(16)
+            .behavior_profile(BehaviorProfile::ModernDynamic)
+            .mmap_tape(MmapTape::open(&path, cells).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(machine.tape().len(), cells);
+        machine.run().unwrap();
+        assert_eq!(machine.tape_memory_usage(), 0);
+
+        // The cell is in the file without flushing the tape
+        assert_eq!(fs::read(&path).unwrap()[cells - 1], 3);
+
+        // Growing the tape grows the file
+        machine.extend_program(Program::from(">+")).unwrap();
+        machine.run().unwrap();
+        drop(machine);
+        let file = fs::read(&path).unwrap();
+        assert_eq!(file.len(), cells + 1);
+        assert_eq!(file[cells - 1..], [3, 1]);
+    }
+}
@@ -0,0 +1,389 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Mutation testing for brainfuck programs
+//!
+//! A mutant is a copy of a program with a single small change: a `+` turned
+//! into a `-` or the other way around, a `<` turned into a `>` or the other
+//! way around, or an instruction other than a bracket deleted. Running the
+//! tests of a program against its mutants shows how thorough the tests are:
+//! a mutant that still passes the tests, a surviving mutant, points at an
+//! instruction whose effect the tests never check.
+//!
+//! [`run_dir`] mutates every program of a directory of tests, as found by
+//! [`test_runner::discover`](crate::test_runner::discover), and collects the
+//! results in a [`MutationReport`].
+
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    fs,
+    path::Path,
+};
+
+use crate::{
+    coverage,
+    test_runner::{
+        self,
+        Outcome,
+        TestCase,
+    },
+    FileError,
+    Instruction,
+    Limits,
+    Program,
+};
+
+/// A single change to a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mutant {
+    /// The position of the changed instruction in the `Program`
+    pub position:    usize,
+    /// The instruction in the original program
+    pub original:    Instruction,
+    /// The instruction that replaces it, or `None` if it is deleted
+    pub replacement: Option<Instruction>,
+}
+
+impl Mutant {
+    /// The mutated copy of `program`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     mutate::Mutant,
+    ///     Instruction,
+    ///     Program,
+    /// };
+    ///
+    /// let mutant = Mutant {
+    ///     position:    1,
+    ///     original:    Instruction::IncrementValue,
+    ///     replacement: None,
+    /// };
+    ///
+    /// assert_eq!(mutant.apply(&Program::from("++.")), Program::from("+."));
+    /// ```
+    #[must_use]
+    pub fn apply(&self, program: &Program) -> Program {
+        let replacement: Vec<Instruction> = self.replacement.into_iter().collect();
+        let mut mutated = program.clone();
+        // Brackets are never mutated, so the brackets stay balanced
+        let _ = mutated.splice(self.position..=self.position, &Program::from(replacement));
+        mutated
+    }
+}
+
+impl Display for Mutant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let character = |instruction: Instruction| instruction.to_char().unwrap_or(' ');
+        match self.replacement {
+            Some(replacement) => write!(
+                f,
+                "replace '{}' with '{}'",
+                character(self.original),
+                character(replacement)
+            ),
+            None => write!(f, "delete '{}'", character(self.original)),
+        }
+    }
+}
+
+/// Every mutant of a program, in program order
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     mutate,
+///     Program,
+/// };
+///
+/// let mutants = mutate::mutants(&Program::from("+[.]"));
+/// let descriptions: Vec<String> =
+///     mutants.iter().map(ToString::to_string).collect();
+///
+/// assert_eq!(
+///     descriptions,
+///     ["replace '+' with '-'", "delete '+'", "delete '.'"]
+/// );
+/// ```
+#[must_use]
+pub fn mutants(program: &Program) -> Vec<Mutant> {
+    let mut mutants = Vec::new();
+    for position in 0..program.length().unwrap_or(0) {
+        let Some(original) = program.get_instruction(position) else {
+            continue;
+        };
+        let swapped = match original {
+            Instruction::IncrementValue => Some(Instruction::DecrementValue),
+            Instruction::DecrementValue => Some(Instruction::IncrementValue),
+            Instruction::IncrementPointer => Some(Instruction::DecrementPointer),
+            Instruction::DecrementPointer => Some(Instruction::IncrementPointer),
+            _ => None,
+        };
+        let deletable = matches!(
+            original,
+            Instruction::IncrementValue
+                | Instruction::DecrementValue
+                | Instruction::IncrementPointer
+                | Instruction::DecrementPointer
+                | Instruction::OutputValue
+                | Instruction::InputValue
+        );
+        if let Some(swapped) = swapped {
+            mutants.push(Mutant {
+                position,
+                original,
+                replacement: Some(swapped),
+            });
+        }
+        if deletable {
+            mutants.push(Mutant {
+                position,
+                original,
+                replacement: None,
+            });
+        }
+    }
+    mutants
+}
+
+/// The result of running the tests of a program against one of its mutants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutantOutcome {
+    /// The name of the test
+    pub test:     String,
+    /// The line and column of the changed instruction in the program file,
+    /// counting from 1
+    pub location: (usize, usize),
+    /// The change
+    pub mutant:   Mutant,
+    /// Whether the mutant still passed the test
+    pub survived: bool,
+}
+
+/// The results of mutation testing
+///
+/// The `Display` implementation prints a line per surviving mutant, a line
+/// per test that was skipped and a summary.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MutationReport {
+    /// Every mutant that was tested, in the order of the tests
+    pub mutants: Vec<MutantOutcome>,
+    /// The tests that failed without any mutation, whose programs were not
+    /// mutated
+    pub skipped: Vec<(TestCase, Outcome)>,
+}
+
+impl MutationReport {
+    /// The mutants that passed their test
+    pub fn survivors(&self) -> impl Iterator<Item = &MutantOutcome> {
+        self.mutants.iter().filter(|outcome| outcome.survived)
+    }
+
+    /// The number of mutants that failed their test
+    #[must_use]
+    pub fn killed(&self) -> usize {
+        self.mutants.len() - self.survivors().count()
+    }
+
+    /// The share of the mutants that failed their test, as a percentage, or
+    /// `None` if there were no mutants
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn score(&self) -> Option<f64> {
+        (!self.mutants.is_empty()).then(|| self.killed() as f64 * 100.0 / self.mutants.len() as f64)
+    }
+}
+
+impl Display for MutationReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for outcome in self.survivors() {
+            let (line, column) = outcome.location;
+            writeln!(
+                f,
+                "SURVIVED {}:{line}:{column} {}",
+                outcome.test, outcome.mutant
+            )?;
+        }
+        for (case, _) in &self.skipped {
+            writeln!(f, "SKIPPED  {} fails without mutation", case.name)?;
+        }
+        if !self.mutants.is_empty() || !self.skipped.is_empty() {
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "{} mutants, {} killed, {} survived",
+            self.mutants.len(),
+            self.killed(),
+            self.mutants.len() - self.killed()
+        )?;
+        if let Some(score) = self.score() {
+            write!(f, " ({score:.1}% killed)")?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Run every test against every mutant of its program
+///
+/// A test that fails on its own program is skipped, since its mutants would
+/// fail too. A mutant that fails in any way, including by running into one of
+/// the `limits`, is killed.
+///
+/// # Arguments
+///
+/// * `cases` - The tests to run
+/// * `limits` - The resource limits of every run
+#[must_use]
+pub fn run(cases: Vec<TestCase>, limits: Limits) -> MutationReport {
+    let mut report = MutationReport::default();
+    for case in cases {
+        let outcome = case.run(limits);
+        let source = match (outcome.is_passed(), fs::read_to_string(&case.program)) {
+            (true, Ok(source)) => source,
+            (true, Err(error)) => {
+                report
+                    .skipped
+                    .push((case, Outcome::Error(error.to_string())));
+                continue;
+            }
+            (false, _) => {
+                report.skipped.push((case, outcome));
+                continue;
+            }
+        };
+
+        let program = Program::parse(&source, case.dialect);
+        for mutant in mutants(&program) {
+            let survived = case.run_program(mutant.apply(&program), limits).is_passed();
+            report.mutants.push(MutantOutcome {
+                test: case.name.clone(),
+                location: coverage::location(&source, mutant.position),
+                mutant,
+                survived,
+            });
+        }
+    }
+    report
+}
+
+/// Run every test in a directory and its subdirectories against every mutant
+/// of its program
+///
+/// Every run may execute at most [`test_runner::MAX_STEPS`] instructions.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs;
+///
+/// use brainfoamkit_lib::mutate;
+///
+/// let directory = tempfile::tempdir().unwrap();
+/// fs::write(directory.path().join("cat.bf"), ",.+").unwrap();
+/// fs::write(directory.path().join("cat.input"), "A").unwrap();
+/// fs::write(directory.path().join("cat.expected"), "A").unwrap();
+///
+/// let report = mutate::run_dir(directory.path()).unwrap();
+///
+/// // Nothing checks the final `+`
+/// assert_eq!(report.mutants.len(), 4);
+/// assert_eq!(report.survivors().count(), 2);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the directory or one of its subdirectories cannot be
+/// read.
+pub fn run_dir(directory: &Path) -> Result<MutationReport, FileError> {
+    Ok(run(
+        test_runner::discover(directory)?,
+        Limits::new().max_steps(test_runner::MAX_STEPS),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_mutants() {
+        let mutants = mutants(&Program::from("><,x[-]"));
+        let replacements: Vec<(usize, Option<Instruction>)> = mutants
+            .iter()
+            .map(|mutant| (mutant.position, mutant.replacement))
+            .collect();
+
+        assert_eq!(
+            replacements,
+            [
+                (0, Some(Instruction::DecrementPointer)),
+                (0, None),
+                (1, Some(Instruction::IncrementPointer)),
+                (1, None),
+                (2, None),
+                (5, Some(Instruction::IncrementValue)),
+                (5, None),
+            ]
+        );
+        assert_eq!(
+            mutants[0].apply(&Program::from("><,x[-]")),
+            Program::from("<<,x[-]")
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let directory = tempdir().unwrap();
+        fs::write(directory.path().join("a.bf"), "++\n+.").unwrap();
+        fs::write(directory.path().join("a.expected"), "\x03").unwrap();
+        fs::write(directory.path().join("broken.bf"), "+.").unwrap();
+        fs::write(directory.path().join("broken.expected"), "x").unwrap();
+
+        let report = run_dir(directory.path()).unwrap();
+
+        assert_eq!(report.mutants.len(), 7);
+        assert_eq!(report.killed(), 7);
+        assert_eq!(report.score(), Some(100.0));
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.mutants[4].location, (2, 1));
+        assert_eq!(
+            report.to_string(),
+            "SKIPPED  broken fails without mutation\n\n7 mutants, 7 killed, 0 survived (100.0% \
+             killed)\n"
+        );
+    }
+
+    #[test]
+    fn test_survivors() {
+        let directory = tempdir().unwrap();
+        // Nothing checks the second cell
+        fs::write(directory.path().join("walk.bf"), "+>+<.").unwrap();
+        fs::write(directory.path().join("walk.expected"), "\x01").unwrap();
+
+        let report = run_dir(directory.path()).unwrap();
+        let survivors: Vec<String> = report
+            .survivors()
+            .map(|outcome| outcome.mutant.to_string())
+            .collect();
+
+        assert_eq!(
+            survivors,
+            ["replace '+' with '-'", "delete '+'", "delete '<'"]
+        );
+        assert!(report
+            .to_string()
+            .starts_with("SURVIVED walk:1:3 replace '+' with '-'\n"));
+    }
+}
@@ -0,0 +1,508 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Readers and writers that connect a program to a network peer
+//!
+//! A [`TcpReader`] feeds the bytes a peer sends to `,` and a [`TcpWriter`]
+//! sends every byte written by `.` back to it, so a program can be hosted on a
+//! socket, as `bfkrun serve` does. [`split`] turns a connection into both.
+//! A [`TapeBroadcast`] lets more peers watch the machine of one of them.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{
+//!     io::{
+//!         Read,
+//!         Write,
+//!     },
+//!     net::{
+//!         TcpListener,
+//!         TcpStream,
+//!     },
+//!     thread,
+//! };
+//!
+//! use brainfoamkit_lib::{
+//!     net,
+//!     Program,
+//!     VirtualMachine,
+//! };
+//!
+//! let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+//! let address = listener.local_addr().unwrap();
+//! let server = thread::spawn(move || {
+//!     let (stream, _) = listener.accept().unwrap();
+//!     let (input, output) = net::split(stream).unwrap();
+//!     let mut machine = VirtualMachine::builder()
+//!         .input_device(input)
+//!         .output_device(output)
+//!         .program(Program::from(",+.,+."))
+//!         .build()
+//!         .unwrap();
+//!     machine.run().unwrap();
+//! });
+//!
+//! let mut peer = TcpStream::connect(address).unwrap();
+//! peer.write_all(b"HA").unwrap();
+//! let mut reply = Vec::new();
+//! peer.read_to_end(&mut reply).unwrap();
+//! server.join().unwrap();
+//!
+//! assert_eq!(reply, b"IB");
+//! ```
+
+use std::{
+    io::{
+        self,
+        Read,
+        Write,
+    },
+    net::{
+        Shutdown,
+        TcpStream,
+    },
+    sync::{
+        Arc,
+        Mutex,
+        MutexGuard,
+        PoisonError,
+    },
+};
+
+use serde_json::{
+    json,
+    Value,
+};
+
+use crate::{
+    ReadError,
+    VMReader,
+    VMReaderType,
+    VMWriter,
+    VMWriterType,
+    VirtualMachine,
+};
+
+/// Reads the input of the `VirtualMachine` from a TCP connection
+///
+/// `,` waits until the peer sends a byte. Once the peer closes its end of the
+/// connection, the input has ended.
+#[derive(Debug)]
+pub struct TcpReader {
+    stream: TcpStream,
+}
+
+impl TcpReader {
+    /// Create a reader for the bytes received on `stream`
+    #[must_use]
+    pub const fn new(stream: TcpStream) -> Self {
+        Self { stream }
+    }
+
+    /// The connection the reader receives from
+    #[must_use]
+    pub const fn get_ref(&self) -> &TcpStream {
+        &self.stream
+    }
+}
+
+impl VMReader for TcpReader {
+    /// Read the next byte sent by the peer
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the peer closed the connection,
+    /// if the byte read is not within the ASCII range or if the connection
+    /// fails.
+    fn read(&mut self) -> Result<u8, ReadError> {
+        let mut buffer = [0u8; 1];
+        self.stream.read_exact(&mut buffer)?;
+
+        if buffer[0] <= 128 {
+            Ok(buffer[0])
+        } else {
+            Err(ReadError::NotAscii(buffer[0]))
+        }
+    }
+
+    fn get_vmreader_type(&self) -> VMReaderType {
+        VMReaderType::Tcp
+    }
+}
+
+/// Writes the output of the `VirtualMachine` to a TCP connection
+///
+/// Every byte is sent as soon as it is written, so the peer sees a prompt
+/// before the program waits for its answer. Finishing the output shuts down
+/// the sending half of the connection, which tells the peer that the program
+/// is done.
+#[derive(Debug)]
+pub struct TcpWriter {
+    stream: TcpStream,
+}
+
+impl TcpWriter {
+    /// Create a writer that sends the output to `stream`
+    ///
+    /// Nagle's algorithm is turned off for the connection, so that every byte
+    /// is sent right away.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the option of the connection
+    /// could not be set.
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(Self { stream })
+    }
+
+    /// The connection the writer sends to
+    #[must_use]
+    pub const fn get_ref(&self) -> &TcpStream {
+        &self.stream
+    }
+}
+
+impl VMWriter for TcpWriter {
+    /// Send a single byte to the peer
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte could not be sent.
+    fn write(&mut self, value: u8) -> io::Result<()> {
+        self.stream.write_all(&[value])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.stream.flush()?;
+        match self.stream.shutdown(Shutdown::Write) {
+            Err(error) if error.kind() != io::ErrorKind::NotConnected => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        VMWriterType::Tcp
+    }
+}
+
+/// Split a connection into a reader for the input and a writer for the output
+/// of a program
+///
+/// # Errors
+///
+/// This function will return an error if the connection could not be cloned
+/// or configured.
+pub fn split(stream: TcpStream) -> io::Result<(TcpReader, TcpWriter)> {
+    let reader = TcpReader::new(stream.try_clone()?);
+    Ok((reader, TcpWriter::new(stream)?))
+}
+
+/// Sends the state of a machine to observers as it changes
+///
+/// A `TapeBroadcast` lets a room full of people watch a program that one of
+/// them drives. Every observer first receives a snapshot of the whole tape,
+/// and then an update with the cells that changed every time the machine is
+/// [`publish`](Self::publish)ed. Every message is a JSON object on a line of
+/// its own:
+///
+/// ```text
+/// {"bytes_written":0,"halted":false,"memory_pointer":0,"program_counter":0,"steps":0,"tape":[0,0,0],"type":"snapshot"}
+/// {"bytes_written":0,"changes":[[0,1]],"halted":true,"memory_pointer":1,"program_counter":2,"steps":2,"type":"update"}
+/// ```
+///
+/// An observer that can no longer be written to, for example because it
+/// disconnected, is dropped. Cloning a `TapeBroadcast` creates another handle
+/// to the same observers, so that one thread can accept observers while
+/// another runs the machine.
+///
+/// # Examples
+///
+/// ```
+/// use std::{
+///     io::{
+///         BufRead,
+///         BufReader,
+///     },
+///     net::{
+///         TcpListener,
+///         TcpStream,
+///     },
+/// };
+///
+/// use brainfoamkit_lib::{
+///     net::TapeBroadcast,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let observer = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+/// let broadcast = TapeBroadcast::new();
+/// broadcast.add_observer(listener.accept().unwrap().0);
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("+>"))
+///     .tape_size(3)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+/// broadcast.publish(&machine);
+///
+/// let mut lines = BufReader::new(observer).lines();
+/// assert!(lines
+///     .next()
+///     .unwrap()
+///     .unwrap()
+///     .contains(r#""type":"snapshot""#));
+/// assert!(lines
+///     .next()
+///     .unwrap()
+///     .unwrap()
+///     .contains(r#""changes":[[0,1]]"#));
+/// ```
+#[derive(Clone, Default)]
+pub struct TapeBroadcast {
+    state: Arc<Mutex<BroadcastState>>,
+}
+
+/// The observers of a `TapeBroadcast` and the state they were last sent
+#[derive(Default)]
+struct BroadcastState {
+    observers:       Vec<Box<dyn Write + Send>>,
+    tape:            Vec<u8>,
+    steps:           usize,
+    program_counter: usize,
+    memory_pointer:  usize,
+    bytes_written:   usize,
+    halted:          bool,
+}
+
+impl TapeBroadcast {
+    /// Create a broadcast without observers
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the last published state to `observer` and add it to the
+    /// observers of future updates
+    ///
+    /// The observer is dropped right away if the snapshot cannot be written.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - Where to write the messages, such as a `TcpStream`
+    pub fn add_observer<O>(&self, mut observer: O)
+    where
+        O: Write + Send + 'static,
+    {
+        let mut state = self.lock();
+        let mut snapshot = state.message("snapshot");
+        snapshot["tape"] = json!(state.tape);
+        if send(&mut observer, &snapshot).is_ok() {
+            state.observers.push(Box::new(observer));
+        }
+    }
+
+    /// Send the cells of `machine` that changed since the last update to every
+    /// observer
+    ///
+    /// Nothing is sent if the machine has not executed an instruction since
+    /// the last update.
+    ///
+    /// # Arguments
+    ///
+    /// * `machine` - The machine the observers are watching
+    pub fn publish<R, W>(&self, machine: &VirtualMachine<R, W>)
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        let mut state = self.lock();
+        if state.steps == machine.steps() && !state.tape.is_empty() {
+            return;
+        }
+
+        let tape: Vec<u8> = machine.tape().iter().map(u8::from).collect();
+        let changes: Vec<(usize, u8)> = tape
+            .iter()
+            .enumerate()
+            .filter(|&(cell, value)| state.tape.get(cell).copied().unwrap_or(0) != *value)
+            .map(|(cell, value)| (cell, *value))
+            .collect();
+        state.tape = tape;
+        state.steps = machine.steps();
+        state.program_counter = machine.program_counter();
+        state.memory_pointer = machine.memory_pointer();
+        state.bytes_written = machine.bytes_written();
+        state.halted = machine.is_halted();
+
+        let mut update = state.message("update");
+        update["changes"] = json!(changes);
+        state
+            .observers
+            .retain_mut(|observer| send(observer, &update).is_ok());
+    }
+
+    /// The number of observers that are still connected
+    #[must_use]
+    pub fn observers(&self) -> usize {
+        self.lock().observers.len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BroadcastState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl BroadcastState {
+    /// A message of `kind` with the position of the machine
+    fn message(&self, kind: &str) -> Value {
+        json!({
+            "type": kind,
+            "steps": self.steps,
+            "program_counter": self.program_counter,
+            "memory_pointer": self.memory_pointer,
+            "bytes_written": self.bytes_written,
+            "halted": self.halted,
+        })
+    }
+}
+
+/// Write `message` to `observer` as a line of JSON
+fn send<O: Write + ?Sized>(observer: &mut O, message: &Value) -> io::Result<()> {
+    writeln!(observer, "{message}")?;
+    observer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    fn connection() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (server, client)
+    }
+
+    #[test]
+    fn test_reader() {
+        let (server, mut client) = connection();
+        let mut reader = TcpReader::new(server);
+        client.write_all(&[b'a', 200]).unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+
+        assert_eq!(reader.read().unwrap(), b'a');
+        assert!(matches!(reader.read(), Err(ReadError::NotAscii(200))));
+        assert!(matches!(reader.read(), Err(ReadError::Eof)));
+        assert_eq!(reader.get_vmreader_type(), VMReaderType::Tcp);
+    }
+
+    #[test]
+    fn test_writer() {
+        let (server, mut client) = connection();
+        let (_, mut writer) = split(server).unwrap();
+        writer.write(b'o').unwrap();
+        writer.write(b'k').unwrap();
+        writer.finish().unwrap();
+
+        let mut received = Vec::new();
+        client.read_to_end(&mut received).unwrap();
+        assert_eq!(received, b"ok");
+        assert_eq!(writer.get_vmwriter_type(), VMWriterType::Tcp);
+    }
+
+    /// A writer whose data can still be read after it was handed over
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn messages(&self) -> Vec<Value> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    struct Disconnected;
+
+    impl Write for Disconnected {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_broadcast() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(crate::MockReader::default())
+            .program(crate::Program::from("++>+<"))
+            .tape_size(3)
+            .build()
+            .unwrap();
+        let broadcast = TapeBroadcast::new();
+        let early = SharedBuffer::default();
+        broadcast.add_observer(early.clone());
+
+        machine.execute_instruction().unwrap();
+        machine.execute_instruction().unwrap();
+        broadcast.publish(&machine);
+        broadcast.publish(&machine);
+
+        let late = SharedBuffer::default();
+        broadcast.add_observer(late.clone());
+        machine.run().unwrap();
+        broadcast.publish(&machine);
+
+        let messages = early.messages();
+        assert_eq!(messages.len(), 3, "Publishing twice sends one update");
+        assert_eq!(messages[0]["type"], "snapshot");
+        assert_eq!(messages[1]["changes"], json!([[0, 2]]));
+        assert_eq!(messages[2]["changes"], json!([[1, 1]]));
+        assert_eq!(messages[2]["halted"], true);
+
+        let messages = late.messages();
+        assert_eq!(messages[0]["tape"], json!([2, 0, 0]));
+        assert_eq!(messages[0]["steps"], 2);
+        assert_eq!(messages[1]["memory_pointer"], 0);
+    }
+
+    #[test]
+    fn test_disconnected_observers() {
+        let broadcast = TapeBroadcast::new();
+        broadcast.add_observer(Disconnected);
+        assert_eq!(broadcast.observers(), 0);
+
+        broadcast.add_observer(SharedBuffer::default());
+        assert_eq!(broadcast.observers(), 1);
+    }
+}
@@ -0,0 +1,306 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashSet,
+    mem,
+    sync::{
+        Arc,
+        OnceLock,
+    },
+};
+
+use crate::Byte;
+
+/// A sparse tape stored in pages that can be shared
+///
+/// A program that roams far along a growing tape leaves long stretches of
+/// cells behind that are zero again, or that were never written at all. A
+/// `PagedTape` splits its cells into pages of [`PAGE_SIZE`](Self::PAGE_SIZE)
+/// cells. Every page of zeros is a reference to one canonical zero page, which
+/// costs nothing but the reference, and pages with the same cells can share
+/// one copy.
+///
+/// Writing to a shared page copies it first. The copy is kept even once its
+/// cells are back to zero, until [`compact`](Self::compact) runs: compacting
+/// points every page of zeros back at the zero page and makes the pages with
+/// the same cells share one copy. A long run can compact its tape from time
+/// to time and check [`memory_usage`](Self::memory_usage) against its budget.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     PagedTape,
+/// };
+///
+/// let mut tape = PagedTape::new(4 * PagedTape::PAGE_SIZE);
+/// tape.set(PagedTape::PAGE_SIZE + 10, Byte::from(1));
+/// let one_page = tape.memory_usage();
+///
+/// tape.set(10, Byte::from(1));
+/// tape.set(10, Byte::from(0));
+/// assert!(tape.memory_usage() > one_page);
+///
+/// // The first page is zero again, only the second one is kept
+/// assert!(tape.compact() > 0);
+/// assert_eq!(tape.memory_usage(), one_page);
+/// assert_eq!(tape.get(PagedTape::PAGE_SIZE + 10), Some(Byte::from(1)));
+///
+/// // Setting a cell beyond the end grows the tape
+/// tape.set(5 * PagedTape::PAGE_SIZE, Byte::from(2));
+/// assert_eq!(tape.len(), 5 * PagedTape::PAGE_SIZE + 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PagedTape {
+    pages: Vec<Arc<[Byte]>>,
+    len:   usize,
+}
+
+impl PagedTape {
+    /// The number of cells in a page
+    pub const PAGE_SIZE: usize = 4096;
+
+    /// Create a tape of `len` cells holding zero
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self {
+            pages: vec![zero_page(); page_count(len)],
+            len,
+        }
+    }
+
+    /// Create a tape holding `cells`
+    ///
+    /// Every page gets its own copy of its cells until the tape is
+    /// [compacted](Self::compact).
+    #[must_use]
+    pub fn from_cells(cells: &[Byte]) -> Self {
+        let pages = cells
+            .chunks(Self::PAGE_SIZE)
+            .map(|chunk| {
+                let mut page = vec![Byte::default(); Self::PAGE_SIZE];
+                page[..chunk.len()].copy_from_slice(chunk);
+                Arc::from(page)
+            })
+            .collect();
+        Self {
+            pages,
+            len: cells.len(),
+        }
+    }
+
+    /// The number of cells on the tape
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the tape has no cells at all
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The value of a cell, or `None` if it is beyond the end of the tape
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Byte> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.pages[index / Self::PAGE_SIZE][index % Self::PAGE_SIZE])
+    }
+
+    /// A reference to a cell, or `None` if it is beyond the end of the tape
+    pub(crate) fn cell(&self, index: usize) -> Option<&Byte> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.pages[index / Self::PAGE_SIZE][index % Self::PAGE_SIZE])
+    }
+
+    /// Change the value of a cell
+    ///
+    /// A cell beyond the end of the tape grows the tape up to it, with the
+    /// cells in between holding zero. Writing a zero to a page of zeros does
+    /// not copy it.
+    pub fn set(&mut self, index: usize, value: Byte) {
+        if index >= self.len {
+            self.resize(index + 1);
+        }
+        let page = &mut self.pages[index / Self::PAGE_SIZE];
+        if page[index % Self::PAGE_SIZE] != value {
+            copy_on_write(page)[index % Self::PAGE_SIZE] = value;
+        }
+    }
+
+    /// Grow or shrink the tape to `len` cells
+    ///
+    /// The cells added hold zero.
+    pub fn resize(&mut self, len: usize) {
+        if len < self.len {
+            // The cells dropped from the last page have to read as zero again
+            // if the tape grows back over them
+            let start = len % Self::PAGE_SIZE;
+            self.pages.truncate(page_count(len));
+            if let Some(page) = self.pages.last_mut().filter(|_| start > 0) {
+                if page[start..].iter().any(|cell| *cell != Byte::default()) {
+                    copy_on_write(page)[start..].fill(Byte::default());
+                }
+            }
+        } else {
+            self.pages.resize(page_count(len), zero_page());
+        }
+        self.len = len;
+    }
+
+    /// The values of every cell
+    #[must_use]
+    pub fn cells(&self) -> Vec<Byte> {
+        self.iter().collect()
+    }
+
+    /// The values of every cell, in order
+    pub fn iter(&self) -> impl Iterator<Item = Byte> + '_ {
+        self.pages
+            .iter()
+            .flat_map(|page| page.iter().copied())
+            .take(self.len)
+    }
+
+    /// Replace the cells with `cells`, from the first one on
+    ///
+    /// Cells beyond the end of the tape are dropped, and the cells of the
+    /// tape beyond the end of `cells` keep their values.
+    pub fn copy_from(&mut self, cells: &[Byte]) {
+        for (index, value) in cells.iter().take(self.len).enumerate() {
+            self.set(index, *value);
+        }
+    }
+
+    /// Share the pages of zeros and the pages with the same cells
+    ///
+    /// Every page holding only zeros is replaced by the canonical zero page,
+    /// and the pages holding the same cells end up sharing one copy.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes freed, as counted by
+    /// [`memory_usage`](Self::memory_usage).
+    pub fn compact(&mut self) -> usize {
+        let before = self.memory_usage();
+        let zero = zero_page();
+        let mut seen: HashSet<Arc<[Byte]>> = HashSet::new();
+        for page in &mut self.pages {
+            if Arc::ptr_eq(page, &zero) {
+                continue;
+            }
+            if page.iter().all(|cell| *cell == Byte::default()) {
+                *page = Arc::clone(&zero);
+            } else if let Some(shared) = seen.get(&**page) {
+                *page = Arc::clone(shared);
+            } else {
+                seen.insert(Arc::clone(page));
+            }
+        }
+        self.pages.shrink_to_fit();
+        before.saturating_sub(self.memory_usage())
+    }
+
+    /// The approximate number of bytes the tape takes up
+    ///
+    /// This counts the table of pages and every page the tape does not share
+    /// with the canonical zero page once, however many times it is used. The
+    /// zero page is shared by every `PagedTape` and is not counted.
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        let zero = zero_page();
+        let pages: HashSet<*const Byte> = self
+            .pages
+            .iter()
+            .filter(|page| !Arc::ptr_eq(page, &zero))
+            .map(|page| page.as_ptr())
+            .collect();
+        self.pages.capacity() * mem::size_of::<Arc<[Byte]>>()
+            + pages.len() * Self::PAGE_SIZE * mem::size_of::<Byte>()
+    }
+}
+
+impl PartialEq for PagedTape {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for PagedTape {}
+
+impl From<&[Byte]> for PagedTape {
+    fn from(cells: &[Byte]) -> Self {
+        Self::from_cells(cells)
+    }
+}
+
+/// The number of pages needed to hold `len` cells
+const fn page_count(len: usize) -> usize {
+    (len + PagedTape::PAGE_SIZE - 1) / PagedTape::PAGE_SIZE
+}
+
+/// The page of zeros shared by every `PagedTape`
+fn zero_page() -> Arc<[Byte]> {
+    static ZERO_PAGE: OnceLock<Arc<[Byte]>> = OnceLock::new();
+    Arc::clone(ZERO_PAGE.get_or_init(|| Arc::from(vec![Byte::default(); PagedTape::PAGE_SIZE])))
+}
+
+/// The cells of `page` to change, copying them first if they are shared
+fn copy_on_write(page: &mut Arc<[Byte]>) -> &mut [Byte] {
+    if Arc::get_mut(page).is_none() {
+        *page = Arc::from(page.to_vec());
+    }
+    Arc::get_mut(page).unwrap_or_else(|| unreachable!("the page was just copied"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_shares_duplicate_pages() {
+        let mut cells = vec![Byte::default(); 4 * PagedTape::PAGE_SIZE];
+        cells[0] = Byte::from(7);
+        cells[2 * PagedTape::PAGE_SIZE] = Byte::from(7);
+        let mut tape = PagedTape::from_cells(&cells);
+        let page = PagedTape::PAGE_SIZE * mem::size_of::<Byte>();
+        let table = tape.memory_usage() - 4 * page;
+
+        assert_eq!(tape.compact(), 3 * page);
+        assert_eq!(tape.memory_usage(), table + page);
+        assert_eq!(tape.cells(), cells);
+
+        // Writing to a shared page leaves the other one alone
+        tape.set(1, Byte::from(1));
+        assert_eq!(tape.get(1), Some(Byte::from(1)));
+        assert_eq!(tape.get(2 * PagedTape::PAGE_SIZE + 1), Some(Byte::from(0)));
+        assert_eq!(tape.memory_usage(), table + 2 * page);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut tape = PagedTape::new(3);
+        tape.set(2, Byte::from(5));
+        tape.resize(2);
+        assert_eq!(tape.get(2), None);
+
+        tape.resize(PagedTape::PAGE_SIZE + 1);
+        assert_eq!(tape.get(2), Some(Byte::from(0)));
+        assert_eq!(tape.iter().count(), PagedTape::PAGE_SIZE + 1);
+
+        // Growing with a zero does not copy a page
+        let mut tape = PagedTape::new(0);
+        tape.set(10 * PagedTape::PAGE_SIZE, Byte::default());
+        assert!(tape.memory_usage() < PagedTape::PAGE_SIZE);
+        assert_eq!(tape, PagedTape::new(10 * PagedTape::PAGE_SIZE + 1));
+    }
+}
@@ -0,0 +1,244 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use alloc::string::String;
+use core::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+#[cfg(feature = "std")]
+use std::{
+    error::Error,
+    io,
+    path::PathBuf,
+};
+
+/// Text could not be parsed
+///
+/// Returned when parsing the name of a setting, the instructions and
+/// brackets of a `Program`, a tape in hexadecimal or a source for the
+/// [`Preprocessor`](crate::Preprocessor).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     ParseError,
+///     Program,
+/// };
+///
+/// let error = Program::from("+]").jump_table().unwrap_err();
+///
+/// assert!(matches!(
+///     error,
+///     ParseError::UnmatchedBracket {
+///         bracket:  ']',
+///         position: 1,
+///     }
+/// ));
+/// assert_eq!(error.to_string(), "Unmatched ']' at instruction 1");
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The name of a setting, such as a dialect or a behavior profile, is not
+    /// known
+    UnknownName {
+        /// The kind of setting
+        kind: &'static str,
+        /// The name that was given
+        name: String,
+    },
+    /// Text that is not an instruction was converted to one
+    NotAnInstruction(String),
+    /// A string that should hold a single instruction holds more or fewer
+    /// characters
+    NotASingleInstruction(String),
+    /// A bracket or parenthesis of a `Program` has no partner
+    UnmatchedBracket {
+        /// The bracket or parenthesis without a partner
+        bracket:  char,
+        /// The position of the bracket in the program
+        position: usize,
+    },
+    /// A hexadecimal tape holds something other than a pair of digits
+    InvalidHex(String),
+    /// A `@define` directive has no macro name
+    MalformedDefine {
+        /// The line of the directive
+        line:   usize,
+        /// The column the name was expected at
+        column: usize,
+    },
+    /// A macro name holds characters other than letters, digits and
+    /// underscores
+    InvalidMacroName {
+        /// The name that was given
+        name:   String,
+        /// The line of the directive
+        line:   usize,
+        /// The column of the name
+        column: usize,
+    },
+    /// A macro is defined for the second time
+    MacroRedefined(String),
+    /// A macro is used without being defined
+    UndefinedMacro {
+        /// The name of the macro
+        name:   String,
+        /// The line it is used on
+        line:   usize,
+        /// The column it is used at
+        column: usize,
+    },
+    /// Macros expand to each other in a cycle
+    MacroCycle(String),
+    /// An `@include` directive has no quoted path
+    MalformedInclude {
+        /// The line of the directive
+        line:   usize,
+        /// The column of the directive
+        column: usize,
+        /// The text after `@include`
+        found:  String,
+    },
+    /// Files include each other in a cycle
+    IncludeCycle(String),
+    /// An included file could not be read
+    #[cfg(feature = "std")]
+    Include {
+        /// The path of the included file
+        path:   PathBuf,
+        /// The reason it could not be read
+        source: io::Error,
+    },
+    /// A `@data` directive is not of the form `@data "file" -> cells N..`
+    MalformedData {
+        /// The line of the directive
+        line:   usize,
+        /// The column of the directive
+        column: usize,
+        /// The text after `@data`
+        found:  String,
+    },
+    /// A data file holds more bytes than the range of cells it is loaded into
+    #[cfg(feature = "std")]
+    DataTooLarge {
+        /// The path of the data file
+        path:  PathBuf,
+        /// The number of bytes in the file
+        bytes: usize,
+        /// The number of cells in the range
+        cells: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownName { kind, name } => write!(f, "Unknown {kind} '{name}'"),
+            Self::NotAnInstruction(text) => write!(f, "'{text}' is not a BrainFuck instruction"),
+            Self::NotASingleInstruction(text) => {
+                write!(f, "Expected a single BrainFuck instruction, found '{text}'")
+            }
+            Self::UnmatchedBracket { bracket, position } => {
+                write!(f, "Unmatched '{bracket}' at instruction {position}")
+            }
+            Self::InvalidHex(word) => write!(f, "Expected two hexadecimal digits, found '{word}'"),
+            Self::MalformedDefine { line, column } => {
+                write!(f, "Malformed define at {line}:{column}")
+            }
+            Self::InvalidMacroName { name, line, column } => {
+                write!(f, "Invalid macro name '{name}' at {line}:{column}")
+            }
+            Self::MacroRedefined(name) => write!(f, "Macro '{name}' is already defined"),
+            Self::UndefinedMacro { name, line, column } => {
+                write!(f, "Undefined macro '{name}' used at {line}:{column}")
+            }
+            Self::MacroCycle(chain) => write!(f, "Macro cycle detected: {chain}"),
+            Self::MalformedInclude {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "Malformed include at {line}:{column}: Expected a quoted path, found '{found}'"
+            ),
+            Self::IncludeCycle(chain) => write!(f, "Include cycle detected: {chain}"),
+            #[cfg(feature = "std")]
+            Self::Include { path, .. } => {
+                write!(f, "Unable to read included file {}", path.display())
+            }
+            Self::MalformedData {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "Malformed data at {line}:{column}: Expected '\"file\" -> cells N..', found \
+                 '{found}'"
+            ),
+            #[cfg(feature = "std")]
+            Self::DataTooLarge { path, bytes, cells } => write!(
+                f,
+                "{} holds {bytes} bytes, but its range only has {cells} cells",
+                path.display()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Include { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        let error = ParseError::MalformedInclude {
+            line:   2,
+            column: 1,
+            found:  String::from("lib.bf"),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Malformed include at 2:1: Expected a quoted path, found 'lib.bf'"
+        );
+    }
+
+    #[test]
+    fn test_data_message() {
+        let error = ParseError::MalformedData {
+            line:   3,
+            column: 1,
+            found:  String::from("\"a.bin\""),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Malformed data at 3:1: Expected '\"file\" -> cells N..', found '\"a.bin\"'"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_include_message() {
+        let error = ParseError::Include {
+            path:   PathBuf::from("lib.bf"),
+            source: io::Error::from(io::ErrorKind::NotFound),
+        };
+        assert_eq!(error.to_string(), "Unable to read included file lib.bf");
+        assert!(error.source().is_some());
+    }
+}
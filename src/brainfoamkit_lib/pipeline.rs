@@ -0,0 +1,282 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use alloc::collections::BTreeMap;
+use core::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+
+use crate::format::human_count;
+
+/// A stage of the [`Pipeline`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub enum PipelineStage {
+    /// The instruction is read from the program
+    Fetch,
+    /// The instruction is worked out
+    Decode,
+    /// The instruction is carried out, and a jump is resolved
+    Execute,
+}
+
+impl PipelineStage {
+    /// Every stage, in the order an instruction goes through them
+    pub const ALL: [Self; 3] = [Self::Fetch, Self::Decode, Self::Execute];
+}
+
+impl Display for PipelineStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Fetch => "Fetch",
+            Self::Decode => "Decode",
+            Self::Execute => "Execute",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// What a stage of the [`Pipeline`] holds during a cycle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum PipelineSlot {
+    /// Nothing has reached the stage yet
+    #[default]
+    Empty,
+    /// The stage idles, because the instruction fetched into it was on the
+    /// wrong side of a jump and was flushed
+    Bubble,
+    /// The instruction at this position in the program
+    Instruction(usize),
+}
+
+/// The totals of a [`Pipeline`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct PipelineStats {
+    /// The number of instructions executed
+    pub instructions: usize,
+    /// The number of cycles until the last instruction left the pipeline
+    pub cycles:       usize,
+    /// The number of cycles lost to flushes
+    pub stall_cycles: usize,
+    /// The number of jumps that flushed the pipeline
+    pub flushes:      usize,
+}
+
+impl PipelineStats {
+    /// The average number of cycles per instruction
+    ///
+    /// An ideal pipeline gets close to one cycle per instruction, every flush
+    /// adds [`Pipeline::FLUSH_PENALTY`] cycles. Zero if no instruction was
+    /// executed.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cycles_per_instruction(&self) -> f64 {
+        if self.instructions == 0 {
+            0.0
+        } else {
+            self.cycles as f64 / self.instructions as f64
+        }
+    }
+}
+
+impl Display for PipelineStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} instructions in {} cycles ({:.2} per instruction), {} stall cycles from {} flushes",
+            human_count(self.instructions),
+            human_count(self.cycles),
+            self.cycles_per_instruction(),
+            human_count(self.stall_cycles),
+            human_count(self.flushes)
+        )
+    }
+}
+
+/// A toy three-stage pipeline fed with the instructions a program executes
+///
+/// Every cycle, each instruction moves on from [`Fetch`](PipelineStage::Fetch)
+/// to [`Decode`](PipelineStage::Decode) to
+/// [`Execute`](PipelineStage::Execute), and the instruction after it in the
+/// program is fetched. That guess is right until a jump is taken: a `[` that
+/// skips its loop, a `]` that repeats it, or a procedure call or return. The
+/// jump is only resolved in the execute stage, so the two instructions
+/// fetched after it are flushed and the pipeline stalls for
+/// [`FLUSH_PENALTY`](Self::FLUSH_PENALTY) cycles.
+///
+/// The pipeline is a model for teaching, the `VirtualMachine` does not run
+/// any faster or slower because of it.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Pipeline,
+///     PipelineSlot,
+/// };
+///
+/// // The instructions executed by `+[-].`, the loop running once
+/// let mut pipeline = Pipeline::new();
+/// for program_counter in [0, 1, 2, 3, 4] {
+///     pipeline.issue(program_counter);
+/// }
+///
+/// assert_eq!(
+///     pipeline.stages(),
+///     [
+///         PipelineSlot::Instruction(4),
+///         PipelineSlot::Instruction(3),
+///         PipelineSlot::Instruction(2),
+///     ]
+/// );
+/// let stats = pipeline.stats();
+/// assert_eq!(stats.instructions, 5);
+/// assert_eq!(stats.flushes, 0);
+/// assert_eq!(stats.cycles, 7);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pipeline {
+    stages:       [PipelineSlot; 3],
+    cycles:       usize,
+    instructions: usize,
+    stall_cycles: usize,
+    previous:     Option<usize>,
+    jump_flushes: BTreeMap<usize, usize>,
+}
+
+impl Pipeline {
+    /// The number of cycles lost when a jump flushes the pipeline
+    pub const FLUSH_PENALTY: usize = 2;
+
+    /// Create an empty pipeline
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the pipeline the next instruction the program executes
+    ///
+    /// If the instruction does not follow the previous one in the program,
+    /// the previous one was a jump, and the pipeline stalls before the
+    /// instruction is fetched.
+    ///
+    /// # Arguments
+    ///
+    /// * `program_counter` - The position of the instruction in the program
+    ///
+    /// # Returns
+    ///
+    /// The number of cycles the instruction took to enter the pipeline: one,
+    /// plus the stall if a jump flushed it.
+    pub fn issue(&mut self, program_counter: usize) -> usize {
+        let mut cycles = 1;
+        if let Some(previous) = self.previous {
+            if previous + 1 != program_counter {
+                for _ in 0..Self::FLUSH_PENALTY {
+                    self.advance(PipelineSlot::Bubble);
+                }
+                self.stall_cycles += Self::FLUSH_PENALTY;
+                *self.jump_flushes.entry(previous).or_insert(0) += 1;
+                cycles += Self::FLUSH_PENALTY;
+            }
+        }
+        self.advance(PipelineSlot::Instruction(program_counter));
+        self.instructions += 1;
+        self.previous = Some(program_counter);
+        cycles
+    }
+
+    fn advance(&mut self, fetched: PipelineSlot) {
+        self.stages = [fetched, self.stages[0], self.stages[1]];
+        self.cycles += 1;
+    }
+
+    /// What each stage holds in the latest cycle, in the order of
+    /// [`PipelineStage::ALL`]
+    #[must_use]
+    pub const fn stages(&self) -> [PipelineSlot; 3] {
+        self.stages
+    }
+
+    /// What `stage` holds in the latest cycle
+    #[must_use]
+    pub const fn slot(&self, stage: PipelineStage) -> PipelineSlot {
+        self.stages[stage as usize]
+    }
+
+    /// The number of cycles so far
+    #[must_use]
+    pub const fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// The totals of the pipeline, as if it were drained now
+    ///
+    /// Draining takes the cycles the last instruction needs to leave the
+    /// execute stage.
+    #[must_use]
+    pub fn stats(&self) -> PipelineStats {
+        let drain = if self.instructions == 0 {
+            0
+        } else {
+            PipelineStage::ALL.len() - 1
+        };
+        PipelineStats {
+            instructions: self.instructions,
+            cycles:       self.cycles + drain,
+            stall_cycles: self.stall_cycles,
+            flushes:      self.jump_flushes.values().sum(),
+        }
+    }
+
+    /// The number of times each jump flushed the pipeline, by the position
+    /// of the jump in the program
+    #[must_use]
+    pub const fn jump_flushes(&self) -> &BTreeMap<usize, usize> {
+        &self.jump_flushes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{
+        CoreMachine,
+        Program,
+    };
+
+    #[test]
+    fn test_flushes_on_jumps() {
+        // `++[-]` runs the loop twice, so `]` at 4 jumps back once
+        let mut machine = CoreMachine::new(Program::from("++[-]"), 4, || None, |_| {}).unwrap();
+        let mut trace = Vec::new();
+        while !machine.is_halted() {
+            trace.push(machine.program_counter());
+            machine.execute_instruction();
+        }
+        assert_eq!(trace, [0, 1, 2, 3, 4, 3, 4]);
+
+        let mut pipeline = Pipeline::new();
+        let cycles: Vec<usize> = trace.into_iter().map(|pc| pipeline.issue(pc)).collect();
+        assert_eq!(cycles, [1, 1, 1, 1, 1, 3, 1]);
+        assert_eq!(pipeline.jump_flushes(), &BTreeMap::from([(4, 1)]));
+        assert_eq!(pipeline.slot(PipelineStage::Execute), PipelineSlot::Bubble);
+
+        let stats = pipeline.stats();
+        assert_eq!(stats.instructions, 7);
+        assert_eq!(stats.stall_cycles, 2);
+        assert_eq!(stats.cycles, 7 + 2 + 2);
+        assert_eq!(
+            stats.to_string(),
+            "7 instructions in 11 cycles (1.57 per instruction), 2 stall cycles from 1 flushes"
+        );
+    }
+}
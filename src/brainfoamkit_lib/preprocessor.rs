@@ -0,0 +1,958 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::{
+    FileError,
+    ParseError,
+    Program,
+};
+
+/// The name used in the source map for sources that do not come from a file
+const INLINE_SOURCE_NAME: &str = "<input>";
+
+/// The location of a single character in the original source files
+///
+/// Every character emitted by the [`Preprocessor`](struct.Preprocessor.html)
+/// carries a `SourceLocation` that points back to the file, line and column it
+/// came from. Characters expanded from a macro point at the macro definition.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::Preprocessor;
+///
+/// let source = Preprocessor::new().process_str("+\n>").unwrap();
+/// let location = source.source_map().location(2).unwrap();
+///
+/// assert_eq!(location.line(), 2);
+/// assert_eq!(location.column(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation {
+    file:   usize,
+    line:   usize,
+    column: usize,
+}
+
+impl SourceLocation {
+    /// The index of the file in the [`SourceMap`](struct.SourceMap.html)
+    #[must_use]
+    pub const fn file(&self) -> usize {
+        self.file
+    }
+
+    /// The one-based line number of the character
+    #[must_use]
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The one-based column number of the character
+    #[must_use]
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+}
+
+/// Maps every character of preprocessed source back to its origin
+///
+/// The `SourceMap` holds one [`SourceLocation`](struct.SourceLocation.html)
+/// for every character in the preprocessed output. Since a `Program` is built
+/// one instruction per character, the index of an instruction in the
+/// `Program` can be used directly to look up its location.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::Preprocessor;
+///
+/// let source = Preprocessor::new()
+///     .process_str("@define inc ++\n@use inc")
+///     .unwrap();
+///
+/// assert_eq!(source.source(), "\n++");
+/// assert_eq!(source.source_map().describe(1).unwrap(), "<input>:1:13");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    files:     Vec<String>,
+    locations: Vec<SourceLocation>,
+}
+
+impl SourceMap {
+    /// Get the location of the character at the given index
+    ///
+    /// # Returns
+    ///
+    /// The `SourceLocation` of the character or `None` if the index is out of
+    /// bounds.
+    #[must_use]
+    pub fn location(&self, index: usize) -> Option<SourceLocation> {
+        self.locations.get(index).copied()
+    }
+
+    /// Get the name of the file a `SourceLocation` refers to
+    #[must_use]
+    pub fn file_name(&self, location: SourceLocation) -> &str {
+        self.files
+            .get(location.file)
+            .map_or(INLINE_SOURCE_NAME, String::as_str)
+    }
+
+    /// Describe the location of the character at the given index
+    ///
+    /// The description has the form `file:line:column`.
+    ///
+    /// # Returns
+    ///
+    /// The description or `None` if the index is out of bounds.
+    #[must_use]
+    pub fn describe(&self, index: usize) -> Option<String> {
+        self.location(index).map(|location| {
+            format!(
+                "{}:{}:{}",
+                self.file_name(location),
+                location.line,
+                location.column
+            )
+        })
+    }
+
+    /// The number of characters covered by the `SourceMap`
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Returns `true` if the `SourceMap` covers no characters
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    fn add_file(&mut self, name: String) -> usize {
+        if let Some(index) = self.files.iter().position(|file| *file == name) {
+            index
+        } else {
+            self.files.push(name);
+            self.files.len() - 1
+        }
+    }
+}
+
+/// The contents of a file to load into the tape before a program runs
+///
+/// A `DataSegment` is declared with a `@data "file.bin" -> cells 100..`
+/// directive, see [`Preprocessor`](struct.Preprocessor.html), or in the `data`
+/// of a program in a [`Manifest`](crate::project::Manifest), and loaded with
+/// [`VirtualMachine::load_data`](crate::VirtualMachine::load_data).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::Preprocessor;
+/// use tempfile::tempdir;
+///
+/// let directory = tempdir().unwrap();
+/// std::fs::write(directory.path().join("table.bin"), [1, 2, 3]).unwrap();
+///
+/// let source = Preprocessor::new()
+///     .base_dir(directory.path())
+///     .process_str("@data \"table.bin\" -> cells 10..\n>")
+///     .unwrap();
+/// let segment = &source.data_segments()[0];
+///
+/// assert_eq!(segment.start(), 10);
+/// assert_eq!(segment.bytes(), [1, 2, 3]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataSegment {
+    path:  PathBuf,
+    start: usize,
+    bytes: Vec<u8>,
+}
+
+impl DataSegment {
+    /// Read the bytes to load from a file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to read
+    /// * `start` - The cell to load the first byte into
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn read(path: &Path, start: usize) -> Result<Self, FileError> {
+        let bytes = fs::read(path).map_err(|source| FileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            start,
+            bytes,
+        })
+    }
+
+    /// The file the bytes were read from
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The cell the first byte is loaded into
+    #[must_use]
+    pub const fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The bytes to load, one per cell
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// The result of running the [`Preprocessor`](struct.Preprocessor.html)
+///
+/// Holds the expanded source text together with its
+/// [`SourceMap`](struct.SourceMap.html) and the
+/// [`DataSegment`](struct.DataSegment.html)s it declares.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreprocessedSource {
+    source:     String,
+    source_map: SourceMap,
+    data:       Vec<DataSegment>,
+}
+
+impl PreprocessedSource {
+    /// The expanded source text
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The `SourceMap` for the expanded source text
+    #[must_use]
+    pub const fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// The data declared by `@data` directives, in the order they appear
+    #[must_use]
+    pub fn data_segments(&self) -> &[DataSegment] {
+        &self.data
+    }
+
+    /// Convert the expanded source into a `Program`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Preprocessor,
+    ///     Program,
+    /// };
+    ///
+    /// let source = Preprocessor::new()
+    ///     .process_str("@define two ++\n@use two @use two")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(source.program(), Program::from("\n++ ++"));
+    /// ```
+    #[must_use]
+    pub fn program(&self) -> Program {
+        Program::from(self.source.as_str())
+    }
+
+    fn push(&mut self, character: char, location: SourceLocation) {
+        self.source.push(character);
+        self.source_map.locations.push(location);
+    }
+}
+
+impl Display for PreprocessedSource {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// A single character of a macro body along with its origin
+type MacroBody = Vec<(char, SourceLocation)>;
+
+/// Textual preprocessor for `BrainFuck` sources
+///
+/// The `Preprocessor` runs before a `Program` is parsed and allows larger
+/// programs to be split across files and to reuse snippets of code. It
+/// understands four directives:
+///
+/// | Directive | Effect |
+/// | :-------- | :----- |
+/// | `@define name body` | Define a macro called `name` holding the rest of the line |
+/// | `@use name` | Expand the macro called `name` in place |
+/// | `@include "file.bf"` | Insert the preprocessed contents of `file.bf` |
+/// | `@data "file.bin" -> cells 100..` | Load the bytes of `file.bin` into the cells from 100 on |
+///
+/// `@define`, `@include` and `@data` must be the only thing on their line.
+/// `@use` may appear anywhere, including inside macro bodies. Included paths
+/// and data files are resolved relative to the including file, or to the base
+/// directory for sources processed from a string.
+///
+/// The range of a `@data` directive is either open, `100..`, or closed,
+/// `100..200`, in which case the file may not hold more bytes than the range
+/// has cells. The data is collected in the
+/// [`data_segments`](struct.PreprocessedSource.html#method.data_segments) of
+/// the result, ready to be loaded into the tape before the program runs.
+///
+/// Every file is included at most once: an `@include` of a file that was
+/// already included is skipped, so that two files can include the same file
+/// of macros and both be included in turn. Cycles between includes or macros
+/// are still detected and reported as errors.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::Preprocessor;
+///
+/// let source = Preprocessor::new()
+///     .process_str("@define clear [-]\n+++@use clear")
+///     .unwrap();
+///
+/// assert_eq!(source.source(), "\n+++[-]");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+    base_dir:       Option<PathBuf>,
+    macros:         HashMap<String, MacroBody>,
+    include_stack:  Vec<PathBuf>,
+    included:       HashSet<PathBuf>,
+    expansion_path: Vec<String>,
+}
+
+impl Preprocessor {
+    /// Create a new `Preprocessor` with no macros defined
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the directory that includes in string sources are resolved against
+    ///
+    /// If this is not set, the current working directory is used.
+    #[must_use]
+    pub fn base_dir<P: AsRef<Path>>(mut self, base_dir: P) -> Self {
+        self.base_dir = Some(base_dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Preprocess a source held in memory
+    ///
+    /// # Errors
+    ///
+    /// * If a directive is malformed
+    /// * If an undefined macro is used or a macro is defined twice
+    /// * If an included file or a data file can not be read
+    /// * If a data file does not fit in the range of its directive
+    /// * If a cycle between includes or macros is found
+    pub fn process_str(&mut self, source: &str) -> Result<PreprocessedSource, ParseError> {
+        let mut output = PreprocessedSource::default();
+        let file = output.source_map.add_file(INLINE_SOURCE_NAME.to_string());
+        let base_dir = self.base_dir.clone().unwrap_or_default();
+
+        self.process_source(source, file, &base_dir, &mut output)?;
+
+        Ok(output)
+    }
+
+    /// Preprocess a source file
+    ///
+    /// # Errors
+    ///
+    /// * If the file can not be read
+    /// * If any of the errors listed for [`process_str()`](#method.process_str)
+    ///   occur
+    pub fn process_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<PreprocessedSource, ParseError> {
+        let mut output = PreprocessedSource::default();
+        self.include(path.as_ref(), &mut output)?;
+
+        Ok(output)
+    }
+
+    fn include(&mut self, path: &Path, output: &mut PreprocessedSource) -> Result<(), ParseError> {
+        let unreadable = |source| ParseError::Include {
+            path: path.to_path_buf(),
+            source,
+        };
+        let canonical = path.canonicalize().map_err(unreadable)?;
+
+        if self.include_stack.contains(&canonical) {
+            let chain = self
+                .include_stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(ParseError::IncludeCycle(chain));
+        }
+        if !self.included.insert(canonical.clone()) {
+            return Ok(());
+        }
+
+        let source = fs::read_to_string(&canonical).map_err(unreadable)?;
+        let file = output.source_map.add_file(path.display().to_string());
+        let base_dir = canonical
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        self.include_stack.push(canonical);
+        let result = self.process_source(&source, file, &base_dir, output);
+        self.include_stack.pop();
+
+        result
+    }
+
+    fn process_source(
+        &mut self,
+        source: &str,
+        file: usize,
+        base_dir: &Path,
+        output: &mut PreprocessedSource,
+    ) -> Result<(), ParseError> {
+        for (line_index, line) in source.split_inclusive('\n').enumerate() {
+            let line_number = line_index + 1;
+            let content = line.trim_end_matches(['\n', '\r']);
+            let trimmed = content.trim_start();
+            let indent = content.len() - trimmed.len();
+
+            if trimmed.starts_with("@define") {
+                self.define(content, file, line_number)?;
+            } else if let Some(rest) = trimmed.strip_prefix("@include") {
+                let included =
+                    parse_include_path(rest).ok_or_else(|| ParseError::MalformedInclude {
+                        line:   line_number,
+                        column: indent + 1,
+                        found:  rest.trim().to_string(),
+                    })?;
+                self.include(&base_dir.join(included), output)?;
+            } else if let Some(rest) = trimmed.strip_prefix("@data") {
+                let (path, start, end) =
+                    parse_data(rest).ok_or_else(|| ParseError::MalformedData {
+                        line:   line_number,
+                        column: indent + 1,
+                        found:  rest.trim().to_string(),
+                    })?;
+                output
+                    .data
+                    .push(load_data(&base_dir.join(path), start, end)?);
+            } else {
+                let characters = content
+                    .chars()
+                    .enumerate()
+                    .map(|(column, character)| {
+                        (
+                            character,
+                            SourceLocation {
+                                file,
+                                line: line_number,
+                                column: column + 1,
+                            },
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                self.expand(&characters, output)?;
+            }
+
+            // Keep the line structure of the source intact
+            if line.len() > content.len() {
+                let column = content.chars().count() + 1;
+                for character in line[content.len()..].chars() {
+                    output.push(
+                        character,
+                        SourceLocation {
+                            file,
+                            line: line_number,
+                            column,
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn define(&mut self, content: &str, file: usize, line: usize) -> Result<(), ParseError> {
+        let characters = content.chars().collect::<Vec<_>>();
+        let skip_whitespace = |mut index: usize| {
+            while characters.get(index).is_some_and(|c| c.is_whitespace()) {
+                index += 1;
+            }
+            index
+        };
+
+        let keyword_end = skip_whitespace(0) + "@define".len();
+        let name_start = skip_whitespace(keyword_end);
+        if name_start == keyword_end {
+            return Err(ParseError::MalformedDefine {
+                line,
+                column: keyword_end + 1,
+            });
+        }
+
+        let mut name_end = name_start;
+        while characters.get(name_end).is_some_and(|c| !c.is_whitespace()) {
+            name_end += 1;
+        }
+        let name = characters[name_start..name_end].iter().collect::<String>();
+
+        if !is_valid_macro_name(&name) {
+            return Err(ParseError::InvalidMacroName {
+                name,
+                line,
+                column: name_start + 1,
+            });
+        }
+        if self.macros.contains_key(&name) {
+            return Err(ParseError::MacroRedefined(name));
+        }
+
+        let body_start = skip_whitespace(name_end);
+        let body = characters
+            .iter()
+            .enumerate()
+            .skip(body_start)
+            .map(|(column, character)| {
+                (
+                    *character,
+                    SourceLocation {
+                        file,
+                        line,
+                        column: column + 1,
+                    },
+                )
+            })
+            .collect();
+
+        self.macros.insert(name, body);
+
+        Ok(())
+    }
+
+    fn expand(
+        &mut self,
+        characters: &[(char, SourceLocation)],
+        output: &mut PreprocessedSource,
+    ) -> Result<(), ParseError> {
+        let mut index = 0;
+
+        while index < characters.len() {
+            if let Some((name, consumed)) = parse_use(&characters[index..]) {
+                let location = characters[index].1;
+                self.expand_macro(&name, location, output)?;
+                index += consumed;
+            } else {
+                let (character, location) = characters[index];
+                output.push(character, location);
+                index += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn expand_macro(
+        &mut self,
+        name: &str,
+        location: SourceLocation,
+        output: &mut PreprocessedSource,
+    ) -> Result<(), ParseError> {
+        if self
+            .expansion_path
+            .iter()
+            .any(|expanding| expanding == name)
+        {
+            let chain = self
+                .expansion_path
+                .iter()
+                .map(String::as_str)
+                .chain(std::iter::once(name))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(ParseError::MacroCycle(chain));
+        }
+
+        let Some(body) = self.macros.get(name).cloned() else {
+            return Err(ParseError::UndefinedMacro {
+                name:   name.to_string(),
+                line:   location.line,
+                column: location.column,
+            });
+        };
+
+        self.expansion_path.push(name.to_string());
+        let result = self.expand(&body, output);
+        self.expansion_path.pop();
+
+        result
+    }
+}
+
+/// Check whether a macro name only uses letters, digits and underscores
+fn is_valid_macro_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+/// Parse the quoted path of an `@include` directive
+fn parse_include_path(rest: &str) -> Option<&str> {
+    rest.trim()
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|path| !path.is_empty() && !path.contains('"'))
+}
+
+/// Parse the path and range of a `@data "file" -> cells N..` directive
+///
+/// Returns the path, the first cell and, for a closed range, the end of the
+/// range.
+fn parse_data(rest: &str) -> Option<(&str, usize, Option<usize>)> {
+    let (path, range) = rest.split_once("->")?;
+    let path = parse_include_path(path)?;
+    let range = range.trim().strip_prefix("cells")?;
+    if !range.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    let (start, end) = range.trim().split_once("..")?;
+    let start = start.parse().ok()?;
+    let end = match end {
+        "" => None,
+        end => Some(end.parse().ok().filter(|end| *end >= start)?),
+    };
+    Some((path, start, end))
+}
+
+/// Read the file of a `@data` directive
+fn load_data(path: &Path, start: usize, end: Option<usize>) -> Result<DataSegment, ParseError> {
+    let bytes = fs::read(path).map_err(|source| ParseError::Include {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if let Some(cells) = end.map(|end| end - start) {
+        if bytes.len() > cells {
+            return Err(ParseError::DataTooLarge {
+                path: path.to_path_buf(),
+                bytes: bytes.len(),
+                cells,
+            });
+        }
+    }
+
+    Ok(DataSegment {
+        path: path.to_path_buf(),
+        start,
+        bytes,
+    })
+}
+
+/// Parse an `@use name` token at the start of the characters
+///
+/// Returns the macro name and the number of characters consumed.
+fn parse_use(characters: &[(char, SourceLocation)]) -> Option<(String, usize)> {
+    const KEYWORD: &str = "@use";
+
+    let keyword_length = KEYWORD.chars().count();
+    if characters.len() <= keyword_length
+        || !characters
+            .iter()
+            .map(|(character, _)| *character)
+            .take(keyword_length)
+            .eq(KEYWORD.chars())
+    {
+        return None;
+    }
+
+    let spaces = characters[keyword_length..]
+        .iter()
+        .take_while(|(character, _)| *character == ' ' || *character == '\t')
+        .count();
+    if spaces == 0 {
+        return None;
+    }
+
+    let name = characters[keyword_length + spaces..]
+        .iter()
+        .map(|(character, _)| *character)
+        .take_while(|character| character.is_ascii_alphanumeric() || *character == '_')
+        .collect::<String>();
+    if name.is_empty() {
+        return None;
+    }
+
+    let consumed = keyword_length + spaces + name.chars().count();
+    Some((name, consumed))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_plain_source_is_unchanged() {
+        let source = Preprocessor::new().process_str("++[>+<-]\n.").unwrap();
+
+        assert_eq!(source.source(), "++[>+<-]\n.");
+        assert_eq!(source.source_map().len(), 10);
+        assert_eq!(source.source_map().describe(9).unwrap(), "<input>:2:1");
+    }
+
+    #[test]
+    fn test_define_and_use() {
+        let source = Preprocessor::new()
+            .process_str("@define inc +++\n>@use inc<")
+            .unwrap();
+
+        assert_eq!(source.source(), "\n>+++<");
+        assert_eq!(source.program(), Program::from("\n>+++<"));
+    }
+
+    #[test]
+    fn test_nested_macros() {
+        let source = Preprocessor::new()
+            .process_str("@define one +\n@define two @use one@use one\n@use two")
+            .unwrap();
+
+        assert_eq!(source.source(), "\n\n++");
+    }
+
+    #[test]
+    fn test_macro_source_map_points_to_definition() {
+        let source = Preprocessor::new()
+            .process_str("@define clear [-]\n@use clear")
+            .unwrap();
+
+        assert_eq!(source.source(), "\n[-]");
+        let location = source.source_map().location(1).unwrap();
+        assert_eq!(location.line(), 1);
+        assert_eq!(location.column(), 15);
+        let location = source.source_map().location(3).unwrap();
+        assert_eq!(location.column(), 17);
+    }
+
+    #[test]
+    fn test_undefined_macro() {
+        let result = Preprocessor::new().process_str("@use missing");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_duplicate_macro() {
+        let result = Preprocessor::new().process_str("@define a +\n@define a -");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_macro_cycle() {
+        let result = Preprocessor::new().process_str("@define a @use b\n@define b @use a\n@use a");
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("a -> b -> a"), "{error}");
+    }
+
+    #[test]
+    fn test_use_without_name_is_kept() {
+        let source = Preprocessor::new().process_str("@use").unwrap();
+
+        assert_eq!(source.source(), "@use");
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = tempdir().unwrap();
+        let mut library = fs::File::create(dir.path().join("lib.bf")).unwrap();
+        writeln!(library, "@define clear [-]").unwrap();
+        writeln!(library, "+").unwrap();
+
+        let source = Preprocessor::new()
+            .base_dir(dir.path())
+            .process_str("@include \"lib.bf\"\n@use clear")
+            .unwrap();
+
+        assert_eq!(source.source(), "\n+\n\n[-]");
+        let location = source.source_map().location(1).unwrap();
+        assert!(source.source_map().file_name(location).ends_with("lib.bf"));
+        assert_eq!(location.line(), 2);
+    }
+
+    #[test]
+    fn test_include_relative_to_including_file() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        fs::write(dir.path().join("main.bf"), "@include \"nested/a.bf\"\n").unwrap();
+        fs::write(dir.path().join("nested/a.bf"), "@include \"b.bf\"\n").unwrap();
+        fs::write(dir.path().join("nested/b.bf"), "+-").unwrap();
+
+        let source = Preprocessor::new()
+            .process_file(dir.path().join("main.bf"))
+            .unwrap();
+
+        assert_eq!(source.source(), "+-\n\n");
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.bf"), "@include \"b.bf\"\n").unwrap();
+        fs::write(dir.path().join("b.bf"), "@include \"a.bf\"\n").unwrap();
+
+        let result = Preprocessor::new().process_file(dir.path().join("a.bf"));
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("Include cycle detected"), "{error}");
+    }
+
+    #[test]
+    fn test_include_once() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("shared.bf"), "@define inc +\n").unwrap();
+        fs::write(dir.path().join("left.bf"), "@include \"shared.bf\"\n").unwrap();
+        fs::write(dir.path().join("right.bf"), "@include \"shared.bf\"\n").unwrap();
+
+        let source = Preprocessor::new()
+            .base_dir(dir.path())
+            .process_str("@include \"left.bf\"\n@include \"right.bf\"\n@use inc")
+            .unwrap();
+
+        assert_eq!(source.source().trim(), "+");
+    }
+
+    #[test]
+    fn test_missing_include() {
+        let dir = tempdir().unwrap();
+        let result = Preprocessor::new()
+            .base_dir(dir.path())
+            .process_str("@include \"missing.bf\"");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_malformed_include() {
+        let result = Preprocessor::new().process_str("@include missing.bf");
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_data() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        fs::write(dir.path().join("nested/a.bin"), [1, 2]).unwrap();
+        fs::write(dir.path().join("nested/b.bin"), [3]).unwrap();
+        fs::write(
+            dir.path().join("nested/main.bf"),
+            "  @data \"a.bin\" -> cells 0..\n@data \"b.bin\"->cells 5..6\n+",
+        )
+        .unwrap();
+
+        let source = Preprocessor::new()
+            .process_file(dir.path().join("nested/main.bf"))
+            .unwrap();
+
+        assert_eq!(source.source(), "\n\n+");
+        assert_eq!(source.source_map().location(2).unwrap().line(), 3);
+        let segments = source.data_segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start(), segments[0].bytes()), (0, &[1, 2][..]));
+        assert_eq!((segments[1].start(), segments[1].bytes()), (5, &[3][..]));
+        assert!(segments[1].path().ends_with("b.bin"));
+    }
+
+    #[test]
+    fn test_data_too_large() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.bin"), [1, 2, 3]).unwrap();
+        let result = Preprocessor::new()
+            .base_dir(dir.path())
+            .process_str("@data \"a.bin\" -> cells 4..6");
+
+        assert!(matches!(
+            result,
+            Err(ParseError::DataTooLarge {
+                bytes: 3,
+                cells: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_missing_data() {
+        let dir = tempdir().unwrap();
+        let result = Preprocessor::new()
+            .base_dir(dir.path())
+            .process_str("@data \"missing.bin\" -> cells 0..");
+
+        assert!(matches!(result, Err(ParseError::Include { .. })));
+    }
+
+    #[test]
+    fn test_malformed_data() {
+        for directive in [
+            "@data a.bin -> cells 0..",
+            "@data \"a.bin\" cells 0..",
+            "@data \"a.bin\" -> cell 0..",
+            "@data \"a.bin\" -> cells0..",
+            "@data \"a.bin\" -> cells 0",
+            "@data \"a.bin\" -> cells x..",
+            "@data \"a.bin\" -> cells 4..2",
+        ] {
+            let result = Preprocessor::new().process_str(&format!("+\n {directive}"));
+
+            assert!(
+                matches!(
+                    result,
+                    Err(ParseError::MalformedData {
+                        line: 2,
+                        column: 2,
+                        ..
+                    })
+                ),
+                "{directive}"
+            );
+        }
+    }
+}
@@ -0,0 +1,560 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    path::Path,
+};
+
+use serde_json::{
+    json,
+    Value,
+};
+
+use crate::{
+    format::human_count,
+    vm_reader::VMReader,
+    vm_writer::VMWriter,
+    FileError,
+    VMError,
+    VirtualMachine,
+};
+
+/// A span of execution recorded by the [`Profiler`](struct.Profiler.html)
+///
+/// A span covers either the whole run of the program or a single execution of
+/// a loop, from entering its body to leaving it. Time is measured in executed
+/// instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileSpan {
+    entry: Option<usize>,
+    start: usize,
+    end:   usize,
+    depth: usize,
+}
+
+impl ProfileSpan {
+    /// The position of the loop's `[` in the `Program`, or `None` for the span
+    /// covering the whole program
+    #[must_use]
+    pub const fn entry(&self) -> Option<usize> {
+        self.entry
+    }
+
+    /// The step at which the span started
+    #[must_use]
+    pub const fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The step at which the span ended
+    #[must_use]
+    pub const fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The nesting depth of the span, zero for the whole program
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The name of the span as shown in trace viewers
+    #[must_use]
+    pub fn name(&self) -> String {
+        self.entry
+            .map_or_else(|| String::from("program"), |entry| format!("loop@{entry}"))
+    }
+}
+
+/// Records loop spans while driving a `VirtualMachine`
+///
+/// The `Profiler` executes a `VirtualMachine` one instruction at a time and
+/// records a [`ProfileSpan`](struct.ProfileSpan.html) for every loop that is
+/// entered, nested inside a span for the whole program. The result can be
+/// exported to the Chrome trace format (for `chrome://tracing` or Perfetto)
+/// or to the speedscope format (for <https://www.speedscope.app>).
+///
+/// Timestamps are measured in executed instructions rather than wall clock
+/// time, so profiles are deterministic. In the Chrome trace, every instruction
+/// is shown as one microsecond.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Profiler,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("++[>++[-]<-]"))
+///     .build()
+///     .unwrap();
+///
+/// let mut profiler = Profiler::new();
+/// profiler.run(&mut machine).unwrap();
+///
+/// // The program, the outer loop and two runs of the inner loop
+/// assert_eq!(profiler.spans().len(), 4);
+/// assert!(profiler.chrome_trace().contains("\"name\":\"loop@2\""));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    spans: Vec<ProfileSpan>,
+    open:  Vec<ProfileSpan>,
+}
+
+impl Profiler {
+    /// Create a new `Profiler` without any recorded spans
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Execute a single instruction of the `VirtualMachine` and record any
+    /// loop that was entered or left
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction).
+    pub fn step<R, W>(&mut self, machine: &mut VirtualMachine<R, W>) -> Result<(), VMError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        if self.open.is_empty() {
+            self.open.push(ProfileSpan {
+                entry: None,
+                start: machine.steps(),
+                end:   machine.steps(),
+                depth: 0,
+            });
+        }
+
+        let depth = machine.loop_stack().len();
+        let start = machine.steps();
+        machine.execute_instruction()?;
+        let loop_stack = machine.loop_stack();
+
+        if loop_stack.len() > depth {
+            self.open.push(ProfileSpan {
+                entry: loop_stack.last().map(|frame| frame.entry()),
+                start,
+                end: start,
+                depth: loop_stack.len(),
+            });
+        } else if loop_stack.len() < depth {
+            self.close(machine.steps());
+        }
+
+        Ok(())
+    }
+
+    /// Execute the `VirtualMachine` until it halts, recording every loop
+    ///
+    /// Breaks on input and output are ignored. When the run ends, every span
+    /// that is still open is closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction).
+    /// The spans recorded up to the error are kept.
+    pub fn run<R, W>(&mut self, machine: &mut VirtualMachine<R, W>) -> Result<(), VMError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        let mut result = Ok(());
+        while !machine.is_halted() {
+            if let Err(error) = self.step(machine) {
+                result = Err(error);
+                break;
+            }
+        }
+        self.finish(machine.steps());
+        result
+    }
+
+    /// Close every span that is still open at the given step
+    pub fn finish(&mut self, step: usize) {
+        while !self.open.is_empty() {
+            self.close(step);
+        }
+    }
+
+    /// The spans closed so far, in the order they ended
+    #[must_use]
+    pub fn spans(&self) -> &[ProfileSpan] {
+        &self.spans
+    }
+
+    /// Summarize the recorded spans as a table of the loops that executed the
+    /// most instructions
+    ///
+    /// Every loop is listed once, with the number of times it ran and the
+    /// instructions executed inside it, nested loops included, as a share of
+    /// the whole run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Profiler,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("++[>++[-]<-]"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut profiler = Profiler::new();
+    /// profiler.run(&mut machine).unwrap();
+    ///
+    /// assert_eq!(
+    ///     profiler.summary(),
+    ///     "25 steps\nloop@2       1 runs      23 steps  92.0%\nloop@6       2 \
+    ///      runs      10 steps  40.0%\n"
+    /// );
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn summary(&self) -> String {
+        let total = self
+            .spans
+            .iter()
+            .filter(|span| span.entry.is_none())
+            .map(|span| span.end - span.start)
+            .sum::<usize>();
+
+        let mut summary = format!("{} steps\n", human_count(total));
+        for (entry, (runs, steps)) in self.loops() {
+            let share = if total == 0 {
+                0.0
+            } else {
+                steps as f64 * 100.0 / total as f64
+            };
+            let _ = writeln!(
+                summary,
+                "{:<10} {:>3} runs {:>7} steps {share:>5.1}%",
+                format!("loop@{entry}"),
+                human_count(runs),
+                human_count(steps),
+            );
+        }
+        summary
+    }
+
+    /// The position of the `[` of the loop that executed the most
+    /// instructions, nested loops included
+    ///
+    /// Ties go to the loop that comes first in the program. Returns `None` if
+    /// no loop was entered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Profiler,
+    ///     Program,
+    ///     VirtualMachine,
+    /// };
+    ///
+    /// let mut machine = VirtualMachine::builder()
+    ///     .input_device(std::io::stdin())
+    ///     .program(Program::from("+[-]+++[-]"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut profiler = Profiler::new();
+    /// profiler.run(&mut machine).unwrap();
+    ///
+    /// assert_eq!(profiler.hottest_loop(), Some(7));
+    /// ```
+    #[must_use]
+    pub fn hottest_loop(&self) -> Option<usize> {
+        self.loops().first().map(|(entry, _)| *entry)
+    }
+
+    /// Render the recorded spans in the Chrome trace event format
+    ///
+    /// Every span becomes a complete (`"X"`) event on a single thread, so
+    /// nested loops are shown as nested slices.
+    #[must_use]
+    pub fn chrome_trace(&self) -> String {
+        let mut spans = self.spans.clone();
+        spans.sort_by_key(|span| (span.start, span.depth));
+
+        let events: Vec<Value> = spans
+            .iter()
+            .map(|span| {
+                json!({
+                    "name": span.name(),
+                    "cat": "loop",
+                    "ph": "X",
+                    "ts": span.start,
+                    "dur": span.end - span.start,
+                    "pid": 1,
+                    "tid": 1,
+                })
+            })
+            .collect();
+
+        json!({ "traceEvents": events }).to_string()
+    }
+
+    /// Render the recorded spans in the speedscope evented profile format
+    #[must_use]
+    pub fn speedscope(&self) -> String {
+        let mut frames: Vec<String> = Vec::new();
+        // (step, is_opening, order, frame)
+        let mut events: Vec<(usize, bool, usize, usize)> = Vec::new();
+        for span in &self.spans {
+            let name = span.name();
+            let frame = frames
+                .iter()
+                .position(|existing| *existing == name)
+                .unwrap_or_else(|| {
+                    frames.push(name);
+                    frames.len() - 1
+                });
+            events.push((span.start, true, span.depth, frame));
+            events.push((span.end, false, usize::MAX - span.depth, frame));
+        }
+        // Closing events sort before opening events at the same step, outer
+        // spans open first and inner spans close first.
+        events.sort_unstable();
+
+        let start = self.spans.iter().map(|span| span.start).min().unwrap_or(0);
+        let end = self.spans.iter().map(|span| span.end).max().unwrap_or(0);
+        let frames: Vec<Value> = frames.iter().map(|name| json!({ "name": name })).collect();
+        let events: Vec<Value> = events
+            .iter()
+            .map(|(at, opening, _, frame)| {
+                json!({
+                    "type": if *opening { "O" } else { "C" },
+                    "frame": frame,
+                    "at": at,
+                })
+            })
+            .collect();
+
+        json!({
+            "$schema": "https://www.speedscope.app/file-format-schema.json",
+            "shared": { "frames": frames },
+            "profiles": [{
+                "type": "evented",
+                "name": "brainfoamkit",
+                "unit": "none",
+                "startValue": start,
+                "endValue": end,
+                "events": events,
+            }],
+            "exporter": "brainfoamkit",
+        })
+        .to_string()
+    }
+
+    /// Write the recorded spans to a file in the Chrome trace event format
+    ///
+    /// The file can be loaded in `chrome://tracing` or Perfetto.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn export_chrome_trace<P: AsRef<Path>>(&self, path: P) -> Result<(), FileError> {
+        let path = path.as_ref();
+        fs::write(path, self.chrome_trace()).map_err(|source| FileError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Write the recorded spans to a file in the speedscope format
+    ///
+    /// The file can be loaded in <https://www.speedscope.app>.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn export_speedscope<P: AsRef<Path>>(&self, path: P) -> Result<(), FileError> {
+        let path = path.as_ref();
+        fs::write(path, self.speedscope()).map_err(|source| FileError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Every loop with the number of times it ran and the instructions
+    /// executed inside it, the loop that executed the most first
+    fn loops(&self) -> Vec<(usize, (usize, usize))> {
+        let mut loops: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+        for span in &self.spans {
+            if let Some(entry) = span.entry {
+                let (runs, steps) = loops.entry(entry).or_default();
+                *runs += 1;
+                *steps += span.end - span.start;
+            }
+        }
+        let mut loops: Vec<(usize, (usize, usize))> = loops.into_iter().collect();
+        loops.sort_by_key(|(entry, (_, steps))| (std::cmp::Reverse(*steps), *entry));
+        loops
+    }
+
+    fn close(&mut self, step: usize) {
+        if let Some(mut span) = self.open.pop() {
+            span.end = step;
+            self.spans.push(span);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        vm_reader::MockReader,
+        Program,
+    };
+
+    #[test]
+    fn test_spans() {
+        let mut vm = VirtualMachine::testing("+[>++[-]<-]", b"").unwrap();
+        let mut profiler = Profiler::new();
+        profiler.run(&mut vm).unwrap();
+
+        let spans: Vec<(Option<usize>, usize, usize, usize)> = profiler
+            .spans()
+            .iter()
+            .map(|span| (span.entry(), span.start(), span.end(), span.depth()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(Some(5), 5, 10, 2), (Some(1), 1, 13, 1), (None, 0, 13, 0),]
+        );
+    }
+
+    #[test]
+    fn test_skipped_loop() {
+        let mut vm = VirtualMachine::testing("[+]+", b"").unwrap();
+        let mut profiler = Profiler::new();
+        profiler.run(&mut vm).unwrap();
+
+        assert_eq!(profiler.spans().len(), 1);
+        assert_eq!(profiler.spans()[0].name(), "program");
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let mut vm = VirtualMachine::testing("+[-]", b"").unwrap();
+        let mut profiler = Profiler::new();
+        profiler.run(&mut vm).unwrap();
+
+        let trace: Value = serde_json::from_str(&profiler.chrome_trace()).unwrap();
+        assert_eq!(
+            trace,
+            json!({
+                "traceEvents": [
+                    { "name": "program", "cat": "loop", "ph": "X", "ts": 0, "dur": 4, "pid": 1, "tid": 1 },
+                    { "name": "loop@1", "cat": "loop", "ph": "X", "ts": 1, "dur": 3, "pid": 1, "tid": 1 },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_speedscope() {
+        let mut vm = VirtualMachine::testing("+[-]", b"").unwrap();
+        let mut profiler = Profiler::new();
+        profiler.run(&mut vm).unwrap();
+
+        let profile: Value = serde_json::from_str(&profiler.speedscope()).unwrap();
+        assert_eq!(
+            profile["shared"]["frames"],
+            json!([{ "name": "loop@1" }, { "name": "program" }])
+        );
+        assert_eq!(profile["profiles"][0]["startValue"], 0);
+        assert_eq!(profile["profiles"][0]["endValue"], 4);
+        assert_eq!(
+            profile["profiles"][0]["events"],
+            json!([
+                { "type": "O", "frame": 1, "at": 0 },
+                { "type": "O", "frame": 0, "at": 1 },
+                { "type": "C", "frame": 0, "at": 4 },
+                { "type": "C", "frame": 1, "at": 4 },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_speedscope_sibling_loops() {
+        let mut vm = VirtualMachine::testing("+[-]+[-]", b"").unwrap();
+        let mut profiler = Profiler::new();
+        profiler.run(&mut vm).unwrap();
+
+        let profile: Value = serde_json::from_str(&profiler.speedscope()).unwrap();
+        let events: Vec<(String, u64)> = profile["profiles"][0]["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| {
+                (
+                    event["type"].as_str().unwrap().to_string(),
+                    event["at"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        let expected: Vec<(String, u64)> =
+            [("O", 0), ("O", 1), ("C", 4), ("O", 5), ("C", 8), ("C", 8)]
+                .iter()
+                .map(|(kind, at)| ((*kind).to_string(), *at))
+                .collect();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn test_export() {
+        let mut vm = VirtualMachine::testing("+[-]", b"").unwrap();
+        let mut profiler = Profiler::new();
+        profiler.run(&mut vm).unwrap();
+
+        let directory = tempfile::tempdir().unwrap();
+        let trace = directory.path().join("trace.json");
+        let profile = directory.path().join("profile.speedscope.json");
+        profiler.export_chrome_trace(&trace).unwrap();
+        profiler.export_speedscope(&profile).unwrap();
+
+        assert_eq!(fs::read_to_string(trace).unwrap(), profiler.chrome_trace());
+        assert_eq!(fs::read_to_string(profile).unwrap(), profiler.speedscope());
+    }
+
+    #[test]
+    fn test_run_error_keeps_spans() {
+        let mut vm = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(Vec::new()),
+            })
+            .program(Program::from("+[]"))
+            .limits(crate::Limits::new().max_steps(10))
+            .build()
+            .unwrap();
+        let mut profiler = Profiler::new();
+
+        assert!(profiler.run(&mut vm).is_err());
+        assert_eq!(profiler.spans().len(), 2);
+        assert!(profiler.spans().iter().all(|span| span.end() == 10));
+    }
+}
@@ -3,16 +3,33 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::{
+use alloc::{
+    string::ToString,
+    vec,
+    vec::Vec,
+};
+use core::{
     fmt::{
         self,
         Display,
         Formatter,
     },
-    ops::Index,
+    ops::{
+        Add,
+        AddAssign,
+        Bound,
+        Index,
+        Range,
+        RangeBounds,
+    },
 };
 
-use crate::Instruction;
+use crate::{
+    Dialect,
+    Instruction,
+    ParseError,
+    ProgramStatistics,
+};
 
 /// Structure to hold the program.
 ///
@@ -88,6 +105,8 @@ use crate::Instruction;
 pub struct Program {
     /// The instructions for the program
     instructions: Vec<Instruction>,
+    /// The dialect the program was parsed in
+    dialect:      Dialect,
 }
 
 impl Program {
@@ -249,6 +268,505 @@ impl Program {
             Some(self.instructions.len())
         }
     }
+
+    /// Build the jump table of the program
+    ///
+    /// The jump table maps every `JumpForward` instruction to its matching
+    /// `JumpBackward` instruction and vice versa. The `DefineProcedure` and
+    /// `EndProcedure` instructions of the pbrain dialect are matched the same
+    /// way. All other instructions map to `None`. Building the table also
+    /// validates that the brackets of the program are balanced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Program;
+    ///
+    /// let program = Program::from("+[>[-]<]");
+    /// let jump_table = program.jump_table().unwrap();
+    ///
+    /// assert_eq!(jump_table[1], Some(7));
+    /// assert_eq!(jump_table[7], Some(1));
+    /// assert_eq!(jump_table[3], Some(5));
+    /// assert_eq!(jump_table[0], None);
+    ///
+    /// assert!(Program::from("[[]").jump_table().is_err());
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A vector with one entry per instruction of the program
+    ///
+    /// # Errors
+    ///
+    /// Returns an error pointing at the offending instruction if the brackets
+    /// of the program are not balanced.
+    pub fn jump_table(&self) -> Result<Vec<Option<usize>>, ParseError> {
+        let mut jump_table = vec![None; self.instructions.len()];
+        let mut open_brackets = Vec::new();
+        let mut open_procedures = Vec::new();
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            match instruction {
+                Instruction::JumpForward => open_brackets.push(index),
+                Instruction::JumpBackward => {
+                    let Some(start) = open_brackets.pop() else {
+                        return Err(ParseError::UnmatchedBracket {
+                            bracket:  ']',
+                            position: index,
+                        });
+                    };
+                    jump_table[start] = Some(index);
+                    jump_table[index] = Some(start);
+                }
+                Instruction::DefineProcedure => open_procedures.push(index),
+                Instruction::EndProcedure => {
+                    let Some(start) = open_procedures.pop() else {
+                        return Err(ParseError::UnmatchedBracket {
+                            bracket:  ')',
+                            position: index,
+                        });
+                    };
+                    jump_table[start] = Some(index);
+                    jump_table[index] = Some(start);
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(start) = open_brackets.pop() {
+            return Err(ParseError::UnmatchedBracket {
+                bracket:  '[',
+                position: start,
+            });
+        }
+        if let Some(start) = open_procedures.pop() {
+            return Err(ParseError::UnmatchedBracket {
+                bracket:  '(',
+                position: start,
+            });
+        }
+
+        Ok(jump_table)
+    }
+
+    /// Concatenate two programs
+    ///
+    /// This method returns a new `Program` holding the instructions of `self`
+    /// followed by the instructions of `other`. The same operation is
+    /// available through the `+` operator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Program;
+    ///
+    /// let program = Program::from("++").concat(&Program::from(">."));
+    ///
+    /// assert_eq!(program, Program::from("++>."));
+    /// assert_eq!(program, Program::from("++") + Program::from(">."));
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The concatenated `Program`
+    #[must_use]
+    pub fn concat(&self, other: &Self) -> Self {
+        let mut instructions = self.instructions.clone();
+        instructions.extend_from_slice(&other.instructions);
+
+        Self {
+            instructions,
+            dialect: self.dialect,
+        }
+    }
+
+    /// Get a sub-program covering a range of instructions
+    ///
+    /// The sub-program is not validated, so slicing through a loop results in
+    /// a `Program` with unbalanced brackets. Use
+    /// [`jump_table()`](#method.jump_table) to check the result if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of instructions to copy
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Program;
+    ///
+    /// let program = Program::from("++[->+<]");
+    ///
+    /// assert_eq!(program.slice(2..), Program::from("[->+<]"));
+    /// assert_eq!(program.slice(..2), Program::from("++"));
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A new `Program` holding the instructions in the range
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds of the program.
+    #[must_use]
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let range = self.resolve_range(&range);
+
+        Self {
+            instructions: self.instructions[range].to_vec(),
+            dialect:      self.dialect,
+        }
+    }
+
+    /// Replace a range of instructions with another program
+    ///
+    /// The jump table of the resulting program is revalidated. If the
+    /// brackets are no longer balanced the program is left untouched and an
+    /// error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The range of instructions to replace
+    /// * `replacement` - The program to insert in place of the range
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Program;
+    ///
+    /// let mut program = Program::from("+[-]+");
+    ///
+    /// program.splice(1..4, &Program::from(">")).unwrap();
+    /// assert_eq!(program, Program::from("+>+"));
+    ///
+    /// assert!(program.splice(0..1, &Program::from("[")).is_err());
+    /// assert_eq!(program, Program::from("+>+"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the brackets of the resulting program are not
+    /// balanced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds of the program.
+    pub fn splice<R: RangeBounds<usize>>(
+        &mut self,
+        range: R,
+        replacement: &Self,
+    ) -> Result<(), ParseError> {
+        let range = self.resolve_range(&range);
+        let mut instructions = self.instructions.clone();
+        instructions.splice(range, replacement.instructions.iter().copied());
+
+        let spliced = Self {
+            instructions,
+            dialect: self.dialect,
+        };
+        spliced.jump_table()?;
+        *self = spliced;
+
+        Ok(())
+    }
+
+    /// Parse the source code of a program written in a `Dialect`
+    ///
+    /// Characters that are not instructions in the dialect become `NoOp`s.
+    /// Loading a program with `Program::from` is equivalent to parsing it in
+    /// the `Standard` dialect.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source code of the program
+    /// * `dialect` - The dialect the program is written in
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Dialect,
+    ///     Instruction,
+    ///     Program,
+    /// };
+    ///
+    /// let program = Program::parse("+#", Dialect::Debug);
+    /// assert_eq!(program.get_instruction(1), Some(Instruction::DebugDump));
+    ///
+    /// let program = Program::parse("+#", Dialect::Standard);
+    /// assert_eq!(program.get_instruction(1), Some(Instruction::NoOp));
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The parsed `Program`
+    #[must_use]
+    pub fn parse(source: &str, dialect: Dialect) -> Self {
+        Self {
+            instructions: source.chars().map(|c| dialect.instruction(c)).collect(),
+            dialect,
+        }
+    }
+
+    /// Parse a program with custom instructions
+    ///
+    /// Like [`parse()`](#method.parse), but every character in `extensions`
+    /// that is not already an instruction of the dialect becomes an
+    /// [`Instruction::Extension`], instead of a comment. The machine runs
+    /// them with the handlers registered on its builder, see
+    /// [`InstructionHandler`](crate::InstructionHandler).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Dialect,
+    ///     Instruction,
+    ///     Program,
+    /// };
+    ///
+    /// let program =
+    ///     Program::parse_with_extensions("+%$", Dialect::Standard, &['%', '+']);
+    ///
+    /// assert_eq!(
+    ///     program.get_instruction(1),
+    ///     Some(Instruction::Extension('%'))
+    /// );
+    /// assert_eq!(
+    ///     program.get_instruction(0),
+    ///     Some(Instruction::IncrementValue)
+    /// );
+    /// assert_eq!(program.get_instruction(2), Some(Instruction::NoOp));
+    /// ```
+    #[must_use]
+    pub fn parse_with_extensions(source: &str, dialect: Dialect, extensions: &[char]) -> Self {
+        Self {
+            instructions: source
+                .chars()
+                .map(|c| match dialect.instruction(c) {
+                    Instruction::NoOp if extensions.contains(&c) => Instruction::Extension(c),
+                    instruction => instruction,
+                })
+                .collect(),
+            dialect,
+        }
+    }
+
+    /// The dialect the program was parsed in
+    ///
+    /// Programs that were not parsed with [`parse()`](#method.parse) or
+    /// [`parse_with_extensions()`](#method.parse_with_extensions) are in the
+    /// `Standard` dialect. Programs put together from others, with
+    /// [`concat()`](#method.concat) or [`slice()`](#method.slice), keep the
+    /// dialect of the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Dialect,
+    ///     Program,
+    /// };
+    ///
+    /// assert_eq!(Program::parse("+@", Dialect::Stack).dialect(), Dialect::Stack);
+    /// assert_eq!(Program::from("+@").dialect(), Dialect::Standard);
+    /// ```
+    #[must_use]
+    pub const fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Split a combined program and input file at the first `!`
+    ///
+    /// Many brainfuck test suites store a program together with its input,
+    /// separated by a `!`. Everything before the first `!` is the source of
+    /// the program, and every byte after it is the input. Without a `!`, the
+    /// whole text is the program and the input is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The combined program and input
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Program;
+    ///
+    /// let (source, input) = Program::split_input(",[.,]!hello");
+    ///
+    /// assert_eq!(source, ",[.,]");
+    /// assert_eq!(input, b"hello");
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The source of the program and the bytes of its input
+    ///
+    /// # See Also
+    ///
+    /// * [`parse_with_input`](#method.parse_with_input): Parse the program as
+    ///   well
+    #[must_use]
+    pub fn split_input(source: &str) -> (&str, &[u8]) {
+        match source.split_once('!') {
+            Some((program, input)) => (program, input.as_bytes()),
+            None => (source, &[]),
+        }
+    }
+
+    /// Parse a combined program and input file
+    ///
+    /// The text before the first `!` is parsed as a program in `dialect`, and
+    /// every byte after it becomes the input, see
+    /// [`split_input`](#method.split_input).
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The combined program and input
+    /// * `dialect` - The dialect the program is written in
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Dialect,
+    ///     Instruction,
+    ///     Program,
+    /// };
+    ///
+    /// let (program, input) = Program::parse_with_input(",[.,]#!hello", Dialect::Debug);
+    ///
+    /// assert_eq!(program.get_instruction(5), Some(Instruction::DebugDump));
+    /// assert_eq!(input, b"hello");
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The `Program` and the bytes of its input
+    ///
+    /// # See Also
+    ///
+    /// * [`VecReader`](struct.VecReader.html): Feed the input to a
+    ///   `VirtualMachine`
+    #[must_use]
+    pub fn parse_with_input(source: &str, dialect: Dialect) -> (Self, Vec<u8>) {
+        let (source, input) = Self::split_input(source);
+        (Self::parse(source, dialect), input.to_vec())
+    }
+
+    /// Compute a static overview of the program
+    ///
+    /// The statistics include a histogram of the instructions, the number of
+    /// loops and their deepest nesting, the ratio of comments to code, an
+    /// estimate of the number of cells the program uses and whether its
+    /// brackets are balanced. The program is not run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Program;
+    ///
+    /// let statistics = Program::from("+[>[-]<]").statistics();
+    ///
+    /// assert_eq!(statistics.loops(), 2);
+    /// assert_eq!(statistics.max_nesting_depth(), 2);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The [`ProgramStatistics`](struct.ProgramStatistics.html) of the program
+    #[must_use]
+    pub fn statistics(&self) -> ProgramStatistics {
+        ProgramStatistics::new(self)
+    }
+
+    /// Get a stable hash of the instructions of a `Program`
+    ///
+    /// The fingerprint is the 64-bit FNV-1a hash of the program's
+    /// instructions, written as their characters. Comments do not take part
+    /// in it, so two programs that only differ in their comments or
+    /// whitespace have the same fingerprint. Unlike the `Hash` of the
+    /// standard library, the fingerprint is the same on every platform and in
+    /// every version of this crate, so it can be stored and compared across
+    /// runs.
+    ///
+    /// The same characters mean different things in different dialects, so
+    /// the name of the dialect is hashed ahead of the instructions. Programs
+    /// of the standard dialect leave it out, and keep the fingerprint they
+    /// had before dialects took part in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Dialect,
+    ///     Program,
+    /// };
+    ///
+    /// let program = Program::from("+[-]");
+    /// let commented = Program::from("+ clear the cell [-]");
+    ///
+    /// assert_eq!(program.fingerprint(), commented.fingerprint());
+    /// assert_ne!(program.fingerprint(), Program::from("-[+]").fingerprint());
+    ///
+    /// // `@` halts in pbrain and pushes a cell in the stack dialect
+    /// assert_ne!(
+    ///     Program::parse("+@", Dialect::Pbrain).fingerprint(),
+    ///     Program::parse("+@", Dialect::Stack).fingerprint()
+    /// );
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The fingerprint of the program
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let dialect = match self.dialect {
+            Dialect::Standard => None,
+            dialect => Some(dialect.to_string()),
+        };
+        // The names of dialects are letters, which are never instructions
+        dialect
+            .iter()
+            .flat_map(|name| name.chars().chain([':']))
+            .chain(self.instructions.iter().filter_map(Instruction::to_char))
+            .fold(OFFSET_BASIS, |hash, character| {
+                (hash ^ u64::from(character)).wrapping_mul(PRIME)
+            })
+    }
+
+    fn resolve_range<R: RangeBounds<usize>>(&self, range: &R) -> Range<usize> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.instructions.len(),
+        };
+
+        start..end
+    }
+}
+
+impl Add for Program {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        self.concat(&other)
+    }
+}
+
+impl AddAssign for Program {
+    fn add_assign(&mut self, other: Self) {
+        self.instructions.extend(other.instructions);
+    }
 }
 
 impl Default for Program {
@@ -306,7 +824,7 @@ impl From<&str> for Program {
             instructions.push(Instruction::from_char(c));
         }
 
-        Self { instructions }
+        Self::from(instructions)
     }
 }
 
@@ -342,7 +860,10 @@ impl From<Vec<Instruction>> for Program {
     ///
     /// * [`from()`](#method.from): Load a `Program` from a string
     fn from(instructions: Vec<Instruction>) -> Self {
-        Self { instructions }
+        Self {
+            instructions,
+            dialect: Dialect::Standard,
+        }
     }
 }
 
@@ -516,10 +1037,150 @@ mod tests {
         assert_eq!(program[7], Instruction::DecrementValue);
     }
 
+    #[test]
+    fn test_jump_table() {
+        let program = Program::from("[[]+]");
+        let jump_table = program.jump_table().unwrap();
+
+        assert_eq!(jump_table, vec![Some(4), Some(2), Some(1), None, Some(0)]);
+        assert_eq!(Program::from("").jump_table().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_jump_table_unbalanced() {
+        let error = Program::from("[[]").jump_table().unwrap_err();
+        assert_eq!(error.to_string(), "Unmatched '[' at instruction 0");
+
+        let error = Program::from("+]").jump_table().unwrap_err();
+        assert_eq!(error.to_string(), "Unmatched ']' at instruction 1");
+    }
+
+    #[test]
+    fn test_concat() {
+        let first = Program::from("+>");
+        let second = Program::from("<-");
+
+        assert_eq!(first.concat(&second), Program::from("+><-"));
+        assert_eq!(first.concat(&Program::from("")), first);
+    }
+
+    #[test]
+    fn test_add() {
+        let program = Program::from("[") + Program::from("-") + Program::from("]");
+
+        assert_eq!(program, Program::from("[-]"));
+    }
+
+    #[test]
+    fn test_add_assign() {
+        let mut program = Program::from("++");
+        program += Program::from(".");
+
+        assert_eq!(program, Program::from("++."));
+    }
+
+    #[test]
+    fn test_slice() {
+        let program = Program::from(">>++<<--");
+
+        assert_eq!(program.slice(2..4), Program::from("++"));
+        assert_eq!(program.slice(2..=4), Program::from("++<"));
+        assert_eq!(program.slice(..), program);
+        assert_eq!(program.slice(8..), Program::from(""));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_slice_out_of_bounds() {
+        let program = Program::from("++");
+        let _ = program.slice(1..5);
+    }
+
+    #[test]
+    fn test_splice() {
+        let mut program = Program::from("+++");
+
+        program.splice(1..2, &Program::from("[-]")).unwrap();
+        assert_eq!(program, Program::from("+[-]+"));
+
+        program.splice(.., &Program::from("")).unwrap();
+        assert_eq!(program.length(), None);
+    }
+
+    #[test]
+    fn test_jump_table_procedures() {
+        let program = Program::parse("+(-[.])", Dialect::Pbrain);
+        let jump_table = program.jump_table().unwrap();
+
+        assert_eq!(jump_table[1], Some(6));
+        assert_eq!(jump_table[6], Some(1));
+        assert_eq!(jump_table[3], Some(5));
+
+        assert!(Program::parse("(", Dialect::Pbrain).jump_table().is_err());
+        assert!(Program::parse(")", Dialect::Pbrain).jump_table().is_err());
+        assert!(Program::parse("(", Dialect::Standard).jump_table().is_ok());
+    }
+
+    #[test]
+    fn test_splice_unbalanced() {
+        let mut program = Program::from("[+]");
+
+        assert!(program.splice(2..3, &Program::from("")).is_err());
+        assert_eq!(program, Program::from("[+]"));
+    }
+
     #[test]
     #[should_panic(expected = "index out of bounds")]
     fn test_index_out_of_bounds() {
         let program = Program::from(">>++<<--");
         let _ = program[8];
     }
+
+    #[test]
+    fn test_split_input() {
+        assert_eq!(Program::split_input("+.!a!b"), ("+.", &b"a!b"[..]));
+        assert_eq!(Program::split_input("+."), ("+.", &b""[..]));
+        assert_eq!(Program::split_input("+.!"), ("+.", &b""[..]));
+    }
+
+    #[test]
+    fn test_parse_with_input() {
+        let (program, input) = Program::parse_with_input("+.!a!b", Dialect::Standard);
+        assert_eq!(program, Program::from("+."));
+        assert_eq!(input, b"a!b");
+
+        let (program, input) = Program::parse_with_input("+.", Dialect::Standard);
+        assert_eq!(program, Program::from("+."));
+        assert!(input.is_empty());
+
+        let (program, input) = Program::parse_with_input("+#!#", Dialect::Debug);
+        assert_eq!(program, Program::parse("+#", Dialect::Debug));
+        assert_eq!(input, b"#");
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(Program::from("").fingerprint(), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(Program::from("+[-]").fingerprint(), 0xff9c_8dbe_c716_4f55);
+        assert_eq!(
+            Program::from("+\n[-] comment").fingerprint(),
+            Program::from("+[-]").fingerprint()
+        );
+        assert_ne!(
+            Program::from("+-").fingerprint(),
+            Program::from("-+").fingerprint()
+        );
+        assert_eq!(
+            Program::parse("+[-]", Dialect::Standard).fingerprint(),
+            0xff9c_8dbe_c716_4f55
+        );
+        assert_ne!(
+            Program::parse("+[-]", Dialect::Debug).fingerprint(),
+            Program::parse("+[-]", Dialect::Standard).fingerprint()
+        );
+        assert_ne!(
+            Program::parse("@", Dialect::Pbrain).fingerprint(),
+            Program::parse("@", Dialect::Stack).fingerprint()
+        );
+    }
 }
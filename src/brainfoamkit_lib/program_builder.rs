@@ -0,0 +1,786 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{
+    Instruction,
+    Program,
+};
+
+/// `ProgramBuilder` is a small assembler for building a `Program` from Rust
+/// code.
+///
+/// Instead of concatenating strings of `BrainFuck` source, the builder exposes
+/// one fluent method per instruction. Loops are created with a closure, so the
+/// resulting `Program` always has balanced brackets.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Program,
+///     ProgramBuilder,
+/// };
+///
+/// // Multiply 3 by 2 and move the result into the second cell
+/// let program = ProgramBuilder::new()
+///     .inc(3)
+///     .loop_(|body| body.dec(1).right(1).inc(2).left(1))
+///     .right(1)
+///     .out()
+///     .build();
+///
+/// assert_eq!(program, Program::from("+++[->++<]>."));
+/// ```
+///
+/// ## Generating code with the higher-level helpers
+///
+/// The builder keeps track of the cell the memory pointer is on, relative to
+/// the cell it started on. This allows helpers such as
+/// [`move_to()`](#method.move_to), [`copy_cell()`](#method.copy_cell) and
+/// [`add_cells()`](#method.add_cells) to work with absolute cell indices.
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Program,
+///     ProgramBuilder,
+/// };
+///
+/// let program = ProgramBuilder::new()
+///     .set_cell(2)
+///     .move_to(1)
+///     .set_cell(3)
+///     .add_cells(0, 1)
+///     .build();
+///
+/// assert_eq!(program, Program::from("[-]++>[-]+++<[->+<]"));
+/// ```
+///
+/// Position tracking assumes that every loop returns to the cell it started
+/// on, which is true for all the helpers in this module.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgramBuilder {
+    /// The instructions emitted so far
+    instructions: Vec<Instruction>,
+
+    /// The cell the memory pointer is on, relative to the starting cell
+    position: isize,
+}
+
+impl ProgramBuilder {
+    /// Creates a new `ProgramBuilder` with no instructions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::ProgramBuilder;
+    ///
+    /// let program = ProgramBuilder::new().build();
+    ///
+    /// assert_eq!(program.length(), None);
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            instructions: Vec::new(),
+            position:     0,
+        }
+    }
+
+    /// Returns the cell the memory pointer is on after the instructions
+    /// emitted so far, relative to the cell it started on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::ProgramBuilder;
+    ///
+    /// let builder = ProgramBuilder::new().right(3).left(1);
+    ///
+    /// assert_eq!(builder.position(), 2);
+    /// ```
+    #[must_use]
+    pub const fn position(&self) -> isize {
+        self.position
+    }
+
+    /// Increment the value in the current cell `count` times.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of `+` instructions to emit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().inc(3).build();
+    ///
+    /// assert_eq!(program, Program::from("+++"));
+    /// ```
+    #[must_use]
+    pub fn inc(self, count: usize) -> Self {
+        self.repeat(Instruction::IncrementValue, count)
+    }
+
+    /// Decrement the value in the current cell `count` times.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of `-` instructions to emit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().dec(2).build();
+    ///
+    /// assert_eq!(program, Program::from("--"));
+    /// ```
+    #[must_use]
+    pub fn dec(self, count: usize) -> Self {
+        self.repeat(Instruction::DecrementValue, count)
+    }
+
+    /// Move the memory pointer `count` cells to the right.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of `>` instructions to emit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().right(2).build();
+    ///
+    /// assert_eq!(program, Program::from(">>"));
+    /// ```
+    #[must_use]
+    pub fn right(mut self, count: usize) -> Self {
+        self.position = self.position.saturating_add_unsigned(count);
+        self.repeat(Instruction::IncrementPointer, count)
+    }
+
+    /// Move the memory pointer `count` cells to the left.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of `<` instructions to emit
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().left(2).build();
+    ///
+    /// assert_eq!(program, Program::from("<<"));
+    /// ```
+    #[must_use]
+    pub fn left(mut self, count: usize) -> Self {
+        self.position = self.position.saturating_sub_unsigned(count);
+        self.repeat(Instruction::DecrementPointer, count)
+    }
+
+    /// Output the value of the current cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().out().build();
+    ///
+    /// assert_eq!(program, Program::from("."));
+    /// ```
+    #[must_use]
+    pub fn out(self) -> Self {
+        self.instruction(Instruction::OutputValue)
+    }
+
+    /// Read a value from the input into the current cell.
+    ///
+    /// The trailing underscore avoids a clash with the `in` keyword.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().in_().build();
+    ///
+    /// assert_eq!(program, Program::from(","));
+    /// ```
+    #[must_use]
+    pub fn in_(self) -> Self {
+        self.instruction(Instruction::InputValue)
+    }
+
+    /// Emit a loop whose body is built by the given closure.
+    ///
+    /// The closure receives an empty `ProgramBuilder`, positioned on the
+    /// current cell, and returns it with the body of the loop. The body is
+    /// wrapped in a matching pair of `[` and `]` instructions, so loops
+    /// built this way are always balanced.
+    ///
+    /// The trailing underscore avoids a clash with the `loop` keyword.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - A closure that builds the body of the loop
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new()
+    ///     .loop_(|outer| outer.loop_(|inner| inner.dec(1)).right(1))
+    ///     .build();
+    ///
+    /// assert_eq!(program, Program::from("[[-]>]"));
+    /// ```
+    #[must_use]
+    pub fn loop_<F>(self, body: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        let mut body = body(Self {
+            instructions: Vec::new(),
+            position:     self.position,
+        });
+
+        let mut builder = self.instruction(Instruction::JumpForward);
+        builder.instructions.append(&mut body.instructions);
+        builder.position = body.position;
+        builder.instruction(Instruction::JumpBackward)
+    }
+
+    /// Move the memory pointer to the given cell.
+    ///
+    /// The cell is relative to the cell the memory pointer started on.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The cell to move the memory pointer to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().move_to(3).move_to(1).build();
+    ///
+    /// assert_eq!(program, Program::from(">>><<"));
+    /// ```
+    #[must_use]
+    pub fn move_to(self, cell: usize) -> Self {
+        let target = isize::try_from(cell).unwrap_or(isize::MAX);
+        let distance = target.abs_diff(self.position);
+
+        if target >= self.position {
+            self.right(distance)
+        } else {
+            self.left(distance)
+        }
+    }
+
+    /// Set the value of the current cell to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().clear().build();
+    ///
+    /// assert_eq!(program, Program::from("[-]"));
+    /// ```
+    #[must_use]
+    pub fn clear(self) -> Self {
+        self.loop_(|body| body.dec(1))
+    }
+
+    /// Set the value of the current cell to `value`.
+    ///
+    /// The cell is cleared first. Values above 128 are reached by
+    /// decrementing, relying on the cell wrapping around, since that needs
+    /// fewer instructions.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to store in the current cell
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().set_cell(3).build();
+    /// assert_eq!(program, Program::from("[-]+++"));
+    ///
+    /// let program = ProgramBuilder::new().set_cell(254).build();
+    /// assert_eq!(program, Program::from("[-]--"));
+    /// ```
+    #[must_use]
+    pub fn set_cell(self, value: u8) -> Self {
+        self.clear().adjust(0, value)
+    }
+
+    /// Add the value of the cell `from` to the cell `to`.
+    ///
+    /// This is destructive: the cell `from` is zero afterwards. The memory
+    /// pointer ends on `from`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The cell to take the value from
+    /// * `to` - The cell to add the value to
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` and `to` are the same cell, which would loop forever.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().add_cells(0, 2).build();
+    ///
+    /// assert_eq!(program, Program::from("[->>+<<]"));
+    /// ```
+    #[must_use]
+    pub fn add_cells(self, from: usize, to: usize) -> Self {
+        assert_ne!(from, to, "Cannot add a cell to itself");
+        self.move_to(from)
+            .loop_(|body| body.dec(1).move_to(to).inc(1).move_to(from))
+    }
+
+    /// Move the value of the cell `from` into the cell `to`.
+    ///
+    /// The cell `to` is cleared first and the cell `from` is zero afterwards.
+    /// The memory pointer ends on `from`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The cell to take the value from
+    /// * `to` - The cell to move the value to
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` and `to` are the same cell, which would clear the
+    /// value instead of moving it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().move_value(0, 1).build();
+    ///
+    /// assert_eq!(program, Program::from(">[-]<[->+<]"));
+    /// ```
+    #[must_use]
+    pub fn move_value(self, from: usize, to: usize) -> Self {
+        assert_ne!(from, to, "Cannot move a cell into itself");
+        self.move_to(to).clear().add_cells(from, to)
+    }
+
+    /// Copy the value of the cell `from` into the cell `to`.
+    ///
+    /// A `BrainFuck` program can only copy a value by moving it into two cells
+    /// at once, so a scratch cell `tmp` is needed. The value is restored into
+    /// `from` afterwards and `to` and `tmp` are cleared before use. The memory
+    /// pointer ends on `tmp`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The cell to copy the value from
+    /// * `to` - The cell to copy the value to
+    /// * `tmp` - A scratch cell used while copying
+    ///
+    /// # Panics
+    ///
+    /// Panics if any two of `from`, `to` and `tmp` are the same cell, which
+    /// would clear the value or loop forever.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().copy_cell(0, 1, 2).build();
+    ///
+    /// assert_eq!(program, Program::from(">[-]>[-]<<[->+>+<<]>>[-<<+>>]"));
+    /// ```
+    #[must_use]
+    pub fn copy_cell(self, from: usize, to: usize, tmp: usize) -> Self {
+        assert!(
+            from != to && from != tmp && to != tmp,
+            "Cannot copy with overlapping cells"
+        );
+        self.move_to(to)
+            .clear()
+            .move_to(tmp)
+            .clear()
+            .move_to(from)
+            .loop_(|body| {
+                body.dec(1)
+                    .move_to(to)
+                    .inc(1)
+                    .move_to(tmp)
+                    .inc(1)
+                    .move_to(from)
+            })
+            .move_to(tmp)
+            .loop_(|body| body.dec(1).move_to(from).inc(1).move_to(tmp))
+    }
+
+    /// Print a string using the current cell as scratch space.
+    ///
+    /// The current cell is cleared first and then adjusted from one byte of
+    /// the string to the next, outputting each byte in turn. The cell holds
+    /// the last byte of the string afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to print
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().print_str("\x02\x01").build();
+    ///
+    /// assert_eq!(program, Program::from("[-]++.-."));
+    /// ```
+    #[must_use]
+    pub fn print_str(self, text: &str) -> Self {
+        let (builder, _) = text
+            .bytes()
+            .fold((self.clear(), 0), |(builder, current), byte| {
+                (builder.adjust(current, byte).out(), byte)
+            });
+        builder
+    }
+
+    /// Append a single `Instruction`.
+    ///
+    /// Loop instructions can not be added this way since they could leave the
+    /// `Program` unbalanced. Use [`loop_()`](#method.loop_) instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `instruction` - The instruction to append
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instruction is `JumpForward` or `JumpBackward`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Instruction,
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new()
+    ///     .push(Instruction::IncrementValue)
+    ///     .push(Instruction::NoOp)
+    ///     .build();
+    ///
+    /// assert_eq!(program, Program::from("+ "));
+    /// ```
+    #[must_use]
+    pub fn push(self, instruction: Instruction) -> Self {
+        assert!(
+            !matches!(
+                instruction,
+                Instruction::JumpForward | Instruction::JumpBackward
+            ),
+            "Use `loop_` to emit loops"
+        );
+        self.instruction(instruction)
+    }
+
+    /// Build the `Program`.
+    ///
+    /// # Returns
+    ///
+    /// A `Program` containing all the instructions emitted so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Program,
+    ///     ProgramBuilder,
+    /// };
+    ///
+    /// let program = ProgramBuilder::new().inc(1).out().build();
+    ///
+    /// assert_eq!(program, Program::from("+."));
+    /// ```
+    #[must_use]
+    pub fn build(self) -> Program {
+        Program::from(self.instructions)
+    }
+
+    fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// Change the current cell from `current` to `target` using the fewest
+    /// instructions, relying on the cell wrapping around.
+    fn adjust(self, current: u8, target: u8) -> Self {
+        let up = target.wrapping_sub(current);
+        let down = current.wrapping_sub(target);
+
+        if up <= down {
+            self.inc(usize::from(up))
+        } else {
+            self.dec(usize::from(down))
+        }
+    }
+
+    fn repeat(mut self, instruction: Instruction, count: usize) -> Self {
+        self.instructions
+            .extend(std::iter::repeat(instruction).take(count));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let builder = ProgramBuilder::new();
+
+        assert!(builder.instructions.is_empty());
+        assert_eq!(builder, ProgramBuilder::default());
+    }
+
+    #[test]
+    fn test_basic_instructions() {
+        let program = ProgramBuilder::new()
+            .inc(2)
+            .dec(1)
+            .right(3)
+            .left(2)
+            .out()
+            .in_()
+            .build();
+
+        assert_eq!(program, Program::from("++->>><<.,"));
+    }
+
+    #[test]
+    fn test_zero_count() {
+        let program = ProgramBuilder::new().inc(0).right(0).build();
+
+        assert_eq!(program.length(), None);
+    }
+
+    #[test]
+    fn test_loop() {
+        let program = ProgramBuilder::new()
+            .inc(5)
+            .loop_(|body| body.dec(1).right(1).inc(1).left(1))
+            .build();
+
+        assert_eq!(program, Program::from("+++++[->+<]"));
+    }
+
+    #[test]
+    fn test_nested_loops() {
+        let program = ProgramBuilder::new()
+            .loop_(|outer| outer.right(1).loop_(|inner| inner.dec(1)).left(1))
+            .build();
+
+        assert_eq!(program, Program::from("[>[-]<]"));
+        assert_eq!(program.find_matching_bracket(0), Some(6));
+        assert_eq!(program.find_matching_bracket(2), Some(4));
+    }
+
+    #[test]
+    fn test_empty_loop() {
+        let program = ProgramBuilder::new().loop_(|body| body).build();
+
+        assert_eq!(program, Program::from("[]"));
+    }
+
+    #[test]
+    fn test_position_tracking() {
+        let builder = ProgramBuilder::new().right(4).left(1);
+        assert_eq!(builder.position(), 3);
+
+        let builder = builder.loop_(|body| body.left(3).inc(1).right(3));
+        assert_eq!(builder.position(), 3);
+
+        let builder = ProgramBuilder::new().left(2);
+        assert_eq!(builder.position(), -2);
+        assert_eq!(builder.move_to(1).position(), 1);
+    }
+
+    #[test]
+    fn test_move_to() {
+        let program = ProgramBuilder::new()
+            .move_to(2)
+            .move_to(2)
+            .move_to(0)
+            .build();
+
+        assert_eq!(program, Program::from(">><<"));
+    }
+
+    #[test]
+    fn test_set_cell() {
+        assert_eq!(
+            ProgramBuilder::new().set_cell(0).build(),
+            Program::from("[-]")
+        );
+        assert_eq!(
+            ProgramBuilder::new().set_cell(128).build(),
+            Program::from(format!("[-]{}", "+".repeat(128)).as_str())
+        );
+        assert_eq!(
+            ProgramBuilder::new().set_cell(255).build(),
+            Program::from("[-]-")
+        );
+    }
+
+    #[test]
+    fn test_add_cells() {
+        let builder = ProgramBuilder::new().add_cells(2, 0);
+
+        assert_eq!(builder.position(), 2);
+        assert_eq!(builder.build(), Program::from(">>[-<<+>>]"));
+    }
+
+    #[test]
+    fn test_move_value() {
+        let builder = ProgramBuilder::new().move_value(1, 3);
+
+        assert_eq!(builder.position(), 1);
+        assert_eq!(builder.build(), Program::from(">>>[-]<<[->>+<<]"));
+    }
+
+    #[test]
+    fn test_copy_cell() {
+        let builder = ProgramBuilder::new().right(1).copy_cell(1, 0, 2);
+
+        assert_eq!(builder.position(), 2);
+        assert_eq!(
+            builder.build(),
+            Program::from("><[-]>>[-]<[-<+>>+<]>[-<+>]")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot add a cell to itself")]
+    fn test_add_cells_to_itself() {
+        let _ = ProgramBuilder::new().add_cells(1, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot move a cell into itself")]
+    fn test_move_value_into_itself() {
+        let _ = ProgramBuilder::new().move_value(2, 2);
+    }
+
+    #[test]
+    fn test_copy_cell_overlapping() {
+        for (from, to, tmp) in [(0, 0, 1), (0, 1, 0), (0, 1, 1)] {
+            let copy = std::panic::catch_unwind(|| {
+                let _ = ProgramBuilder::new().copy_cell(from, to, tmp);
+            });
+            assert!(copy.is_err(), "{from}, {to}, {tmp}");
+        }
+    }
+
+    #[test]
+    fn test_print_str() {
+        let program = ProgramBuilder::new().print_str("AB@").build();
+        let expected = format!("[-]{}.+.{}.", "+".repeat(65), "-".repeat(2));
+
+        assert_eq!(program, Program::from(expected.as_str()));
+    }
+
+    #[test]
+    fn test_print_str_empty() {
+        let program = ProgramBuilder::new().print_str("").build();
+
+        assert_eq!(program, Program::from("[-]"));
+    }
+
+    #[test]
+    fn test_push() {
+        let program = ProgramBuilder::new()
+            .push(Instruction::OutputValue)
+            .push(Instruction::NoOp)
+            .build();
+
+        assert_eq!(program, Program::from(". "));
+    }
+
+    #[test]
+    #[should_panic(expected = "Use `loop_` to emit loops")]
+    fn test_push_loop_instruction() {
+        let _ = ProgramBuilder::new().push(Instruction::JumpForward);
+    }
+}
@@ -0,0 +1,304 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use alloc::vec::Vec;
+use core::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+
+use crate::{
+    Instruction,
+    Program,
+};
+
+/// A static overview of a `Program`
+///
+/// `ProgramStatistics` are computed from the text of a program without running
+/// it. See [`Program::statistics`](struct.Program.html#method.statistics).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Instruction,
+///     Program,
+/// };
+///
+/// let statistics = Program::from("++[>+<-] add").statistics();
+///
+/// assert_eq!(statistics.count(Instruction::IncrementValue), 3);
+/// assert_eq!(statistics.code_length(), 8);
+/// assert_eq!(statistics.comment_length(), 4);
+/// assert_eq!(statistics.loops(), 1);
+/// assert_eq!(statistics.max_nesting_depth(), 1);
+/// assert_eq!(statistics.estimated_tape_usage(), 2);
+/// assert!(statistics.is_balanced());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgramStatistics {
+    counts:               [usize; 8],
+    extensions:           Vec<(Instruction, usize)>,
+    comment_length:       usize,
+    max_nesting_depth:    usize,
+    estimated_tape_usage: usize,
+    balanced:             bool,
+}
+
+impl ProgramStatistics {
+    /// Compute the statistics of a `Program`
+    pub(crate) fn new(program: &Program) -> Self {
+        let instructions = Instruction::all();
+        let mut counts = [0; 8];
+        let mut extensions: Vec<(Instruction, usize)> = Vec::new();
+        let mut comment_length = 0;
+        let mut depth: usize = 0;
+        let mut max_nesting_depth = 0;
+        let mut pointer: isize = 0;
+        let mut lowest_pointer: isize = 0;
+        let mut highest_pointer: isize = 0;
+
+        for index in 0..program.length().unwrap_or(0) {
+            let Some(instruction) = program.get_instruction(index) else {
+                continue;
+            };
+            match instructions.iter().position(|known| *known == instruction) {
+                Some(position) => counts[position] += 1,
+                None if instruction == Instruction::NoOp => comment_length += 1,
+                None => match extensions
+                    .iter_mut()
+                    .find(|(known, _)| *known == instruction)
+                {
+                    Some((_, count)) => *count += 1,
+                    None => extensions.push((instruction, 1)),
+                },
+            }
+            match instruction {
+                Instruction::IncrementPointer => pointer += 1,
+                Instruction::DecrementPointer => pointer -= 1,
+                Instruction::JumpForward => {
+                    depth += 1;
+                    max_nesting_depth = max_nesting_depth.max(depth);
+                }
+                Instruction::JumpBackward => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            lowest_pointer = lowest_pointer.min(pointer);
+            highest_pointer = highest_pointer.max(pointer);
+        }
+
+        Self {
+            counts,
+            extensions,
+            comment_length,
+            max_nesting_depth,
+            estimated_tape_usage: highest_pointer.abs_diff(lowest_pointer) + 1,
+            balanced: program.jump_table().is_ok(),
+        }
+    }
+
+    /// The number of times an instruction appears in the program
+    ///
+    /// # Arguments
+    ///
+    /// * `instruction` - The instruction to count. `NoOp` counts the comment
+    ///   characters of the program.
+    #[must_use]
+    pub fn count(&self, instruction: Instruction) -> usize {
+        if instruction == Instruction::NoOp {
+            return self.comment_length;
+        }
+        self.histogram()
+            .into_iter()
+            .find(|(known, _)| *known == instruction)
+            .map_or(0, |(_, count)| count)
+    }
+
+    /// Every instruction with the number of times it appears in the program
+    ///
+    /// The eight standard instructions are always listed. Extension
+    /// instructions of a [`Dialect`](enum.Dialect.html) follow them if the
+    /// program uses any.
+    #[must_use]
+    pub fn histogram(&self) -> Vec<(Instruction, usize)> {
+        Instruction::all()
+            .iter()
+            .copied()
+            .zip(self.counts.iter().copied())
+            .chain(self.extensions.iter().copied())
+            .collect()
+    }
+
+    /// The number of instructions in the program, not counting comments
+    #[must_use]
+    pub fn code_length(&self) -> usize {
+        self.counts.iter().sum::<usize>()
+            + self
+                .extensions
+                .iter()
+                .map(|(_, count)| count)
+                .sum::<usize>()
+    }
+
+    /// The number of comment characters in the program
+    #[must_use]
+    pub const fn comment_length(&self) -> usize {
+        self.comment_length
+    }
+
+    /// The number of comment characters per instruction
+    ///
+    /// # Returns
+    ///
+    /// The ratio of comment characters to instructions, or `0.0` for a
+    /// program without instructions.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn comment_ratio(&self) -> f64 {
+        match self.code_length() {
+            0 => 0.0,
+            code_length => self.comment_length as f64 / code_length as f64,
+        }
+    }
+
+    /// The number of loops in the program
+    #[must_use]
+    pub const fn loops(&self) -> usize {
+        self.counts[6]
+    }
+
+    /// The deepest level of loop nesting in the program
+    #[must_use]
+    pub const fn max_nesting_depth(&self) -> usize {
+        self.max_nesting_depth
+    }
+
+    /// An estimate of the number of cells the program uses
+    ///
+    /// The estimate follows the pointer movements in the text of the program,
+    /// going through every loop body once. Programs that move the pointer a
+    /// different amount on every loop iteration use more cells than this.
+    #[must_use]
+    pub const fn estimated_tape_usage(&self) -> usize {
+        self.estimated_tape_usage
+    }
+
+    /// Returns `true` if every `[` has a matching `]`
+    #[must_use]
+    pub const fn is_balanced(&self) -> bool {
+        self.balanced
+    }
+}
+
+impl Display for ProgramStatistics {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "Instructions:")?;
+        for (instruction, count) in self.histogram() {
+            let character = instruction.to_char().unwrap_or(' ');
+            writeln!(f, "  {character}  {instruction:<6}  {count}")?;
+        }
+        writeln!(f, "Code length:          {}", self.code_length())?;
+        writeln!(f, "Comment length:       {}", self.comment_length)?;
+        writeln!(f, "Comment to code:      {:.2}", self.comment_ratio())?;
+        writeln!(f, "Loops:                {}", self.loops())?;
+        writeln!(f, "Max nesting depth:    {}", self.max_nesting_depth)?;
+        writeln!(f, "Estimated tape usage: {}", self.estimated_tape_usage)?;
+        writeln!(
+            f,
+            "Brackets:             {}",
+            if self.balanced {
+                "balanced"
+            } else {
+                "unbalanced"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let statistics = Program::from("+++>>.,[]<-").statistics();
+        let histogram = statistics.histogram();
+
+        assert_eq!(histogram.len(), 8);
+        assert_eq!(histogram[0], (Instruction::IncrementPointer, 2));
+        assert_eq!(histogram[2], (Instruction::IncrementValue, 3));
+        assert_eq!(statistics.count(Instruction::OutputValue), 1);
+        assert_eq!(statistics.count(Instruction::NoOp), 0);
+    }
+
+    #[test]
+    fn test_comments() {
+        let statistics = Program::from("+ plus -").statistics();
+
+        assert_eq!(statistics.code_length(), 2);
+        assert_eq!(statistics.comment_length(), 6);
+        assert_eq!(statistics.count(Instruction::NoOp), 6);
+        assert!((statistics.comment_ratio() - 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_empty_program() {
+        let statistics = Program::from("").statistics();
+
+        assert_eq!(statistics.code_length(), 0);
+        assert!((statistics.comment_ratio() - 0.0).abs() < f64::EPSILON);
+        assert_eq!(statistics.estimated_tape_usage(), 1);
+        assert!(statistics.is_balanced());
+    }
+
+    #[test]
+    fn test_nesting() {
+        let statistics = Program::from("[[]][[[]]]").statistics();
+
+        assert_eq!(statistics.loops(), 5);
+        assert_eq!(statistics.max_nesting_depth(), 3);
+    }
+
+    #[test]
+    fn test_tape_usage() {
+        assert_eq!(Program::from(">>><").statistics().estimated_tape_usage(), 4);
+        assert_eq!(
+            Program::from("<<>>>").statistics().estimated_tape_usage(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        assert!(!Program::from("[[]").statistics().is_balanced());
+        assert!(!Program::from("]").statistics().is_balanced());
+    }
+
+    #[test]
+    fn test_extensions() {
+        let statistics = Program::parse("+#-#", crate::Dialect::Debug).statistics();
+
+        assert_eq!(statistics.count(Instruction::DebugDump), 2);
+        assert_eq!(statistics.code_length(), 4);
+        assert_eq!(statistics.comment_length(), 0);
+        assert_eq!(statistics.histogram().len(), 9);
+
+        let statistics = Program::from("+#-#").statistics();
+        assert_eq!(statistics.comment_length(), 2);
+        assert_eq!(statistics.histogram().len(), 8);
+        assert_eq!(statistics.count(Instruction::DebugDump), 0);
+    }
+
+    #[test]
+    fn test_display() {
+        let report = Program::from("+[-]").statistics().to_string();
+
+        assert!(report.contains("  +  INCVAL  1"));
+        assert!(report.contains("Loops:                1"));
+        assert!(report.contains("Brackets:             balanced"));
+    }
+}
@@ -0,0 +1,95 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/// How far a run of the `VirtualMachine` has got
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of instructions executed
+    pub steps:           usize,
+    /// The number of times a loop was left at its `]`, a rough measure of
+    /// how much of the work of a program is done. Loops skipped at their `[`
+    /// are not counted.
+    pub loops_completed: usize,
+    /// The number of bytes written to the output device
+    pub bytes_written:   usize,
+}
+
+/// Receives the [`Progress`] of a long run
+///
+/// A reporter is set with
+/// [`VirtualMachineBuilder::progress_reporter`](crate::VirtualMachineBuilder::progress_reporter),
+/// which also sets how many steps go by between two reports. Any
+/// `FnMut(&Progress)` closure is a reporter.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::{
+///     Arc,
+///     Mutex,
+/// };
+///
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     Program,
+///     Progress,
+///     VirtualMachine,
+/// };
+///
+/// let reports = Arc::new(Mutex::new(Vec::new()));
+/// let recorder = Arc::clone(&reports);
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from("+++[.-]"))
+///     .progress_reporter(6, move |progress: &Progress| {
+///         recorder.lock().unwrap().push(*progress)
+///     })
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// assert_eq!(
+///     *reports.lock().unwrap(),
+///     [
+///         Progress {
+///             steps:           6,
+///             loops_completed: 0,
+///             bytes_written:   1,
+///         },
+///         Progress {
+///             steps:           12,
+///             loops_completed: 0,
+///             bytes_written:   3,
+///         },
+///         Progress {
+///             steps:           13,
+///             loops_completed: 1,
+///             bytes_written:   3,
+///         },
+///     ]
+/// );
+/// ```
+pub trait ProgressReporter: Send {
+    /// Receive the progress of the run
+    ///
+    /// This is called after every `every` steps, and once more when the
+    /// program halts.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - How far the run has got
+    fn report(&mut self, progress: &Progress);
+}
+
+impl<F> ProgressReporter for F
+where
+    F: FnMut(&Progress) + Send,
+{
+    fn report(&mut self, progress: &Progress) {
+        self(progress);
+    }
+}
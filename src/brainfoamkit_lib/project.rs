@@ -0,0 +1,609 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Brainfuck projects
+//!
+//! A project lists its programs in a `bfk.toml` manifest, together with their
+//! dialect, behavior profile, input, expected output and the data files to
+//! load into their tape:
+//!
+//! ```toml
+//! [[program]]
+//! name = "hello"
+//! path = "src/hello.bf"
+//! expected = "tests/hello.expected"
+//!
+//! [[program]]
+//! name = "rot13"
+//! path = "src/rot13.b"
+//! profile = "strict"
+//! input = "tests/rot13.input"
+//! expected = "tests/rot13.expected"
+//! data = [{ path = "tables/rot13.bin", start = 100 }]
+//! ```
+//!
+//! Only `name` and `path` are required. Paths are relative to the directory
+//! of the manifest. Every entry of `data` loads the bytes of a file into the
+//! tape from the cell `start` on before the program runs, like a
+//! `@data "tables/rot13.bin" -> cells 100..` directive of the
+//! [`Preprocessor`](crate::Preprocessor). A program with an `expected`
+//! output is also a test, which [`Manifest::run_tests`] runs the same way as
+//! the tests of a directory, see [`test_runner`](crate::test_runner).
+//!
+//! [`Manifest::scaffold`] starts a new project with a manifest, a program
+//! and its test.
+
+use std::{
+    collections::HashSet,
+    env,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    config::Config,
+    test_runner::{
+        Report,
+        TestCase,
+    },
+    BehaviorProfile,
+    ConfigError,
+    DataSegment,
+    Dialect,
+    FileError,
+    Limits,
+    Program,
+    VMError,
+};
+
+/// The name of the manifest looked for by [`Manifest::discover`]
+pub const MANIFEST_NAME: &str = "bfk.toml";
+
+/// The program of a new project, which greets the world
+const SCAFFOLD_PROGRAM: &str = "\
+Print Hello World! followed by a newline
+
+++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]
+>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.
+";
+
+/// The example of a new project, a test run by `bfkrun test examples`
+const SCAFFOLD_EXAMPLE: &str = "\
+Copy three bytes of the input to the output
+
+,.,.,.
+";
+
+/// The manifest of a new project, with `{name}` standing for its name
+const SCAFFOLD_MANIFEST: &str = r#"# The programs of the project. Run one with `bfkrun run <name>`, and every
+# program with an `expected` output with `bfkrun test`.
+
+[[program]]
+name = {name}
+path = "src/main.bf"
+expected = "tests/hello.expected"
+
+# The tests in a directory need no manifest: `bfkrun test examples` runs
+# every program next to a `.expected` file, with its `.input` file.
+[[program]]
+name = "echo"
+path = "examples/echo.bf"
+input = "examples/echo.input"
+expected = "examples/echo.expected"
+"#;
+
+/// The programs of a project, as listed in its `bfk.toml`
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     project::Manifest,
+///     BehaviorProfile,
+/// };
+///
+/// let manifest = Manifest::from_toml(
+///     r#"
+///     [[program]]
+///     name = "hello"
+///     path = "hello.bf"
+///     profile = "strict"
+///     "#,
+/// )
+/// .unwrap();
+///
+/// let hello = manifest.program("hello").unwrap();
+/// assert_eq!(hello.path.to_str(), Some("hello.bf"));
+/// assert_eq!(hello.profile, Some(BehaviorProfile::Strict));
+/// assert!(manifest.program("goodbye").is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Manifest {
+    /// The programs of the project, in the order they are listed
+    #[serde(default, rename = "program")]
+    pub programs: Vec<ProjectProgram>,
+}
+
+/// A program listed in a [`Manifest`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ProjectProgram {
+    /// The name the program is known by in the project
+    pub name:     String,
+    /// The source file of the program
+    pub path:     PathBuf,
+    /// The dialect the program is written in
+    #[serde(default)]
+    pub dialect:  Dialect,
+    /// The behavior profile the program runs with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile:  Option<BehaviorProfile>,
+    /// The file holding the input of the program
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input:    Option<PathBuf>,
+    /// The file holding the expected output of the program. Programs with an
+    /// expected output are tests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<PathBuf>,
+    /// The files to load into the tape before the program runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data:     Vec<ProjectData>,
+}
+
+/// A data file of a [`ProjectProgram`], loaded into its tape before it runs
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectData {
+    /// The file holding the bytes to load, one per cell
+    pub path:  PathBuf,
+    /// The cell to load the first byte into
+    pub start: usize,
+}
+
+impl ProjectData {
+    /// Read the bytes of the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn read(&self) -> Result<DataSegment, FileError> {
+        DataSegment::read(&self.path, self.start)
+    }
+}
+
+impl Manifest {
+    /// Parse a manifest from the contents of a TOML file
+    ///
+    /// The paths of the programs are kept as they are written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contents are not valid TOML, contain unknown
+    /// keys or values of the wrong type, or define a program twice.
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        let manifest: Self = toml::from_str(contents)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Read a manifest file
+    ///
+    /// The paths of the programs are resolved against the directory of the
+    /// manifest.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid
+    /// manifest.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|source| FileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut manifest: Self =
+            toml::from_str(&contents).map_err(|source| ConfigError::InvalidFile {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        manifest.validate()?;
+
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+        for program in &mut manifest.programs {
+            program.path = root.join(&program.path);
+            program.input = program.input.as_ref().map(|input| root.join(input));
+            program.expected = program
+                .expected
+                .as_ref()
+                .map(|expected| root.join(expected));
+            for data in &mut program.data {
+                data.path = root.join(&data.path);
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Find the nearest manifest
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The directory to start looking in
+    ///
+    /// # Returns
+    ///
+    /// The path of the first `bfk.toml` found in `start` or one of its
+    /// ancestors, or `None` if there is none.
+    #[must_use]
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|directory| directory.join(MANIFEST_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Load the nearest manifest of the current directory
+    ///
+    /// # Returns
+    ///
+    /// The nearest manifest, or `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current directory cannot be accessed or the
+    /// manifest is not valid.
+    pub fn discover() -> Result<Option<Self>, ConfigError> {
+        let current = env::current_dir().map_err(ConfigError::CurrentDirectory)?;
+        Self::find(&current)
+            .map(|path| Self::load(&path))
+            .transpose()
+    }
+
+    /// The program called `name`
+    #[must_use]
+    pub fn program(&self, name: &str) -> Option<&ProjectProgram> {
+        self.programs.iter().find(|program| program.name == name)
+    }
+
+    /// The programs with an expected output, as tests named after the
+    /// programs
+    #[must_use]
+    pub fn test_cases(&self) -> Vec<TestCase> {
+        self.programs
+            .iter()
+            .filter_map(|program| {
+                Some(TestCase {
+                    name:     program.name.clone(),
+                    program:  program.path.clone(),
+                    expected: program.expected.clone()?,
+                    input:    program.input.clone(),
+                    dialect:  program.dialect,
+                    config:   program.config(),
+                    data:     program.data.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Run every test of the project
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The resource limits of every test
+    ///
+    /// # Returns
+    ///
+    /// The results, in the order the programs are listed.
+    #[must_use]
+    pub fn run_tests(&self, limits: Limits) -> Report {
+        let results = self
+            .test_cases()
+            .into_iter()
+            .map(|case| {
+                let outcome = case.run(limits);
+                (case, outcome)
+            })
+            .collect();
+        Report { results }
+    }
+
+    /// Start a new project in `root`
+    ///
+    /// The project is named after the directory, which must not exist yet.
+    /// It holds:
+    ///
+    /// * `bfk.toml`, the manifest
+    /// * `src/main.bf`, a program printing `Hello World!`
+    /// * `tests/hello.expected`, the output the program is tested against
+    /// * `examples/echo.bf`, with an `.input` and an `.expected` file next to
+    ///   it, a test that the test runner also finds in its directory
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The directory of the new project
+    ///
+    /// # Returns
+    ///
+    /// The manifest of the new project, with its paths resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `root` already exists or a file cannot be
+    /// written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     project::Manifest,
+    ///     Limits,
+    /// };
+    ///
+    /// let directory = tempfile::tempdir().unwrap();
+    /// let manifest =
+    ///     Manifest::scaffold(&directory.path().join("greeter")).unwrap();
+    ///
+    /// assert!(manifest.program("greeter").is_some());
+    /// assert!(manifest.run_tests(Limits::new()).is_success());
+    /// ```
+    pub fn scaffold(root: &Path) -> Result<Self, ConfigError> {
+        if root.exists() {
+            return Err(ConfigError::ProjectExists(root.to_path_buf()));
+        }
+        let name = root.file_name().map_or_else(
+            || String::from("main"),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let manifest = SCAFFOLD_MANIFEST.replace("{name}", &toml::Value::String(name).to_string());
+
+        let files = [
+            (MANIFEST_NAME, manifest.as_str()),
+            ("src/main.bf", SCAFFOLD_PROGRAM),
+            ("tests/hello.expected", "Hello World!\n"),
+            ("examples/echo.bf", SCAFFOLD_EXAMPLE),
+            ("examples/echo.input", "abc"),
+            ("examples/echo.expected", "abc"),
+        ];
+        for (file, contents) in files {
+            let path = root.join(file);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|source| FileError::Write {
+                    path: parent.to_path_buf(),
+                    source,
+                })?;
+            }
+            fs::write(&path, contents).map_err(|source| FileError::Write { path, source })?;
+        }
+        Self::load(&root.join(MANIFEST_NAME))
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut names = HashSet::new();
+        for program in &self.programs {
+            if !names.insert(program.name.as_str()) {
+                return Err(ConfigError::DuplicateProgram(program.name.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ProjectProgram {
+    /// The configuration the program runs with
+    #[must_use]
+    pub fn config(&self) -> Config {
+        Config {
+            profile: self.profile,
+            ..Config::default()
+        }
+    }
+
+    /// Read and parse the program, and check that it can be run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source file or one of the data files cannot be
+    /// read, or the brackets of the program are not balanced.
+    pub fn build(&self) -> Result<Program, VMError> {
+        let source = fs::read_to_string(&self.path).map_err(|source| FileError::Read {
+            path: self.path.clone(),
+            source,
+        })?;
+        let program = Program::parse(&source, self.dialect);
+        program.jump_table()?;
+        self.data_segments()?;
+        Ok(program)
+    }
+
+    /// Read the data files of the program
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a data file cannot be read.
+    pub fn data_segments(&self) -> Result<Vec<DataSegment>, FileError> {
+        self.data.iter().map(ProjectData::read).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::test_runner::Outcome;
+
+    const MANIFEST: &str = r#"
+        [[program]]
+        name = "echo"
+        path = "src/echo.bf"
+        input = "tests/echo.input"
+        expected = "tests/echo.expected"
+
+        [[program]]
+        name = "debug"
+        path = "src/debug.bf"
+        dialect = "debug"
+        profile = "strict"
+        expected = "tests/debug.expected"
+
+        [[program]]
+        name = "tool"
+        path = "src/tool.bf"
+    "#;
+
+    fn project() -> tempfile::TempDir {
+        let directory = tempdir().unwrap();
+        let root = directory.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("tests")).unwrap();
+        fs::write(root.join(MANIFEST_NAME), MANIFEST).unwrap();
+        fs::write(root.join("src/echo.bf"), ",.,.").unwrap();
+        fs::write(root.join("tests/echo.input"), "hi").unwrap();
+        fs::write(root.join("tests/echo.expected"), "hi").unwrap();
+        fs::write(root.join("src/debug.bf"), "+#<").unwrap();
+        fs::write(root.join("tests/debug.expected"), "").unwrap();
+        fs::write(root.join("src/tool.bf"), "[").unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_load_resolves_paths() {
+        let directory = project();
+        let manifest = Manifest::load(&directory.path().join(MANIFEST_NAME)).unwrap();
+
+        assert_eq!(manifest.programs.len(), 3);
+        let echo = manifest.program("echo").unwrap();
+        assert_eq!(echo.path, directory.path().join("src/echo.bf"));
+        assert_eq!(
+            echo.input.as_deref(),
+            Some(directory.path().join("tests/echo.input").as_path())
+        );
+        assert_eq!(echo.dialect, Dialect::Standard);
+        assert_eq!(manifest.program("debug").unwrap().dialect, Dialect::Debug);
+    }
+
+    #[test]
+    fn test_find() {
+        let directory = project();
+        let nested = directory.path().join("src");
+
+        assert_eq!(
+            Manifest::find(&nested),
+            Some(directory.path().join(MANIFEST_NAME))
+        );
+    }
+
+    #[test]
+    fn test_build() {
+        let directory = project();
+        let manifest = Manifest::load(&directory.path().join(MANIFEST_NAME)).unwrap();
+
+        assert!(manifest.program("echo").unwrap().build().is_ok());
+        assert!(matches!(
+            manifest.program("tool").unwrap().build(),
+            Err(VMError::Program(_))
+        ));
+    }
+
+    #[test]
+    fn test_run_tests() {
+        let directory = project();
+        let manifest = Manifest::load(&directory.path().join(MANIFEST_NAME)).unwrap();
+        let report = manifest.run_tests(Limits::new().max_steps(1000));
+        let names: Vec<_> = report
+            .results
+            .iter()
+            .map(|(case, _)| case.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["echo", "debug"]);
+        assert_eq!(report.results[0].1, Outcome::Passed);
+        assert!(matches!(report.results[1].1, Outcome::Error(_)));
+    }
+
+    #[test]
+    fn test_data() {
+        let directory = tempdir().unwrap();
+        let root = directory.path();
+        fs::create_dir_all(root.join("tables")).unwrap();
+        fs::write(
+            root.join(MANIFEST_NAME),
+            r#"
+            [[program]]
+            name = "greet"
+            path = "greet.bf"
+            expected = "greet.expected"
+            data = [{ path = "tables/greeting.bin", start = 2 }]
+            "#,
+        )
+        .unwrap();
+        fs::write(root.join("greet.bf"), ">>.>.").unwrap();
+        fs::write(root.join("greet.expected"), "hi").unwrap();
+        fs::write(root.join("tables/greeting.bin"), "hi").unwrap();
+
+        let manifest = Manifest::load(&root.join(MANIFEST_NAME)).unwrap();
+        let greet = manifest.program("greet").unwrap();
+        assert_eq!(greet.data[0].path, root.join("tables/greeting.bin"));
+        assert_eq!(greet.data_segments().unwrap()[0].bytes(), b"hi");
+        assert!(greet.build().is_ok());
+        assert!(manifest.run_tests(Limits::new()).is_success());
+
+        fs::remove_file(root.join("tables/greeting.bin")).unwrap();
+        assert!(matches!(greet.build(), Err(VMError::File(_))));
+    }
+
+    #[test]
+    fn test_invalid_manifests() {
+        let duplicate = "[[program]]\nname = \"a\"\npath = \"a.bf\"\n\n[[program]]\nname = \
+                         \"a\"\npath = \"b.bf\"\n";
+
+        assert!(matches!(
+            Manifest::from_toml(duplicate),
+            Err(ConfigError::DuplicateProgram(name)) if name == "a"
+        ));
+        assert!(matches!(
+            Manifest::from_toml("[[program]]\nname = \"a\"\n"),
+            Err(ConfigError::Toml(_))
+        ));
+        assert!(matches!(
+            Manifest::from_toml("[[program]]\nname = \"a\"\npath = \"a.bf\"\nspeed = 11\n"),
+            Err(ConfigError::Toml(_))
+        ));
+        assert_eq!(Manifest::from_toml("").unwrap(), Manifest::default());
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let directory = tempdir().unwrap();
+        let root = directory.path().join("my project");
+        let manifest = Manifest::scaffold(&root).unwrap();
+
+        let names: Vec<_> = manifest
+            .programs
+            .iter()
+            .map(|program| program.name.as_str())
+            .collect();
+        assert_eq!(names, ["my project", "echo"]);
+        assert_eq!(manifest.programs[0].path, root.join("src/main.bf"));
+
+        let report = manifest.run_tests(Limits::new().max_steps(10_000));
+        assert!(report.is_success(), "{report}");
+        let examples = crate::test_runner::run_dir(&root.join("examples")).unwrap();
+        assert_eq!(examples.results.len(), 1);
+        assert!(examples.is_success());
+
+        assert!(matches!(
+            Manifest::scaffold(&root),
+            Err(ConfigError::ProjectExists(path)) if path == root
+        ));
+    }
+}
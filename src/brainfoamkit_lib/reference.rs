@@ -0,0 +1,161 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A minimal reference interpreter
+//!
+//! This interpreter keeps the tape as a plain array of `u8` and implements
+//! every instruction in a line or two, so that it is easy to convince yourself
+//! that it is correct. It exists to cross-check the
+//! [`VirtualMachine`](crate::VirtualMachine), see
+//! [`differential::compare`](crate::differential::compare).
+//!
+//! The semantics match the `VirtualMachine`: cells wrap around on overflow,
+//! the pointer wraps around at both ends of the tape and reading past the end
+//! of the input leaves the current cell unchanged.
+
+use crate::{
+    Instruction,
+    LimitExceeded,
+    Program,
+    VMError,
+};
+
+/// The result of running a program on the reference interpreter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// Every byte the program wrote
+    pub output: Vec<u8>,
+    /// The tape after the program halted
+    pub tape:   Vec<u8>,
+}
+
+/// Run a `Program` on the reference interpreter
+///
+/// # Arguments
+///
+/// * `program` - The program to run
+/// * `input` - The bytes the program reads, in order
+/// * `tape_size` - The number of cells on the tape
+/// * `max_steps` - The maximum number of instructions to execute
+///
+/// # Returns
+///
+/// The output and the final tape of the program
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     reference,
+///     Program,
+/// };
+///
+/// let outcome = reference::run(&Program::from(",+."), b"A", 4, 100).unwrap();
+///
+/// assert_eq!(outcome.output, b"B");
+/// assert_eq!(outcome.tape, vec![66, 0, 0, 0]);
+/// ```
+///
+/// # Errors
+///
+/// * Returns an error if the brackets of the program are not balanced.
+/// * Returns a [`LimitExceeded`] error if the program does not halt within
+///   `max_steps` instructions.
+///
+/// # Panics
+///
+/// Panics if `tape_size` is zero.
+pub fn run(
+    program: &Program,
+    input: &[u8],
+    tape_size: usize,
+    max_steps: usize,
+) -> Result<Outcome, VMError> {
+    assert!(tape_size > 0, "the tape needs at least one cell");
+    let jump_table = program.jump_table()?;
+    let mut tape = vec![0u8; tape_size];
+    let mut input = input.iter();
+    let mut output = Vec::new();
+    let mut pointer = 0;
+    let mut pc = 0;
+    let mut steps = 0;
+
+    while let Some(instruction) = program.get_instruction(pc) {
+        if steps >= max_steps {
+            return Err(LimitExceeded::Steps { limit: max_steps }.into());
+        }
+        match instruction {
+            Instruction::IncrementPointer => pointer = (pointer + 1) % tape_size,
+            Instruction::DecrementPointer => pointer = (pointer + tape_size - 1) % tape_size,
+            Instruction::IncrementValue => tape[pointer] = tape[pointer].wrapping_add(1),
+            Instruction::DecrementValue => tape[pointer] = tape[pointer].wrapping_sub(1),
+            Instruction::OutputValue => output.push(tape[pointer]),
+            Instruction::InputValue => {
+                if let Some(byte) = input.next() {
+                    tape[pointer] = *byte;
+                }
+            }
+            Instruction::JumpForward if tape[pointer] == 0 => {
+                pc = jump_table[pc].unwrap_or(pc);
+            }
+            Instruction::JumpBackward if tape[pointer] != 0 => {
+                pc = jump_table[pc].unwrap_or(pc);
+            }
+            _ => {}
+        }
+        pc += 1;
+        steps += 1;
+    }
+
+    Ok(Outcome { output, tape })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hello_world() {
+        let program = Program::from(
+            "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.\
+             ------.--------.>>+.>++.",
+        );
+        let outcome = run(&program, &[], 30000, 10_000).unwrap();
+
+        assert_eq!(outcome.output, b"Hello World!\n");
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let outcome = run(&Program::from("-<+"), &[], 3, 10).unwrap();
+
+        assert_eq!(outcome.tape, vec![255, 0, 1]);
+
+        let outcome = run(&Program::from(">>>+"), &[], 3, 10).unwrap();
+        assert_eq!(outcome.tape, vec![1, 0, 0]);
+    }
+
+    #[test]
+    fn test_end_of_input() {
+        let outcome = run(&Program::from("+,>,"), b"", 2, 10).unwrap();
+
+        assert_eq!(outcome.tape, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let error = run(&Program::from("+[]"), &[], 1, 50).unwrap_err();
+
+        assert!(matches!(
+            error,
+            VMError::LimitExceeded(LimitExceeded::Steps { limit: 50 })
+        ));
+    }
+
+    #[test]
+    fn test_unbalanced_program() {
+        assert!(run(&Program::from("[+"), &[], 1, 10).is_err());
+    }
+}
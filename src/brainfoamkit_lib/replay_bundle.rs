@@ -0,0 +1,472 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    config::Config,
+    random_byte::clock_seed,
+    CaptureWriter,
+    Dialect,
+    FileError,
+    Program,
+    ReadError,
+    ReplayError,
+    VMReader,
+    VMWriter,
+    VecReader,
+    VirtualMachine,
+};
+
+/// The version of the replay bundle format written by [`ReplayBundle::save`]
+const FORMAT_VERSION: u32 = 1;
+
+/// Everything needed to run a program again exactly as it ran once
+///
+/// A bundle holds the source of the program, its dialect, the configuration
+/// it ran with, the seed of its random bytes, every byte it read and what
+/// came out of the run. Given the
+/// same bundle, [`replay`](Self::replay) runs the program the same way on
+/// any machine, without looking at the environment or a
+/// `brainfoamkit.toml`, so a reported behavior can be reproduced and checked
+/// bit for bit.
+///
+/// Bundles are saved as JSON, conventionally in a file ending in `.bfkrun`.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     config::Config,
+///     CaptureWriter,
+///     Dialect,
+///     ReplayBundle,
+///     VecReader,
+/// };
+///
+/// let (bundle, _) = ReplayBundle::record(
+///     ",+.",
+///     Dialect::Standard,
+///     Config::default(),
+///     None,
+///     VecReader::new(b"a".to_vec()),
+///     CaptureWriter::default(),
+/// )
+/// .unwrap();
+/// assert_eq!(bundle.input, b"a");
+/// assert_eq!(bundle.outcome.output, b"b");
+///
+/// let bundle = ReplayBundle::from_json(&bundle.to_json()).unwrap();
+/// assert_eq!(bundle.replay().unwrap(), bundle.outcome);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ReplayBundle {
+    /// The version of the format of the bundle
+    pub version: u32,
+    /// The source of the program
+    pub program: String,
+    /// The dialect the program is written in
+    pub dialect: Dialect,
+    /// The configuration the program ran with, with every setting of the
+    /// environment and the configuration files already merged in
+    pub config:  Config,
+    /// The seed of the random bytes of `?`, for a program in the
+    /// [`Random`](Dialect::Random) dialect
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed:    Option<u64>,
+    /// Every byte the program read, in order
+    pub input:   Vec<u8>,
+    /// What came out of the recorded run
+    pub outcome: ReplayOutcome,
+}
+
+/// What came out of running the program of a [`ReplayBundle`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ReplayOutcome {
+    /// Every byte the program wrote
+    pub output: Vec<u8>,
+    /// The number of instructions executed
+    pub steps:  usize,
+    /// Why the program failed, if it did
+    pub error:  Option<String>,
+}
+
+impl ReplayBundle {
+    /// Run a program and record everything needed to replay it
+    ///
+    /// The program reads from `input` and writes to `output` as it would
+    /// without recording, so an interactive run can be recorded as it
+    /// happens.
+    ///
+    /// A program in the [`Random`](Dialect::Random) dialect gets its random
+    /// bytes from `seed`, or from a seed taken from the clock, and the seed is
+    /// saved in the bundle so that the replay gets the same bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source of the program
+    /// * `dialect` - The dialect the program is written in
+    /// * `config` - The configuration to run the program with
+    /// * `seed` - The seed of the random bytes of `?`
+    /// * `input` - The input device of the program
+    /// * `output` - The output device of the program
+    ///
+    /// # Returns
+    ///
+    /// The bundle, and the output device once the program stopped
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration is not supported or the brackets
+    /// of the program are not balanced. A program that fails while it runs is
+    /// recorded with its error.
+    pub fn record<R, W>(
+        source: &str,
+        dialect: Dialect,
+        config: Config,
+        seed: Option<u64>,
+        input: R,
+        output: W,
+    ) -> Result<(Self, W), ReplayError>
+    where
+        R: VMReader,
+        W: VMWriter,
+    {
+        let seed = seed.or_else(|| (dialect == Dialect::Random).then(clock_seed));
+        let (outcome, devices) = run(
+            source,
+            dialect,
+            &config,
+            seed,
+            RecordingReader::new(input),
+            Recorder {
+                inner:    output,
+                recorded: Vec::new(),
+            },
+        )?;
+
+        let bundle = Self {
+            version: FORMAT_VERSION,
+            program: source.to_string(),
+            dialect,
+            config,
+            seed,
+            input: devices.0.into_recorded(),
+            outcome: ReplayOutcome {
+                output: devices.1.recorded,
+                ..outcome
+            },
+        };
+        Ok((bundle, devices.1.inner))
+    }
+
+    /// Run the program of the bundle again, on the recorded input
+    ///
+    /// # Returns
+    ///
+    /// What came out of the run, which equals the recorded
+    /// [`outcome`](Self::outcome) if the behavior was reproduced
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration of the bundle is not supported or
+    /// the brackets of the program are not balanced.
+    pub fn replay(&self) -> Result<ReplayOutcome, ReplayError> {
+        let (outcome, devices) = run(
+            &self.program,
+            self.dialect,
+            &self.config,
+            self.seed,
+            VecReader::new(self.input.clone()),
+            CaptureWriter::default(),
+        )?;
+
+        Ok(ReplayOutcome {
+            output: devices.1.data,
+            ..outcome
+        })
+    }
+
+    /// The bundle as JSON
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Read a bundle from JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a bundle, or a bundle of a newer
+    /// version of the format.
+    pub fn from_json(json: &str) -> Result<Self, ReplayError> {
+        let bundle: Self = serde_json::from_str(json)?;
+        if bundle.version > FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedVersion(bundle.version));
+        }
+        Ok(bundle)
+    }
+
+    /// Write the bundle to a file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), FileError> {
+        fs::write(path, self.to_json()).map_err(|source| FileError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Read a bundle from a file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or does not hold a bundle.
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let json = fs::read_to_string(path).map_err(|source| FileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_json(&json)
+    }
+}
+
+/// Run `source` until it halts or fails.
+fn run<R, W>(
+    source: &str,
+    dialect: Dialect,
+    config: &Config,
+    seed: Option<u64>,
+    input: R,
+    output: W,
+) -> Result<(ReplayOutcome, (R, W)), ReplayError>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    let builder = VirtualMachine::builder()
+        .input_device(input)
+        .output_device(output)
+        .program(Program::parse(source, dialect));
+    let builder = match seed {
+        Some(seed) => builder.random_seed(seed),
+        None => builder,
+    };
+    let mut machine = config.apply(builder)?.build()?;
+
+    let error = machine.run().err().map(|error| error.to_string());
+    machine.output_device().finish()?;
+    let steps = machine.steps();
+    let (_, _, devices, _) = machine.into_parts();
+
+    Ok((
+        ReplayOutcome {
+            output: Vec::new(),
+            steps,
+            error,
+        },
+        (devices.input, devices.output),
+    ))
+}
+
+/// A reader that keeps a copy of every byte read through it
+///
+/// Used by [`ReplayBundle::record`] to record the input of a program, and
+/// useful on its own to capture what an interactive program was given.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     RecordingReader,
+///     VMReader,
+///     VecReader,
+/// };
+///
+/// let mut reader = RecordingReader::new(VecReader::new(b"hi".to_vec()));
+/// reader.read().unwrap();
+///
+/// assert_eq!(reader.recorded(), b"h");
+/// ```
+#[derive(Debug)]
+pub struct RecordingReader<R: VMReader> {
+    inner:    R,
+    recorded: Vec<u8>,
+}
+
+impl<R: VMReader> RecordingReader<R> {
+    /// Record the bytes read from `inner`
+    #[must_use]
+    pub const fn new(inner: R) -> Self {
+        Self {
+            inner,
+            recorded: Vec::new(),
+        }
+    }
+
+    /// The bytes read so far
+    #[must_use]
+    pub fn recorded(&self) -> &[u8] {
+        &self.recorded
+    }
+
+    /// Take the bytes read so far
+    #[must_use]
+    pub fn into_recorded(self) -> Vec<u8> {
+        self.recorded
+    }
+}
+
+impl<R: VMReader> VMReader for RecordingReader<R> {
+    fn read(&mut self) -> Result<u8, ReadError> {
+        let byte = self.inner.read()?;
+        self.recorded.push(byte);
+        Ok(byte)
+    }
+}
+
+/// A writer that keeps a copy of everything written to it.
+struct Recorder<W: VMWriter> {
+    inner:    W,
+    recorded: Vec<u8>,
+}
+
+impl<W: VMWriter> VMWriter for Recorder<W> {
+    fn write(&mut self, value: u8) -> io::Result<()> {
+        self.recorded.push(value);
+        self.inner.write(value)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.inner.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BehaviorProfile,
+        EofBehavior,
+    };
+
+    fn record(source: &str, input: &[u8], config: Config) -> ReplayBundle {
+        ReplayBundle::record(
+            source,
+            Dialect::Standard,
+            config,
+            None,
+            VecReader::new(input.to_vec()),
+            CaptureWriter::default(),
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let bundle = record(
+            ",[.,]",
+            b"echo",
+            Config {
+                eof: Some(EofBehavior::Zero),
+                tape_size: Some(4),
+                ..Config::default()
+            },
+        );
+
+        assert_eq!(bundle.input, b"echo");
+        assert_eq!(bundle.outcome.output, b"echo");
+        assert_eq!(bundle.outcome.error, None);
+        assert_eq!(bundle.replay().unwrap(), bundle.outcome);
+    }
+
+    #[test]
+    fn test_failures_are_recorded() {
+        let bundle = record(
+            "<",
+            b"",
+            Config {
+                profile: Some(BehaviorProfile::Strict),
+                ..Config::default()
+            },
+        );
+
+        assert!(bundle.outcome.error.is_some());
+        assert_eq!(bundle.replay().unwrap(), bundle.outcome);
+    }
+
+    #[test]
+    fn test_random_dialect() {
+        let record = |seed| {
+            ReplayBundle::record(
+                "??.?.",
+                Dialect::Random,
+                Config::default(),
+                seed,
+                VecReader::new(Vec::new()),
+                CaptureWriter::default(),
+            )
+            .unwrap()
+            .0
+        };
+
+        let bundle = record(None);
+        assert!(bundle.seed.is_some());
+        assert_eq!(bundle.outcome.output.len(), 2);
+        let bundle = ReplayBundle::from_json(&bundle.to_json()).unwrap();
+        assert_eq!(bundle.replay().unwrap(), bundle.outcome);
+
+        assert_eq!(record(Some(7)).seed, Some(7));
+        assert_eq!(record(Some(7)).outcome, record(Some(7)).outcome);
+    }
+
+    #[test]
+    fn test_json() {
+        let bundle = record("+.", b"", Config::default());
+        let json = bundle.to_json();
+
+        assert!(json.contains(r#""dialect": "standard""#));
+        assert!(!json.contains("seed"));
+        assert_eq!(ReplayBundle::from_json(&json).unwrap(), bundle);
+
+        let newer = json.replace(r#""version": 1"#, r#""version": 2"#);
+        assert!(matches!(
+            ReplayBundle::from_json(&newer),
+            Err(ReplayError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            ReplayBundle::from_json("{}"),
+            Err(ReplayError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("run.bfkrun");
+        let bundle = record(",.", b"x", Config::default());
+
+        bundle.save(&path).unwrap();
+        assert_eq!(ReplayBundle::load(&path).unwrap(), bundle);
+    }
+}
@@ -0,0 +1,194 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    time::Duration,
+};
+
+use crate::format::{
+    human_bytes,
+    human_duration,
+};
+
+/// The CPU time and the peak memory used by the process
+///
+/// The wall clock time of a run depends on whatever else the machine is
+/// doing. The CPU time and the peak memory do much less, which makes them the
+/// better numbers to compare two ways of running the same program, for
+/// example the interpreter against an optimized engine.
+///
+/// With the `resources` feature on Unix, [`current`](Self::current) asks the
+/// operating system with `getrusage`. Without it, or on other platforms, the
+/// usage is not available. The CPU time of a single run is the difference
+/// between the usage after and before it, see [`since`](Self::since). The
+/// peak memory can only be known for the whole process.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use brainfoamkit_lib::ResourceUsage;
+///
+/// let before =
+///     ResourceUsage::new(1 << 20, Duration::from_millis(5), Duration::ZERO);
+/// let after = ResourceUsage::new(
+///     3 << 20,
+///     Duration::from_millis(25),
+///     Duration::from_millis(2),
+/// );
+/// let run = after.since(&before);
+///
+/// assert_eq!(run.cpu_time(), Duration::from_millis(22));
+/// assert_eq!(
+///     run.to_string(),
+///     "22.0 ms of CPU time (20.0 ms user, 2.0 ms system) with a peak memory \
+///      of 3.0 MiB"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    peak_memory: usize,
+    user_time:   Duration,
+    system_time: Duration,
+}
+
+impl ResourceUsage {
+    /// Create a `ResourceUsage` from its numbers
+    ///
+    /// # Arguments
+    ///
+    /// * `peak_memory` - The most memory the process held at once, in bytes
+    /// * `user_time` - The CPU time spent running the process itself
+    /// * `system_time` - The CPU time spent in the kernel for the process
+    #[must_use]
+    pub const fn new(peak_memory: usize, user_time: Duration, system_time: Duration) -> Self {
+        Self {
+            peak_memory,
+            user_time,
+            system_time,
+        }
+    }
+
+    /// The resources the process used so far
+    ///
+    /// # Returns
+    ///
+    /// The usage, or `None` without the `resources` feature, on platforms
+    /// other than Unix, or if the operating system does not answer.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        usage()
+    }
+
+    /// The CPU time used since `start`, with the peak memory of `self`
+    #[must_use]
+    pub fn since(&self, start: &Self) -> Self {
+        Self {
+            peak_memory: self.peak_memory,
+            user_time:   self.user_time.saturating_sub(start.user_time),
+            system_time: self.system_time.saturating_sub(start.system_time),
+        }
+    }
+
+    /// The most memory the process held at once, in bytes
+    #[must_use]
+    pub const fn peak_memory(&self) -> usize {
+        self.peak_memory
+    }
+
+    /// The CPU time spent running the process itself
+    #[must_use]
+    pub const fn user_time(&self) -> Duration {
+        self.user_time
+    }
+
+    /// The CPU time spent in the kernel on behalf of the process
+    #[must_use]
+    pub const fn system_time(&self) -> Duration {
+        self.system_time
+    }
+
+    /// The CPU time spent in total, user and system
+    #[must_use]
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+}
+
+impl Display for ResourceUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of CPU time ({} user, {} system) with a peak memory of {}",
+            human_duration(self.cpu_time()),
+            human_duration(self.user_time),
+            human_duration(self.system_time),
+            human_bytes(self.peak_memory)
+        )
+    }
+}
+
+#[cfg(all(feature = "resources", unix))]
+fn usage() -> Option<ResourceUsage> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+    // SAFETY: getrusage only writes to the struct it is given
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: the struct was zeroed, and filled in by getrusage
+    let usage = unsafe { usage.assume_init() };
+
+    let time = |time: libc::timeval| {
+        Some(Duration::new(
+            u64::try_from(time.tv_sec).ok()?,
+            u32::try_from(time.tv_usec).ok()? * 1000,
+        ))
+    };
+    // The peak is in kilobytes, except on macOS where it is in bytes
+    let unit = if cfg!(target_os = "macos") {
+        1
+    } else {
+        1024
+    };
+    Some(ResourceUsage::new(
+        usize::try_from(usage.ru_maxrss).ok()?.saturating_mul(unit),
+        time(usage.ru_utime)?,
+        time(usage.ru_stime)?,
+    ))
+}
+
+#[cfg(not(all(feature = "resources", unix)))]
+const fn usage() -> Option<ResourceUsage> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since_keeps_the_peak() {
+        let start = ResourceUsage::new(100, Duration::from_secs(2), Duration::from_secs(1));
+        let end = ResourceUsage::new(50, Duration::from_secs(3), Duration::from_millis(500));
+
+        let run = end.since(&start);
+        assert_eq!(run.peak_memory(), 50);
+        assert_eq!(run.user_time(), Duration::from_secs(1));
+        assert_eq!(run.system_time(), Duration::ZERO);
+    }
+
+    #[cfg(all(feature = "resources", unix))]
+    #[test]
+    fn test_current() {
+        let usage = ResourceUsage::current().unwrap();
+        assert!(usage.peak_memory() > 0);
+    }
+}
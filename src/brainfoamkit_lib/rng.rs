@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use rand_core::{
+    impls,
+    RngCore,
+    SeedableRng,
+};
+
+/// A small, seeded pseudo-random number generator
+///
+/// This is SplitMix64: fast, good enough for injecting faults and picking
+/// values, and the same sequence for the same seed on every platform. It is
+/// not suitable for anything that needs to be unpredictable.
+///
+/// The seeds recorded by `bfkrun run --seed`, replay bundles and fault plans
+/// have to replay the same run in every later version of the crate. `rand`
+/// does not promise that for its own generators, so the default stays this
+/// one, whose sequence is fixed by the tests below. Callers who want another
+/// generator hand a [`RngCore`] to [`RandomByte`](crate::RandomByte).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator that yields the sequence of `seed`
+    pub(crate) const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next number of the sequence
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `0` up to, but not including, `bound`
+    ///
+    /// `bound` must not be zero.
+    pub(crate) fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// `true` once in `one_in` calls, on average. Never `true` if `one_in` is
+    /// zero.
+    pub(crate) fn one_in(&mut self, one_in: u32) -> bool {
+        one_in != 0 && self.below(u64::from(one_in)) == 0
+    }
+}
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        // The high bits of SplitMix64 are as good as the low ones
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        Self::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, destination: &mut [u8]) {
+        impls::fill_bytes_via_next(self, destination);
+    }
+}
+
+impl SeedableRng for Rng {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(u64::from_le_bytes(seed))
+    }
+
+    fn seed_from_u64(state: u64) -> Self {
+        Self::new(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut first = Rng::new(42);
+        let mut second = Rng::new(42);
+
+        for _ in 0..16 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn test_known_sequence() {
+        let mut rng = Rng::new(0);
+
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn test_seedable() {
+        let mut seeded = Rng::seed_from_u64(0);
+        let mut from_bytes = Rng::from_seed(0_u64.to_le_bytes());
+
+        assert_eq!(RngCore::next_u64(&mut seeded), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(RngCore::next_u32(&mut from_bytes), 0xE220_A839);
+    }
+
+    #[test]
+    fn test_one_in() {
+        let mut rng = Rng::new(7);
+
+        assert!((0..100).all(|_| rng.one_in(1)));
+        assert!((0..100).all(|_| !rng.one_in(0)));
+        let hits = (0..10_000).filter(|_| rng.one_in(10)).count();
+        assert!((800..1200).contains(&hits));
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::iter::FusedIterator;
+
+use crate::{
+    Byte,
+    Instruction,
+    VMError,
+    VMReader,
+    VMWriter,
+    VirtualMachine,
+};
+
+/// Something that happened while a `VirtualMachine` ran, yielded by
+/// [`VirtualMachine::run_iter`](crate::VirtualMachine::run_iter)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionEvent {
+    /// The program wrote the contained value to the output device
+    Output(Byte),
+    /// The next instruction reads from the input device. It is executed when
+    /// the iterator is advanced, so the input can be provided in between.
+    NeedsInput,
+    /// The program executed a `#` of the `Debug` dialect
+    Breakpoint,
+    /// The program counter moved past the last instruction of the `Program`
+    Halted,
+}
+
+/// An iterator that runs a `VirtualMachine` from one [`ExecutionEvent`] to
+/// the next
+///
+/// Every call to `next` executes instructions until something happens that
+/// the caller may want to react to. The iterator ends after yielding
+/// [`ExecutionEvent::Halted`] or an error. Breaking on input and output with
+/// [`set_break_on_input`](crate::VirtualMachine::set_break_on_input) and
+/// [`set_break_on_output`](crate::VirtualMachine::set_break_on_output) is
+/// ignored, the events take their place.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     CaptureWriter,
+///     ExecutionEvent,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from("+.+."))
+///     .build()
+///     .unwrap();
+///
+/// let events = machine.run_iter().collect::<Result<Vec<_>, _>>().unwrap();
+///
+/// assert_eq!(
+///     events,
+///     [
+///         ExecutionEvent::Output(Byte::from(1)),
+///         ExecutionEvent::Output(Byte::from(2)),
+///         ExecutionEvent::Halted,
+///     ]
+/// );
+/// ```
+pub struct RunIter<'a, R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    machine:         &'a mut VirtualMachine<R, W>,
+    input_announced: bool,
+    finished:        bool,
+}
+
+impl<'a, R, W> RunIter<'a, R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    pub(crate) fn new(machine: &'a mut VirtualMachine<R, W>) -> Self {
+        Self {
+            machine,
+            input_announced: false,
+            finished: false,
+        }
+    }
+
+    /// The machine being run, to inspect it or provide input between events
+    pub fn machine(&mut self) -> &mut VirtualMachine<R, W> {
+        self.machine
+    }
+}
+
+impl<R, W> Iterator for RunIter<'_, R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    type Item = Result<ExecutionEvent, VMError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            let Some(instruction) = self.machine.get_instruction() else {
+                self.finished = true;
+                return Some(Ok(ExecutionEvent::Halted));
+            };
+            if instruction == Instruction::InputValue && !self.input_announced {
+                self.input_announced = true;
+                return Some(Ok(ExecutionEvent::NeedsInput));
+            }
+            self.input_announced = false;
+
+            let value = self.machine.tape()[self.machine.memory_pointer()];
+            if let Err(error) = self.machine.execute_instruction() {
+                self.finished = true;
+                return Some(Err(error));
+            }
+            match instruction {
+                Instruction::OutputValue => return Some(Ok(ExecutionEvent::Output(value))),
+                Instruction::DebugDump => return Some(Ok(ExecutionEvent::Breakpoint)),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R, W> FusedIterator for RunIter<'_, R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CaptureWriter,
+        Dialect,
+        LimitExceeded,
+        Limits,
+        Program,
+        VecReader,
+    };
+
+    fn machine(program: Program, input: &[u8]) -> VirtualMachine<VecReader, CaptureWriter> {
+        VirtualMachine::builder()
+            .input_device(VecReader::new(input.to_vec()))
+            .output_device(CaptureWriter::default())
+            .program(program)
+            .limits(Limits::new().max_steps(100))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_events() {
+        let mut vm = machine(Program::parse(",+.#", Dialect::Debug), b"a");
+        let mut events = vm.run_iter();
+
+        assert_eq!(events.next().unwrap().unwrap(), ExecutionEvent::NeedsInput);
+        assert_eq!(events.machine().program_counter(), 0);
+        assert_eq!(
+            events.next().unwrap().unwrap(),
+            ExecutionEvent::Output(Byte::from(b'b'))
+        );
+        assert_eq!(events.next().unwrap().unwrap(), ExecutionEvent::Breakpoint);
+        assert_eq!(events.next().unwrap().unwrap(), ExecutionEvent::Halted);
+        assert!(events.next().is_none());
+
+        assert_eq!(vm.output_device().data, b"b");
+    }
+
+    #[test]
+    fn test_error_ends_the_iterator() {
+        let mut vm = machine(Program::from("+[]"), &[]);
+        let mut events = vm.run_iter();
+
+        assert!(matches!(
+            events.next(),
+            Some(Err(VMError::LimitExceeded(LimitExceeded::Steps {
+                limit: 100,
+            })))
+        ));
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn test_halted_machine() {
+        let mut vm = machine(Program::from("+"), &[]);
+        vm.run().unwrap();
+
+        let events = vm.run_iter().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(events, [ExecutionEvent::Halted]);
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::Path,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Bookmarks,
+    FileError,
+    SessionError,
+};
+
+/// The version of the session format written by [`DebugSession::save`]
+const FORMAT_VERSION: u32 = 1;
+
+/// What a debugger remembers about a program between runs
+///
+/// A session holds the [`Bookmarks`] and the breakpoints set while debugging
+/// a program, so that the next debugging session of the same program can
+/// start with them. It does not hold the state of the machine: see
+/// [`Checkpoint`](crate::Checkpoint) for that.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::DebugSession;
+///
+/// let mut session = DebugSession::default();
+/// session.bookmarks.bookmark_cell("result", 2);
+/// session.breakpoints.insert(7);
+///
+/// let restored = DebugSession::from_json(&session.to_json()).unwrap();
+/// assert_eq!(restored.bookmarks.cell("result"), Some(2));
+/// assert!(restored.breakpoints.contains(&7));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DebugSession {
+    /// The version of the format the session was written in
+    pub version:     u32,
+    /// The named cells and instructions
+    #[serde(default)]
+    pub bookmarks:   Bookmarks,
+    /// The positions of the instructions to pause at
+    #[serde(default)]
+    pub breakpoints: BTreeSet<usize>,
+}
+
+impl Default for DebugSession {
+    fn default() -> Self {
+        Self {
+            version:     FORMAT_VERSION,
+            bookmarks:   Bookmarks::default(),
+            breakpoints: BTreeSet::new(),
+        }
+    }
+}
+
+impl DebugSession {
+    /// The session as JSON
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Read a session from JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a session, or a session of a newer
+    /// version of the format.
+    pub fn from_json(json: &str) -> Result<Self, SessionError> {
+        let session: Self = serde_json::from_str(json)?;
+        if session.version > FORMAT_VERSION {
+            return Err(SessionError::UnsupportedVersion(session.version));
+        }
+        Ok(session)
+    }
+
+    /// Write the session to a file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), FileError> {
+        fs::write(path, self.to_json()).map_err(|source| FileError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Read a session from a file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or does not hold a
+    /// session.
+    pub fn load(path: &Path) -> Result<Self, SessionError> {
+        let json = fs::read_to_string(path).map_err(|source| FileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_json(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join("program.session.json");
+        let mut session = DebugSession::default();
+        session.bookmarks.bookmark_pc("main_loop", 5);
+        session.breakpoints.insert(3);
+
+        session.save(&path).unwrap();
+        assert_eq!(DebugSession::load(&path).unwrap(), session);
+    }
+
+    #[test]
+    fn test_invalid_sessions() {
+        assert!(matches!(
+            DebugSession::from_json(r#"{"version": 2}"#),
+            Err(SessionError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            DebugSession::from_json(r#"{"version": 1, "bookmarks": 3}"#),
+            Err(SessionError::Invalid(_))
+        ));
+        assert_eq!(
+            DebugSession::from_json(r#"{"version": 1}"#).unwrap(),
+            DebugSession::default()
+        );
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    io::Stdout,
+    sync::{
+        Arc,
+        Mutex,
+        MutexGuard,
+        PoisonError,
+    },
+};
+
+use crate::{
+    VMError,
+    VMReader,
+    VMWriter,
+    VirtualMachine,
+};
+
+/// A `VirtualMachine` that can be shared between threads
+///
+/// `SharedVm` wraps the machine in an `Arc<Mutex<_>>`. Cloning a `SharedVm`
+/// creates another handle to the same machine. Every method locks the machine
+/// for as short as possible, so that a worker thread can step the machine
+/// while another thread, such as the render loop of a user interface,
+/// inspects it between steps.
+///
+/// `SharedVm` is `Send` and `Sync` whenever the input and output devices of
+/// the machine are `Send`.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     Program,
+///     SharedVm,
+///     VirtualMachine,
+/// };
+///
+/// let machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from("++++++++[>++++++++<-]>+."))
+///     .build()
+///     .unwrap();
+/// let shared = SharedVm::new(machine);
+///
+/// let worker = {
+///     let shared = shared.clone();
+///     thread::spawn(move || while shared.step_n(10).unwrap() > 0 {})
+/// };
+/// worker.join().unwrap();
+///
+/// assert!(shared.is_halted());
+/// assert_eq!(
+///     shared.with(|machine| machine.output_device().data.clone()),
+///     b"A"
+/// );
+/// ```
+pub struct SharedVm<R, W = Stdout>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    machine: Arc<Mutex<VirtualMachine<R, W>>>,
+}
+
+impl<R, W> SharedVm<R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    /// Share a `VirtualMachine`
+    #[must_use]
+    pub fn new(machine: VirtualMachine<R, W>) -> Self {
+        Self {
+            machine: Arc::new(Mutex::new(machine)),
+        }
+    }
+
+    /// Execute the next instruction of the machine
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction).
+    pub fn step(&self) -> Result<(), VMError> {
+        self.lock().execute_instruction()
+    }
+
+    /// Execute up to `steps` instructions while holding the lock once
+    ///
+    /// Stepping in batches keeps the overhead of locking low while still
+    /// giving other threads regular access to the machine.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - The maximum number of instructions to execute
+    ///
+    /// # Returns
+    ///
+    /// The number of instructions executed, which is less than `steps` if the
+    /// machine halted. Zero means that the machine was already halted.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of
+    /// [`VirtualMachine::execute_instruction`](crate::VirtualMachine::execute_instruction).
+    pub fn step_n(&self, steps: usize) -> Result<usize, VMError> {
+        let mut machine = self.lock();
+        let mut executed = 0;
+        while executed < steps && !machine.is_halted() {
+            machine.execute_instruction()?;
+            executed += 1;
+        }
+
+        Ok(executed)
+    }
+
+    /// Whether the program of the machine has finished
+    #[must_use]
+    pub fn is_halted(&self) -> bool {
+        self.lock().is_halted()
+    }
+
+    /// Lock the machine and call `f` with it
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A function that inspects or modifies the machine
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`
+    pub fn with<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut VirtualMachine<R, W>) -> T,
+    {
+        f(&mut self.lock())
+    }
+
+    /// Take the machine back out of the `SharedVm`
+    ///
+    /// # Errors
+    ///
+    /// Returns the `SharedVm` unchanged if other handles to the machine still
+    /// exist.
+    pub fn try_unwrap(self) -> Result<VirtualMachine<R, W>, Self> {
+        Arc::try_unwrap(self.machine)
+            .map(|machine| machine.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .map_err(|machine| Self { machine })
+    }
+
+    /// Lock the machine.
+    ///
+    /// A thread that panicked while holding the lock cannot leave the machine
+    /// in a state that is unsafe to inspect, so a poisoned lock is recovered.
+    fn lock(&self) -> MutexGuard<'_, VirtualMachine<R, W>> {
+        self.machine.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<R, W> Clone for SharedVm<R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    fn clone(&self) -> Self {
+        Self {
+            machine: Arc::clone(&self.machine),
+        }
+    }
+}
+
+impl<R, W> From<VirtualMachine<R, W>> for SharedVm<R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    fn from(machine: VirtualMachine<R, W>) -> Self {
+        Self::new(machine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Stdin,
+        thread,
+    };
+
+    use super::*;
+    use crate::{
+        CaptureWriter,
+        MockReader,
+    };
+
+    fn assert_send<T: Send>() {}
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    fn shared(source: &str) -> SharedVm<MockReader, CaptureWriter> {
+        SharedVm::new(VirtualMachine::testing(source, b"").unwrap())
+    }
+
+    #[test]
+    fn test_thread_bounds() {
+        assert_send::<VirtualMachine<Stdin, Stdout>>();
+        assert_send::<VirtualMachine<MockReader, CaptureWriter>>();
+        assert_send_sync::<SharedVm<Stdin, Stdout>>();
+        assert_send_sync::<SharedVm<MockReader, CaptureWriter>>();
+    }
+
+    #[test]
+    fn test_step() {
+        let shared = shared("++");
+
+        shared.step().unwrap();
+        assert_eq!(shared.with(|machine| machine.program_counter()), 1);
+        assert!(!shared.is_halted());
+
+        shared.step().unwrap();
+        assert!(shared.is_halted());
+    }
+
+    #[test]
+    fn test_step_n() {
+        let shared = shared("+++++");
+
+        assert_eq!(shared.step_n(3).unwrap(), 3);
+        assert_eq!(shared.step_n(3).unwrap(), 2);
+        assert_eq!(shared.step_n(3).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_step_on_worker_thread() {
+        let shared = shared("+++[>++<-]>.");
+        let worker = {
+            let shared = shared.clone();
+            thread::spawn(move || while shared.step_n(2).unwrap() > 0 {})
+        };
+        worker.join().unwrap();
+
+        let mut machine = shared.try_unwrap().ok().unwrap();
+        assert_eq!(machine.steps(), 24);
+        assert_eq!(machine.output_device().data, [6]);
+    }
+
+    #[test]
+    fn test_try_unwrap_with_other_handles() {
+        let shared = shared("+");
+        let other = shared.clone();
+
+        let Err(shared) = shared.try_unwrap() else {
+            panic!("The machine should still be shared");
+        };
+        drop(other);
+        assert!(shared.try_unwrap().is_ok());
+    }
+}
@@ -0,0 +1,213 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::VecDeque,
+    mem,
+};
+
+use crate::{
+    Byte,
+    VMSnapshot,
+};
+
+/// Named save states of a debugging session
+///
+/// Saving the state of a run under a name, such as `before_loop`, and
+/// restoring it later makes it cheap to try something out and go back. A
+/// large tape makes every snapshot large, so the store keeps at most
+/// `max_bytes` of them: once it holds more, the snapshots used least recently
+/// are dropped until it fits again. The snapshot saved last is always kept,
+/// even if it is larger than the whole budget.
+///
+/// Saving a name again replaces its snapshot. Both saving and getting a
+/// snapshot count as using it.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     CaptureWriter,
+///     Program,
+///     SnapshotStore,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from("+++[-]"))
+///     .build()
+///     .unwrap();
+/// let mut store = SnapshotStore::new(SnapshotStore::DEFAULT_MAX_BYTES);
+///
+/// for _ in 0..3 {
+///     machine.execute_instruction().unwrap();
+/// }
+/// store.save("before_loop", machine.snapshot());
+/// machine.run().unwrap();
+/// assert_eq!(machine.tape()[0], Byte::from(0));
+///
+/// machine.restore(store.get("before_loop").unwrap()).unwrap();
+/// assert_eq!(machine.tape()[0], Byte::from(3));
+/// assert_eq!(machine.program_counter(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    /// The snapshots with their names, the one used most recently first
+    slots:     VecDeque<(String, VMSnapshot)>,
+    max_bytes: usize,
+}
+
+impl SnapshotStore {
+    /// The memory a store keeps snapshots in unless told otherwise, enough for
+    /// a few hundred snapshots of the classic 30,000 cell tape
+    pub const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+    /// Create an empty store keeping at most `max_bytes` of snapshots
+    #[must_use]
+    pub const fn new(max_bytes: usize) -> Self {
+        Self {
+            slots: VecDeque::new(),
+            max_bytes,
+        }
+    }
+
+    /// Save `snapshot` under `name`, replacing any snapshot saved under it
+    ///
+    /// # Returns
+    ///
+    /// The names of the snapshots dropped to make room, the one used least
+    /// recently first.
+    pub fn save(&mut self, name: impl Into<String>, snapshot: VMSnapshot) -> Vec<String> {
+        let name = name.into();
+        self.remove(&name);
+        self.slots.push_front((name, snapshot));
+
+        let mut evicted = Vec::new();
+        while self.memory_usage() > self.max_bytes && self.slots.len() > 1 {
+            if let Some((name, _)) = self.slots.pop_back() {
+                evicted.push(name);
+            }
+        }
+        evicted
+    }
+
+    /// The snapshot saved under `name`, if it is still kept
+    pub fn get(&mut self, name: &str) -> Option<&VMSnapshot> {
+        let index = self.position(name)?;
+        let slot = self.slots.remove(index)?;
+        self.slots.push_front(slot);
+        self.slots.front().map(|(_, snapshot)| snapshot)
+    }
+
+    /// Drop the snapshot saved under `name`
+    ///
+    /// # Returns
+    ///
+    /// The snapshot, if there was one.
+    pub fn remove(&mut self, name: &str) -> Option<VMSnapshot> {
+        let index = self.position(name)?;
+        self.slots.remove(index).map(|(_, snapshot)| snapshot)
+    }
+
+    /// Drop every snapshot
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    /// The names of the snapshots, the one used most recently first
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(name, _)| name)
+    }
+
+    /// The snapshots with their names, the one used most recently first
+    ///
+    /// Unlike [`get`](Self::get), this does not count as using them.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &VMSnapshot)> {
+        self.slots
+            .iter()
+            .map(|(name, snapshot)| (name.as_str(), snapshot))
+    }
+
+    /// The approximate number of bytes the snapshots take up, mostly their
+    /// tapes
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        self.slots
+            .iter()
+            .map(|(name, snapshot)| {
+                name.len()
+                    + mem::size_of::<VMSnapshot>()
+                    + snapshot.tape().len() * mem::size_of::<Byte>()
+            })
+            .sum()
+    }
+
+    /// The number of snapshots
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns `true` if there are no snapshots
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.slots.iter().position(|(slot, _)| slot == name)
+    }
+}
+
+impl Default for SnapshotStore {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Counters,
+        TapeBackend,
+    };
+
+    fn snapshot(cells: usize) -> VMSnapshot {
+        VMSnapshot::new(
+            TapeBackend::new(vec![Byte::default(); cells]),
+            Counters::default(),
+        )
+    }
+
+    #[test]
+    fn test_least_recently_used_are_evicted() {
+        let one = {
+            let mut store = SnapshotStore::new(usize::MAX);
+            store.save("a", snapshot(100));
+            store.memory_usage()
+        };
+
+        let mut store = SnapshotStore::new(one * 2);
+        assert!(store.save("a", snapshot(100)).is_empty());
+        assert!(store.save("b", snapshot(100)).is_empty());
+        assert!(store.get("a").is_some());
+        assert_eq!(store.save("c", snapshot(100)), ["b"]);
+        assert_eq!(store.names().collect::<Vec<_>>(), ["c", "a"]);
+
+        // Saving a name again replaces its snapshot
+        assert!(store.save("a", snapshot(100)).is_empty());
+        assert_eq!(store.names().collect::<Vec<_>>(), ["a", "c"]);
+
+        // The snapshot saved last is kept even if it is too large
+        assert_eq!(store.save("huge", snapshot(10_000)), ["c", "a"]);
+        assert_eq!(store.len(), 1);
+        assert!(store.remove("huge").is_some());
+        assert!(store.is_empty());
+    }
+}
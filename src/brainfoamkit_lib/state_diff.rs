@@ -0,0 +1,304 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::VecDeque,
+    iter::FusedIterator,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Instruction,
+    VMError,
+    VMReader,
+    VMWriter,
+    VirtualMachine,
+};
+
+/// A change to the state of a `VirtualMachine`, yielded by
+/// [`VirtualMachine::diff_stream`](crate::VirtualMachine::diff_stream)
+///
+/// Applying the changes in order to a copy of the tape and the memory pointer
+/// keeps the copy in sync with the machine, so a front-end can mirror the
+/// machine without taking snapshots of the whole tape.
+///
+/// The changes serialize as compact, tagged objects:
+///
+/// ```
+/// use brainfoamkit_lib::StateDiff;
+///
+/// let diff = StateDiff::CellChanged {
+///     index: 3,
+///     old:   0,
+///     new:   1,
+/// };
+///
+/// assert_eq!(
+///     serde_json::to_string(&diff).unwrap(),
+///     r#"{"event":"cell-changed","index":3,"old":0,"new":1}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum StateDiff {
+    /// The value of the cell at `index` changed from `old` to `new`
+    CellChanged {
+        /// The position of the cell on the tape
+        index: usize,
+        /// The value of the cell before the change
+        old:   u8,
+        /// The value of the cell after the change
+        new:   u8,
+    },
+    /// The memory pointer moved from the cell at `from` to the cell at `to`
+    PointerMoved {
+        /// The position of the memory pointer before the move
+        from: usize,
+        /// The position of the memory pointer after the move
+        to:   usize,
+    },
+    /// The program wrote `value` to the output device
+    OutputEmitted {
+        /// The value that was written
+        value: u8,
+    },
+}
+
+/// An iterator that runs a `VirtualMachine` and yields every change to its
+/// state as a [`StateDiff`]
+///
+/// Instructions that change nothing, such as the brackets, yield nothing.
+/// The iterator ends once the program halts, or after yielding an error.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     Program,
+///     StateDiff,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from(">+."))
+///     .build()
+///     .unwrap();
+///
+/// let diffs = machine
+///     .diff_stream()
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(
+///     diffs,
+///     [
+///         StateDiff::PointerMoved { from: 0, to: 1 },
+///         StateDiff::CellChanged {
+///             index: 1,
+///             old:   0,
+///             new:   1,
+///         },
+///         StateDiff::OutputEmitted { value: 1 },
+///     ]
+/// );
+/// ```
+pub struct DiffStream<'a, R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    machine:  &'a mut VirtualMachine<R, W>,
+    pending:  VecDeque<StateDiff>,
+    finished: bool,
+}
+
+impl<'a, R, W> DiffStream<'a, R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    pub(crate) fn new(machine: &'a mut VirtualMachine<R, W>) -> Self {
+        Self {
+            machine,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// The machine being run, to inspect it between changes
+    pub fn machine(&mut self) -> &mut VirtualMachine<R, W> {
+        self.machine
+    }
+
+    /// Execute the next instruction and queue the changes it made.
+    ///
+    /// Every instruction changes at most the current cell and the memory
+    /// pointer, so only those are compared.
+    fn step(&mut self, instruction: Instruction) -> Result<(), VMError> {
+        let pointer = self.machine.memory_pointer();
+        let old = u8::from(&self.machine.tape()[pointer]);
+        self.machine.execute_instruction()?;
+
+        let new = u8::from(&self.machine.tape()[pointer]);
+        if new != old {
+            self.pending.push_back(StateDiff::CellChanged {
+                index: pointer,
+                old,
+                new,
+            });
+        }
+        let moved_to = self.machine.memory_pointer();
+        if moved_to != pointer {
+            self.pending.push_back(StateDiff::PointerMoved {
+                from: pointer,
+                to:   moved_to,
+            });
+        }
+        if instruction == Instruction::OutputValue {
+            self.pending
+                .push_back(StateDiff::OutputEmitted { value: old });
+        }
+        Ok(())
+    }
+}
+
+impl<R, W> Iterator for DiffStream<'_, R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+    type Item = Result<StateDiff, VMError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(diff) = self.pending.pop_front() {
+                return Some(Ok(diff));
+            }
+            if self.finished {
+                return None;
+            }
+            let Some(instruction) = self.machine.get_instruction() else {
+                self.finished = true;
+                return None;
+            };
+            if let Err(error) = self.step(instruction) {
+                self.finished = true;
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+impl<R, W> FusedIterator for DiffStream<'_, R, W>
+where
+    R: VMReader,
+    W: VMWriter,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CaptureWriter,
+        LimitExceeded,
+        Limits,
+        Program,
+        VecReader,
+    };
+
+    fn machine(program: &str, input: &[u8]) -> VirtualMachine<VecReader, CaptureWriter> {
+        VirtualMachine::builder()
+            .input_device(VecReader::new(input.to_vec()))
+            .output_device(CaptureWriter::default())
+            .program(Program::from(program))
+            .limits(Limits::new().max_steps(100))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_diffs_mirror_the_machine() {
+        let mut vm = machine(",>++[-<+>]<.", b"a");
+        let mut tape = vec![0_u8; vm.tape().len()];
+        let mut pointer = 0;
+        let mut output = Vec::new();
+
+        for diff in vm.diff_stream() {
+            match diff.unwrap() {
+                StateDiff::CellChanged { index, old, new } => {
+                    assert_eq!(tape[index], old);
+                    tape[index] = new;
+                }
+                StateDiff::PointerMoved { from, to } => {
+                    assert_eq!(pointer, from);
+                    pointer = to;
+                }
+                StateDiff::OutputEmitted { value } => output.push(value),
+            }
+        }
+
+        let expected: Vec<u8> = vm.tape().iter().map(u8::from).collect();
+        assert_eq!(tape, expected);
+        assert_eq!(pointer, vm.memory_pointer());
+        assert_eq!(output, b"c");
+        assert_eq!(vm.output_device().data, b"c");
+    }
+
+    #[test]
+    fn test_unchanged_state_yields_nothing() {
+        let mut vm = machine("[>]", &[]);
+
+        assert!(vm.diff_stream().next().is_none());
+        assert!(vm.is_halted());
+    }
+
+    #[test]
+    fn test_error_ends_the_stream() {
+        let mut vm = machine("+[]", &[]);
+        let mut diffs = vm.diff_stream();
+
+        assert_eq!(
+            diffs.next().unwrap().unwrap(),
+            StateDiff::CellChanged {
+                index: 0,
+                old:   0,
+                new:   1,
+            }
+        );
+        assert!(matches!(
+            diffs.next(),
+            Some(Err(VMError::LimitExceeded(LimitExceeded::Steps {
+                limit: 100,
+            })))
+        ));
+        assert!(diffs.next().is_none());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let diffs = [
+            StateDiff::PointerMoved { from: 0, to: 1 },
+            StateDiff::OutputEmitted { value: 65 },
+        ];
+        let json = serde_json::to_string(&diffs).unwrap();
+
+        assert_eq!(
+            json,
+            r#"[{"event":"pointer-moved","from":0,"to":1},{"event":"output-emitted","value":65}]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Vec<StateDiff>>(&json).unwrap(),
+            diffs
+        );
+    }
+}
@@ -0,0 +1,510 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Symbolic execution of small `Program`s
+//!
+//! **This module is experimental.** Its API may change in any release.
+//!
+//! [`explore`] runs a [`Program`] without knowing its input. Every byte read
+//! by `,` is a variable, and the value of every cell is either a constant or
+//! a variable plus a constant, which is all `+` and `-` can produce. Whenever
+//! a loop tests a cell that holds a variable and both outcomes are possible,
+//! the run splits in two, one assuming the cell is zero and one assuming it
+//! is not. Every run that ends is a [`Path`], with the constraints on the
+//! input that lead down it, the values it writes and an example input. Loops
+//! like `[-]` simply clear the cell instead of splitting at every iteration.
+//!
+//! The number of paths grows quickly with every loop over an input byte, so
+//! the exploration is bounded by [`Bounds`]. It is meant for short programs,
+//! such as the password checks of reverse engineering challenges.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+};
+
+use crate::{
+    Instruction,
+    ParseError,
+    Program,
+};
+
+/// The value of a cell during symbolic execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    /// A value that does not depend on the input
+    Concrete(u8),
+    /// An input byte plus a constant, wrapping around like a cell
+    Input {
+        /// The position of the byte in the input, counting from 0
+        index:  usize,
+        /// The constant added to the byte
+        offset: u8,
+    },
+}
+
+impl Value {
+    /// The value once `delta` is added, wrapping around
+    #[must_use]
+    pub const fn add(self, delta: u8) -> Self {
+        match self {
+            Self::Concrete(value) => Self::Concrete(value.wrapping_add(delta)),
+            Self::Input { index, offset } => Self::Input {
+                index,
+                offset: offset.wrapping_add(delta),
+            },
+        }
+    }
+
+    /// The value for a concrete input, or `None` if the input is too short
+    #[must_use]
+    pub fn evaluate(&self, input: &[u8]) -> Option<u8> {
+        match *self {
+            Self::Concrete(value) => Some(value),
+            Self::Input { index, offset } => input.get(index).map(|byte| byte.wrapping_add(offset)),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Self::Concrete(value) => write!(f, "{value}"),
+            Self::Input { index, offset: 0 } => write!(f, "in[{index}]"),
+            Self::Input { index, offset } => write!(f, "in[{index}]+{offset}"),
+        }
+    }
+}
+
+/// A condition on an input byte that a path depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraint {
+    /// The position of the byte in the input, counting from 0
+    pub input: usize,
+    /// The value the byte is compared with
+    pub value: u8,
+    /// Whether the byte equals the value, or differs from it
+    pub equal: bool,
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let operator = if self.equal {
+            "=="
+        } else {
+            "!="
+        };
+        write!(f, "in[{}] {operator} {}", self.input, self.value)
+    }
+}
+
+/// How a path ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathEnd {
+    /// The program halted
+    Halted,
+    /// The path ran into the step limit of the [`Bounds`] and was cut short
+    StepLimit,
+    /// The memory pointer moved left of the first cell
+    PointerUnderflow,
+    /// The program reached an instruction that is not executed
+    /// symbolically, such as a procedure of the `pbrain` dialect
+    Unsupported(Instruction),
+}
+
+impl Display for PathEnd {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Halted => write!(f, "halted"),
+            Self::StepLimit => write!(f, "step limit reached"),
+            Self::PointerUnderflow => write!(f, "pointer moved left of cell 0"),
+            Self::Unsupported(instruction) => write!(f, "unsupported {instruction}"),
+        }
+    }
+}
+
+/// One way through a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    /// The conditions on the input that lead down the path, in the order the
+    /// program tests them
+    pub constraints: Vec<Constraint>,
+    /// The values written by `.`, in order
+    pub outputs:     Vec<Value>,
+    /// The number of bytes read by `,`
+    pub inputs:      usize,
+    /// How the path ended
+    pub end:         PathEnd,
+    domains:         Vec<Domain>,
+}
+
+impl Path {
+    /// An input that leads down the path, choosing the smallest possible
+    /// value for every byte
+    #[must_use]
+    pub fn example_input(&self) -> Vec<u8> {
+        self.domains.iter().map(Domain::example).collect()
+    }
+
+    /// The bytes written for the [`example_input`](Self::example_input)
+    #[must_use]
+    pub fn example_output(&self) -> Vec<u8> {
+        let input = self.example_input();
+        self.outputs
+            .iter()
+            .filter_map(|value| value.evaluate(&input))
+            .collect()
+    }
+}
+
+/// The limits of an exploration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    /// Stop once this many paths are found
+    pub max_paths: usize,
+    /// Cut a path short after this many instructions
+    pub max_steps: usize,
+}
+
+impl Default for Bounds {
+    /// At most 100 paths of at most 100,000 steps each
+    fn default() -> Self {
+        Self {
+            max_paths: 100,
+            max_steps: 100_000,
+        }
+    }
+}
+
+/// The paths found by [`explore`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exploration {
+    /// The paths, in the order they were found
+    pub paths:    Vec<Path>,
+    /// `true` if every path was explored, `false` if the exploration stopped
+    /// at the path limit
+    pub complete: bool,
+}
+
+/// Explore the paths through a `Program` for every possible input
+///
+/// Paths are explored depth first, taking the branch where the tested cell
+/// is not zero first. The input never ends: every `,` reads a new byte.
+///
+/// # Arguments
+///
+/// * `program` - The program to explore
+/// * `bounds` - The limits of the exploration
+///
+/// # Errors
+///
+/// Returns an error if the brackets of the program are not balanced.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     symbolic::{
+///         self,
+///         Bounds,
+///     },
+///     Program,
+/// };
+///
+/// // Print 'Y' if the input is 'A' and 'N' otherwise
+/// let program = Program::from(
+///     ",>++++++++[<-------->-]<->+<[>-<[-]]>[<+++++++++++>-]\
+///      >+++++++++++++[<<++++++>>-]<<.",
+/// );
+/// let exploration = symbolic::explore(&program, Bounds::default()).unwrap();
+///
+/// assert!(exploration.complete);
+/// let accepted = exploration
+///     .paths
+///     .iter()
+///     .find(|path| path.example_output() == b"Y")
+///     .unwrap();
+/// assert_eq!(accepted.example_input(), b"A");
+/// ```
+pub fn explore(program: &Program, bounds: Bounds) -> Result<Exploration, ParseError> {
+    let instructions: Vec<Instruction> = (0..program.length().unwrap_or(0))
+        .filter_map(|index| program.get_instruction(index))
+        .collect();
+    let jump_table = program.jump_table()?;
+
+    let mut paths = Vec::new();
+    let mut pending = vec![State::default()];
+    while let Some(mut state) = pending.pop() {
+        if paths.len() >= bounds.max_paths {
+            pending.push(state);
+            break;
+        }
+        let end = state.run(&instructions, &jump_table, bounds.max_steps, &mut pending);
+        paths.push(Path {
+            constraints: state.constraints,
+            outputs: state.outputs,
+            inputs: state.domains.len(),
+            end,
+            domains: state.domains,
+        });
+    }
+
+    Ok(Exploration {
+        paths,
+        complete: pending.is_empty(),
+    })
+}
+
+/// The values an input byte can still take on a path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Domain {
+    fixed:    Option<u8>,
+    excluded: BTreeSet<u8>,
+}
+
+impl Domain {
+    fn can_equal(&self, value: u8) -> bool {
+        self.fixed
+            .map_or(!self.excluded.contains(&value), |fixed| fixed == value)
+    }
+
+    fn can_differ(&self, value: u8) -> bool {
+        match self.fixed {
+            Some(fixed) => fixed != value,
+            None => {
+                let others = self.excluded.iter().filter(|&&excluded| excluded != value);
+                others.count() < usize::from(u8::MAX)
+            }
+        }
+    }
+
+    fn example(&self) -> u8 {
+        self.fixed.unwrap_or_else(|| {
+            (0..=u8::MAX)
+                .find(|value| !self.excluded.contains(value))
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// A run through the program in progress
+#[derive(Debug, Clone, Default)]
+struct State {
+    tape:            Vec<Value>,
+    memory_pointer:  usize,
+    program_counter: usize,
+    steps:           usize,
+    constraints:     Vec<Constraint>,
+    outputs:         Vec<Value>,
+    domains:         Vec<Domain>,
+}
+
+impl State {
+    fn cell(&mut self) -> &mut Value {
+        if self.memory_pointer >= self.tape.len() {
+            self.tape
+                .resize(self.memory_pointer + 1, Value::Concrete(0));
+        }
+        &mut self.tape[self.memory_pointer]
+    }
+
+    /// Run until the path ends, adding the paths that split off to `pending`
+    fn run(
+        &mut self,
+        instructions: &[Instruction],
+        jump_table: &[Option<usize>],
+        max_steps: usize,
+        pending: &mut Vec<Self>,
+    ) -> PathEnd {
+        while let Some(&instruction) = instructions.get(self.program_counter) {
+            if self.steps >= max_steps {
+                return PathEnd::StepLimit;
+            }
+            match instruction {
+                Instruction::IncrementPointer => self.memory_pointer += 1,
+                Instruction::DecrementPointer => match self.memory_pointer.checked_sub(1) {
+                    Some(previous) => self.memory_pointer = previous,
+                    None => return PathEnd::PointerUnderflow,
+                },
+                Instruction::IncrementValue => *self.cell() = self.cell().add(1),
+                Instruction::DecrementValue => *self.cell() = self.cell().add(u8::MAX),
+                Instruction::OutputValue => {
+                    let value = *self.cell();
+                    self.outputs.push(value);
+                }
+                Instruction::InputValue => {
+                    *self.cell() = Value::Input {
+                        index:  self.domains.len(),
+                        offset: 0,
+                    };
+                    self.domains.push(Domain::default());
+                }
+                // A loop like `[-]` sets the cell to zero whatever it holds,
+                // which saves splitting the path at every iteration
+                Instruction::JumpForward
+                    if jump_table.get(self.program_counter)
+                        == Some(&Some(self.program_counter + 2))
+                        && matches!(
+                            instructions.get(self.program_counter + 1),
+                            Some(Instruction::IncrementValue | Instruction::DecrementValue)
+                        ) =>
+                {
+                    *self.cell() = Value::Concrete(0);
+                    self.program_counter += 2;
+                }
+                Instruction::JumpForward | Instruction::JumpBackward => {
+                    let nonzero = self.branch(pending);
+                    let jumps = (instruction == Instruction::JumpForward) != nonzero;
+                    if jumps {
+                        if let Some(Some(target)) = jump_table.get(self.program_counter) {
+                            self.program_counter = *target;
+                        }
+                    }
+                }
+                Instruction::NoOp | Instruction::DebugDump => {}
+                Instruction::Halt => return PathEnd::Halted,
+                Instruction::DefineProcedure
+                | Instruction::EndProcedure
+                | Instruction::CallProcedure
+                | Instruction::Extension(_) => return PathEnd::Unsupported(instruction),
+            }
+            self.program_counter += 1;
+            self.steps += 1;
+        }
+        PathEnd::Halted
+    }
+
+    /// Decide whether the current cell is zero. If it holds an input byte
+    /// that can make it either, a copy of the state that assumes it is zero
+    /// is added to `pending`, and this state assumes it is not.
+    ///
+    /// # Returns
+    ///
+    /// `true` if this state continues with a cell that is not zero.
+    fn branch(&mut self, pending: &mut Vec<Self>) -> bool {
+        let (index, offset) = match *self.cell() {
+            Value::Concrete(value) => return value != 0,
+            Value::Input { index, offset } => (index, offset),
+        };
+        // The cell is zero when the input byte makes up for the offset
+        let zero = 0u8.wrapping_sub(offset);
+        let domain = &self.domains[index];
+        let can_be_zero = domain.can_equal(zero);
+        let can_be_nonzero = domain.can_differ(zero);
+
+        if can_be_zero && can_be_nonzero {
+            let mut other = self.clone();
+            other.domains[index].fixed = Some(zero);
+            other.constraints.push(Constraint {
+                input: index,
+                value: zero,
+                equal: true,
+            });
+            // The other path executes this instruction again, which then
+            // finds the cell zero
+            pending.push(other);
+
+            self.domains[index].excluded.insert(zero);
+            self.constraints.push(Constraint {
+                input: index,
+                value: zero,
+                equal: false,
+            });
+        }
+        can_be_nonzero
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn explore_all(source: &str) -> Exploration {
+        explore(&Program::from(source), Bounds::default()).unwrap()
+    }
+
+    #[test]
+    fn test_concrete_program() {
+        let exploration = explore_all("++++++++[>++++++++<-]>+.");
+
+        assert!(exploration.complete);
+        assert_eq!(exploration.paths.len(), 1);
+        let path = &exploration.paths[0];
+        assert_eq!(path.end, PathEnd::Halted);
+        assert!(path.constraints.is_empty());
+        assert_eq!(path.example_output(), b"A");
+    }
+
+    #[test]
+    fn test_symbolic_output() {
+        let exploration = explore_all(",++.");
+        let path = &exploration.paths[0];
+
+        assert_eq!(path.inputs, 1);
+        assert_eq!(
+            path.outputs,
+            [Value::Input {
+                index:  0,
+                offset: 2,
+            }]
+        );
+        assert_eq!(path.outputs[0].to_string(), "in[0]+2");
+        assert_eq!(path.example_output(), [2]);
+    }
+
+    #[test]
+    fn test_branches() {
+        // Print 1 if the input is 3 and 0 otherwise
+        let exploration = explore_all(",--->+<[>-<[-]]>.");
+
+        assert!(exploration.complete);
+        assert_eq!(exploration.paths.len(), 2);
+        let nonzero = &exploration.paths[0];
+        assert_eq!(nonzero.constraints[0].to_string(), "in[0] != 3");
+        assert_eq!(nonzero.example_input(), [0]);
+        assert_eq!(nonzero.example_output(), [0]);
+
+        let zero = &exploration.paths[1];
+        assert_eq!(zero.constraints[0].to_string(), "in[0] == 3");
+        assert_eq!(zero.example_input(), [3]);
+        assert_eq!(zero.example_output(), [1]);
+    }
+
+    #[test]
+    fn test_bounds() {
+        // Moving an input byte splits at every iteration
+        let program = Program::from(",[->+<]");
+        let exploration = explore(
+            &program,
+            Bounds {
+                max_paths: 10,
+                max_steps: 1000,
+            },
+        )
+        .unwrap();
+        assert!(!exploration.complete);
+        assert_eq!(exploration.paths.len(), 10);
+
+        let exploration = explore(
+            &Program::from("+[]"),
+            Bounds {
+                max_paths: 10,
+                max_steps: 50,
+            },
+        )
+        .unwrap();
+        assert_eq!(exploration.paths[0].end, PathEnd::StepLimit);
+    }
+
+    #[test]
+    fn test_path_ends() {
+        assert_eq!(explore_all("<").paths[0].end, PathEnd::PointerUnderflow);
+        assert!(explore(&Program::from("[+"), Bounds::default()).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeSet;
+
+/// A byte written by the program, with the input bytes that influenced it
+///
+/// Recorded by a [`VirtualMachine`](crate::VirtualMachine) with taint
+/// tracking enabled, see [`TaintTracker`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintedOutput {
+    /// The position of the byte in the output, counting from 0
+    pub position: usize,
+    /// The value written
+    pub value:    u8,
+    /// The positions of the input bytes that influenced the value, counting
+    /// from 0, in ascending order
+    pub sources:  Vec<usize>,
+}
+
+/// Follows which input bytes influence which cells and output bytes
+///
+/// Every cell carries a set of tags, one for every input byte that
+/// influenced its value. A `,` that reads a byte replaces the tags of the
+/// cell with the position of that byte in the input. A `,` at the end of the
+/// input that sets the cell to 0 or 255, as with
+/// [`EofBehavior::Zero`](crate::EofBehavior::Zero) or
+/// [`EofBehavior::Max`](crate::EofBehavior::Max), replaces them with the tags
+/// of the cells tested by the loops it runs in. A `+` or `-` keeps the tags
+/// of the cell and adds those of the cells tested by the loops it runs in,
+/// since the number of iterations decides how often it runs: that is how
+/// `[->+<]` carries the tags of one cell to another. A `.` reports the tags
+/// of the cell as the sources of the byte it writes.
+///
+/// The tags are conservative in one direction only: they follow the flows
+/// through loops that run, but not the information carried by a loop that
+/// is skipped. Procedure calls of the `pbrain` dialect are not followed, and
+/// neither are the instructions run by an
+/// [`InstructionHandler`](crate::InstructionHandler), such as the random bytes
+/// of `?`, the pops of `$` or host calls: the cells they write keep their old
+/// tags, and the bytes they read or write are not counted.
+///
+/// Taint tracking is enabled with
+/// [`VirtualMachineBuilder::taint_tracking`](crate::VirtualMachineBuilder::taint_tracking).
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     Program,
+///     VecReader,
+///     VirtualMachine,
+/// };
+///
+/// // Add the first two input bytes, then print the third one
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(VecReader::new(vec![1, 2, 3]))
+///     .output_device(CaptureWriter::default())
+///     .program(Program::from(",>,[-<+>]<.,."))
+///     .taint_tracking(true)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// let taint = machine.taint().unwrap();
+/// assert_eq!(taint.outputs()[0].value, 3);
+/// assert_eq!(taint.outputs()[0].sources, [0, 1]);
+/// assert_eq!(taint.outputs()[1].sources, [2]);
+/// assert_eq!(taint.inputs_read(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaintTracker {
+    cells:       Vec<BTreeSet<usize>>,
+    /// The tags of the cells tested by the loops that are running, innermost
+    /// last
+    loops:       Vec<BTreeSet<usize>>,
+    inputs_read: usize,
+    outputs:     Vec<TaintedOutput>,
+}
+
+impl TaintTracker {
+    /// The bytes written so far, with their sources
+    #[must_use]
+    pub fn outputs(&self) -> &[TaintedOutput] {
+        &self.outputs
+    }
+
+    /// The positions of the input bytes that influenced the value of the cell
+    /// at `index`, in ascending order
+    pub fn cell(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.cells.get(index).into_iter().flatten().copied()
+    }
+
+    /// The number of bytes read from the input so far
+    #[must_use]
+    pub const fn inputs_read(&self) -> usize {
+        self.inputs_read
+    }
+
+    fn cell_mut(&mut self, index: usize) -> &mut BTreeSet<usize> {
+        if index >= self.cells.len() {
+            self.cells.resize_with(index + 1, BTreeSet::new);
+        }
+        &mut self.cells[index]
+    }
+
+    fn tags(&self, index: usize) -> BTreeSet<usize> {
+        self.cells.get(index).cloned().unwrap_or_default()
+    }
+
+    /// A `,` read a byte into the cell at `index`
+    pub(crate) fn read(&mut self, index: usize) {
+        let input = self.inputs_read;
+        self.inputs_read += 1;
+        *self.cell_mut(index) = BTreeSet::from([input]);
+    }
+
+    /// The tags of the cells tested by every loop that is running
+    fn control(&self) -> BTreeSet<usize> {
+        self.loops.iter().flatten().copied().collect()
+    }
+
+    /// The cell at `index` changed without reading a byte
+    pub(crate) fn write(&mut self, index: usize) {
+        let control = self.control();
+        self.cell_mut(index).extend(control);
+    }
+
+    /// The cell at `index` was set to a constant without reading a byte
+    pub(crate) fn overwrite(&mut self, index: usize) {
+        let control = self.control();
+        *self.cell_mut(index) = control;
+    }
+
+    /// A `.` wrote the value of the cell at `index`
+    pub(crate) fn output(&mut self, index: usize, value: u8) {
+        self.outputs.push(TaintedOutput {
+            position: self.outputs.len(),
+            value,
+            sources: self.cell(index).collect(),
+        });
+    }
+
+    /// A `[` entered a loop testing the cell at `index`
+    pub(crate) fn enter_loop(&mut self, index: usize) {
+        let tags = self.tags(index);
+        self.loops.push(tags);
+    }
+
+    /// A `]` repeated the innermost loop after testing the cell at `index`
+    pub(crate) fn repeat_loop(&mut self, index: usize) {
+        let tags = self.tags(index);
+        if let Some(control) = self.loops.last_mut() {
+            control.extend(tags);
+        }
+    }
+
+    /// A `]` left the innermost loop
+    pub(crate) fn exit_loop(&mut self) {
+        self.loops.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        CaptureWriter,
+        EofBehavior,
+        Program,
+        VecReader,
+        VirtualMachine,
+    };
+
+    fn sources(source: &str, input: &[u8]) -> Vec<Vec<usize>> {
+        let mut machine = VirtualMachine::builder()
+            .input_device(VecReader::new(input.to_vec()))
+            .output_device(CaptureWriter::default())
+            .program(Program::from(source))
+            .taint_tracking(true)
+            .build()
+            .unwrap();
+        machine.run().unwrap();
+        machine
+            .taint()
+            .unwrap()
+            .outputs()
+            .iter()
+            .map(|output| output.sources.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_constant_output_is_untainted() {
+        assert_eq!(sources("++.,.", b"a"), [vec![], vec![0]]);
+    }
+
+    #[test]
+    fn test_read_replaces_tags() {
+        assert_eq!(sources(",,.", b"ab"), [vec![1]]);
+    }
+
+    #[test]
+    fn test_copy_through_loops() {
+        // Move the first byte two cells to the right through a temporary
+        assert_eq!(sources(",[->+<]>[->+<]>.", b"\x05"), [vec![0]]);
+    }
+
+    #[test]
+    fn test_nested_loop_control() {
+        // The product of the two input bytes
+        assert_eq!(
+            sources(",>,<[->[->+>+<<]>>[-<<+>>]<<<]>>.", b"\x02\x03"),
+            [vec![0, 1]]
+        );
+    }
+
+    #[test]
+    fn test_end_of_input_is_not_a_read() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(VecReader::new(vec![]))
+            .program(Program::from(",,"))
+            .taint_tracking(true)
+            .build()
+            .unwrap();
+        machine.run().unwrap();
+
+        assert_eq!(machine.taint().unwrap().inputs_read(), 0);
+        assert_eq!(machine.taint().unwrap().cell(0).count(), 0);
+    }
+
+    #[test]
+    fn test_end_of_input_overwrites_tags() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(VecReader::new(vec![b'a']))
+            .output_device(CaptureWriter::default())
+            .program(Program::from(",.,."))
+            .eof_behavior(EofBehavior::Zero)
+            .taint_tracking(true)
+            .build()
+            .unwrap();
+        machine.run().unwrap();
+
+        assert_eq!(machine.output_device().data, b"a\0");
+        let taint = machine.taint().unwrap();
+        assert_eq!(taint.outputs()[0].sources, [0]);
+        assert!(taint.outputs()[1].sources.is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use alloc::vec::Vec;
+
+use crate::{
+    Bit,
+    Byte,
+};
+
+/// A cell holding zero, usable in constant expressions
+const ZERO: Byte = Byte::new(
+    Bit::zero(),
+    Bit::zero(),
+    Bit::zero(),
+    Bit::zero(),
+    Bit::zero(),
+    Bit::zero(),
+    Bit::zero(),
+    Bit::zero(),
+);
+
+/// The storage of the cells of a [`CoreMachine`](crate::CoreMachine)
+///
+/// A tape only hands out its cells as a slice, so that the machine does not
+/// care where they live. A `Vec<Byte>` keeps them on the heap, a
+/// [`FixedTape`] in an array that needs no allocator.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     FixedTape,
+///     Tape,
+/// };
+///
+/// let mut tape = FixedTape::<4>::new();
+/// tape.cells_mut()[1] = Byte::from(7);
+///
+/// assert_eq!(tape.cells()[1], Byte::from(7));
+/// assert_eq!(tape.cells().len(), 4);
+/// ```
+pub trait Tape {
+    /// The cells of the tape, in order
+    fn cells(&self) -> &[Byte];
+
+    /// The cells of the tape, in order, to change their values
+    fn cells_mut(&mut self) -> &mut [Byte];
+}
+
+impl Tape for Vec<Byte> {
+    fn cells(&self) -> &[Byte] {
+        self
+    }
+
+    fn cells_mut(&mut self) -> &mut [Byte] {
+        self
+    }
+}
+
+/// A tape of `N` cells stored in an array
+///
+/// The cells live wherever the `FixedTape` does, on the stack or in a
+/// `static`, so running a program on it needs no heap for the tape. Its size
+/// is fixed when the program is compiled, which suits microcontrollers with a
+/// few kilobytes of memory.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CoreMachine,
+///     FixedTape,
+///     Program,
+/// };
+///
+/// let mut output = Vec::new();
+/// let mut machine = CoreMachine::with_tape(
+///     Program::from("++>+++[<+>-]<."),
+///     FixedTape::<8>::new(),
+///     || None,
+///     |byte| output.push(byte),
+/// );
+/// machine.run();
+/// drop(machine);
+///
+/// assert_eq!(output, [5]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedTape<const N: usize> {
+    cells: [Byte; N],
+}
+
+impl<const N: usize> FixedTape<N> {
+    /// Create a tape of `N` cells that all hold zero
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is zero. In a constant expression, this fails to
+    /// compile instead.
+    #[must_use]
+    pub const fn new() -> Self {
+        assert!(N > 0, "the tape needs at least one cell");
+        Self { cells: [ZERO; N] }
+    }
+}
+
+impl<const N: usize> Default for FixedTape<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Tape for FixedTape<N> {
+    fn cells(&self) -> &[Byte] {
+        &self.cells
+    }
+
+    fn cells_mut(&mut self) -> &mut [Byte] {
+        &mut self.cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_tape_starts_at_zero() {
+        const TAPE: FixedTape<3> = FixedTape::new();
+
+        assert_eq!(TAPE.cells(), [Byte::default(); 3]);
+        assert_eq!(ZERO, Byte::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "the tape needs at least one cell")]
+    fn test_empty_fixed_tape() {
+        let _ = FixedTape::<0>::new();
+    }
+
+    #[test]
+    fn test_vec_tape() {
+        let mut tape = vec![Byte::default(); 2];
+        tape.cells_mut()[0] = Byte::from(1);
+
+        assert_eq!(Tape::cells(&tape), [Byte::from(1), Byte::default()]);
+    }
+}
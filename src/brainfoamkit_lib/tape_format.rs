@@ -0,0 +1,157 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    str::FromStr,
+};
+
+use crate::ParseError;
+
+/// The number of cells on every line of a hex dump
+const HEX_CELLS_PER_LINE: usize = 16;
+
+/// The file format of a tape loaded with
+/// [`VirtualMachine::load_tape_from`](crate::VirtualMachine::load_tape_from)
+/// or saved with
+/// [`VirtualMachine::dump_tape_to`](crate::VirtualMachine::dump_tape_to)
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::TapeFormat;
+///
+/// let cells = [0, 72, 255];
+///
+/// assert_eq!(TapeFormat::Raw.encode(&cells), cells);
+/// assert_eq!(TapeFormat::Hex.encode(&cells), b"00 48 ff\n");
+/// assert_eq!(TapeFormat::Hex.decode(b"00 48\nFF").unwrap(), cells);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TapeFormat {
+    /// One byte per cell
+    #[default]
+    Raw,
+    /// Two hexadecimal digits per cell, separated by whitespace
+    Hex,
+}
+
+impl TapeFormat {
+    /// Encode the values of the cells of a tape in this format
+    ///
+    /// Hex dumps hold 16 cells on every line.
+    #[must_use]
+    pub fn encode(self, cells: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Raw => cells.to_vec(),
+            Self::Hex => cells
+                .chunks(HEX_CELLS_PER_LINE)
+                .map(|line| {
+                    let line: Vec<String> =
+                        line.iter().map(|value| format!("{value:02x}")).collect();
+                    line.join(" ") + "\n"
+                })
+                .collect::<String>()
+                .into_bytes(),
+        }
+    }
+
+    /// Decode the values of the cells of a tape from this format
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a hex dump holds something other than pairs of
+    /// hexadecimal digits separated by whitespace.
+    pub fn decode(self, data: &[u8]) -> Result<Vec<u8>, ParseError> {
+        match self {
+            Self::Raw => Ok(data.to_vec()),
+            Self::Hex => data
+                .split(u8::is_ascii_whitespace)
+                .filter(|word| !word.is_empty())
+                .map(|word| {
+                    std::str::from_utf8(word)
+                        .ok()
+                        .filter(|word| {
+                            word.len() == 2 && word.bytes().all(|digit| digit.is_ascii_hexdigit())
+                        })
+                        .and_then(|word| u8::from_str_radix(word, 16).ok())
+                        .ok_or_else(|| ParseError::InvalidHex(word.escape_ascii().to_string()))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Display for TapeFormat {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Raw => write!(f, "raw"),
+            Self::Hex => write!(f, "hex"),
+        }
+    }
+}
+
+impl FromStr for TapeFormat {
+    type Err = ParseError;
+
+    /// Parse the name of a tape format
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::TapeFormat;
+    ///
+    /// assert_eq!("hex".parse::<TapeFormat>().unwrap(), TapeFormat::Hex);
+    /// assert!("base64".parse::<TapeFormat>().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name does not match any format.
+    fn from_str(name: &str) -> Result<Self, ParseError> {
+        match name {
+            "raw" => Ok(Self::Raw),
+            "hex" => Ok(Self::Hex),
+            _ => Err(ParseError::UnknownName {
+                kind: "tape format",
+                name: name.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let cells: Vec<u8> = (0..=40).collect();
+        let dump = TapeFormat::Hex.encode(&cells);
+
+        assert_eq!(String::from_utf8_lossy(&dump).lines().count(), 3);
+        assert_eq!(TapeFormat::Hex.decode(&dump).unwrap(), cells);
+        assert_eq!(TapeFormat::Hex.encode(&[]), b"");
+    }
+
+    #[test]
+    fn test_invalid_hex() {
+        assert!(TapeFormat::Hex.decode(b"0").is_err());
+        assert!(TapeFormat::Hex.decode(b"000").is_err());
+        assert!(TapeFormat::Hex.decode(b"zz").is_err());
+        assert!(TapeFormat::Hex.decode(b"+1").is_err());
+    }
+
+    #[test]
+    fn test_names() {
+        for format in [TapeFormat::Raw, TapeFormat::Hex] {
+            assert_eq!(format.to_string().parse::<TapeFormat>().unwrap(), format);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    borrow::Cow,
+    mem,
+    ops::{
+        Index,
+        Range,
+    },
+};
+
+#[cfg(feature = "mmap")]
+use crate::MmapTape;
+use crate::{
+    Byte,
+    FileError,
+    PagedTape,
+};
+
+/// The cells of a `VirtualMachine`, wherever they are stored
+///
+/// The cells of a machine are kept in memory, or with the `mmap` feature in
+/// a memory-mapped file, see [`MmapTape`](crate::MmapTape). A tape that grows
+/// when the pointer moves off its end, with
+/// [`TapeEdge::Grow`](crate::TapeEdge::Grow), is kept in a
+/// [`PagedTape`](crate::PagedTape), so that the stretches of zeros a program
+/// leaves behind share one page. A `TapeStorage` reads and writes the cells
+/// the same way wherever they are, so a machine whose tape is much larger than
+/// the memory of the computer runs like any other.
+///
+/// Reading a cell, with an index or [`get`](Self::get), is cheap wherever
+/// the cells are. Only a fixed tape in memory holds its cells as one slice of
+/// `Byte`s, so [`slice`](Self::slice) and [`edit`](Self::edit) copy the cells
+/// of any other tape.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     Program,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from(">++"))
+///     .tape_size(4)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// let tape = machine.tape();
+/// assert_eq!(tape[1], Byte::from(2));
+/// assert_eq!(tape.get(4), None);
+/// assert_eq!(tape.iter().map(u8::from).collect::<Vec<_>>(), [0, 2, 0, 0]);
+/// assert_eq!(tape.slice(1..3)[..], [Byte::from(2), Byte::from(0)]);
+/// ```
+#[derive(Debug)]
+pub struct TapeStorage {
+    cells: Cells,
+}
+
+#[derive(Debug)]
+enum Cells {
+    Memory(Vec<Byte>),
+    Paged {
+        tape:      PagedTape,
+        /// The memory usage of the tape after it was last compacted
+        compacted: usize,
+    },
+    #[cfg(feature = "mmap")]
+    Mapped(MmapTape),
+}
+
+impl TapeStorage {
+    /// A tape holding `cells` in memory
+    pub(crate) const fn memory(cells: Vec<Byte>) -> Self {
+        Self {
+            cells: Cells::Memory(cells),
+        }
+    }
+
+    /// A tape holding its cells in pages, which can grow without copying
+    /// the cells it already has
+    pub(crate) fn paged(tape: PagedTape) -> Self {
+        let compacted = tape.memory_usage();
+        Self {
+            cells: Cells::Paged { tape, compacted },
+        }
+    }
+
+    /// A tape holding its cells in the file of `tape`
+    #[cfg(feature = "mmap")]
+    pub(crate) const fn mapped(tape: MmapTape) -> Self {
+        Self {
+            cells: Cells::Mapped(tape),
+        }
+    }
+
+    /// The number of cells on the tape
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &self.cells {
+            Cells::Memory(cells) => cells.len(),
+            Cells::Paged { tape, .. } => tape.len(),
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => tape.len(),
+        }
+    }
+
+    /// Whether the tape has no cells at all
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The cell at `index`, or `None` if it is beyond the end of the tape
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Byte> {
+        match &self.cells {
+            Cells::Memory(cells) => cells.get(index),
+            Cells::Paged { tape, .. } => tape.cell(index),
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => tape.bytes().get(index).map(|value| byte(*value)),
+        }
+    }
+
+    /// The cells of the tape, in order
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Byte> + ExactSizeIterator + '_ {
+        (0..self.len()).map(|index| &self[index])
+    }
+
+    /// The cells in `range`
+    ///
+    /// The cells of a tape in memory are borrowed, those of any other tape are
+    /// copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` goes beyond the end of the tape, like indexing a
+    /// slice does.
+    #[must_use]
+    pub fn slice(&self, range: Range<usize>) -> Cow<'_, [Byte]> {
+        match &self.cells {
+            Cells::Memory(cells) => Cow::Borrowed(&cells[range]),
+            Cells::Paged { .. } => range.map(|index| self[index]).collect(),
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => tape.bytes()[range]
+                .iter()
+                .copied()
+                .map(Byte::from)
+                .collect(),
+        }
+    }
+
+    /// The values of every cell
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<Byte> {
+        self.slice(0..self.len()).into_owned()
+    }
+
+    /// Replace the value of the cell at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is beyond the end of the tape.
+    pub fn set(&mut self, index: usize, value: Byte) {
+        self.update(index, |cell| *cell = value);
+    }
+
+    /// Change the cells in `range` through a slice
+    ///
+    /// The cells of a tape in memory are changed in place. Those of any other
+    /// tape are copied into a slice, and the slice is written back once `edit`
+    /// returns.
+    ///
+    /// # Returns
+    ///
+    /// What `edit` returns, or `None` if `range` goes beyond the end of the
+    /// tape. `edit` is not called then.
+    pub fn edit<T>(
+        &mut self,
+        range: Range<usize>,
+        edit: impl FnOnce(&mut [Byte]) -> T,
+    ) -> Option<T> {
+        match &mut self.cells {
+            Cells::Memory(cells) => cells.get_mut(range).map(edit),
+            Cells::Paged { tape, .. } => {
+                if range.start > range.end || range.end > tape.len() {
+                    return None;
+                }
+                let mut cells: Vec<Byte> =
+                    range.clone().filter_map(|index| tape.get(index)).collect();
+                let result = edit(&mut cells);
+                for (index, cell) in range.zip(cells) {
+                    tape.set(index, cell);
+                }
+                Some(result)
+            }
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => {
+                let bytes = tape.bytes_mut().get_mut(range)?;
+                let mut cells: Vec<Byte> = bytes.iter().copied().map(Byte::from).collect();
+                let result = edit(&mut cells);
+                for (value, cell) in bytes.iter_mut().zip(&cells) {
+                    *value = u8::from(cell);
+                }
+                Some(result)
+            }
+        }
+    }
+
+    /// The approximate number of bytes of memory the cells take up
+    ///
+    /// The cells of a paged tape are counted as
+    /// [`PagedTape::memory_usage`](crate::PagedTape::memory_usage) does, so
+    /// its pages of zeros cost nothing. The cells of a memory-mapped tape are
+    /// in its file, and the operating system only keeps the parts of it in
+    /// use in memory, so they are not counted.
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        match &self.cells {
+            Cells::Memory(cells) => cells.capacity() * mem::size_of::<Byte>(),
+            Cells::Paged { tape, .. } => tape.memory_usage(),
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(_) => 0,
+        }
+    }
+
+    /// Change the cell at `index` in place
+    pub(crate) fn update(&mut self, index: usize, update: impl FnOnce(&mut Byte)) {
+        match &mut self.cells {
+            Cells::Memory(cells) => update(&mut cells[index]),
+            Cells::Paged { tape, .. } => {
+                let mut cell = tape
+                    .get(index)
+                    .unwrap_or_else(|| panic!("cell {index} is beyond the end of the tape"));
+                update(&mut cell);
+                tape.set(index, cell);
+            }
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => {
+                let value = &mut tape.bytes_mut()[index];
+                let mut cell = Byte::from(*value);
+                update(&mut cell);
+                *value = u8::from(&cell);
+            }
+        }
+    }
+
+    /// Add a cell holding zero to the end of the tape
+    pub(crate) fn push(&mut self) -> Result<(), FileError> {
+        match &mut self.cells {
+            Cells::Memory(cells) => cells.push(Byte::default()),
+            Cells::Paged { tape, .. } => tape.resize(tape.len() + 1),
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => tape.resize(tape.len() + 1)?,
+        }
+        Ok(())
+    }
+
+    /// Replace every cell with `cells`, which may change the length of the
+    /// tape
+    pub(crate) fn replace(&mut self, cells: &[Byte]) -> Result<(), FileError> {
+        match &mut self.cells {
+            Cells::Memory(current) => *current = cells.to_vec(),
+            Cells::Paged { tape, .. } => {
+                tape.resize(cells.len());
+                tape.copy_from(cells);
+            }
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => {
+                tape.resize(cells.len())?;
+                tape.copy_from(cells);
+            }
+        }
+        Ok(())
+    }
+
+    /// Share the pages of zeros and the pages with the same cells of a paged
+    /// tape, see [`PagedTape::compact`](crate::PagedTape::compact)
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes freed, which is always zero for any other tape.
+    pub(crate) fn compact(&mut self) -> usize {
+        let Cells::Paged { tape, compacted } = &mut self.cells else {
+            return 0;
+        };
+        let freed = tape.compact();
+        *compacted = tape.memory_usage();
+        freed
+    }
+
+    /// Compact a paged tape once the pages copied by writes since it was last
+    /// compacted have doubled its memory usage
+    ///
+    /// Checking costs a pass over the table of pages, so the machine only
+    /// checks when its tape grows onto a new page.
+    pub(crate) fn compact_when_grown(&mut self) {
+        if let Cells::Paged { tape, compacted } = &self.cells {
+            if tape.len() % PagedTape::PAGE_SIZE == 1 && tape.memory_usage() > 2 * *compacted {
+                self.compact();
+            }
+        }
+    }
+
+    /// Wait until the changes to a memory-mapped tape are on disk
+    #[cfg(feature = "mmap")]
+    pub(crate) fn flush(&self) -> Result<(), FileError> {
+        match &self.cells {
+            Cells::Memory(_) | Cells::Paged { .. } => Ok(()),
+            Cells::Mapped(tape) => tape.flush(),
+        }
+    }
+
+    /// Take the cells out of the tape
+    pub(crate) fn into_vec(self) -> Vec<Byte> {
+        match self.cells {
+            Cells::Memory(cells) => cells,
+            Cells::Paged { tape, .. } => tape.cells(),
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => tape.cells(),
+        }
+    }
+}
+
+impl Index<usize> for TapeStorage {
+    type Output = Byte;
+
+    fn index(&self, index: usize) -> &Byte {
+        match &self.cells {
+            Cells::Memory(cells) => &cells[index],
+            Cells::Paged { tape, .. } => tape
+                .cell(index)
+                .unwrap_or_else(|| panic!("cell {index} is beyond the end of the tape")),
+            #[cfg(feature = "mmap")]
+            Cells::Mapped(tape) => byte(tape.bytes()[index]),
+        }
+    }
+}
+
+/// The `Byte` holding `value`, for the cells that are not stored as `Byte`s
+#[cfg(feature = "mmap")]
+fn byte(value: u8) -> &'static Byte {
+    static BYTES: std::sync::OnceLock<Vec<Byte>> = std::sync::OnceLock::new();
+    &BYTES.get_or_init(|| (0..=u8::MAX).map(Byte::from).collect())[usize::from(value)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit() {
+        let mut tape = TapeStorage::memory(vec![Byte::default(); 3]);
+
+        assert_eq!(tape.edit(1..3, |cells| cells.swap(0, 1)), Some(()));
+        assert!(tape.edit(2..4, |_| ()).is_none());
+        tape.set(1, Byte::from(5));
+        tape.update(1, Byte::increment);
+        tape.push().unwrap();
+
+        assert_eq!(tape.to_vec(), [0, 6, 0, 0].map(Byte::from));
+        assert_eq!(tape.iter().next_back(), Some(&Byte::default()));
+    }
+
+    #[test]
+    fn test_paged() {
+        let mut tape = TapeStorage::paged(PagedTape::new(2));
+        let empty = tape.memory_usage();
+
+        tape.set(1, Byte::from(3));
+        tape.update(1, Byte::increment);
+        assert_eq!(tape.edit(0..2, |cells| cells.reverse()), Some(()));
+        assert!(tape.edit(1..3, |_| ()).is_none());
+        assert_eq!(tape.to_vec(), [4, 0].map(Byte::from));
+
+        // Growing onto a new page does not copy the cells already there
+        tape.set(0, Byte::default());
+        for _ in 0..PagedTape::PAGE_SIZE {
+            tape.push().unwrap();
+        }
+        assert_eq!(tape.len(), PagedTape::PAGE_SIZE + 2);
+        assert!(tape.memory_usage() > empty);
+        assert!(tape.compact() > 0);
+        assert_eq!(tape[PagedTape::PAGE_SIZE + 1], Byte::default());
+        assert!(tape.memory_usage() < PagedTape::PAGE_SIZE);
+    }
+}
@@ -0,0 +1,642 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Tests for brainfuck programs
+//!
+//! A test is a program file, ending in `.bf` or `.b`, next to a file with the
+//! same name ending in `.expected` that holds the exact output of the program.
+//! An optional file ending in `.input` holds the input of the program:
+//!
+//! ```text
+//! tests/
+//! ├── cat.bf
+//! ├── cat.expected
+//! ├── cat.input
+//! ├── hello.bf
+//! └── hello.expected
+//! ```
+//!
+//! Program files without an `.expected` file are not tests, so a directory of
+//! tests can hold helper programs too. [`run_dir`] runs every test in a
+//! directory and its subdirectories and collects the results in a [`Report`],
+//! which prints as a readable summary or as [TAP](https://testanything.org/).
+//!
+//! [`run_dir_with_chaos`] runs the tests on input and output devices that
+//! fail now and then, to check that every failure of a device stops the
+//! program with an error instead of silently changing its output.
+
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::{
+    config::Config,
+    project::ProjectData,
+    CaptureWriter,
+    Dialect,
+    FaultInjectingReader,
+    FaultInjectingWriter,
+    FileError,
+    InjectedFault,
+    Limits,
+    Program,
+    VMError,
+    VecReader,
+    VirtualMachine,
+    DEFAULT_FAULT_RATE,
+};
+
+/// The number of instructions a test may execute before it fails
+pub const MAX_STEPS: usize = 10_000_000;
+
+/// The extensions of program files
+const PROGRAM_EXTENSIONS: [&str; 2] = ["bf", "b"];
+
+/// A program together with its expected output and input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    /// The name of the test: the path of the program relative to the test
+    /// directory, without its extension
+    pub name:     String,
+    /// The program file
+    pub program:  PathBuf,
+    /// The file holding the expected output
+    pub expected: PathBuf,
+    /// The file holding the input, if there is one
+    pub input:    Option<PathBuf>,
+    /// The dialect the program is written in
+    pub dialect:  Dialect,
+    /// The configuration to run the program with
+    pub config:   Config,
+    /// The files to load into the tape before the program runs
+    pub data:     Vec<ProjectData>,
+}
+
+impl TestCase {
+    /// Run the test
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The resource limits of the run
+    ///
+    /// # Returns
+    ///
+    /// The outcome of the test. A test whose files cannot be read, whose
+    /// program does not parse or that exceeds a limit has an
+    /// [`Outcome::Error`].
+    #[must_use]
+    pub fn run(&self, limits: Limits) -> Outcome {
+        outcome(self.try_run(limits, None))
+    }
+
+    /// Run the test with another program in place of its program file, for
+    /// example a mutant of it
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The program to run on the input of the test
+    /// * `limits` - The resource limits of the run
+    ///
+    /// # Returns
+    ///
+    /// The outcome of the test, as with [`run`](Self::run).
+    #[must_use]
+    pub fn run_program(&self, program: Program, limits: Limits) -> Outcome {
+        outcome(self.try_run_program(program, limits, None))
+    }
+
+    /// Run the test on input and output devices that fail now and then
+    ///
+    /// The devices are a [`FaultInjectingReader`] and a
+    /// [`FaultInjectingWriter`] with the default rate, seeded with `seed`, so
+    /// the same seed injects the same faults on every run.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The resource limits of the run
+    /// * `seed` - The seed of the injected faults
+    ///
+    /// # Returns
+    ///
+    /// The outcome of the test. A program that is stopped by an injected
+    /// fault has an [`Outcome::Faulted`], which counts as a pass.
+    #[must_use]
+    pub fn run_with_chaos(&self, limits: Limits, seed: u64) -> Outcome {
+        match self.try_run(limits, Some(seed)) {
+            Ok((expected, actual)) if expected == actual => Outcome::Passed,
+            Ok((expected, actual)) => Outcome::Failed { expected, actual },
+            Err(error) if InjectedFault::caused(&error) => Outcome::Faulted(describe_error(&error)),
+            Err(error) => Outcome::Error(describe_error(&error)),
+        }
+    }
+
+    fn try_run(&self, limits: Limits, chaos: Option<u64>) -> Result<(Vec<u8>, Vec<u8>), VMError> {
+        let source = read_to_string(&self.program)?;
+        self.try_run_program(Program::parse(&source, self.dialect), limits, chaos)
+    }
+
+    fn try_run_program(
+        &self,
+        program: Program,
+        limits: Limits,
+        chaos: Option<u64>,
+    ) -> Result<(Vec<u8>, Vec<u8>), VMError> {
+        let expected = read(&self.expected)?;
+        let input = self.input.as_deref().map(read).transpose()?;
+        let data = self
+            .data
+            .iter()
+            .map(ProjectData::read)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let seed = chaos.unwrap_or_default();
+        let rate = if chaos.is_some() {
+            DEFAULT_FAULT_RATE
+        } else {
+            0
+        };
+        let builder = VirtualMachine::builder()
+            .input_device(
+                FaultInjectingReader::new(VecReader::new(input.unwrap_or_default()), seed)
+                    .with_rate(rate),
+            )
+            .output_device(
+                FaultInjectingWriter::new(CaptureWriter::default(), !seed).with_rate(rate),
+            )
+            .program(program)
+            .limits(limits);
+        let mut machine = self.config.apply(builder)?.build()?;
+        machine.load_data(&data)?;
+        machine.run()?;
+
+        let (_, _, devices, _) = machine.into_parts();
+        Ok((expected, devices.output.into_inner().data))
+    }
+}
+
+/// The outcome of a single test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The program wrote the expected output
+    Passed,
+    /// The program wrote something else
+    Failed {
+        /// The expected output
+        expected: Vec<u8>,
+        /// The output of the program
+        actual:   Vec<u8>,
+    },
+    /// The program could not be run to completion
+    Error(String),
+    /// The program was stopped by a fault injected with
+    /// [`run_with_chaos`](TestCase::run_with_chaos). The fault reached the
+    /// caller as an error, so the test counts as passed.
+    Faulted(String),
+}
+
+impl Outcome {
+    /// Whether the test passed
+    #[must_use]
+    pub const fn is_passed(&self) -> bool {
+        matches!(self, Self::Passed | Self::Faulted(_))
+    }
+}
+
+/// The results of running a directory of tests
+///
+/// The `Display` implementation prints a line per test, the differences
+/// between the expected and the actual output of every failed test and a
+/// summary. [`tap`](Report::tap) prints the same results in the Test
+/// Anything Protocol.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The tests that were run, with their outcomes, in the order they ran
+    pub results: Vec<(TestCase, Outcome)>,
+}
+
+impl Report {
+    /// The number of tests that passed
+    #[must_use]
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, outcome)| outcome.is_passed())
+            .count()
+    }
+
+    /// The number of tests that failed or could not be run
+    #[must_use]
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+
+    /// Whether every test passed
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// The results in version 13 of the Test Anything Protocol
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::test_runner::Report;
+    ///
+    /// assert_eq!(Report::default().tap(), "TAP version 13\n1..0\n");
+    /// ```
+    #[must_use]
+    pub fn tap(&self) -> String {
+        let mut tap = format!("TAP version 13\n1..{}\n", self.results.len());
+        for (number, (case, outcome)) in self.results.iter().enumerate() {
+            let status = if outcome.is_passed() {
+                "ok"
+            } else {
+                "not ok"
+            };
+            tap.push_str(&format!("{status} {} - {}\n", number + 1, case.name));
+            for line in details(outcome).lines() {
+                tap.push_str(&format!("# {line}\n"));
+            }
+        }
+        tap
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (case, outcome) in &self.results {
+            let status = match outcome {
+                Outcome::Passed => "PASS",
+                Outcome::Failed { .. } => "FAIL",
+                Outcome::Error(_) => "ERROR",
+                Outcome::Faulted(_) => "FAULT",
+            };
+            writeln!(f, "{status:<5} {}", case.name)?;
+            for line in details(outcome).lines() {
+                writeln!(f, "      {line}")?;
+            }
+        }
+        writeln!(f)?;
+        writeln!(f, "{} passed, {} failed", self.passed(), self.failed())
+    }
+}
+
+/// Find the tests in a directory and its subdirectories
+///
+/// # Arguments
+///
+/// * `directory` - The directory holding the tests
+///
+/// # Returns
+///
+/// The tests, ordered by name.
+///
+/// # Errors
+///
+/// Returns an error if the directory or one of its subdirectories cannot be
+/// read.
+pub fn discover(directory: &Path) -> Result<Vec<TestCase>, FileError> {
+    let mut cases = Vec::new();
+    collect(directory, directory, &mut cases)?;
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(cases)
+}
+
+/// Run every test in a directory and its subdirectories
+///
+/// Every test may execute at most [`MAX_STEPS`] instructions.
+///
+/// # Arguments
+///
+/// * `directory` - The directory holding the tests
+///
+/// # Examples
+///
+/// ```
+/// use std::fs;
+///
+/// use brainfoamkit_lib::test_runner;
+///
+/// let directory = tempfile::tempdir().unwrap();
+/// fs::write(directory.path().join("echo.bf"), ",.").unwrap();
+/// fs::write(directory.path().join("echo.input"), "A").unwrap();
+/// fs::write(directory.path().join("echo.expected"), "A").unwrap();
+///
+/// let report = test_runner::run_dir(directory.path()).unwrap();
+///
+/// assert!(report.is_success());
+/// assert_eq!(report.passed(), 1);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the directory or one of its subdirectories cannot be
+/// read. Tests that cannot be run are reported as an [`Outcome::Error`].
+pub fn run_dir(directory: &Path) -> Result<Report, FileError> {
+    run_dir_with_limits(directory, Limits::new().max_steps(MAX_STEPS))
+}
+
+/// Run every test in a directory and its subdirectories with the given
+/// limits
+///
+/// # Errors
+///
+/// Returns an error if the directory or one of its subdirectories cannot be
+/// read. Tests that cannot be run are reported as an [`Outcome::Error`].
+pub fn run_dir_with_limits(directory: &Path, limits: Limits) -> Result<Report, FileError> {
+    let results = discover(directory)?
+        .into_iter()
+        .map(|case| {
+            let outcome = case.run(limits);
+            (case, outcome)
+        })
+        .collect();
+    Ok(Report { results })
+}
+
+/// Run every test in a directory and its subdirectories on input and output
+/// devices that fail now and then
+///
+/// See [`TestCase::run_with_chaos`].
+///
+/// # Arguments
+///
+/// * `directory` - The directory holding the tests
+/// * `limits` - The resource limits of every test
+/// * `seed` - The seed of the injected faults
+///
+/// # Errors
+///
+/// Returns an error if the directory or one of its subdirectories cannot be
+/// read. Tests that cannot be run are reported as an [`Outcome::Error`].
+pub fn run_dir_with_chaos(
+    directory: &Path,
+    limits: Limits,
+    seed: u64,
+) -> Result<Report, FileError> {
+    let results = discover(directory)?
+        .into_iter()
+        .map(|case| {
+            let outcome = case.run_with_chaos(limits, seed);
+            (case, outcome)
+        })
+        .collect();
+    Ok(Report { results })
+}
+
+/// Compare an expected and an actual output line by line
+///
+/// Lines only in the expected output are prefixed with `-`, lines only in the
+/// actual output with `+` and lines in both with a space.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::test_runner::diff;
+///
+/// assert_eq!(diff("a\nb\nc\n", "a\nx\nc\n"), " a\n-b\n+x\n c\n");
+/// ```
+#[must_use]
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // The length of the longest common subsequence of the suffixes
+    let mut common = vec![vec![0_usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff.push_str(&format!(" {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if j == actual.len() || (i < expected.len() && common[i + 1][j] >= common[i][j + 1])
+        {
+            diff.push_str(&format!("-{}\n", expected[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+{}\n", actual[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// What is worth knowing about an outcome besides its status
+fn details(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Passed => String::new(),
+        Outcome::Failed { expected, actual } => diff(
+            &String::from_utf8_lossy(expected),
+            &String::from_utf8_lossy(actual),
+        ),
+        Outcome::Error(error) | Outcome::Faulted(error) => error.clone(),
+    }
+}
+
+fn collect(root: &Path, directory: &Path, cases: &mut Vec<TestCase>) -> Result<(), FileError> {
+    let unreadable = |source| FileError::Read {
+        path: directory.to_path_buf(),
+        source,
+    };
+    for entry in fs::read_dir(directory).map_err(unreadable)? {
+        let path = entry.map_err(unreadable)?.path();
+        if path.is_dir() {
+            collect(root, &path, cases)?;
+            continue;
+        }
+        let is_program = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| PROGRAM_EXTENSIONS.contains(&extension));
+        let expected = path.with_extension("expected");
+        if !is_program || !expected.is_file() {
+            continue;
+        }
+
+        let input = Some(path.with_extension("input")).filter(|input| input.is_file());
+        let name = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .with_extension("")
+            .display()
+            .to_string();
+        cases.push(TestCase {
+            name,
+            program: path,
+            expected,
+            input,
+            dialect: Dialect::Standard,
+            config: Config::default(),
+            data: Vec::new(),
+        });
+    }
+    Ok(())
+}
+
+/// The outcome of a run without injected faults
+fn outcome(result: Result<(Vec<u8>, Vec<u8>), VMError>) -> Outcome {
+    match result {
+        Ok((expected, actual)) if expected == actual => Outcome::Passed,
+        Ok((expected, actual)) => Outcome::Failed { expected, actual },
+        Err(error) => Outcome::Error(describe_error(&error)),
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, FileError> {
+    fs::read(path).map_err(|source| FileError::Read {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn read_to_string(path: &Path) -> Result<String, FileError> {
+    fs::read_to_string(path).map_err(|source| FileError::Read {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// The message of an error followed by the messages of its sources
+fn describe_error(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn write(directory: &Path, name: &str, contents: &str) {
+        let path = directory.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_discover() {
+        let directory = tempdir().unwrap();
+        write(directory.path(), "b.bf", "+");
+        write(directory.path(), "b.expected", "");
+        write(directory.path(), "nested/a.b", ",.");
+        write(directory.path(), "nested/a.expected", "x");
+        write(directory.path(), "nested/a.input", "x");
+        write(directory.path(), "helper.bf", "+");
+        write(directory.path(), "notes.txt", "");
+
+        let cases = discover(directory.path()).unwrap();
+        let names: Vec<_> = cases.iter().map(|case| case.name.as_str()).collect();
+
+        assert_eq!(names, ["b", "nested/a"]);
+        assert_eq!(cases[0].input, None);
+        assert_eq!(
+            cases[1].input,
+            Some(directory.path().join("nested").join("a.input"))
+        );
+    }
+
+    #[test]
+    fn test_run_dir_with_chaos() {
+        let directory = tempdir().unwrap();
+        write(directory.path(), "echo.bf", ",[.,]");
+        write(
+            directory.path(),
+            "echo.input",
+            &format!("{}\0", "x".repeat(64)),
+        );
+        write(directory.path(), "echo.expected", &"x".repeat(64));
+        let limits = Limits::new().max_steps(MAX_STEPS);
+
+        let report = run_dir_with_chaos(directory.path(), limits, 1).unwrap();
+
+        assert!(report.is_success());
+        assert!(matches!(report.results[0].1, Outcome::Faulted(_)));
+        assert!(report.to_string().starts_with("FAULT echo"));
+        assert_eq!(
+            report,
+            run_dir_with_chaos(directory.path(), limits, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_run_dir() {
+        let directory = tempdir().unwrap();
+        write(directory.path(), "pass.bf", ",+.");
+        write(directory.path(), "pass.input", "A");
+        write(directory.path(), "pass.expected", "B");
+        write(directory.path(), "fail.bf", ",.");
+        write(directory.path(), "fail.input", "A");
+        write(directory.path(), "fail.expected", "B");
+        write(directory.path(), "loop.bf", "+[]");
+        write(directory.path(), "loop.expected", "");
+
+        let report = run_dir_with_limits(directory.path(), Limits::new().max_steps(100)).unwrap();
+
+        assert_eq!(report.passed(), 1);
+        assert_eq!(report.failed(), 2);
+        assert!(!report.is_success());
+        assert_eq!(
+            report.results[0].1,
+            Outcome::Failed {
+                expected: b"B".to_vec(),
+                actual:   b"A".to_vec(),
+            }
+        );
+        assert!(matches!(report.results[1].1, Outcome::Error(_)));
+        assert_eq!(report.results[2].1, Outcome::Passed);
+
+        let tap = report.tap();
+        assert!(tap.starts_with("TAP version 13\n1..3\nnot ok 1 - fail\n# -B\n# +A\n"));
+        assert!(tap.ends_with("ok 3 - pass\n"));
+
+        let pretty = report.to_string();
+        assert!(pretty.starts_with("FAIL  fail\n      -B\n      +A\nERROR loop\n"));
+        assert!(pretty.ends_with("1 passed, 2 failed\n"));
+    }
+
+    #[test]
+    fn test_missing_directory() {
+        let directory = tempdir().unwrap();
+        assert!(run_dir(&directory.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(diff("", ""), "");
+        assert_eq!(diff("a\n", "a\n"), " a\n");
+        assert_eq!(diff("a\nb\n", "b\n"), "-a\n b\n");
+        assert_eq!(diff("a\n", "a\nb\n"), " a\n+b\n");
+        assert_eq!(diff("a\nb\n", "c\nd\n"), "-a\n-b\n+c\n+d\n");
+    }
+}
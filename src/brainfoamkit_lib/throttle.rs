@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// A token bucket that limits how many instructions run per second
+///
+/// Every instruction takes a token. Tokens come back at `rate` per second,
+/// up to a burst of `burst` tokens, so a run can never get more than `burst`
+/// instructions ahead of the rate. An instruction that finds the bucket empty
+/// waits until its token arrives.
+///
+/// Used by [`VirtualMachine::set_speed`](crate::VirtualMachine::set_speed),
+/// and useful on its own to pace anything that steps a machine.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use brainfoamkit_lib::Throttle;
+///
+/// let mut throttle = Throttle::new(10);
+///
+/// // The first token is there right away, the next one after a tenth of a
+/// // second
+/// assert_eq!(throttle.delay(), Duration::ZERO);
+/// assert!(throttle.delay() > Duration::from_millis(90));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throttle {
+    rate:   u32,
+    burst:  u32,
+    tokens: f64,
+    last:   Instant,
+}
+
+impl Throttle {
+    /// Allow `rate` instructions per second, one at a time
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is zero.
+    #[must_use]
+    pub fn new(rate: u32) -> Self {
+        assert!(rate > 0, "The rate of a throttle must be at least 1");
+        Self {
+            rate,
+            burst: 1,
+            tokens: 1.0,
+            last: Instant::now(),
+        }
+    }
+
+    /// Let up to `burst` instructions run at once after a pause, as long as
+    /// they stay within the rate on average
+    ///
+    /// # Returns
+    ///
+    /// The `Throttle` by value with the burst set, and a full bucket.
+    #[must_use]
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self.tokens = f64::from(self.burst);
+        self
+    }
+
+    /// The number of instructions allowed per second
+    #[must_use]
+    pub const fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// The number of instructions that may run at once
+    #[must_use]
+    pub const fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Take a token for the next instruction
+    ///
+    /// # Returns
+    ///
+    /// How long to wait before the instruction may run, which is zero if a
+    /// token was available. The token is reserved either way, so the caller
+    /// must wait for the whole delay before taking the next one.
+    pub fn delay(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens =
+            (elapsed.mul_add(f64::from(self.rate), self.tokens)).min(f64::from(self.burst)) - 1.0;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / f64::from(self.rate))
+        }
+    }
+
+    /// Take a token for the next instruction, sleeping until it is there
+    pub fn wait(&mut self) {
+        let delay = self.delay();
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        let mut throttle = Throttle::new(1000);
+        let start = Instant::now();
+        for _ in 0..51 {
+            throttle.wait();
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_burst() {
+        let mut throttle = Throttle::new(1).with_burst(3);
+
+        assert_eq!(throttle.burst(), 3);
+        for _ in 0..3 {
+            assert_eq!(throttle.delay(), Duration::ZERO);
+        }
+        assert!(throttle.delay() > Duration::from_millis(900));
+        assert!(throttle.delay() > Duration::from_millis(1900));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1")]
+    fn test_zero_rate() {
+        let _ = Throttle::new(0);
+    }
+}
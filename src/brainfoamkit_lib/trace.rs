@@ -0,0 +1,491 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Traces that can be compared across versions of the crate
+//!
+//! The crate writes down what a run did in several formats: the trace of a
+//! [`CrashBundle`](crate::crash::CrashBundle), a list of
+//! [`TraceEvent`](crate::crash::TraceEvent)s, and the Chrome trace and
+//! speedscope profiles of the [`Profiler`](crate::Profiler). Besides the
+//! behavior of the program, these hold details that may change from one
+//! release to the next, such as the names of instructions, the order of
+//! fields or the timing of spans. [`normalize`] turns any of them into a
+//! canonical text with one event per line that keeps only the behavior, and
+//! [`compare`] lists the events two traces disagree on, so that a trace saved
+//! before an upgrade can be checked against one taken after it.
+//!
+//! In the canonical form, an executed instruction is a line such as
+//! `step 3 + ptr=0 cell=1`, with the position of the instruction, its
+//! character, and the memory pointer and the value of the cell before it.
+//! A span of a profile is an `enter loop@2` line and a matching `exit
+//! loop@2` line, nested as the spans were, without their timestamps.
+//!
+//! # Examples
+//!
+//! ```
+//! use brainfoamkit_lib::{
+//!     trace,
+//!     Profiler,
+//!     Program,
+//!     VirtualMachine,
+//! };
+//!
+//! let mut machine = VirtualMachine::builder()
+//!     .input_device(std::io::stdin())
+//!     .program(Program::from("+[-]"))
+//!     .build()
+//!     .unwrap();
+//! let mut profiler = Profiler::new();
+//! profiler.run(&mut machine).unwrap();
+//!
+//! let chrome = profiler.chrome_trace();
+//! assert_eq!(
+//!     trace::normalize(&chrome).unwrap(),
+//!     "enter program\nenter loop@1\nexit loop@1\nexit program\n"
+//! );
+//!
+//! // The same run in another format has no semantic differences
+//! let speedscope = profiler.speedscope();
+//! assert!(trace::compare(&chrome, &speedscope).unwrap().is_empty());
+//! ```
+
+use std::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+
+use serde_json::{
+    Map,
+    Value,
+};
+
+use crate::{
+    Instruction,
+    TraceError,
+};
+
+/// Every instruction that is not an extension, to find them by name
+const INSTRUCTIONS: [Instruction; 14] = [
+    Instruction::IncrementPointer,
+    Instruction::DecrementPointer,
+    Instruction::IncrementValue,
+    Instruction::DecrementValue,
+    Instruction::OutputValue,
+    Instruction::InputValue,
+    Instruction::JumpForward,
+    Instruction::JumpBackward,
+    Instruction::NoOp,
+    Instruction::DebugDump,
+    Instruction::DefineProcedure,
+    Instruction::EndProcedure,
+    Instruction::CallProcedure,
+    Instruction::Halt,
+];
+
+/// An event that only one of two compared traces has
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceDifference {
+    /// The event is only in the old trace
+    Removed {
+        /// The line of the event in the normalized old trace, from 1
+        line:  usize,
+        /// The event, in the canonical form
+        event: String,
+    },
+    /// The event is only in the new trace
+    Added {
+        /// The line of the event in the normalized new trace, from 1
+        line:  usize,
+        /// The event, in the canonical form
+        event: String,
+    },
+}
+
+impl Display for TraceDifference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Removed { line, event } => write!(f, "-{line}: {event}"),
+            Self::Added { line, event } => write!(f, "+{line}: {event}"),
+        }
+    }
+}
+
+/// Turn a trace into the canonical form, one event per line
+///
+/// The trace can be a crash bundle, a JSON list of trace events, a Chrome
+/// trace or a speedscope profile, as written by any version of the crate.
+/// Text that is not JSON is taken to be normalized already, so normalizing
+/// twice changes nothing.
+///
+/// # Errors
+///
+/// Returns an error if the trace is JSON in none of these formats, or if one
+/// of its events is missing a field.
+pub fn normalize(trace: &str) -> Result<String, TraceError> {
+    Ok(events(trace)?
+        .iter()
+        .map(|event| format!("{event}\n"))
+        .collect())
+}
+
+/// The events that are only in `old` or only in `new`
+///
+/// Both traces are [normalized](normalize) first, so differences in format
+/// or timing are not reported. The events both traces share are matched up
+/// as in a line by line diff: an event inserted in the middle of a trace is a
+/// single difference.
+///
+/// # Returns
+///
+/// The differences in the order of the traces, none if they behave the same.
+///
+/// # Errors
+///
+/// Returns an error if either trace cannot be normalized.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::trace::{
+///     compare,
+///     TraceDifference,
+/// };
+///
+/// let old = "step 0 + ptr=0 cell=0\nstep 1 > ptr=0 cell=1\n";
+/// let new = r#"[
+///     { "program-counter": 0, "instruction": "INCVAL", "memory-pointer": 0, "cell": 0 },
+///     { "program-counter": 1, "instruction": "INCVAL", "memory-pointer": 0, "cell": 1 }
+/// ]"#;
+///
+/// assert_eq!(
+///     compare(old, new).unwrap(),
+///     [
+///         TraceDifference::Removed {
+///             line:  2,
+///             event: String::from("step 1 > ptr=0 cell=1"),
+///         },
+///         TraceDifference::Added {
+///             line:  2,
+///             event: String::from("step 1 + ptr=0 cell=1"),
+///         },
+///     ]
+/// );
+/// ```
+pub fn compare(old: &str, new: &str) -> Result<Vec<TraceDifference>, TraceError> {
+    let old = events(old)?;
+    let new = events(new)?;
+    Ok(differences(&old, &new))
+}
+
+/// The events of a trace in the canonical form
+fn events(trace: &str) -> Result<Vec<String>, TraceError> {
+    let Ok(value) = serde_json::from_str::<Value>(trace) else {
+        return Ok(trace
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect());
+    };
+    match &value {
+        Value::Array(events) => steps(events),
+        Value::Object(object) => {
+            if let Some(Value::Array(events)) = object.get("traceEvents") {
+                chrome(events)
+            } else if let Some(Value::Array(events)) = object.get("trace") {
+                steps(events)
+            } else if object.contains_key("profiles") {
+                speedscope(object)
+            } else {
+                Err(TraceError::UnknownFormat)
+            }
+        }
+        _ => Err(TraceError::UnknownFormat),
+    }
+}
+
+/// The executed instructions of a crash bundle or a list of trace events
+fn steps(events: &[Value]) -> Result<Vec<String>, TraceError> {
+    events
+        .iter()
+        .enumerate()
+        .map(|(index, event)| {
+            let number = |field: &'static str| {
+                event
+                    .get(field)
+                    .and_then(Value::as_u64)
+                    .ok_or(TraceError::InvalidEvent { index, field })
+            };
+            let instruction = event.get("instruction").and_then(Value::as_str).ok_or(
+                TraceError::InvalidEvent {
+                    index,
+                    field: "instruction",
+                },
+            )?;
+            Ok(format!(
+                "step {} {} ptr={} cell={}",
+                number("program-counter")?,
+                character(instruction),
+                number("memory-pointer")?,
+                number("cell")?
+            ))
+        })
+        .collect()
+}
+
+/// The character of an instruction written by its name, such as `INCPTR`
+fn character(name: &str) -> String {
+    if let Some(extension) = name
+        .strip_prefix("EXT(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return extension.to_string();
+    }
+    INSTRUCTIONS
+        .iter()
+        .find(|instruction| instruction.to_string() == name)
+        .and_then(Instruction::to_char)
+        .map_or_else(|| name.to_string(), String::from)
+}
+
+/// The spans of a Chrome trace
+fn chrome(events: &[Value]) -> Result<Vec<String>, TraceError> {
+    let mut spans = Vec::new();
+    for (index, event) in events.iter().enumerate() {
+        if event.get("ph").and_then(Value::as_str) != Some("X") {
+            continue;
+        }
+        let invalid = |field| TraceError::InvalidEvent { index, field };
+        let name = event
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid("name"))?;
+        let start = event
+            .get("ts")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid("ts"))?;
+        let duration = event
+            .get("dur")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid("dur"))?;
+        spans.push((name.to_string(), start, start + duration));
+    }
+    Ok(nest(spans))
+}
+
+/// The spans of every profile of a speedscope file
+fn speedscope(object: &Map<String, Value>) -> Result<Vec<String>, TraceError> {
+    let frames: Vec<&str> = object
+        .get("shared")
+        .and_then(|shared| shared.get("frames"))
+        .and_then(Value::as_array)
+        .map(|frames| {
+            frames
+                .iter()
+                .map(|frame| {
+                    frame
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let events = object
+        .get("profiles")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|profile| profile.get("events").and_then(Value::as_array))
+        .flatten();
+
+    let mut spans = Vec::new();
+    let mut open = Vec::new();
+    for (index, event) in events.enumerate() {
+        let invalid = |field| TraceError::InvalidEvent { index, field };
+        let name = event
+            .get("frame")
+            .and_then(Value::as_u64)
+            .and_then(|frame| frames.get(usize::try_from(frame).ok()?))
+            .ok_or_else(|| invalid("frame"))?;
+        let at = event
+            .get("at")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid("at"))?;
+        match event.get("type").and_then(Value::as_str) {
+            Some("O") => open.push(at),
+            Some("C") => {
+                let start = open.pop().ok_or_else(|| invalid("type"))?;
+                spans.push(((*name).to_string(), start, at));
+            }
+            _ => return Err(invalid("type")),
+        }
+    }
+    Ok(nest(spans))
+}
+
+/// `enter` and `exit` lines for spans given by their name, start and end
+///
+/// A span that starts inside another one and ends with it or before is
+/// nested in it. Spans that start together are nested with the longest one
+/// outside.
+fn nest(mut spans: Vec<(String, u64, u64)>) -> Vec<String> {
+    spans.sort_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+
+    let mut lines = Vec::new();
+    let mut open: Vec<(String, u64)> = Vec::new();
+    for (name, start, end) in spans {
+        while open.last().is_some_and(|(_, open_end)| *open_end <= start) {
+            if let Some((name, _)) = open.pop() {
+                lines.push(format!("exit {name}"));
+            }
+        }
+        lines.push(format!("enter {name}"));
+        open.push((name, end));
+    }
+    while let Some((name, _)) = open.pop() {
+        lines.push(format!("exit {name}"));
+    }
+    lines
+}
+
+/// The events to remove from `old` and add to make `new`, as few as a
+/// longest common subsequence allows
+fn differences(old: &[String], new: &[String]) -> Vec<TraceDifference> {
+    // Only the events between the common start and end need to be matched up
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // The length of the longest common subsequence of the suffixes
+    let mut common = vec![vec![0_usize; new_middle.len() + 1]; old_middle.len() + 1];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            common[i][j] = if old_middle[i] == new_middle[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut differences = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            i += 1;
+            j += 1;
+        } else if j == new_middle.len()
+            || (i < old_middle.len() && common[i + 1][j] >= common[i][j + 1])
+        {
+            differences.push(TraceDifference::Removed {
+                line:  prefix + i + 1,
+                event: old_middle[i].clone(),
+            });
+            i += 1;
+        } else {
+            differences.push(TraceDifference::Added {
+                line:  prefix + j + 1,
+                event: new_middle[j].clone(),
+            });
+            j += 1;
+        }
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        crash::CrashBundle,
+        vm_reader::MockReader,
+        Dialect,
+        Profiler,
+        Program,
+        VirtualMachine,
+    };
+
+    #[test]
+    fn test_normalize_crash_bundle() {
+        let mut machine = VirtualMachine::builder()
+            .input_device(MockReader {
+                data: Cursor::new(Vec::new()),
+            })
+            .program(Program::from("+>-"))
+            .history_capacity(8)
+            .build()
+            .unwrap();
+        machine.run().unwrap();
+        let bundle = CrashBundle::capture(
+            &machine,
+            "+>-",
+            Dialect::Standard,
+            Config::default(),
+            String::from("test"),
+        );
+
+        let normalized = normalize(&bundle.to_json()).unwrap();
+        assert_eq!(
+            normalized,
+            "step 0 + ptr=0 cell=0\nstep 1 > ptr=0 cell=1\nstep 2 - ptr=1 cell=0\n"
+        );
+        assert_eq!(normalize(&normalized).unwrap(), normalized);
+    }
+
+    #[test]
+    fn test_compare_profiles() {
+        let profile = |input: u8| {
+            let mut machine = VirtualMachine::builder()
+                .input_device(MockReader {
+                    data: Cursor::new(vec![input]),
+                })
+                .program(Program::from(",[-]+"))
+                .build()
+                .unwrap();
+            let mut profiler = Profiler::new();
+            profiler.run(&mut machine).unwrap();
+            profiler
+        };
+
+        // Loops that run longer only differ in timing
+        let old = profile(2);
+        assert!(compare(&old.chrome_trace(), &old.speedscope())
+            .unwrap()
+            .is_empty());
+        assert!(compare(&old.chrome_trace(), &profile(100).chrome_trace())
+            .unwrap()
+            .is_empty());
+
+        // A loop that is skipped is a difference
+        assert_eq!(
+            compare(&old.speedscope(), &profile(0).chrome_trace())
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["-2: enter loop@1", "-3: exit loop@1"]
+        );
+
+        assert!(matches!(
+            normalize("{\"version\": 1}"),
+            Err(TraceError::UnknownFormat)
+        ));
+    }
+}
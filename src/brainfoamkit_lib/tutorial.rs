@@ -0,0 +1,265 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Lessons that teach Brainfuck one instruction at a time
+//!
+//! A [`Lesson`] explains a bit of the language and sets a small task: the
+//! tape starts with some values, the program gets some input, and the
+//! [`Attempt`] of the learner has to reach a goal, such as a value in a cell
+//! or a line of output. [`lessons`] returns the built-in lessons, which start
+//! with `+` on its own and end with printing a greeting.
+//!
+//! # Examples
+//!
+//! ```
+//! use brainfoamkit_lib::tutorial;
+//!
+//! let lesson = &tutorial::lessons()[0];
+//!
+//! let attempt = lesson.attempt("").unwrap();
+//! assert!(!lesson.is_solved_by(&attempt));
+//!
+//! let attempt = lesson.attempt(lesson.solution).unwrap();
+//! assert!(lesson.is_solved_by(&attempt));
+//! ```
+
+use crate::{
+    Byte,
+    CaptureWriter,
+    Counters,
+    IoDevices,
+    LimitExceeded,
+    Program,
+    TapeBackend,
+    VMError,
+    VecReader,
+    VirtualMachine,
+};
+
+/// The number of instructions an attempt may execute before it is stopped
+pub const MAX_STEPS: usize = 100_000;
+
+/// The number of cells on the tape of an attempt
+pub const TAPE_SIZE: usize = 16;
+
+/// A single lesson of the tutorial
+#[derive(Debug, Clone, Copy)]
+pub struct Lesson {
+    /// A short name for the lesson
+    pub title:        &'static str,
+    /// What the lesson teaches and the task to solve
+    pub instructions: &'static str,
+    /// The values of the first cells of the tape when the program starts. The
+    /// other cells start at zero.
+    pub tape:         &'static [u8],
+    /// The input of the program
+    pub input:        &'static [u8],
+    /// A program that solves the task
+    pub solution:     &'static str,
+    /// Whether an attempt reached the goal of the lesson
+    pub goal:         fn(&Attempt) -> bool,
+}
+
+/// The state a program left behind after running in a lesson
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attempt {
+    /// Every byte the program wrote
+    pub output:         Vec<u8>,
+    /// The values of the cells once the program halted
+    pub tape:           Vec<u8>,
+    /// The position of the memory pointer once the program halted
+    pub memory_pointer: usize,
+}
+
+impl Lesson {
+    /// Run a program on the starting tape and input of the lesson
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The program written by the learner
+    ///
+    /// # Returns
+    ///
+    /// The output and the final state of the tape, which can be checked with
+    /// [`is_solved_by`](Self::is_solved_by)
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error if the brackets of the program are not balanced.
+    /// * Returns an error if the program fails, for example a [`LimitExceeded`]
+    ///   error because it runs for more than [`MAX_STEPS`] instructions.
+    pub fn attempt(&self, source: &str) -> Result<Attempt, VMError> {
+        let mut cells = vec![Byte::default(); TAPE_SIZE.max(self.tape.len())];
+        for (cell, value) in cells.iter_mut().zip(self.tape) {
+            *cell = Byte::from(*value);
+        }
+        let mut machine = VirtualMachine::from_parts(
+            Program::from(source),
+            TapeBackend::new(cells),
+            IoDevices {
+                input:  VecReader::new(self.input.to_vec()),
+                output: CaptureWriter::default(),
+            },
+            Counters::default(),
+        )?;
+
+        while !machine.is_halted() {
+            if machine.steps() >= MAX_STEPS {
+                return Err(LimitExceeded::Steps { limit: MAX_STEPS }.into());
+            }
+            machine.execute_instruction()?;
+        }
+
+        Ok(Attempt {
+            output:         machine.output_device().data.clone(),
+            tape:           machine.tape().iter().map(u8::from).collect(),
+            memory_pointer: machine.memory_pointer(),
+        })
+    }
+
+    /// Whether an attempt reached the goal of the lesson
+    #[must_use]
+    pub fn is_solved_by(&self, attempt: &Attempt) -> bool {
+        (self.goal)(attempt)
+    }
+}
+
+/// The lessons of the tutorial, in the order they should be taken
+#[must_use]
+pub const fn lessons() -> &'static [Lesson] {
+    &LESSONS
+}
+
+const LESSONS: [Lesson; 10] = [
+    Lesson {
+        title:        "Adding one",
+        instructions: "The tape is a row of cells that all start at 0, and the pointer starts on \
+                       the first cell. `+` adds one to the cell under the pointer. Make cell 0 \
+                       hold 1.",
+        tape:         &[],
+        input:        &[],
+        solution:     "+",
+        goal:         |attempt| attempt.tape[0] == 1,
+    },
+    Lesson {
+        title:        "Counting up",
+        instructions: "Every `+` adds one more. Make cell 0 hold 5.",
+        tape:         &[],
+        input:        &[],
+        solution:     "+++++",
+        goal:         |attempt| attempt.tape[0] == 5,
+    },
+    Lesson {
+        title:        "Counting down",
+        instructions: "`-` subtracts one from the cell under the pointer. Cell 0 starts at 10. \
+                       Make it hold 7.",
+        tape:         &[10],
+        input:        &[],
+        solution:     "---",
+        goal:         |attempt| attempt.tape[0] == 7,
+    },
+    Lesson {
+        title:        "Moving the pointer",
+        instructions: "`>` moves the pointer one cell to the right and `<` moves it back to the \
+                       left. Make cell 2 hold 3 and leave the pointer on cell 0.",
+        tape:         &[],
+        input:        &[],
+        solution:     ">>+++<<",
+        goal:         |attempt| attempt.tape[2] == 3 && attempt.memory_pointer == 0,
+    },
+    Lesson {
+        title:        "Output",
+        instructions: "`.` writes the value of the cell under the pointer as a character. Cell 0 \
+                       holds 72, which is the letter H. Print it.",
+        tape:         &[72],
+        input:        &[],
+        solution:     ".",
+        goal:         |attempt| attempt.output == b"H",
+    },
+    Lesson {
+        title:        "Input",
+        instructions: "`,` reads a character of input into the cell under the pointer. The input \
+                       of this lesson is the letter b. Read it, add one and print the result.",
+        tape:         &[],
+        input:        b"b",
+        solution:     ",+.",
+        goal:         |attempt| attempt.output == b"c",
+    },
+    Lesson {
+        title:        "Your first loop",
+        instructions: "`[` skips past the matching `]` if the cell under the pointer is 0. \
+                       Otherwise the instructions in between run, and `]` jumps back to the `[` \
+                       while the cell is not 0. Cell 0 starts at 42. Set it to 0 using a loop.",
+        tape:         &[42],
+        input:        &[],
+        solution:     "[-]",
+        goal:         |attempt| attempt.tape[0] == 0,
+    },
+    Lesson {
+        title:        "Moving a value",
+        instructions: "A loop can carry a value from one cell to another: take one from the first \
+                       cell, add one to the second, and repeat until the first cell is 0. Move \
+                       the 4 in cell 0 to cell 1.",
+        tape:         &[4],
+        input:        &[],
+        solution:     "[->+<]",
+        goal:         |attempt| attempt.tape[..2] == [0, 4],
+    },
+    Lesson {
+        title:        "Doubling",
+        instructions: "Adding two to the second cell every time the loop takes one from the first \
+                       cell doubles the value. Cell 0 holds 6. Make cell 1 hold 12 and leave cell \
+                       0 at 0.",
+        tape:         &[6],
+        input:        &[],
+        solution:     "[->++<]",
+        goal:         |attempt| attempt.tape[..2] == [0, 12],
+    },
+    Lesson {
+        title:        "Hello",
+        instructions: "Loops make large numbers easy to reach: 8 times 9 is 72, the letter H, and \
+                       105 is the letter i. Print \"Hi\".",
+        tape:         &[],
+        input:        &[],
+        solution:     "++++++++[>+++++++++<-]>.+++++++++++++++++++++++++++++++++.",
+        goal:         |attempt| attempt.output == b"Hi",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solutions() {
+        for lesson in lessons() {
+            let attempt = lesson.attempt(lesson.solution).unwrap();
+            assert!(lesson.is_solved_by(&attempt), "{}", lesson.title);
+        }
+    }
+
+    #[test]
+    fn test_empty_programs_fail() {
+        for lesson in lessons() {
+            let attempt = lesson.attempt("").unwrap();
+            assert!(!lesson.is_solved_by(&attempt), "{}", lesson.title);
+        }
+    }
+
+    #[test]
+    fn test_starting_state() {
+        let attempt = lessons()[2].attempt("").unwrap();
+
+        assert_eq!(attempt.tape.len(), TAPE_SIZE);
+        assert_eq!(attempt.tape[0], 10);
+        assert_eq!(attempt.memory_pointer, 0);
+    }
+
+    #[test]
+    fn test_endless_loop() {
+        assert!(lessons()[0].attempt("+[]").is_err());
+        assert!(lessons()[0].attempt("[").is_err());
+    }
+}
@@ -6,17 +6,29 @@
 use std::{
     fs::File,
     io::{
+        self,
         Cursor,
+        IsTerminal,
         Read,
         Stdin,
+        Write,
     },
 };
 
-use anyhow::{
-    anyhow,
-    Result,
+use crossterm::{
+    event::{
+        self,
+        Event,
+        KeyCode,
+        KeyEvent,
+        KeyEventKind,
+        KeyModifiers,
+    },
+    terminal,
 };
 
+use crate::ReadError;
+
 /// Allowable types of `VMReader`
 ///
 /// This enum is used to determine the type of `VMReader` that is being used.
@@ -27,6 +39,12 @@ use anyhow::{
 /// * File - A file as implemented by the [std::fs::File struct](https://doc.rust-lang.org/std/fs/struct.File.html)
 /// * Mock - A mock reader as implemented by the [`MockReader`
 ///   struct](struct.MockReader.html)
+/// * Vec - An in-memory buffer as implemented by the [`VecReader`
+///   struct](struct.VecReader.html)
+/// * Terminal - STDIN with terminal options as implemented by the
+///   [`TerminalReader` struct](struct.TerminalReader.html)
+/// * Tcp - A network connection as implemented by the [`TcpReader`
+///   struct](net/struct.TcpReader.html)
 /// * Unknown - The default type of `VMReader`
 ///
 /// # Examples
@@ -65,6 +83,15 @@ pub enum VMReaderType {
     /// A mock reader as implemented by the [`MockReader`
     /// struct](struct.MockReader.html)
     Mock,
+    /// An in-memory buffer as implemented by the [`VecReader`
+    /// struct](struct.VecReader.html)
+    Vec,
+    /// STDIN with terminal options as implemented by the [`TerminalReader`
+    /// struct](struct.TerminalReader.html)
+    Terminal,
+    /// A network connection as implemented by the [`TcpReader`
+    /// struct](net/struct.TcpReader.html)
+    Tcp,
     /// The default type of `VMReader`
     Unknown,
 }
@@ -127,10 +154,67 @@ pub trait VMReader {
     ///
     /// This function will return an error if the byte read from the reader is
     /// not within the ASCII range.
-    fn read(&mut self) -> Result<u8> {
+    fn read(&mut self) -> Result<u8, ReadError> {
         Ok(0)
     }
 
+    /// Read a decimal number from 0 to 255 from the reader
+    ///
+    /// This function is used by `,` in
+    /// [`IoMode::Numeric`](enum.IoMode.html#variant.Numeric). It skips
+    /// leading whitespace and reads digits up to the first byte that is not a
+    /// digit, which is consumed, or up to the end of the input.
+    ///
+    /// # Returns
+    ///
+    /// The number, or `None` if the input ends before a number starts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     VMReader,
+    ///     VecReader,
+    /// };
+    ///
+    /// let mut reader = VecReader::new(b" 12\n255".to_vec());
+    ///
+    /// assert_eq!(reader.read_number().unwrap(), Some(12));
+    /// assert_eq!(reader.read_number().unwrap(), Some(255));
+    /// assert_eq!(reader.read_number().unwrap(), None);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the input holds something other
+    /// than a number, or a number larger than 255.
+    fn read_number(&mut self) -> Result<Option<u8>, ReadError> {
+        let mut byte = loop {
+            match self.read() {
+                Ok(byte) if byte.is_ascii_whitespace() => {}
+                Ok(byte) => break byte,
+                Err(_) => return Ok(None),
+            }
+        };
+        if !byte.is_ascii_digit() {
+            return Err(ReadError::NotANumber(byte));
+        }
+
+        let mut number: u32 = 0;
+        while byte.is_ascii_digit() {
+            number = number * 10 + u32::from(byte - b'0');
+            if number > u32::from(u8::MAX) {
+                return Err(ReadError::NumberTooLarge);
+            }
+            match self.read() {
+                Ok(next) => byte = next,
+                Err(_) => break,
+            }
+        }
+
+        Ok(u8::try_from(number).ok())
+    }
+
     /// Get the type of the reader
     ///
     /// This function returns the type of the reader as a `VMReaderType` enum.
@@ -211,14 +295,14 @@ impl VMReader for MockReader {
     ///     brainfoamkit_lib::VMReaderType::Mock
     /// );
     /// ```
-    fn read(&mut self) -> Result<u8> {
+    fn read(&mut self) -> Result<u8, ReadError> {
         let mut buffer = [0u8; 1];
         self.data.read_exact(&mut buffer)?;
 
         if buffer[0] <= 128 {
             Ok(buffer[0])
         } else {
-            Err(anyhow!("Byte is not within the ASCII range"))
+            Err(ReadError::NotAscii(buffer[0]))
         }
     }
 
@@ -227,6 +311,252 @@ impl VMReader for MockReader {
     }
 }
 
+/// The `VecReader` struct
+///
+/// This struct feeds a fixed sequence of bytes to the `VirtualMachine`, such as
+/// the input embedded after the `!` of a combined program and input file. See
+/// [`Program::parse_with_input`](struct.Program.html#method.parse_with_input).
+///
+/// Once every byte has been read, further reads return an error and the
+/// `VirtualMachine` leaves the current cell unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     VMReader,
+///     VMReaderType,
+///     VecReader,
+/// };
+///
+/// let mut reader = VecReader::new(b"hi".to_vec());
+///
+/// assert_eq!(reader.read().unwrap(), b'h');
+/// assert_eq!(reader.read().unwrap(), b'i');
+/// assert!(reader.read().is_err());
+/// assert_eq!(reader.get_vmreader_type(), VMReaderType::Vec);
+/// ```
+///
+/// # See Also
+///
+/// * [`VMReader`](trait.VMReader.html)
+/// * [`MockReader`](struct.MockReader.html)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VecReader {
+    data:     Vec<u8>,
+    position: usize,
+}
+
+impl VecReader {
+    /// Create a new `VecReader` that yields the given bytes in order
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The bytes to read
+    #[must_use]
+    pub const fn new(data: Vec<u8>) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// The bytes that have not been read yet
+    #[must_use]
+    pub fn remaining(&self) -> &[u8] {
+        &self.data[self.position..]
+    }
+}
+
+impl From<Vec<u8>> for VecReader {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(data)
+    }
+}
+
+/// The implementation of the `VMReader` trait for the `VecReader` struct
+impl VMReader for VecReader {
+    /// Read the next byte of the buffer
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if every byte has been read or if
+    /// the next byte is not within the ASCII range.
+    fn read(&mut self) -> Result<u8, ReadError> {
+        let byte = *self.data.get(self.position).ok_or(ReadError::Eof)?;
+        self.position += 1;
+
+        if byte <= 128 {
+            Ok(byte)
+        } else {
+            Err(ReadError::NotAscii(byte))
+        }
+    }
+
+    fn get_vmreader_type(&self) -> VMReaderType {
+        VMReaderType::Vec
+    }
+}
+
+/// The `TerminalReader` struct
+///
+/// This struct reads the input of the `VirtualMachine` from STDIN, with the
+/// options interactive programs need when STDIN is a terminal.
+///
+/// By default, the terminal hands over the input a line at a time, once the
+/// user presses Enter, and shows what the user types. In raw mode, every key
+/// is read as soon as it is pressed and is not shown, so that `,` reads a
+/// single key press. Enter is read as a newline, and Ctrl-D ends the input.
+/// The terminal is only in raw mode while `,` waits for a key, so the output
+/// of the program is not affected.
+///
+/// With echo, every byte that is read is also written to STDOUT. This shows
+/// the input in raw mode, and in the output when the input is piped in from a
+/// file.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     TerminalReader,
+///     VMReader,
+///     VMReaderType,
+/// };
+///
+/// let reader = TerminalReader::new().raw(true).echo(true);
+///
+/// assert!(reader.is_raw());
+/// assert!(reader.echoes());
+/// assert_eq!(reader.get_vmreader_type(), VMReaderType::Terminal);
+/// ```
+///
+/// # See Also
+///
+/// * [`VMReader`](trait.VMReader.html)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalReader {
+    raw:  bool,
+    echo: bool,
+}
+
+impl TerminalReader {
+    /// Create a new `TerminalReader` that reads STDIN a line at a time
+    /// without echo
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            raw:  false,
+            echo: false,
+        }
+    }
+
+    /// Read a key at a time without the terminal showing it
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to read in raw mode
+    #[must_use]
+    pub const fn raw(mut self, enabled: bool) -> Self {
+        self.raw = enabled;
+        self
+    }
+
+    /// Write every byte that is read to STDOUT
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to echo the input
+    #[must_use]
+    pub const fn echo(mut self, enabled: bool) -> Self {
+        self.echo = enabled;
+        self
+    }
+
+    /// Whether the reader reads in raw mode
+    #[must_use]
+    pub const fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    /// Whether the reader echoes the input
+    #[must_use]
+    pub const fn echoes(&self) -> bool {
+        self.echo
+    }
+
+    /// Wait for a key press in raw mode
+    fn read_key() -> Result<u8, ReadError> {
+        if !io::stdin().is_terminal() {
+            return Err(ReadError::NotATerminal);
+        }
+
+        terminal::enable_raw_mode()?;
+        let key = loop {
+            match event::read() {
+                Ok(Event::Key(KeyEvent {
+                    code,
+                    modifiers,
+                    kind: KeyEventKind::Press,
+                    ..
+                })) => {
+                    if let Some(key) = Self::key_byte(code, modifiers) {
+                        break key;
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => break Err(error.into()),
+            }
+        };
+        terminal::disable_raw_mode()?;
+        key
+    }
+
+    /// The byte a key press stands for, or `None` to ignore the key
+    fn key_byte(code: KeyCode, modifiers: KeyModifiers) -> Option<Result<u8, ReadError>> {
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            return match code {
+                KeyCode::Char('c') => Some(Err(ReadError::Interrupted)),
+                KeyCode::Char('d') => Some(Err(ReadError::Eof)),
+                _ => None,
+            };
+        }
+        match code {
+            KeyCode::Enter => Some(Ok(b'\n')),
+            KeyCode::Tab => Some(Ok(b'\t')),
+            KeyCode::Backspace => Some(Ok(8)),
+            KeyCode::Esc => Some(Ok(27)),
+            KeyCode::Char(character) if character.is_ascii() => Some(Ok(character as u8)),
+            _ => None,
+        }
+    }
+}
+
+/// The implementation of the `VMReader` trait for the `TerminalReader` struct
+impl VMReader for TerminalReader {
+    /// Read a single byte from STDIN
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no more input, if the
+    /// byte read is not within the ASCII range, or if raw mode is enabled and
+    /// STDIN is not a terminal.
+    fn read(&mut self) -> Result<u8, ReadError> {
+        let byte = if self.raw {
+            Self::read_key()?
+        } else {
+            VMReader::read(&mut io::stdin())?
+        };
+
+        if self.echo {
+            let mut stdout = io::stdout();
+            stdout.write_all(&[byte])?;
+            stdout.flush()?;
+        }
+        Ok(byte)
+    }
+
+    fn get_vmreader_type(&self) -> VMReaderType {
+        VMReaderType::Terminal
+    }
+}
+
 /// The implementation of the `VMReader` trait for the `Stdin` struct
 impl VMReader for Stdin {
     /// Read a single byte from STDIN
@@ -238,14 +568,14 @@ impl VMReader for Stdin {
     ///
     /// This function will return an error if the byte read from STDIN is not
     /// within the ASCII range.
-    fn read(&mut self) -> Result<u8> {
+    fn read(&mut self) -> Result<u8, ReadError> {
         let mut buffer = [0u8; 1];
         self.read_exact(&mut buffer)?;
 
         if buffer[0] <= 128 {
             Ok(buffer[0])
         } else {
-            Err(anyhow!("Byte is not within the ASCII range"))
+            Err(ReadError::NotAscii(buffer[0]))
         }
     }
 
@@ -265,14 +595,14 @@ impl VMReader for File {
     ///
     /// This function will return an error if the byte read from the file is not
     /// within the ASCII range.
-    fn read(&mut self) -> Result<u8> {
+    fn read(&mut self) -> Result<u8, ReadError> {
         let mut buffer = [0u8; 1];
         self.read_exact(&mut buffer)?;
 
         if buffer[0] <= 128 {
             Ok(buffer[0])
         } else {
-            Err(anyhow!("Byte is not within the ASCII range"))
+            Err(ReadError::NotAscii(buffer[0]))
         }
     }
 
@@ -304,6 +634,20 @@ mod tests {
         assert_eq!(reader.get_vmreader_type(), VMReaderType::Unknown);
     }
 
+    #[test]
+    fn test_read_number() {
+        let mut reader = VecReader::new(b"\t007,0 300".to_vec());
+        assert_eq!(reader.read_number().unwrap(), Some(7));
+        assert_eq!(reader.read_number().unwrap(), Some(0));
+        assert!(reader.read_number().is_err());
+
+        let mut reader = VecReader::new(b"  x".to_vec());
+        assert!(reader.read_number().is_err());
+
+        let mut reader = VecReader::new(b"  ".to_vec());
+        assert_eq!(reader.read_number().unwrap(), None);
+    }
+
     #[test]
     fn test_read_from_stdin() {
         let mut stdin = Cursor::new("A".as_bytes());
@@ -350,4 +694,49 @@ mod tests {
 
         temp_file.close().unwrap();
     }
+
+    #[test]
+    fn test_terminal_reader() {
+        let reader = TerminalReader::new();
+        assert!(!reader.is_raw());
+        assert!(!reader.echoes());
+        assert_eq!(reader, TerminalReader::default());
+
+        let reader = reader.raw(true).echo(true);
+        assert!(reader.is_raw());
+        assert!(reader.echoes());
+        assert_eq!(reader.get_vmreader_type(), VMReaderType::Terminal);
+    }
+
+    #[test]
+    fn test_terminal_keys() {
+        let none = KeyModifiers::NONE;
+        let key = |code, modifiers| TerminalReader::key_byte(code, modifiers);
+
+        assert_eq!(key(KeyCode::Char('a'), none).unwrap().unwrap(), b'a');
+        assert_eq!(
+            key(KeyCode::Char('A'), KeyModifiers::SHIFT)
+                .unwrap()
+                .unwrap(),
+            b'A'
+        );
+        assert_eq!(key(KeyCode::Enter, none).unwrap().unwrap(), b'\n');
+        assert!(key(KeyCode::Char('é'), none).is_none());
+        assert!(key(KeyCode::Left, none).is_none());
+        assert!(key(KeyCode::Char('d'), KeyModifiers::CONTROL)
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_read_from_vec() {
+        let mut reader = VecReader::from(vec![65, 200, 66]);
+
+        assert_eq!(reader.read().unwrap(), 65);
+        assert!(reader.read().is_err());
+        assert_eq!(reader.remaining(), &[66]);
+        assert_eq!(reader.read().unwrap(), 66);
+        assert!(reader.read().is_err());
+        assert!(reader.remaining().is_empty());
+    }
 }
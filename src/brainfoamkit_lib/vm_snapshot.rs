@@ -0,0 +1,401 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::BTreeMap,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Counters,
+    LoopFrame,
+    TapeBackend,
+};
+
+/// The state of a `VirtualMachine` at one point of a run: its tape, memory
+/// pointer and counters, the loops it is in and the procedures it defined and
+/// is calling
+///
+/// Taken with
+/// [`VirtualMachine::snapshot`](crate::VirtualMachine::snapshot). Two
+/// snapshots are equal if the machines are in the same state, so a test can
+/// assert the whole state of a machine at once, and print the
+/// [`diff`](Self::diff) if it is not the expected one.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     Counters,
+///     Program,
+///     TapeBackend,
+///     VMSnapshot,
+///     VirtualMachine,
+/// };
+///
+/// let mut machine = VirtualMachine::builder()
+///     .input_device(std::io::stdin())
+///     .program(Program::from("++>+"))
+///     .tape_size(2)
+///     .build()
+///     .unwrap();
+/// machine.run().unwrap();
+///
+/// let expected = VMSnapshot::new(
+///     TapeBackend::new(vec![Byte::from(2), Byte::from(1)])
+///         .with_memory_pointer(1),
+///     Counters {
+///         program_counter: 4,
+///         steps:           4,
+///         bytes_written:   0,
+///     },
+/// );
+/// let differences = machine.snapshot().diff(&expected);
+///
+/// assert!(
+///     differences.is_empty(),
+///     "{}",
+///     differences
+///         .iter()
+///         .map(ToString::to_string)
+///         .collect::<Vec<_>>()
+///         .join("\n")
+/// );
+/// assert_eq!(machine.snapshot(), expected);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct VMSnapshot {
+    tape:       TapeBackend,
+    counters:   Counters,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    loop_stack: Vec<LoopFrame>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    procedures: BTreeMap<u8, usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    call_stack: Vec<usize>,
+}
+
+impl VMSnapshot {
+    /// Create a snapshot from a tape and counters, outside of any loop and
+    /// with no procedures
+    ///
+    /// # Arguments
+    ///
+    /// * `tape` - The cells and the position of the memory pointer
+    /// * `counters` - The program counter, steps and bytes written
+    #[must_use]
+    pub const fn new(tape: TapeBackend, counters: Counters) -> Self {
+        Self {
+            tape,
+            counters,
+            loop_stack: Vec::new(),
+            procedures: BTreeMap::new(),
+            call_stack: Vec::new(),
+        }
+    }
+
+    /// The snapshot inside the loops of `loop_stack`, innermost last
+    #[must_use]
+    pub fn with_loop_stack(mut self, loop_stack: Vec<LoopFrame>) -> Self {
+        self.loop_stack = loop_stack;
+        self
+    }
+
+    /// The snapshot with the `pbrain` procedures of `procedures`, by the
+    /// position of their `(`, and the active calls of `call_stack`, by the
+    /// position of their `:`
+    #[must_use]
+    pub fn with_procedures(
+        mut self,
+        procedures: BTreeMap<u8, usize>,
+        call_stack: Vec<usize>,
+    ) -> Self {
+        self.procedures = procedures;
+        self.call_stack = call_stack;
+        self
+    }
+
+    /// The cells of the machine and the position of the memory pointer
+    #[must_use]
+    pub const fn tape(&self) -> &TapeBackend {
+        &self.tape
+    }
+
+    /// The program counter, steps and bytes written of the machine
+    #[must_use]
+    pub const fn counters(&self) -> Counters {
+        self.counters
+    }
+
+    /// The loops the machine is in, innermost last
+    #[must_use]
+    pub fn loop_stack(&self) -> &[LoopFrame] {
+        &self.loop_stack
+    }
+
+    /// The position of the `(` of every procedure the machine defined
+    #[must_use]
+    pub const fn procedures(&self) -> &BTreeMap<u8, usize> {
+        &self.procedures
+    }
+
+    /// The positions of the `:` of the active procedure calls
+    #[must_use]
+    pub fn call_stack(&self) -> &[usize] {
+        &self.call_stack
+    }
+
+    /// Compare this snapshot with `other`
+    ///
+    /// Only the tapes and the counters are compared. The loops and procedures
+    /// only take part in `==`.
+    ///
+    /// # Returns
+    ///
+    /// Every way in which the snapshots differ, with the value of this
+    /// snapshot on the left and the value of `other` on the right. The list
+    /// is empty if the snapshots are equal.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<StateDifference> {
+        let mut differences = Vec::new();
+
+        if self.tape.len() != other.tape.len() {
+            differences.push(StateDifference::TapeLength {
+                left:  self.tape.len(),
+                right: other.tape.len(),
+            });
+        }
+        differences.extend(
+            self.tape
+                .cells()
+                .iter()
+                .zip(other.tape.cells())
+                .enumerate()
+                .filter(|(_, (left, right))| left != right)
+                .map(|(index, (left, right))| StateDifference::Cell {
+                    index,
+                    left: u8::from(left),
+                    right: u8::from(right),
+                }),
+        );
+
+        let pairs = [
+            (
+                self.tape.memory_pointer(),
+                other.tape.memory_pointer(),
+                MachineCounter::MemoryPointer,
+            ),
+            (
+                self.counters.program_counter,
+                other.counters.program_counter,
+                MachineCounter::ProgramCounter,
+            ),
+            (
+                self.counters.steps,
+                other.counters.steps,
+                MachineCounter::Steps,
+            ),
+            (
+                self.counters.bytes_written,
+                other.counters.bytes_written,
+                MachineCounter::BytesWritten,
+            ),
+        ];
+        differences.extend(
+            pairs
+                .into_iter()
+                .filter(|(left, right, _)| left != right)
+                .map(|(left, right, counter)| StateDifference::Counter {
+                    counter,
+                    left,
+                    right,
+                }),
+        );
+
+        differences
+    }
+}
+
+/// A position or counter of a `VirtualMachine`, as compared by
+/// [`VMSnapshot::diff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MachineCounter {
+    /// The position of the memory pointer on the tape
+    MemoryPointer,
+    /// The position of the next instruction to execute
+    ProgramCounter,
+    /// The number of instructions executed
+    Steps,
+    /// The number of bytes written to the output device
+    BytesWritten,
+}
+
+impl Display for MachineCounter {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::MemoryPointer => write!(f, "memory pointer"),
+            Self::ProgramCounter => write!(f, "program counter"),
+            Self::Steps => write!(f, "steps"),
+            Self::BytesWritten => write!(f, "bytes written"),
+        }
+    }
+}
+
+/// One way in which two [`VMSnapshot`]s differ
+///
+/// `left` is the value of the snapshot `diff` was called on and `right` the
+/// value of the snapshot it was compared with, as in `assert_eq!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateDifference {
+    /// The tapes have a different number of cells. The cells both tapes have
+    /// are still compared.
+    TapeLength {
+        /// The number of cells of the left tape
+        left:  usize,
+        /// The number of cells of the right tape
+        right: usize,
+    },
+    /// The cell at `index` holds different values
+    Cell {
+        /// The position of the cell on the tape
+        index: usize,
+        /// The value of the cell on the left tape
+        left:  u8,
+        /// The value of the cell on the right tape
+        right: u8,
+    },
+    /// A position or counter differs
+    Counter {
+        /// The position or counter that differs
+        counter: MachineCounter,
+        /// Its value on the left
+        left:    usize,
+        /// Its value on the right
+        right:   usize,
+    },
+}
+
+impl Display for StateDifference {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::TapeLength { left, right } => {
+                write!(f, "tape length: {left} != {right}")
+            }
+            Self::Cell { index, left, right } => write!(f, "cell {index}: {left} != {right}"),
+            Self::Counter {
+                counter,
+                left,
+                right,
+            } => write!(f, "{counter}: {left} != {right}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Byte;
+
+    fn snapshot(cells: &[u8], memory_pointer: usize, steps: usize) -> VMSnapshot {
+        VMSnapshot::new(
+            TapeBackend::new(cells.iter().copied().map(Byte::from).collect())
+                .with_memory_pointer(memory_pointer),
+            Counters {
+                program_counter: steps,
+                steps,
+                bytes_written: 0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_equal_snapshots() {
+        let left = snapshot(&[1, 2, 3], 1, 4);
+
+        assert_eq!(left, left.clone());
+        assert!(left.diff(&left.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let left = snapshot(&[1, 2, 3], 1, 4);
+        let right = snapshot(&[1, 5, 3, 0], 2, 6);
+        let differences = left.diff(&right);
+
+        assert_ne!(left, right);
+        assert_eq!(
+            differences,
+            [
+                StateDifference::TapeLength { left: 3, right: 4 },
+                StateDifference::Cell {
+                    index: 1,
+                    left:  2,
+                    right: 5,
+                },
+                StateDifference::Counter {
+                    counter: MachineCounter::MemoryPointer,
+                    left:    1,
+                    right:   2,
+                },
+                StateDifference::Counter {
+                    counter: MachineCounter::ProgramCounter,
+                    left:    4,
+                    right:   6,
+                },
+                StateDifference::Counter {
+                    counter: MachineCounter::Steps,
+                    left:    4,
+                    right:   6,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let differences = snapshot(&[1], 0, 0).diff(&snapshot(&[2, 0], 0, 1));
+        let lines: Vec<String> = differences.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            lines,
+            [
+                "tape length: 1 != 2",
+                "cell 0: 1 != 2",
+                "program counter: 0 != 1",
+                "steps: 0 != 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let snapshot = snapshot(&[0, 255, 7], 2, 9);
+        let json = serde_json::to_string(&snapshot).unwrap();
+
+        assert!(json.contains(r#""cells":[0,255,7]"#));
+        assert!(json.contains(r#""memory-pointer":2"#));
+        assert_eq!(serde_json::from_str::<VMSnapshot>(&json).unwrap(), snapshot);
+        assert!(!json.contains("loop-stack"));
+
+        let snapshot = snapshot
+            .with_loop_stack(vec![LoopFrame::new(3, 2)])
+            .with_procedures(BTreeMap::from([(1, 0)]), vec![5]);
+        let json = serde_json::to_string(&snapshot).unwrap();
+
+        assert!(json.contains(r#""loop-stack":[{"entry":3,"iterations":2}]"#));
+        assert_eq!(serde_json::from_str::<VMSnapshot>(&json).unwrap(), snapshot);
+    }
+}
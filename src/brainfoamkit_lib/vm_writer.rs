@@ -0,0 +1,845 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{
+        Result,
+        Stdout,
+        Write,
+    },
+    str,
+};
+
+use crate::Asciicast;
+
+/// Allowable types of `VMWriter`
+///
+/// This enum is used to determine the type of `VMWriter` that is being used.
+///
+/// The currently supported types are:
+///
+/// * Stdout - The standard output device as implemented by the [std::io::Stdout
+///   struct](https://doc.rust-lang.org/std/io/struct.Stdout.html)
+/// * File - A file as implemented by the [std::fs::File struct](https://doc.rust-lang.org/std/fs/struct.File.html)
+/// * Capture - An in-memory writer as implemented by the [`CaptureWriter`
+///   struct](struct.CaptureWriter.html)
+/// * Tcp - A network connection as implemented by the [`TcpWriter`
+///   struct](net/struct.TcpWriter.html)
+/// * Unknown - The default type of `VMWriter`
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     VMWriter,
+///     VMWriterType,
+/// };
+///
+/// let stdout = std::io::stdout();
+/// let capture = CaptureWriter::default();
+///
+/// assert_eq!(stdout.get_vmwriter_type(), VMWriterType::Stdout);
+/// assert_eq!(capture.get_vmwriter_type(), VMWriterType::Capture);
+/// ```
+///
+/// # See Also
+///
+/// * [`VMWriter`](trait.VMWriter.html)
+/// * [`CaptureWriter`](struct.CaptureWriter.html)
+#[derive(Debug, PartialEq, Eq)]
+pub enum VMWriterType {
+    /// The standard output device as implemented by the [std::io::Stdout struct](https://doc.rust-lang.org/std/io/struct.Stdout.html)
+    Stdout,
+    /// A file as implemented by the [std::fs::File struct](https://doc.rust-lang.org/std/fs/struct.File.html)
+    File,
+    /// An in-memory writer as implemented by the [`CaptureWriter`
+    /// struct](struct.CaptureWriter.html)
+    Capture,
+    /// A network connection as implemented by the [`TcpWriter`
+    /// struct](net/struct.TcpWriter.html)
+    Tcp,
+    /// The default type of `VMWriter`
+    Unknown,
+}
+
+/// The `VMWriter` trait
+///
+/// This trait is used to implement a `Writer` for the `VirtualMachine`. It is
+/// the output counterpart of the [`VMReader`](trait.VMReader.html) trait and
+/// allows us to abstract over several different types of `Writer`s, including
+/// `Stdout` and `File`. This trait is also implemented for the `CaptureWriter`
+/// struct, which is used for testing.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     VMWriter,
+/// };
+///
+/// let mut capture = CaptureWriter::default();
+/// capture.write(72).unwrap();
+/// capture.write(105).unwrap();
+///
+/// assert_eq!(capture.data, b"Hi");
+/// ```
+///
+/// # See Also
+///
+/// * [`VMWriterType`](enum.VMWriterType.html)
+/// * [`CaptureWriter`](struct.CaptureWriter.html)
+/// * [Stdout](https://doc.rust-lang.org/std/io/struct.Stdout.html)
+/// * [File](https://doc.rust-lang.org/std/fs/struct.File.html)
+pub trait VMWriter {
+    /// Write a single byte to the writer
+    ///
+    /// This function writes a single byte produced by the `VirtualMachine` to
+    /// the writer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte could not be written.
+    fn write(&mut self, _value: u8) -> Result<()> {
+        Ok(())
+    }
+
+    /// Flush any buffered output
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the output could not be flushed.
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Write a value as a decimal number followed by a newline
+    ///
+    /// This function is used by `.` in
+    /// [`IoMode::Numeric`](enum.IoMode.html#variant.Numeric).
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     VMWriter,
+    /// };
+    ///
+    /// let mut capture = CaptureWriter::default();
+    ///
+    /// assert_eq!(capture.write_number(42).unwrap(), 3);
+    /// assert_eq!(capture.data, b"42\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the number could not be written.
+    fn write_number(&mut self, value: u8) -> Result<usize> {
+        let text = format!("{value}\n");
+        for byte in text.bytes() {
+            self.write(byte)?;
+        }
+        Ok(text.len())
+    }
+
+    /// Finish the output once the program has stopped
+    ///
+    /// Writers that hold back part of the output until more of it arrives
+    /// write it out, as far as they can, and flush. By default, this only
+    /// flushes the writer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the output could not be written
+    /// or flushed.
+    fn finish(&mut self) -> Result<()> {
+        self.flush()
+    }
+
+    /// Get the type of the writer
+    ///
+    /// This function returns the type of the writer as a `VMWriterType` enum.
+    ///
+    /// The default type of `VMWriter` is `Unknown`, and is used when the type
+    /// of the writer is not set.
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        VMWriterType::Unknown
+    }
+
+    /// Flush the writer after every `interval` bytes
+    ///
+    /// Buffered writers such as `Stdout` may hold on to the output of a long
+    /// running program. The returned [`FlushEvery`] adapter flushes the
+    /// writer regularly, so that the output shows up while the program is
+    /// still running.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The number of bytes to write between flushes. An interval
+    ///   of zero is treated as one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     VMWriter,
+    ///     VMWriterType,
+    /// };
+    ///
+    /// let stdout = std::io::stdout().flush_every(4096);
+    ///
+    /// assert_eq!(stdout.get_vmwriter_type(), VMWriterType::Stdout);
+    /// ```
+    fn flush_every(self, interval: usize) -> FlushEvery<Self>
+    where
+        Self: Sized,
+    {
+        FlushEvery {
+            inner:    self,
+            interval: interval.max(1),
+            pending:  0,
+        }
+    }
+
+    /// Record everything written to the writer as an asciicast
+    ///
+    /// The returned [`RecordingWriter`] passes every byte on to this writer
+    /// and adds it to `cast` along with the time it was written, so that the
+    /// session can be replayed later.
+    ///
+    /// # Arguments
+    ///
+    /// * `cast` - The recording to add the output to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     Asciicast,
+    ///     CaptureWriter,
+    ///     VMWriter,
+    /// };
+    ///
+    /// let cast = Asciicast::new(Vec::new(), 80, 24).unwrap();
+    /// let mut writer = CaptureWriter::default().record(cast);
+    /// writer.write(b'A').unwrap();
+    ///
+    /// let (capture, cast) = writer.into_parts();
+    /// let recording = String::from_utf8(cast.into_inner()).unwrap();
+    ///
+    /// assert_eq!(capture.data, b"A");
+    /// assert!(recording.lines().nth(1).unwrap().ends_with(r#","o","A"]"#));
+    /// ```
+    fn record<O: Write>(self, cast: Asciicast<O>) -> RecordingWriter<Self, O>
+    where
+        Self: Sized,
+    {
+        RecordingWriter { inner: self, cast }
+    }
+
+    /// Decode the output as UTF-8 before passing it on
+    ///
+    /// Programs write one byte at a time, so a character that takes several
+    /// bytes in UTF-8 is written in several steps. The returned
+    /// [`Utf8DecodingWriter`] holds on to the bytes of a character until it is
+    /// complete, so that the writer only ever sees whole characters, and
+    /// replaces bytes that are not valid UTF-8 with `U+FFFD`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::{
+    ///     CaptureWriter,
+    ///     VMWriter,
+    /// };
+    ///
+    /// let mut writer = CaptureWriter::default().decode_utf8();
+    /// for value in "é!".bytes().chain([0xff]) {
+    ///     writer.write(value).unwrap();
+    /// }
+    ///
+    /// assert_eq!(writer.get_ref().as_string_lossy(), "é!\u{fffd}");
+    /// ```
+    fn decode_utf8(self) -> Utf8DecodingWriter<Self>
+    where
+        Self: Sized,
+    {
+        Utf8DecodingWriter {
+            inner:   self,
+            pending: Vec::with_capacity(4),
+        }
+    }
+}
+
+/// The implementation of the `VMWriter` trait for boxed writers, so that the
+/// writer of a `VirtualMachine` can be chosen at runtime
+impl<W: VMWriter + ?Sized> VMWriter for Box<W> {
+    fn write(&mut self, value: u8) -> Result<()> {
+        (**self).write(value)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        (**self).flush()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
+
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        (**self).get_vmwriter_type()
+    }
+}
+
+/// A `VMWriter` that flushes another writer at a fixed interval
+///
+/// Created with [`VMWriter::flush_every`].
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     VMWriter,
+/// };
+///
+/// let mut writer = CaptureWriter::default().flush_every(2);
+/// writer.write(72).unwrap();
+/// writer.write(105).unwrap();
+///
+/// assert_eq!(writer.pending(), 0);
+/// assert_eq!(writer.get_ref().data, b"Hi");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlushEvery<W: VMWriter> {
+    inner:    W,
+    interval: usize,
+    pending:  usize,
+}
+
+impl<W: VMWriter> FlushEvery<W> {
+    /// The wrapped writer
+    #[must_use]
+    pub const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The number of bytes written between flushes
+    #[must_use]
+    pub const fn interval(&self) -> usize {
+        self.interval
+    }
+
+    /// The number of bytes written since the last flush
+    #[must_use]
+    pub const fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Unwrap the writer without flushing it
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// The implementation of the `VMWriter` trait for the `FlushEvery` adapter
+impl<W: VMWriter> VMWriter for FlushEvery<W> {
+    /// Write a single byte and flush if the interval has been reached
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte could not be written or
+    /// the wrapped writer could not be flushed.
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.inner.write(value)?;
+        self.pending += 1;
+        if self.pending >= self.interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.pending = 0;
+        self.inner.flush()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.pending = 0;
+        self.inner.finish()
+    }
+
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        self.inner.get_vmwriter_type()
+    }
+}
+
+/// A `VMWriter` that records the output of another writer as an asciicast
+///
+/// Created with [`VMWriter::record`]. The recording mimics a terminal: a line
+/// feed is recorded as a carriage return followed by a line feed, and bytes
+/// above 127 are recorded as the Latin-1 character with the same value.
+#[derive(Debug)]
+pub struct RecordingWriter<W: VMWriter, O: Write> {
+    inner: W,
+    cast:  Asciicast<O>,
+}
+
+impl<W: VMWriter, O: Write> RecordingWriter<W, O> {
+    /// The wrapped writer
+    #[must_use]
+    pub const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Stop recording and return the wrapped writer and the recording
+    #[must_use]
+    pub fn into_parts(self) -> (W, Asciicast<O>) {
+        (self.inner, self.cast)
+    }
+}
+
+/// The implementation of the `VMWriter` trait for the `RecordingWriter`
+/// decorator
+impl<W: VMWriter, O: Write> VMWriter for RecordingWriter<W, O> {
+    /// Write a single byte and add it to the recording
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte could not be written or
+    /// recorded.
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.inner.write(value)?;
+        if value == b'\n' {
+            self.cast.output("\r\n")
+        } else {
+            self.cast.output(char::from(value).encode_utf8(&mut [0; 2]))
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()?;
+        self.cast.flush()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()?;
+        self.cast.flush()
+    }
+
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        self.inner.get_vmwriter_type()
+    }
+}
+
+/// A `VMWriter` that decodes the output of another writer as UTF-8
+///
+/// Created with [`VMWriter::decode_utf8`]. The bytes of a character are held
+/// back until the character is complete and then passed on together. Bytes
+/// that cannot be part of a valid character are replaced with the replacement
+/// character `U+FFFD`, in the same way as [`String::from_utf8_lossy`].
+///
+/// Flushing passes on the complete characters only, so that flushing in the
+/// middle of a character does not break it. The bytes of an incomplete
+/// character left when the program stops are replaced by
+/// [`finish`](VMWriter::finish).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Utf8DecodingWriter<W: VMWriter> {
+    inner:   W,
+    pending: Vec<u8>,
+}
+
+impl<W: VMWriter> Utf8DecodingWriter<W> {
+    /// The wrapped writer
+    #[must_use]
+    pub const fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The bytes of the incomplete character held back
+    #[must_use]
+    pub fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+
+    /// Unwrap the writer, dropping the bytes of an incomplete character
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn write_replacement(&mut self) -> Result<()> {
+        for value in char::REPLACEMENT_CHARACTER.encode_utf8(&mut [0; 3]).bytes() {
+            self.inner.write(value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The implementation of the `VMWriter` trait for the `Utf8DecodingWriter`
+/// decorator
+impl<W: VMWriter> VMWriter for Utf8DecodingWriter<W> {
+    /// Add a single byte to the current character and pass the character on
+    /// once it is complete
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the character could not be
+    /// written.
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.pending.push(value);
+        match str::from_utf8(&self.pending) {
+            Ok(_) => {
+                for value in self.pending.drain(..) {
+                    self.inner.write(value)?;
+                }
+                Ok(())
+            }
+            // The character is not complete yet
+            Err(error) if error.error_len().is_none() => Ok(()),
+            Err(_) if self.pending.len() == 1 => {
+                self.pending.clear();
+                self.write_replacement()
+            }
+            Err(_) => {
+                // The held back bytes are not the start of a character after
+                // all, but the new byte may well be
+                self.pending.clear();
+                self.write_replacement()?;
+                self.write(value)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+
+    /// Replace the bytes of an incomplete character and finish the wrapped
+    /// writer
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the replacement character could
+    /// not be written or the wrapped writer could not be finished.
+    fn finish(&mut self) -> Result<()> {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.write_replacement()?;
+        }
+        self.inner.finish()
+    }
+
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        self.inner.get_vmwriter_type()
+    }
+}
+
+/// The `CaptureWriter` struct
+///
+/// This struct is used to implement an in-memory `Writer` for the
+/// `VirtualMachine`. Every byte written is appended to `data`, which allows
+/// tests to inspect the output of a program.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     CaptureWriter,
+///     VMWriter,
+/// };
+///
+/// let mut capture = CaptureWriter::default();
+/// capture.write(65).unwrap();
+///
+/// assert_eq!(capture.data, vec![65]);
+/// ```
+///
+/// # See Also
+///
+/// * [`VMWriter`](trait.VMWriter.html)
+/// * [`VMWriterType`](enum.VMWriterType.html)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CaptureWriter {
+    pub data: Vec<u8>,
+}
+
+impl CaptureWriter {
+    /// The captured data as text
+    ///
+    /// Bytes that are not valid UTF-8 are replaced with `U+FFFD`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::CaptureWriter;
+    ///
+    /// let capture = CaptureWriter {
+    ///     data: vec![72, 105, 0xff],
+    /// };
+    ///
+    /// assert_eq!(capture.as_string_lossy(), "Hi\u{fffd}");
+    /// ```
+    #[must_use]
+    pub fn as_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.data)
+    }
+}
+
+/// The implementation of the `VMWriter` trait for the `CaptureWriter` struct
+impl VMWriter for CaptureWriter {
+    /// Append a single byte to the captured data
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.data.push(value);
+        Ok(())
+    }
+
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        VMWriterType::Capture
+    }
+}
+
+/// The implementation of the `VMWriter` trait for the `Stdout` struct
+impl VMWriter for Stdout {
+    /// Write a single byte to STDOUT
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte could not be written to
+    /// STDOUT.
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.write_all(&[value])?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Write::flush(self)?;
+        Ok(())
+    }
+
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        VMWriterType::Stdout
+    }
+}
+
+/// The implementation of the `VMWriter` trait for the `File` struct
+impl VMWriter for File {
+    /// Write a single byte to a file
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the byte could not be written to
+    /// the file.
+    fn write(&mut self, value: u8) -> Result<()> {
+        self.write_all(&[value])?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Write::flush(self)?;
+        Ok(())
+    }
+
+    fn get_vmwriter_type(&self) -> VMWriterType {
+        VMWriterType::File
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{
+        Read,
+        Seek,
+    };
+
+    use tempfile::tempfile;
+
+    use super::*;
+
+    struct DefaultWriter;
+
+    impl VMWriter for DefaultWriter {}
+
+    #[test]
+    fn test_default_trait() {
+        let mut writer = DefaultWriter;
+        assert!(writer.write(65).is_ok());
+        assert!(writer.flush().is_ok());
+        assert_eq!(writer.get_vmwriter_type(), VMWriterType::Unknown);
+    }
+
+    #[test]
+    fn test_write_to_capture() {
+        let mut capture = CaptureWriter::default();
+        capture.write(65).unwrap();
+        capture.write(66).unwrap();
+        capture.flush().unwrap();
+
+        assert_eq!(capture.data, b"AB");
+    }
+
+    #[test]
+    fn test_write_to_file() {
+        let mut file = tempfile().unwrap();
+        VMWriter::write(&mut file, 65).unwrap();
+        VMWriter::flush(&mut file).unwrap();
+
+        let mut contents = String::new();
+        file.rewind().unwrap();
+        file.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "A");
+    }
+
+    #[test]
+    fn test_get_vmwriter_type() {
+        let stdout = std::io::stdout();
+        let file = tempfile().unwrap();
+        let capture = CaptureWriter::default();
+        let default = DefaultWriter;
+
+        assert_eq!(stdout.get_vmwriter_type(), VMWriterType::Stdout);
+        assert_eq!(file.get_vmwriter_type(), VMWriterType::File);
+        assert_eq!(capture.get_vmwriter_type(), VMWriterType::Capture);
+        assert_eq!(default.get_vmwriter_type(), VMWriterType::Unknown);
+    }
+
+    #[derive(Default)]
+    struct CountingWriter {
+        data:    Vec<u8>,
+        flushes: usize,
+    }
+
+    impl VMWriter for CountingWriter {
+        fn write(&mut self, value: u8) -> Result<()> {
+            self.data.push(value);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_flush_every() {
+        let mut writer = CountingWriter::default().flush_every(3);
+        for value in 0..7 {
+            writer.write(value).unwrap();
+        }
+
+        assert_eq!(writer.interval(), 3);
+        assert_eq!(writer.pending(), 1);
+        assert_eq!(writer.get_ref().flushes, 2);
+
+        writer.flush().unwrap();
+        assert_eq!(writer.pending(), 0);
+
+        let inner = writer.into_inner();
+        assert_eq!(inner.flushes, 3);
+        assert_eq!(inner.data, vec![0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_record() {
+        let cast = Asciicast::new(Vec::new(), 80, 24).unwrap();
+        let mut writer = CountingWriter::default().record(cast);
+        for value in [b'H', b'\n', 233] {
+            writer.write(value).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(writer.get_ref().flushes, 1);
+        assert_eq!(writer.get_vmwriter_type(), VMWriterType::Unknown);
+
+        let (inner, cast) = writer.into_parts();
+        assert_eq!(inner.data, vec![b'H', b'\n', 233]);
+
+        let recording = String::from_utf8(cast.into_inner()).unwrap();
+        let events: Vec<serde_json::Value> = recording
+            .lines()
+            .skip(1)
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let data: Vec<&str> = events
+            .iter()
+            .map(|event| event[2].as_str().unwrap())
+            .collect();
+        assert_eq!(data, ["H", "\r\n", "é"]);
+    }
+
+    #[test]
+    fn test_boxed_writer() {
+        let mut writer: Box<dyn VMWriter> = Box::new(CaptureWriter::default());
+        writer.write(1).unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(writer.get_vmwriter_type(), VMWriterType::Capture);
+    }
+
+    #[test]
+    fn test_decode_utf8() {
+        let mut writer = CountingWriter::default().decode_utf8();
+        for value in "a€".bytes() {
+            writer.write(value).unwrap();
+        }
+        assert_eq!(writer.get_ref().data, "a€".as_bytes());
+
+        writer.write(0xe2).unwrap();
+        writer.write(0x82).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.pending(), [0xe2, 0x82]);
+        assert_eq!(writer.get_ref().flushes, 1);
+
+        writer.finish().unwrap();
+        assert!(writer.pending().is_empty());
+        assert_eq!(writer.get_ref().flushes, 2);
+        assert_eq!(writer.into_inner().data, "a€\u{fffd}".as_bytes());
+    }
+
+    #[test]
+    fn test_decode_invalid_utf8() {
+        let input = [b'a', 0x80, 0xe2, 0x82, b'b', 0xf0, 0x9f, 0x98, 0x80, 0xc0];
+        let mut writer = CaptureWriter::default().decode_utf8();
+        for value in input {
+            writer.write(value).unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            writer.get_ref().as_string_lossy(),
+            String::from_utf8_lossy(&input)
+        );
+    }
+
+    #[test]
+    fn test_finish() {
+        let mut writer = CountingWriter::default().flush_every(8);
+        writer.write(1).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(writer.pending(), 0);
+        assert_eq!(writer.get_ref().flushes, 1);
+    }
+
+    #[test]
+    fn test_flush_every_zero_interval() {
+        let mut writer = CountingWriter::default().flush_every(0);
+        writer.write(1).unwrap();
+
+        assert_eq!(writer.interval(), 1);
+        assert_eq!(writer.get_ref().flushes, 1);
+        assert_eq!(writer.get_vmwriter_type(), VMWriterType::Unknown);
+    }
+}
@@ -0,0 +1,162 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use core::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+
+use crate::Byte;
+
+/// The order of the bytes of a number spread over several cells
+///
+/// Brainfuck programs that need numbers larger than 255 keep them in
+/// consecutive cells. Whether the most significant byte comes first is up to
+/// the program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// The most significant byte is in the first cell
+    #[default]
+    Big,
+    /// The least significant byte is in the first cell
+    Little,
+}
+
+/// A Word is a 16-bit unsigned integer (u16) made of two Bytes.
+///
+/// A `Word` puts together the value of two consecutive cells, see
+/// [`VirtualMachine::read_u16_at`](crate::VirtualMachine::read_u16_at) to
+/// read one straight from the tape.
+///
+/// # Examples
+///
+/// ```
+/// use brainfoamkit_lib::{
+///     Byte,
+///     Endianness,
+///     Word,
+/// };
+///
+/// let cells = [Byte::from(0x12), Byte::from(0x34)];
+///
+/// assert_eq!(u16::from(&Word::from_bytes(cells, Endianness::Big)), 0x1234);
+/// assert_eq!(
+///     u16::from(&Word::from_bytes(cells, Endianness::Little)),
+///     0x3412
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Word {
+    high_byte: Byte,
+    low_byte:  Byte,
+}
+
+impl Word {
+    /// Create a new Word from its most and least significant Bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `high_byte` - The most significant Byte
+    /// * `low_byte` - The least significant Byte
+    #[must_use]
+    pub const fn new(high_byte: Byte, low_byte: Byte) -> Self {
+        Self {
+            high_byte,
+            low_byte,
+        }
+    }
+
+    /// Create a new Word from two Bytes in the given order
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The Bytes, in the order they have on the tape
+    /// * `endianness` - Which of them is the most significant
+    #[must_use]
+    pub const fn from_bytes(bytes: [Byte; 2], endianness: Endianness) -> Self {
+        match endianness {
+            Endianness::Big => Self::new(bytes[0], bytes[1]),
+            Endianness::Little => Self::new(bytes[1], bytes[0]),
+        }
+    }
+
+    /// The two Bytes of the Word in the given order
+    ///
+    /// This is the inverse of [`from_bytes()`](#method.from_bytes).
+    ///
+    /// # Arguments
+    ///
+    /// * `endianness` - Which Byte comes first
+    #[must_use]
+    pub const fn to_bytes(self, endianness: Endianness) -> [Byte; 2] {
+        match endianness {
+            Endianness::Big => [self.high_byte, self.low_byte],
+            Endianness::Little => [self.low_byte, self.high_byte],
+        }
+    }
+
+    /// The most significant Byte of the Word
+    #[must_use]
+    pub const fn get_high_byte(&self) -> Byte {
+        self.high_byte
+    }
+
+    /// The least significant Byte of the Word
+    #[must_use]
+    pub const fn get_low_byte(&self) -> Byte {
+        self.low_byte
+    }
+}
+
+impl Display for Word {
+    /// Converts the Word to a String.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use brainfoamkit_lib::Word;
+    ///
+    /// assert_eq!(Word::from(0xBEEF).to_string(), "0xBEEF");
+    /// ```
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let number = u16::from(self);
+        write!(f, "{number:#06X}")
+    }
+}
+
+impl From<u16> for Word {
+    fn from(number: u16) -> Self {
+        let [high, low] = number.to_be_bytes();
+        Self::new(Byte::from(high), Byte::from(low))
+    }
+}
+
+impl From<&Word> for u16 {
+    fn from(word: &Word) -> Self {
+        Self::from_be_bytes([u8::from(&word.high_byte), u8::from(&word.low_byte)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for number in [0, 1, 0x00FF, 0x0100, 0xABCD, u16::MAX] {
+            let word = Word::from(number);
+            assert_eq!(u16::from(&word), number);
+            for endianness in [Endianness::Big, Endianness::Little] {
+                assert_eq!(
+                    Word::from_bytes(word.to_bytes(endianness), endianness),
+                    word
+                );
+            }
+        }
+        assert_eq!(Word::from(0x0102).get_high_byte(), Byte::from(1));
+        assert_eq!(Word::from(0x0102).get_low_byte(), Byte::from(2));
+    }
+}
@@ -0,0 +1,804 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    sync::mpsc::{
+        Receiver,
+        Sender,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use anyhow::{
+    anyhow,
+    Result,
+};
+use brainfoamkit_lib::{
+    analysis,
+    BitView,
+    Byte,
+    CarryStep,
+    DebugSession,
+    Dialect,
+    Program,
+};
+
+use crate::{
+    editor::{
+        ProgramEditor,
+        BYTES_PER_ROW,
+    },
+    movie::Movie,
+    prompt::{
+        BookmarkTarget,
+        Prompt,
+    },
+    search::{
+        Search,
+        SearchMode,
+        SearchPrompt,
+    },
+    theme::Theme,
+    worker::{
+        self,
+        DebugCommand,
+        StateUpdate,
+    },
+};
+
+/// How long each bit flip of a changed cell is shown.
+const BIT_FLIP_INTERVAL: Duration = Duration::from_millis(120);
+
+/// The state of the visualizer.
+///
+/// The virtual machine runs on a worker thread, so that a compute-heavy
+/// program does not slow down the UI. The `App` sends it `DebugCommand`s and
+/// keeps the most recent `StateUpdate` for rendering, along with the source of
+/// the program and the flags that drive the application loop. If a `Movie` is
+/// being recorded, every new state is offered to it.
+///
+/// While a search or the name of a bookmark is typed, the `App` keeps the
+/// prompt. Once a search is submitted, the worker reports the matches on the
+/// tape and the tape view follows the current one instead of the memory
+/// pointer. Jumping to a bookmark moves the tape view or the program view in
+/// the same way. With a session file, the bookmarks and breakpoints are
+/// restored when the `App` starts and saved when it finishes.
+///
+/// When the cell under the memory pointer changes, the `App` animates the
+/// change one bit flip at a time, in the order a carry or a borrow ripples
+/// through the bits.
+///
+/// States can be saved by name with `:save NAME` and gone back to with
+/// `:restore NAME`, or just `:restore` for the one used last. The worker keeps
+/// them, dropping the ones used least recently once they take up too much
+/// memory.
+///
+/// While the program is edited, the `App` keeps the edited copy. Once the
+/// edit is applied, the worker goes back to the state saved to restart from,
+/// or to the initial state, and continues with the patched program.
+pub struct App {
+    source:       Vec<char>,
+    dialect:      Dialect,
+    commands:     Sender<DebugCommand>,
+    updates:      Receiver<StateUpdate>,
+    state:        StateUpdate,
+    heatmap:      bool,
+    pipeline:     bool,
+    themes:       Vec<Theme>,
+    theme:        usize,
+    should_quit:  bool,
+    movie:        Option<Movie>,
+    session:      Option<PathBuf>,
+    prompt:       Option<Prompt>,
+    search_mode:  SearchMode,
+    search:       Option<Search>,
+    tape_view:    Option<usize>,
+    program_view: Option<usize>,
+    bookmark:     usize,
+    slice:        Option<Slice>,
+    message:      Option<String>,
+    bit_flips:    Option<BitFlips>,
+    editor:       Option<ProgramEditor>,
+}
+
+/// The bits flipped by the latest change of a cell, revealed one at a time.
+pub struct BitFlips {
+    pub cell:    usize,
+    pub before:  Byte,
+    /// The value of the cell after each flip, the last one being its new
+    /// value.
+    pub frames:  Vec<Byte>,
+    /// The index of the bit flipped on the way to each frame.
+    pub flips:   Vec<u8>,
+    /// Whether an increment or a decrement carries on past each flip.
+    pub carries: Vec<bool>,
+    /// The number of frames shown so far.
+    pub shown:   usize,
+    since:       Instant,
+}
+
+impl BitFlips {
+    /// The value of the cell as far as the animation got.
+    pub fn current(&self) -> Byte {
+        self.shown
+            .checked_sub(1)
+            .and_then(|frame| self.frames.get(frame))
+            .copied()
+            .unwrap_or(self.before)
+    }
+
+    /// The bit flipped most recently, if any.
+    pub fn flipping(&self) -> Option<u8> {
+        self.shown
+            .checked_sub(1)
+            .and_then(|frame| self.flips.get(frame))
+            .copied()
+    }
+
+    /// Whether the carry, or the borrow, of the most recent flip moves on to
+    /// the next bit.
+    pub fn is_carrying(&self) -> bool {
+        self.shown
+            .checked_sub(1)
+            .and_then(|frame| self.carries.get(frame))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Whether there are flips left to show.
+    pub fn is_playing(&self) -> bool {
+        self.shown < self.frames.len()
+    }
+}
+
+/// How the worker shares its time between running the program and showing
+/// it.
+///
+/// By default a running program executes as fast as it can, and the UI is
+/// sent the state it has reached about once a frame. How far a program gets
+/// between two frames then depends on the instructions it happens to execute,
+/// so a loop-heavy program jumps ahead by thousands of steps in one frame and
+/// a handful in the next. With `steps_per_frame`, the worker executes exactly
+/// that many instructions per frame and waits for the next one, so the run
+/// moves on at an even pace. With `io_priority`, the worker sends its state as
+/// soon as the program writes output, so that the output pane is never a
+/// frame behind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AppConfig {
+    /// The number of instructions executed per frame, or `None` to run as
+    /// fast as possible
+    pub steps_per_frame: Option<usize>,
+    /// Whether pending output is shown before the frame is over
+    pub io_priority:     bool,
+}
+
+/// The instructions that can influence the final value of a cell, shown by
+/// dimming every other instruction.
+pub struct Slice {
+    pub cell:         usize,
+    pub instructions: BTreeSet<usize>,
+}
+
+impl App {
+    /// Create a new `App` for the given brainfuck source and start the worker
+    /// thread executing it. The execution is recorded to `movie`, if given.
+    /// The first of `themes` is used until the user switches to another one.
+    /// The bookmarks and breakpoints of the `session` file are restored, if
+    /// it exists. The worker paces the run as the `config` says.
+    pub fn new(
+        source: &str,
+        dialect: Dialect,
+        themes: Vec<Theme>,
+        movie: Option<Movie>,
+        speed: Option<u32>,
+        session: Option<PathBuf>,
+        config: AppConfig,
+    ) -> Result<Self> {
+        let restored = match &session {
+            Some(path) if path.exists() => DebugSession::load(path)?,
+            _ => DebugSession::default(),
+        };
+        let (commands, updates, state) = worker::spawn(source, dialect, speed, restored, config)?;
+
+        Ok(Self {
+            source: source.chars().collect(),
+            dialect,
+            commands,
+            updates,
+            state,
+            heatmap: false,
+            pipeline: false,
+            themes,
+            theme: 0,
+            should_quit: false,
+            movie,
+            session,
+            prompt: None,
+            search_mode: SearchMode::Bytes,
+            search: None,
+            tape_view: None,
+            program_view: None,
+            bookmark: 0,
+            slice: None,
+            message: None,
+            bit_flips: None,
+            editor: None,
+        })
+    }
+
+    /// The characters of the program source. Each character corresponds to
+    /// the instruction at the same position in the `Program`.
+    pub fn source(&self) -> &[char] {
+        &self.source
+    }
+
+    /// The most recent state of the virtual machine.
+    pub const fn state(&self) -> &StateUpdate {
+        &self.state
+    }
+
+    pub fn output(&self) -> &[u8] {
+        &self.state.output
+    }
+
+    pub fn status(&self) -> String {
+        if let Some(message) = &self.message {
+            message.clone()
+        } else if let Some(error) = &self.state.error {
+            format!("Error: {error}")
+        } else if self.state.halted {
+            String::from("Halted")
+        } else if self.state.running {
+            String::from("Running")
+        } else if let Some(breakpoint) = self.state.breakpoint {
+            format!("Breakpoint at {breakpoint}")
+        } else {
+            String::from("Paused")
+        }
+    }
+
+    pub const fn is_running(&self) -> bool {
+        self.state.running
+    }
+
+    /// The bit flips of the latest change to the cell under the memory
+    /// pointer, if it changed.
+    pub const fn bit_flips(&self) -> Option<&BitFlips> {
+        self.bit_flips.as_ref()
+    }
+
+    /// Whether bit flips are still being revealed.
+    pub fn is_animating(&self) -> bool {
+        self.bit_flips.as_ref().is_some_and(BitFlips::is_playing)
+    }
+
+    /// Whether tape cells are colored by how often they were accessed.
+    pub const fn shows_heatmap(&self) -> bool {
+        self.heatmap
+    }
+
+    pub fn toggle_heatmap(&mut self) {
+        self.heatmap = !self.heatmap;
+    }
+
+    /// Whether the pipeline pane is shown in place of the cell history.
+    pub const fn shows_pipeline(&self) -> bool {
+        self.pipeline
+    }
+
+    pub fn toggle_pipeline(&mut self) {
+        self.pipeline = !self.pipeline;
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.themes[self.theme]
+    }
+
+    /// Switch to the next theme, wrapping around after the last one.
+    pub fn next_theme(&mut self) {
+        self.theme = (self.theme + 1) % self.themes.len();
+    }
+
+    pub const fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    /// Start or stop continuous execution.
+    pub fn toggle_running(&mut self) -> Result<()> {
+        if self.state.running {
+            self.send(DebugCommand::Pause)
+        } else {
+            self.send(DebugCommand::Run)
+        }
+    }
+
+    /// Execute a single instruction.
+    pub fn step(&mut self) -> Result<()> {
+        self.send(DebugCommand::Step)
+    }
+
+    /// Pause whenever the program reaches the current instruction again.
+    pub fn set_breakpoint(&mut self) -> Result<()> {
+        self.send(DebugCommand::SetBreakpoint(self.state.program_counter))
+    }
+
+    /// Plot the value of the cell under the memory pointer from now on.
+    pub fn select_cell(&mut self) -> Result<()> {
+        self.send(DebugCommand::SelectCell(self.state.memory_pointer))
+    }
+
+    /// The backward slice being shown, if any.
+    pub const fn slice(&self) -> Option<&Slice> {
+        self.slice.as_ref()
+    }
+
+    /// Show the instructions that can influence the final value of the cell
+    /// plotted with 'c', or of the cell under the memory pointer, or stop
+    /// showing them.
+    pub fn toggle_slice(&mut self) {
+        if self.slice.take().is_some() {
+            return;
+        }
+        let cell = self
+            .state
+            .selected_cell
+            .unwrap_or(self.state.memory_pointer);
+        let source: String = self.source.iter().collect();
+        let instructions = analysis::backward_slice(&Program::parse(&source, self.dialect), cell);
+        self.slice = Some(Slice {
+            cell,
+            instructions: instructions.into_iter().collect(),
+        });
+    }
+
+    /// The text being typed in the status line, if any.
+    pub const fn prompt(&self) -> Option<&Prompt> {
+        self.prompt.as_ref()
+    }
+
+    /// The search whose matches are shown, if any.
+    pub const fn search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    /// The cell the tape view is centered on: the current match of the
+    /// search, the bookmark jumped to or the memory pointer.
+    pub fn tape_focus(&self) -> usize {
+        let matches = &self.state.search_matches;
+        self.search
+            .as_ref()
+            .and_then(|search| matches.get(search.current.min(matches.len().saturating_sub(1))))
+            .copied()
+            .or(self.tape_view)
+            .unwrap_or(self.state.memory_pointer)
+    }
+
+    /// The instruction the program view is centered on: the bookmark jumped
+    /// to or the current instruction.
+    pub fn program_focus(&self) -> usize {
+        self.program_view.unwrap_or(self.state.program_counter)
+    }
+
+    /// Start typing a search, in the mode of the previous one.
+    pub fn start_search(&mut self) {
+        self.prompt = Some(Prompt::Search(SearchPrompt::new(self.search_mode)));
+        self.message = None;
+    }
+
+    /// Start typing the name of a bookmark for the cell under the memory
+    /// pointer.
+    pub fn start_cell_bookmark(&mut self) {
+        self.start_bookmark(BookmarkTarget::Cell(self.state.memory_pointer));
+    }
+
+    /// Start typing the name of a bookmark for the current instruction.
+    pub fn start_instruction_bookmark(&mut self) {
+        self.start_bookmark(BookmarkTarget::Instruction(self.state.program_counter));
+    }
+
+    /// Start typing a command for the saved states.
+    pub fn start_command(&mut self) {
+        self.prompt = Some(Prompt::Command(String::new()));
+        self.message = None;
+    }
+
+    fn start_bookmark(&mut self, target: BookmarkTarget) {
+        self.prompt = Some(Prompt::Bookmark {
+            target,
+            name: String::new(),
+        });
+        self.message = None;
+    }
+
+    pub fn type_prompt(&mut self, character: char) {
+        if let Some(prompt) = &mut self.prompt {
+            prompt.text_mut().push(character);
+        }
+    }
+
+    pub fn delete_prompt(&mut self) {
+        if let Some(prompt) = &mut self.prompt {
+            prompt.text_mut().pop();
+        }
+    }
+
+    /// Switch the search being typed between values and ASCII text.
+    pub fn toggle_search_mode(&mut self) {
+        if let Some(Prompt::Search(prompt)) = &mut self.prompt {
+            prompt.mode = prompt.mode.toggled();
+            self.search_mode = prompt.mode;
+        }
+    }
+
+    /// Act on the text that was typed. A search that cannot be understood or
+    /// a bookmark without a name is reported in the status line instead.
+    pub fn submit_prompt(&mut self) -> Result<()> {
+        match self.prompt.take() {
+            None => Ok(()),
+            Some(Prompt::Search(prompt)) => self.submit_search(prompt),
+            Some(Prompt::Bookmark { target, name }) => {
+                let name = name.trim();
+                if name.is_empty() {
+                    self.message = Some(String::from("A bookmark needs a name"));
+                    return Ok(());
+                }
+                let name = name.to_string();
+                self.send(match target {
+                    BookmarkTarget::Cell(cell) => DebugCommand::BookmarkCell(name, cell),
+                    BookmarkTarget::Instruction(pc) => DebugCommand::BookmarkPc(name, pc),
+                })
+            }
+            Some(Prompt::Command(command)) => self.run_command(&command),
+        }
+    }
+
+    /// Save, restore or delete a state named by a single word. A command
+    /// that cannot be understood, or names no saved state, is reported in
+    /// the status line instead.
+    fn run_command(&mut self, command: &str) -> Result<()> {
+        let mut words = command.split_whitespace();
+        let (verb, name) = (words.next().unwrap_or_default(), words.next());
+        if words.next().is_some() {
+            self.message = Some(String::from("A saved state is named by a single word"));
+            return Ok(());
+        }
+        let saved = |name: &str| {
+            self.state
+                .snapshots
+                .iter()
+                .find(|(saved, _)| saved == name)
+                .map(|&(_, steps)| steps)
+        };
+
+        match (verb, name) {
+            ("save", Some(name)) => {
+                self.message = Some(format!(
+                    "Saved the state after {} steps as {name}",
+                    self.state.steps
+                ));
+                self.send(DebugCommand::SaveSnapshot(name.to_string()))
+            }
+            ("restore", name) => {
+                let Some(name) =
+                    name.or_else(|| self.state.snapshots.first().map(|(name, _)| name.as_str()))
+                else {
+                    self.message = Some(String::from("No state has been saved"));
+                    return Ok(());
+                };
+                let Some(steps) = saved(name) else {
+                    self.message = Some(format!("No state is saved as {name}"));
+                    return Ok(());
+                };
+                self.message = Some(format!("Went back to {name}, after {steps} steps"));
+                self.bit_flips = None;
+                let name = name.to_string();
+                self.send(DebugCommand::RestoreSnapshot(name))
+            }
+            ("delete", Some(name)) => {
+                if saved(name).is_none() {
+                    self.message = Some(format!("No state is saved as {name}"));
+                    return Ok(());
+                }
+                self.message = Some(format!("Deleted the state saved as {name}"));
+                self.send(DebugCommand::DeleteSnapshot(name.to_string()))
+            }
+            _ => {
+                self.message = Some(format!(
+                    "Unknown command ':{command}', expected save NAME, restore [NAME] or delete \
+                     NAME"
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn submit_search(&mut self, prompt: SearchPrompt) -> Result<()> {
+        match prompt.pattern() {
+            Ok(pattern) if pattern.is_empty() => self.clear_search(),
+            Ok(pattern) => {
+                self.search = Some(Search {
+                    query:   prompt.text,
+                    length:  pattern.len(),
+                    current: 0,
+                });
+                self.send(DebugCommand::Search(pattern))
+            }
+            Err(error) => {
+                self.message = Some(error.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Stop typing without acting on the text.
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None;
+    }
+
+    /// Forget the search and the bookmark jumped to, and return the views to
+    /// the memory pointer and the current instruction.
+    pub fn clear_search(&mut self) -> Result<()> {
+        self.message = None;
+        self.tape_view = None;
+        self.program_view = None;
+        if self.search.take().is_some() {
+            self.send(DebugCommand::Search(Vec::new()))?;
+        }
+        Ok(())
+    }
+
+    /// Move the tape view to the next match, or the previous one if
+    /// `forward` is false, wrapping around at either end.
+    pub fn next_match(&mut self, forward: bool) {
+        let matches = self.state.search_matches.len();
+        if let Some(search) = &mut self.search {
+            if matches > 0 {
+                search.current = if forward {
+                    (search.current + 1) % matches
+                } else {
+                    (search.current + matches - 1) % matches
+                };
+            }
+        }
+    }
+
+    /// The bookmarked cells followed by the bookmarked instructions, each
+    /// ordered by name.
+    pub fn bookmarks(&self) -> Vec<(&str, BookmarkTarget)> {
+        let bookmarks = &self.state.bookmarks;
+        bookmarks
+            .cells()
+            .map(|(name, cell)| (name, BookmarkTarget::Cell(cell)))
+            .chain(
+                bookmarks
+                    .instructions()
+                    .map(|(name, pc)| (name, BookmarkTarget::Instruction(pc))),
+            )
+            .collect()
+    }
+
+    /// The position of the selected bookmark in [`bookmarks`](Self::bookmarks).
+    pub fn selected_bookmark(&self) -> usize {
+        self.bookmark
+            .min(self.state.bookmarks.len().saturating_sub(1))
+    }
+
+    /// Select the next bookmark, or the previous one if `forward` is false,
+    /// wrapping around at either end.
+    pub fn next_bookmark(&mut self, forward: bool) {
+        let bookmarks = self.state.bookmarks.len();
+        if bookmarks > 0 {
+            let selected = self.selected_bookmark();
+            self.bookmark = if forward {
+                (selected + 1) % bookmarks
+            } else {
+                (selected + bookmarks - 1) % bookmarks
+            };
+        }
+    }
+
+    /// Move the tape view or the program view to the selected bookmark, in
+    /// place of any search.
+    pub fn jump_to_bookmark(&mut self) -> Result<()> {
+        let Some(&(_, target)) = self.bookmarks().get(self.selected_bookmark()) else {
+            return Ok(());
+        };
+        self.clear_search()?;
+        match target {
+            BookmarkTarget::Cell(cell) => self.tape_view = Some(cell),
+            BookmarkTarget::Instruction(pc) => self.program_view = Some(pc),
+        }
+        Ok(())
+    }
+
+    /// Advance the application by one frame, picking up the most recent state
+    /// sent by the worker thread.
+    pub fn tick(&mut self) -> Result<()> {
+        if let Some(state) = self.updates.try_iter().last() {
+            self.animate_cell_change(&state);
+            self.state = state;
+            if let Some(movie) = &mut self.movie {
+                movie.update();
+            }
+        }
+        if let Some(bit_flips) = &mut self.bit_flips {
+            if bit_flips.is_playing() && bit_flips.since.elapsed() >= BIT_FLIP_INTERVAL {
+                bit_flips.shown += 1;
+                bit_flips.since = Instant::now();
+            }
+        }
+        if let Some(movie) = &mut self.movie {
+            movie.capture(&self.source, &self.state)?;
+        }
+        Ok(())
+    }
+
+    /// Start animating the bits of the cell under the memory pointer if
+    /// `state` changed it. An increment or a decrement is shown as its carry
+    /// or borrow rippling through the bits, any other change as the bits that
+    /// differ flipping from the least significant up.
+    fn animate_cell_change(&mut self, state: &StateUpdate) {
+        let pointer = state.memory_pointer;
+        if pointer != self.state.memory_pointer {
+            self.bit_flips = None;
+            return;
+        }
+        let (Some(&before), Some(&after)) = (self.state.tape.get(pointer), state.tape.get(pointer))
+        else {
+            return;
+        };
+        if before == after {
+            return;
+        }
+        let (before_value, after_value) = (u8::from(&before), u8::from(&after));
+        let steps: Vec<CarryStep> = if after_value == before_value.wrapping_add(1) {
+            before.increment_steps().collect()
+        } else if after_value == before_value.wrapping_sub(1) {
+            before.decrement_steps().collect()
+        } else {
+            let view = BitView::new(before);
+            view.flips_to(after)
+                .into_iter()
+                .zip(view.ripple(after))
+                .map(|(bit, value)| CarryStep {
+                    bit,
+                    value,
+                    carry: false,
+                })
+                .collect()
+        };
+        self.bit_flips = Some(BitFlips {
+            cell: pointer,
+            before,
+            frames: steps.iter().map(|step| step.value).collect(),
+            flips: steps.iter().map(|step| step.bit).collect(),
+            carries: steps.iter().map(|step| step.carry).collect(),
+            shown: 0,
+            since: Instant::now(),
+        });
+    }
+
+    /// The program being edited, if any.
+    pub const fn editor(&self) -> Option<&ProgramEditor> {
+        self.editor.as_ref()
+    }
+
+    /// Start editing the program at the instruction in view, pausing it.
+    pub fn start_editing(&mut self) -> Result<()> {
+        if self.state.running {
+            self.send(DebugCommand::Pause)?;
+        }
+        self.editor = Some(ProgramEditor::new(&self.source, self.program_focus()));
+        self.message = None;
+        Ok(())
+    }
+
+    pub fn type_edit(&mut self, character: char) {
+        if let Some(editor) = &mut self.editor {
+            editor.insert(character);
+            self.message = None;
+        }
+    }
+
+    /// Remove the character before the cursor, or under it with `forward`.
+    pub fn delete_edit(&mut self, forward: bool) {
+        if let Some(editor) = &mut self.editor {
+            if forward {
+                editor.delete();
+            } else {
+                editor.backspace();
+            }
+            self.message = None;
+        }
+    }
+
+    /// Move the cursor of the editor forward or back by a character, or by a
+    /// row of the hex view with `row`.
+    pub fn move_edit_cursor(&mut self, forward: bool, row: bool) {
+        if let Some(editor) = &mut self.editor {
+            let distance = if row {
+                BYTES_PER_ROW
+            } else {
+                1
+            };
+            editor.move_by(distance, forward);
+        }
+    }
+
+    /// Patch the program with the edit and restart it from the saved state.
+    /// An edit that leaves the brackets unbalanced is reported in the status
+    /// line instead, and can be fixed before applying it again.
+    pub fn apply_edit(&mut self) -> Result<()> {
+        let Some(editor) = self.editor.take() else {
+            return Ok(());
+        };
+        if !editor.is_changed() {
+            return Ok(());
+        }
+        if let Err(error) = editor.validate(self.dialect) {
+            self.message = Some(format!("Unable to apply the edit: {error}"));
+            self.editor = Some(editor);
+            return Ok(());
+        }
+
+        let (range, replacement) = editor.change();
+        self.source = editor.text().to_vec();
+        self.slice = None;
+        self.program_view = None;
+        self.bit_flips = None;
+        self.message = Some(String::from(
+            "Patched the program and went back to the saved state",
+        ));
+        self.send(DebugCommand::PatchProgram(range, replacement))
+    }
+
+    /// Stop editing and forget the edit.
+    pub fn cancel_edit(&mut self) {
+        self.editor = None;
+        self.message = None;
+    }
+
+    /// Remember the current state, to go back to once the program is patched.
+    pub fn save_restart_point(&mut self) -> Result<()> {
+        self.message = Some(format!(
+            "Saved the state after {} steps to restart from",
+            self.state.steps
+        ));
+        self.send(DebugCommand::SaveRestartPoint)
+    }
+
+    /// Record the final frame of the movie, if one is being recorded, and
+    /// save the bookmarks and breakpoints to the session file, if there is
+    /// one.
+    pub fn finish(&mut self) -> Result<()> {
+        if let Some(movie) = &mut self.movie {
+            movie.finish(&self.source, &self.state)?;
+        }
+        if let Some(path) = &self.session {
+            let session = DebugSession {
+                bookmarks: self.state.bookmarks.clone(),
+                breakpoints: self.state.breakpoints.clone(),
+                ..DebugSession::default()
+            };
+            session.save(path)?;
+        }
+        Ok(())
+    }
+
+    fn send(&self, command: DebugCommand) -> Result<()> {
+        self.commands
+            .send(command)
+            .map_err(|_| anyhow!("the virtual machine thread stopped"))
+    }
+}
@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::ops::Range;
+
+use brainfoamkit_lib::{
+    Dialect,
+    ParseError,
+    Program,
+};
+
+/// The number of bytes on a row of the hex editor pane.
+pub const BYTES_PER_ROW: usize = 16;
+
+/// A copy of the program source being edited, with the position of the
+/// cursor. Nothing reaches the worker until the edit is applied.
+pub struct ProgramEditor {
+    original: Vec<char>,
+    text:     Vec<char>,
+    cursor:   usize,
+}
+
+impl ProgramEditor {
+    /// Start editing `source` with the cursor on the instruction at `cursor`.
+    pub fn new(source: &[char], cursor: usize) -> Self {
+        Self {
+            original: source.to_vec(),
+            text:     source.to_vec(),
+            cursor:   cursor.min(source.len()),
+        }
+    }
+
+    /// The edited source.
+    pub fn text(&self) -> &[char] {
+        &self.text
+    }
+
+    /// The position new instructions are inserted at.
+    pub const fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Insert `character` before the cursor and move past it.
+    pub fn insert(&mut self, character: char) {
+        self.text.insert(self.cursor, character);
+        self.cursor += 1;
+    }
+
+    /// Remove the character before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.text.remove(self.cursor);
+        }
+    }
+
+    /// Remove the character under the cursor.
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    /// Move the cursor `distance` characters forward, or back, stopping at
+    /// either end.
+    pub fn move_by(&mut self, distance: usize, forward: bool) {
+        self.cursor = if forward {
+            self.cursor.saturating_add(distance).min(self.text.len())
+        } else {
+            self.cursor.saturating_sub(distance)
+        };
+    }
+
+    /// Whether the source differs from the one the edit started from.
+    pub fn is_changed(&self) -> bool {
+        self.text != self.original
+    }
+
+    /// Check that the edited program can run, that is that its brackets are
+    /// balanced in `dialect`.
+    pub fn validate(&self, dialect: Dialect) -> Result<(), ParseError> {
+        let source: String = self.text.iter().collect();
+        Program::parse(&source, dialect).jump_table().map(|_| ())
+    }
+
+    /// The edit as a single replacement: the range of the original source
+    /// that changed, and the text that took its place. The characters both
+    /// sources start and end with are left out, so that the program counter
+    /// can follow the instructions that did not change.
+    pub fn change(&self) -> (Range<usize>, String) {
+        let prefix = self
+            .original
+            .iter()
+            .zip(&self.text)
+            .take_while(|(before, after)| before == after)
+            .count();
+        let suffix = self.original[prefix..]
+            .iter()
+            .rev()
+            .zip(self.text[prefix..].iter().rev())
+            .take_while(|(before, after)| before == after)
+            .count();
+        (
+            prefix..self.original.len() - suffix,
+            self.text[prefix..self.text.len() - suffix].iter().collect(),
+        )
+    }
+}
@@ -2,3 +2,81 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
+
+use std::time::Duration;
+
+use anyhow::{
+    Context,
+    Result,
+};
+use crossterm::event::{
+    self,
+    Event,
+    KeyCode,
+    KeyEventKind,
+};
+
+use crate::app::App;
+
+/// Handle any pending terminal events. The poll waits up to `timeout` so that
+/// a paused application does not spin, while a running one keeps animating.
+pub fn handle_events(app: &mut App, timeout: Duration) -> Result<()> {
+    if event::poll(timeout).context("event poll failed")? {
+        if let Event::Key(key) = event::read().context("event read failed")? {
+            if key.kind != KeyEventKind::Press {
+                return Ok(());
+            }
+            if app.editor().is_some() {
+                match key.code {
+                    KeyCode::Char(character) => app.type_edit(character),
+                    KeyCode::Backspace => app.delete_edit(false),
+                    KeyCode::Delete => app.delete_edit(true),
+                    KeyCode::Left => app.move_edit_cursor(false, false),
+                    KeyCode::Right => app.move_edit_cursor(true, false),
+                    KeyCode::Up => app.move_edit_cursor(false, true),
+                    KeyCode::Down => app.move_edit_cursor(true, true),
+                    KeyCode::Enter => app.apply_edit()?,
+                    KeyCode::Esc => app.cancel_edit(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            if app.prompt().is_some() {
+                match key.code {
+                    KeyCode::Char(character) => app.type_prompt(character),
+                    KeyCode::Backspace => app.delete_prompt(),
+                    KeyCode::Tab => app.toggle_search_mode(),
+                    KeyCode::Enter => app.submit_prompt()?,
+                    KeyCode::Esc => app.cancel_prompt(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            match key.code {
+                KeyCode::Char('q') => app.quit(),
+                KeyCode::Char('s' | ' ') => app.step()?,
+                KeyCode::Char('r') => app.toggle_running()?,
+                KeyCode::Char('b') => app.set_breakpoint()?,
+                KeyCode::Char('c') => app.select_cell()?,
+                KeyCode::Char('h') => app.toggle_heatmap(),
+                KeyCode::Char('p') => app.toggle_pipeline(),
+                KeyCode::Char('x') => app.toggle_slice(),
+                KeyCode::Char('t') => app.next_theme(),
+                KeyCode::Char('/') => app.start_search(),
+                KeyCode::Char('n') => app.next_match(true),
+                KeyCode::Char('N') => app.next_match(false),
+                KeyCode::Char('m') => app.start_cell_bookmark(),
+                KeyCode::Char('M') => app.start_instruction_bookmark(),
+                KeyCode::Char('j') => app.next_bookmark(true),
+                KeyCode::Char('k') => app.next_bookmark(false),
+                KeyCode::Char('e') => app.start_editing()?,
+                KeyCode::Char('S') => app.save_restart_point()?,
+                KeyCode::Char(':') => app.start_command(),
+                KeyCode::Enter => app.jump_to_bookmark()?,
+                KeyCode::Esc => app.clear_search()?,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
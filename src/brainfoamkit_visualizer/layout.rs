@@ -2,3 +2,676 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
+
+use brainfoamkit_lib::{
+    format::human_count,
+    Bit,
+    BitView,
+    CellStats,
+    Instruction,
+    PipelineSlot,
+    PipelineStage,
+};
+use ratatui::{
+    prelude::*,
+    symbols::Marker,
+    widgets::{
+        Axis,
+        Block,
+        Borders,
+        Chart,
+        Dataset,
+        GraphType,
+        List,
+        ListItem,
+        Paragraph,
+        Wrap,
+    },
+};
+
+use crate::{
+    app::App,
+    editor::{
+        ProgramEditor,
+        BYTES_PER_ROW,
+    },
+    prompt::BookmarkTarget,
+};
+
+/// Render the application. The screen is split into the program, the tape
+/// around the memory pointer, the stack of the `stack` dialect, an explanation
+/// of the next instruction next to the bits of the current cell, the trajectory
+/// of the memory pointer next to the history of the selected cell or the
+/// pipeline, the execution history, the open loops, the bookmarks, the captured
+/// output and a status line. While the program is edited, the hex editor takes
+/// the place of the history, the loops, the bookmarks and the output.
+pub fn render(frame: &mut Frame, app: &App) {
+    let stack_height = if app.state().auxiliary_tape.is_some() {
+        3
+    } else {
+        0
+    };
+    let [program_area, tape_area, stack_area, explanation_area, trajectory_area, main_area, status_area] =
+        Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(stack_height),
+            Constraint::Length(3),
+            Constraint::Length(10),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.size());
+    let [explanation_area, bits_area] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .areas(explanation_area);
+    let [trajectory_area, cell_history_area] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .areas(trajectory_area);
+    let [history_area, loops_area, bookmarks_area, output_area] = Layout::horizontal([
+        Constraint::Percentage(35),
+        Constraint::Percentage(15),
+        Constraint::Percentage(20),
+        Constraint::Percentage(30),
+    ])
+    .areas(main_area);
+
+    render_program(frame, app, program_area);
+    render_tape(frame, app, tape_area);
+    render_stack(frame, app, stack_area);
+    render_explanation(frame, app, explanation_area);
+    render_bits(frame, app, bits_area);
+    render_trajectory(frame, app, trajectory_area);
+    if app.shows_pipeline() {
+        render_pipeline(frame, app, cell_history_area);
+    } else {
+        render_cell_history(frame, app, cell_history_area);
+    }
+    if let Some(editor) = app.editor() {
+        render_editor(frame, app, editor, main_area);
+    } else {
+        render_history(frame, app, history_area);
+        render_loops(frame, app, loops_area);
+        render_bookmarks(frame, app, bookmarks_area);
+        render_output(frame, app, output_area);
+    }
+    render_status(frame, app, status_area);
+}
+
+/// Render a window of the program source, keeping the current instruction, or
+/// the bookmark jumped to, in view and the current instruction highlighted.
+/// While a slice is shown, the instructions outside of it are dimmed.
+fn render_program(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme();
+    let width = usize::from(area.width.saturating_sub(2));
+    let program_counter = app.state().program_counter;
+    let breakpoints = &app.state().breakpoints;
+    let start = app.program_focus().saturating_sub(width / 2);
+    let slice = app.slice();
+
+    let spans: Vec<Span> = app
+        .source()
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(width)
+        .map(|(index, character)| {
+            let character = if character.is_whitespace() {
+                ' '
+            } else {
+                *character
+            };
+            let span = Span::styled(
+                character.to_string(),
+                theme.instruction(Instruction::from_char(character)),
+            );
+            let span = if breakpoints.contains(&index) {
+                span.fg(theme.breakpoint).underlined()
+            } else {
+                span
+            };
+            let span = match slice {
+                Some(slice) if !slice.instructions.contains(&index) => span.dim(),
+                _ => span,
+            };
+            if index == program_counter {
+                span.patch_style(theme.highlighted())
+            } else {
+                span
+            }
+        })
+        .collect();
+
+    let title = match slice {
+        Some(slice) => format!("Program (slice of cell {})", slice.cell),
+        None => String::from("Program"),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+/// Render the cells around the memory pointer, highlighting the current cell.
+/// With the heatmap overlay enabled, cells are colored by how often the
+/// program accessed them. During a search, the view follows the current match
+/// instead and the cells of every match are shown in the breakpoint color.
+/// After a jump to a bookmark, the view shows the bookmarked cell.
+fn render_tape(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme();
+    let cells = usize::from(area.width.saturating_sub(2)) / 5;
+    let tape = &app.state().tape;
+    let heatmap: &[CellStats] = if app.shows_heatmap() {
+        &app.state().heatmap
+    } else {
+        &[]
+    };
+    let max_accesses = heatmap.iter().map(CellStats::accesses).max().unwrap_or(0);
+    let memory_pointer = app.state().memory_pointer;
+    let selected_cell = app.state().selected_cell;
+    let matches = &app.state().search_matches;
+    let match_length = app.search().map_or(0, |search| search.length);
+    let start = app
+        .tape_focus()
+        .saturating_sub(cells / 2)
+        .min(tape.len().saturating_sub(cells));
+
+    let spans: Vec<Span> = tape
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(cells)
+        .map(|(index, cell)| {
+            let mut span = Span::raw(format!(" {:>3} ", u8::from(cell)));
+            let accesses = heatmap.get(index).map_or(0, CellStats::accesses);
+            if let Some(color) = theme.heat(accesses, max_accesses) {
+                span = span.bg(color).fg(Color::Black);
+            }
+            if selected_cell == Some(index) {
+                span = span.underlined();
+            }
+            // The last match starting at or before the cell is the only one
+            // that can cover it
+            let matched = match matches.binary_search(&index) {
+                Ok(_) => true,
+                Err(position) => position > 0 && matches[position - 1] + match_length > index,
+            };
+            if matched {
+                span = span.fg(theme.breakpoint).bold();
+            }
+            if index == memory_pointer {
+                span.patch_style(theme.highlighted())
+            } else {
+                span
+            }
+        })
+        .collect();
+
+    let mut title = format!("Tape (pointer at {memory_pointer}");
+    if app.shows_heatmap() {
+        title.push_str(", heatmap");
+    }
+    if let Some(search) = app.search() {
+        if matches.is_empty() {
+            title.push_str(&format!(", no match for '{}'", search.query));
+        } else {
+            title.push_str(&format!(
+                ", match {} of {} for '{}'",
+                search.current.min(matches.len() - 1) + 1,
+                matches.len(),
+                search.query
+            ));
+        }
+    }
+    title.push(')');
+    let block = Block::default().borders(Borders::ALL).title(title);
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+/// Render the top of the stack of the `stack` dialect, with the top of the
+/// stack on the right, if the program has one.
+fn render_stack(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(stack) = &app.state().auxiliary_tape else {
+        return;
+    };
+    let cells = usize::from(area.width.saturating_sub(2)) / 5;
+    let start = stack.len().saturating_sub(cells);
+    let spans: Vec<Span> = stack
+        .iter()
+        .enumerate()
+        .skip(start)
+        .map(|(index, cell)| {
+            let span = Span::raw(format!(" {:>3} ", u8::from(cell)));
+            if index + 1 == stack.len() {
+                span.patch_style(app.theme().highlighted())
+            } else {
+                span
+            }
+        })
+        .collect();
+
+    let title = format!("Stack ({} cells)", stack.len());
+    let block = Block::default().borders(Borders::ALL).title(title);
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+/// Render a sentence describing what the next instruction is about to do.
+fn render_explanation(frame: &mut Frame, app: &App, area: Rect) {
+    let explanation = app
+        .state()
+        .explanation
+        .as_deref()
+        .unwrap_or("The program has finished");
+    let block = Block::default().borders(Borders::ALL).title("Next step");
+    frame.render_widget(
+        Paragraph::new(explanation)
+            .block(block)
+            .wrap(Wrap { trim: true }),
+        area,
+    );
+}
+
+/// Render the current cell as its two nybbles of four bits each, with its
+/// value in hex and decimal. After the cell changed, the bits flip one at a
+/// time, from the least significant up, the way a carry or a borrow ripples
+/// through them: the bit flipping is highlighted, those already flipped are
+/// bold and the title follows the carry.
+fn render_bits(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme();
+    let pointer = app.state().memory_pointer;
+    let bit_flips = app
+        .bit_flips()
+        .filter(|bit_flips| bit_flips.cell == pointer);
+    let byte = match bit_flips {
+        Some(bit_flips) => bit_flips.current(),
+        None => app.state().tape.get(pointer).copied().unwrap_or_default(),
+    };
+    let view = BitView::new(byte);
+
+    let mut spans = Vec::new();
+    for (position, bit) in view.bits().into_iter().enumerate() {
+        #[allow(clippy::cast_possible_truncation)]
+        let index = 7 - position as u8;
+        if position == 4 {
+            spans.push(Span::raw(" "));
+        }
+        let digit = if bit == Bit::One {
+            "1"
+        } else {
+            "0"
+        };
+        let span = Span::raw(digit);
+        let span = match bit_flips {
+            Some(bit_flips) if bit_flips.flipping() == Some(index) => {
+                span.patch_style(theme.highlighted())
+            }
+            Some(bit_flips) if bit_flips.flips[..bit_flips.shown].contains(&index) => span.bold(),
+            _ => span,
+        };
+        spans.push(span);
+    }
+    spans.push(Span::raw(format!(
+        "  = 0x{:X} 0x{:X} = {}",
+        u8::from(&view.high_nybble()),
+        u8::from(&view.low_nybble()),
+        u8::from(&byte)
+    )));
+
+    let title = match bit_flips {
+        Some(bit_flips) if bit_flips.is_playing() => {
+            let carry = match bit_flips.flipping() {
+                Some(7) if bit_flips.is_carrying() => String::from(", wrapping around"),
+                Some(bit) if bit_flips.is_carrying() => format!(", carrying into bit {}", bit + 1),
+                _ => String::new(),
+            };
+            format!(
+                "Cell {pointer}: {} -> {}{carry}",
+                u8::from(&bit_flips.before),
+                bit_flips.frames.last().map_or(0, u8::from)
+            )
+        }
+        _ => format!("Cell {pointer} bits"),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+/// Render the position of the memory pointer over the most recent steps, so
+/// that scans across the tape and the shape of loops stand out.
+fn render_trajectory(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme();
+    let trajectory = &app.state().trajectory;
+    let points: Vec<(f64, f64)> = trajectory
+        .iter()
+        .map(|&(step, pointer)| (step as f64, pointer as f64))
+        .collect();
+    let first = trajectory.first().map_or(0, |&(step, _)| step);
+    let last = trajectory
+        .last()
+        .map_or(0, |&(step, _)| step)
+        .max(first + 1);
+    let lowest = trajectory
+        .iter()
+        .map(|&(_, pointer)| pointer)
+        .min()
+        .unwrap_or(0);
+    let highest = trajectory
+        .iter()
+        .map(|&(_, pointer)| pointer)
+        .max()
+        .unwrap_or(0)
+        .max(lowest + 1);
+
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(theme.highlight))
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Pointer trajectory"),
+        )
+        .x_axis(
+            Axis::default()
+                .bounds([first as f64, last as f64])
+                .labels(vec![first.to_string().into(), last.to_string().into()]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([lowest as f64, highest as f64])
+                .labels(vec![lowest.to_string().into(), highest.to_string().into()]),
+        );
+    frame.render_widget(chart, area);
+}
+
+/// Render the value of the selected cell over the most recent steps, to follow
+/// counters and accumulators as the program changes them.
+fn render_cell_history(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(cell) = app.state().selected_cell else {
+        let block = Block::default().borders(Borders::ALL).title("Cell history");
+        frame.render_widget(
+            Paragraph::new("Press 'c' to plot the cell under the memory pointer")
+                .block(block)
+                .wrap(Wrap { trim: true }),
+            area,
+        );
+        return;
+    };
+
+    let theme = app.theme();
+    let history = &app.state().cell_history;
+    let points: Vec<(f64, f64)> = history
+        .iter()
+        .map(|&(step, value)| (step as f64, f64::from(value)))
+        .collect();
+    let first = history.first().map_or(0, |&(step, _)| step);
+    let last = history.last().map_or(0, |&(step, _)| step).max(first + 1);
+    let lowest = history.iter().map(|&(_, value)| value).min().unwrap_or(0);
+    let highest = history
+        .iter()
+        .map(|&(_, value)| value)
+        .max()
+        .unwrap_or(0)
+        .max(lowest.saturating_add(1));
+
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(theme.highlight))
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Cell {cell} history")),
+        )
+        .x_axis(
+            Axis::default()
+                .bounds([first as f64, last as f64])
+                .labels(vec![first.to_string().into(), last.to_string().into()]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([f64::from(lowest), f64::from(highest)])
+                .labels(vec![lowest.to_string().into(), highest.to_string().into()]),
+        );
+    frame.render_widget(chart, area);
+}
+
+/// Render the toy fetch, decode and execute pipeline over the most recent
+/// cycles, newest on the right. Each stage shows what was fetched as many
+/// cycles before as it is far from the fetch stage, bubbles left by the flush
+/// after a jump as dots.
+fn render_pipeline(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme();
+    let state = app.state();
+    let label_width = 8;
+    let cycles = usize::from(area.width.saturating_sub(2)).saturating_sub(label_width);
+    let fetches = &state.pipeline;
+    let start = fetches.len().saturating_sub(cycles);
+
+    let mut lines: Vec<Line> = PipelineStage::ALL
+        .iter()
+        .enumerate()
+        .map(|(depth, stage)| {
+            let mut spans = vec![Span::raw(format!("{:<label_width$}", stage.to_string()))];
+            spans.extend((start..fetches.len()).map(|cycle| {
+                match cycle.checked_sub(depth).map(|fetched| fetches[fetched]) {
+                    Some(PipelineSlot::Instruction(position)) => {
+                        let character = app.source().get(position).copied().unwrap_or(' ');
+                        Span::styled(
+                            character.to_string(),
+                            theme.instruction(Instruction::from_char(character)),
+                        )
+                    }
+                    Some(PipelineSlot::Bubble) => Span::raw("·").fg(theme.breakpoint),
+                    Some(PipelineSlot::Empty) | None => Span::raw(" "),
+                }
+            }));
+            Line::from(spans)
+        })
+        .collect();
+    let stats = &state.pipeline_stats;
+    lines.push(Line::raw(""));
+    lines.push(Line::raw(format!(
+        "{} cycles, {:.2} per instruction",
+        human_count(stats.cycles),
+        stats.cycles_per_instruction()
+    )));
+    lines.push(Line::raw(format!(
+        "{} stall cycles from {} flushes",
+        human_count(stats.stall_cycles),
+        human_count(stats.flushes)
+    )));
+    if let Some((jump, flushes)) = state.worst_jump {
+        let character = app.source().get(jump).copied().unwrap_or(' ');
+        lines.push(Line::raw(format!(
+            "Most flushes: {character} at {jump}, {} times",
+            human_count(flushes)
+        )));
+    }
+
+    let block = Block::default().borders(Borders::ALL).title("Pipeline");
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Render the most recently executed instructions, newest first.
+fn render_history(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .state()
+        .history
+        .iter()
+        .rev()
+        .map(|entry| {
+            ListItem::new(format!(
+                "{:>6}  {:<6}  ptr {:>5}  cell {:>3}",
+                entry.program_counter(),
+                entry.instruction().to_string(),
+                entry.memory_pointer(),
+                u8::from(&entry.cell()),
+            ))
+        })
+        .collect();
+
+    let block = Block::default().borders(Borders::ALL).title("History");
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+/// Render the loops the program is currently inside, innermost first.
+fn render_loops(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .state()
+        .loops
+        .iter()
+        .rev()
+        .map(|loop_frame| {
+            ListItem::new(format!(
+                "[ at {:>5}  x{}",
+                loop_frame.entry(),
+                loop_frame.iterations()
+            ))
+        })
+        .collect();
+
+    let block = Block::default().borders(Borders::ALL).title("Loops");
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+/// Render the bookmarked cells and instructions, marking the selected one.
+fn render_bookmarks(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme();
+    let selected = app.selected_bookmark();
+    let items: Vec<ListItem> = app
+        .bookmarks()
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, target))| {
+            let item = ListItem::new(match target {
+                BookmarkTarget::Cell(cell) => format!("{name}  cell {cell}"),
+                BookmarkTarget::Instruction(pc) => format!("{name}  at {pc}"),
+            });
+            if index == selected {
+                item.style(theme.highlighted())
+            } else {
+                item
+            }
+        })
+        .chain(
+            app.state()
+                .snapshots
+                .iter()
+                .map(|(name, steps)| ListItem::new(format!(":{name}  step {steps}")).italic()),
+        )
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Bookmarks and saved states");
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+/// Render the program being edited as rows of bytes, each with its offset,
+/// the bytes in hexadecimal and the characters they stand for, keeping the
+/// cursor in view and highlighted.
+fn render_editor(frame: &mut Frame, app: &App, editor: &ProgramEditor, area: Rect) {
+    let theme = app.theme();
+    let text = editor.text();
+    let rows = usize::from(area.height.saturating_sub(2)).max(1);
+    let cursor_row = editor.cursor() / BYTES_PER_ROW;
+    let first_row = cursor_row.saturating_sub(rows / 2);
+
+    let lines: Vec<Line> = (first_row..)
+        .take(rows)
+        .take_while(|row| row * BYTES_PER_ROW <= text.len())
+        .map(|row| {
+            let start = row * BYTES_PER_ROW;
+            let mut hex = vec![Span::raw(format!("{start:06x}  "))];
+            let mut characters = vec![Span::raw("  ")];
+            for position in start..start + BYTES_PER_ROW {
+                let character = text.get(position).copied();
+                let (byte, shown) = match character {
+                    Some(character) => (
+                        u8::try_from(character)
+                            .map_or_else(|_| String::from("··"), |byte| format!("{byte:02x}")),
+                        if character.is_control() {
+                            '.'
+                        } else {
+                            character
+                        },
+                    ),
+                    None => (String::from("  "), ' '),
+                };
+                let style = character.map_or_else(Style::default, |character| {
+                    theme.instruction(Instruction::from_char(character))
+                });
+                let (byte, shown) = if position == editor.cursor() {
+                    (
+                        Span::styled(byte, style).patch_style(theme.highlighted()),
+                        Span::styled(shown.to_string(), style).patch_style(theme.highlighted()),
+                    )
+                } else {
+                    (
+                        Span::styled(byte, style),
+                        Span::styled(shown.to_string(), style),
+                    )
+                };
+                hex.push(byte);
+                hex.push(Span::raw(" "));
+                characters.push(shown);
+            }
+            hex.extend(characters);
+            Line::from(hex)
+        })
+        .collect();
+
+    let title = format!(
+        "Edit program ({} of {} bytes{})",
+        editor.cursor(),
+        text.len(),
+        if editor.is_changed() {
+            ", changed"
+        } else {
+            ""
+        }
+    );
+    let block = Block::default().borders(Borders::ALL).title(title);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Render everything the program has written so far.
+fn render_output(frame: &mut Frame, app: &App, area: Rect) {
+    let output = String::from_utf8_lossy(app.output()).into_owned();
+    let block = Block::default().borders(Borders::ALL).title("Output");
+    frame.render_widget(
+        Paragraph::new(output)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Render the state of the application and the available keys.
+fn render_status(frame: &mut Frame, app: &App, area: Rect) {
+    if let Some(prompt) = app.prompt() {
+        frame.render_widget(Paragraph::new(prompt.status()), area);
+        return;
+    }
+    if app.editor().is_some() {
+        let status = format!(
+            "{} | type to insert  Backspace/Delete: remove  arrows: move  Enter: patch and \
+             restart  Esc: discard",
+            app.status()
+        );
+        frame.render_widget(Paragraph::new(status), area);
+        return;
+    }
+    let status = format!(
+        "{} after {} steps | s: step  r: run/pause  b: breakpoint  c: plot cell  /: search  m/M: \
+         bookmark  x: slice  e: edit  S: save restart  :: save/restore state  h: heatmap  p: \
+         pipeline  t: theme ({})  q: quit",
+        app.status(),
+        human_count(app.state().steps),
+        app.theme().name
+    );
+    frame.render_widget(Paragraph::new(status), area);
+}
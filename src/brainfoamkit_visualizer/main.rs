@@ -3,23 +3,163 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+mod app;
+mod editor;
+mod input_handling;
+mod layout;
+mod movie;
+mod prompt;
+mod search;
+mod theme;
 mod utilities;
+mod worker;
+
+use std::{
+    path::PathBuf,
+    time::Duration,
+};
+
 use anyhow::{
+    anyhow,
     Context,
     Result,
 };
+use brainfoamkit_lib::{
+    config::Config,
+    Dialect,
+};
+use clap::{
+    builder::RangedU64ValueParser,
+    Parser,
+};
+
+use crate::{
+    app::{
+        App,
+        AppConfig,
+    },
+    movie::Movie,
+    theme::Theme,
+};
 
-/// This is a bare minimum example. There are many approaches to running an
-/// application loop, so this is not meant to be prescriptive. It is only meant
-/// to demonstrate the basic setup and teardown of a terminal application.
+/// Step through a brainfuck program in the terminal
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// The brainfuck program to visualize
+    program:         PathBuf,
+    /// Record the execution to this file as an asciicast v2 movie
+    #[arg(long, value_name = "FILE")]
+    record:          Option<PathBuf>,
+    /// The minimum time between two frames of the movie, in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 100)]
+    frame_interval:  u64,
+    /// Load the colors from this TOML file instead of
+    /// `~/.config/brainfoamkit/theme.toml`
+    #[arg(long, value_name = "FILE")]
+    theme:           Option<PathBuf>,
+    /// Run at most this many instructions per second, so that the run can be
+    /// followed as it happens
+    #[arg(long, value_name = "IPS", value_parser = clap::value_parser!(u32).range(1..))]
+    speed:           Option<u32>,
+    /// Restore the bookmarks and breakpoints from this file, if it exists,
+    /// and save them to it when the visualizer quits
+    #[arg(long, value_name = "FILE")]
+    session:         Option<PathBuf>,
+    /// The dialect the program is written in. With `stack`, the stack of `@`
+    /// and `$` is shown below the tape.
+    #[arg(long, default_value_t = Dialect::Standard)]
+    dialect:         Dialect,
+    /// Execute exactly this many instructions per frame of a running
+    /// program, instead of as many as fit in it
+    #[arg(long, value_name = "STEPS", conflicts_with = "speed", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    steps_per_frame: Option<usize>,
+    /// Show the output of a running program as soon as it is written,
+    /// instead of with the next frame
+    #[arg(long)]
+    io_priority:     bool,
+}
+
+/// Step through a brainfuck program in the terminal.
+///
+/// The program is read from the file given as the first argument. The
+/// visualizer shows the program, the tape around the memory pointer, the most
+/// recently executed instructions and the output produced so far. Press 's' to
+/// execute a single instruction, 'r' to run or pause, 'b' to set a breakpoint
+/// on the current instruction, 'c' to plot the value of the cell under the
+/// memory pointer over the most recent steps, 't' to switch themes and 'q' to
+/// quit. Press '/' to search the tape for values, or for text after pressing
+/// Tab, then 'n' and 'N' to move the tape view between the matches and Esc to
+/// return it to the memory pointer.
+///
+/// Press 'm' to bookmark the cell under the memory pointer or 'M' to bookmark
+/// the current instruction, then 'j' and 'k' to select a bookmark and Enter to
+/// move the tape or program view to it. With `--session`, the bookmarks and
+/// breakpoints are kept from one run of the visualizer to the next. Press 'x'
+/// to dim every instruction that cannot influence the final value of the
+/// plotted cell, or of the cell under the memory pointer, and again to undim
+/// them.
 ///
-/// A more robust application would probably want to handle errors and ensure
-/// that the terminal is restored to a sane state before exiting. This example
-/// does not do that. It also does not handle events or update the application
-/// state. It just draws a greeting and exits when the user presses 'q'.
+/// Press 'e' to edit the program in a hex view of its bytes, inserting and
+/// deleting instructions, then Enter to patch the running program with the
+/// edit, or Esc to discard it. A patched program starts over from the state
+/// saved with 'S', or from the beginning.
+///
+/// Type ':save NAME' to save the state of the run under a name and
+/// ':restore NAME' to go back to it, or ':restore' for the state used last,
+/// and ':delete NAME' to forget it. The saved states are listed with the
+/// bookmarks.
+///
+/// The visualizer starts with the theme named by `theme` in the nearest
+/// `brainfoamkit.toml` or by `BRAINFOAMKIT_THEME`, unless a theme file is
+/// given with `--theme`. With `--speed`, a running program is slowed down to
+/// that many instructions per second. With `--steps-per-frame`, it moves on
+/// by the same number of instructions every frame, and with `--io-priority`
+/// its output is shown the moment it is written.
 fn main() -> Result<()> {
+    let args = Args::parse();
+    let source = std::fs::read_to_string(&args.program)
+        .with_context(|| format!("unable to read program from {}", args.program.display()))?;
+    let movie = args
+        .record
+        .map(|path| Movie::create(&path, Duration::from_millis(args.frame_interval)))
+        .transpose()?;
+    let themes = match &args.theme {
+        Some(path) => {
+            let mut themes = vec![Theme::load(path)?];
+            themes.extend(Theme::built_in());
+            themes
+        }
+        None => {
+            let mut themes = Theme::available()?;
+            if let Some(name) = Config::resolve()?.theme {
+                let index = themes
+                    .iter()
+                    .position(|theme| theme.name == name)
+                    .ok_or_else(|| anyhow!("unknown theme '{name}'"))?;
+                themes.rotate_left(index);
+            }
+            themes
+        }
+    };
+    let config = AppConfig {
+        steps_per_frame: args.steps_per_frame,
+        io_priority:     args.io_priority,
+    };
+    let mut app = App::new(
+        &source,
+        args.dialect,
+        themes,
+        movie,
+        args.speed,
+        args.session,
+        config,
+    )
+    .context("unable to load program")?;
+
     let mut terminal = utilities::setup_terminal().context("setup failed")?;
-    utilities::run(&mut terminal).context("app loop failed")?;
+    let result = utilities::run(&mut terminal, &mut app).context("app loop failed");
     utilities::restore_terminal(&mut terminal).context("restore terminal failed")?;
-    Ok(())
+    result?;
+    app.finish().context("unable to finish recording")
 }
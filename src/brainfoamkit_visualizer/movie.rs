@@ -0,0 +1,149 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::BufWriter,
+    path::Path,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use brainfoamkit_lib::Asciicast;
+
+use crate::worker::StateUpdate;
+
+/// The number of columns of a recorded frame.
+const WIDTH: u16 = 80;
+
+/// The number of rows of a recorded frame.
+const HEIGHT: u16 = 6;
+
+/// Clear the screen and move the cursor home before drawing a frame.
+const CLEAR: &str = "\u{1b}[2J\u{1b}[H";
+
+/// Highlight the current instruction and cell.
+const REVERSED: &str = "\u{1b}[7m";
+
+/// Reset the highlighting.
+const RESET: &str = "\u{1b}[0m";
+
+/// A recording of the execution of a program as an asciicast v2 "movie".
+///
+/// Every frame shows the program around the program counter, the tape around
+/// the memory pointer and the latest output. Frames are recorded at most once
+/// per `interval`, however quickly the virtual machine runs, so that the
+/// recording plays back at a watchable pace.
+pub struct Movie {
+    cast:       Asciicast<BufWriter<File>>,
+    interval:   Duration,
+    last_frame: Option<Instant>,
+    pending:    bool,
+}
+
+impl Movie {
+    /// Start recording to the file at `path`, recording a frame at most once
+    /// per `interval`.
+    pub fn create(path: &Path, interval: Duration) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("unable to create recording {}", path.display()))?;
+
+        Ok(Self {
+            cast: Asciicast::new(BufWriter::new(file), WIDTH, HEIGHT)?,
+            interval,
+            last_frame: None,
+            // The first frame shows the machine before it executes anything
+            pending: true,
+        })
+    }
+
+    /// Note that the state of the virtual machine changed since the last
+    /// frame.
+    pub fn update(&mut self) {
+        self.pending = true;
+    }
+
+    /// Record a frame of the latest state if it changed and the frame interval
+    /// has passed.
+    pub fn capture(&mut self, source: &[char], state: &StateUpdate) -> Result<()> {
+        let due = self
+            .last_frame
+            .map_or(true, |last_frame| last_frame.elapsed() >= self.interval);
+        if self.pending && due {
+            self.record(source, state)?;
+        }
+        Ok(())
+    }
+
+    /// Record the final state, if it was not recorded yet, and flush the
+    /// recording.
+    pub fn finish(&mut self, source: &[char], state: &StateUpdate) -> Result<()> {
+        if self.pending {
+            self.record(source, state)?;
+        }
+        Ok(self.cast.flush()?)
+    }
+
+    fn record(&mut self, source: &[char], state: &StateUpdate) -> Result<()> {
+        self.cast.output(&frame(source, state))?;
+        self.last_frame = Some(Instant::now());
+        self.pending = false;
+        Ok(())
+    }
+}
+
+/// Draw a frame of the program, the tape and the output as terminal text.
+fn frame(source: &[char], state: &StateUpdate) -> String {
+    let width = usize::from(WIDTH);
+    let mut frame = String::from(CLEAR);
+
+    let start = state.program_counter.saturating_sub(width / 2);
+    for (index, character) in source.iter().enumerate().skip(start).take(width) {
+        let character = if character.is_whitespace() {
+            ' '
+        } else {
+            *character
+        };
+        if index == state.program_counter {
+            let _ = write!(frame, "{REVERSED}{character}{RESET}");
+        } else {
+            frame.push(character);
+        }
+    }
+    frame.push_str("\r\n\r\n");
+
+    let cells = width / 5;
+    let start = state
+        .memory_pointer
+        .saturating_sub(cells / 2)
+        .min(state.tape.len().saturating_sub(cells));
+    for (index, cell) in state.tape.iter().enumerate().skip(start).take(cells) {
+        let value = u8::from(cell);
+        if index == state.memory_pointer {
+            let _ = write!(frame, "{REVERSED} {value:>3} {RESET}");
+        } else {
+            let _ = write!(frame, " {value:>3} ");
+        }
+    }
+    frame.push_str("\r\n\r\n");
+
+    let output = String::from_utf8_lossy(&state.output);
+    let last_line = output.lines().last().unwrap_or_default();
+    let _ = write!(
+        frame,
+        "step {:>10}  output: {}",
+        state.steps,
+        last_line.chars().take(width - 25).collect::<String>()
+    );
+
+    frame
+}
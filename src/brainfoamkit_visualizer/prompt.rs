@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::search::SearchPrompt;
+
+/// A position a bookmark can name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookmarkTarget {
+    /// A cell of the tape.
+    Cell(usize),
+    /// An instruction of the program.
+    Instruction(usize),
+}
+
+/// Text being typed in the status line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Prompt {
+    /// A search of the tape, typed after `/`.
+    Search(SearchPrompt),
+    /// The name of a new bookmark.
+    Bookmark {
+        target: BookmarkTarget,
+        name:   String,
+    },
+    /// A command for the saved states, typed after `:`.
+    Command(String),
+}
+
+impl Prompt {
+    /// The text typed so far.
+    pub fn text_mut(&mut self) -> &mut String {
+        match self {
+            Self::Search(prompt) => &mut prompt.text,
+            Self::Bookmark { name, .. } => name,
+            Self::Command(command) => command,
+        }
+    }
+
+    /// The status line shown while the prompt is typed.
+    pub fn status(&self) -> String {
+        match self {
+            Self::Search(prompt) => format!(
+                "/{} | searching {} (Tab: switch)  Enter: search  Esc: cancel",
+                prompt.text, prompt.mode
+            ),
+            Self::Bookmark {
+                target: BookmarkTarget::Cell(cell),
+                name,
+            } => format!("Bookmark cell {cell} as: {name} | Enter: save  Esc: cancel"),
+            Self::Bookmark {
+                target: BookmarkTarget::Instruction(pc),
+                name,
+            } => format!("Bookmark instruction {pc} as: {name} | Enter: save  Esc: cancel"),
+            Self::Command(command) => format!(
+                ":{command} | save NAME, restore [NAME] or delete NAME  Enter: run  Esc: cancel"
+            ),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::fmt;
+
+use anyhow::{
+    anyhow,
+    Result,
+};
+use brainfoamkit_lib::AsciiTable;
+
+/// How the text typed after `/` is turned into the values to look for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Values separated by spaces, in decimal or as `0x` followed by
+    /// hexadecimal digits, such as `72 0x69`.
+    Bytes,
+    /// Text whose ASCII characters are matched against the cells.
+    Ascii,
+}
+
+impl SearchMode {
+    /// The other mode.
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Bytes => Self::Ascii,
+            Self::Ascii => Self::Bytes,
+        }
+    }
+}
+
+impl fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes => write!(f, "bytes"),
+            Self::Ascii => write!(f, "ASCII"),
+        }
+    }
+}
+
+/// The search being typed after `/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPrompt {
+    pub text: String,
+    pub mode: SearchMode,
+}
+
+impl SearchPrompt {
+    /// Start typing a search in the given mode.
+    pub const fn new(mode: SearchMode) -> Self {
+        Self {
+            text: String::new(),
+            mode,
+        }
+    }
+
+    /// The values to look for on the tape.
+    ///
+    /// Fails if a value is not a number from 0 to 255, or if the text holds a
+    /// character that is not in the ASCII table.
+    pub fn pattern(&self) -> Result<Vec<u8>> {
+        match self.mode {
+            SearchMode::Bytes => self.text.split_whitespace().map(parse_byte).collect(),
+            SearchMode::Ascii => AsciiTable::new()
+                .encode(&self.text)
+                .ok_or_else(|| anyhow!("'{}' is not printable ASCII text", self.text)),
+        }
+    }
+}
+
+/// A search whose matches are shown in the tape view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    /// The text the search was typed as, for the title of the tape view.
+    pub query:   String,
+    /// The number of cells in a match.
+    pub length:  usize,
+    /// The match the tape view is centered on, counting from the first one.
+    pub current: usize,
+}
+
+fn parse_byte(value: &str) -> Result<u8> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(digits) => u8::from_str_radix(digits, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| anyhow!("'{value}' is not a value from 0 to 255"))
+}
@@ -0,0 +1,208 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    env,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use brainfoamkit_lib::Instruction;
+use ratatui::style::{
+    Color,
+    Style,
+    Stylize,
+};
+use serde::Deserialize;
+
+/// The colors used to draw the instructions of a program.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InstructionColors {
+    /// `>` and `<`
+    pub pointer: Color,
+    /// `+` and `-`
+    pub value:   Color,
+    /// `.` and `,`
+    pub io:      Color,
+    /// `[` and `]`
+    pub loops:   Color,
+    /// Everything that is not an instruction
+    pub comment: Color,
+}
+
+impl Default for InstructionColors {
+    fn default() -> Self {
+        Theme::dark().instructions
+    }
+}
+
+/// The colors of the visualizer.
+///
+/// A theme file is a TOML file with the same keys as this struct. Keys that
+/// are missing take their value from the dark theme:
+///
+/// ```toml
+/// name = "solarized"
+/// highlight = "#b58900"
+/// heatmap = ["blue", "green", "yellow", "red"]
+///
+/// [instructions]
+/// loops = "magenta"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    #[serde(default = "custom_name")]
+    pub name:           String,
+    pub instructions:   InstructionColors,
+    /// The background of the current instruction and the current cell
+    pub highlight:      Color,
+    /// The text of the current instruction and the current cell
+    pub highlight_text: Color,
+    pub breakpoint:     Color,
+    /// The background of tape cells, from the least to the most accessed
+    pub heatmap:        [Color; 4],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name:           String::from("dark"),
+            instructions:   InstructionColors {
+                pointer: Color::Cyan,
+                value:   Color::Green,
+                io:      Color::Magenta,
+                loops:   Color::Yellow,
+                comment: Color::DarkGray,
+            },
+            highlight:      Color::Yellow,
+            highlight_text: Color::Black,
+            breakpoint:     Color::Red,
+            heatmap:        [Color::Blue, Color::Green, Color::Yellow, Color::Red],
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name:           String::from("light"),
+            instructions:   InstructionColors {
+                pointer: Color::Blue,
+                value:   Color::Green,
+                io:      Color::Magenta,
+                loops:   Color::Red,
+                comment: Color::Gray,
+            },
+            highlight:      Color::Blue,
+            highlight_text: Color::White,
+            breakpoint:     Color::Red,
+            heatmap:        [
+                Color::LightBlue,
+                Color::LightGreen,
+                Color::LightYellow,
+                Color::LightRed,
+            ],
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            name:           String::from("high-contrast"),
+            instructions:   InstructionColors {
+                pointer: Color::White,
+                value:   Color::White,
+                io:      Color::LightYellow,
+                loops:   Color::LightCyan,
+                comment: Color::DarkGray,
+            },
+            highlight:      Color::White,
+            highlight_text: Color::Black,
+            breakpoint:     Color::LightRed,
+            heatmap:        [Color::Blue, Color::Green, Color::Yellow, Color::Red],
+        }
+    }
+
+    /// The built-in themes, in the order the theme key cycles through them.
+    pub fn built_in() -> Vec<Self> {
+        vec![Self::dark(), Self::light(), Self::high_contrast()]
+    }
+
+    /// Load a theme from a TOML file. A theme without a name is called
+    /// "custom".
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("unable to read theme from {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("invalid theme in {}", path.display()))
+    }
+
+    /// The themes available at runtime: the user's theme, if there is one,
+    /// followed by the built-in themes.
+    ///
+    /// The user's theme is read from `brainfoamkit/theme.toml` in the
+    /// configuration directory, `$XDG_CONFIG_HOME` or `~/.config`.
+    pub fn available() -> Result<Vec<Self>> {
+        let mut themes = Vec::new();
+        if let Some(path) = user_theme_path().filter(|path| path.exists()) {
+            themes.push(Self::load(&path)?);
+        }
+        themes.extend(Self::built_in());
+        Ok(themes)
+    }
+
+    /// The style of an instruction that is not highlighted.
+    pub fn instruction(&self, instruction: Instruction) -> Style {
+        let colors = &self.instructions;
+        let color = match instruction {
+            Instruction::IncrementPointer | Instruction::DecrementPointer => colors.pointer,
+            Instruction::IncrementValue | Instruction::DecrementValue => colors.value,
+            Instruction::OutputValue | Instruction::InputValue => colors.io,
+            Instruction::JumpForward | Instruction::JumpBackward => colors.loops,
+            _ => colors.comment,
+        };
+        Style::default().fg(color)
+    }
+
+    /// The style of the current instruction and the current cell.
+    pub fn highlighted(&self) -> Style {
+        Style::default()
+            .bg(self.highlight)
+            .fg(self.highlight_text)
+            .bold()
+    }
+
+    /// Pick a background color for a cell from its share of the accesses of
+    /// the most frequently accessed cell.
+    pub fn heat(&self, accesses: usize, max_accesses: usize) -> Option<Color> {
+        if accesses == 0 || max_accesses == 0 {
+            return None;
+        }
+        let level = (accesses * self.heatmap.len() / max_accesses).min(self.heatmap.len() - 1);
+        Some(self.heatmap[level])
+    }
+}
+
+fn custom_name() -> String {
+    String::from("custom")
+}
+
+fn user_theme_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("brainfoamkit").join("theme.toml"))
+}
@@ -16,11 +16,6 @@ use anyhow::{
     Result,
 };
 use crossterm::{
-    event::{
-        self,
-        Event,
-        KeyCode,
-    },
     execute,
     terminal::{
         disable_raw_mode,
@@ -29,9 +24,12 @@ use crossterm::{
         LeaveAlternateScreen,
     },
 };
-use ratatui::{
-    prelude::*,
-    widgets::Paragraph,
+use ratatui::prelude::*;
+
+use crate::{
+    app::App,
+    input_handling,
+    layout,
 };
 
 /// Setup the terminal. This is where you would enable raw mode, enter the
@@ -54,38 +52,21 @@ pub fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Re
     terminal.show_cursor().context("unable to show cursor")
 }
 
-/// Run the application loop. This is where you would handle events and update
-/// the application state. This example exits when the user presses 'q'. Other
-/// styles of application loops are possible, for example, you could have
-/// multiple application states and switch between them based on events, or you
-/// could have a single application state and update it based on events.
-pub fn run(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
-    loop {
-        terminal.draw(render_app)?;
-        if should_quit()? {
-            break;
-        }
+/// Run the application loop. Each iteration draws the application, handles
+/// any pending input and picks up the latest state of the virtual machine,
+/// which runs on its own thread. While paused, the event poll waits up to
+/// 250ms so that the loop does not spin; a running program, or the bit flips
+/// of a changed cell, are redrawn roughly 60 times a second.
+pub fn run(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) -> Result<()> {
+    while !app.should_quit() {
+        terminal.draw(|frame| layout::render(frame, app))?;
+        let timeout = if app.is_running() || app.is_animating() {
+            Duration::from_millis(16)
+        } else {
+            Duration::from_millis(250)
+        };
+        input_handling::handle_events(app, timeout)?;
+        app.tick()?;
     }
     Ok(())
 }
-
-/// Render the application. This is where you would draw the application UI.
-/// This example just draws a greeting.
-pub fn render_app(frame: &mut ratatui::Frame) {
-    let greeting = Paragraph::new("Hello World! (press 'q' to quit)");
-    frame.render_widget(greeting, frame.size());
-}
-
-/// Check if the user has pressed 'q'. This is where you would handle events.
-/// This example just checks if the user has pressed 'q' and returns true if
-/// they have. It does not handle any other events. There is a 250ms timeout on
-/// the event poll so that the application can exit in a timely manner, and to
-/// ensure that the terminal is rendered at least once every 250ms.
-pub fn should_quit() -> Result<bool> {
-    if event::poll(Duration::from_millis(250)).context("event poll failed")? {
-        if let Event::Key(key) = event::read().context("event read failed")? {
-            return Ok(KeyCode::Char('q') == key.code);
-        }
-    }
-    Ok(false)
-}
@@ -0,0 +1,528 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{
+        BTreeSet,
+        VecDeque,
+    },
+    io::Cursor,
+    ops::Range,
+    sync::mpsc::{
+        self,
+        Receiver,
+        RecvTimeoutError,
+        Sender,
+    },
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use anyhow::Result;
+use brainfoamkit_lib::{
+    explain,
+    AuxiliaryTape,
+    Bookmarks,
+    Byte,
+    CaptureWriter,
+    CellHistoryRecorder,
+    CellStats,
+    DebugSession,
+    Dialect,
+    HistoryEntry,
+    LoopFrame,
+    MockReader,
+    Pipeline,
+    PipelineSlot,
+    PipelineStats,
+    Program,
+    SnapshotStore,
+    VMSnapshot,
+    VirtualMachine,
+};
+
+use crate::app::AppConfig;
+
+/// The number of executed instructions shown in the history pane.
+const HISTORY_CAPACITY: usize = 64;
+
+/// The number of steps shown in the pointer trajectory pane.
+const TRAJECTORY_CAPACITY: usize = 512;
+
+/// The number of steps shown in the cell history pane.
+const CELL_HISTORY_CAPACITY: usize = 512;
+
+/// The number of cycles shown in the pipeline pane.
+const PIPELINE_CAPACITY: usize = 128;
+
+/// The number of instructions executed between checks for new commands while
+/// the program is running.
+const STEPS_PER_BATCH: usize = 10_000;
+
+/// The minimum time between two `StateUpdate`s of a running program. The UI
+/// redraws at roughly 60 frames per second, so sending more often is wasted.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(16);
+
+/// The virtual machine driven by the visualizer. Output is captured so that it
+/// can be shown in its own pane instead of corrupting the terminal.
+type Machine = VirtualMachine<MockReader, CaptureWriter>;
+
+/// A request from the UI to the worker thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugCommand {
+    /// Execute a single instruction and pause.
+    Step,
+    /// Execute instructions until the program halts, fails, hits a breakpoint
+    /// or is paused.
+    Run,
+    /// Stop running.
+    Pause,
+    /// Pause before the instruction at the given position is executed.
+    SetBreakpoint(usize),
+    /// Record the value of the cell at the given position from now on.
+    SelectCell(usize),
+    /// Look for these values on the tape, or stop looking if there are none.
+    Search(Vec<u8>),
+    /// Name the cell at the given position.
+    BookmarkCell(String, usize),
+    /// Name the instruction at the given position.
+    BookmarkPc(String, usize),
+    /// Remember the current state to go back to once the program is patched.
+    SaveRestartPoint,
+    /// Go back to the saved state and replace the instructions in the range,
+    /// of the program as it was then, with the source.
+    PatchProgram(Range<usize>, String),
+    /// Save the current state under a name.
+    SaveSnapshot(String),
+    /// Go back to the state saved under a name.
+    RestoreSnapshot(String),
+    /// Forget the state saved under a name.
+    DeleteSnapshot(String),
+}
+
+/// A snapshot of the virtual machine, sent from the worker thread to the UI.
+#[derive(Debug, Clone, Default)]
+pub struct StateUpdate {
+    pub program_counter: usize,
+    pub memory_pointer:  usize,
+    pub steps:           usize,
+    pub tape:            Vec<Byte>,
+    pub heatmap:         Vec<CellStats>,
+    pub history:         Vec<HistoryEntry>,
+    pub loops:           Vec<LoopFrame>,
+    /// The position of the memory pointer after each of the most recent
+    /// steps, as pairs of the step number and the position, oldest first.
+    pub trajectory:      Vec<(usize, usize)>,
+    /// The cell whose value is recorded, if one was selected.
+    pub selected_cell:   Option<usize>,
+    /// The value of the selected cell after each of the most recent steps,
+    /// as pairs of the step number and the value, oldest first.
+    pub cell_history:    Vec<(usize, u8)>,
+    /// What the pipeline fetched in each of the most recent cycles, oldest
+    /// first. A stage holds what was fetched as many cycles ago as it is far
+    /// from the fetch stage.
+    pub pipeline:        Vec<PipelineSlot>,
+    pub pipeline_stats:  PipelineStats,
+    /// The jump that flushed the pipeline most often and how often it did,
+    /// if any.
+    pub worst_jump:      Option<(usize, usize)>,
+    /// The first cell of every place on the tape that holds the values
+    /// searched for.
+    pub search_matches:  Vec<usize>,
+    /// The cells of the stack of the `stack` dialect, from the bottom to the
+    /// top, or `None` in the other dialects.
+    pub auxiliary_tape:  Option<Vec<Byte>>,
+    pub bookmarks:       Bookmarks,
+    /// The names of the saved states with their number of steps, the one
+    /// used most recently first.
+    pub snapshots:       Vec<(String, usize)>,
+    pub output:          Vec<u8>,
+    pub breakpoints:     BTreeSet<usize>,
+    pub running:         bool,
+    pub halted:          bool,
+    /// The breakpoint the program is paused at, if any.
+    pub breakpoint:      Option<usize>,
+    /// The error that stopped the program, if any.
+    pub error:           Option<String>,
+    /// A description of what the next instruction is about to do, unless the
+    /// program has halted.
+    pub explanation:     Option<String>,
+}
+
+/// Start a worker thread running the program in `source`.
+///
+/// The visualizer does not read from the terminal, so any `,` in the program
+/// leaves the current cell unchanged. With a `speed`, the program runs at
+/// most that many instructions per second. The bookmarks and breakpoints of
+/// the `session` are in place from the start, and the run is paced as the
+/// `config` says. A patched program restarts from the initial state until
+/// another one is saved. The worker stops once the returned `Sender` is
+/// dropped.
+///
+/// # Returns
+///
+/// A channel to send `DebugCommand`s to the worker, a channel receiving its
+/// `StateUpdate`s and the initial state of the machine.
+pub fn spawn(
+    source: &str,
+    dialect: Dialect,
+    speed: Option<u32>,
+    session: DebugSession,
+    config: AppConfig,
+) -> Result<(Sender<DebugCommand>, Receiver<StateUpdate>, StateUpdate)> {
+    let auxiliary_tape = (dialect == Dialect::Stack).then(AuxiliaryTape::new);
+    let builder = VirtualMachine::builder()
+        .input_device(MockReader {
+            data: Cursor::new(Vec::new()),
+        })
+        .output_device(CaptureWriter::default())
+        .program(Program::parse(source, dialect))
+        .history_capacity(HISTORY_CAPACITY)
+        .profiling(true);
+    let builder = match &auxiliary_tape {
+        Some(tape) => builder.auxiliary_tape(tape.clone()),
+        None => builder,
+    };
+    let mut machine = builder.build()?;
+    if let Some(speed) = speed {
+        machine.set_speed(speed);
+    }
+    machine.set_bookmarks(session.bookmarks);
+
+    let (command_sender, commands) = mpsc::channel();
+    let (update_sender, updates) = mpsc::channel();
+    let trajectory = VecDeque::from([(machine.steps(), machine.memory_pointer())]);
+    let restart_point = machine.snapshot();
+    let mut worker = Worker {
+        machine,
+        dialect,
+        restart_point,
+        snapshots: SnapshotStore::default(),
+        trajectory,
+        cell_history: None,
+        pipeline: Pipeline::new(),
+        fetches: VecDeque::new(),
+        auxiliary_tape,
+        search: Vec::new(),
+        breakpoints: session.breakpoints,
+        running: false,
+        breakpoint: None,
+        error: None,
+        config,
+        output_written: false,
+    };
+    let initial = worker.snapshot();
+    thread::spawn(move || worker.serve(&commands, &update_sender));
+
+    Ok((command_sender, updates, initial))
+}
+
+/// The state owned by the worker thread.
+struct Worker {
+    machine:        Machine,
+    dialect:        Dialect,
+    /// The state a patched program restarts from, taken on the program as it
+    /// is now
+    restart_point:  VMSnapshot,
+    /// The states saved by name, which only fit the program as it is now
+    snapshots:      SnapshotStore,
+    trajectory:     VecDeque<(usize, usize)>,
+    cell_history:   Option<CellHistoryRecorder>,
+    pipeline:       Pipeline,
+    fetches:        VecDeque<PipelineSlot>,
+    auxiliary_tape: Option<AuxiliaryTape>,
+    search:         Vec<u8>,
+    breakpoints:    BTreeSet<usize>,
+    running:        bool,
+    breakpoint:     Option<usize>,
+    error:          Option<String>,
+    config:         AppConfig,
+    /// Whether the latest batch stopped because the program wrote output
+    output_written: bool,
+}
+
+impl Worker {
+    /// Handle commands until the UI hangs up. While paused, the worker blocks
+    /// on the command channel. While running, it executes batches of
+    /// instructions and checks for commands in between. With a number of
+    /// steps per frame, it waits for the next frame between two batches.
+    fn serve(&mut self, commands: &Receiver<DebugCommand>, updates: &Sender<StateUpdate>) {
+        let mut last_update = Instant::now();
+        loop {
+            let command = if self.running {
+                let wait = if self.is_paced() {
+                    UPDATE_INTERVAL.saturating_sub(last_update.elapsed())
+                } else {
+                    Duration::ZERO
+                };
+                match commands.recv_timeout(wait) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            } else {
+                match commands.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => return,
+                }
+            };
+
+            if let Some(command) = command {
+                self.handle(command);
+            }
+            // A paced machine runs its batch once the frame is over, not
+            // whenever a command happens to arrive
+            let frame_over = last_update.elapsed() >= UPDATE_INTERVAL;
+            if self.running && (frame_over || !self.is_paced()) {
+                self.run_batch();
+            }
+
+            // A paused machine only changes in response to a command, so its
+            // state is always sent. A running one is throttled to the frame
+            // rate of the UI, unless its output goes first.
+            if !self.running
+                || last_update.elapsed() >= UPDATE_INTERVAL
+                || std::mem::take(&mut self.output_written)
+            {
+                if updates.send(self.snapshot()).is_err() {
+                    return;
+                }
+                last_update = Instant::now();
+            }
+        }
+    }
+
+    fn handle(&mut self, command: DebugCommand) {
+        match command {
+            DebugCommand::Step => {
+                self.running = false;
+                self.breakpoint = None;
+                self.execute();
+            }
+            DebugCommand::Run => {
+                self.running = !self.machine.is_halted() && self.error.is_none();
+                // Resuming at a breakpoint must not stop at it again
+                if self.running && self.breakpoint.take().is_some() {
+                    self.execute();
+                }
+            }
+            DebugCommand::Pause => self.running = false,
+            DebugCommand::SetBreakpoint(position) => {
+                self.breakpoints.insert(position);
+            }
+            DebugCommand::SelectCell(cell) => {
+                let recorder = self
+                    .cell_history
+                    .get_or_insert_with(|| CellHistoryRecorder::new(cell, CELL_HISTORY_CAPACITY));
+                recorder.select(cell);
+                recorder.record(&self.machine);
+            }
+            DebugCommand::Search(pattern) => self.search = pattern,
+            DebugCommand::BookmarkCell(name, cell) => self.machine.bookmark_cell(name, cell),
+            DebugCommand::BookmarkPc(name, pc) => self.machine.bookmark_pc(name, pc),
+            DebugCommand::SaveRestartPoint => self.restart_point = self.machine.snapshot(),
+            DebugCommand::PatchProgram(range, replacement) => self.patch(range, &replacement),
+            DebugCommand::SaveSnapshot(name) => {
+                self.snapshots.save(name, self.machine.snapshot());
+            }
+            DebugCommand::RestoreSnapshot(name) => {
+                if let Some(snapshot) = self.snapshots.get(&name).cloned() {
+                    self.running = false;
+                    self.breakpoint = None;
+                    match self.machine.restore(&snapshot) {
+                        Ok(()) => {
+                            self.error = None;
+                            self.start_over();
+                        }
+                        Err(error) => self.error = Some(error.to_string()),
+                    }
+                }
+            }
+            DebugCommand::DeleteSnapshot(name) => {
+                self.snapshots.remove(&name);
+            }
+        }
+    }
+
+    /// Go back to the restart point and splice the replacement into the
+    /// program. The restart point was taken on the unpatched program, so the
+    /// machine goes back to it first and the splice then moves its program
+    /// counter along with the instructions. The output, the trajectory, the
+    /// pipeline and the plotted cell start over from the restart point, while
+    /// the stack of the `stack` dialect is kept as it is. The states saved by
+    /// name are forgotten, since their program counters are those of the
+    /// unpatched program.
+    fn patch(&mut self, range: Range<usize>, replacement: &str) {
+        self.running = false;
+        self.breakpoint = None;
+        let replacement = Program::parse(replacement, self.dialect);
+        let result = self
+            .machine
+            .restore(&self.restart_point)
+            .map_err(|error| error.to_string())
+            .and_then(|()| {
+                self.machine
+                    .splice_program(range.clone(), &replacement)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            self.error = Some(error);
+            return;
+        }
+        self.error = None;
+        self.restart_point = self.machine.snapshot();
+        self.snapshots.clear();
+        self.start_over();
+
+        let inserted = replacement.length().unwrap_or(0);
+        self.breakpoints = self
+            .breakpoints
+            .iter()
+            .filter_map(|&position| {
+                if position <= range.start {
+                    Some(position)
+                } else if position < range.end {
+                    None
+                } else {
+                    Some(position - range.len() + inserted)
+                }
+            })
+            .collect();
+    }
+
+    /// Forget the output, the trajectory, the pipeline and the plotted values
+    /// that came after the state the machine was just restored to.
+    fn start_over(&mut self) {
+        let written = self.machine.bytes_written();
+        self.machine.output_device().data.truncate(written);
+        self.trajectory.clear();
+        self.trajectory
+            .push_back((self.machine.steps(), self.machine.memory_pointer()));
+        self.pipeline = Pipeline::new();
+        self.fetches.clear();
+        if let Some(recorder) = &mut self.cell_history {
+            *recorder = CellHistoryRecorder::new(recorder.cell(), CELL_HISTORY_CAPACITY);
+            recorder.record(&self.machine);
+        }
+    }
+
+    fn run_batch(&mut self) {
+        // A throttled machine waits between instructions, so it checks for
+        // commands after every one of them to stay responsive
+        let batch = if self.machine.speed().is_some() {
+            1
+        } else {
+            self.config.steps_per_frame.unwrap_or(STEPS_PER_BATCH)
+        };
+        let written = self.machine.output_device().data.len();
+        for _ in 0..batch {
+            if !self.running {
+                return;
+            }
+            let program_counter = self.machine.program_counter();
+            if self.breakpoints.contains(&program_counter) {
+                self.running = false;
+                self.breakpoint = Some(program_counter);
+                return;
+            }
+            self.execute();
+            if self.config.io_priority && self.machine.output_device().data.len() > written {
+                self.output_written = true;
+                return;
+            }
+        }
+    }
+
+    /// Whether the run moves on by a fixed number of steps per frame.
+    fn is_paced(&self) -> bool {
+        self.config.steps_per_frame.is_some() && self.machine.speed().is_none()
+    }
+
+    fn execute(&mut self) {
+        if self.error.is_some() {
+            self.running = false;
+            return;
+        }
+        let program_counter = self.machine.program_counter();
+        if let Err(error) = self.machine.execute_instruction() {
+            self.running = false;
+            self.error = Some(error.to_string());
+        } else {
+            self.issue(program_counter);
+            if self.trajectory.len() == TRAJECTORY_CAPACITY {
+                self.trajectory.pop_front();
+            }
+            self.trajectory
+                .push_back((self.machine.steps(), self.machine.memory_pointer()));
+            if let Some(recorder) = &mut self.cell_history {
+                recorder.record(&self.machine);
+            }
+        }
+        if self.machine.is_halted() {
+            self.running = false;
+        }
+    }
+
+    /// Feed the instruction just executed to the pipeline, recording the
+    /// bubbles of a flush before it.
+    fn issue(&mut self, program_counter: usize) {
+        let cycles = self.pipeline.issue(program_counter);
+        let bubbles = std::iter::repeat(PipelineSlot::Bubble).take(cycles - 1);
+        for fetched in bubbles.chain([PipelineSlot::Instruction(program_counter)]) {
+            if self.fetches.len() == PIPELINE_CAPACITY {
+                self.fetches.pop_front();
+            }
+            self.fetches.push_back(fetched);
+        }
+    }
+
+    fn snapshot(&mut self) -> StateUpdate {
+        StateUpdate {
+            program_counter: self.machine.program_counter(),
+            memory_pointer:  self.machine.memory_pointer(),
+            steps:           self.machine.steps(),
+            tape:            self.machine.tape().to_vec(),
+            heatmap:         self.machine.heatmap(),
+            history:         self.machine.history().iter().copied().collect(),
+            loops:           self.machine.loop_stack().to_vec(),
+            trajectory:      self.trajectory.iter().copied().collect(),
+            selected_cell:   self.cell_history.as_ref().map(CellHistoryRecorder::cell),
+            cell_history:    self
+                .cell_history
+                .as_ref()
+                .map(|recorder| recorder.samples().collect())
+                .unwrap_or_default(),
+            pipeline:        self.fetches.iter().copied().collect(),
+            pipeline_stats:  self.pipeline.stats(),
+            worst_jump:      self
+                .pipeline
+                .jump_flushes()
+                .iter()
+                .max_by_key(|(_, flushes)| **flushes)
+                .map(|(jump, flushes)| (*jump, *flushes)),
+            search_matches:  self.machine.find_in_tape(&self.search),
+            auxiliary_tape:  self.auxiliary_tape.as_ref().map(AuxiliaryTape::cells),
+            bookmarks:       self.machine.bookmarks().clone(),
+            snapshots:       self
+                .snapshots
+                .iter()
+                .map(|(name, snapshot)| (name.to_string(), snapshot.counters().steps))
+                .collect(),
+            output:          self.machine.output_device().data.clone(),
+            breakpoints:     self.breakpoints.clone(),
+            running:         self.running,
+            halted:          self.machine.is_halted(),
+            breakpoint:      self.breakpoint,
+            error:           self.error.clone(),
+            explanation:     self
+                .machine
+                .get_instruction()
+                .map(|instruction| explain::explain_step(instruction, &self.machine)),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-FileCopyrightText: 2023 - 2024 Ali Sajid Imami
+//
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::Path,
+    process::{
+        Command,
+        Output,
+        Stdio,
+    },
+};
+
+/// Run `bfkrun` with `args` and no input
+fn bfkrun(args: &[&str], directory: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bfkrun"))
+        .args(args)
+        .current_dir(directory)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_stack_dialect() {
+    let directory = tempfile::tempdir().unwrap();
+    let directory = directory.path();
+    // Push 3, clear the cell and pop the 3 back
+    fs::write(directory.join("stack.bf"), "+++@[-]$.").unwrap();
+
+    let output = bfkrun(
+        &[
+            "run",
+            "--dialect",
+            "stack",
+            "--output-format",
+            "json",
+            "stack.bf",
+        ],
+        directory,
+    );
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(output.status.success(), "{document}");
+    assert_eq!(document["status"], "halted");
+    assert_eq!(document["output"], "Aw==");
+
+    let output = bfkrun(&["record", "--dialect", "stack", "stack.bf"], directory);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, [3]);
+
+    let output = bfkrun(&["replay", "stack.bfkrun"], directory);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, [3]);
+}
+
+#[test]
+fn test_manifest_data() {
+    let directory = tempfile::tempdir().unwrap();
+    let directory = directory.path();
+    fs::write(
+        directory.join("bfk.toml"),
+        "[[program]]\nname = \"greet\"\npath = \"greet.bf\"\ndata = [{ path = \"greeting.bin\", \
+         start = 2 }]\n",
+    )
+    .unwrap();
+    fs::write(directory.join("greet.bf"), ">>.>.").unwrap();
+    fs::write(directory.join("greeting.bin"), "hi").unwrap();
+
+    let output = bfkrun(&["run", "greet"], directory);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, b"hi");
+}
+
+#[test]
+fn test_manifest_stack_dialect() {
+    let directory = tempfile::tempdir().unwrap();
+    let directory = directory.path();
+    fs::write(
+        directory.join("bfk.toml"),
+        "[[program]]\nname = \"stack\"\npath = \"stack.bf\"\ndialect = \"stack\"\nexpected = \
+         \"stack.expected\"\n",
+    )
+    .unwrap();
+    fs::write(directory.join("stack.bf"), "+++@[-]$.").unwrap();
+    fs::write(directory.join("stack.expected"), [3]).unwrap();
+
+    let output = bfkrun(&["test"], directory);
+    assert!(
+        output.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_preprocess() {
+    let directory = tempfile::tempdir().unwrap();
+    let directory = directory.path();
+    // Both halves include the same macros, which are only defined once
+    fs::write(directory.join("macros.bf"), "@define next >.\n").unwrap();
+    fs::write(directory.join("left.bf"), "@include \"macros.bf\"\n").unwrap();
+    fs::write(directory.join("right.bf"), "@include \"macros.bf\"\n").unwrap();
+    fs::write(directory.join("greeting.bin"), "hi").unwrap();
+    fs::write(
+        directory.join("greet.bf"),
+        "@include \"left.bf\"\n@include \"right.bf\"\n@data \"greeting.bin\" -> cells 1..\n@use \
+         next @use next\n",
+    )
+    .unwrap();
+
+    let output = bfkrun(&["run", "--preprocess", "greet.bf"], directory);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, b"hi");
+}